@@ -0,0 +1,184 @@
+// benches/hot_endpoints.rs
+//
+// Benchmarks des routes les plus sollicitées, mesurées de bout en bout contre
+// un serveur lancé (le code réellement servi, couche SQL comprise) :
+//
+// - `auth` : extracteur Bearer, wallet en cache et wallets tournants ;
+// - `properties` : liste des propriétés filtrée selon le rôle ;
+// - `investments` : création d'un investissement (1 part).
+//
+// Préparation (base de test, jamais la production) :
+//   cargo run --release --bin loadgen -- --users 10000 --properties 500 --investments 200000
+//   USER_CACHE_TTL_SECS=0 cargo run --release   # 0 : chaque requête relit l'utilisateur en base
+//   cargo bench --bench hot_endpoints
+//
+// Les wallets et la propriété utilisés sont lus dans DATABASE_URL ; le serveur
+// est joint via BENCH_BASE_URL (http://localhost:3000 par défaut). Comparer
+// deux refactorisations : `cargo bench -- --save-baseline avant`, puis
+// `cargo bench -- --baseline avant`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use sqlx::PgPool;
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+/// Wallets tournants pour le benchmark de l'extracteur
+const ROTATING_WALLETS: i64 = 1000;
+
+struct Fixtures {
+    base_url: String,
+    client: reqwest::Client,
+    admin: String,
+    manager: String,
+    users: Vec<String>,
+    property_id: Uuid,
+    token_price: String,
+}
+
+async fn load_fixtures() -> Fixtures {
+    dotenvy::dotenv().ok();
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL doit être définie");
+    let base_url = env::var("BENCH_BASE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+    let pool = PgPool::connect(&database_url).await.expect("Connexion à la base impossible");
+
+    let wallet_for = |role: &'static str| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query_scalar::<_, String>(
+                "SELECT u.wallet FROM users u LEFT JOIN properties p ON p.created_by = u.id
+                 WHERE u.role = $1::user_role GROUP BY u.id ORDER BY COUNT(p.id) DESC, u.created_at LIMIT 1"
+            )
+            .bind(role)
+            .fetch_optional(&pool)
+            .await
+            .expect("Lecture des utilisateurs impossible")
+            .unwrap_or_else(|| panic!("Aucun utilisateur '{}' : lancer d'abord le binaire loadgen", role))
+        }
+    };
+    let admin = wallet_for("admin").await;
+    let manager = wallet_for("manager").await;
+
+    let users = sqlx::query_scalar::<_, String>(
+        "SELECT wallet FROM users WHERE role = 'user' ORDER BY created_at LIMIT $1"
+    )
+    .bind(ROTATING_WALLETS)
+    .fetch_all(&pool)
+    .await
+    .expect("Lecture des utilisateurs impossible");
+    assert!(!users.is_empty(), "Aucun utilisateur : lancer d'abord le binaire loadgen");
+
+    // Propriété ouverte à tous ayant le plus de parts disponibles
+    let (property_id, token_price) = sqlx::query_as::<_, (Uuid, String)>(
+        "SELECT p.id, p.token_price::text FROM properties p LEFT JOIN investments i ON i.property_id = p.id
+         WHERE p.status = 'validated' AND p.published_at IS NOT NULL AND NOT p.requires_accreditation
+         GROUP BY p.id
+         ORDER BY p.total_price / p.token_price - COALESCE(SUM(i.shares), 0) DESC LIMIT 1"
+    )
+    .fetch_optional(&pool)
+    .await
+    .expect("Lecture des propriétés impossible")
+    .expect("Aucune propriété validée : lancer d'abord le binaire loadgen");
+
+    // Le serveur doit répondre avant de mesurer quoi que ce soit
+    let client = reqwest::Client::new();
+    client.get(format!("{}/health", base_url))
+        .send()
+        .await
+        .unwrap_or_else(|e| panic!("Serveur injoignable sur {} : {}", base_url, e));
+
+    Fixtures { base_url, client, admin, manager, users, property_id, token_price }
+}
+
+async fn get(fixtures: &Fixtures, path: &str, wallet: &str) {
+    let response = fixtures.client
+        .get(format!("{}{}", fixtures.base_url, path))
+        .bearer_auth(wallet)
+        .send()
+        .await
+        .expect("Requête échouée");
+    assert!(response.status().is_success(), "{} : {}", path, response.status());
+    response.bytes().await.expect("Réponse illisible");
+}
+
+fn bench_auth(c: &mut Criterion, runtime: &Runtime, fixtures: &Fixtures) {
+    let mut group = c.benchmark_group("auth");
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("bearer_same_wallet", |b| {
+        b.to_async(runtime).iter(|| get(fixtures, "/api/me/terms", &fixtures.users[0]))
+    });
+
+    let next = AtomicUsize::new(0);
+    group.bench_function("bearer_rotating_wallets", |b| {
+        b.to_async(runtime).iter(|| {
+            let wallet = &fixtures.users[next.fetch_add(1, Ordering::Relaxed) % fixtures.users.len()];
+            get(fixtures, "/api/me/terms", wallet)
+        })
+    });
+    group.finish();
+}
+
+fn bench_properties(c: &mut Criterion, runtime: &Runtime, fixtures: &Fixtures) {
+    let mut group = c.benchmark_group("properties");
+
+    for (role, wallet) in [("user", &fixtures.users[0]), ("manager", &fixtures.manager), ("admin", &fixtures.admin)] {
+        group.bench_with_input(BenchmarkId::new("list_by_role", role), wallet, |b, wallet| {
+            b.to_async(runtime).iter(|| get(fixtures, "/api/properties", wallet))
+        });
+    }
+    group.bench_function("list_public", |b| {
+        b.to_async(runtime).iter(|| async {
+            let response = fixtures.client
+                .get(format!("{}/properties/public", fixtures.base_url))
+                .send()
+                .await
+                .expect("Requête échouée");
+            assert!(response.status().is_success());
+            response.bytes().await.expect("Réponse illisible");
+        })
+    });
+    group.finish();
+}
+
+fn bench_investments(c: &mut Criterion, runtime: &Runtime, fixtures: &Fixtures) {
+    let mut group = c.benchmark_group("investments");
+
+    let next = AtomicUsize::new(0);
+    group.bench_function("create", |b| {
+        b.to_async(runtime).iter(|| {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let wallet = &fixtures.users[index % fixtures.users.len()];
+            async move {
+                let response = fixtures.client
+                    .post(format!("{}/api/investments", fixtures.base_url))
+                    .bearer_auth(wallet)
+                    .json(&serde_json::json!({
+                        "property_id": fixtures.property_id,
+                        "amount_eth": fixtures.token_price,
+                        "shares": 1,
+                        "tx_hash": format!("0x{:064x}", index)
+                    }))
+                    .send()
+                    .await
+                    .expect("Requête échouée");
+                assert!(response.status().is_success(), "création : {}", response.status());
+                response.bytes().await.expect("Réponse illisible");
+            }
+        })
+    });
+    group.finish();
+}
+
+fn hot_endpoints(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("Runtime tokio");
+    let fixtures = runtime.block_on(load_fixtures());
+
+    bench_auth(c, &runtime, &fixtures);
+    bench_properties(c, &runtime, &fixtures);
+    bench_investments(c, &runtime, &fixtures);
+}
+
+criterion_group!(benches, hot_endpoints);
+criterion_main!(benches);
@@ -7,8 +7,79 @@ CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 -- Activer l'extension pgcrypto pour les fonctions de hachage
 CREATE EXTENSION IF NOT EXISTS "pgcrypto";
 
+-- Activer l'extension pg_trgm pour l'autocomplétion des propriétés (index trigrammes)
+CREATE EXTENSION IF NOT EXISTS "pg_trgm";
+
 -- Supprimer les tables existantes si présentes (ordre dépendant des contraintes)
+DROP TABLE IF EXISTS distribution_runs CASCADE;
+DROP TABLE IF EXISTS property_managers CASCADE;
+DROP TABLE IF EXISTS backups CASCADE;
+DROP TABLE IF EXISTS shadow_read_diffs CASCADE;
+DROP TABLE IF EXISTS dual_write_divergences CASCADE;
+DROP TABLE IF EXISTS row_changes CASCADE;
+DROP TABLE IF EXISTS onchain_investments CASCADE;
+DROP TABLE IF EXISTS outbox CASCADE;
+DROP TABLE IF EXISTS jobs CASCADE;
+DROP TABLE IF EXISTS worker_jobs CASCADE;
+DROP TABLE IF EXISTS investments_trash CASCADE;
+DROP TABLE IF EXISTS properties_trash CASCADE;
+DROP TABLE IF EXISTS user_merges CASCADE;
+DROP TABLE IF EXISTS investment_certificates CASCADE;
+DROP TABLE IF EXISTS tax_rules CASCADE;
+DROP TABLE IF EXISTS exchange_rates CASCADE;
+DROP TABLE IF EXISTS property_investment_daily CASCADE;
+DROP TABLE IF EXISTS investment_revisions CASCADE;
+DROP TABLE IF EXISTS announcement_dismissals CASCADE;
+DROP TABLE IF EXISTS announcements CASCADE;
+DROP TABLE IF EXISTS contents CASCADE;
+DROP TABLE IF EXISTS email_digests CASCADE;
+DROP TABLE IF EXISTS notification_unsubscribe_tokens CASCADE;
+DROP TABLE IF EXISTS notification_preferences CASCADE;
+DROP TABLE IF EXISTS user_events CASCADE;
+DROP TABLE IF EXISTS role_change_requests CASCADE;
+DROP TABLE IF EXISTS impersonation_actions CASCADE;
+DROP TABLE IF EXISTS impersonation_sessions CASCADE;
+DROP TABLE IF EXISTS signed_requests CASCADE;
+DROP TABLE IF EXISTS user_api_tokens CASCADE;
+DROP TABLE IF EXISTS action_confirmations CASCADE;
+DROP TABLE IF EXISTS two_factor_step_ups CASCADE;
+DROP TABLE IF EXISTS totp_recovery_codes CASCADE;
+DROP TABLE IF EXISTS user_totp CASCADE;
+DROP TABLE IF EXISTS user_wallets CASCADE;
+DROP TABLE IF EXISTS tenants CASCADE;
+DROP TABLE IF EXISTS feature_flags CASCADE;
+DROP TABLE IF EXISTS request_logs_archive CASCADE;
+DROP TABLE IF EXISTS retention_policies CASCADE;
+DROP TABLE IF EXISTS auth_failures_archive CASCADE;
+DROP TABLE IF EXISTS document_downloads_archive CASCADE;
+DROP TABLE IF EXISTS notifications_archive CASCADE;
+DROP TABLE IF EXISTS request_logs CASCADE;
+DROP TABLE IF EXISTS reports CASCADE;
+DROP TABLE IF EXISTS property_status_events CASCADE;
+DROP TABLE IF EXISTS exposure_limits CASCADE;
+DROP TABLE IF EXISTS accreditations CASCADE;
+DROP TABLE IF EXISTS user_acceptances CASCADE;
+DROP TABLE IF EXISTS legal_documents CASCADE;
+DROP TABLE IF EXISTS risk_alert_notes CASCADE;
+DROP TABLE IF EXISTS risk_alerts CASCADE;
+DROP TABLE IF EXISTS auth_failures CASCADE;
+DROP TABLE IF EXISTS compliance_flags CASCADE;
+DROP TABLE IF EXISTS kyc_verifications CASCADE;
+DROP TABLE IF EXISTS notifications CASCADE;
+DROP TABLE IF EXISTS investment_refunds CASCADE;
+DROP TABLE IF EXISTS fiat_payments CASCADE;
+DROP TABLE IF EXISTS indexer_cursors CASCADE;
+DROP TABLE IF EXISTS token_transfers CASCADE;
+DROP TABLE IF EXISTS pending_txs CASCADE;
+DROP TABLE IF EXISTS document_downloads CASCADE;
+DROP TABLE IF EXISTS document_pins CASCADE;
+DROP TABLE IF EXISTS property_media CASCADE;
+DROP TABLE IF EXISTS property_tags CASCADE;
+DROP TABLE IF EXISTS tags CASCADE;
+DROP TABLE IF EXISTS api_key_usage CASCADE;
+DROP TABLE IF EXISTS api_keys CASCADE;
 DROP TABLE IF EXISTS sessions CASCADE;
+DROP TABLE IF EXISTS investment_intents CASCADE;
 DROP TABLE IF EXISTS investments CASCADE;
 DROP TABLE IF EXISTS properties CASCADE;
 DROP TABLE IF EXISTS roles CASCADE;
@@ -16,32 +87,152 @@ DROP TABLE IF EXISTS users CASCADE;
 
 -- Supprimer les fonctions existantes si elles existent
 DROP FUNCTION IF EXISTS get_user_role(TEXT);
+DROP FUNCTION IF EXISTS sync_property_shares_sold() CASCADE;
+DROP FUNCTION IF EXISTS property_yield_bucket(NUMERIC);
+DROP FUNCTION IF EXISTS sync_property_investment_daily() CASCADE;
+DROP FUNCTION IF EXISTS issue_investment_certificate() CASCADE;
+DROP FUNCTION IF EXISTS capture_row_change() CASCADE;
 
 -- Supprimer les types existants si ils existent
 DROP TYPE IF EXISTS property_status CASCADE;
 DROP TYPE IF EXISTS user_role CASCADE;
+DROP TYPE IF EXISTS media_kind CASCADE;
+DROP TYPE IF EXISTS relay_status CASCADE;
+DROP TYPE IF EXISTS investment_status CASCADE;
+DROP TYPE IF EXISTS fiat_payment_status CASCADE;
+DROP TYPE IF EXISTS refund_status CASCADE;
+DROP TYPE IF EXISTS kyc_status CASCADE;
+DROP TYPE IF EXISTS flag_status CASCADE;
+DROP TYPE IF EXISTS alert_severity CASCADE;
+DROP TYPE IF EXISTS alert_status CASCADE;
+DROP TYPE IF EXISTS legal_document_kind CASCADE;
+DROP TYPE IF EXISTS accreditation_status CASCADE;
+DROP TYPE IF EXISTS report_kind CASCADE;
+DROP TYPE IF EXISTS report_format CASCADE;
+DROP TYPE IF EXISTS report_status CASCADE;
+DROP TYPE IF EXISTS retention_entity CASCADE;
+DROP TYPE IF EXISTS job_status CASCADE;
+DROP TYPE IF EXISTS onchain_investment_status CASCADE;
+DROP TYPE IF EXISTS property_manager_role CASCADE;
 
 -- Créer l'enum pour les statuts de propriété
-CREATE TYPE property_status AS ENUM ('pending', 'validated', 'rejected');
+CREATE TYPE property_status AS ENUM ('draft', 'pending', 'validated', 'rejected');
 
 -- Créer l'enum pour les rôles utilisateur
 CREATE TYPE user_role AS ENUM ('user', 'manager', 'admin');
 
+-- Créer l'enum pour les médias de la galerie
+CREATE TYPE media_kind AS ENUM ('image', 'video', 'virtual_tour');
+
+-- Créer l'enum pour les transactions du relayer
+CREATE TYPE relay_status AS ENUM ('queued', 'submitted', 'confirmed', 'failed');
+
+-- Créer l'enum pour le règlement on-chain des investissements
+CREATE TYPE investment_status AS ENUM ('pending_settlement', 'settled', 'refunded');
+
+-- Créer l'enum pour les paiements en euros (Stripe)
+-- oversold : paiement reçu alors que les parts étaient épuisées (à rembourser)
+CREATE TYPE fiat_payment_status AS ENUM ('requires_payment', 'succeeded', 'failed', 'canceled', 'oversold');
+
+-- Créer l'enum pour le suivi des remboursements
+CREATE TYPE refund_status AS ENUM ('requested', 'approved', 'rejected', 'paid');
+
+-- Créer l'enum pour la vérification d'identité (KYC)
+CREATE TYPE kyc_status AS ENUM ('not_started', 'pending', 'approved', 'rejected', 'resubmission_requested');
+
+-- Créer l'enum pour la revue des signalements de conformité
+CREATE TYPE flag_status AS ENUM ('open', 'cleared', 'confirmed');
+
+-- Créer l'enum pour la gravité des alertes d'activité suspecte (ordre croissant)
+CREATE TYPE alert_severity AS ENUM ('low', 'medium', 'high');
+
+-- Créer l'enum pour le traitement des alertes d'activité suspecte
+CREATE TYPE alert_status AS ENUM ('open', 'acknowledged');
+
+-- Créer l'enum pour les documents légaux à accepter avant d'investir
+CREATE TYPE legal_document_kind AS ENUM ('terms', 'risk_disclosure');
+
+-- Créer l'enum pour l'accréditation des investisseurs qualifiés
+-- ('expired' est déduit de expires_at à la lecture)
+CREATE TYPE accreditation_status AS ENUM ('pending', 'approved', 'rejected', 'expired');
+
+-- Créer les enums des rapports générés en tâche de fond
+CREATE TYPE report_kind AS ENUM ('investments', 'users', 'properties', 'refunds');
+CREATE TYPE report_format AS ENUM ('csv', 'pdf');
+CREATE TYPE report_status AS ENUM ('queued', 'running', 'completed', 'failed');
+
+-- Créer l'enum des tables soumises à une durée de conservation
+CREATE TYPE retention_entity AS ENUM ('notifications', 'document_downloads', 'auth_failures', 'request_logs');
+
+-- Créer l'enum des tâches de la file durable (dead : tentatives épuisées)
+CREATE TYPE job_status AS ENUM ('queued', 'running', 'succeeded', 'dead');
+
+-- Créer l'enum du rapprochement des investissements observés on-chain
+CREATE TYPE onchain_investment_status AS ENUM ('matched', 'unmatched', 'resolved', 'dismissed');
+
+-- Créer l'enum des rôles des co-gestionnaires d'une propriété
+CREATE TYPE property_manager_role AS ENUM ('owner', 'editor', 'viewer');
+
+-- Plateformes (marques) servies par la même instance : résolues par nom
+-- d'hôte ou en-tête X-Tenant, chacune avec ses origines CORS et son habillage
+CREATE TABLE tenants (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    slug TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    hostnames TEXT[] NOT NULL DEFAULT '{}',
+    cors_origins TEXT[] NOT NULL DEFAULT '{}',
+    branding JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Plateforme par défaut (identifiant fixe, voir tenants.rs) : données
+-- existantes et requêtes sans hôte ni en-tête reconnus
+INSERT INTO tenants (id, slug, name)
+VALUES ('00000000-0000-0000-0000-000000000001', 'default', 'Plateforme principale');
+
 -- Table users avec role intégré et enum strict
 CREATE TABLE users (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    wallet TEXT NOT NULL UNIQUE,
+    tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id),
+    -- Adresse Ethereum en minuscules (voir wallet_address.rs) : une seule
+    -- forme par wallet, quelle que soit la casse envoyée par le client.
+    -- Unique parmi les comptes actifs (users_wallet_key) : le wallet d'un
+    -- compte fusionné est libéré par sa désactivation et reste en historique
+    wallet TEXT NOT NULL CHECK (wallet ~ '^0x[0-9a-f]{40}$'),
     name TEXT,
     role user_role NOT NULL DEFAULT 'user',
+    -- Apparition (anonymisée) dans le classement public des investisseurs
+    leaderboard_opt_in BOOLEAN NOT NULL DEFAULT FALSE,
+    email TEXT, -- Adresse des e-mails (résumé hebdomadaire), facultative
+    -- Devise des contre-valeurs affichées (voir exchange_rates)
+    preferred_currency TEXT NOT NULL DEFAULT 'EUR' CHECK (preferred_currency IN ('EUR', 'USD')),
+    country TEXT CHECK (country ~ '^[A-Z]{2}$'), -- Résidence fiscale (ISO 3166-1 alpha-2)
+    -- Doublon fusionné dans un autre compte (voir user_merges) : plus d'authentification
+    deactivated_at TIMESTAMPTZ,
+    merged_into UUID REFERENCES users(id),
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
 -- Table properties avec le nouveau système de status
 CREATE TABLE properties (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    onchain_id TEXT NOT NULL UNIQUE,
+    tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id),
+    -- Identifiant de l'actif sur le contrat registre : unique, et restreint
+    -- aux caractères sûrs dans une URL ou un appel de contrat
+    onchain_id TEXT NOT NULL CONSTRAINT properties_onchain_id_key UNIQUE
+        CONSTRAINT properties_onchain_id_check CHECK (onchain_id ~ '^[A-Za-z0-9_-]{1,64}$'),
+    -- Contrat registre qui porte l'actif (minuscules), renseigné à l'enregistrement
+    contract_address TEXT CHECK (contract_address ~ '^0x[0-9a-f]{40}$'),
+    -- Slug d'URL, unique sur la plateforme (voir slug.rs)
+    slug TEXT NOT NULL,
     name TEXT NOT NULL,
     location TEXT NOT NULL,
+    -- Localisation normalisée, déduite de `location` par géocodage (voir
+    -- geocoding.rs) : NULL tant que le géocodage n'a pas abouti
+    country TEXT CHECK (country ~ '^[A-Z]{2}$'), -- ISO 3166-1 alpha-2
+    region TEXT,
+    city TEXT,
+    geocoded_at TIMESTAMPTZ,
     type TEXT NOT NULL,
     description TEXT,
     total_price NUMERIC NOT NULL,
@@ -51,22 +242,1135 @@ CREATE TABLE properties (
     documents TEXT[],
     created_by UUID NOT NULL REFERENCES users(id),
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-    status property_status NOT NULL DEFAULT 'pending',
+    status property_status NOT NULL DEFAULT 'draft',
     status_updated_at TIMESTAMPTZ,
-    status_updated_by UUID REFERENCES users(id)
+    status_updated_by UUID REFERENCES users(id),
+    publish_at TIMESTAMPTZ,
+    published_at TIMESTAMPTZ,
+    amenities JSONB NOT NULL DEFAULT '{}'::jsonb,
+    requires_accreditation BOOLEAN NOT NULL DEFAULT FALSE, -- Offre réservée aux investisseurs accrédités
+    -- Enregistrement sur le contrat registre (transaction du relayer)
+    registry_tx_id UUID,
+    registry_rollback_status property_status, -- Statut restauré si l'enregistrement échoue
+    registry_tx_hash TEXT,
+    registered_at TIMESTAMPTZ,
+    -- Parts émises et parts vendues (investissements non remboursés, tenu à
+    -- jour par trigger) : la contrainte rend la survente impossible
+    total_shares BIGINT GENERATED ALWAYS AS (
+        CASE WHEN token_price > 0 THEN FLOOR(total_price / token_price)::bigint ELSE 0 END
+    ) STORED,
+    shares_sold BIGINT NOT NULL DEFAULT 0,
+    CONSTRAINT properties_shares_sold_check CHECK (shares_sold >= 0 AND shares_sold <= total_shares),
+    CONSTRAINT properties_tenant_slug_key UNIQUE (tenant_id, slug)
+);
+
+-- Vocabulaire de tags géré par l'admin
+CREATE TABLE tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    slug TEXT NOT NULL UNIQUE,
+    label TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Association propriétés <-> tags
+CREATE TABLE property_tags (
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (property_id, tag_id)
+);
+
+CREATE INDEX idx_property_tags_tag ON property_tags(tag_id);
+
+-- Galerie de médias des propriétés (images, vidéos, visites virtuelles)
+CREATE TABLE property_media (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    kind media_kind NOT NULL,
+    url TEXT NOT NULL,
+    caption TEXT,
+    position INTEGER NOT NULL DEFAULT 0,
+    is_cover BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_property_media_property ON property_media(property_id, position);
+-- Une seule image de couverture par propriété
+CREATE UNIQUE INDEX idx_property_media_cover ON property_media(property_id) WHERE is_cover;
+
+-- Journal d'audit des URL de téléchargement signées délivrées
+CREATE TABLE document_downloads (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    document_index INTEGER NOT NULL,
+    document_key TEXT NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_document_downloads_property ON document_downloads(property_id, created_at);
+
+-- CID IPFS des documents épinglés (référence immuable pour les métadonnées on-chain)
+CREATE TABLE document_pins (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    document_key TEXT NOT NULL,
+    cid TEXT NOT NULL,
+    pinned_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (property_id, document_key)
 );
 
 -- Table investments
 CREATE TABLE investments (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id),
     user_id UUID NOT NULL REFERENCES users(id),
-    property_id UUID NOT NULL REFERENCES properties(id),
+    -- Une propriété n'est jamais supprimée sous ses investissements : ils passent
+    -- d'abord à la corbeille (suppression en cascade, voir deletion_impact.rs)
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE RESTRICT,
+    amount_eth NUMERIC NOT NULL CHECK (amount_eth > 0),
+    amount_wei NUMERIC(78, 0) NOT NULL CHECK (amount_wei = amount_eth * 1000000000000000000), -- Même montant, en wei
+    shares INTEGER NOT NULL CHECK (shares > 0),
+    tx_hash TEXT, -- NULL tant qu'un paiement en euros n'est pas réglé on-chain
+    status investment_status NOT NULL DEFAULT 'settled',
+    settled_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Intentions d'investissement EIP-712 : cotation signée par l'investisseur
+-- avant la transaction on-chain (signature NULL = en attente de signature)
+CREATE TABLE investment_intents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    investor TEXT NOT NULL,
+    shares INTEGER NOT NULL CHECK (shares > 0),
+    price_per_share_wei NUMERIC(78, 0) NOT NULL,
+    amount_eth NUMERIC NOT NULL,
+    amount_wei NUMERIC(78, 0) NOT NULL CHECK (amount_wei = amount_eth * 1000000000000000000),
+    nonce BIGINT NOT NULL,
+    deadline TIMESTAMPTZ NOT NULL,
+    digest TEXT NOT NULL,
+    signature TEXT,
+    signed_at TIMESTAMPTZ,
+    investment_id UUID UNIQUE REFERENCES investments(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, nonce)
+);
+
+-- Table api_keys (accès partenaires à l'API publique en lecture seule)
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Plateforme dont la clé lit les données : refusée sur toute autre
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    name TEXT NOT NULL,
+    key_prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    daily_quota INTEGER NOT NULL DEFAULT 1000 CHECK (daily_quota >= 0),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ
+);
+
+-- Compteur d'appels journalier par clé
+CREATE TABLE api_key_usage (
+    api_key_id UUID NOT NULL REFERENCES api_keys(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    request_count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (api_key_id, day)
+);
+
+-- File persistante des transactions envoyées par le relayer (wallet du serveur)
+CREATE TABLE pending_txs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Plateforme pour laquelle la transaction est envoyée (wallet du serveur partagé)
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    kind TEXT NOT NULL,
+    property_id UUID REFERENCES properties(id) ON DELETE SET NULL,
+    to_address TEXT NOT NULL,
+    data TEXT NOT NULL DEFAULT '0x',
+    value_wei NUMERIC(78, 0) NOT NULL DEFAULT 0,
+    status relay_status NOT NULL DEFAULT 'queued',
+    from_address TEXT,
+    nonce BIGINT,
+    gas_limit BIGINT,
+    max_fee_per_gas NUMERIC(78, 0),
+    max_priority_fee_per_gas NUMERIC(78, 0),
+    tx_hash TEXT,
+    tx_hashes TEXT[] NOT NULL DEFAULT '{}', -- Tous les hash diffusés (remplacements inclus)
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    submitted_at TIMESTAMPTZ,
+    confirmed_at TIMESTAMPTZ,
+    block_number BIGINT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (from_address, nonce)
+);
+
+CREATE INDEX idx_pending_txs_status ON pending_txs(status, next_attempt_at);
+
+ALTER TABLE properties ADD CONSTRAINT properties_registry_tx_id_fkey
+    FOREIGN KEY (registry_tx_id) REFERENCES pending_txs(id) ON DELETE SET NULL;
+
+-- Transferts des jetons des propriétés (mint et burn compris), alimentés par l'indexation
+CREATE TABLE token_transfers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    property_id UUID REFERENCES properties(id) ON DELETE SET NULL,
+    token_id TEXT NOT NULL,
+    from_address TEXT, -- NULL pour un mint
+    to_address TEXT,   -- NULL pour un burn
+    amount NUMERIC(78, 0) NOT NULL,
+    block_number BIGINT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    batch_index INTEGER NOT NULL DEFAULT 0, -- Position dans un TransferBatch
+    source TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tx_hash, log_index, batch_index)
+);
+
+CREATE INDEX idx_token_transfers_token ON token_transfers(token_id, block_number);
+CREATE INDEX idx_token_transfers_property ON token_transfers(property_id);
+
+-- Dernier bloc traité par chaque source d'indexation
+CREATE TABLE indexer_cursors (
+    source TEXT PRIMARY KEY,
+    last_block BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Paiements en euros via Stripe (un PaymentIntent par demande)
+CREATE TABLE fiat_payments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    shares INTEGER NOT NULL CHECK (shares > 0),
+    amount_eur NUMERIC(12, 2) NOT NULL,
+    amount_eth NUMERIC NOT NULL,
+    amount_wei NUMERIC(78, 0) NOT NULL CHECK (amount_wei = amount_eth * 1000000000000000000),
+    eur_per_eth NUMERIC NOT NULL, -- Taux appliqué à la cotation
+    stripe_payment_intent_id TEXT UNIQUE,
+    status fiat_payment_status NOT NULL DEFAULT 'requires_payment',
+    investment_id UUID UNIQUE REFERENCES investments(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Demandes de remboursement des investissements (une seule demande ouverte par investissement)
+CREATE TABLE investment_refunds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    investment_id UUID NOT NULL REFERENCES investments(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT,
+    status refund_status NOT NULL DEFAULT 'requested',
     amount_eth NUMERIC NOT NULL,
+    amount_wei NUMERIC(78, 0) NOT NULL CHECK (amount_wei = amount_eth * 1000000000000000000),
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMPTZ,
+    review_comment TEXT,
+    tx_hash TEXT, -- Transaction de remboursement, renseignée au paiement
+    paid_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_investment_refunds_open ON investment_refunds(investment_id) WHERE status <> 'rejected';
+CREATE INDEX idx_investment_refunds_status ON investment_refunds(status, created_at);
+
+-- Notifications in-app des utilisateurs
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    message TEXT NOT NULL,
+    data JSONB NOT NULL DEFAULT '{}',
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_notifications_user ON notifications(user_id, created_at DESC);
+
+-- Vérification d'identité (KYC) : dossier chez le prestataire et statut courant
+CREATE TABLE kyc_verifications (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL, -- 'sumsub' ou 'manual' (décision d'un admin sans dossier)
+    applicant_id TEXT UNIQUE,
+    status kyc_status NOT NULL DEFAULT 'not_started',
+    review_answer TEXT,
+    reject_labels TEXT[] NOT NULL DEFAULT '{}',
+    moderation_comment TEXT,
+    reviewed_at TIMESTAMPTZ,
+    overridden_by UUID REFERENCES users(id) ON DELETE SET NULL, -- Dernière décision manuelle d'un admin
+    override_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_kyc_verifications_status ON kyc_verifications(status, updated_at);
+
+-- Signalements du filtrage des wallets (sanctions, listes de blocage)
+CREATE TABLE compliance_flags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    wallet TEXT NOT NULL,
+    source TEXT NOT NULL, -- 'sanctions_list' ou 'chainalysis_oracle'
+    reason TEXT NOT NULL,
+    status flag_status NOT NULL DEFAULT 'open',
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMPTZ,
+    review_note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, wallet, source) -- Un signalement levé n'est pas recréé
+);
+
+CREATE INDEX idx_compliance_flags_status ON compliance_flags(status, created_at);
+
+-- Échecs d'authentification (wallet inconnu, jeton invalide, mauvaise signature),
+-- évalués par les règles de détection et par le blocage temporaire
+CREATE TABLE auth_failures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    wallet TEXT NOT NULL,
+    ip TEXT, -- Premier hop de X-Forwarded-For ou X-Real-IP
+    reason TEXT NOT NULL DEFAULT 'unknown_wallet', -- unknown_wallet, invalid_token, bad_signature
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_auth_failures_ip ON auth_failures(ip, created_at);
+CREATE INDEX idx_auth_failures_wallet ON auth_failures(wallet, created_at);
+
+-- Alertes d'activité suspecte levées par le moteur de règles
+CREATE TABLE risk_alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rule TEXT NOT NULL, -- 'investment_velocity', 'amount_below_threshold' ou 'auth_failures'
+    severity alert_severity NOT NULL,
+    subject TEXT NOT NULL, -- 'user:<uuid>', 'ip:<adresse>' ou 'wallet:<adresse>'
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    occurrences INTEGER NOT NULL DEFAULT 1,
+    status alert_status NOT NULL DEFAULT 'open',
+    acknowledged_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    acknowledged_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Une seule alerte ouverte par règle et par sujet : les déclenchements suivants s'y ajoutent
+CREATE UNIQUE INDEX idx_risk_alerts_open ON risk_alerts(rule, subject) WHERE status = 'open';
+CREATE INDEX idx_risk_alerts_status ON risk_alerts(status, severity, last_seen_at);
+
+-- Notes des admins sur les alertes
+CREATE TABLE risk_alert_notes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    alert_id UUID NOT NULL REFERENCES risk_alerts(id) ON DELETE CASCADE,
+    author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_risk_alert_notes_alert ON risk_alert_notes(alert_id, created_at);
+
+-- Documents légaux versionnés (CGU, avertissement sur les risques) : la version
+-- en vigueur de chaque type est la dernière publiée
+CREATE TABLE legal_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind legal_document_kind NOT NULL,
+    version TEXT NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    published_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (kind, version)
+);
+
+CREATE INDEX idx_legal_documents_current ON legal_documents(kind, published_at DESC);
+
+-- Acceptations des documents légaux par les utilisateurs
+CREATE TABLE user_acceptances (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    document_id UUID NOT NULL REFERENCES legal_documents(id) ON DELETE CASCADE,
+    accepted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ip TEXT,
+    user_agent TEXT,
+    UNIQUE (user_id, document_id)
+);
+
+-- Accréditation des investisseurs qualifiés : justificatifs envoyés, revue d'un admin et échéance
+CREATE TABLE accreditations (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    status accreditation_status NOT NULL DEFAULT 'pending',
+    documents TEXT[] NOT NULL DEFAULT '{}', -- Clés des justificatifs dans le bucket privé
+    submitted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMPTZ,
+    review_note TEXT,
+    expires_at TIMESTAMPTZ, -- Renseigné à l'approbation
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_accreditations_status ON accreditations(status, submitted_at);
+
+-- Limites d'exposition des investisseurs. Portée : globale (property_id NULL :
+-- max_eth plafonne l'exposition totale, max_pct est le plafond par défaut en %
+-- de chaque propriété) ou par propriété ; user_id renseigné pour une dérogation
+-- individuelle, qui remplace la limite générale de même portée. Une valeur NULL
+-- signifie « pas de limite ».
+CREATE TABLE exposure_limits (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Plateforme de l'admin : une limite globale ne vaut que pour ses utilisateurs
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    property_id UUID REFERENCES properties(id) ON DELETE CASCADE,
+    max_eth NUMERIC CHECK (max_eth > 0),
+    max_pct NUMERIC CHECK (max_pct > 0 AND max_pct <= 100),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Une seule limite par portée (les NULL sont considérés égaux)
+CREATE UNIQUE INDEX idx_exposure_limits_scope ON exposure_limits (
+    tenant_id,
+    (COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid)),
+    (COALESCE(property_id, '00000000-0000-0000-0000-000000000000'::uuid))
+);
+
+-- Historique des changements de statut des propriétés (séries temporelles de l'admin)
+CREATE TABLE property_status_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    status property_status NOT NULL,
+    changed_by UUID REFERENCES users(id), -- NULL : changement automatique (annulation on-chain)
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_property_status_events_status ON property_status_events(status, created_at);
+
+-- Rapports demandés par les admins, générés en tâche de fond puis déposés dans le bucket privé
+CREATE TABLE reports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id), -- Plateforme exportée, celle de l'admin
+    kind report_kind NOT NULL,
+    format report_format NOT NULL,
+    from_date TIMESTAMPTZ, -- Bornes sur la date de création des lignes (NULL : sans limite)
+    to_date TIMESTAMPTZ,
+    status report_status NOT NULL DEFAULT 'queued',
+    storage_key TEXT,
+    row_count INTEGER,
+    size BIGINT,
+    error TEXT,
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_reports_queued ON reports(created_at) WHERE status = 'queued';
+CREATE INDEX idx_reports_tenant ON reports(tenant_id, created_at DESC);
+
+-- Journal des requêtes des routes financièrement sensibles (investissements,
+-- changements de rôle et de statut), corps de requête expurgé
+CREATE TABLE request_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    route_group TEXT NOT NULL,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    route TEXT, -- Modèle de la route (ex. /api/investments/:id)
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    status INTEGER NOT NULL,
+    request_body JSONB, -- NULL si le corps est vide, non JSON ou trop volumineux
+    ip TEXT,
+    scheme TEXT, -- http ou https, d'après les proxies de confiance
+    duration_ms INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_request_logs_created ON request_logs(created_at);
+CREATE INDEX idx_request_logs_actor ON request_logs(actor_id, created_at);
+
+-- Durée de conservation par table : au-delà, les lignes sont déplacées dans la table d'archive
+CREATE TABLE retention_policies (
+    entity retention_entity PRIMARY KEY,
+    retention_days INTEGER NOT NULL CHECK (retention_days > 0),
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_run_at TIMESTAMPTZ,
+    last_archived INTEGER -- Lignes archivées lors du dernier passage
+);
+
+-- Tables d'archive : mêmes colonnes (sans clés étrangères) et date d'archivage
+CREATE TABLE notifications_archive (LIKE notifications INCLUDING DEFAULTS);
+ALTER TABLE notifications_archive ADD COLUMN archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+CREATE TABLE document_downloads_archive (LIKE document_downloads INCLUDING DEFAULTS);
+ALTER TABLE document_downloads_archive ADD COLUMN archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+CREATE TABLE auth_failures_archive (LIKE auth_failures INCLUDING DEFAULTS);
+ALTER TABLE auth_failures_archive ADD COLUMN archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+CREATE TABLE request_logs_archive (LIKE request_logs INCLUDING DEFAULTS);
+ALTER TABLE request_logs_archive ADD COLUMN archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+-- Drapeaux de fonctionnalités : activation globale, restreinte à des
+-- environnements (APP_ENV) et/ou à des rôles ; tableau vide = pas de restriction
+CREATE TABLE feature_flags (
+    key TEXT PRIMARY KEY,
+    description TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    environments TEXT[] NOT NULL DEFAULT '{}',
+    roles user_role[] NOT NULL DEFAULT '{}',
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Wallets secondaires d'un utilisateur (le wallet principal reste users.wallet),
+-- rattachés après signature d'un message de défi (EIP-191)
+CREATE TABLE user_wallets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    wallet TEXT NOT NULL, -- En minuscules
+    challenge TEXT NOT NULL, -- Message à signer
+    verified_at TIMESTAMPTZ, -- NULL : signature attendue
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, wallet)
+);
+
+-- Un wallet vérifié n'appartient qu'à un seul utilisateur
+CREATE UNIQUE INDEX idx_user_wallets_verified ON user_wallets(wallet) WHERE verified_at IS NOT NULL;
+
+-- Double authentification TOTP (RFC 6238), optionnelle, exigée pour les
+-- opérations d'administration destructrices une fois activée
+CREATE TABLE user_totp (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL, -- Base32, partagé avec l'application d'authentification
+    enabled_at TIMESTAMPTZ, -- NULL : enrôlement non confirmé
+    last_used_step BIGINT, -- Dernier pas de 30s accepté (anti-rejeu)
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Codes de secours à usage unique (empreinte SHA-256)
+CREATE TABLE totp_recovery_codes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Jetons de courte durée obtenus après saisie d'un code (empreinte SHA-256)
+CREATE TABLE two_factor_step_ups (
+    token_hash TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_totp_recovery_codes_user ON totp_recovery_codes(user_id);
+
+-- Confirmations des opérations dangereuses : un défi (action + cible) est
+-- créé par l'endpoint refusé (428), puis échangé contre un jeton à usage
+-- unique via POST /api/confirmations
+CREATE TABLE action_confirmations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(), -- Identifiant du défi
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    action TEXT NOT NULL, -- ex. delete_property, update_user_role
+    target TEXT NOT NULL, -- Identifiant de la ressource visée
+    token_hash TEXT UNIQUE, -- Empreinte SHA-256 du jeton, NULL tant que non confirmé
+    expires_at TIMESTAMPTZ NOT NULL,
+    confirmed_at TIMESTAMPTZ,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_action_confirmations_user ON action_confirmations(user_id);
+
+-- Jetons d'API personnels, longue durée et limités à des portées en lecture
+-- (tableurs, bots) ; seule l'empreinte SHA-256 est stockée
+CREATE TABLE user_api_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    token_prefix TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL, -- read:portfolio, read:properties
+    expires_at TIMESTAMPTZ, -- NULL : sans expiration
+    revoked_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_user_api_tokens_user ON user_api_tokens(user_id);
+
+-- Requêtes signées par le wallet (non-répudiation des créations
+-- d'investissement) : le couple (wallet, nonce) n'est accepté qu'une fois
+CREATE TABLE signed_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    wallet TEXT NOT NULL, -- Signataire, en minuscules
+    nonce TEXT NOT NULL,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    body_hash TEXT NOT NULL, -- SHA-256 du corps, en hexadécimal
+    signature TEXT NOT NULL,
+    signed_at TIMESTAMPTZ NOT NULL, -- Horodatage fourni par le client
+    resource_id UUID, -- Ressource créée (investissement)
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (wallet, nonce)
+);
+
+CREATE INDEX idx_signed_requests_resource ON signed_requests(resource_id);
+
+-- Impersonation en lecture seule par un admin (support) : jeton dédié,
+-- motif obligatoire et journal de chaque requête effectuée
+CREATE TABLE impersonation_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    admin_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE, -- Empreinte SHA-256 du jeton imp_...
+    reason TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE impersonation_actions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    session_id UUID NOT NULL REFERENCES impersonation_sessions(id) ON DELETE CASCADE,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL, -- Chemin et paramètres de requête
+    status INTEGER NOT NULL,
+    ip TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_impersonation_sessions_created ON impersonation_sessions(created_at DESC);
+CREATE INDEX idx_impersonation_actions_session ON impersonation_actions(session_id, created_at);
+
+-- Changements de rôle impliquant le rôle admin (octroi ou retrait) : demande
+-- en attente jusqu'à l'approbation d'un second admin (principe des quatre yeux)
+CREATE TABLE role_change_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    previous_role user_role NOT NULL, -- Rôle au moment de la demande
+    requested_role user_role NOT NULL,
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected', 'cancelled', 'expired')),
+    decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decided_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Une seule demande en attente par utilisateur
+CREATE UNIQUE INDEX idx_role_change_requests_pending ON role_change_requests(user_id) WHERE status = 'pending';
+CREATE INDEX idx_role_change_requests_tenant ON role_change_requests(tenant_id, created_at DESC);
+
+-- Journal d'audit du compte : connexions et modifications du profil (rôle,
+-- wallets, double authentification, classement). Les investissements et les
+-- soumissions de propriétés sont lus dans leurs propres tables pour la
+-- chronologie d'activité (voir activity.rs)
+CREATE TABLE user_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL, -- Auteur, s'il n'est pas l'utilisateur lui-même
+    kind TEXT NOT NULL,
+    data JSONB NOT NULL DEFAULT '{}',
+    ip TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_user_events_user ON user_events(user_id, created_at DESC);
+CREATE INDEX idx_property_status_events_changed_by ON property_status_events(changed_by, created_at DESC);
+
+-- Préférences de notification par type d'événement et par canal ; sans ligne,
+-- le canal est actif (voir notification_preferences.rs)
+CREATE TABLE notification_preferences (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL, -- Type d'événement (notifications.kind)
+    channel TEXT NOT NULL CHECK (channel IN ('in_app', 'email', 'webhook')),
+    enabled BOOLEAN NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, kind, channel)
+);
+
+-- Jeton de désinscription placé en pied des e-mails (un par utilisateur)
+CREATE TABLE notification_unsubscribe_tokens (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Résumés hebdomadaires : une ligne par utilisateur et par semaine traitée
+-- (envoyé, vide donc non envoyé, ou en échec)
+CREATE TABLE email_digests (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    week_start DATE NOT NULL, -- Lundi de la semaine d'envoi
+    status TEXT NOT NULL CHECK (status IN ('sent', 'empty', 'failed')),
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, week_start)
+);
+
+-- Contenus éditoriaux (FAQ, avertissements sur les risques, annonces) en
+-- Markdown, un par slug et par langue sur chaque plateforme
+CREATE TABLE contents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    slug TEXT NOT NULL,
+    locale TEXT NOT NULL DEFAULT 'fr',
+    title TEXT NOT NULL,
+    body TEXT NOT NULL, -- Markdown
+    published BOOLEAN NOT NULL DEFAULT FALSE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, slug, locale)
+);
+
+-- Bannières d'annonce (maintenance, nouvelles offres) ciblées par rôle,
+-- affichées entre starts_at et ends_at
+CREATE TABLE announcements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL, -- Markdown
+    audience TEXT NOT NULL DEFAULT 'all' CHECK (audience IN ('all', 'investors', 'managers')),
+    starts_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ends_at TIMESTAMPTZ, -- Sans fin si NULL
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at IS NULL OR ends_at > starts_at)
+);
+
+CREATE INDEX idx_announcements_active ON announcements(tenant_id, starts_at);
+
+-- Annonces masquées par chaque utilisateur
+CREATE TABLE announcement_dismissals (
+    announcement_id UUID NOT NULL REFERENCES announcements(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    dismissed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (announcement_id, user_id)
+);
+
+-- Historique des modifications d'un investissement : champs modifiés
+-- ({"champ": {"from": ..., "to": ...}}), auteur et motif
+CREATE TABLE investment_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    investment_id UUID NOT NULL REFERENCES investments(id) ON DELETE CASCADE,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reason TEXT,
+    changes JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_investment_revisions_investment ON investment_revisions(investment_id, created_at);
+
+-- Agrégat journalier des investissements non remboursés d'une propriété,
+-- tenu à jour par trigger (onglet statistiques de la page propriété)
+CREATE TABLE property_investment_daily (
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    day TIMESTAMPTZ NOT NULL,
+    investments_count BIGINT NOT NULL DEFAULT 0,
+    volume_eth NUMERIC NOT NULL DEFAULT 0,
+    shares BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (property_id, day)
+);
+
+-- Cours de l'ETH relevés auprès du service de cotation (unités de la devise pour 1 ETH)
+CREATE TABLE exchange_rates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    currency TEXT NOT NULL CHECK (currency IN ('EUR', 'USD')),
+    eth_rate NUMERIC NOT NULL CHECK (eth_rate > 0),
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_exchange_rates_currency ON exchange_rates(currency, fetched_at);
+
+-- Retenue à la source par pays de résidence fiscale et par type de revenu
+CREATE TABLE tax_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    country TEXT NOT NULL CHECK (country ~ '^[A-Z]{2}$'),
+    rate NUMERIC(5, 2) NOT NULL CHECK (rate >= 0 AND rate <= 100), -- Pourcentage
+    applies_to TEXT NOT NULL DEFAULT 'distributions' CHECK (applies_to IN ('distributions', 'capital_gains')),
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, country, applies_to)
+);
+
+-- Certificats d'investissement : empreinte SHA-256 de la forme canonique
+-- `pa-certificate-v1|<wallet>|<onchain_id>|<parts>|<tx_hash>`, vérifiable
+-- publiquement ; un seul certificat valide par investissement
+CREATE TABLE investment_certificates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    investment_id UUID NOT NULL REFERENCES investments(id) ON DELETE CASCADE,
+    hash TEXT NOT NULL UNIQUE,
+    wallet TEXT NOT NULL,
+    onchain_id TEXT NOT NULL,
     shares INTEGER NOT NULL,
     tx_hash TEXT NOT NULL,
+    issued_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ -- Remboursement ou correction de l'investissement
+);
+
+CREATE UNIQUE INDEX idx_investment_certificates_valid ON investment_certificates(investment_id) WHERE revoked_at IS NULL;
+
+-- Fusions de comptes d'une même personne : les avoirs du doublon passent au
+-- compte conservé, le doublon est désactivé
+CREATE TABLE user_merges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    survivor_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    duplicate_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    duplicate_wallet TEXT NOT NULL,
+    merged_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reason TEXT,
+    moved JSONB NOT NULL DEFAULT '{}', -- Nombre de lignes transférées par table
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
+CREATE INDEX idx_user_merges_tenant ON user_merges(tenant_id, created_at DESC);
+
+-- Corbeille : lignes supprimées, restaurables par un admin jusqu'à leur purge
+CREATE TABLE properties_trash (LIKE properties INCLUDING DEFAULTS);
+ALTER TABLE properties_trash ADD COLUMN deleted_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE properties_trash ADD COLUMN deleted_by UUID;
+ALTER TABLE properties_trash ADD PRIMARY KEY (id);
+CREATE TABLE investments_trash (LIKE investments INCLUDING DEFAULTS);
+ALTER TABLE investments_trash ADD COLUMN deleted_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE investments_trash ADD COLUMN deleted_by UUID;
+ALTER TABLE investments_trash ADD PRIMARY KEY (id);
+
+CREATE INDEX idx_properties_trash_tenant ON properties_trash(tenant_id, deleted_at DESC);
+CREATE INDEX idx_investments_trash_tenant ON investments_trash(tenant_id, deleted_at DESC);
+
+-- Tâches périodiques des workers : bail du worker qui exécute la tâche, pour
+-- qu'elle ne tourne que sur un processus à la fois (voir worker.rs)
+CREATE TABLE worker_jobs (
+    name TEXT PRIMARY KEY,
+    locked_by TEXT, -- hôte:pid du worker tenant le bail
+    locked_until TIMESTAMPTZ,
+    last_started_at TIMESTAMPTZ,
+    last_finished_at TIMESTAMPTZ,
+    last_error TEXT
+);
+
+-- File de tâches durable consommée par les workers (voir jobs.rs) : une tâche
+-- en échec est retentée plus tard, puis passe en `dead` une fois ses
+-- tentatives épuisées, jusqu'à sa relance par un admin
+CREATE TABLE jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    status job_status NOT NULL DEFAULT 'queued',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_by TEXT, -- hôte:pid du worker qui exécute la tâche
+    locked_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_jobs_due ON jobs(run_at) WHERE status = 'queued';
+CREATE INDEX idx_jobs_running ON jobs(locked_at) WHERE status = 'running';
+CREATE INDEX idx_jobs_dead ON jobs(created_at DESC) WHERE status = 'dead';
+
+-- Outbox des événements métier (voir outbox.rs) : écrits dans la transaction
+-- de la modification, publiés ensuite par les workers
+CREATE TABLE outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_type TEXT NOT NULL, -- InvestmentCreated, PropertyValidated
+    aggregate_id UUID NOT NULL, -- investissement ou propriété concerné
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    available_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), -- prochain essai de publication
+    published_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_outbox_pending ON outbox(created_at) WHERE published_at IS NULL;
+CREATE INDEX idx_outbox_aggregate ON outbox(aggregate_id, created_at);
+
+-- Événements `Invested` du contrat d'investissement reçus par l'indexation,
+-- rapprochés d'un investissement ou en attente de résolution par un admin
+CREATE TABLE onchain_investments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tx_hash TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    block_number BIGINT NOT NULL,
+    wallet TEXT NOT NULL, -- En minuscules
+    onchain_property_id TEXT NOT NULL, -- Identifiant décimal émis par le contrat
+    shares INTEGER NOT NULL CHECK (shares > 0),
+    source TEXT NOT NULL,
+    status onchain_investment_status NOT NULL DEFAULT 'unmatched',
+    reason TEXT, -- Motif du dernier échec de rapprochement
+    investment_id UUID REFERENCES investments(id) ON DELETE SET NULL,
+    resolved_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    resolved_at TIMESTAMPTZ,
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tx_hash, log_index)
+);
+
+CREATE INDEX idx_onchain_investments_status ON onchain_investments(status, created_at);
+
+-- Double écriture pendant une bascule de base (voir dual_write.rs) : les
+-- écritures de l'API sur users, properties et investments sont consignées
+-- par trigger, puis rejouées dans l'ordre sur l'autre base par les workers
+CREATE TABLE row_changes (
+    id BIGSERIAL PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    operation TEXT NOT NULL CHECK (operation IN ('INSERT', 'UPDATE', 'DELETE')),
+    row_id UUID NOT NULL,
+    old_row JSONB, -- État attendu sur l'autre base (UPDATE, DELETE)
+    new_row JSONB, -- État à écrire (INSERT, UPDATE)
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    applied_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_row_changes_pending ON row_changes(id) WHERE applied_at IS NULL;
+CREATE INDEX idx_row_changes_applied ON row_changes(applied_at) WHERE applied_at IS NOT NULL;
+
+-- Écarts constatés en rejouant une écriture : ligne de l'autre base absente,
+-- inattendue ou différente de l'état attendu, ou écriture refusée
+CREATE TABLE dual_write_divergences (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    change_id BIGINT NOT NULL,
+    table_name TEXT NOT NULL,
+    row_id UUID NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('missing', 'unexpected', 'mismatch', 'apply_failed')),
+    expected JSONB,
+    actual JSONB,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_dual_write_divergences_created ON dual_write_divergences(created_at DESC);
+
+-- Lectures fantômes (voir shadow_reads.rs) : réponses dont la requête de
+-- remplacement ou le réplica a donné un résultat différent, ou a échoué
+CREATE TABLE shadow_read_diffs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    endpoint TEXT NOT NULL,
+    request_key TEXT NOT NULL, -- Paramètres de la requête (plateforme, propriété...)
+    differences JSONB NOT NULL DEFAULT '[]'::jsonb, -- [{ path, legacy, shadow }]
+    error TEXT,
+    legacy_ms INTEGER NOT NULL,
+    shadow_ms INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_shadow_read_diffs_created ON shadow_read_diffs(created_at DESC);
+
+-- Sauvegardes logiques demandées par les admins (copie des tables principales,
+-- voir backups.rs), exportées en tâche de fond vers le bucket privé ; mêmes
+-- statuts que les rapports
+CREATE TABLE backups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    status report_status NOT NULL DEFAULT 'queued',
+    comment TEXT, -- Motif (ex. avant une opération en masse)
+    tables TEXT[] NOT NULL,
+    storage_key TEXT,
+    row_count BIGINT,
+    size BIGINT,
+    error TEXT,
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_backups_created ON backups(created_at DESC);
+
+-- Co-gestionnaires d'une propriété (voir property_managers.rs), en plus de son
+-- créateur : owner gère aussi les co-gestionnaires, editor la galerie et les
+-- documents, viewer consulte la propriété et ses investissements
+CREATE TABLE property_managers (
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role property_manager_role NOT NULL,
+    added_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (property_id, user_id)
+);
+
+CREATE INDEX idx_property_managers_user ON property_managers(user_id);
+
+-- Versements des revenus d'une propriété, enregistrés par l'admin (voir
+-- distributions.rs) : les revenus courent de nouveau à partir de `paid_through`
+CREATE TABLE distribution_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    property_id UUID NOT NULL REFERENCES properties(id) ON DELETE CASCADE,
+    paid_through TIMESTAMPTZ NOT NULL,
+    recorded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_distribution_runs_property ON distribution_runs(property_id, paid_through DESC);
+
+CREATE INDEX idx_users_tenant ON users(tenant_id);
+CREATE UNIQUE INDEX users_wallet_key ON users(wallet) WHERE deactivated_at IS NULL;
+CREATE INDEX idx_properties_tenant ON properties(tenant_id);
+-- Autocomplétion (voir suggest.rs) : préfixes de mots du nom et de la localisation
+CREATE INDEX idx_properties_name_trgm ON properties USING gin (lower(name) gin_trgm_ops);
+CREATE INDEX idx_properties_location_trgm ON properties USING gin (lower(location) gin_trgm_ops);
+-- Filtres et facettes de localisation (voir facets.rs)
+CREATE INDEX idx_properties_place ON properties(tenant_id, country, region, city);
+CREATE INDEX idx_investments_tenant ON investments(tenant_id);
+CREATE INDEX idx_api_keys_tenant ON api_keys(tenant_id, created_at DESC);
+
+CREATE INDEX idx_notifications_created ON notifications(created_at);
+CREATE INDEX idx_document_downloads_created ON document_downloads(created_at);
+CREATE INDEX idx_auth_failures_created ON auth_failures(created_at);
+
+CREATE INDEX idx_investments_pending_settlement ON investments(created_at) WHERE status = 'pending_settlement';
+
+-- Tranche de rendement annuel (en %) des filtres et facettes de propriétés
+-- (voir facets.rs) : bornes basses incluses
+CREATE OR REPLACE FUNCTION property_yield_bucket(annual_yield NUMERIC)
+RETURNS TEXT AS $$
+    SELECT CASE
+        WHEN annual_yield < 3 THEN '0-3'
+        WHEN annual_yield < 5 THEN '3-5'
+        WHEN annual_yield < 8 THEN '5-8'
+        ELSE '8+'
+    END
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Tient à jour properties.shares_sold à chaque écriture sur investments (y
+-- compris directe) ; le verrou de la ligne de la propriété sérialise les
+-- investissements concurrents, la contrainte properties_shares_sold_check
+-- refuse ceux qui dépasseraient les parts émises
+CREATE OR REPLACE FUNCTION sync_property_shares_sold()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Écriture rejouée par la double écriture : shares_sold arrive avec la propriété
+    IF current_setting('pa.replaying', true) = 'on' THEN
+        RETURN NULL;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.status <> 'refunded' THEN
+        UPDATE properties SET shares_sold = shares_sold - OLD.shares WHERE id = OLD.property_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.status <> 'refunded' THEN
+        UPDATE properties SET shares_sold = shares_sold + NEW.shares WHERE id = NEW.property_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER investments_shares_sold
+    AFTER INSERT OR DELETE OR UPDATE OF shares, status, property_id ON investments
+    FOR EACH ROW EXECUTE FUNCTION sync_property_shares_sold();
+
+-- Tient à jour property_investment_daily : chaque écriture sur investments
+-- retire l'ancienne ligne de son jour et ajoute la nouvelle, sans recalcul
+CREATE OR REPLACE FUNCTION sync_property_investment_daily()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.status <> 'refunded' THEN
+        UPDATE property_investment_daily SET
+            investments_count = investments_count - 1,
+            volume_eth = volume_eth - OLD.amount_eth,
+            shares = shares - OLD.shares
+        WHERE property_id = OLD.property_id AND day = date_trunc('day', OLD.created_at);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.status <> 'refunded' THEN
+        INSERT INTO property_investment_daily (property_id, day, investments_count, volume_eth, shares)
+        VALUES (NEW.property_id, date_trunc('day', NEW.created_at), 1, NEW.amount_eth, NEW.shares)
+        ON CONFLICT (property_id, day) DO UPDATE SET
+            investments_count = property_investment_daily.investments_count + 1,
+            volume_eth = property_investment_daily.volume_eth + EXCLUDED.volume_eth,
+            shares = property_investment_daily.shares + EXCLUDED.shares;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER investments_daily_stats
+    AFTER INSERT OR DELETE OR UPDATE OF amount_eth, shares, status, property_id, created_at ON investments
+    FOR EACH ROW EXECUTE FUNCTION sync_property_investment_daily();
+
+-- Émet le certificat d'un investissement confirmé (réglé avec un hash de
+-- transaction), quel que soit le chemin d'écriture ; une correction révoque le
+-- certificat précédent et en émet un nouveau, un remboursement le révoque
+CREATE OR REPLACE FUNCTION issue_investment_certificate()
+RETURNS TRIGGER AS $$
+DECLARE
+    v_wallet TEXT;
+    v_onchain_id TEXT;
+    v_hash TEXT;
+BEGIN
+    IF NEW.status = 'settled' AND NEW.tx_hash IS NOT NULL THEN
+        SELECT lower(u.wallet), p.onchain_id INTO v_wallet, v_onchain_id
+        FROM users u, properties p
+        WHERE u.id = NEW.user_id AND p.id = NEW.property_id;
+
+        v_hash := encode(sha256(convert_to(
+            'pa-certificate-v1|' || v_wallet || '|' || v_onchain_id || '|' || NEW.shares || '|' || lower(NEW.tx_hash),
+            'UTF8')), 'hex');
+
+        UPDATE investment_certificates SET revoked_at = NOW()
+        WHERE investment_id = NEW.id AND revoked_at IS NULL AND hash <> v_hash;
+
+        INSERT INTO investment_certificates (investment_id, hash, wallet, onchain_id, shares, tx_hash)
+        VALUES (NEW.id, v_hash, v_wallet, v_onchain_id, NEW.shares, lower(NEW.tx_hash))
+        ON CONFLICT (hash) DO UPDATE SET revoked_at = NULL;
+    ELSE
+        UPDATE investment_certificates SET revoked_at = NOW()
+        WHERE investment_id = NEW.id AND revoked_at IS NULL;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER investments_certificate
+    AFTER INSERT OR UPDATE OF shares, tx_hash, status ON investments
+    FOR EACH ROW EXECUTE FUNCTION issue_investment_certificate();
+
+-- Consigne les écritures des connexions de l'API quand la double écriture est
+-- active (réglage de session `pa.capture_changes`, voir dual_write.rs)
+CREATE OR REPLACE FUNCTION capture_row_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF current_setting('pa.capture_changes', true) IS DISTINCT FROM 'on' THEN
+        RETURN NULL;
+    END IF;
+    INSERT INTO row_changes (table_name, operation, row_id, old_row, new_row)
+    VALUES (
+        TG_TABLE_NAME,
+        TG_OP,
+        CASE WHEN TG_OP = 'DELETE' THEN OLD.id ELSE NEW.id END,
+        CASE WHEN TG_OP <> 'INSERT' THEN to_jsonb(OLD) END,
+        CASE WHEN TG_OP <> 'DELETE' THEN to_jsonb(NEW) END
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_row_changes
+    AFTER INSERT OR UPDATE OR DELETE ON users
+    FOR EACH ROW EXECUTE FUNCTION capture_row_change();
+
+CREATE TRIGGER properties_row_changes
+    AFTER INSERT OR UPDATE OR DELETE ON properties
+    FOR EACH ROW EXECUTE FUNCTION capture_row_change();
+
+CREATE TRIGGER investments_row_changes
+    AFTER INSERT OR UPDATE OR DELETE ON investments
+    FOR EACH ROW EXECUTE FUNCTION capture_row_change();
+
 -- Fonction pour obtenir le rôle d'un utilisateur à partir de son wallet
 CREATE OR REPLACE FUNCTION get_user_role(wallet_address TEXT)
 RETURNS TEXT AS $$
@@ -83,12 +1387,80 @@ $$ LANGUAGE plpgsql;
 -- Activer Row Level Security (RLS)
 ALTER TABLE properties ENABLE ROW LEVEL SECURITY;
 ALTER TABLE investments ENABLE ROW LEVEL SECURITY;
+ALTER TABLE investment_intents ENABLE ROW LEVEL SECURITY;
+ALTER TABLE fiat_payments ENABLE ROW LEVEL SECURITY;
+ALTER TABLE investment_refunds ENABLE ROW LEVEL SECURITY;
+ALTER TABLE notifications ENABLE ROW LEVEL SECURITY;
+ALTER TABLE kyc_verifications ENABLE ROW LEVEL SECURITY;
+ALTER TABLE compliance_flags ENABLE ROW LEVEL SECURITY;
+ALTER TABLE auth_failures ENABLE ROW LEVEL SECURITY;
+ALTER TABLE risk_alerts ENABLE ROW LEVEL SECURITY;
+ALTER TABLE risk_alert_notes ENABLE ROW LEVEL SECURITY;
+ALTER TABLE legal_documents ENABLE ROW LEVEL SECURITY;
+ALTER TABLE user_acceptances ENABLE ROW LEVEL SECURITY;
+ALTER TABLE accreditations ENABLE ROW LEVEL SECURITY;
+ALTER TABLE exposure_limits ENABLE ROW LEVEL SECURITY;
+ALTER TABLE property_status_events ENABLE ROW LEVEL SECURITY;
+ALTER TABLE reports ENABLE ROW LEVEL SECURITY;
+ALTER TABLE retention_policies ENABLE ROW LEVEL SECURITY;
+ALTER TABLE notifications_archive ENABLE ROW LEVEL SECURITY;
+ALTER TABLE document_downloads_archive ENABLE ROW LEVEL SECURITY;
+ALTER TABLE auth_failures_archive ENABLE ROW LEVEL SECURITY;
+ALTER TABLE request_logs ENABLE ROW LEVEL SECURITY;
+ALTER TABLE request_logs_archive ENABLE ROW LEVEL SECURITY;
+ALTER TABLE feature_flags ENABLE ROW LEVEL SECURITY;
+ALTER TABLE tenants ENABLE ROW LEVEL SECURITY;
+ALTER TABLE user_wallets ENABLE ROW LEVEL SECURITY;
+ALTER TABLE user_totp ENABLE ROW LEVEL SECURITY;
+ALTER TABLE totp_recovery_codes ENABLE ROW LEVEL SECURITY;
+ALTER TABLE two_factor_step_ups ENABLE ROW LEVEL SECURITY;
+ALTER TABLE action_confirmations ENABLE ROW LEVEL SECURITY;
+ALTER TABLE user_api_tokens ENABLE ROW LEVEL SECURITY;
+ALTER TABLE signed_requests ENABLE ROW LEVEL SECURITY;
+ALTER TABLE impersonation_sessions ENABLE ROW LEVEL SECURITY;
+ALTER TABLE impersonation_actions ENABLE ROW LEVEL SECURITY;
+ALTER TABLE role_change_requests ENABLE ROW LEVEL SECURITY;
+ALTER TABLE user_events ENABLE ROW LEVEL SECURITY;
+ALTER TABLE notification_preferences ENABLE ROW LEVEL SECURITY;
+ALTER TABLE notification_unsubscribe_tokens ENABLE ROW LEVEL SECURITY;
+ALTER TABLE email_digests ENABLE ROW LEVEL SECURITY;
+ALTER TABLE contents ENABLE ROW LEVEL SECURITY;
+ALTER TABLE announcements ENABLE ROW LEVEL SECURITY;
+ALTER TABLE announcement_dismissals ENABLE ROW LEVEL SECURITY;
+ALTER TABLE investment_revisions ENABLE ROW LEVEL SECURITY;
+ALTER TABLE property_investment_daily ENABLE ROW LEVEL SECURITY;
+ALTER TABLE exchange_rates ENABLE ROW LEVEL SECURITY;
+ALTER TABLE tax_rules ENABLE ROW LEVEL SECURITY;
+ALTER TABLE investment_certificates ENABLE ROW LEVEL SECURITY;
+ALTER TABLE user_merges ENABLE ROW LEVEL SECURITY;
+ALTER TABLE properties_trash ENABLE ROW LEVEL SECURITY;
+ALTER TABLE investments_trash ENABLE ROW LEVEL SECURITY;
+ALTER TABLE worker_jobs ENABLE ROW LEVEL SECURITY;
+ALTER TABLE jobs ENABLE ROW LEVEL SECURITY;
+ALTER TABLE outbox ENABLE ROW LEVEL SECURITY;
+ALTER TABLE onchain_investments ENABLE ROW LEVEL SECURITY;
+ALTER TABLE row_changes ENABLE ROW LEVEL SECURITY;
+ALTER TABLE dual_write_divergences ENABLE ROW LEVEL SECURITY;
+ALTER TABLE shadow_read_diffs ENABLE ROW LEVEL SECURITY;
+ALTER TABLE backups ENABLE ROW LEVEL SECURITY;
+ALTER TABLE property_managers ENABLE ROW LEVEL SECURITY;
+ALTER TABLE distribution_runs ENABLE ROW LEVEL SECURITY;
+ALTER TABLE pending_txs ENABLE ROW LEVEL SECURITY;
+ALTER TABLE token_transfers ENABLE ROW LEVEL SECURITY;
+ALTER TABLE indexer_cursors ENABLE ROW LEVEL SECURITY;
 ALTER TABLE users ENABLE ROW LEVEL SECURITY;
+ALTER TABLE api_keys ENABLE ROW LEVEL SECURITY;
+ALTER TABLE api_key_usage ENABLE ROW LEVEL SECURITY;
+ALTER TABLE tags ENABLE ROW LEVEL SECURITY;
+ALTER TABLE property_tags ENABLE ROW LEVEL SECURITY;
+ALTER TABLE property_media ENABLE ROW LEVEL SECURITY;
+ALTER TABLE document_downloads ENABLE ROW LEVEL SECURITY;
+ALTER TABLE document_pins ENABLE ROW LEVEL SECURITY;
 
 -- Politiques RLS pour properties
 CREATE POLICY "Tous peuvent voir les propriétés validées" 
     ON properties FOR SELECT 
-    USING (status = 'validated');
+    USING (status = 'validated' AND published_at IS NOT NULL);
 
 CREATE POLICY "Admin et manager peuvent voir toutes les propriétés" 
     ON properties FOR SELECT 
@@ -151,6 +1523,15 @@ CREATE POLICY "Utilisateurs peuvent modifier leur propre profil"
         get_user_role(auth.jwt()->>'wallet') = 'admin'
     );
 
--- Création d'un utilisateur administrateur par défaut (à modifier avec vos propres valeurs)
+-- Création d'un utilisateur administrateur par défaut (à modifier avec vos propres valeurs,
+-- adresse en minuscules)
 INSERT INTO users (wallet, name, role) 
-VALUES ('0xAdminWalletAddress', 'Admin', 'admin'); 
\ No newline at end of file
+VALUES ('0x00000000000000000000000000000000000ad111', 'Admin', 'admin'); 
+
+-- Durées de conservation par défaut (modifiables via /api/admin/retention)
+INSERT INTO retention_policies (entity, retention_days)
+VALUES ('notifications', 180), ('document_downloads', 365), ('auth_failures', 30), ('request_logs', 1825);
+
+-- Fonctionnalités existantes, actives par défaut (désactivables via /api/admin/feature-flags)
+INSERT INTO feature_flags (key, description, enabled)
+VALUES ('fiat_payments', 'Paiements en euros via Stripe', TRUE);
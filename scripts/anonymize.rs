@@ -0,0 +1,149 @@
+// scripts/anonymize.rs
+//
+// Anonymise une COPIE de la base de production pour l'utiliser en staging ou
+// en test : wallets, noms, hash de transactions et identifiants externes sont
+// remplacés par une empreinte SHA-256 salée, les corps de requêtes journalisés
+// sont effacés. La transformation est
+// déterministe (une même valeur donne toujours le même résultat, quelle que
+// soit la table), ce qui préserve les jointures et les contraintes d'unicité.
+// Les UUID ne sont pas modifiés : les clés étrangères restent valides.
+//
+// Usage : ANONYMIZE_SALT=<secret> cargo run --bin anonymize -- <database_url>
+
+use sqlx::PgPool;
+use dotenvy::dotenv;
+use std::env;
+
+/// Type de valeur remplacée, qui détermine le format de l'empreinte
+#[derive(Clone, Copy)]
+enum Kind {
+    /// Adresse Ethereum (`0x` + 40 caractères hexadécimaux, insensible à la casse)
+    Address,
+    /// Hash de transaction ou digest (`0x` + 64 caractères hexadécimaux)
+    Hash,
+    /// Signature ECDSA (`0x` + 130 caractères hexadécimaux)
+    Signature,
+    /// Nom affiché
+    Name,
+    /// Adresse e-mail (domaine réservé example.invalid)
+    Email,
+    /// Adresse IP (plage privée 10.0.0.0/8)
+    Ip,
+    /// Identifiant chez un prestataire (Stripe, Sumsub)
+    ExternalId,
+    /// Contenu libre non transformable (corps de requêtes journalisés) : effacé
+    Erased,
+}
+
+/// Colonnes à anonymiser : (table, colonne, type, tableau de valeurs)
+const COLUMNS: &[(&str, &str, Kind, bool)] = &[
+    ("users", "wallet", Kind::Address, false),
+    ("users", "name", Kind::Name, false),
+    ("users", "email", Kind::Email, false),
+    ("user_wallets", "wallet", Kind::Address, false),
+    // Le message de défi contient le wallet en clair : remplacé par son empreinte
+    ("user_wallets", "challenge", Kind::Hash, false),
+    // Secrets TOTP rendus inutilisables : supprimer la ligne de user_totp pour réenrôler
+    ("user_totp", "secret", Kind::Hash, false),
+    // Jetons d'API personnels de production inutilisables en staging
+    ("user_api_tokens", "token_hash", Kind::Hash, false),
+    ("impersonation_sessions", "token_hash", Kind::Hash, false),
+    ("impersonation_actions", "ip", Kind::Ip, false),
+    ("user_events", "ip", Kind::Ip, false),
+    ("properties", "registry_tx_hash", Kind::Hash, false),
+    ("investments", "tx_hash", Kind::Hash, false),
+    ("investment_intents", "investor", Kind::Address, false),
+    ("investment_intents", "digest", Kind::Hash, false),
+    ("investment_intents", "signature", Kind::Signature, false),
+    ("signed_requests", "wallet", Kind::Address, false),
+    ("signed_requests", "signature", Kind::Signature, false),
+    ("pending_txs", "from_address", Kind::Address, false),
+    ("pending_txs", "tx_hash", Kind::Hash, false),
+    ("pending_txs", "tx_hashes", Kind::Hash, true),
+    ("token_transfers", "from_address", Kind::Address, false),
+    ("token_transfers", "to_address", Kind::Address, false),
+    ("token_transfers", "tx_hash", Kind::Hash, false),
+    ("fiat_payments", "stripe_payment_intent_id", Kind::ExternalId, false),
+    ("investment_refunds", "tx_hash", Kind::Hash, false),
+    ("kyc_verifications", "applicant_id", Kind::ExternalId, false),
+    ("compliance_flags", "wallet", Kind::Address, false),
+    ("auth_failures", "wallet", Kind::Address, false),
+    ("auth_failures", "ip", Kind::Ip, false),
+    ("auth_failures_archive", "wallet", Kind::Address, false),
+    ("auth_failures_archive", "ip", Kind::Ip, false),
+    ("request_logs", "ip", Kind::Ip, false),
+    ("request_logs", "request_body", Kind::Erased, false),
+    ("request_logs_archive", "ip", Kind::Ip, false),
+    ("request_logs_archive", "request_body", Kind::Erased, false),
+];
+
+/// Expression SQL de l'empreinte de `value` ; le sel est le paramètre `$1`
+fn scrambled(kind: Kind, value: &str) -> String {
+    let digest = |prefix: &str, input: &str| format!(
+        "encode(sha256(convert_to($1 || ':{}:' || {}, 'UTF8')), 'hex')", prefix, input
+    );
+    match kind {
+        Kind::Address => format!("'0x' || left({}, 40)", digest("address", &format!("lower({})", value))),
+        Kind::Hash => format!("'0x' || {}", digest("hash", &format!("lower({})", value))),
+        Kind::Signature => format!(
+            "'0x' || {} || {} || '1b'",
+            digest("signature-r", value),
+            digest("signature-s", value)
+        ),
+        Kind::Name => format!("'Utilisateur ' || left({}, 8)", digest("name", value)),
+        Kind::Email => format!("'user-' || left({}, 12) || '@example.invalid'", digest("email", &format!("lower({})", value))),
+        Kind::Ip => {
+            let hex = digest("ip", value);
+            format!(
+                "'10.' || get_byte(decode({hex}, 'hex'), 0) || '.' || get_byte(decode({hex}, 'hex'), 1) || '.' || get_byte(decode({hex}, 'hex'), 2)"
+            )
+        },
+        Kind::ExternalId => format!("'anon_' || left({}, 24)", digest("external", value)),
+        Kind::Erased => "NULL".to_string(),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv().ok();
+
+    // L'URL est demandée explicitement : DATABASE_URL (souvent la production
+    // dans le .env) n'est jamais utilisée par défaut
+    let database_url = env::args().nth(1)
+        .or_else(|| env::var("ANONYMIZE_DATABASE_URL").ok())
+        .expect("Usage : cargo run --bin anonymize -- <database_url> (ou ANONYMIZE_DATABASE_URL)");
+    // Sans sel secret, une empreinte de wallet public serait retrouvable par force brute
+    let salt = env::var("ANONYMIZE_SALT")
+        .ok()
+        .filter(|s| s.len() >= 16)
+        .expect("ANONYMIZE_SALT doit être défini (16 caractères minimum)");
+
+    let pool = PgPool::connect(&database_url).await?;
+
+    println!("🔄 Anonymisation de la base...");
+
+    // Tout ou rien : une base à moitié anonymisée ne doit jamais servir
+    let mut tx = pool.begin().await?;
+    for (table, column, kind, is_array) in COLUMNS {
+        let expression = if *is_array {
+            format!("ARRAY(SELECT {} FROM unnest({}) AS v)", scrambled(*kind, "v"), column)
+        } else {
+            scrambled(*kind, column)
+        };
+        let statement = format!(
+            "UPDATE {table} SET {column} = {expression} WHERE {column} IS NOT NULL"
+        );
+        // Un effacement n'utilise pas le sel
+        let query = match kind {
+            Kind::Erased => sqlx::query(&statement),
+            _ => sqlx::query(&statement).bind(&salt),
+        };
+        let result = query.execute(&mut *tx).await?;
+        println!("✅ {}.{} : {} ligne(s)", table, column, result.rows_affected());
+    }
+    tx.commit().await?;
+
+    println!("✅ Anonymisation terminée avec succès!");
+    println!("ℹ️  Pour vous connecter en admin, remplacez le wallet d'un compte admin par le vôtre.");
+    Ok(())
+}
@@ -0,0 +1,394 @@
+// scripts/loadgen.rs
+//
+// Générateur de données de charge : insère N utilisateurs, M propriétés et K
+// investissements avec des distributions réalistes, pour mesurer les routes de
+// liste (et la pagination / le cache) à un volume proche de la production.
+//
+// - utilisateurs : ~2% de managers, inscriptions plus nombreuses ces derniers mois ;
+// - propriétés : 70% validées et publiées, prix log-normaux, rendement 3 à 9% ;
+// - investissements : quelques propriétés concentrent l'essentiel des
+//   investissements (Zipf) et quelques gros investisseurs l'essentiel des
+//   montants (Pareto), sans dépasser le nombre de parts de chaque propriété.
+//
+// Les données générées sont repérables (wallets `0x10adc0de…`, onchain_id
+// `loadgen-…`) et supprimées par `--clean`.
+//
+// Usage : cargo run --release --bin loadgen -- --users 10000 --properties 500 --investments 200000 [--seed 42]
+//         cargo run --release --bin loadgen -- --clean
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use dotenvy::dotenv;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sqlx::PgPool;
+use std::env;
+use std::str::FromStr;
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Lignes insérées par requête
+const BATCH_SIZE: usize = 1000;
+/// Préfixe des wallets générés
+const WALLET_PREFIX: &str = "0x10adc0de";
+/// Préfixe des onchain_id (et des slugs) générés
+const ONCHAIN_PREFIX: &str = "loadgen-";
+
+const CITIES: &[&str] = &[
+    "Paris", "Lyon", "Marseille", "Bordeaux", "Lille", "Nantes", "Toulouse", "Nice", "Rennes", "Montpellier",
+];
+const PROPERTY_TYPES: &[&str] = &["apartment", "house", "studio", "commercial", "office"];
+const TOKEN_PRICES: &[&str] = &["0.01", "0.05", "0.1", "0.25", "0.5"];
+
+struct Options {
+    users: usize,
+    properties: usize,
+    investments: usize,
+    seed: u64,
+    clean: bool,
+}
+
+fn parse_options() -> Options {
+    let mut options = Options { users: 1000, properties: 100, investments: 10000, seed: 42, clean: false };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or_else(|| panic!("{} attend un nombre", name));
+        match arg.as_str() {
+            "--users" => options.users = value("--users") as usize,
+            "--properties" => options.properties = value("--properties") as usize,
+            "--investments" => options.investments = value("--investments") as usize,
+            "--seed" => options.seed = value("--seed"),
+            "--clean" => options.clean = true,
+            other => panic!("Option inconnue : {}", other),
+        }
+    }
+    options
+}
+
+fn random_hex(rng: &mut StdRng, bytes: usize) -> String {
+    (0..bytes).map(|_| format!("{:02x}", rng.gen::<u8>())).collect()
+}
+
+fn random_uuid(rng: &mut StdRng) -> Uuid {
+    uuid::Builder::from_random_bytes(rng.gen()).into_uuid()
+}
+
+/// Date aléatoire entre `from` et `to`
+fn between(rng: &mut StdRng, from: DateTime<Utc>, to: DateTime<Utc>) -> DateTime<Utc> {
+    let span = (to - from).num_seconds().max(1);
+    from + Duration::seconds(rng.gen_range(0..span))
+}
+
+/// Tirage de Pareto (loi de puissance) de minimum 1
+fn pareto(rng: &mut StdRng, alpha: f64) -> f64 {
+    let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+    u.powf(-1.0 / alpha)
+}
+
+fn decimal(value: f64, scale: usize) -> BigDecimal {
+    BigDecimal::from_str(&format!("{:.*}", scale, value)).expect("décimal valide")
+}
+
+struct GeneratedProperty {
+    id: Uuid,
+    token_price: BigDecimal,
+    remaining_shares: i64,
+    published_at: Option<DateTime<Utc>>,
+}
+
+async fn insert_users(pool: &PgPool, rng: &mut StdRng, count: usize) -> Result<(Vec<(Uuid, DateTime<Utc>)>, Vec<Uuid>), sqlx::Error> {
+    let now = Utc::now();
+    let managers_count = (count / 50).max(1).min(count);
+    let mut users = Vec::with_capacity(count);
+    let mut managers = Vec::with_capacity(managers_count);
+
+    for chunk_start in (0..count).step_by(BATCH_SIZE) {
+        let chunk_end = (chunk_start + BATCH_SIZE).min(count);
+        let (mut ids, mut wallets, mut names, mut roles, mut opt_ins, mut dates) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for index in chunk_start..chunk_end {
+            let id = random_uuid(rng);
+            // Croissance : les inscriptions récentes sont plus nombreuses
+            let days_ago = 365.0 * (1.0 - rng.gen::<f64>().sqrt());
+            let created_at = now - Duration::seconds((days_ago * 86400.0) as i64);
+            let is_manager = index < managers_count;
+            ids.push(id);
+            wallets.push(format!("{}{}", WALLET_PREFIX, random_hex(rng, 16)));
+            names.push(format!("Investisseur {}", index + 1));
+            roles.push(if is_manager { "manager" } else { "user" }.to_string());
+            opt_ins.push(rng.gen_bool(0.15));
+            dates.push(created_at);
+            if is_manager {
+                managers.push(id);
+            }
+            users.push((id, created_at));
+        }
+        sqlx::query(
+            r#"INSERT INTO users (id, wallet, name, role, leaderboard_opt_in, created_at)
+               SELECT * FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::user_role[], $5::bool[], $6::timestamptz[])"#
+        )
+        .bind(ids)
+        .bind(wallets)
+        .bind(names)
+        .bind(roles)
+        .bind(opt_ins)
+        .bind(dates)
+        .execute(pool)
+        .await?;
+    }
+    Ok((users, managers))
+}
+
+async fn insert_properties(pool: &PgPool, rng: &mut StdRng, count: usize, managers: &[Uuid]) -> Result<Vec<GeneratedProperty>, sqlx::Error> {
+    let now = Utc::now();
+    let run = random_hex(rng, 4);
+    let mut properties = Vec::with_capacity(count);
+
+    for chunk_start in (0..count).step_by(BATCH_SIZE) {
+        let chunk_end = (chunk_start + BATCH_SIZE).min(count);
+        let (mut ids, mut onchain_ids, mut names, mut locations, mut types) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let (mut total_prices, mut token_prices, mut yields, mut creators, mut created) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let (mut statuses, mut reviewed, mut published) = (Vec::new(), Vec::new(), Vec::new());
+        let (mut event_properties, mut event_statuses, mut event_dates) = (Vec::new(), Vec::new(), Vec::new());
+
+        for index in chunk_start..chunk_end {
+            let id = random_uuid(rng);
+            let created_at = between(rng, now - Duration::days(365), now);
+            let city = CITIES[rng.gen_range(0..CITIES.len())];
+            let token_price = BigDecimal::from_str(TOKEN_PRICES[rng.gen_range(0..TOKEN_PRICES.len())]).expect("décimal valide");
+            // Prix log-normal centré sur ~150 ETH (Box-Muller)
+            let normal = (-2.0 * rng.gen_range(f64::EPSILON..1.0f64).ln()).sqrt()
+                * (2.0 * std::f64::consts::PI * rng.gen::<f64>()).cos();
+            let total_price = decimal((150.0 * (0.8 * normal).exp()).clamp(10.0, 5000.0).round(), 0);
+            let total_shares = (&total_price / &token_price).with_scale(0).to_string().parse::<i64>().unwrap_or(0);
+
+            let status = match rng.gen_range(0..100) {
+                0..=69 => "validated",
+                70..=79 => "pending",
+                80..=89 => "draft",
+                _ => "rejected",
+            };
+            let reviewed_at = (status == "validated" || status == "rejected")
+                .then(|| between(rng, created_at, (created_at + Duration::days(14)).min(now)));
+            let published_at = if status == "validated" { reviewed_at } else { None };
+
+            if status != "draft" {
+                event_properties.push(id);
+                event_statuses.push("pending".to_string());
+                event_dates.push(created_at);
+            }
+            if let Some(reviewed_at) = reviewed_at {
+                event_properties.push(id);
+                event_statuses.push(status.to_string());
+                event_dates.push(reviewed_at);
+            }
+
+            ids.push(id);
+            onchain_ids.push(format!("{}{}-{}", ONCHAIN_PREFIX, run, index + 1));
+            names.push(format!("{} {} {}", PROPERTY_TYPES[index % PROPERTY_TYPES.len()], city, index + 1));
+            locations.push(city.to_string());
+            types.push(PROPERTY_TYPES[index % PROPERTY_TYPES.len()].to_string());
+            total_prices.push(total_price);
+            token_prices.push(token_price.clone());
+            yields.push(decimal(rng.gen_range(3.0..9.0), 2));
+            creators.push(managers[rng.gen_range(0..managers.len())]);
+            created.push(created_at);
+            statuses.push(status.to_string());
+            reviewed.push(reviewed_at);
+            published.push(published_at);
+            properties.push(GeneratedProperty { id, token_price, remaining_shares: total_shares, published_at });
+        }
+
+        // Le slug reprend l'onchain_id, déjà unique
+        sqlx::query(
+            r#"INSERT INTO properties (id, onchain_id, slug, name, location, type, total_price, token_price, annual_yield,
+                                       documents, created_by, created_at, status, status_updated_at, published_at)
+               SELECT id, onchain_id, onchain_id, name, location, type, total_price, token_price, annual_yield,
+                      ARRAY['loadgen.pdf'], created_by, created_at, status, status_updated_at, published_at
+               FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::text[], $5::text[], $6::numeric[], $7::numeric[],
+                           $8::numeric[], $9::uuid[], $10::timestamptz[], $11::property_status[], $12::timestamptz[],
+                           $13::timestamptz[])
+                    AS t(id, onchain_id, name, location, type, total_price, token_price, annual_yield,
+                         created_by, created_at, status, status_updated_at, published_at)"#
+        )
+        .bind(ids)
+        .bind(onchain_ids)
+        .bind(names)
+        .bind(locations)
+        .bind(types)
+        .bind(total_prices)
+        .bind(token_prices)
+        .bind(yields)
+        .bind(creators)
+        .bind(created)
+        .bind(statuses)
+        .bind(reviewed)
+        .bind(published)
+        .execute(pool)
+        .await?;
+
+        // Historique des statuts, pour les séries de /api/admin/analytics
+        sqlx::query(
+            r#"INSERT INTO property_status_events (property_id, status, created_at)
+               SELECT * FROM UNNEST($1::uuid[], $2::property_status[], $3::timestamptz[])"#
+        )
+        .bind(event_properties)
+        .bind(event_statuses)
+        .bind(event_dates)
+        .execute(pool)
+        .await?;
+    }
+    Ok(properties)
+}
+
+async fn insert_investments(
+    pool: &PgPool,
+    rng: &mut StdRng,
+    count: usize,
+    users: &[(Uuid, DateTime<Utc>)],
+    properties: &mut [GeneratedProperty],
+) -> Result<usize, sqlx::Error> {
+    let now = Utc::now();
+    let mut open: Vec<usize> = (0..properties.len()).filter(|i| properties[*i].published_at.is_some()).collect();
+    if open.is_empty() || users.is_empty() {
+        return Ok(0);
+    }
+    // Popularité des propriétés (Zipf) et poids des investisseurs (Pareto)
+    let property_weights: Vec<f64> = (0..open.len()).map(|rank| 1.0 / ((rank + 1) as f64).powf(1.1)).collect();
+    let user_weights: Vec<f64> = users.iter().map(|_| pareto(rng, 1.16)).collect();
+    let user_index = WeightedIndex::new(&user_weights).expect("poids valides");
+    let mut property_index = WeightedIndex::new(&property_weights).expect("poids valides");
+
+    let mut inserted = 0;
+    while inserted < count && !open.is_empty() {
+        let (mut user_ids, mut property_ids, mut amounts, mut shares_list, mut hashes, mut dates) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        while user_ids.len() < BATCH_SIZE.min(count - inserted) && !open.is_empty() {
+            let slot = property_index.sample(rng);
+            let property = &mut properties[open[slot]];
+            let shares = (pareto(rng, 1.5).floor() as i64).min(500).min(property.remaining_shares);
+            if shares == 0 {
+                // Propriété entièrement financée : elle sort du tirage
+                open.swap_remove(slot);
+                if open.is_empty() {
+                    break;
+                }
+                property_index = WeightedIndex::new(&property_weights[..open.len()]).expect("poids valides");
+                continue;
+            }
+            property.remaining_shares -= shares;
+
+            let (user_id, user_created_at) = users[user_index.sample(rng)];
+            let from = user_created_at.max(property.published_at.unwrap_or(now));
+            user_ids.push(user_id);
+            property_ids.push(property.id);
+            amounts.push(&property.token_price * BigDecimal::from(shares));
+            shares_list.push(shares as i32);
+            hashes.push(format!("0x{}", random_hex(rng, 32)));
+            dates.push(between(rng, from.min(now), now));
+        }
+        let batch = user_ids.len();
+        if batch == 0 {
+            break;
+        }
+
+        sqlx::query(
+            r#"INSERT INTO investments (user_id, property_id, amount_eth, shares, tx_hash, settled_at, created_at)
+               SELECT user_id, property_id, amount_eth, shares, tx_hash, created_at, created_at
+               FROM UNNEST($1::uuid[], $2::uuid[], $3::numeric[], $4::int[], $5::text[], $6::timestamptz[])
+                    AS t(user_id, property_id, amount_eth, shares, tx_hash, created_at)"#
+        )
+        .bind(user_ids)
+        .bind(property_ids)
+        .bind(amounts)
+        .bind(shares_list)
+        .bind(hashes)
+        .bind(dates)
+        .execute(pool)
+        .await?;
+        inserted += batch;
+    }
+    Ok(inserted)
+}
+
+/// Supprime les données générées (et les investissements qui les référencent)
+async fn clean(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let wallets = format!("{}%", WALLET_PREFIX);
+    let onchain_ids = format!("{}%", ONCHAIN_PREFIX);
+    let mut tx = pool.begin().await?;
+    let investments = sqlx::query(
+        r#"DELETE FROM investments
+           WHERE user_id IN (SELECT id FROM users WHERE wallet LIKE $1)
+              OR property_id IN (SELECT id FROM properties WHERE onchain_id LIKE $2)"#
+    )
+    .bind(&wallets)
+    .bind(&onchain_ids)
+    .execute(&mut *tx)
+    .await?;
+    let properties = sqlx::query("DELETE FROM properties WHERE onchain_id LIKE $1")
+        .bind(&onchain_ids)
+        .execute(&mut *tx)
+        .await?;
+    let users = sqlx::query("DELETE FROM users WHERE wallet LIKE $1")
+        .bind(&wallets)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    println!(
+        "✅ Supprimés : {} utilisateur(s), {} propriété(s), {} investissement(s)",
+        users.rows_affected(), properties.rows_affected(), investments.rows_affected()
+    );
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv().ok();
+    let options = parse_options();
+
+    let database_url = env::var("DATABASE_URL")
+        .expect("DATABASE_URL doit être définie dans le fichier .env");
+    let pool = PgPool::connect(&database_url).await?;
+
+    if options.clean {
+        println!("🔄 Suppression des données de charge...");
+        clean(&pool).await?;
+        return Ok(());
+    }
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let started = Instant::now();
+
+    println!("🔄 Génération de {} utilisateur(s)...", options.users);
+    let (users, managers) = insert_users(&pool, &mut rng, options.users).await?;
+
+    let mut properties = Vec::new();
+    if managers.is_empty() {
+        println!("⚠️  Aucun manager généré : pas de propriétés ni d'investissements");
+    } else {
+        println!("🔄 Génération de {} propriété(s)...", options.properties);
+        properties = insert_properties(&pool, &mut rng, options.properties, &managers).await?;
+    }
+
+    println!("🔄 Génération de {} investissement(s)...", options.investments);
+    let investments = insert_investments(&pool, &mut rng, options.investments, &users, &mut properties).await?;
+    if investments < options.investments {
+        println!("⚠️  Seulement {} investissement(s) : toutes les propriétés publiées sont financées", investments);
+    }
+
+    // Statistiques à jour pour le planificateur avant les mesures
+    sqlx::query("ANALYZE users, properties, investments, property_status_events")
+        .execute(&pool)
+        .await?;
+
+    println!(
+        "✅ {} utilisateur(s), {} propriété(s), {} investissement(s) générés en {:.1}s",
+        users.len(), properties.len(), investments, started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
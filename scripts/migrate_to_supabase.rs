@@ -1,14 +1,431 @@
 // scripts/migrate_to_supabase.rs
+//
+// Applique migrations/supabase_migration.sql à la base DATABASE_URL. Avant
+// toute écriture, le schéma réel de la base (information_schema) est comparé
+// à celui que déclare le script : tables existantes qui seraient vidées par
+// leur suppression, colonnes d'un ancien schéma sans équivalent (par exemple
+// `properties.is_validated` de migrations/schema.sql), types différents,
+// tables ou types existants que le script crée sans les supprimer d'abord,
+// prérequis Supabase absents (`auth.jwt()`, rôle `authenticated`). La
+// migration est alors annulée avec un rapport lisible ; `--force` l'applique
+// malgré la perte de données. Le script est ensuite exécuté d'un bloc dans une
+// transaction : en cas d'erreur, la base reste inchangée.
+//
+// Sauvegarde logique sans accès pg_dump (Supabase) : `--export-dir` écrit les
+// plateformes, utilisateurs, propriétés et investissements dans un dossier
+// horodaté, en JSON (rechargeable par `--import`) et en CSV (lecture dans un
+// tableur). L'import ajoute les lignes absentes, en une transaction.
+//
+// Usage : cargo run --bin migrate_to_supabase [-- --check | --force]
+//   --check : affiche le rapport sans rien modifier
+//   --export-dir <dossier> : sauvegarde dans <dossier>/<AAAAMMJJTHHMMSSZ>/, sans migration
+//   --import <dossier> : recharge les fichiers JSON d'une sauvegarde, sans migration
 
-use sqlx::PgPool;
+use chrono::Utc;
+use sqlx::{Executor, PgPool, Row};
 use dotenvy::dotenv;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
 use std::{env, fs};
 
+const MIGRATION_FILE: &str = "migrations/supabase_migration.sql";
+
+/// Tables sauvegardées, dans l'ordre d'import (clés étrangères)
+const BACKUP_TABLES: &[&str] = &["tenants", "users", "properties", "investments"];
+
+/// Colonnes tenues à jour par trigger, recalculées à l'import des investissements
+const DERIVED_COLUMNS: &[(&str, &str)] = &[("properties", "shares_sold")];
+
+/// Colonnes d'une table : (nom, type au format `udt_name` de Postgres)
+type Columns = Vec<(String, String)>;
+
+/// Schéma déclaré par le script de migration
+#[derive(Default)]
+struct DeclaredSchema {
+    tables: BTreeMap<String, Columns>,
+    dropped_tables: BTreeSet<String>,
+    types: BTreeSet<String>,
+    dropped_types: BTreeSet<String>,
+}
+
+/// Nom d'objet SQL, sans guillemets et en minuscules
+fn ident(raw: &str) -> String {
+    raw.trim_matches(|c: char| c == '"' || c == '(' || c == ';' || c == ',').to_lowercase()
+}
+
+/// Type déclaré d'une colonne, au format `udt_name` (`TIMESTAMPTZ` → `timestamptz`,
+/// `INTEGER` → `int4`, `TEXT[]` → `_text`, type énuméré → son nom)
+fn udt_name(declaration: &str) -> String {
+    let upper = declaration.trim().to_uppercase();
+    let first = upper.split_whitespace().next().unwrap_or_default();
+    let is_array = first.ends_with("[]");
+    let base = first.split(['(', '[']).next().unwrap_or_default();
+    let name = match base {
+        _ if upper.starts_with("DOUBLE PRECISION") => "float8",
+        _ if upper.starts_with("TIMESTAMP WITH TIME ZONE") => "timestamptz",
+        _ if upper.starts_with("CHARACTER VARYING") => "varchar",
+        "UUID" => "uuid",
+        "TEXT" => "text",
+        "NUMERIC" | "DECIMAL" => "numeric",
+        "BOOLEAN" | "BOOL" => "bool",
+        "INTEGER" | "INT" | "INT4" | "SERIAL" => "int4",
+        "BIGINT" | "INT8" | "BIGSERIAL" => "int8",
+        "SMALLINT" | "INT2" => "int2",
+        "REAL" | "FLOAT4" => "float4",
+        "TIMESTAMPTZ" => "timestamptz",
+        "TIMESTAMP" => "timestamp",
+        "VARCHAR" => "varchar",
+        "JSONB" => "jsonb",
+        "JSON" => "json",
+        "BYTEA" => "bytea",
+        "INET" => "inet",
+        "DATE" => "date",
+        other => return format!("{}{}", if is_array { "_" } else { "" }, ident(other)),
+    };
+    format!("{}{}", if is_array { "_" } else { "" }, name)
+}
+
+/// Éléments du corps d'un `CREATE TABLE`, séparés par les virgules de premier niveau
+fn split_top_level(body: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (i, c) in body.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&body[start..i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    items.push(&body[start..]);
+    items
+}
+
+/// Colonnes déclarées dans le corps d'un `CREATE TABLE` (hors contraintes de table)
+fn parse_columns(body: &str) -> Columns {
+    split_top_level(body)
+        .into_iter()
+        .filter_map(|item| {
+            let item = item.trim();
+            let (name, declaration) = item.split_once(char::is_whitespace)?;
+            let keyword = name.to_uppercase();
+            if matches!(keyword.as_str(), "CONSTRAINT" | "PRIMARY" | "UNIQUE" | "CHECK" | "FOREIGN" | "EXCLUDE") {
+                return None;
+            }
+            Some((ident(name), udt_name(declaration)))
+        })
+        .collect()
+}
+
+/// Analyse les instructions du script utiles à la comparaison
+fn parse_migration(sql: &str) -> DeclaredSchema {
+    let without_comments: String = sql.lines()
+        .map(|line| line.split("--").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut schema = DeclaredSchema::default();
+    for statement in without_comments.split(';') {
+        let statement = statement.trim();
+        let words: Vec<&str> = statement.split_whitespace().collect();
+        let upper: Vec<String> = words.iter().take(6).map(|w| w.to_uppercase()).collect();
+        let upper: Vec<&str> = upper.iter().map(String::as_str).collect();
+        match upper.as_slice() {
+            ["DROP", "TABLE", "IF", "EXISTS", ..] if words.len() > 4 => {
+                schema.dropped_tables.insert(ident(words[4]));
+            },
+            ["DROP", "TYPE", "IF", "EXISTS", ..] if words.len() > 4 => {
+                schema.dropped_types.insert(ident(words[4]));
+            },
+            ["CREATE", "TYPE", ..] if words.len() > 2 => {
+                schema.types.insert(ident(words[2]));
+            },
+            ["ALTER", "TABLE", _, "ADD", "COLUMN", ..] if words.len() > 6 => {
+                let column = (ident(words[5]), udt_name(&words[6..].join(" ")));
+                schema.tables.entry(ident(words[2])).or_default().push(column);
+            },
+            ["CREATE", "TABLE", ..] => {
+                let (Some(open), Some(close)) = (statement.find('('), statement.rfind(')')) else { continue };
+                let name = statement[..open].split_whitespace().last().map(ident).unwrap_or_default();
+                let body = &statement[open + 1..close];
+                let columns = match body.trim().strip_prefix("LIKE ").or_else(|| body.trim().strip_prefix("like ")) {
+                    // Copie d'une table déjà déclarée (archives, corbeille)
+                    Some(like) => {
+                        let source = like.split_whitespace().next().map(ident).unwrap_or_default();
+                        schema.tables.get(&source).cloned().unwrap_or_default()
+                    },
+                    None => parse_columns(body),
+                };
+                schema.tables.entry(name).or_default().splice(0..0, columns);
+            },
+            _ => {},
+        }
+    }
+    schema
+}
+
+/// Tables et colonnes existantes du schéma `public`
+async fn existing_tables(pool: &PgPool) -> Result<BTreeMap<String, Columns>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"SELECT c.table_name::text, c.column_name::text, c.udt_name::text
+           FROM information_schema.columns c
+           JOIN information_schema.tables t
+             ON t.table_schema = c.table_schema AND t.table_name = c.table_name AND t.table_type = 'BASE TABLE'
+           WHERE c.table_schema = 'public'
+           ORDER BY c.table_name, c.ordinal_position"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tables: BTreeMap<String, Columns> = BTreeMap::new();
+    for row in rows {
+        tables.entry(row.get(0)).or_default().push((row.get(1), row.get(2)));
+    }
+    Ok(tables)
+}
+
+/// Types définis par l'utilisateur du schéma `public`
+async fn existing_types(pool: &PgPool) -> Result<BTreeSet<String>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"SELECT t.typname::text FROM pg_type t
+           JOIN pg_namespace n ON n.oid = t.typnamespace
+           WHERE n.nspname = 'public' AND t.typtype = 'e'"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|row| row.get(0)).collect())
+}
+
+/// Résultat de la comparaison : problèmes bloquants et simples informations
+#[derive(Default)]
+struct ProbeReport {
+    blocking: Vec<String>,
+    data_loss: Vec<String>,
+    notes: Vec<String>,
+}
+
+/// Compare la base au schéma déclaré, sans rien modifier
+async fn probe(pool: &PgPool, migration_sql: &str, declared: &DeclaredSchema) -> Result<ProbeReport, sqlx::Error> {
+    let tables = existing_tables(pool).await?;
+    let types = existing_types(pool).await?;
+    let mut report = ProbeReport::default();
+
+    // Objets fournis par Supabase, utilisés par les politiques RLS du script
+    let (has_jwt, has_role): (bool, bool) = sqlx::query_as(
+        r#"SELECT EXISTS (SELECT 1 FROM pg_proc p JOIN pg_namespace n ON n.oid = p.pronamespace
+                          WHERE n.nspname = 'auth' AND p.proname = 'jwt'),
+                  EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'authenticated')"#
+    )
+    .fetch_one(pool)
+    .await?;
+    if migration_sql.contains("auth.jwt()") && !has_jwt {
+        report.blocking.push("fonction auth.jwt() absente : base Supabase requise pour les politiques RLS".to_string());
+    }
+    if migration_sql.contains("TO authenticated") && !has_role {
+        report.blocking.push("rôle authenticated absent : base Supabase requise pour les politiques RLS".to_string());
+    }
+
+    for type_name in declared.types.iter().filter(|t| types.contains(*t) && !declared.dropped_types.contains(*t)) {
+        report.blocking.push(format!(
+            "type {} : existe déjà et n'est pas supprimé par le script (CREATE TYPE échouerait)", type_name
+        ));
+    }
+
+    for (table, columns) in &tables {
+        let Some(expected) = declared.tables.get(table) else {
+            report.notes.push(format!("table {} : absente du script, conservée telle quelle (sauf suppression en cascade)", table));
+            continue;
+        };
+        if !declared.dropped_tables.contains(table) {
+            report.blocking.push(format!(
+                "table {} : existe déjà et n'est pas supprimée par le script (CREATE TABLE échouerait)", table
+            ));
+        }
+
+        // Nom issu d'information_schema, entre guillemets
+        let rows: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM \"{}\"", table.replace('"', "\"\"")))
+            .fetch_one(pool)
+            .await?;
+        if rows > 0 {
+            report.data_loss.push(format!("table {} : {} ligne(s) supprimée(s) avec la table", table, rows));
+        }
+
+        for (column, udt) in columns {
+            match expected.iter().find(|(name, _)| name == column) {
+                None => report.notes.push(format!("{}.{} ({}) : colonne sans équivalent dans le nouveau schéma", table, column, udt)),
+                Some((_, expected_udt)) if expected_udt != udt => report.notes.push(format!(
+                    "{}.{} : type {} en base, {} dans le nouveau schéma", table, column, udt, expected_udt
+                )),
+                Some(_) => {},
+            }
+        }
+        for (column, udt) in expected.iter().filter(|(name, _)| columns.iter().all(|(c, _)| c != name)) {
+            report.notes.push(format!("{}.{} ({}) : colonne absente de la base, créée par le script", table, column, udt));
+        }
+    }
+    Ok(report)
+}
+
+/// Identifiant SQL entre guillemets
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Cellule CSV, entre guillemets si nécessaire. Les valeurs sont écrites telles
+/// quelles (sauvegarde fidèle, pas de neutralisation des formules)
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// Sauvegarde les tables dans un sous-dossier horodaté de `dir` ; renvoie ce sous-dossier
+async fn export(pool: &PgPool, dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let target = dir.join(Utc::now().format("%Y%m%dT%H%M%SZ").to_string());
+    fs::create_dir_all(&target)?;
+    let tables = existing_tables(pool).await?;
+
+    for table in BACKUP_TABLES {
+        let Some(columns) = tables.get(*table) else {
+            println!("  ℹ️  table {} absente de la base : ignorée", table);
+            continue;
+        };
+        let order = if columns.iter().any(|(name, _)| name == "created_at") { "ORDER BY created_at" } else { "" };
+
+        // JSON produit par Postgres : les montants (wei sur 78 chiffres) restent exacts
+        let json: String = sqlx::query_scalar(&format!(
+            "SELECT COALESCE(json_agg(t {}), '[]')::text FROM {} t", order, quoted(table)
+        ))
+        .fetch_one(pool)
+        .await?;
+        fs::write(target.join(format!("{}.json", table)), &json)?;
+
+        let cells = columns.iter().map(|(name, _)| format!("{}::text", quoted(name))).collect::<Vec<_>>().join(", ");
+        let rows: Vec<Vec<Option<String>>> = sqlx::query_scalar(&format!(
+            "SELECT ARRAY[{}] FROM {} {}", cells, quoted(table), order
+        ))
+        .fetch_all(pool)
+        .await?;
+        let mut csv = columns.iter().map(|(name, _)| csv_cell(name)).collect::<Vec<_>>().join(",");
+        csv.push('\n');
+        for row in &rows {
+            csv.push_str(&row.iter().map(|cell| csv_cell(cell.as_deref().unwrap_or_default())).collect::<Vec<_>>().join(","));
+            csv.push('\n');
+        }
+        fs::write(target.join(format!("{}.csv", table)), csv)?;
+
+        println!("  ✅ {} : {} ligne(s)", table, rows.len());
+    }
+    Ok(target)
+}
+
+/// Recharge les fichiers JSON d'une sauvegarde. Les lignes déjà présentes (même
+/// clé) sont conservées ; une erreur annule tout l'import.
+async fn import(pool: &PgPool, dir: &Path) -> Result<(), Box<dyn Error>> {
+    let tables = existing_tables(pool).await?;
+    let generated: BTreeSet<(String, String)> = sqlx::query_as(
+        r#"SELECT table_name::text, column_name::text FROM information_schema.columns
+           WHERE table_schema = 'public' AND is_generated = 'ALWAYS'"#
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut tx = pool.begin().await?;
+    for table in BACKUP_TABLES {
+        let path = dir.join(format!("{}.json", table));
+        if !path.exists() {
+            println!("  ℹ️  {} absent : table ignorée", path.display());
+            continue;
+        }
+        let columns = tables.get(*table)
+            .ok_or_else(|| format!("table {} absente de la base : appliquer la migration avant l'import", table))?;
+        let json = fs::read_to_string(&path)?;
+
+        let keys: BTreeSet<String> = sqlx::query_scalar(
+            "SELECT DISTINCT key FROM json_array_elements($1::json) row, json_object_keys(row) key"
+        )
+        .bind(&json)
+        .fetch_all(&mut tx)
+        .await?
+        .into_iter()
+        .collect();
+        for key in keys.iter().filter(|key| columns.iter().all(|(name, _)| name != *key)) {
+            println!("  ℹ️  {}.{} : colonne absente de la base, ignorée", table, key);
+        }
+
+        // Colonnes absentes de la sauvegarde : valeur par défaut
+        let list = columns.iter()
+            .map(|(name, _)| name)
+            .filter(|name| keys.contains(*name))
+            .filter(|name| !generated.contains(&(table.to_string(), name.to_string())))
+            .filter(|name| !DERIVED_COLUMNS.contains(&(*table, name.as_str())))
+            .map(|name| quoted(name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let total: i32 = sqlx::query_scalar("SELECT json_array_length($1::json)")
+            .bind(&json)
+            .fetch_one(&mut tx)
+            .await?;
+        let inserted = sqlx::query(&format!(
+            "INSERT INTO {table} ({list}) SELECT {list} FROM json_populate_recordset(NULL::{table}, $1::json) ON CONFLICT DO NOTHING",
+            table = quoted(table),
+            list = list
+        ))
+        .bind(&json)
+        .execute(&mut tx)
+        .await
+        .map_err(|e| format!("Import annulé, aucune modification effectuée ({}) : {}", table, e))?
+        .rows_affected();
+
+        println!("  ✅ {} : {} ligne(s) importée(s), {} déjà présente(s)", table, inserted, total as u64 - inserted);
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Valeur d'une option `--nom valeur` ou `--nom=valeur`
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let prefix = format!("{}=", name);
+    args.iter().enumerate().find_map(|(i, arg)| match arg.strip_prefix(&prefix) {
+        Some(value) => Some(value),
+        None if arg == name => args.get(i + 1).map(String::as_str),
+        None => None,
+    })
+}
+
+fn print_report(report: &ProbeReport) {
+    println!("🔎 Comparaison du schéma de la base avec {}", MIGRATION_FILE);
+    for line in &report.blocking {
+        println!("  ❌ {}", line);
+    }
+    for line in &report.data_loss {
+        println!("  ⚠️  {}", line);
+    }
+    for line in &report.notes {
+        println!("  ℹ️  {}", line);
+    }
+    if report.blocking.is_empty() && report.data_loss.is_empty() && report.notes.is_empty() {
+        println!("  ✅ Base vide : rien à signaler");
+    }
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn Error>> {
     // Charger les variables d'environnement
     dotenv().ok();
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    let check_only = args.iter().any(|a| a == "--check");
+    let force = args.iter().any(|a| a == "--force");
+
     // Récupérer l'URL de la base de données
     let database_url = env::var("DATABASE_URL")
         .expect("DATABASE_URL doit être définie dans le fichier .env");
@@ -16,23 +433,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Connexion à la base de données
     let pool = PgPool::connect(&database_url).await?;
 
-    println!("🔄 Exécution de la migration vers Supabase...");
+    // Sauvegarde ou restauration logique, sans migration
+    if let Some(dir) = option_value(&args, "--export-dir") {
+        println!("💾 Sauvegarde des données...");
+        let target = export(&pool, Path::new(dir)).await?;
+        println!("✅ Sauvegarde écrite dans {}", target.display());
+        return Ok(());
+    }
+    if let Some(dir) = option_value(&args, "--import") {
+        println!("📥 Import de la sauvegarde {}...", dir);
+        import(&pool, Path::new(dir)).await?;
+        println!("✅ Import terminé avec succès!");
+        return Ok(());
+    }
 
     // Lire le fichier de migration
-    let migration_sql = fs::read_to_string("migrations/supabase_migration.sql")
+    let migration_sql = fs::read_to_string(MIGRATION_FILE)
         .expect("Impossible de lire le fichier migrations/supabase_migration.sql");
 
-    // Exécuter chaque instruction SQL séparément
-    for statement in migration_sql.split(';') {
-        let trimmed = statement.trim();
-        if !trimmed.is_empty() {
-            match sqlx::query(trimmed).execute(&pool).await {
-                Ok(_) => println!("✅ Instruction SQL exécutée avec succès"),
-                Err(e) => println!("❌ Erreur lors de l'exécution de l'instruction SQL: {}", e),
-            }
-        }
+    // Vérification du schéma avant toute écriture
+    let report = probe(&pool, &migration_sql, &parse_migration(&migration_sql)).await?;
+    print_report(&report);
+    if check_only {
+        return Ok(());
+    }
+    if !report.blocking.is_empty() {
+        return Err("Migration annulée : schéma incompatible avec le script, aucune modification effectuée".into());
+    }
+    if !report.data_loss.is_empty() && !force {
+        return Err("Migration annulée : des données seraient supprimées. Sauvegarder avec --export-dir, puis relancer avec --force pour l'appliquer quand même".into());
+    }
+
+    println!("🔄 Exécution de la migration vers Supabase...");
+
+    // Script exécuté d'un bloc (corps de fonctions `$$ ... $$` compris) : tout ou rien
+    let mut tx = pool.begin().await?;
+    if let Err(e) = tx.execute(migration_sql.as_str()).await {
+        tx.rollback().await?;
+        return Err(format!("Migration annulée, aucune modification effectuée : {}", e).into());
     }
+    tx.commit().await?;
 
     println!("✅ Migration terminée avec succès!");
     Ok(())
-} 
\ No newline at end of file
+}
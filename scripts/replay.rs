@@ -0,0 +1,231 @@
+// scripts/replay.rs
+//
+// Rejoue un dossier de fixtures capturées par l'API (voir src/fixtures.rs)
+// contre une base de test, et compare chaque réponse à celle enregistrée :
+// des tests de non-régression tirés du trafic réel. L'API est lancée par le
+// script sur la base `REPLAY_DATABASE_URL` (distincte de `DATABASE_URL`),
+// remise à neuf avec `--reset`. Les fixtures sont rejouées dans l'ordre de
+// capture ; les identifiants créés pendant le rejeu remplacent ceux de la
+// capture dans les requêtes suivantes. Les horodatages et les valeurs
+// expurgées ne sont pas comparés, les fixtures `replayable: false` sont
+// ignorées. Pour un scénario complet, capturer depuis une base neuve.
+//
+// Usage : REPLAY_DATABASE_URL=postgres://.../pa_test cargo run --bin replay -- <dossier> [--reset]
+//   --reset : applique migrations/supabase_migration.sql à la base de test (tables supprimées puis recréées)
+//   --api-bin <chemin> : binaire de l'API (target/debug/my-api par défaut)
+//   --port <port> : port d'écoute de l'API lancée (3999 par défaut)
+
+use chrono::DateTime;
+use dotenvy::dotenv;
+use serde_json::Value;
+use sqlx::{Executor, PgPool};
+use std::collections::HashMap;
+use std::error::Error;
+use std::process::Stdio;
+use std::time::Duration;
+use std::{env, fs};
+use uuid::Uuid;
+
+const MIGRATION_FILE: &str = "migrations/supabase_migration.sql";
+
+/// Écarts affichés par fixture
+const MAX_DIFFERENCES: usize = 10;
+
+/// Attente maximale du démarrage de l'API
+const STARTUP_TIMEOUT_SECS: u64 = 30;
+
+/// Valeur des éléments expurgés à la capture
+const REDACTED: &str = "[expurgé]";
+
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let prefix = format!("{}=", name);
+    args.iter().enumerate().find_map(|(i, arg)| match arg.strip_prefix(&prefix) {
+        Some(value) => Some(value),
+        None if arg == name => args.get(i + 1).map(String::as_str),
+        None => None,
+    })
+}
+
+/// Remplace les identifiants de la capture par ceux créés pendant le rejeu
+fn substitute(text: &str, ids: &HashMap<String, String>) -> String {
+    ids.iter().fold(text.to_string(), |text, (captured, replayed)| text.replace(captured, replayed))
+}
+
+/// Compare la réponse enregistrée à celle du rejeu. Un identifiant inconnu de
+/// la capture est associé à celui du rejeu ; les horodatages et les valeurs
+/// expurgées acceptent toute chaîne.
+fn compare(path: &str, expected: &Value, actual: &Value, ids: &mut HashMap<String, String>, out: &mut Vec<String>) {
+    if out.len() >= MAX_DIFFERENCES {
+        return;
+    }
+    match (expected, actual) {
+        (Value::String(e), Value::String(_)) if e == REDACTED || DateTime::parse_from_rfc3339(e).is_ok() => {},
+        (Value::String(e), Value::String(a)) if Uuid::parse_str(e).is_ok() && Uuid::parse_str(a).is_ok() => {
+            match ids.get(e) {
+                Some(replayed) if replayed != a => out.push(format!("{} : identifiant {} attendu (capturé {}), reçu {}", path, replayed, e, a)),
+                Some(_) => {},
+                None => {
+                    ids.insert(e.clone(), a.clone());
+                },
+            }
+        },
+        (Value::Object(e), Value::Object(a)) => {
+            for key in e.keys().chain(a.keys().filter(|key| !e.contains_key(*key))) {
+                let path = format!("{}.{}", path, key);
+                compare(&path, e.get(key).unwrap_or(&Value::Null), a.get(key).unwrap_or(&Value::Null), ids, out);
+            }
+        },
+        (Value::Array(e), Value::Array(a)) if e.len() != a.len() => {
+            out.push(format!("{}.length : {} attendu, reçu {}", path, e.len(), a.len()));
+        },
+        (Value::Array(e), Value::Array(a)) => {
+            for (i, (e, a)) in e.iter().zip(a).enumerate() {
+                compare(&format!("{}[{}]", path, i), e, a, ids, out);
+            }
+        },
+        _ if expected != actual => out.push(format!("{} : {} attendu, reçu {}", path, expected, actual)),
+        _ => {},
+    }
+}
+
+/// Rejoue une fixture ; renvoie les écarts constatés
+async fn replay(
+    client: &reqwest::Client,
+    base_url: &str,
+    fixture: &Value,
+    ids: &mut HashMap<String, String>,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let method = reqwest::Method::from_bytes(fixture["method"].as_str().unwrap_or("GET").as_bytes())?;
+    let path = substitute(fixture["path"].as_str().unwrap_or("/"), ids);
+    let mut request = client.request(method, format!("{}{}", base_url, path));
+    for (name, value) in fixture["headers"].as_object().into_iter().flatten() {
+        if let Some(value) = value.as_str() {
+            request = request.header(name.as_str(), substitute(value, ids));
+        }
+    }
+    if !fixture["request"].is_null() {
+        request = request.body(substitute(&fixture["request"].to_string(), ids));
+    }
+
+    let response = request.send().await?;
+    let status = response.status().as_u16();
+    let body = response.bytes().await?;
+    let actual = serde_json::from_slice::<Value>(&body).unwrap_or(Value::Null);
+
+    let mut differences = Vec::new();
+    let expected_status = fixture["status"].as_u64().unwrap_or_default();
+    if u64::from(status) != expected_status {
+        differences.push(format!("statut : {} attendu, reçu {}", expected_status, status));
+    }
+    if !fixture["response"].is_null() {
+        compare("$", &fixture["response"], &actual, ids, &mut differences);
+    }
+    Ok(differences)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv().ok();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let dir = args.first().filter(|arg| !arg.starts_with("--"))
+        .expect("Usage : replay <dossier de fixtures> [--reset] [--api-bin <chemin>] [--port <port>]");
+    let reset = args.iter().any(|a| a == "--reset");
+    let api_bin = option_value(&args, "--api-bin").unwrap_or("target/debug/my-api");
+    let port = option_value(&args, "--port").unwrap_or("3999");
+
+    let database_url = env::var("REPLAY_DATABASE_URL")
+        .expect("REPLAY_DATABASE_URL doit désigner la base de test du rejeu");
+    if env::var("DATABASE_URL").is_ok_and(|url| url == database_url) {
+        return Err("REPLAY_DATABASE_URL doit être distincte de DATABASE_URL : le rejeu écrit dans la base".into());
+    }
+
+    // Fixtures dans l'ordre de capture (noms numérotés)
+    let mut files: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("Aucune fixture dans {}", dir).into());
+    }
+
+    if reset {
+        println!("🧹 Remise à neuf de la base de test...");
+        let migration_sql = fs::read_to_string(MIGRATION_FILE)
+            .expect("Impossible de lire le fichier migrations/supabase_migration.sql");
+        let pool = PgPool::connect(&database_url).await?;
+        let mut tx = pool.begin().await?;
+        tx.execute(migration_sql.as_str()).await?;
+        tx.commit().await?;
+        pool.close().await;
+    }
+
+    // API lancée sur la base de test, sans capture ni fautes injectées (valeurs
+    // vides plutôt que supprimées : le fichier .env ne les redéfinit pas)
+    let log_path = env::temp_dir().join("replay-api.log");
+    let mut api = tokio::process::Command::new(api_bin)
+        .env("DATABASE_URL", &database_url)
+        .env("PORT", port)
+        .env("FIXTURE_CAPTURE_DIR", "")
+        .env("CHAOS_ENABLED", "false")
+        .env("DUAL_WRITE_DATABASE_URL", "")
+        .env("SHADOW_READS_ENDPOINTS", "")
+        .stdout(Stdio::from(fs::File::create(&log_path)?))
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Lancement de {} impossible (cargo build d'abord ?) : {}", api_bin, e))?;
+    let base_url = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?;
+
+    let mut ready = false;
+    for _ in 0..STARTUP_TIMEOUT_SECS * 4 {
+        if client.get(format!("{}/health/ready", base_url)).send().await.is_ok_and(|r| r.status().is_success()) {
+            ready = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    if !ready {
+        api.kill().await.ok();
+        return Err(format!("L'API n'a pas démarré (journal : {})", log_path.display()).into());
+    }
+
+    println!("🔁 Rejeu de {} fixture(s) de {}", files.len(), dir);
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    let mut ids = HashMap::new();
+    for file in &files {
+        let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let fixture: Value = serde_json::from_str(&fs::read_to_string(file)?)?;
+        if fixture["replayable"] == Value::Bool(false) {
+            skipped += 1;
+            println!("  ⏭️  {} (éléments expurgés à la capture)", name);
+            continue;
+        }
+        match replay(&client, &base_url, &fixture, &mut ids).await {
+            Ok(differences) if differences.is_empty() => {
+                passed += 1;
+                println!("  ✅ {}", name);
+            },
+            Ok(differences) => {
+                failed += 1;
+                println!("  ❌ {}", name);
+                for difference in differences {
+                    println!("       {}", difference);
+                }
+            },
+            Err(e) => {
+                failed += 1;
+                println!("  ❌ {} : {}", name, e);
+            },
+        }
+    }
+    api.kill().await.ok();
+
+    println!("\n{} identique(s), {} différente(s), {} ignorée(s)", passed, failed, skipped);
+    if failed > 0 {
+        return Err(format!("{} réponse(s) différente(s) de la capture", failed).into());
+    }
+    Ok(())
+}
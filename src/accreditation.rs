@@ -0,0 +1,349 @@
+// accreditation.rs
+//
+// Accréditation des investisseurs qualifiés : l'utilisateur envoie ses
+// justificatifs dans le bucket privé, un admin les examine et fixe une date
+// d'échéance. Les propriétés marquées `requires_accreditation` n'acceptent que
+// les investisseurs dont l'accréditation est approuvée et non échue.
+
+use axum::{
+    extract::{BodyStream, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{
+    Accreditation, AccreditationListQuery, AccreditationStatus, ReviewAccreditationRequest, UploadQuery, UserRole,
+};
+use crate::notifications::{notify, notify_tenant_admins};
+use crate::storage::StorageConfig;
+use crate::upload::{self, DOCUMENT_POLICY};
+
+/// Durée de validité d'une approbation sans échéance explicite
+/// (`ACCREDITATION_VALIDITY_DAYS`, 365 jours par défaut)
+fn validity_days() -> i64 {
+    env::var("ACCREDITATION_VALIDITY_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(365)
+}
+
+/// Accréditation d'un utilisateur, limitée à une plateforme si `tenant_id` est
+/// renseigné (consultation par un admin)
+async fn find_accreditation(pool: &PgPool, user_id: Uuid, tenant_id: Option<Uuid>) -> Result<Option<Accreditation>, sqlx::Error> {
+    sqlx::query_as!(
+        Accreditation,
+        r#"SELECT user_id,
+                  CASE WHEN status = 'approved' AND expires_at <= NOW() THEN 'expired' ELSE status END
+                      as "status!: AccreditationStatus",
+                  documents, submitted_at, reviewed_by, reviewed_at, review_note, expires_at, updated_at
+           FROM accreditations
+           WHERE user_id = $1 AND ($2::uuid IS NULL OR user_id IN (SELECT id FROM users WHERE tenant_id = $2))"#,
+        user_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Refuse (403) l'investissement dans une offre réservée tant que
+/// l'accréditation de l'utilisateur n'est pas approuvée et en cours de validité
+pub async fn ensure_accredited(pool: &PgPool, user_id: Uuid) -> Result<(), Response> {
+    let accreditation = find_accreditation(pool, user_id, None)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification de l'accréditation: {}", e)
+        }))).into_response())?;
+
+    match accreditation.map(|a| a.status) {
+        Some(AccreditationStatus::Approved) => Ok(()),
+        status => Err((StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Cette offre est réservée aux investisseurs accrédités",
+            "accreditation_status": status
+        }))).into_response()),
+    }
+}
+
+/// Route pour consulter son accréditation (`null` si aucune demande)
+pub async fn get_my_accreditation(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match find_accreditation(&pool, user.id, None).await {
+        Ok(accreditation) => (StatusCode::OK, Json(serde_json::json!({
+            "accreditation": accreditation
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour envoyer un justificatif d'accréditation (corps brut, `?filename=` requis).
+/// Le premier envoi ouvre une demande (ou une nouvelle après un refus ou une
+/// échéance), les suivants complètent la demande en attente.
+pub async fn upload_accreditation_document(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+    Query(query): Query<UploadQuery>,
+    headers: HeaderMap,
+    body: BodyStream,
+) -> impl IntoResponse {
+    let storage = match storage {
+        Some(storage) => storage,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Stockage des documents non configuré"
+        }))).into_response(),
+    };
+
+    // Inutile de recevoir le fichier si l'accréditation est déjà valide
+    match find_accreditation(&pool, user.id, None).await {
+        Ok(Some(accreditation)) if accreditation.status == AccreditationStatus::Approved => {
+            return (StatusCode::CONFLICT, Json(serde_json::json!({
+                "error": "Votre accréditation est déjà valide",
+                "expires_at": accreditation.expires_at
+            }))).into_response();
+        },
+        Ok(_) => {},
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    }
+
+    let file = match upload::receive(&DOCUMENT_POLICY, &query.filename, &headers, body).await {
+        Ok(file) => file,
+        Err(rejection) => return rejection.into_response(),
+    };
+    let key = format!("users/{}/accreditation/{}-{}", user.id, Uuid::new_v4(), file.file_name);
+    if let Err(e) = storage.put_document(&key, &file.path, file.content_type).await {
+        return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({
+            "error": format!("Erreur lors du dépôt du fichier: {}", e)
+        }))).into_response();
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let previous = sqlx::query_scalar!(
+            r#"SELECT status as "status: AccreditationStatus" FROM accreditations WHERE user_id = $1 FOR UPDATE"#,
+            user.id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let accreditation = sqlx::query_as!(
+            Accreditation,
+            r#"INSERT INTO accreditations (user_id, documents) VALUES ($1, ARRAY[$2])
+               ON CONFLICT (user_id) DO UPDATE SET
+                   documents = CASE WHEN accreditations.status = 'pending'
+                       THEN array_append(accreditations.documents, $2) ELSE ARRAY[$2] END,
+                   submitted_at = CASE WHEN accreditations.status = 'pending'
+                       THEN accreditations.submitted_at ELSE NOW() END,
+                   status = 'pending', reviewed_by = NULL, reviewed_at = NULL, review_note = NULL,
+                   expires_at = NULL, updated_at = NOW()
+               WHERE NOT (accreditations.status = 'approved' AND accreditations.expires_at > NOW())
+               RETURNING user_id, status as "status: AccreditationStatus", documents, submitted_at,
+                         reviewed_by, reviewed_at, review_note, expires_at, updated_at"#,
+            user.id,
+            key
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let accreditation = match accreditation {
+            Some(accreditation) => accreditation,
+            None => return Ok(None),
+        };
+        if previous != Some(AccreditationStatus::Pending) {
+            notify_tenant_admins(&mut tx, user.tenant_id, None, "accreditation_submitted", "Nouvelle demande d'accréditation à examiner", serde_json::json!({
+                "user_id": user.id
+            }))
+            .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(accreditation))
+    }
+    .await;
+
+    match result {
+        Ok(Some(accreditation)) => (StatusCode::CREATED, Json(serde_json::json!({
+            "accreditation": accreditation,
+            "document": {
+                "key": key,
+                "content_type": file.content_type,
+                "size": file.size
+            },
+            "message": "Justificatif ajouté, demande en attente de revue"
+        }))).into_response(),
+        Ok(None) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Votre accréditation est déjà valide"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : demandes d'accréditation de la plateforme (en attente d'abord,
+/// `?status=` pour filtrer)
+pub async fn get_accreditations(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<AccreditationListQuery>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent consulter les accréditations"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        Accreditation,
+        r#"SELECT user_id, status as "status!: AccreditationStatus", documents, submitted_at,
+                  reviewed_by, reviewed_at, review_note, expires_at, updated_at
+           FROM (SELECT user_id,
+                        CASE WHEN status = 'approved' AND expires_at <= NOW() THEN 'expired' ELSE status END as status,
+                        documents, submitted_at, reviewed_by, reviewed_at, review_note, expires_at, updated_at
+                 FROM accreditations
+                 WHERE user_id IN (SELECT id FROM users WHERE tenant_id = $2)) a
+           WHERE ($1::accreditation_status IS NULL OR status = $1)
+           ORDER BY status = 'pending' DESC, submitted_at DESC"#,
+        query.status as Option<AccreditationStatus>,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(accreditations) => envelope::list("accreditations", &accreditations).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : URL signée d'un justificatif (`doc_id` : index dans `documents`)
+pub async fn download_accreditation_document(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+    Path((user_id, doc_id)): Path<(Uuid, usize)>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent consulter les justificatifs d'accréditation"
+        }))).into_response();
+    }
+    let storage = match storage {
+        Some(storage) => storage,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Stockage des documents non configuré"
+        }))).into_response(),
+    };
+
+    let key = match find_accreditation(&pool, user_id, Some(user.tenant_id)).await {
+        Ok(accreditation) => match accreditation.and_then(|a| a.documents.into_iter().nth(doc_id)) {
+            Some(key) => key,
+            None => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Justificatif non trouvé"
+            }))).into_response(),
+        },
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    };
+
+    let now = Utc::now();
+    (StatusCode::OK, Json(serde_json::json!({
+        "url": storage.presign_get(&key, now),
+        "expires_at": now + Duration::seconds(storage.url_ttl_secs as i64),
+        "expires_in": storage.url_ttl_secs
+    }))).into_response()
+}
+
+/// Route admin : approuver (avec une échéance) ou refuser une accréditation.
+/// Une accréditation approuvée peut aussi être révoquée en la refusant.
+pub async fn review_accreditation(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<ReviewAccreditationRequest>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent examiner les accréditations"
+        }))).into_response();
+    }
+
+    let now = Utc::now();
+    let expires_at = match payload.status {
+        AccreditationStatus::Approved => match payload.expires_at {
+            Some(expires_at) if expires_at <= now => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "error": "La date d'échéance doit être dans le futur"
+            }))).into_response(),
+            Some(expires_at) => Some(expires_at),
+            None => Some(now + Duration::days(validity_days())),
+        },
+        AccreditationStatus::Rejected => None,
+        _ => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Statut de revue invalide (approved ou rejected)"
+        }))).into_response(),
+    };
+    let note = payload.note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let accreditation = sqlx::query_as!(
+            Accreditation,
+            r#"UPDATE accreditations
+               SET status = $2, expires_at = $3, review_note = $4, reviewed_by = $5, reviewed_at = NOW(),
+                   updated_at = NOW()
+               WHERE user_id = $1 AND user_id IN (SELECT id FROM users WHERE tenant_id = $6)
+               RETURNING user_id, status as "status: AccreditationStatus", documents, submitted_at,
+                         reviewed_by, reviewed_at, review_note, expires_at, updated_at"#,
+            user_id,
+            payload.status as AccreditationStatus,
+            expires_at,
+            note,
+            user.id,
+            user.tenant_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        if let Some(accreditation) = &accreditation {
+            let (kind, message) = if accreditation.status == AccreditationStatus::Approved {
+                ("accreditation_approved", "Votre accréditation d'investisseur qualifié a été approuvée")
+            } else {
+                ("accreditation_rejected", "Votre demande d'accréditation a été refusée")
+            };
+            notify(&mut tx, user_id, kind, message, serde_json::json!({
+                "expires_at": accreditation.expires_at,
+                "note": accreditation.review_note
+            }))
+            .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(accreditation)
+    }
+    .await;
+
+    match result {
+        Ok(Some(accreditation)) => (StatusCode::OK, Json(serde_json::json!({
+            "accreditation": accreditation,
+            "message": "Accréditation mise à jour"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Aucune demande d'accréditation pour cet utilisateur"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,156 @@
+// activity.rs
+//
+// Chronologie d'activité d'un utilisateur, pour le support et la transparence :
+// connexions et modifications du profil enregistrées dans `user_events`, plus
+// les investissements, les propriétés créées et les soumissions lus dans leurs
+// propres tables. Les pages vont du plus récent au plus ancien, triées par
+// date puis identifiant ; le curseur (voir pagination.rs) porte les deux, pour
+// que des événements simultanés ne soient ni répétés ni sautés.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{ActivityQuery, UserRole};
+use crate::pagination::{Cursor, Pagination};
+
+/// Enregistre un événement du compte (`actor_id` : auteur s'il n'est pas l'utilisateur)
+pub async fn record<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    user_id: Uuid,
+    actor_id: Option<Uuid>,
+    kind: &str,
+    data: Value,
+    ip: Option<String>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO user_events (user_id, actor_id, kind, data, ip) VALUES ($1, $2, $3, $4, $5)",
+        user_id,
+        actor_id,
+        kind,
+        data,
+        ip
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Enregistre un événement hors transaction, sans faire échouer la requête
+pub fn record_detached(pool: PgPool, user_id: Uuid, actor_id: Option<Uuid>, kind: &'static str, data: Value, ip: Option<String>) {
+    tokio::spawn(async move {
+        if let Err(e) = record(&pool, user_id, actor_id, kind, data, ip).await {
+            tracing::error!("Erreur lors de l'enregistrement de l'activité {}: {}", kind, e);
+        }
+    });
+}
+
+async fn timeline(pool: &PgPool, user_id: Uuid, query: ActivityQuery, pagination: Pagination) -> Response {
+    let (after_at, after_id) = pagination.cursor_bounds();
+
+    match sqlx::query!(
+        r#"SELECT id as "id!", kind as "kind!", occurred_at as "occurred_at!", actor_id, ip, data as "data!"
+           FROM (
+               SELECT e.id, e.kind, e.created_at as occurred_at, e.actor_id, e.ip, e.data
+               FROM user_events e
+               WHERE e.user_id = $1
+               UNION ALL
+               SELECT i.id, 'investment_created', i.created_at, NULL::uuid, NULL::text,
+                      jsonb_build_object('investment_id', i.id, 'property_id', i.property_id, 'property_name', p.name,
+                                         'amount_eth', i.amount_eth, 'shares', i.shares, 'status', i.status)
+               FROM investments i
+               JOIN properties p ON p.id = i.property_id
+               WHERE i.user_id = $1
+               UNION ALL
+               SELECT p.id, 'property_created', p.created_at, NULL::uuid, NULL::text,
+                      jsonb_build_object('property_id', p.id, 'name', p.name)
+               FROM properties p
+               WHERE p.created_by = $1
+               UNION ALL
+               SELECT s.id, 'property_submitted', s.created_at, NULL::uuid, NULL::text,
+                      jsonb_build_object('property_id', p.id, 'name', p.name)
+               FROM property_status_events s
+               JOIN properties p ON p.id = s.property_id
+               WHERE s.changed_by = $1 AND s.status = 'pending'
+           ) activity
+           WHERE ($2::text IS NULL OR kind = $2)
+           AND ($3::timestamptz IS NULL OR (occurred_at, id) < ($3, $4))
+           ORDER BY occurred_at DESC, id DESC
+           LIMIT $5 OFFSET $6"#,
+        user_id,
+        query.kind,
+        after_at,
+        after_id,
+        pagination.limit(),
+        pagination.offset()
+    )
+    .fetch_all(pool)
+    .await {
+        Ok(mut rows) => {
+            let page = pagination.finish(&mut rows, |row| Cursor::new(row.occurred_at, row.id));
+            let activity = rows.into_iter().map(|row| serde_json::json!({
+                "kind": row.kind,
+                "occurred_at": row.occurred_at,
+                "actor_id": row.actor_id,
+                "ip": row.ip,
+                "data": row.data
+            })).collect::<Vec<_>>();
+            envelope::list("activity", &activity)
+                .paginated(page)
+                .with("user_id", user_id)
+                .into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération de l'activité: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : sa propre chronologie d'activité
+pub async fn get_my_activity(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<ActivityQuery>,
+    pagination: Pagination,
+) -> impl IntoResponse {
+    timeline(&pool, user.id, query, pagination).await
+}
+
+/// Route admin : chronologie d'activité d'un utilisateur de la plateforme
+pub async fn get_user_activity(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<ActivityQuery>,
+    pagination: Pagination,
+) -> impl IntoResponse {
+    if !matches!(admin.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut consulter l'activité des utilisateurs"
+        }))).into_response();
+    }
+
+    match sqlx::query_scalar!(
+        "SELECT id FROM users WHERE id = $1 AND tenant_id = $2",
+        user_id,
+        admin.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(_)) => timeline(&pool, user_id, query, pagination).await,
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Utilisateur non trouvé"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    }
+}
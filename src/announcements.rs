@@ -0,0 +1,291 @@
+// announcements.rs
+//
+// Bannières d'annonce (maintenance, nouvelles offres) gérées par les admins de
+// la plateforme. Chaque annonce cible une audience (`all`, `investors` : rôle
+// user, `managers`) et s'affiche entre `starts_at` et `ends_at` ; un
+// utilisateur peut la masquer, elle disparaît alors de `GET /api/me/announcements`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{Announcement, AnnouncementListQuery, CreateAnnouncementRequest, UpdateAnnouncementRequest, UserRole};
+
+pub const AUDIENCES: &[&str] = &["all", "investors", "managers"];
+
+/// Annonce avec son nombre de masquages (vue admin)
+#[derive(Debug, Serialize)]
+pub struct AnnouncementSummary {
+    pub id: Uuid,
+    pub title: String,
+    pub body: String,
+    pub audience: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub dismissals: i64,
+}
+
+/// Audiences dont un rôle voit les annonces (toutes pour l'admin)
+fn audiences_for(role: &UserRole) -> Vec<String> {
+    let audiences: &[&str] = match role {
+        UserRole::User => &["all", "investors"],
+        UserRole::Manager => &["all", "managers"],
+        UserRole::Admin => AUDIENCES,
+    };
+    audiences.iter().map(|a| a.to_string()).collect()
+}
+
+fn admin_only(role: &UserRole) -> Option<Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer les annonces"
+    }))).into_response())
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+        "error": message
+    }))).into_response()
+}
+
+fn is_check_violation(e: &sqlx::Error) -> bool {
+    e.as_database_error().and_then(|e| e.code()).as_deref() == Some("23514")
+}
+
+fn announcement_error(e: sqlx::Error) -> Response {
+    if is_check_violation(&e) {
+        return bad_request("La date de fin doit être postérieure à la date de début");
+    }
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de l'enregistrement de l'annonce: {}", e)
+    }))).into_response()
+}
+
+/// Route : annonces en cours destinées à l'utilisateur et non masquées
+pub async fn get_my_announcements(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match sqlx::query!(
+        r#"SELECT a.id, a.title, a.body, a.audience, a.starts_at, a.ends_at FROM announcements a
+           WHERE a.tenant_id = $1
+           AND a.audience = ANY($2)
+           AND a.starts_at <= NOW() AND (a.ends_at IS NULL OR a.ends_at > NOW())
+           AND NOT EXISTS (SELECT 1 FROM announcement_dismissals d WHERE d.announcement_id = a.id AND d.user_id = $3)
+           ORDER BY a.starts_at DESC"#,
+        user.tenant_id,
+        &audiences_for(&user.role),
+        user.id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => {
+            let announcements = rows.into_iter().map(|row| serde_json::json!({
+                "id": row.id,
+                "title": row.title,
+                "body": row.body,
+                "audience": row.audience,
+                "starts_at": row.starts_at,
+                "ends_at": row.ends_at
+            })).collect::<Vec<_>>();
+            envelope::list("announcements", &announcements).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : masquer une annonce (sans effet si déjà masquée)
+pub async fn dismiss_announcement(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match sqlx::query_scalar!(
+        r#"INSERT INTO announcement_dismissals (announcement_id, user_id)
+           SELECT id, $2 FROM announcements WHERE id = $1 AND tenant_id = $3 AND audience = ANY($4)
+           ON CONFLICT (announcement_id, user_id) DO UPDATE SET dismissed_at = announcement_dismissals.dismissed_at
+           RETURNING dismissed_at"#,
+        id,
+        user.id,
+        user.tenant_id,
+        &audiences_for(&user.role)
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(dismissed_at)) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Annonce masquée",
+            "dismissed_at": dismissed_at
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Annonce non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : annonces de la plateforme avec leur nombre de masquages
+/// (`?active=true` : seulement celles en cours)
+pub async fn get_announcements(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<AnnouncementListQuery>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        AnnouncementSummary,
+        r#"SELECT a.id, a.title, a.body, a.audience, a.starts_at, a.ends_at, a.created_by, a.created_at, a.updated_at,
+                  COUNT(d.user_id) as "dismissals!"
+           FROM announcements a
+           LEFT JOIN announcement_dismissals d ON d.announcement_id = a.id
+           WHERE a.tenant_id = $1
+           AND (NOT COALESCE($2, FALSE) OR (a.starts_at <= NOW() AND (a.ends_at IS NULL OR a.ends_at > NOW())))
+           GROUP BY a.id
+           ORDER BY a.starts_at DESC"#,
+        admin.tenant_id,
+        query.active
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(announcements) => envelope::list("announcements", &announcements).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : publier une annonce
+pub async fn create_announcement(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateAnnouncementRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+    let audience = payload.audience.unwrap_or_else(|| "all".to_string());
+    if !AUDIENCES.contains(&audience.as_str()) {
+        return bad_request("Audience invalide (all, investors, managers)");
+    }
+    if payload.title.trim().is_empty() {
+        return bad_request("Le titre est requis");
+    }
+
+    match sqlx::query_as!(
+        Announcement,
+        r#"INSERT INTO announcements (tenant_id, title, body, audience, starts_at, ends_at, created_by)
+           VALUES ($1, $2, $3, $4, COALESCE($5, NOW()), $6, $7)
+           RETURNING *"#,
+        admin.tenant_id,
+        payload.title.trim(),
+        payload.body,
+        audience,
+        payload.starts_at,
+        payload.ends_at,
+        admin.id
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(announcement) => (StatusCode::CREATED, Json(serde_json::json!({
+            "announcement": announcement,
+            "message": "Annonce créée"
+        }))).into_response(),
+        Err(e) => announcement_error(e),
+    }
+}
+
+/// Route admin : modifier une annonce ; les champs absents restent inchangés
+/// (`ends_at` à maintenant pour la retirer immédiatement)
+pub async fn update_announcement(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateAnnouncementRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+    if payload.audience.as_deref().is_some_and(|audience| !AUDIENCES.contains(&audience)) {
+        return bad_request("Audience invalide (all, investors, managers)");
+    }
+    if payload.title.as_deref().is_some_and(|title| title.trim().is_empty()) {
+        return bad_request("Le titre est requis");
+    }
+
+    match sqlx::query_as!(
+        Announcement,
+        r#"UPDATE announcements SET
+               title = COALESCE($3, title),
+               body = COALESCE($4, body),
+               audience = COALESCE($5, audience),
+               starts_at = COALESCE($6, starts_at),
+               ends_at = COALESCE($7, ends_at),
+               updated_at = NOW()
+           WHERE id = $1 AND tenant_id = $2
+           RETURNING *"#,
+        id,
+        admin.tenant_id,
+        payload.title.as_deref().map(str::trim),
+        payload.body,
+        payload.audience,
+        payload.starts_at,
+        payload.ends_at
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(announcement)) => (StatusCode::OK, Json(serde_json::json!({
+            "announcement": announcement,
+            "message": "Annonce mise à jour"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Annonce non trouvée"
+        }))).into_response(),
+        Err(e) => announcement_error(e),
+    }
+}
+
+/// Route admin : supprimer une annonce (et ses masquages)
+pub async fn delete_announcement(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    match sqlx::query!(
+        "DELETE FROM announcements WHERE id = $1 AND tenant_id = $2",
+        id,
+        admin.tenant_id
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Annonce non trouvée"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Annonce supprimée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
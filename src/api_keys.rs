@@ -0,0 +1,291 @@
+// api_keys.rs
+
+use axum::{
+    extract::{FromRequestParts, Path, State},
+    http::{request::Parts, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::db::DbHealth;
+use crate::envelope;
+use crate::models::{ApiKey, CreateApiKeyRequest, Tenant, UpdateApiKeyRequest, UserRole};
+
+// Préfixe des clés générées, utile pour les repérer dans les logs ou un dépôt de code
+const KEY_PREFIX: &str = "pak_";
+
+/// Hash SHA-256 d'une clé : seule cette empreinte est stockée en base
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Génère une nouvelle clé aléatoire (32 octets encodés en hexadécimal)
+fn generate_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", KEY_PREFIX, hex::encode(bytes))
+}
+
+/// Extracteur d'accès partenaire via le header `X-API-Key`, valable sur la
+/// seule plateforme de la clé. Chaque appel est comptabilisé et refusé (429)
+/// au-delà du quota journalier.
+pub struct ApiKeyAuth(pub Uuid);
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for ApiKeyAuth
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let key = parts.headers
+            .get("X-API-Key")
+            .ok_or((StatusCode::UNAUTHORIZED, "Header X-API-Key manquant"))?
+            .to_str()
+            .map_err(|_| (StatusCode::UNAUTHORIZED, "Header X-API-Key invalide"))?
+            .trim()
+            .to_string();
+
+        let pool = parts.extensions
+            .get::<PgPool>()
+            .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Pool manquant"))?
+            .clone();
+
+        let tenant_id = parts.extensions
+            .get::<Tenant>()
+            .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Plateforme non résolue"))?
+            .id;
+
+        // Une seule connexion pour la vérification et le décompte, dont
+        // l'attente est mesurée (voir db.rs)
+        let mut conn = parts.extensions
+            .get::<DbHealth>()
+            .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "État de la base manquant"))?
+            .acquire(&pool)
+            .await
+            .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Erreur de base de données"))?;
+
+        // Une clé d'une autre plateforme est traitée comme inconnue
+        let api_key = sqlx::query!(
+            "SELECT id, daily_quota, is_active FROM api_keys WHERE key_hash = $1 AND tenant_id = $2",
+            hash_key(&key),
+            tenant_id
+        )
+        .fetch_optional(&mut conn)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Erreur de base de données"))?
+        .ok_or((StatusCode::UNAUTHORIZED, "Clé d'API invalide"))?;
+
+        if !api_key.is_active {
+            return Err((StatusCode::FORBIDDEN, "Clé d'API désactivée"));
+        }
+
+        // Incrément atomique du compteur du jour, sans dépasser le quota
+        let usage = sqlx::query!(
+            r#"INSERT INTO api_key_usage (api_key_id, day, request_count)
+               SELECT $1, CURRENT_DATE, 1 WHERE $2 > 0
+               ON CONFLICT (api_key_id, day) DO UPDATE
+               SET request_count = api_key_usage.request_count + 1
+               WHERE api_key_usage.request_count < $2
+               RETURNING request_count"#,
+            api_key.id,
+            api_key.daily_quota
+        )
+        .fetch_optional(&mut conn)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Erreur de base de données"))?;
+
+        if usage.is_none() {
+            return Err((StatusCode::TOO_MANY_REQUESTS, "Quota journalier atteint"));
+        }
+
+        let _ = sqlx::query!("UPDATE api_keys SET last_used_at = NOW() WHERE id = $1", api_key.id)
+            .execute(&mut conn)
+            .await;
+
+        Ok(ApiKeyAuth(api_key.id))
+    }
+}
+
+// Routes d'administration des clés
+
+/// Route pour lister les clés d'API de la plateforme avec l'utilisation du
+/// jour (admin seulement)
+pub async fn get_api_keys(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut gérer les clés d'API"
+        }))).into_response();
+    }
+
+    match sqlx::query!(
+        r#"SELECT k.id, k.name, k.key_prefix, k.daily_quota, k.is_active, k.created_by,
+           k.created_at, k.last_used_at, COALESCE(u.request_count, 0) as "used_today!"
+           FROM api_keys k
+           LEFT JOIN api_key_usage u ON u.api_key_id = k.id AND u.day = CURRENT_DATE
+           WHERE k.tenant_id = $1
+           ORDER BY k.created_at DESC"#,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => {
+            let api_keys: Vec<serde_json::Value> = rows.into_iter().map(|row| {
+                serde_json::json!({
+                    "id": row.id,
+                    "name": row.name,
+                    "key_prefix": row.key_prefix,
+                    "daily_quota": row.daily_quota,
+                    "is_active": row.is_active,
+                    "created_by": row.created_by,
+                    "created_at": row.created_at,
+                    "last_used_at": row.last_used_at,
+                    "used_today": row.used_today
+                })
+            }).collect();
+
+            envelope::list("api_keys", &api_keys).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour créer une clé d'API de la plateforme (admin seulement).
+/// La clé en clair n'est renvoyée qu'une seule fois.
+pub async fn create_api_key(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut gérer les clés d'API"
+        }))).into_response();
+    }
+
+    if payload.name.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le nom de la clé est requis"
+        }))).into_response();
+    }
+
+    let daily_quota = payload.daily_quota.unwrap_or(1000);
+    if daily_quota < 0 {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le quota journalier doit être positif"
+        }))).into_response();
+    }
+
+    let key = generate_key();
+    let key_prefix = key.chars().take(KEY_PREFIX.len() + 8).collect::<String>();
+
+    match sqlx::query_as!(
+        ApiKey,
+        r#"INSERT INTO api_keys (name, key_prefix, key_hash, daily_quota, created_by, tenant_id)
+           VALUES ($1, $2, $3, $4, $5, $6)
+           RETURNING id, name, key_prefix, daily_quota, is_active, created_by, created_at, last_used_at"#,
+        payload.name.trim(),
+        key_prefix,
+        hash_key(&key),
+        daily_quota,
+        user.id,
+        user.tenant_id
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(api_key) => (StatusCode::CREATED, Json(serde_json::json!({
+            "api_key": api_key,
+            "key": key,
+            "message": "Clé d'API créée : conservez-la, elle ne sera plus affichée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour modifier une clé d'API : nom, quota, activation (admin seulement)
+pub async fn update_api_key(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(api_key_id): Path<Uuid>,
+    Json(payload): Json<UpdateApiKeyRequest>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut gérer les clés d'API"
+        }))).into_response();
+    }
+
+    if matches!(payload.daily_quota, Some(q) if q < 0) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le quota journalier doit être positif"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        ApiKey,
+        r#"UPDATE api_keys SET
+           name = COALESCE($2, name),
+           daily_quota = COALESCE($3, daily_quota),
+           is_active = COALESCE($4, is_active)
+           WHERE id = $1 AND tenant_id = $5
+           RETURNING id, name, key_prefix, daily_quota, is_active, created_by, created_at, last_used_at"#,
+        api_key_id,
+        payload.name,
+        payload.daily_quota,
+        payload.is_active,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(api_key)) => (StatusCode::OK, Json(serde_json::json!({
+            "api_key": api_key,
+            "message": "Clé d'API mise à jour avec succès"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Clé d'API non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour supprimer une clé d'API (admin seulement)
+pub async fn delete_api_key(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(api_key_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut gérer les clés d'API"
+        }))).into_response();
+    }
+
+    match sqlx::query!("DELETE FROM api_keys WHERE id = $1 AND tenant_id = $2", api_key_id, user.tenant_id)
+        .execute(&pool)
+        .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Clé d'API non trouvée"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Clé d'API supprimée avec succès"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
@@ -1,75 +1,123 @@
 /// src/auth.rs
 use axum::{
     extract::{FromRequestParts, State},
-    http::{request::Parts, StatusCode},
+    http::{request::Parts, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
+    Extension,
     Json,
 };
 use axum_extra::extract::cookie::CookieJar;
-use chrono::Utc;
-use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use serde::Deserialize;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
-use crate::models::{User, UserRole};
+use crate::activity;
+use crate::cache::UserCache;
+use crate::db::DbHealth;
+use crate::models::{Tenant, User, UserRole};
+use crate::brute_force::{self, AuthLockout};
+use crate::risk::{self, RiskRules};
+use crate::impersonation;
+use crate::user_tokens;
+use crate::wallet_address::WalletAddress;
 
-/// Structure renvoyée après connexion
-#[derive(Debug, Serialize)]
-pub struct SessionUser {
-    pub id: Uuid,
-    pub wallet: String,
-    pub name: Option<String>,
-    pub role: UserRole,
-    pub created_at: chrono::DateTime<Utc>,
-}
+// Structure renvoyée après connexion, partagée avec les clients
+pub use pa_backend_types::SessionUser;
 
 /// Payload JSON pour le login par wallet
 #[derive(Debug, Deserialize)]
 pub struct LoginRequest {
-    pub wallet: String,
+    pub wallet: WalletAddress,
 }
 
 /// Payload JSON pour l'authentification par bearer token
+#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 pub struct BearerAuthRequest {
     pub wallet: String,
 }
 
+/// Utilisateur par wallet principal ou par wallet secondaire vérifié (voir wallets.rs)
+async fn find_user_by_wallet<'e, E: PgExecutor<'e>>(executor: E, wallet: &WalletAddress) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as!(
+        User,
+        r#"SELECT id, tenant_id, wallet as "wallet: WalletAddress", name, role as "role: UserRole", created_at
+           FROM users
+           WHERE deactivated_at IS NULL
+           AND (wallet = $1 OR id = (SELECT user_id FROM user_wallets WHERE wallet = $1 AND verified_at IS NOT NULL))
+           LIMIT 1"#,
+        wallet.as_str()
+    )
+    .fetch_optional(executor)
+    .await
+}
+
 /// Handler `POST /auth/login` (simplifié sans sessions)
 pub async fn login(
     State(pool): State<PgPool>,
+    Extension(rules): Extension<RiskRules>,
+    Extension(lockout): Extension<AuthLockout>,
+    Extension(tenant): Extension<Tenant>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
 ) -> Response {
+    // Adresse IP bloquée après des échecs répétés (voir brute_force.rs)
+    let ip = risk::client_ip(&headers);
+    match lockout.is_locked(&pool, None, ip.as_deref()).await {
+        Ok(true) => return (StatusCode::TOO_MANY_REQUESTS, brute_force::LOCKED_MESSAGE).into_response(),
+        Ok(false) => {},
+        Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Erreur de base de données").into_response(),
+    }
+
     // Récupérer l'utilisateur par wallet
-    let user = match sqlx::query_as!(
-        User,
-        r#"SELECT id, wallet, name, role as "role: UserRole", created_at
-           FROM users
-           WHERE wallet = $1"#, payload.wallet
-    )
-    .fetch_optional(&pool)
+    let user = match find_user_by_wallet(&pool, &payload.wallet)
     .await
     .unwrap() {
-        Some(u) => u,
-        _ => return (StatusCode::UNAUTHORIZED, "Wallet invalide").into_response(),
+        // Un wallet n'est reconnu que sur sa propre plateforme
+        Some(u) if u.tenant_id == tenant.id => u,
+        _ => {
+            risk::auth_failed(pool, rules, payload.wallet.as_str().to_string(), ip, "unknown_wallet");
+            return (StatusCode::UNAUTHORIZED, "Wallet invalide").into_response();
+        },
     };
 
     let session_user = SessionUser {
         id: user.id,
+        tenant_id: user.tenant_id,
         wallet: user.wallet,
         name: user.name,
         role: user.role,
         created_at: user.created_at,
+        impersonation_id: None,
     };
+    activity::record_detached(pool, session_user.id, None, "login", serde_json::json!({ "wallet": payload.wallet }), ip);
 
     (StatusCode::OK, Json(session_user)).into_response()
 }
 
+/// Handler `GET /api/me` : utilisateur authentifié, avec le bandeau
+/// d'impersonation (`impersonation`, null hors session de support)
+pub async fn get_me(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> Response {
+    match impersonation::banner(&pool, &user).await {
+        Ok(banner) => (StatusCode::OK, Json(serde_json::json!({
+            "user": user,
+            "impersonation": banner
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
 /// Handler `POST /auth/logout` (simplifié)
 pub async fn logout() -> impl IntoResponse {
     (StatusCode::OK, Json(serde_json::json!({"message": "Déconnecté avec succès"})))
 }
 
 /// Extracteur d'utilisateur authentifié (cookies - conservé pour compatibilité)
+#[allow(dead_code)]
 pub struct AuthUser(pub SessionUser);
 
 #[axum::async_trait]
@@ -118,38 +166,119 @@ where
 
         let wallet = auth_header.strip_prefix("Bearer ").unwrap().trim();
 
+        // Récupérer le cache des utilisateurs
+        let cache = parts.extensions
+            .get::<UserCache>()
+            .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Cache manquant"))?
+            .clone();
+
+        // Plateforme résolue par le middleware (voir tenants.rs) : un wallet
+        // n'est reconnu que sur sa propre plateforme
+        let tenant_id = parts.extensions.get::<Tenant>().map(|tenant| tenant.id);
+        let same_tenant = |user: &SessionUser| tenant_id.is_none_or(|id| id == user.tenant_id);
+
+        let is_token = wallet.starts_with(user_tokens::TOKEN_PREFIX);
+        let is_impersonation = wallet.starts_with(impersonation::TOKEN_PREFIX);
+        // Adresse normalisée : `0xABC…` et `0xabc…` désignent le même utilisateur
+        let address = WalletAddress::parse(wallet);
+        if let Some(address) = address.as_ref().filter(|_| !is_token && !is_impersonation) {
+            if let Some(cached) = cache.get(address.as_str()) {
+                return if same_tenant(&cached) {
+                    Ok(BearerAuthUser(cached))
+                } else {
+                    Err((StatusCode::UNAUTHORIZED, "Wallet invalide"))
+                };
+            }
+        }
+
         // Récupérer le pool
         let pool = parts.extensions
             .get::<PgPool>()
             .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Pool manquant"))?
             .clone();
 
-        // Récupérer l'utilisateur par wallet
-        let user = sqlx::query_as!(
-            User,
-            r#"SELECT id, wallet, name, role as "role: UserRole", created_at
-               FROM users
-               WHERE wallet = $1"#, wallet
-        )
-        .fetch_optional(&pool)
-        .await
-        .map_err(|_| (StatusCode::UNAUTHORIZED, "Erreur de base de données"))?;
-
-        if let Some(u) = user {
-            Ok(BearerAuthUser(SessionUser {
+        // Adresse IP bloquée après des échecs répétés (voir brute_force.rs)
+        let ip = risk::client_ip(&parts.headers);
+        if let Some(lockout) = parts.extensions.get::<AuthLockout>() {
+            let locked = lockout.is_locked(&pool, None, ip.as_deref())
+                .await
+                .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Erreur de base de données"))?;
+            if locked {
+                return Err((StatusCode::TOO_MANY_REQUESTS, brute_force::LOCKED_MESSAGE));
+            }
+        }
+
+        // Jeton d'API personnel (voir user_tokens.rs) : jamais mis en cache,
+        // pour qu'une révocation prenne effet immédiatement
+        // Jeton d'impersonation (voir impersonation.rs) : même traitement,
+        // pour qu'une session terminée par l'admin cesse aussitôt
+        if is_token || is_impersonation {
+            let authenticated = if is_token {
+                user_tokens::authenticate(&pool, wallet, &parts.method, parts.uri.path()).await
+            } else {
+                impersonation::authenticate(&pool, wallet, &parts.method).await
+            };
+            let session_user = authenticated
+                .inspect_err(|(status, _)| {
+                    if *status != StatusCode::UNAUTHORIZED {
+                        return;
+                    }
+                    // Seul le préfixe du jeton est conservé
+                    if let Some(rules) = parts.extensions.get::<RiskRules>() {
+                        let prefix = wallet.chars().take(user_tokens::TOKEN_PREFIX.len() + 8).collect();
+                        risk::auth_failed(pool.clone(), rules.clone(), prefix, ip.clone(), "invalid_token");
+                    }
+                })?;
+            return if same_tenant(&session_user) {
+                Ok(BearerAuthUser(session_user))
+            } else {
+                Err((StatusCode::UNAUTHORIZED, "Wallet invalide"))
+            };
+        }
+
+        // Récupérer l'utilisateur par wallet (attente de la connexion mesurée, voir db.rs)
+        let user = match &address {
+            Some(address) => {
+                let health = parts.extensions
+                    .get::<DbHealth>()
+                    .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "État de la base manquant"))?;
+                let mut conn = health.acquire(&pool)
+                    .await
+                    .map_err(|_| (StatusCode::UNAUTHORIZED, "Erreur de base de données"))?;
+                find_user_by_wallet(&mut *conn, address)
+                    .await
+                    .map_err(|_| (StatusCode::UNAUTHORIZED, "Erreur de base de données"))?
+            },
+            None => None,
+        };
+
+        if let (Some(u), Some(address)) = (user, address) {
+            let session_user = SessionUser {
                 id: u.id,
+                tenant_id: u.tenant_id,
                 wallet: u.wallet,
                 name: u.name,
                 role: u.role,
                 created_at: u.created_at,
-            }))
+                impersonation_id: None,
+            };
+            cache.insert(address.as_str(), session_user.clone());
+            if !same_tenant(&session_user) {
+                return Err((StatusCode::UNAUTHORIZED, "Wallet invalide"));
+            }
+            Ok(BearerAuthUser(session_user))
         } else {
+            // Évalué par la règle des échecs d'authentification répétés
+            if let Some(rules) = parts.extensions.get::<RiskRules>() {
+                risk::auth_failed(pool, rules.clone(), wallet.to_string(), ip, "unknown_wallet");
+            }
             Err((StatusCode::UNAUTHORIZED, "Wallet invalide"))
         }
     }
 }
 
 /// Middleware qui vérifie le rôle admin avec Bearer Token
+#[allow(dead_code)]
 pub async fn require_admin_bearer(
     BearerAuthUser(user): BearerAuthUser,
 ) -> Result<BearerAuthUser, Response> {
@@ -161,6 +290,7 @@ pub async fn require_admin_bearer(
 }
 
 /// Middleware qui vérifie le rôle manager ou admin avec Bearer Token
+#[allow(dead_code)]
 pub async fn require_manager_or_admin_bearer(
     BearerAuthUser(user): BearerAuthUser,
 ) -> Result<BearerAuthUser, Response> {
@@ -172,6 +302,7 @@ pub async fn require_manager_or_admin_bearer(
 }
 
 /// Middleware simple qui vérifie le rôle admin (pour compatibilité)
+#[allow(dead_code)]
 pub async fn require_admin_role(
     AuthUser(user): AuthUser,
 ) -> Result<AuthUser, Response> {
@@ -183,6 +314,7 @@ pub async fn require_admin_role(
 }
 
 /// Middleware simple qui vérifie le rôle manager ou admin (pour compatibilité)
+#[allow(dead_code)]
 pub async fn require_manager_or_admin_role(
     AuthUser(user): AuthUser,
 ) -> Result<AuthUser, Response> {
@@ -0,0 +1,382 @@
+// backups.rs
+//
+// Sauvegardes logiques à la demande des admins, avant une opération risquée
+// (import, suppression en masse) : la route ne fait qu'enregistrer la demande
+// et mettre en file une tâche `export_backup` (voir jobs.rs). Le worker copie
+// les tables principales (`COPY ... TO STDOUT`) dans une même transaction en
+// lecture seule, donc un instantané cohérent, vers un fichier SQL déposé dans
+// le bucket privé ; le téléchargement passe par une URL présignée.
+//
+// Le fichier se restaure avec psql dans une base créée par la migration :
+// `psql "$DATABASE_URL" -v ON_ERROR_STOP=1 -1 -f backup.sql`. Il vide d'abord
+// les tables sauvegardées (et, en cascade, celles qui en dépendent). Les tables
+// dérivées (statistiques journalières, certificats) sont recalculées par leurs
+// triggers ; la file du relayer n'est pas sauvegardée.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use chrono::{Duration as ChronoDuration, Utc};
+use futures_util::TryStreamExt;
+use sqlx::PgPool;
+use std::env;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::error_codes::ErrorCode;
+use crate::jobs;
+use crate::models::{Backup, CreateBackupRequest, Job, ReportStatus, UserRole};
+use crate::notifications::notify;
+use crate::storage::StorageConfig;
+
+const RECENT_BACKUPS_LIMIT: i64 = 50;
+
+/// Longueur maximale du motif d'une sauvegarde
+const MAX_COMMENT_LEN: usize = 500;
+
+/// Tables sauvegardées, dans l'ordre de restauration (clés étrangères), et
+/// colonnes écartées : `registry_tx_id` désigne la file du relayer, non sauvegardée
+const CORE_TABLES: &[(&str, &[&str])] = &[
+    ("tenants", &[]),
+    ("users", &[]),
+    ("user_wallets", &[]),
+    ("legal_documents", &[]),
+    ("user_acceptances", &[]),
+    ("kyc_verifications", &[]),
+    ("accreditations", &[]),
+    ("tags", &[]),
+    ("properties", &["registry_tx_id"]),
+    ("property_tags", &[]),
+    ("property_managers", &[]),
+    ("property_media", &[]),
+    ("property_status_events", &[]),
+    ("exposure_limits", &[]),
+    ("investments", &[]),
+    ("distribution_runs", &[]),
+    ("investment_revisions", &[]),
+    ("fiat_payments", &[]),
+    ("investment_refunds", &[]),
+    ("onchain_investments", &[]),
+    ("token_transfers", &[]),
+    ("contents", &[]),
+    ("announcements", &[]),
+    ("tax_rules", &[]),
+    ("feature_flags", &[]),
+    ("retention_policies", &[]),
+];
+
+/// Tâche `export_backup` de la file (voir jobs.rs) : exporte la sauvegarde et
+/// la dépose dans le bucket privé. Un échec est renvoyé pour être retenté ; la
+/// sauvegarde n'est marquée en échec qu'à la dernière tentative.
+pub async fn run_job(pool: &PgPool, storage: &StorageConfig, job: &Job) -> Result<(), String> {
+    let backup_id = job.payload.get("backup_id")
+        .and_then(|v| v.as_str())
+        .and_then(|v| v.parse::<Uuid>().ok())
+        .ok_or("backup_id manquant ou invalide")?;
+
+    let backup = sqlx::query_as!(
+        Backup,
+        r#"UPDATE backups SET status = 'running', started_at = NOW()
+           WHERE id = $1 AND status IN ('queued', 'running')
+           RETURNING id, status as "status: ReportStatus", comment, tables, storage_key, row_count, size, error,
+                     requested_by, created_at, started_at, completed_at"#,
+        backup_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let backup = match backup {
+        Some(backup) => backup,
+        // Sauvegarde supprimée ou déjà terminée
+        None => return Ok(()),
+    };
+
+    let path = env::temp_dir().join(format!("backup-{}.sql", backup.id));
+    let key = format!("backups/{}.sql", backup.id);
+    let outcome = match export(pool, &backup, &path).await {
+        Ok(written) => storage
+            .put_document(&key, &path, "application/sql; charset=utf-8")
+            .await
+            .map(|_| written),
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&path).await;
+
+    match outcome {
+        Ok((row_count, size)) => {
+            sqlx::query!(
+                r#"UPDATE backups SET status = 'completed', storage_key = $2, row_count = $3, size = $4,
+                   error = NULL, completed_at = NOW()
+                   WHERE id = $1"#,
+                backup.id,
+                key,
+                row_count as i64,
+                size as i64
+            )
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            if let Some(user_id) = backup.requested_by {
+                notify(pool, user_id, "backup_completed", "Votre sauvegarde est prête à être téléchargée", serde_json::json!({
+                    "backup_id": backup.id
+                })).await.map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        },
+        Err(e) if job.attempts >= job.max_attempts => {
+            sqlx::query!(
+                "UPDATE backups SET status = 'failed', error = $2, completed_at = NOW() WHERE id = $1",
+                backup.id,
+                e
+            )
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            if let Some(user_id) = backup.requested_by {
+                notify(pool, user_id, "backup_failed", "Votre sauvegarde a échoué", serde_json::json!({
+                    "backup_id": backup.id
+                })).await.map_err(|e| e.to_string())?;
+            }
+            Err(e)
+        },
+        // Nouvel essai prévu par la file : la sauvegarde reste en attente
+        Err(e) => {
+            sqlx::query!("UPDATE backups SET status = 'queued', error = $2 WHERE id = $1", backup.id, e)
+                .execute(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            Err(e)
+        },
+    }
+}
+
+async fn write(out: &mut BufWriter<File>, size: &mut u64, bytes: &[u8]) -> Result<(), String> {
+    out.write_all(bytes).await.map_err(|e| e.to_string())?;
+    *size += bytes.len() as u64;
+    Ok(())
+}
+
+/// Écrit le fichier SQL de la sauvegarde ; renvoie le nombre de lignes et la taille
+async fn export(pool: &PgPool, backup: &Backup, path: &std::path::Path) -> Result<(u64, u64), String> {
+    let file = File::create(path).await.map_err(|e| e.to_string())?;
+    let mut out = BufWriter::new(file);
+    let (mut rows, mut size) = (0u64, 0u64);
+
+    let tables: Vec<&str> = CORE_TABLES.iter()
+        .map(|(table, _)| *table)
+        .filter(|table| backup.tables.iter().any(|t| t == table))
+        .collect();
+    let header = format!(
+        "-- Sauvegarde {} du {}{}\n\
+         -- Restauration (remplace les données des tables sauvegardées et des tables qui en dépendent) :\n\
+         --   psql \"$DATABASE_URL\" -v ON_ERROR_STOP=1 -1 -f <fichier>\n\
+         SET client_encoding = 'UTF8';\n\
+         -- properties.shares_sold est restauré tel quel (trigger de suivi des parts désactivé)\n\
+         SELECT set_config('pa.replaying', 'on', false);\n\
+         TRUNCATE {} CASCADE;\n\n",
+        backup.id,
+        Utc::now().to_rfc3339(),
+        backup.comment.as_deref().map(|c| format!(" : {}", c.replace(['\n', '\r'], " "))).unwrap_or_default(),
+        tables.iter().map(|table| format!("public.{}", table)).collect::<Vec<_>>().join(", ")
+    );
+    write(&mut out, &mut size, header.as_bytes()).await?;
+
+    // Instantané cohérent de toutes les tables
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for (table, excluded) in CORE_TABLES.iter().filter(|(table, _)| tables.contains(table)) {
+        // Colonnes générées écartées : recalculées à la restauration
+        let columns: Vec<String> = sqlx::query_scalar!(
+            r#"SELECT column_name as "column_name!" FROM information_schema.columns
+               WHERE table_schema = 'public' AND table_name = $1 AND is_generated = 'NEVER'
+               ORDER BY ordinal_position"#,
+            table
+        )
+        .fetch_all(&mut tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        let columns = columns.into_iter()
+            .filter(|column| !excluded.contains(&column.as_str()))
+            .map(|column| format!("\"{}\"", column))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let statement = format!("COPY public.{} ({}) FROM stdin;\n", table, columns);
+        write(&mut out, &mut size, statement.as_bytes()).await?;
+        let mut stream = tx.copy_out_raw(&format!("COPY public.{} ({}) TO STDOUT", table, columns))
+            .await
+            .map_err(|e| e.to_string())?;
+        while let Some(chunk) = stream.try_next().await.map_err(|e| e.to_string())? {
+            // Format texte : une ligne par enregistrement, retours à la ligne des valeurs échappés
+            rows += chunk.iter().filter(|b| **b == b'\n').count() as u64;
+            write(&mut out, &mut size, &chunk).await?;
+        }
+        drop(stream);
+        write(&mut out, &mut size, b"\\.\n\n").await?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    out.flush().await.map_err(|e| e.to_string())?;
+    Ok((rows, size))
+}
+
+fn admin_only(role: &UserRole) -> Option<Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer les sauvegardes",
+        "code": ErrorCode::AdminRequired
+    }))).into_response())
+}
+
+/// Lien de téléchargement signé d'une sauvegarde terminée
+fn download(storage: &Option<StorageConfig>, backup: &Backup) -> Option<serde_json::Value> {
+    let now = Utc::now();
+    match (storage, &backup.storage_key) {
+        (Some(storage), Some(key)) if backup.status == ReportStatus::Completed => Some(serde_json::json!({
+            "url": storage.presign_get(key, now),
+            "expires_at": now + ChronoDuration::seconds(storage.url_ttl_secs as i64),
+            "expires_in": storage.url_ttl_secs
+        })),
+        _ => None,
+    }
+}
+
+/// Route admin : demander une sauvegarde, exportée en tâche de fond
+pub async fn create_backup(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+    payload: Option<Json<CreateBackupRequest>>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+    if storage.is_none() {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Stockage des documents non configuré",
+            "code": ErrorCode::ServiceUnavailable
+        }))).into_response();
+    }
+    let comment = payload.and_then(|Json(p)| p.comment).map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    if comment.as_ref().is_some_and(|c| c.chars().count() > MAX_COMMENT_LEN) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Motif trop long ({} caractères au plus)", MAX_COMMENT_LEN),
+            "code": ErrorCode::BadRequest
+        }))).into_response();
+    }
+
+    let tables: Vec<String> = CORE_TABLES.iter().map(|(table, _)| table.to_string()).collect();
+    let created = async {
+        let mut tx = pool.begin().await?;
+        let backup = sqlx::query_as!(
+            Backup,
+            r#"INSERT INTO backups (comment, tables, requested_by)
+               VALUES ($1, $2, $3)
+               RETURNING id, status as "status: ReportStatus", comment, tables, storage_key, row_count, size, error,
+                         requested_by, created_at, started_at, completed_at"#,
+            comment,
+            &tables,
+            user.id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        jobs::enqueue(&mut tx, jobs::EXPORT_BACKUP, serde_json::json!({ "backup_id": backup.id })).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(backup)
+    }.await;
+
+    match created {
+        Ok(backup) => {
+            tracing::info!(backup_id = %backup.id, admin_id = %user.id, "Sauvegarde demandée");
+            (StatusCode::ACCEPTED, Json(serde_json::json!({
+                "backup": backup,
+                "message": "Sauvegarde en cours d'export"
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
+
+/// Route admin : sauvegardes récentes, les plus récentes d'abord, avec leur lien de téléchargement
+pub async fn get_backups(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        Backup,
+        r#"SELECT id, status as "status: ReportStatus", comment, tables, storage_key, row_count, size, error,
+                  requested_by, created_at, started_at, completed_at
+           FROM backups
+           ORDER BY created_at DESC
+           LIMIT $1"#,
+        RECENT_BACKUPS_LIMIT
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(backups) => {
+            let backups: Vec<_> = backups.iter().map(|backup| serde_json::json!({
+                "backup": backup,
+                "download": download(&storage, backup)
+            })).collect();
+            envelope::list("backups", &backups).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
+
+/// Route admin : état d'une sauvegarde, avec une URL de téléchargement signée une fois exportée
+pub async fn get_backup(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+    Path(backup_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        Backup,
+        r#"SELECT id, status as "status: ReportStatus", comment, tables, storage_key, row_count, size, error,
+                  requested_by, created_at, started_at, completed_at
+           FROM backups
+           WHERE id = $1"#,
+        backup_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(backup)) => (StatusCode::OK, Json(serde_json::json!({
+            "download": download(&storage, &backup),
+            "backup": backup
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Sauvegarde non trouvée",
+            "code": ErrorCode::BackupNotFound
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,170 @@
+// brute_force.rs
+//
+// Blocage temporaire des tentatives d'authentification : au-delà d'un nombre
+// d'échecs (wallet inconnu, jeton invalide, mauvaise signature) sur une
+// fenêtre glissante, l'adresse IP ou le wallet concerné reçoit 429 jusqu'à ce
+// que les échecs sortent de la fenêtre. Les échecs sont enregistrés dans
+// `auth_failures` par risk::auth_failed.
+//
+// L'authentification par Bearer (requêtes non servies par le cache) et le
+// login ne contrôlent que l'adresse IP ; le seuil par wallet ne s'applique
+// qu'aux vérifications de signature, pour qu'un tiers ne puisse pas bloquer
+// la connexion d'un wallet qui ne lui appartient pas.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use sqlx::PgPool;
+use std::env;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{AuthAttemptListQuery, UserRole};
+use crate::pagination::{Cursor, Pagination};
+
+pub const LOCKED_MESSAGE: &str = "Trop de tentatives d'authentification échouées, réessayer plus tard";
+
+/// Seuils de blocage, lus depuis l'environnement (0 désactive le seuil)
+#[derive(Clone)]
+pub struct AuthLockout {
+    max_per_ip: i64,
+    max_per_wallet: i64,
+    window_secs: f64,
+}
+
+impl AuthLockout {
+    pub fn from_env() -> Self {
+        let env_u64 = |name: &str, default: u64| {
+            env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).unwrap_or(default)
+        };
+        Self {
+            max_per_ip: env_u64("AUTH_LOCKOUT_MAX_PER_IP", 20) as i64,
+            max_per_wallet: env_u64("AUTH_LOCKOUT_MAX_PER_WALLET", 5) as i64,
+            window_secs: env_u64("AUTH_LOCKOUT_WINDOW_SECS", 900).max(1) as f64,
+        }
+    }
+
+    /// Vrai si l'adresse IP ou le wallet a atteint son seuil d'échecs
+    pub async fn is_locked(&self, pool: &PgPool, wallet: Option<&str>, ip: Option<&str>) -> Result<bool, sqlx::Error> {
+        let ip = ip.filter(|_| self.max_per_ip > 0);
+        let wallet = wallet.filter(|_| self.max_per_wallet > 0);
+        if ip.is_none() && wallet.is_none() {
+            return Ok(false);
+        }
+
+        let counts = sqlx::query!(
+            r#"SELECT
+               COUNT(*) FILTER (WHERE ip = $1) as "ip!",
+               COUNT(*) FILTER (WHERE lower(wallet) = lower($2)) as "wallet!"
+               FROM auth_failures
+               WHERE (ip = $1 OR lower(wallet) = lower($2))
+               AND created_at > NOW() - make_interval(secs => $3)"#,
+            ip,
+            wallet,
+            self.window_secs
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok((ip.is_some() && counts.ip >= self.max_per_ip)
+            || (wallet.is_some() && counts.wallet >= self.max_per_wallet))
+    }
+}
+
+/// Route admin : échecs d'authentification récents (`?ip=`, `?wallet=`,
+/// `?reason=` pour filtrer) et adresses ou wallets actuellement bloqués
+pub async fn get_auth_attempts(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(lockout): Extension<AuthLockout>,
+    Query(query): Query<AuthAttemptListQuery>,
+    pagination: Pagination,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent consulter les tentatives d'authentification"
+        }))).into_response();
+    }
+    let (after_at, after_id) = pagination.cursor_bounds();
+
+    let (attempts, page) = match sqlx::query!(
+        r#"SELECT id, wallet, ip, reason, created_at
+           FROM auth_failures
+           WHERE ($1::text IS NULL OR ip = $1)
+           AND ($2::text IS NULL OR lower(wallet) = lower($2))
+           AND ($3::text IS NULL OR reason = $3)
+           AND ($4::timestamptz IS NULL OR (created_at, id) < ($4, $5))
+           ORDER BY created_at DESC, id DESC
+           LIMIT $6 OFFSET $7"#,
+        query.ip,
+        query.wallet,
+        query.reason,
+        after_at,
+        after_id,
+        pagination.limit(),
+        pagination.offset()
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(mut rows) => {
+            let page = pagination.finish(&mut rows, |row| Cursor::new(row.created_at, row.id));
+            let attempts = rows.into_iter().map(|row| serde_json::json!({
+                "id": row.id,
+                "wallet": row.wallet,
+                "ip": row.ip,
+                "reason": row.reason,
+                "created_at": row.created_at
+            })).collect::<Vec<_>>();
+            (attempts, page)
+        },
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    };
+
+    // Sujets au-delà de leur seuil sur la fenêtre courante
+    let locked = match sqlx::query!(
+        r#"SELECT kind as "kind!", subject as "subject!", failures as "failures!", last_failure_at as "last_failure_at!"
+           FROM (
+               SELECT 'ip' as kind, ip as subject, COUNT(*) as failures, MAX(created_at) as last_failure_at
+               FROM auth_failures
+               WHERE ip IS NOT NULL AND $1::bigint > 0 AND created_at > NOW() - make_interval(secs => $3)
+               GROUP BY ip HAVING COUNT(*) >= $1::bigint
+               UNION ALL
+               SELECT 'wallet', lower(wallet), COUNT(*), MAX(created_at)
+               FROM auth_failures
+               WHERE $2::bigint > 0 AND created_at > NOW() - make_interval(secs => $3)
+               GROUP BY lower(wallet) HAVING COUNT(*) >= $2::bigint
+           ) locked
+           ORDER BY last_failure_at DESC"#,
+        lockout.max_per_ip,
+        lockout.max_per_wallet,
+        lockout.window_secs
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => rows.into_iter().map(|row| serde_json::json!({
+            "kind": row.kind,
+            "subject": row.subject,
+            "failures": row.failures,
+            "last_failure_at": row.last_failure_at
+        })).collect::<Vec<_>>(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    };
+
+    envelope::list("attempts", &attempts)
+        .paginated(page)
+        .with("locked", locked)
+        .with("thresholds", serde_json::json!({
+            "max_per_ip": lockout.max_per_ip,
+            "max_per_wallet": lockout.max_per_wallet,
+            "window_secs": lockout.window_secs as u64
+        }))
+        .into_response()
+}
@@ -0,0 +1,122 @@
+// cache.rs
+//
+// Cache des utilisateurs authentifiés. Chaque processus de l'API a le sien :
+// un changement de droits (rôle, wallets, fusion de comptes) invalide les
+// wallets concernés dans ce processus puis dans les autres, par `NOTIFY` sur
+// `INVALIDATION_CHANNEL`. Le TTL reste le filet de sécurité d'une
+// invalidation manquée (écoute coupée pendant un redémarrage de la base).
+
+use moka::sync::Cache;
+use sqlx::postgres::{PgExecutor, PgListener};
+use sqlx::PgPool;
+use std::env;
+use std::time::Duration;
+
+use crate::auth::SessionUser;
+
+/// Canal Postgres des invalidations entre processus : wallets séparés par des
+/// virgules, tout le cache pour un message vide
+const INVALIDATION_CHANNEL: &str = "user_cache_invalidated";
+
+/// Cache mémoire des utilisateurs authentifiés, indexé par wallet.
+/// Évite un aller-retour en base à chaque requête Bearer Token.
+#[derive(Clone)]
+pub struct UserCache {
+    inner: Cache<String, SessionUser>,
+}
+
+impl UserCache {
+    /// Crée le cache avec un TTL configurable via `USER_CACHE_TTL_SECS` (30s par défaut)
+    pub fn from_env() -> Self {
+        let ttl = env::var("USER_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+
+        Self {
+            inner: Cache::builder()
+                .max_capacity(10_000)
+                .time_to_live(Duration::from_secs(ttl))
+                .build(),
+        }
+    }
+
+    pub fn get(&self, wallet: &str) -> Option<SessionUser> {
+        self.inner.get(wallet)
+    }
+
+    /// `wallet` est celui de la requête : le wallet principal ou un wallet
+    /// secondaire rattaché au même utilisateur
+    pub fn insert(&self, wallet: &str, user: SessionUser) {
+        self.inner.insert(wallet.to_string(), user);
+    }
+
+    /// Retire un wallet du cache de ce processus
+    pub fn invalidate(&self, wallet: &str) {
+        self.inner.invalidate(wallet);
+    }
+
+    pub fn invalidate_all(&self) {
+        self.inner.invalidate_all();
+    }
+
+    /// À appeler dès que les droits d'un utilisateur changent (rôle, wallets,
+    /// fusion) : invalide ses wallets dans ce processus et dans les autres
+    pub async fn invalidate_everywhere(&self, pool: &PgPool, wallets: &[String]) {
+        if wallets.is_empty() {
+            return;
+        }
+        wallets.iter().for_each(|wallet| self.invalidate(wallet));
+        if let Err(e) = notify_invalidation(pool, &wallets.join(",")).await {
+            tracing::warn!("Invalidation du cache des utilisateurs non diffusée: {}", e);
+        }
+    }
+
+    /// À appeler quand les utilisateurs sont effacés en masse
+    pub async fn invalidate_all_everywhere(&self, pool: &PgPool) {
+        self.invalidate_all();
+        if let Err(e) = notify_invalidation(pool, "").await {
+            tracing::warn!("Invalidation du cache des utilisateurs non diffusée: {}", e);
+        }
+    }
+
+    /// Applique les invalidations des autres processus
+    pub fn listen(&self, pool: PgPool) {
+        let cache = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let e = follow_invalidations(&pool, &cache).await;
+                tracing::warn!("Écoute des invalidations du cache des utilisateurs interrompue: {}", e);
+                // Des invalidations ont pu être manquées pendant la coupure
+                cache.invalidate_all();
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+}
+
+async fn notify_invalidation<'e>(db: impl PgExecutor<'e>, payload: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(INVALIDATION_CHANNEL)
+        .bind(payload)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+async fn follow_invalidations(pool: &PgPool, cache: &UserCache) -> sqlx::Error {
+    let mut listener = match PgListener::connect_with(pool).await {
+        Ok(listener) => listener,
+        Err(e) => return e,
+    };
+    if let Err(e) = listener.listen(INVALIDATION_CHANNEL).await {
+        return e;
+    }
+    loop {
+        match listener.recv().await {
+            Ok(notification) if notification.payload().is_empty() => cache.invalidate_all(),
+            Ok(notification) => notification.payload().split(',').for_each(|wallet| cache.invalidate(wallet)),
+            Err(e) => return e,
+        }
+    }
+}
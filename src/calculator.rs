@@ -0,0 +1,143 @@
+// calculator.rs
+//
+// Simulateur de rendement : `GET /api/properties/:id/calculator?amount_eth=`
+// projette un investissement dans une propriété à partir de son rendement
+// annuel et du prix de la part. Le frontend (pages de présentation comprises)
+// affiche ces chiffres sans refaire le calcul. Règles d'arrondi (voir money.rs) :
+// - parts : nombre entier de parts achetables, arrondi inférieur ;
+// - montant investi et reste : exacts (prix de la part × parts) ;
+// - revenus annuel et mensuel : estimations à 6 décimales, arrondi bancaire ;
+// - retour sur investissement : années à 2 décimales (arrondi bancaire), mois
+//   entiers arrondis au supérieur ;
+// - contre-valeurs en devise : au centime le plus proche.
+// Le revenu net applique la retenue à la source du pays de l'utilisateur (voir
+// taxes.rs).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::currency;
+use crate::error_codes::ErrorCode;
+use crate::models::YieldCalculation;
+use crate::money::{round_half_even, round_up, AmountInput, TokenAmount};
+
+/// Route : projection d'un investissement de `amount_eth` (ou `amount_wei`)
+/// dans une propriété visible par l'utilisateur
+pub async fn get_yield_calculation(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+    Query(query): Query<AmountInput>,
+) -> impl IntoResponse {
+    let amount = match query.resolve() {
+        Ok(Some(amount)) if amount.is_positive() => amount,
+        Ok(_) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Paramètre amount_eth ou amount_wei positif requis",
+            "code": ErrorCode::InvalidAmount
+        }))).into_response(),
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidAmount
+        }))).into_response(),
+    };
+
+    // Un brouillon n'est visible que par son créateur et ses co-gestionnaires
+    let property = match sqlx::query!(
+        r#"SELECT p.token_price, p.annual_yield,
+                  GREATEST(p.total_shares - p.shares_sold, 0) as "available_shares!",
+                  t.rate as "withholding_rate?"
+           FROM properties p
+           JOIN users u ON u.id = $3
+           LEFT JOIN tax_rules t ON t.tenant_id = p.tenant_id AND t.country = u.country AND t.applies_to = 'distributions'
+           WHERE p.id = $1 AND p.tenant_id = $2
+           AND (p.status <> 'draft' OR p.created_by = $3
+                OR p.id IN (SELECT property_id FROM property_managers WHERE user_id = $3))"#,
+        property_id,
+        user.tenant_id,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(property)) => property,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    };
+
+    let zero = BigDecimal::from(0);
+    let shares = if property.token_price > zero {
+        (amount.as_decimal() / &property.token_price).with_scale(0).to_i64().unwrap_or(0)
+    } else {
+        0
+    };
+    if shares < 1 {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+            "error": "Montant inférieur au prix d'une part",
+            "code": ErrorCode::AmountBelowSharePrice,
+            "token_price": property.token_price
+        }))).into_response();
+    }
+
+    let invested = TokenAmount::for_shares(&property.token_price, shares);
+    let remainder = TokenAmount::from_decimal(amount.as_decimal() - invested.as_decimal());
+    let hundred = BigDecimal::from(100);
+    let annual = invested.as_decimal() * &property.annual_yield / &hundred;
+    let withheld = property.withholding_rate.as_ref()
+        .map(|rate| &annual * rate / &hundred)
+        .unwrap_or_else(|| zero.clone());
+    let annual_income = TokenAmount::estimate(annual.clone());
+    let monthly_income = TokenAmount::estimate(&annual / BigDecimal::from(12));
+    let net_annual_income = TokenAmount::estimate(&annual - &withheld);
+    let (payback_years, payback_months) = if property.annual_yield > zero {
+        let years = &hundred / &property.annual_yield;
+        (Some(round_half_even(&years, 2)), round_up(&(&years * BigDecimal::from(12)), 0).to_i64())
+    } else {
+        (None, None)
+    };
+
+    let fiat = match currency::valuation_for(&pool, user.id).await {
+        Ok(valuation) => serde_json::json!({
+            "format": valuation.format(),
+            "invested": valuation.value(&invested),
+            "annual_income": valuation.value(&annual_income),
+            "monthly_income": valuation.value(&monthly_income),
+            "net_annual_income": valuation.value(&net_annual_income)
+        }),
+        Err(e) => {
+            tracing::error!("Erreur lors de la lecture du cours de l'ETH: {}", e);
+            serde_json::Value::Null
+        },
+    };
+
+    (StatusCode::OK, Json(YieldCalculation {
+        property_id,
+        amount_eth: amount,
+        token_price: property.token_price,
+        shares,
+        invested_eth: invested,
+        remainder_eth: remainder,
+        available_shares: property.available_shares,
+        exceeds_available: shares > property.available_shares,
+        annual_yield: property.annual_yield,
+        annual_income_eth: annual_income,
+        monthly_income_eth: monthly_income,
+        withholding_rate: property.withholding_rate,
+        net_annual_income_eth: net_annual_income,
+        payback_years,
+        payback_months,
+        fiat,
+    })).into_response()
+}
@@ -0,0 +1,134 @@
+// certificates.rs
+//
+// Certificats des investissements confirmés. Dès qu'un investissement est
+// réglé avec un hash de transaction, un trigger émet un certificat portant
+// l'empreinte SHA-256 (hex) de la forme canonique
+// `pa-certificate-v1|<wallet>|<onchain_id>|<parts>|<tx_hash>` (wallet et hash
+// de transaction en minuscules). Un tiers peut vérifier un certificat sans
+// compte, à partir de son empreinte ; il est révoqué si l'investissement est
+// remboursé ou corrigé (un nouveau certificat remplace alors le précédent).
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::digest;
+use crate::models::{InvestmentCertificate, Tenant, UserRole};
+
+/// Forme canonique hachée dans un certificat
+fn canonical_payload(wallet: &str, onchain_id: &str, shares: i32, tx_hash: &str) -> String {
+    format!("pa-certificate-v1|{}|{}|{}|{}", wallet, onchain_id, shares, tx_hash)
+}
+
+fn verify_url(hash: &str) -> String {
+    format!("{}/public/v1/certificates/{}/verify", digest::api_url(), hash)
+}
+
+/// Route : certificat en cours de validité d'un investissement (propriétaire ou admin)
+pub async fn get_investment_certificate(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(investment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let owner = match sqlx::query_scalar!(
+        "SELECT user_id FROM investments WHERE id = $1 AND tenant_id = $2",
+        investment_id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(owner)) => owner,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Investissement non trouvé"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    if !matches!(user.role, UserRole::Admin) && owner != user.id {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin ou le propriétaire peut consulter le certificat de cet investissement"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        InvestmentCertificate,
+        "SELECT * FROM investment_certificates WHERE investment_id = $1 AND revoked_at IS NULL",
+        investment_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(certificate)) => (StatusCode::OK, Json(serde_json::json!({
+            "canonical_payload": canonical_payload(&certificate.wallet, &certificate.onchain_id, certificate.shares, &certificate.tx_hash),
+            "verify_url": verify_url(&certificate.hash),
+            "certificate": certificate
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Aucun certificat valide : l'investissement n'est pas confirmé on-chain ou a été remboursé"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route publique : vérification d'un certificat par son empreinte
+pub async fn verify_certificate(
+    State(pool): State<PgPool>,
+    Extension(tenant): Extension<Tenant>,
+    Path(hash): Path<String>,
+) -> impl IntoResponse {
+    let hash = hash.trim().to_lowercase();
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Empreinte invalide (SHA-256 en hexadécimal)"
+        }))).into_response();
+    }
+
+    match sqlx::query!(
+        r#"SELECT c.hash, c.wallet, c.onchain_id, c.shares, c.tx_hash, c.issued_at, c.revoked_at,
+                  p.name as property_name, p.slug as property_slug
+           FROM investment_certificates c
+           JOIN investments i ON i.id = c.investment_id
+           JOIN properties p ON p.id = i.property_id
+           WHERE c.hash = $1 AND i.tenant_id = $2"#,
+        hash,
+        tenant.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(row)) => (
+            StatusCode::OK,
+            [(header::CACHE_CONTROL, "no-store")],
+            Json(serde_json::json!({
+                "valid": row.revoked_at.is_none(),
+                "canonical_payload": canonical_payload(&row.wallet, &row.onchain_id, row.shares, &row.tx_hash),
+                "hash": row.hash,
+                "wallet": row.wallet,
+                "property": {
+                    "onchain_id": row.onchain_id,
+                    "name": row.property_name,
+                    "slug": row.property_slug
+                },
+                "shares": row.shares,
+                "tx_hash": row.tx_hash,
+                "issued_at": row.issued_at,
+                "revoked_at": row.revoked_at
+            })),
+        ).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "valid": false,
+            "error": "Certificat inconnu"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,346 @@
+// chain.rs
+//
+// Client JSON-RPC minimal vers un nœud Ethereum (`CHAIN_RPC_URL`) : lecture de
+// l'état de la chaîne, estimation du gaz et diffusion des transactions signées.
+// Avec `CHAIN_MODE=mock`, les appels sont servis par une chaîne simulée en
+// mémoire (voir mock_chain.rs). Expose aussi l'état du réseau aux frontends
+// (`/api/chain/status`).
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use chrono::Utc;
+use moka::sync::Cache;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::mock_chain::MockChain;
+use crate::outbound::{Outbound, OutboundClient, OutboundError};
+
+/// Reçu d'une transaction minée
+pub struct TxReceipt {
+    pub success: bool,
+    pub block_number: u64,
+    pub logs: Vec<TxLog>,
+}
+
+/// Événement émis par un contrat (format `eth_getLogs`)
+pub struct TxLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: Option<u64>,
+    pub tx_hash: Option<String>,
+    pub log_index: Option<u64>,
+    pub removed: bool, // Retiré de la chaîne par une réorganisation
+}
+
+impl TxLog {
+    pub fn from_json(log: &Value) -> Self {
+        let text = |value: &Value| value.as_str().unwrap_or_default().to_lowercase();
+        let quantity = |value: &Value| parse_quantity(value).ok().map(|v| v as u64);
+        Self {
+            address: text(&log["address"]),
+            topics: log["topics"].as_array().map(|t| t.iter().map(text).collect()).unwrap_or_default(),
+            data: text(&log["data"]),
+            block_number: quantity(&log["blockNumber"]),
+            tx_hash: log["transactionHash"].as_str().map(str::to_lowercase),
+            log_index: quantity(&log["logIndex"]),
+            removed: log["removed"].as_bool().unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Backend {
+    Node { url: String, client: OutboundClient },
+    Mock(MockChain),
+}
+
+#[derive(Clone)]
+pub struct ChainRpc {
+    backend: Backend,
+    next_id: Arc<AtomicU64>,
+}
+
+impl ChainRpc {
+    /// Renvoie `None` si aucun nœud n'est configuré et que la chaîne simulée
+    /// n'est pas activée
+    pub fn from_env(outbound: &Outbound) -> Option<Self> {
+        let backend = match MockChain::from_env() {
+            Some(mock) => Backend::Mock(mock),
+            None => {
+                let url = env::var("CHAIN_RPC_URL").ok().filter(|v| !v.trim().is_empty())?;
+                Backend::Node { url, client: outbound.client("rpc")? }
+            },
+        };
+        Some(Self { backend, next_id: Arc::new(AtomicU64::new(1)) })
+    }
+
+    /// Chaîne simulée, si `CHAIN_MODE=mock`
+    pub fn mock(&self) -> Option<&MockChain> {
+        match &self.backend {
+            Backend::Mock(mock) => Some(mock),
+            Backend::Node { .. } => None,
+        }
+    }
+
+    /// Échoue aussitôt si le disjoncteur du nœud est ouvert
+    pub fn check(&self) -> Result<(), OutboundError> {
+        match &self.backend {
+            Backend::Node { client, .. } => client.check(),
+            Backend::Mock(_) => Ok(()),
+        }
+    }
+
+    /// Appel JSON-RPC ; les erreurs du nœud sont renvoyées avec leur message.
+    /// Rejouable : une transaction signée déjà diffusée garde le même hash.
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+        let (url, client) = match &self.backend {
+            Backend::Node { url, client } => (url, client),
+            Backend::Mock(mock) => return mock.call(method, &params).map_err(|e| format!("{}: {}", method, e)),
+        };
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params
+        });
+        let response: Value = client
+            .send_idempotent(client.post(url).json(&body))
+            .await
+            .map_err(|e| format!("{}: {}", method, e))?
+            .json()
+            .await
+            .map_err(|e| format!("{}: {}", method, e))?;
+
+        if let Some(error) = response.get("error") {
+            let message = error.get("message").and_then(Value::as_str).unwrap_or("erreur inconnue");
+            return Err(format!("{}: {}", method, message));
+        }
+        response.get("result").cloned().ok_or_else(|| format!("{}: réponse sans résultat", method))
+    }
+
+    pub async fn chain_id(&self) -> Result<u64, String> {
+        parse_quantity(&self.call("eth_chainId", serde_json::json!([])).await?).map(|v| v as u64)
+    }
+
+    pub async fn block_number(&self) -> Result<u64, String> {
+        parse_quantity(&self.call("eth_blockNumber", serde_json::json!([])).await?).map(|v| v as u64)
+    }
+
+    /// Nombre de transactions envoyées par l'adresse (`latest` ou `pending`)
+    pub async fn transaction_count(&self, address: &str, block: &str) -> Result<u64, String> {
+        parse_quantity(&self.call("eth_getTransactionCount", serde_json::json!([address, block])).await?)
+            .map(|v| v as u64)
+    }
+
+    pub async fn balance(&self, address: &str) -> Result<u128, String> {
+        parse_quantity(&self.call("eth_getBalance", serde_json::json!([address, "latest"])).await?)
+    }
+
+    /// Base fee du dernier bloc (EIP-1559)
+    pub async fn base_fee(&self) -> Result<u128, String> {
+        let block = self.call("eth_getBlockByNumber", serde_json::json!(["latest", false])).await?;
+        parse_quantity(block.get("baseFeePerGas").unwrap_or(&Value::Null))
+    }
+
+    pub async fn gas_price(&self) -> Result<u128, String> {
+        parse_quantity(&self.call("eth_gasPrice", serde_json::json!([])).await?)
+    }
+
+    pub async fn max_priority_fee(&self) -> Result<u128, String> {
+        parse_quantity(&self.call("eth_maxPriorityFeePerGas", serde_json::json!([])).await?)
+    }
+
+    pub async fn estimate_gas(&self, from: &str, to: &str, data: &str, value: u128) -> Result<u64, String> {
+        let call = serde_json::json!({
+            "from": from,
+            "to": to,
+            "data": data,
+            "value": format!("0x{:x}", value)
+        });
+        parse_quantity(&self.call("eth_estimateGas", serde_json::json!([call])).await?).map(|v| v as u64)
+    }
+
+    /// Appel en lecture seule d'un contrat au dernier bloc ; renvoie les données brutes
+    pub async fn eth_call(&self, to: &str, data: &str) -> Result<Vec<u8>, String> {
+        let call = serde_json::json!({ "to": to, "data": data });
+        let result = self.call("eth_call", serde_json::json!([call, "latest"])).await?;
+        let raw = result.as_str().ok_or_else(|| "eth_call: résultat absent".to_string())?;
+        hex::decode(raw.trim_start_matches("0x")).map_err(|_| format!("eth_call: résultat invalide: {}", raw))
+    }
+
+    /// Diffuse une transaction signée et renvoie son hash
+    pub async fn send_raw_transaction(&self, raw: &[u8]) -> Result<String, String> {
+        self.call("eth_sendRawTransaction", serde_json::json!([format!("0x{}", hex::encode(raw))]))
+            .await?
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "eth_sendRawTransaction: hash absent".to_string())
+    }
+
+    /// Événements d'un contrat entre deux blocs (inclus), filtrés sur le premier topic
+    pub async fn get_logs(&self, address: &str, topics: &[&str], from_block: u64, to_block: u64) -> Result<Vec<TxLog>, String> {
+        let filter = serde_json::json!({
+            "address": address,
+            "topics": [topics],
+            "fromBlock": format!("0x{:x}", from_block),
+            "toBlock": format!("0x{:x}", to_block)
+        });
+        let logs = self.call("eth_getLogs", serde_json::json!([filter])).await?;
+        Ok(logs.as_array().map(|logs| logs.iter().map(TxLog::from_json).collect()).unwrap_or_default())
+    }
+
+    /// Reçu de la transaction, `None` tant qu'elle n'est pas minée
+    pub async fn transaction_receipt(&self, tx_hash: &str) -> Result<Option<TxReceipt>, String> {
+        let receipt = self.call("eth_getTransactionReceipt", serde_json::json!([tx_hash])).await?;
+        if receipt.is_null() {
+            return Ok(None);
+        }
+        let logs = receipt.get("logs").and_then(Value::as_array).map(|logs| logs.iter().map(TxLog::from_json).collect());
+        Ok(Some(TxReceipt {
+            success: parse_quantity(receipt.get("status").unwrap_or(&Value::Null))? == 1,
+            block_number: parse_quantity(receipt.get("blockNumber").unwrap_or(&Value::Null))? as u64,
+            logs: logs.unwrap_or_default(),
+        }))
+    }
+}
+
+/// Mot ABI (uint256 en big-endian) vers sa représentation décimale
+pub fn uint256_to_decimal(word: &[u8]) -> String {
+    let mut bytes = word.to_vec();
+    let mut digits = Vec::new();
+    while bytes.iter().any(|b| *b != 0) {
+        let mut remainder = 0u32;
+        for byte in bytes.iter_mut() {
+            let value = (remainder << 8) | *byte as u32;
+            *byte = (value / 10) as u8;
+            remainder = value % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.iter().rev().map(|d| *d as char).collect()
+}
+
+/// Hash de transaction normalisé (`0x` + 64 caractères hexadécimaux, en minuscules)
+pub fn parse_tx_hash(raw: &str) -> Option<String> {
+    let hash = raw.trim().to_lowercase();
+    hash.strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .then_some(hash)
+}
+
+/// Quantité JSON-RPC (`0x` + hexadécimal) vers entier
+pub fn parse_quantity(value: &Value) -> Result<u128, String> {
+    let raw = value.as_str().ok_or_else(|| format!("Quantité attendue, reçu {}", value))?;
+    u128::from_str_radix(raw.trim_start_matches("0x"), 16).map_err(|_| format!("Quantité invalide: {}", raw))
+}
+
+/// Cache de l'état du réseau, partagé par toutes les requêtes
+#[derive(Clone)]
+pub struct ChainStatusCache {
+    inner: Cache<(), Value>,
+    /// Dernier état obtenu, servi tant que le disjoncteur du nœud est ouvert
+    last_known: Arc<Mutex<Option<Value>>>,
+}
+
+impl ChainStatusCache {
+    /// Durée de vie configurable via `CHAIN_STATUS_TTL_SECS` (5s par défaut)
+    pub fn from_env() -> Self {
+        let ttl = env::var("CHAIN_STATUS_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(5);
+
+        Self {
+            inner: Cache::builder()
+                .max_capacity(1)
+                .time_to_live(Duration::from_secs(ttl))
+                .build(),
+            last_known: Arc::default(),
+        }
+    }
+}
+
+async fn fetch_status(rpc: &ChainRpc, pool: &PgPool) -> Result<Value, String> {
+    let (chain_id, block_number, base_fee, gas_price, priority_fee) = tokio::try_join!(
+        rpc.chain_id(),
+        rpc.block_number(),
+        rpc.base_fee(),
+        rpc.gas_price(),
+        rpc.max_priority_fee(),
+    )?;
+
+    // Retard de l'indexation : écart entre le dernier bloc et le plus avancé des curseurs
+    let cursor = sqlx::query!(
+        r#"SELECT MAX(last_block) as "last_block", MAX(updated_at) as "updated_at" FROM indexer_cursors"#
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let indexer = cursor.last_block.map(|last_block| serde_json::json!({
+        "last_block": last_block,
+        "lag_blocks": block_number.saturating_sub(last_block as u64),
+        "updated_at": cursor.updated_at
+    }));
+
+    Ok(serde_json::json!({
+        "chain_id": chain_id,
+        "block_number": block_number,
+        "base_fee_per_gas": base_fee.to_string(),
+        "gas_price": gas_price.to_string(),
+        // Frais EIP-1559 suggérés : absorbent deux blocs pleins consécutifs
+        "max_priority_fee_per_gas": priority_fee.to_string(),
+        "max_fee_per_gas": (2 * base_fee + priority_fee).to_string(),
+        "indexer": indexer,
+        "fetched_at": Utc::now()
+    }))
+}
+
+/// Route publique : état du réseau et frais suggérés, mis en cache quelques
+/// secondes ; dernier état connu (`stale`) si le disjoncteur du nœud est ouvert
+pub async fn get_chain_status(
+    State(pool): State<PgPool>,
+    Extension(rpc): Extension<Option<ChainRpc>>,
+    Extension(cache): Extension<ChainStatusCache>,
+) -> impl IntoResponse {
+    let rpc = match rpc {
+        Some(rpc) => rpc,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Nœud Ethereum non configuré (CHAIN_RPC_URL)"
+        }))).into_response(),
+    };
+
+    if let Some(status) = cache.inner.get(&()) {
+        return (StatusCode::OK, Json(status)).into_response();
+    }
+
+    // Nœud indisponible : dernier état connu, signalé comme périmé
+    if let Err(e) = rpc.check() {
+        let last_known = cache.last_known.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        return match last_known {
+            Some(mut status) => {
+                status["stale"] = Value::Bool(true);
+                (StatusCode::OK, Json(status)).into_response()
+            },
+            None => e.response("Nœud Ethereum indisponible", None),
+        };
+    }
+
+    match fetch_status(&rpc, &pool).await {
+        Ok(status) => {
+            cache.inner.insert((), status.clone());
+            *cache.last_known.lock().unwrap_or_else(|e| e.into_inner()) = Some(status.clone());
+            (StatusCode::OK, Json(status)).into_response()
+        },
+        Err(e) => (StatusCode::BAD_GATEWAY, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'appel au nœud: {}", e)
+        }))).into_response(),
+    }
+}
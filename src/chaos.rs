@@ -0,0 +1,171 @@
+// chaos.rs
+//
+// Injection de fautes, pour vérifier la résistance des clients (nouvelles
+// tentatives, rejeu sans double écriture). Réservée aux builds de
+// développement : un build release l'ignore, sauf compilé avec la feature
+// `chaos`. Activée par `CHAOS_ENABLED=true`, elle applique les règles de
+// `CHAOS_RULES` route par route :
+//
+//   CHAOS_RULES="POST /api/investments latency=0.5 latency_ms=800 db_drop=0.1; * /api/properties* error=0.2"
+//
+// Chaque règle donne la méthode (`*` pour toutes), la route telle que
+// déclarée dans main.rs (`/api/properties/:id`, `*` final pour un préfixe) et
+// le taux de chaque faute :
+// - `latency` : attente tirée entre 0 et `latency_ms` (1000 par défaut) avant le handler ;
+// - `error` : réponse 500 sans exécuter le handler ;
+// - `db_drop` : le handler s'exécute, puis la réponse est remplacée par une
+//   perte de connexion à la base : les écritures sont conservées mais le
+//   client ne le sait pas, comme une connexion coupée avant l'acquittement.
+// La faute injectée est signalée par l'en-tête `X-Chaos-Fault`.
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, State},
+    http::{HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error_codes::ErrorCode;
+
+/// Attente maximale par défaut de la faute `latency`
+const DEFAULT_LATENCY_MS: u64 = 1000;
+
+struct ChaosRule {
+    /// `None` : toutes les méthodes
+    method: Option<String>,
+    route: String,
+    latency: f64,
+    latency_ms: u64,
+    error: f64,
+    db_drop: f64,
+}
+
+impl ChaosRule {
+    /// `<MÉTHODE> <route> <faute>=<valeur>...`
+    fn parse(raw: &str) -> Result<Self, String> {
+        let mut parts = raw.split_whitespace();
+        let (method, route) = match (parts.next(), parts.next()) {
+            (Some(method), Some(route)) if route.starts_with('/') => (method, route),
+            _ => return Err("méthode et route attendues".to_string()),
+        };
+        let mut rule = Self {
+            method: (method != "*").then(|| method.to_uppercase()),
+            route: route.to_string(),
+            latency: 0.0,
+            latency_ms: DEFAULT_LATENCY_MS,
+            error: 0.0,
+            db_drop: 0.0,
+        };
+        for part in parts {
+            let (key, value) = part.split_once('=').ok_or_else(|| format!("`{}` : `faute=valeur` attendu", part))?;
+            let rate = || value.parse::<f64>().ok().filter(|v| (0.0..=1.0).contains(v))
+                .ok_or_else(|| format!("`{}` : taux entre 0 et 1 attendu", part));
+            match key {
+                "latency" => rule.latency = rate()?,
+                "latency_ms" => rule.latency_ms = value.parse().map_err(|_| format!("`{}` : durée en ms attendue", part))?,
+                "error" => rule.error = rate()?,
+                "db_drop" => rule.db_drop = rate()?,
+                _ => return Err(format!("faute inconnue `{}` (latency, latency_ms, error, db_drop)", key)),
+            }
+        }
+        Ok(rule)
+    }
+
+    fn matches(&self, method: &str, route: &str) -> bool {
+        self.method.as_deref().is_none_or(|m| m == method)
+            && match self.route.strip_suffix('*') {
+                Some(prefix) => route.starts_with(prefix),
+                None => route == self.route,
+            }
+    }
+}
+
+#[derive(Clone)]
+pub struct Chaos {
+    rules: Arc<Vec<ChaosRule>>,
+}
+
+impl Chaos {
+    /// `None` si `CHAOS_ENABLED` n'est pas activé, si aucune règle n'est
+    /// valide, ou dans un build release compilé sans la feature `chaos`
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("CHAOS_ENABLED").is_ok_and(|v| v.trim().eq_ignore_ascii_case("true"));
+        if !enabled {
+            return None;
+        }
+        if !cfg!(any(debug_assertions, feature = "chaos")) {
+            println!("⚠️  CHAOS_ENABLED ignoré : build release compilé sans la feature `chaos`");
+            return None;
+        }
+
+        let mut rules = Vec::new();
+        for raw in env::var("CHAOS_RULES").unwrap_or_default().split(';').map(str::trim).filter(|r| !r.is_empty()) {
+            match ChaosRule::parse(raw) {
+                Ok(rule) => rules.push(rule),
+                Err(e) => println!("⚠️  CHAOS_RULES : règle ignorée ({}) : {}", e, raw),
+            }
+        }
+        (!rules.is_empty()).then(|| Self { rules: Arc::new(rules) })
+    }
+
+    /// Nombre de règles actives
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+}
+
+fn tagged(mut response: Response, fault: &'static str) -> Response {
+    response.headers_mut().insert("x-chaos-fault", HeaderValue::from_static(fault));
+    response
+}
+
+/// Middleware d'injection ; l'état est la configuration, `None` si inactive
+pub async fn inject_faults(
+    State(chaos): State<Option<Chaos>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let Some(chaos) = chaos else {
+        return next.run(request).await;
+    };
+    let route = request.extensions().get::<MatchedPath>()
+        .map(|m| m.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let Some(rule) = chaos.rules.iter().find(|rule| rule.matches(request.method().as_str(), &route)) else {
+        return next.run(request).await;
+    };
+
+    let latency = rand::random::<f64>() < rule.latency;
+    if latency {
+        let delay = rand::random::<u64>() % (rule.latency_ms + 1);
+        tracing::warn!(route, delay_ms = delay, "Faute injectée : latence");
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+    }
+
+    if rand::random::<f64>() < rule.error {
+        tracing::warn!(route, "Faute injectée : erreur 500");
+        return tagged((StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": "Erreur interne (faute injectée)",
+            "code": ErrorCode::InternalError
+        }))).into_response(), "error");
+    }
+
+    let db_drop = rand::random::<f64>() < rule.db_drop;
+    let response = next.run(request).await;
+    if db_drop {
+        tracing::warn!(route, status = response.status().as_u16(), "Faute injectée : connexion à la base perdue après le handler");
+        return tagged((StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": "Erreur de base de données : connexion perdue (faute injectée)",
+            "code": ErrorCode::DatabaseError
+        }))).into_response(), "db_drop");
+    }
+    if latency {
+        return tagged(response, "latency");
+    }
+    response
+}
@@ -0,0 +1,318 @@
+// compliance.rs
+//
+// Filtrage AML des wallets : chaque utilisateur est comparé aux sources de
+// sanctions configurées (fichier d'adresses, par exemple la liste OFAC, et
+// oracle on-chain de Chainalysis) à l'inscription puis périodiquement. Une
+// correspondance crée un signalement dans `compliance_flags` ; tant qu'il est
+// ouvert ou confirmé, l'utilisateur ne peut plus investir.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::chain::ChainRpc;
+use crate::eip712::{encode_address, keccak256, parse_address};
+use crate::envelope;
+use crate::models::{ComplianceFlag, FlagListQuery, FlagStatus, ReviewFlagRequest, UserRole};
+use crate::notifications::notify_tenant_admins;
+use crate::worker;
+
+/// Bilan d'un passage de filtrage
+#[derive(Default)]
+pub struct ScreeningStats {
+    pub screened: u64,
+    pub flagged: u64,
+    pub errors: u64,
+}
+
+/// Sources de sanctions, lues depuis l'environnement
+#[derive(Clone)]
+pub struct SanctionsScreener {
+    list_file: Option<PathBuf>,
+    oracle: Option<(ChainRpc, String)>,
+}
+
+impl SanctionsScreener {
+    /// Renvoie `None` si aucune source n'est configurée (`SANCTIONS_LIST_FILE`,
+    /// ou `SANCTIONS_ORACLE_ADDRESS` avec un nœud `CHAIN_RPC_URL`)
+    pub fn from_env(rpc: Option<ChainRpc>) -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let screener = Self {
+            list_file: var("SANCTIONS_LIST_FILE").map(PathBuf::from),
+            oracle: rpc.zip(var("SANCTIONS_ORACLE_ADDRESS").map(|address| address.to_lowercase())),
+        };
+        (screener.list_file.is_some() || screener.oracle.is_some()).then_some(screener)
+    }
+
+    /// Adresses du fichier (une par ligne, `#` pour les commentaires), relu à
+    /// chaque passage pour prendre en compte les mises à jour de la liste
+    async fn load_list(&self) -> Result<HashSet<String>, String> {
+        let path = match &self.list_file {
+            Some(path) => path,
+            None => return Ok(HashSet::new()),
+        };
+        let content = tokio::fs::read_to_string(path).await
+            .map_err(|e| format!("Lecture de {}: {}", path.display(), e))?;
+        Ok(content.lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim().to_lowercase())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+
+    /// `isSanctioned(address)` de l'oracle Chainalysis
+    async fn oracle_sanctioned(&self, rpc: &ChainRpc, oracle: &str, address: &[u8; 20]) -> Result<bool, String> {
+        let mut data = keccak256(b"isSanctioned(address)")[..4].to_vec();
+        data.extend_from_slice(&encode_address(address));
+        let result = rpc.eth_call(oracle, &format!("0x{}", hex::encode(data))).await?;
+        Ok(result.iter().any(|b| *b != 0))
+    }
+
+    /// Filtre les wallets des utilisateurs donnés (tous si `None`), limités à
+    /// une plateforme si `tenant_id` est renseigné
+    pub async fn screen_users(&self, pool: &PgPool, user_ids: Option<&[Uuid]>, tenant_id: Option<Uuid>) -> Result<ScreeningStats, String> {
+        let list = self.load_list().await?;
+        let users = sqlx::query!(
+            r#"SELECT id, tenant_id, wallet FROM users
+               WHERE ($1::uuid[] IS NULL OR id = ANY($1)) AND ($2::uuid IS NULL OR tenant_id = $2)"#,
+            user_ids,
+            tenant_id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let mut stats = ScreeningStats::default();
+        for user in users {
+            stats.screened += 1;
+            let wallet = user.wallet.trim().to_lowercase();
+            let mut matches = Vec::new();
+
+            if list.contains(&wallet) {
+                matches.push(("sanctions_list", "Wallet présent dans la liste de sanctions".to_string()));
+            }
+            if let (Some((rpc, oracle)), Some(address)) = (&self.oracle, parse_address(&wallet)) {
+                match self.oracle_sanctioned(rpc, oracle, &address).await {
+                    Ok(true) => matches.push(("chainalysis_oracle", "Wallet sanctionné selon l'oracle Chainalysis".to_string())),
+                    Ok(false) => {},
+                    Err(e) => {
+                        tracing::warn!("Filtrage AML: oracle indisponible pour {}: {}", wallet, e);
+                        stats.errors += 1;
+                    },
+                }
+            }
+
+            for (source, reason) in matches {
+                match raise_flag(pool, user.tenant_id, user.id, &wallet, source, &reason).await {
+                    Ok(true) => stats.flagged += 1,
+                    Ok(false) => {},
+                    Err(e) => {
+                        tracing::error!("Filtrage AML: signalement de {} impossible: {}", wallet, e);
+                        stats.errors += 1;
+                    },
+                }
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Crée le signalement et prévient les admins de la plateforme ; `false` s'il
+/// existait déjà (y compris levé : un faux positif n'est pas signalé à nouveau)
+async fn raise_flag(pool: &PgPool, tenant_id: Uuid, user_id: Uuid, wallet: &str, source: &str, reason: &str) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let flag_id = sqlx::query_scalar!(
+        r#"INSERT INTO compliance_flags (user_id, wallet, source, reason) VALUES ($1, $2, $3, $4)
+           ON CONFLICT (user_id, wallet, source) DO NOTHING
+           RETURNING id"#,
+        user_id,
+        wallet,
+        source,
+        reason
+    )
+    .fetch_optional(&mut tx)
+    .await?;
+
+    if let Some(flag_id) = flag_id {
+        notify_tenant_admins(&mut tx, tenant_id, None, "compliance_flag", "Nouveau signalement de conformité à examiner", serde_json::json!({
+            "flag_id": flag_id,
+            "user_id": user_id,
+            "wallet": wallet,
+            "source": source
+        }))
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(flag_id.is_some())
+}
+
+/// Lance le filtrage périodique de tous les wallets. Intervalle configurable
+/// via `SANCTIONS_SCREENING_INTERVAL_SECS` (24h par défaut).
+pub fn spawn(pool: PgPool, screener: SanctionsScreener) {
+    let interval_secs = env::var("SANCTIONS_SCREENING_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(86_400);
+
+    tokio::spawn(async move {
+        let period = Duration::from_secs(interval_secs);
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match worker::run_exclusive(&pool, "compliance_screening", period, screener.screen_users(&pool, None, None)).await {
+                Some(Ok(stats)) if stats.flagged > 0 || stats.errors > 0 => tracing::warn!(
+                    "Filtrage AML: {} wallet(s) filtré(s), {} signalement(s), {} erreur(s)",
+                    stats.screened, stats.flagged, stats.errors
+                ),
+                None | Some(Ok(_)) => {},
+                Some(Err(e)) => tracing::error!("Erreur du filtrage AML: {}", e),
+            }
+        }
+    });
+}
+
+/// Filtrage d'un nouvel utilisateur, en tâche de fond pour ne pas retarder l'inscription
+pub fn screen_new_user(pool: PgPool, screener: Option<SanctionsScreener>, user_id: Uuid) {
+    if let Some(screener) = screener {
+        tokio::spawn(async move {
+            if let Err(e) = screener.screen_users(&pool, Some(&[user_id]), None).await {
+                tracing::error!("Erreur du filtrage AML à l'inscription: {}", e);
+            }
+        });
+    }
+}
+
+/// Refuse (403) toute création d'investissement pour un utilisateur faisant
+/// l'objet d'un signalement ouvert ou confirmé
+pub async fn ensure_not_flagged(pool: &PgPool, user_id: Uuid) -> Result<(), Response> {
+    let flagged = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM compliance_flags WHERE user_id = $1 AND status IN ('open', 'confirmed')) as "flagged!""#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de la vérification de conformité: {}", e)
+    }))).into_response())?;
+
+    if flagged {
+        return Err((StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Investissement impossible : votre compte fait l'objet d'une vérification de conformité"
+        }))).into_response());
+    }
+    Ok(())
+}
+
+/// Route admin : file des signalements (ouverts d'abord, `?status=` pour filtrer)
+pub async fn get_compliance_flags(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<FlagListQuery>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent consulter les signalements de conformité"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        ComplianceFlag,
+        r#"SELECT id, user_id, wallet, source, reason, status as "status: FlagStatus",
+                  reviewed_by, reviewed_at, review_note, created_at
+           FROM compliance_flags
+           WHERE ($1::flag_status IS NULL OR status = $1)
+           AND user_id IN (SELECT id FROM users WHERE tenant_id = $2)
+           ORDER BY status = 'open' DESC, created_at DESC"#,
+        query.status as Option<FlagStatus>,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(flags) => envelope::list("flags", &flags).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : lever (`cleared`), confirmer (`confirmed`) ou rouvrir un signalement
+pub async fn review_compliance_flag(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(flag_id): Path<Uuid>,
+    Json(payload): Json<ReviewFlagRequest>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent examiner les signalements de conformité"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        ComplianceFlag,
+        r#"UPDATE compliance_flags
+           SET status = $2, review_note = $3, reviewed_by = $4, reviewed_at = NOW()
+           WHERE id = $1 AND user_id IN (SELECT id FROM users WHERE tenant_id = $5)
+           RETURNING id, user_id, wallet, source, reason, status as "status: FlagStatus",
+                     reviewed_by, reviewed_at, review_note, created_at"#,
+        flag_id,
+        payload.status as FlagStatus,
+        payload.note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty()),
+        user.id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(flag)) => (StatusCode::OK, Json(serde_json::json!({
+            "flag": flag,
+            "message": "Signalement mis à jour"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Signalement non trouvé"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : lance immédiatement un filtrage de tous les wallets de la plateforme
+pub async fn run_screening(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(screener): Extension<Option<SanctionsScreener>>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent lancer le filtrage"
+        }))).into_response();
+    }
+    let screener = match screener {
+        Some(screener) => screener,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Filtrage AML non configuré (SANCTIONS_LIST_FILE ou SANCTIONS_ORACLE_ADDRESS)"
+        }))).into_response(),
+    };
+
+    match screener.screen_users(&pool, None, Some(user.tenant_id)).await {
+        Ok(stats) => (StatusCode::OK, Json(serde_json::json!({
+            "screened": stats.screened,
+            "flagged": stats.flagged,
+            "errors": stats.errors
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du filtrage: {}", e)
+        }))).into_response(),
+    }
+}
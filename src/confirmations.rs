@@ -0,0 +1,214 @@
+// confirmations.rs
+//
+// Confirmation explicite des opérations dangereuses (suppression d'une
+// propriété, changement de rôle) : sans en-tête `X-Confirmation-Token`,
+// l'endpoint répond 428 avec un défi (action + cible). Le client l'échange via
+// `POST /api/confirmations` contre un jeton à usage unique, valable
+// `CONFIRMATION_TOKEN_SECS` secondes, puis rejoue la requête avec ce jeton.
+// Le second facteur (voir two_factor.rs) est vérifié à cette étape, ainsi que
+// la signature du wallet si `CONFIRMATIONS_REQUIRE_SIGNATURE=true`.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::env;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::brute_force::{self, AuthLockout};
+use crate::eip712;
+use crate::models::ConfirmActionRequest;
+use crate::risk::{self, RiskRules};
+use crate::two_factor;
+use crate::wallets;
+
+/// Durée de validité d'un défi non confirmé, en secondes
+const CHALLENGE_TTL_SECS: i64 = 300;
+
+fn token_ttl_secs() -> i64 {
+    env::var("CONFIRMATION_TOKEN_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(120)
+}
+
+fn signature_required() -> bool {
+    env::var("CONFIRMATIONS_REQUIRE_SIGNATURE").is_ok_and(|v| v == "true" || v == "1")
+}
+
+/// Message à signer (personal_sign) pour confirmer un défi
+fn challenge_message(challenge_id: uuid::Uuid, action: &str, target: &str) -> String {
+    format!("Confirmer l'action {} sur {}\nDéfi : {}", action, target, challenge_id)
+}
+
+fn internal(e: sqlx::Error) -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de la vérification de la confirmation: {}", e)
+    }))).into_response()
+}
+
+/// À appeler au début des opérations dangereuses : sans jeton valide pour
+/// cette action et cette cible, `Err` contient la réponse 428 avec un
+/// nouveau défi. Un jeton accepté est consommé.
+pub async fn ensure_confirmed(
+    pool: &PgPool,
+    user: &SessionUser,
+    headers: &HeaderMap,
+    action: &str,
+    target: &str,
+) -> Result<(), Response> {
+    let token = headers
+        .get("X-Confirmation-Token")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+
+    if let Some(token) = token {
+        let consumed = sqlx::query!(
+            r#"UPDATE action_confirmations SET used_at = NOW()
+               WHERE token_hash = $1 AND user_id = $2 AND action = $3 AND target = $4
+                 AND used_at IS NULL AND expires_at > NOW()"#,
+            two_factor::hash_secret(token),
+            user.id,
+            action,
+            target
+        )
+        .execute(pool)
+        .await
+        .map_err(internal)?;
+        if consumed.rows_affected() == 1 {
+            return Ok(());
+        }
+    }
+
+    let expires_at = Utc::now() + Duration::seconds(CHALLENGE_TTL_SECS);
+    // Les défis et jetons expirés sont purgés au passage
+    sqlx::query!("DELETE FROM action_confirmations WHERE expires_at < NOW() - INTERVAL '1 day'")
+        .execute(pool)
+        .await
+        .map_err(internal)?;
+    let challenge_id = sqlx::query_scalar!(
+        "INSERT INTO action_confirmations (user_id, action, target, expires_at) VALUES ($1, $2, $3, $4) RETURNING id",
+        user.id,
+        action,
+        target,
+        expires_at
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(internal)?;
+
+    let error = if token.is_some() {
+        "Jeton de confirmation invalide, expiré ou déjà utilisé"
+    } else {
+        "Confirmation requise : obtenir un jeton via POST /api/confirmations"
+    };
+    Err((StatusCode::PRECONDITION_REQUIRED, Json(serde_json::json!({
+        "error": error,
+        "confirmation_required": true,
+        "challenge_id": challenge_id,
+        "action": action,
+        "target": target,
+        "expires_at": expires_at,
+        "signature_required": signature_required(),
+        "message_to_sign": challenge_message(challenge_id, action, target)
+    }))).into_response())
+}
+
+/// Route : confirmer un défi et obtenir le jeton à joindre à la requête
+/// (en-tête `X-Confirmation-Token`)
+pub async fn create_confirmation(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(rules): Extension<RiskRules>,
+    Extension(lockout): Extension<AuthLockout>,
+    headers: HeaderMap,
+    Json(payload): Json<ConfirmActionRequest>,
+) -> impl IntoResponse {
+    let challenge = match sqlx::query!(
+        r#"SELECT id, action, target FROM action_confirmations
+           WHERE id = $1 AND user_id = $2 AND token_hash IS NULL AND expires_at > NOW()"#,
+        payload.challenge_id,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(challenge)) => challenge,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Défi introuvable, expiré ou déjà confirmé"
+        }))).into_response(),
+        Err(e) => return internal(e),
+    };
+
+    if let Err(response) = two_factor::ensure_second_factor(&pool, &user, &headers).await {
+        return response;
+    }
+
+    if signature_required() || payload.signature.is_some() {
+        // Wallet ou adresse IP bloqués après des signatures invalides répétées
+        let ip = risk::client_ip(&headers);
+        match lockout.is_locked(&pool, Some(user.wallet.as_str()), ip.as_deref()).await {
+            Ok(true) => return (StatusCode::TOO_MANY_REQUESTS, Json(serde_json::json!({
+                "error": brute_force::LOCKED_MESSAGE
+            }))).into_response(),
+            Ok(false) => {},
+            Err(e) => return internal(e),
+        }
+
+        let Some(signature) = payload.signature.as_deref() else {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "error": "Signature du message de confirmation requise",
+                "message_to_sign": challenge_message(challenge.id, &challenge.action, &challenge.target)
+            }))).into_response();
+        };
+        let digest = eip712::personal_message_digest(&challenge_message(challenge.id, &challenge.action, &challenge.target));
+        let signer = match eip712::recover_signer(&digest, signature) {
+            Ok(address) => eip712::format_address(&address),
+            Err(message) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "error": message
+            }))).into_response(),
+        };
+        // Le wallet principal ou un wallet rattaché vérifié
+        let owned = match wallets::linked_wallets(&pool, user.id).await {
+            Ok(linked) => signer == user.wallet.as_str() || linked.contains(&signer),
+            Err(e) => return internal(e),
+        };
+        if !owned {
+            risk::auth_failed(pool, rules, user.wallet.as_str().to_string(), ip, "bad_signature");
+            return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({
+                "error": "La signature ne provient pas d'un wallet de ce compte"
+            }))).into_response();
+        }
+    }
+
+    let token = hex::encode(two_factor::random_bytes::<32>());
+    let expires_at = Utc::now() + Duration::seconds(token_ttl_secs());
+    match sqlx::query!(
+        r#"UPDATE action_confirmations SET token_hash = $2, confirmed_at = NOW(), expires_at = $3
+           WHERE id = $1 AND token_hash IS NULL"#,
+        challenge.id,
+        two_factor::hash_secret(&token),
+        expires_at
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Défi déjà confirmé"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "token": token,
+            "action": challenge.action,
+            "target": challenge.target,
+            "expires_at": expires_at
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la confirmation: {}", e)
+        }))).into_response(),
+    }
+}
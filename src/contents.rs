@@ -0,0 +1,293 @@
+// contents.rs
+//
+// Petit CMS : FAQ, avertissements sur les risques et annonces sont rédigés en
+// Markdown par les admins de chaque plateforme et servis par le backend
+// plutôt que codés en dur dans le frontend. Un contenu est identifié par son
+// slug et sa langue ; seuls les contenus publiés sont exposés publiquement,
+// dans la langue demandée ou à défaut dans la langue par défaut.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{Content, ContentListQuery, ContentQuery, CreateContentRequest, Tenant, UpdateContentRequest, UserRole};
+use crate::slug::slugify;
+use crate::tags::is_unique_violation;
+
+/// Langue servie quand la langue demandée n'existe pas
+const DEFAULT_LOCALE: &str = "fr";
+
+fn admin_only(role: &UserRole) -> Option<Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer les contenus"
+    }))).into_response())
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+        "error": message
+    }))).into_response()
+}
+
+/// Langue normalisée (`fr`, `en`, `pt-br`...) ou `None` si invalide
+fn normalize_locale(locale: &str) -> Option<String> {
+    let locale = locale.trim().to_lowercase().replace('_', "-");
+    let valid = (2..=10).contains(&locale.len())
+        && locale.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+        && !locale.starts_with('-')
+        && !locale.ends_with('-');
+    valid.then_some(locale)
+}
+
+/// Vérifie un slug fourni par l'admin (déjà sous forme de slug)
+fn valid_slug(slug: &str) -> bool {
+    !slug.is_empty() && slugify(slug) == slug
+}
+
+fn content_error(e: sqlx::Error) -> Response {
+    if is_unique_violation(&e) {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Un contenu existe déjà pour ce slug et cette langue"
+        }))).into_response();
+    }
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de l'enregistrement du contenu: {}", e)
+    }))).into_response()
+}
+
+/// Route admin : contenus de la plateforme (`?locale=`, `?published=`)
+pub async fn get_contents(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<ContentListQuery>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        Content,
+        r#"SELECT * FROM contents
+           WHERE tenant_id = $1
+           AND ($2::text IS NULL OR locale = $2)
+           AND ($3::boolean IS NULL OR published = $3)
+           ORDER BY slug, locale"#,
+        admin.tenant_id,
+        query.locale,
+        query.published
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(contents) => envelope::list("contents", &contents).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : détail d'un contenu (brouillon compris)
+pub async fn get_content(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        Content,
+        "SELECT * FROM contents WHERE id = $1 AND tenant_id = $2",
+        id,
+        admin.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(content)) => (StatusCode::OK, Json(content)).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Contenu non trouvé"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : créer un contenu (brouillon sauf `published: true`)
+pub async fn create_content(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateContentRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+    if !valid_slug(&payload.slug) {
+        return bad_request("Slug invalide (minuscules, chiffres et tirets uniquement)");
+    }
+    let Some(locale) = normalize_locale(payload.locale.as_deref().unwrap_or(DEFAULT_LOCALE)) else {
+        return bad_request("Langue invalide (ex. fr, en, pt-br)");
+    };
+    if payload.title.trim().is_empty() {
+        return bad_request("Le titre est requis");
+    }
+
+    match sqlx::query_as!(
+        Content,
+        r#"INSERT INTO contents (tenant_id, slug, locale, title, body, published, created_by, updated_by)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+           RETURNING *"#,
+        admin.tenant_id,
+        payload.slug,
+        locale,
+        payload.title.trim(),
+        payload.body,
+        payload.published.unwrap_or(false),
+        admin.id
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(content) => (StatusCode::CREATED, Json(serde_json::json!({
+            "content": content,
+            "message": "Contenu créé"
+        }))).into_response(),
+        Err(e) => content_error(e),
+    }
+}
+
+/// Route admin : modifier un contenu ; les champs absents restent inchangés
+pub async fn update_content(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateContentRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+    if payload.slug.as_deref().is_some_and(|slug| !valid_slug(slug)) {
+        return bad_request("Slug invalide (minuscules, chiffres et tirets uniquement)");
+    }
+    let locale = match payload.locale.as_deref().map(normalize_locale) {
+        Some(None) => return bad_request("Langue invalide (ex. fr, en, pt-br)"),
+        Some(locale) => locale,
+        None => None,
+    };
+    if payload.title.as_deref().is_some_and(|title| title.trim().is_empty()) {
+        return bad_request("Le titre est requis");
+    }
+
+    match sqlx::query_as!(
+        Content,
+        r#"UPDATE contents SET
+               slug = COALESCE($3, slug),
+               locale = COALESCE($4, locale),
+               title = COALESCE($5, title),
+               body = COALESCE($6, body),
+               published = COALESCE($7, published),
+               updated_by = $8,
+               updated_at = NOW()
+           WHERE id = $1 AND tenant_id = $2
+           RETURNING *"#,
+        id,
+        admin.tenant_id,
+        payload.slug,
+        locale,
+        payload.title.as_deref().map(str::trim),
+        payload.body,
+        payload.published,
+        admin.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(content)) => (StatusCode::OK, Json(serde_json::json!({
+            "content": content,
+            "message": "Contenu mis à jour"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Contenu non trouvé"
+        }))).into_response(),
+        Err(e) => content_error(e),
+    }
+}
+
+/// Route admin : supprimer un contenu
+pub async fn delete_content(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    match sqlx::query!(
+        "DELETE FROM contents WHERE id = $1 AND tenant_id = $2",
+        id,
+        admin.tenant_id
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Contenu non trouvé"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Contenu supprimé"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route publique : contenu publié de la plateforme, dans la langue `?locale=`
+/// si elle existe, sinon dans la langue par défaut, sinon dans la première
+/// langue disponible
+pub async fn get_public_content(
+    State(pool): State<PgPool>,
+    Extension(tenant): Extension<Tenant>,
+    Path(slug): Path<String>,
+    Query(query): Query<ContentQuery>,
+) -> impl IntoResponse {
+    let locale = query.locale.as_deref().and_then(normalize_locale).unwrap_or_else(|| DEFAULT_LOCALE.to_string());
+
+    match sqlx::query!(
+        r#"SELECT slug, locale, title, body, updated_at FROM contents
+           WHERE tenant_id = $1 AND slug = $2 AND published
+           ORDER BY locale = $3 DESC, locale = $4 DESC, locale
+           LIMIT 1"#,
+        tenant.id,
+        slug,
+        locale,
+        DEFAULT_LOCALE
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(row)) => (
+            StatusCode::OK,
+            [(header::CACHE_CONTROL, "public, max-age=300")],
+            Json(serde_json::json!({
+                "slug": row.slug,
+                "locale": row.locale,
+                "title": row.title,
+                "body": row.body,
+                "format": "markdown",
+                "updated_at": row.updated_at
+            })),
+        ).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Contenu non trouvé"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
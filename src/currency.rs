@@ -0,0 +1,168 @@
+// currency.rs
+//
+// Devise préférée de l'utilisateur (`users.preferred_currency`, EUR par
+// défaut). Les réponses des investissements et du portefeuille donnent, en
+// plus du montant en ETH, sa contre-valeur dans cette devise au dernier cours
+// relevé (voir rates.rs), avec le cours et l'heure du relevé utilisés, et les
+// métadonnées d'affichage de la devise (symbole, décimales, locale).
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::activity;
+use crate::auth::BearerAuthUser;
+use crate::models::{Investment, UpdateCurrencyRequest};
+use crate::money::TokenAmount;
+use crate::rates::CURRENCIES;
+
+/// Devise de l'utilisateur et dernier cours relevé pour celle-ci
+pub struct Valuation {
+    pub currency: String,
+    rate: Option<(BigDecimal, DateTime<Utc>)>,
+}
+
+impl Valuation {
+    /// Contre-valeur d'un montant en ETH (`null` si aucun cours n'a été relevé),
+    /// arrondie au centime le plus proche (voir money.rs)
+    pub fn value(&self, amount_eth: &TokenAmount) -> Value {
+        match &self.rate {
+            Some((rate, rate_at)) => serde_json::json!({
+                "currency": self.currency,
+                "amount": amount_eth.to_fiat(rate),
+                "rate": rate,
+                "rate_at": rate_at
+            }),
+            None => Value::Null,
+        }
+    }
+
+    /// Métadonnées d'affichage de la devise
+    pub fn format(&self) -> Value {
+        format_metadata(&self.currency)
+    }
+}
+
+/// Symbole, nombre de décimales et locale de formatage d'une devise
+fn format_metadata(currency: &str) -> Value {
+    let (symbol, locale) = match currency {
+        "USD" => ("$", "en-US"),
+        _ => ("€", "fr-FR"),
+    };
+    serde_json::json!({
+        "code": currency,
+        "symbol": symbol,
+        "decimals": 2,
+        "locale": locale
+    })
+}
+
+/// Devise préférée de l'utilisateur et dernier cours relevé
+pub async fn valuation_for<'e, E: sqlx::PgExecutor<'e>>(executor: E, user_id: Uuid) -> Result<Valuation, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT u.preferred_currency, r.eth_rate as "eth_rate?", r.fetched_at as "fetched_at?"
+           FROM users u
+           LEFT JOIN LATERAL (
+               SELECT eth_rate, fetched_at FROM exchange_rates
+               WHERE currency = u.preferred_currency
+               ORDER BY fetched_at DESC
+               LIMIT 1
+           ) r ON TRUE
+           WHERE u.id = $1"#,
+        user_id
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(match row {
+        Some(row) => Valuation {
+            currency: row.preferred_currency,
+            rate: row.eth_rate.zip(row.fetched_at),
+        },
+        None => Valuation { currency: CURRENCIES[0].to_string(), rate: None },
+    })
+}
+
+/// Investissement accompagné de sa contre-valeur (`fiat`), pour les réponses de
+/// création ; un échec de lecture du cours n'empêche pas la réponse
+pub async fn investment_with_fiat(pool: &PgPool, user_id: Uuid, investment: &Investment) -> Value {
+    let mut value = serde_json::json!(investment);
+    value["fiat"] = match valuation_for(pool, user_id).await {
+        Ok(valuation) => valuation.value(&investment.amount_eth),
+        Err(e) => {
+            tracing::error!("Erreur lors de la lecture du cours de l'ETH: {}", e);
+            Value::Null
+        },
+    };
+    value
+}
+
+fn currency_response(valuation: &Valuation) -> Value {
+    serde_json::json!({
+        "preferred_currency": valuation.currency,
+        "format": valuation.format(),
+        "rate": valuation.rate.as_ref().map(|(rate, rate_at)| serde_json::json!({
+            "eth_rate": rate,
+            "rate_at": rate_at
+        })),
+        "available_currencies": CURRENCIES
+    })
+}
+
+/// Route : devise préférée, métadonnées d'affichage et dernier cours
+pub async fn get_my_currency(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match valuation_for(&pool, user.id).await {
+        Ok(valuation) => (StatusCode::OK, Json(currency_response(&valuation))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : changer de devise préférée
+pub async fn update_my_currency(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<UpdateCurrencyRequest>,
+) -> impl IntoResponse {
+    let currency = payload.currency.trim().to_uppercase();
+    if !CURRENCIES.contains(&currency.as_str()) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Devise non prise en charge (disponibles : {})", CURRENCIES.join(", "))
+        }))).into_response();
+    }
+
+    let result = async {
+        sqlx::query!(
+            "UPDATE users SET preferred_currency = $1 WHERE id = $2",
+            currency,
+            user.id
+        )
+        .execute(&pool)
+        .await?;
+        valuation_for(&pool, user.id).await
+    }.await;
+
+    match result {
+        Ok(valuation) => {
+            activity::record_detached(pool.clone(), user.id, None, "currency_changed", serde_json::json!({
+                "currency": currency
+            }), None);
+            (StatusCode::OK, Json(currency_response(&valuation))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
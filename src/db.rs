@@ -1,28 +1,297 @@
 // db.rs
+//
+// Pool de connexions. Le pool est créé sans attendre la base : le serveur
+// démarre aussitôt et `/health` signale `db: down` tant que la connexion n'est
+// pas établie. Une tâche de fond tente la connexion avec un délai croissant
+// (`DB_CONNECT_MAX_ATTEMPTS` essais, 0 pour réessayer indéfiniment) et arrête
+// le processus si la base reste injoignable ; elle vérifie ensuite la base
+// périodiquement. L'attente d'une connexion du pool est mesurée par ces
+// vérifications et par les emprunts des requêtes passant par
+// `DbHealth::acquire` (authentification, clés d'API) ou par la transaction de
+// requête (voir db_tx.rs) ; elle est exposée par `/metrics` et journalisée
+// au-delà de `DB_SLOW_ACQUIRE_MS`.
+// Pendant une double écriture, la base servie est choisie par dual_write.rs.
 
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension,
+};
+use sqlx::pool::PoolConnection;
 use sqlx::postgres::PgPoolOptions;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres};
 use std::env;
-use std::time::Duration;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use crate::dual_write::{self, DualWrite};
 use crate::models::UserRole;
 
-pub async fn init_db() -> PgPool {
+fn env_u64(name: &str, default: u64) -> u64 {
+    env::var(name).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+}
+
+/// Durée en secondes lue dans l'environnement, `None` si 0 (pas de limite)
+fn env_secs(name: &str, default: u64) -> Option<Duration> {
+    Some(env_u64(name, default)).filter(|secs| *secs > 0).map(Duration::from_secs)
+}
+
+/// Réglages du pool, par déploiement
+struct PoolConfig {
+    max_connections: u32,
+    min_connections: u32,
+    acquire_timeout: Duration,
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+}
+
+impl PoolConfig {
+    /// `DB_POOL_MAX_CONNECTIONS` (10), `DB_POOL_MIN_CONNECTIONS` (0),
+    /// `DB_ACQUIRE_TIMEOUT_SECS` (3), `DB_IDLE_TIMEOUT_SECS` (600) et
+    /// `DB_MAX_LIFETIME_SECS` (1800) ; 0 désactive les deux derniers
+    fn from_env() -> Self {
+        let max_connections = env_u64("DB_POOL_MAX_CONNECTIONS", 10).clamp(1, u32::MAX as u64) as u32;
+        Self {
+            max_connections,
+            min_connections: (env_u64("DB_POOL_MIN_CONNECTIONS", 0) as u32).min(max_connections),
+            acquire_timeout: Duration::from_secs(env_u64("DB_ACQUIRE_TIMEOUT_SECS", 3).max(1)),
+            idle_timeout: env_secs("DB_IDLE_TIMEOUT_SECS", 600),
+            max_lifetime: env_secs("DB_MAX_LIFETIME_SECS", 1800),
+        }
+    }
+}
+
+/// Attente d'une connexion mesurée par les vérifications et les requêtes
+#[derive(Default)]
+struct AcquireStats {
+    samples: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+    last_us: AtomicU64,
+    slow: AtomicU64,
+}
+
+/// Disponibilité de la base et attente des connexions, partagées avec
+/// `/health`, `/metrics` et les chargements dépendant de la base au démarrage
+#[derive(Clone)]
+pub struct DbHealth {
+    up: Arc<watch::Sender<bool>>,
+    acquire: Arc<AcquireStats>,
+    /// Seuil de journalisation d'une attente (`DB_SLOW_ACQUIRE_MS`, 500 ms)
+    slow_acquire: Duration,
+    max_connections: u32,
+}
+
+impl DbHealth {
+    pub fn is_up(&self) -> bool {
+        *self.up.borrow()
+    }
+
+    /// Attend que la base soit joignable
+    pub async fn wait_up(&self) {
+        let mut rx = self.up.subscribe();
+        let _ = rx.wait_for(|up| *up).await;
+    }
+
+    fn set(&self, up: bool) {
+        self.up.send_replace(up);
+    }
+
+    /// Connexion du pool, dont l'attente est mesurée
+    pub async fn acquire(&self, pool: &PgPool) -> Result<PoolConnection<Postgres>, sqlx::Error> {
+        let started = Instant::now();
+        let conn = pool.acquire().await?;
+        self.record_acquire(pool, started.elapsed());
+        Ok(conn)
+    }
+
+    /// Enregistre l'attente d'une connexion, journalisée si elle est anormalement longue
+    pub fn record_acquire(&self, pool: &PgPool, wait: Duration) {
+        let slow = wait >= self.slow_acquire;
+        if slow {
+            tracing::warn!(
+                wait_ms = wait.as_millis() as u64,
+                in_use = (pool.size() as usize).saturating_sub(pool.num_idle()),
+                idle = pool.num_idle(),
+                "Attente anormalement longue d'une connexion du pool"
+            );
+        }
+        let us = wait.as_micros() as u64;
+        self.acquire.samples.fetch_add(1, Ordering::Relaxed);
+        self.acquire.total_us.fetch_add(us, Ordering::Relaxed);
+        self.acquire.max_us.fetch_max(us, Ordering::Relaxed);
+        self.acquire.last_us.store(us, Ordering::Relaxed);
+        if slow {
+            self.acquire.slow.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+pub fn init_db(dual_write: Option<&DualWrite>) -> (PgPool, DbHealth) {
     // Récupérer l'URL de connexion à Supabase
-    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    
-    // Créer le pool de connexions avec des options avancées
-    PgPoolOptions::new()
-        .max_connections(10)
-        .acquire_timeout(Duration::from_secs(3))
-        .connect(&db_url)
-        .await
-        .expect("Failed to connect to Supabase database")
+    let db_url = match dual_write {
+        Some(dual_write) => dual_write.serving_url().to_string(),
+        None => env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+    };
+    let config = PoolConfig::from_env();
+
+    // Créer le pool de connexions (connexions ouvertes à la demande), dont les
+    // écritures sont consignées pendant une double écriture
+    let options = match dual_write {
+        Some(_) => dual_write::capture_options(PgPoolOptions::new()),
+        None => PgPoolOptions::new(),
+    };
+    let pool = options
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(config.acquire_timeout)
+        .idle_timeout(config.idle_timeout)
+        .max_lifetime(config.max_lifetime)
+        .connect_lazy(&db_url)
+        .expect("Invalid DATABASE_URL");
+
+    let health = DbHealth {
+        up: Arc::new(watch::channel(false).0),
+        acquire: Arc::default(),
+        slow_acquire: Duration::from_millis(env_u64("DB_SLOW_ACQUIRE_MS", 500)),
+        max_connections: config.max_connections,
+    };
+    spawn_monitor(pool.clone(), health.clone());
+    (pool, health)
+}
+
+/// Vérifie la base ; l'attente d'une connexion est mesurée une fois la base joignable
+async fn ping(pool: &PgPool, health: &DbHealth) -> Result<(), sqlx::Error> {
+    let started = Instant::now();
+    let mut conn = pool.acquire().await?;
+    if health.is_up() {
+        health.record_acquire(pool, started.elapsed());
+    }
+    sqlx::query("SELECT 1").execute(&mut conn).await.map(|_| ())
+}
+
+/// Connexion initiale avec délai croissant (`DB_CONNECT_BACKOFF_MS`, 500 par
+/// défaut, doublé à chaque échec jusqu'à `DB_CONNECT_MAX_BACKOFF_SECS`, 30),
+/// puis vérification toutes les `DB_HEALTH_INTERVAL_SECS` secondes (15)
+fn spawn_monitor(pool: PgPool, health: DbHealth) {
+    let max_attempts = env_u64("DB_CONNECT_MAX_ATTEMPTS", 10);
+    let initial_backoff = Duration::from_millis(env_u64("DB_CONNECT_BACKOFF_MS", 500).max(1));
+    let max_backoff = Duration::from_secs(env_u64("DB_CONNECT_MAX_BACKOFF_SECS", 30)).max(initial_backoff);
+    let check_interval = Duration::from_secs(env_u64("DB_HEALTH_INTERVAL_SECS", 15).max(1));
+
+    tokio::spawn(async move {
+        let mut backoff = initial_backoff;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match ping(&pool, &health).await {
+                Ok(()) => {
+                    tracing::info!(attempt, "Connexion à la base de données établie");
+                    health.set(true);
+                    break;
+                },
+                Err(e) if max_attempts > 0 && attempt >= max_attempts => {
+                    tracing::error!(attempt, max_attempts, error = %e, "Base de données injoignable, arrêt du serveur");
+                    std::process::exit(1);
+                },
+                Err(e) => {
+                    tracing::warn!(
+                        attempt,
+                        max_attempts,
+                        retry_in_ms = backoff.as_millis() as u64,
+                        error = %e,
+                        "Échec de la connexion à la base de données"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(max_backoff);
+                },
+            }
+        }
+
+        let mut interval = tokio::time::interval(check_interval);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let result = ping(&pool, &health).await;
+            match (&result, health.is_up()) {
+                (Err(e), true) => tracing::error!(error = %e, "Base de données injoignable"),
+                (Ok(()), false) => tracing::info!("Base de données de nouveau joignable"),
+                _ => {},
+            }
+            health.set(result.is_ok());
+        }
+    });
+}
+
+/// Route `GET /metrics` : état de la base et du pool au format texte
+/// Prometheus. Protégée par `METRICS_TOKEN` (en-tête `Authorization: Bearer`)
+/// si la variable est définie.
+pub async fn get_metrics(
+    State(pool): State<PgPool>,
+    Extension(health): Extension<DbHealth>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(token) = env::var("METRICS_TOKEN").ok().filter(|t| !t.is_empty()) {
+        let provided = headers.get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if provided != Some(token.as_str()) {
+            return (StatusCode::UNAUTHORIZED, "METRICS_TOKEN requis\n").into_response();
+        }
+    }
+
+    let size = pool.size() as usize;
+    let idle = pool.num_idle().min(size);
+    let acquire = &health.acquire;
+    let seconds = |us: u64| us as f64 / 1_000_000.0;
+
+    let mut body = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, samples: &[(&str, String)]| {
+        let _ = writeln!(body, "# HELP {} {}", name, help);
+        let _ = writeln!(body, "# TYPE {} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = writeln!(body, "{}{} {}", name, labels, value);
+        }
+    };
+    metric("pa_db_up", "gauge", "Base de données joignable (1) ou non (0)", &[
+        ("", (health.is_up() as u8).to_string()),
+    ]);
+    metric("pa_db_pool_connections", "gauge", "Connexions ouvertes du pool, par état", &[
+        ("{state=\"in_use\"}", (size - idle).to_string()),
+        ("{state=\"idle\"}", idle.to_string()),
+    ]);
+    metric("pa_db_pool_max_connections", "gauge", "Taille maximale du pool", &[
+        ("", health.max_connections.to_string()),
+    ]);
+    metric("pa_db_pool_acquire_wait_seconds", "summary", "Attente d'une connexion du pool (vérifications et requêtes)", &[
+        ("_sum", seconds(acquire.total_us.load(Ordering::Relaxed)).to_string()),
+        ("_count", acquire.samples.load(Ordering::Relaxed).to_string()),
+    ]);
+    metric("pa_db_pool_acquire_wait_last_seconds", "gauge", "Dernière attente mesurée", &[
+        ("", seconds(acquire.last_us.load(Ordering::Relaxed)).to_string()),
+    ]);
+    metric("pa_db_pool_acquire_wait_max_seconds", "gauge", "Attente maximale mesurée depuis le démarrage", &[
+        ("", seconds(acquire.max_us.load(Ordering::Relaxed)).to_string()),
+    ]);
+    metric("pa_db_pool_slow_acquires_total", "counter", "Attentes supérieures à DB_SLOW_ACQUIRE_MS", &[
+        ("", acquire.slow.load(Ordering::Relaxed).to_string()),
+    ]);
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        body,
+    ).into_response()
 }
 
 // Fonction utilitaire pour obtenir le rôle d'un utilisateur par wallet
+#[allow(dead_code)]
 pub async fn get_user_role(pool: &PgPool, wallet: &str) -> UserRole {
     let role = sqlx::query!(
-        r#"SELECT role as "role: UserRole" FROM users WHERE wallet = $1"#,
+        r#"SELECT role as "role: UserRole" FROM users WHERE wallet = $1 AND deactivated_at IS NULL"#,
         wallet
     )
     .fetch_optional(pool)
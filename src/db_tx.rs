@@ -0,0 +1,149 @@
+// db_tx.rs
+//
+// Transaction par requête, sur option : la couche `transactional`, posée sur
+// un handler dans main.rs, place un `RequestTx` dans les extensions de la
+// requête. Le handler (ou les fonctions qu'il appelle) l'extrait et y fait ses
+// écritures ; la transaction s'ouvre à la première utilisation. Elle est
+// validée si la réponse est un succès (statut < 400), annulée sinon ou si le
+// handler panique : un handler à plusieurs écritures est atomique sans avoir
+// à faire circuler la transaction à la main. L'attente de sa connexion
+// (ouverture de la transaction comprise) alimente les mesures du pool (voir
+// db.rs).
+
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, State},
+    http::{request::Parts, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::FutureExt;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use std::ops::{Deref, DerefMut};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::db::DbHealth;
+use crate::error_codes::ErrorCode;
+
+struct TxState {
+    pool: PgPool,
+    health: Option<DbHealth>,
+    tx: Option<Transaction<'static, Postgres>>,
+    /// Réponse envoyée : la transaction ne peut plus être utilisée
+    finished: bool,
+}
+
+/// Transaction de la requête en cours
+#[derive(Clone)]
+pub struct RequestTx {
+    state: Arc<Mutex<TxState>>,
+}
+
+/// Connexion de la transaction, réservée tant qu'elle est tenue
+pub struct TxConn(OwnedMutexGuard<TxState>);
+
+impl Deref for TxConn {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &PgConnection {
+        self.0.tx.as_ref().expect("transaction ouverte par RequestTx::conn")
+    }
+}
+
+impl DerefMut for TxConn {
+    fn deref_mut(&mut self) -> &mut PgConnection {
+        self.0.tx.as_mut().expect("transaction ouverte par RequestTx::conn")
+    }
+}
+
+impl RequestTx {
+    fn new(pool: PgPool, health: Option<DbHealth>) -> Self {
+        Self { state: Arc::new(Mutex::new(TxState { pool, health, tx: None, finished: false })) }
+    }
+
+    /// Connexion de la transaction, ouverte au premier appel
+    pub async fn conn(&self) -> Result<TxConn, sqlx::Error> {
+        let mut state = self.state.clone().lock_owned().await;
+        if state.finished {
+            return Err(sqlx::Error::Protocol("transaction de la requête déjà terminée".into()));
+        }
+        if state.tx.is_none() {
+            let started = Instant::now();
+            let tx = state.pool.begin().await?;
+            if let Some(health) = &state.health {
+                health.record_acquire(&state.pool, started.elapsed());
+            }
+            state.tx = Some(tx);
+        }
+        Ok(TxConn(state))
+    }
+
+    /// Valide ou annule la transaction, si elle a été ouverte
+    async fn finish(&self, commit: bool) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().await;
+        state.finished = true;
+        match state.tx.take() {
+            Some(tx) if commit => tx.commit().await,
+            Some(tx) => tx.rollback().await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequestTx {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<RequestTx>().cloned().ok_or_else(|| (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": "Transaction de requête non configurée pour cette route",
+            "code": ErrorCode::InternalError
+        }))).into_response())
+    }
+}
+
+/// Réponse d'un handler qui n'a pas pu ouvrir la transaction
+pub fn transaction_error(e: sqlx::Error) -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de l'ouverture de la transaction: {}", e),
+        "code": ErrorCode::DatabaseError
+    }))).into_response()
+}
+
+/// Couche transactionnelle ; l'état est le pool de la transaction
+pub async fn transactional(
+    State(pool): State<PgPool>,
+    mut request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let health = request.extensions().get::<DbHealth>().cloned();
+    let tx = RequestTx::new(pool, health);
+    request.extensions_mut().insert(tx.clone());
+
+    let response = match AssertUnwindSafe(next.run(request)).catch_unwind().await {
+        Ok(response) => response,
+        Err(_) => {
+            if let Err(e) = tx.finish(false).await {
+                tracing::error!("Annulation de la transaction de la requête impossible: {}", e);
+            }
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": "Erreur interne, modifications annulées",
+                "code": ErrorCode::InternalError
+            }))).into_response();
+        },
+    };
+
+    let commit = !response.status().is_client_error() && !response.status().is_server_error();
+    match tx.finish(commit).await {
+        Ok(()) => response,
+        // Validation refusée (contrainte différée, connexion perdue) : rien n'a été enregistré
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
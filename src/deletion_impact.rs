@@ -0,0 +1,145 @@
+// deletion_impact.rs
+//
+// Aperçu de la suppression d'une propriété, avant de la lancer :
+// `GET /api/properties/:id/deletion-impact` compte les lignes rattachées
+// (investissements par statut, investisseurs touchés, documents, médias...)
+// et indique si la suppression est permise. Une propriété validée, ou ayant
+// des investissements confirmés (réglés), quel que soit son statut, n'est
+// jamais supprimée ; une propriété avec d'autres investissements ne l'est
+// qu'en cascade (`DELETE /api/properties/:id?cascade=true`), qui met ses
+// investissements à la corbeille avec elle, sous un seul jeton de
+// confirmation (action `delete_property_cascade`). La clé étrangère
+// `investments.property_id` (RESTRICT) garantit qu'aucun investissement ne
+// reste orphelin.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::error_codes::ErrorCode;
+use crate::models::{InvestmentCounts, PropertyDeletionImpact, PropertyDependents, PropertyStatus, UserRole};
+use crate::trash;
+
+/// Action de confirmation d'une suppression en cascade, distincte de
+/// `delete_property` : un jeton de suppression simple ne vaut pas pour la cascade
+pub const CASCADE_ACTION: &str = "delete_property_cascade";
+
+/// Impact de la suppression d'une propriété du tenant de l'utilisateur ;
+/// `None` si elle n'existe pas (ou s'il s'agit du brouillon d'un autre)
+pub async fn compute<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user: &SessionUser,
+    property_id: Uuid,
+) -> Result<Option<PropertyDeletionImpact>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT p.status as "status: PropertyStatus", p.created_by,
+                  COALESCE(cardinality(p.documents), 0) as "documents!",
+                  (SELECT COUNT(*) FROM investments i WHERE i.property_id = p.id) as "investments!",
+                  (SELECT COUNT(*) FROM investments i WHERE i.property_id = p.id AND i.status = 'pending_settlement') as "pending_settlement!",
+                  (SELECT COUNT(*) FROM investments i WHERE i.property_id = p.id AND i.status = 'settled') as "settled!",
+                  (SELECT COUNT(*) FROM investments i WHERE i.property_id = p.id AND i.status = 'refunded') as "refunded!",
+                  (SELECT COUNT(DISTINCT i.user_id) FROM investments i WHERE i.property_id = p.id AND i.status <> 'refunded') as "distributions!",
+                  (SELECT COUNT(*) FROM document_pins d WHERE d.property_id = p.id) as "document_pins!",
+                  (SELECT COUNT(*) FROM property_media m WHERE m.property_id = p.id) as "media!",
+                  (SELECT COUNT(*) FROM investment_intents t WHERE t.property_id = p.id) as "intents!"
+           FROM properties p
+           WHERE p.id = $1 AND p.tenant_id = $2"#,
+        property_id,
+        user.tenant_id
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    if matches!(row.status, PropertyStatus::Draft) && row.created_by != user.id {
+        return Ok(None);
+    }
+
+    let mut blockers = Vec::new();
+    if matches!(row.status, PropertyStatus::Validated) {
+        blockers.push(ErrorCode::PropertyLocked.as_str().to_string());
+    }
+    if row.settled > 0 {
+        blockers.push(ErrorCode::PropertyHasConfirmedInvestments.as_str().to_string());
+    }
+    if row.investments > 0 {
+        blockers.push(ErrorCode::PropertyHasInvestments.as_str().to_string());
+    }
+
+    Ok(Some(PropertyDeletionImpact {
+        property_id,
+        deletable: blockers.is_empty(),
+        cascade_deletable: !matches!(row.status, PropertyStatus::Validated) && row.settled == 0,
+        blockers,
+        status: row.status,
+        dependents: PropertyDependents {
+            investments: InvestmentCounts {
+                total: row.investments,
+                pending_settlement: row.pending_settlement,
+                settled: row.settled,
+                refunded: row.refunded,
+            },
+            distributions: row.distributions,
+            documents: row.documents as i64,
+            document_pins: row.document_pins,
+            media: row.media,
+            intents: row.intents,
+        },
+    }))
+}
+
+/// Met à la corbeille les investissements non confirmés de la propriété,
+/// avant la propriété elle-même ; renvoie leur nombre. Un investissement réglé
+/// entre-temps reste en place et la clé étrangère refuse alors la suppression
+/// de la propriété.
+pub async fn trash_dependents(
+    tx: &mut Transaction<'_, Postgres>,
+    property_id: Uuid,
+    deleted_by: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let investment_ids = sqlx::query_scalar!(
+        "SELECT id FROM investments WHERE property_id = $1 AND status <> 'settled' FOR UPDATE",
+        property_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for investment_id in &investment_ids {
+        trash::trash_investment(&mut *tx, *investment_id, deleted_by).await?;
+    }
+    Ok(investment_ids.len() as u64)
+}
+
+/// Route admin : impact de la suppression d'une propriété
+pub async fn get_deletion_impact(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut supprimer des propriétés",
+            "code": ErrorCode::AdminRequired
+        }))).into_response();
+    }
+
+    match compute(&pool, &user, property_id).await {
+        Ok(Some(impact)) => (StatusCode::OK, Json(impact)).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du calcul de l'impact: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,232 @@
+// digest.rs
+//
+// Résumé hebdomadaire par e-mail : une tâche de fond compile, pour chaque
+// utilisateur ayant renseigné une adresse, les propriétés publiées sur sa
+// plateforme dans les 7 derniers jours, ses nouveaux investissements et les
+// remboursements reçus, puis l'envoie via le service d'e-mails (mailer.rs).
+// Chaque semaine (à partir du lundi) est traitée une seule fois par
+// utilisateur (`email_digests`) ; un résumé vide n'est pas envoyé. Le type
+// `weekly_digest` coupé sur le canal `email` (voir notification_preferences.rs)
+// désinscrit l'utilisateur.
+
+use bigdecimal::BigDecimal;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::feeds::xml_escape;
+use crate::mailer::{Email, Mailer};
+use crate::notification_preferences;
+use crate::worker;
+
+/// Période couverte par un résumé, en jours
+const PERIOD_DAYS: i64 = 7;
+
+const TEXT_TEMPLATE: &str = "Bonjour {{name}},
+
+Voici ce qui s'est passé cette semaine.
+
+{{sections}}Pour ne plus recevoir ce résumé : {{unsubscribe_url}}
+";
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="fr">
+<body style="font-family: sans-serif; color: #222">
+<p>Bonjour {{name}},</p>
+<p>Voici ce qui s'est passé cette semaine.</p>
+{{sections}}
+<p style="font-size: 12px; color: #666"><a href="{{unsubscribe_url}}">Ne plus recevoir ce résumé</a></p>
+</body>
+</html>
+"#;
+
+/// Remplace les `{{clé}}` du modèle
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    values.iter().fold(template.to_string(), |rendered, (key, value)| {
+        rendered.replace(&format!("{{{{{}}}}}", key), value)
+    })
+}
+
+/// URL publique de l'API, base des liens de désinscription et de vérification des certificats
+pub fn api_url() -> String {
+    env::var("PUBLIC_API_URL")
+        .unwrap_or_else(|_| "http://localhost:3000".to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+struct Recipient {
+    id: Uuid,
+    tenant_id: Uuid,
+    name: Option<String>,
+    email: String,
+}
+
+/// Sections du résumé : (titre, lignes) ; vide si rien à signaler
+async fn compile(pool: &PgPool, recipient: &Recipient) -> Result<Vec<(&'static str, Vec<String>)>, sqlx::Error> {
+    let since = Utc::now() - Duration::days(PERIOD_DAYS);
+
+    let properties = sqlx::query!(
+        r#"SELECT name, location, annual_yield FROM properties
+           WHERE tenant_id = $1 AND status = 'validated' AND published_at > $2
+           ORDER BY published_at DESC
+           LIMIT 10"#,
+        recipient.tenant_id,
+        since
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let investments = sqlx::query!(
+        r#"SELECT p.name, i.amount_eth, i.shares FROM investments i
+           JOIN properties p ON p.id = i.property_id
+           WHERE i.user_id = $1 AND i.created_at > $2
+           ORDER BY i.created_at DESC"#,
+        recipient.id,
+        since
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let refunds = sqlx::query!(
+        r#"SELECT p.name, r.amount_eth FROM investment_refunds r
+           JOIN investments i ON i.id = r.investment_id
+           JOIN properties p ON p.id = i.property_id
+           WHERE r.user_id = $1 AND r.status = 'paid' AND r.paid_at > $2
+           ORDER BY r.paid_at DESC"#,
+        recipient.id,
+        since
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let eth = |amount: &BigDecimal| amount.round(4).normalized().to_string();
+    let mut sections = Vec::new();
+    if !properties.is_empty() {
+        sections.push(("Nouvelles propriétés", properties.iter().map(|p| {
+            format!("{} ({}) - rendement annuel {} %", p.name, p.location, p.annual_yield.round(2).normalized())
+        }).collect()));
+    }
+    if !investments.is_empty() {
+        sections.push(("Votre portefeuille", investments.iter().map(|i| {
+            format!("{} : {} part(s) pour {} ETH", i.name, i.shares, eth(&i.amount_eth))
+        }).collect()));
+    }
+    if !refunds.is_empty() {
+        sections.push(("Versements reçus", refunds.iter().map(|r| {
+            format!("Remboursement {} : {} ETH", r.name, eth(&r.amount_eth))
+        }).collect()));
+    }
+    Ok(sections)
+}
+
+fn build_email(recipient: &Recipient, sections: &[(&'static str, Vec<String>)], unsubscribe_url: String) -> Email {
+    let name = recipient.name.clone().unwrap_or_else(|| "investisseur".to_string());
+
+    let text_sections = sections.iter().map(|(title, lines)| {
+        format!("{}\n{}\n", title, lines.iter().map(|line| format!("- {}\n", line)).collect::<String>())
+    }).collect::<String>();
+    let html_sections = sections.iter().map(|(title, lines)| {
+        format!(
+            "<h3>{}</h3><ul>{}</ul>",
+            xml_escape(title),
+            lines.iter().map(|line| format!("<li>{}</li>", xml_escape(line))).collect::<String>()
+        )
+    }).collect::<String>();
+
+    Email {
+        to: recipient.email.clone(),
+        subject: "Votre résumé de la semaine".to_string(),
+        text: render(TEXT_TEMPLATE, &[("name", &name), ("sections", &text_sections), ("unsubscribe_url", &unsubscribe_url)]),
+        html: render(HTML_TEMPLATE, &[
+            ("name", &xml_escape(&name)),
+            ("sections", &html_sections),
+            ("unsubscribe_url", &xml_escape(&unsubscribe_url)),
+        ]),
+        unsubscribe_url: Some(unsubscribe_url),
+    }
+}
+
+/// Traite un lot d'utilisateurs dont le résumé de la semaine n'est pas encore
+/// parti ; renvoie le nombre d'envois réussis
+async fn send_due(pool: &PgPool, mailer: &Mailer, batch_size: i64) -> Result<u64, sqlx::Error> {
+    let today = Utc::now().date_naive();
+    let week_start: NaiveDate = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+
+    let recipients = sqlx::query_as!(
+        Recipient,
+        r#"SELECT u.id, u.tenant_id, u.name, u.email as "email!" FROM users u
+           WHERE u.email IS NOT NULL
+           AND NOT EXISTS (SELECT 1 FROM email_digests d WHERE d.user_id = u.id AND d.week_start = $1)
+           AND NOT EXISTS (SELECT 1 FROM notification_preferences np
+                           WHERE np.user_id = u.id AND np.kind = 'weekly_digest' AND np.channel = 'email' AND NOT np.enabled)
+           ORDER BY u.created_at
+           LIMIT $2"#,
+        week_start,
+        batch_size
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut sent = 0;
+    for recipient in recipients {
+        let sections = compile(pool, &recipient).await?;
+        let (status, error) = if sections.is_empty() {
+            ("empty", None)
+        } else {
+            let token = notification_preferences::unsubscribe_token(pool, recipient.id).await?;
+            let unsubscribe_url = format!("{}/notifications/unsubscribe?token={}&kind=weekly_digest", api_url(), token);
+            match mailer.send(&build_email(&recipient, &sections, unsubscribe_url)).await {
+                Ok(()) => {
+                    sent += 1;
+                    ("sent", None)
+                },
+                // Service d'envoi indisponible : les résumés restants partiront au prochain passage
+                Err(e) if e.is_unavailable() => {
+                    tracing::warn!("Envoi des résumés hebdomadaires interrompu: {}", e);
+                    break;
+                },
+                Err(e) => {
+                    tracing::error!("Échec de l'envoi du résumé hebdomadaire à {}: {}", recipient.id, e);
+                    ("failed", Some(e.to_string()))
+                },
+            }
+        };
+
+        sqlx::query!(
+            r#"INSERT INTO email_digests (user_id, week_start, status, error) VALUES ($1, $2, $3, $4)
+               ON CONFLICT (user_id, week_start) DO NOTHING"#,
+            recipient.id,
+            week_start,
+            status,
+            error
+        )
+        .execute(pool)
+        .await?;
+    }
+    Ok(sent)
+}
+
+/// Lance la tâche d'envoi. Intervalle `DIGEST_INTERVAL_SECS` (3600s par
+/// défaut), au plus `DIGEST_BATCH_SIZE` utilisateurs (200) par passage.
+pub fn spawn(pool: PgPool, mailer: Mailer) {
+    let env_u64 = |name: &str, default: u64| {
+        env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).filter(|v| *v > 0).unwrap_or(default)
+    };
+    let interval_secs = env_u64("DIGEST_INTERVAL_SECS", 3600);
+    let batch_size = env_u64("DIGEST_BATCH_SIZE", 200) as i64;
+
+    tokio::spawn(async move {
+        let period = std::time::Duration::from_secs(interval_secs);
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match worker::run_exclusive(&pool, "digest", period, send_due(&pool, &mailer, batch_size)).await {
+                None | Some(Ok(0)) => {},
+                Some(Ok(count)) => tracing::info!("{} résumé(s) hebdomadaire(s) envoyé(s)", count),
+                Some(Err(e)) => tracing::error!("Erreur lors de l'envoi des résumés hebdomadaires: {}", e),
+            }
+        }
+    });
+}
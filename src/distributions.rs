@@ -0,0 +1,259 @@
+// distributions.rs
+//
+// Revenus courus entre deux versements. L'admin enregistre chaque versement
+// des revenus d'une propriété (`distribution_runs`) par la fin de la période
+// versée (`paid_through`) ; `GET /api/me/distributions/accrual` estime, pour
+// chaque position de l'investisseur, les revenus courus depuis et non encore
+// versés : montant investi × rendement annuel × durée de détention / 365
+// jours, à la seconde près. Seuls les investissements réglés courent, à partir
+// de leur règlement ou, s'il est postérieur, du dernier versement. Les
+// estimations sont arrondies comme les distributions estimées (voir money.rs)
+// et le revenu net applique la retenue à la source du pays de l'utilisateur
+// (voir taxes.rs).
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::currency;
+use crate::envelope;
+use crate::error_codes::ErrorCode;
+use crate::models::{DistributionRun, HoldingAccrual, PropertyStatus, RecordDistributionRunRequest, UserRole};
+use crate::money::TokenAmount;
+
+/// Base annuelle des revenus courus (exact/365)
+const SECONDS_PER_YEAR: i64 = 365 * 86_400;
+
+fn admin_only(role: &UserRole) -> Option<Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins enregistrent les versements",
+        "code": ErrorCode::AdminRequired
+    }))).into_response())
+}
+
+fn internal(e: sqlx::Error) -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de la gestion des versements: {}", e),
+        "code": ErrorCode::DatabaseError
+    }))).into_response()
+}
+
+fn property_not_found() -> Response {
+    (StatusCode::NOT_FOUND, Json(serde_json::json!({
+        "error": "Propriété non trouvée",
+        "code": ErrorCode::PropertyNotFound
+    }))).into_response()
+}
+
+/// Route admin : versements enregistrés pour une propriété, du plus récent au
+/// plus ancien
+pub async fn get_distribution_runs(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        DistributionRun,
+        r#"SELECT * FROM distribution_runs
+           WHERE property_id = $1 AND tenant_id = $2
+           ORDER BY paid_through DESC"#,
+        property_id,
+        admin.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(runs) => envelope::list("distribution_runs", &runs).into_response(),
+        Err(e) => internal(e),
+    }
+}
+
+/// Route admin : enregistrer le versement des revenus d'une propriété validée
+/// jusqu'à `paid_through` (maintenant par défaut), postérieur au précédent
+pub async fn record_distribution_run(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+    Json(payload): Json<RecordDistributionRunRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+    let now = Utc::now();
+    let paid_through = payload.paid_through.unwrap_or(now);
+    if paid_through > now {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "La fin de la période versée ne peut pas être dans le futur",
+            "code": ErrorCode::InvalidPaidThrough
+        }))).into_response();
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        // Verrou de la propriété : les versements s'enregistrent un à la fois, dans l'ordre
+        let property = sqlx::query!(
+            r#"SELECT status as "status: PropertyStatus",
+                      (SELECT MAX(paid_through) FROM distribution_runs WHERE property_id = p.id) as last_paid_through
+               FROM properties p
+               WHERE id = $1 AND tenant_id = $2
+               FOR UPDATE"#,
+            property_id,
+            admin.tenant_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        let property = match property {
+            Some(property) => property,
+            None => return Ok(Err(property_not_found())),
+        };
+        if !matches!(property.status, PropertyStatus::Validated) {
+            return Ok(Err((StatusCode::FORBIDDEN, Json(serde_json::json!({
+                "error": "Seule une propriété validée verse des revenus",
+                "code": ErrorCode::PropertyNotValidated
+            }))).into_response()));
+        }
+        if let Some(last) = property.last_paid_through.filter(|last| paid_through <= *last) {
+            return Ok(Err((StatusCode::CONFLICT, Json(serde_json::json!({
+                "error": "La fin de la période versée doit suivre le dernier versement",
+                "code": ErrorCode::DistributionRunOutOfOrder,
+                "last_paid_through": last
+            }))).into_response()));
+        }
+
+        let run = sqlx::query_as!(
+            DistributionRun,
+            r#"INSERT INTO distribution_runs (tenant_id, property_id, paid_through, recorded_by)
+               VALUES ($1, $2, $3, $4)
+               RETURNING *"#,
+            admin.tenant_id,
+            property_id,
+            paid_through,
+            admin.id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(run))
+    }.await;
+
+    match result {
+        Ok(Ok(run)) => {
+            tracing::info!(%property_id, paid_through = %run.paid_through, by = %admin.id, "Versement enregistré");
+            (StatusCode::CREATED, Json(serde_json::json!({
+                "distribution_run": run,
+                "message": "Versement enregistré"
+            }))).into_response()
+        },
+        Ok(Err(response)) => response,
+        Err(e) => internal(e),
+    }
+}
+
+/// Route : revenus courus et non versés de chaque position de l'utilisateur,
+/// avec leur total et sa contre-valeur dans la devise préférée
+pub async fn get_my_accrual(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let as_of = Utc::now();
+
+    let result = async {
+        let rows = sqlx::query!(
+            r#"WITH holdings AS (
+                   SELECT i.property_id, i.shares, i.amount_eth, r.paid_through,
+                          GREATEST(COALESCE(i.settled_at, i.created_at), COALESCE(r.paid_through, '-infinity')) as accrual_start
+                   FROM investments i
+                   LEFT JOIN LATERAL (
+                       SELECT MAX(paid_through) as paid_through FROM distribution_runs WHERE property_id = i.property_id
+                   ) r ON TRUE
+                   WHERE i.user_id = $1 AND i.status = 'settled'
+               )
+               SELECT p.id, p.slug, p.name, p.annual_yield,
+                      SUM(h.shares)::bigint as "shares!",
+                      SUM(h.amount_eth) as "amount_eth!",
+                      MAX(h.paid_through) as last_distribution_at,
+                      MIN(h.accrual_start) as "accrual_start!",
+                      SUM(h.amount_eth * GREATEST(EXTRACT(EPOCH FROM $2 - h.accrual_start), 0)) as "amount_seconds!"
+               FROM holdings h
+               JOIN properties p ON p.id = h.property_id
+               GROUP BY p.id
+               ORDER BY p.name"#,
+            user.id,
+            as_of
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        let withholding_rate = sqlx::query_scalar!(
+            r#"SELECT t.rate FROM tax_rules t
+               JOIN users u ON u.country = t.country AND u.tenant_id = t.tenant_id
+               WHERE u.id = $1 AND t.applies_to = 'distributions'"#,
+            user.id
+        )
+        .fetch_optional(&pool)
+        .await?;
+
+        let valuation = currency::valuation_for(&pool, user.id).await?;
+        Ok::<_, sqlx::Error>((rows, withholding_rate, valuation))
+    }.await;
+
+    let (rows, withholding_rate, valuation) = match result {
+        Ok(result) => result,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du calcul des revenus courus: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    };
+
+    let hundred = BigDecimal::from(100);
+    let year = BigDecimal::from(SECONDS_PER_YEAR);
+    let net_share = withholding_rate.as_ref()
+        .map(|rate| (&hundred - rate) / &hundred)
+        .unwrap_or_else(|| BigDecimal::from(1));
+    let mut total = BigDecimal::from(0);
+    let holdings: Vec<HoldingAccrual> = rows.into_iter()
+        .map(|row| {
+            let accrued = &row.amount_seconds * &row.annual_yield / &hundred / &year;
+            let daily = &row.amount_eth * &row.annual_yield / &hundred / BigDecimal::from(365);
+            total += &accrued;
+            HoldingAccrual {
+                property_id: row.id,
+                slug: row.slug,
+                name: row.name,
+                shares: row.shares,
+                amount_eth: TokenAmount::from_decimal(row.amount_eth),
+                annual_yield: row.annual_yield,
+                last_distribution_at: row.last_distribution_at,
+                accrual_start: row.accrual_start,
+                daily_accrual_eth: TokenAmount::estimate(daily),
+                net_accrued_eth: TokenAmount::estimate(&accrued * &net_share),
+                accrued_eth: TokenAmount::estimate(accrued),
+            }
+        })
+        .collect();
+    let total_accrued = TokenAmount::estimate(total.clone());
+    let net_total_accrued = TokenAmount::estimate(&total * &net_share);
+
+    envelope::list("holdings", &holdings)
+        .with("as_of", as_of)
+        .with("withholding_rate", &withholding_rate)
+        .with("fiat", serde_json::json!({
+            "format": valuation.format(),
+            "total_accrued": valuation.value(&total_accrued),
+            "net_total_accrued": valuation.value(&net_total_accrued)
+        }))
+        .with("total_accrued_eth", total_accrued)
+        .with("net_total_accrued_eth", net_total_accrued)
+        .into_response()
+}
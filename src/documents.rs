@@ -0,0 +1,270 @@
+// documents.rs
+//
+// Envoi des documents des propriétés vers le bucket privé (et, en option, leur
+// épinglage sur IPFS), puis téléchargement via des URL signées à durée de vie
+// courte. Chaque URL délivrée est journalisée pour audit.
+
+use axum::{
+    extract::{BodyStream, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::ipfs::IpfsConfig;
+use crate::media;
+use crate::models::{PropertyStatus, UploadQuery, UserRole};
+use crate::storage::StorageConfig;
+use crate::upload::{self, DOCUMENT_POLICY};
+
+/// Route pour envoyer un document (créateur ou admin).
+/// Le corps de la requête est le fichier brut ; `?filename=` est requis.
+/// Le chemin du fichier dans le bucket est ajouté à la liste `documents`.
+/// Si l'épinglage IPFS est activé, le fichier est aussi épinglé et son CID conservé.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_document(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+    Extension(ipfs): Extension<Option<IpfsConfig>>,
+    Path(property_id): Path<Uuid>,
+    Query(query): Query<UploadQuery>,
+    headers: HeaderMap,
+    body: BodyStream,
+) -> impl IntoResponse {
+    // Mêmes droits que pour la galerie
+    if let Err(response) = media::check_access(&pool, &user, property_id, true).await {
+        return response;
+    }
+
+    let storage = match storage {
+        Some(storage) => storage,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Stockage des documents non configuré"
+        }))).into_response(),
+    };
+
+    let file = match upload::receive(&DOCUMENT_POLICY, &query.filename, &headers, body).await {
+        Ok(file) => file,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    // Épinglage en premier : en cas d'échec, rien n'est encore enregistré
+    let cid = match &ipfs {
+        Some(ipfs) => match ipfs.pin_file(&file.path, &file.file_name, file.content_type).await {
+            Ok(cid) => Some(cid),
+            Err(e) => return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({
+                "error": format!("Erreur lors de l'épinglage IPFS: {}", e)
+            }))).into_response(),
+        },
+        None => None,
+    };
+
+    let key = format!("properties/{}/documents/{}-{}", property_id, Uuid::new_v4(), file.file_name);
+    if let Err(e) = storage.put_document(&key, &file.path, file.content_type).await {
+        return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({
+            "error": format!("Erreur lors du dépôt du fichier: {}", e)
+        }))).into_response();
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let count = sqlx::query_scalar!(
+            r#"UPDATE properties SET documents = array_append(COALESCE(documents, '{}'), $2)
+               WHERE id = $1
+               RETURNING cardinality(documents) as "count!""#,
+            property_id,
+            key
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        if let Some(cid) = &cid {
+            sqlx::query!(
+                "INSERT INTO document_pins (property_id, document_key, cid) VALUES ($1, $2, $3)",
+                property_id,
+                key,
+                cid
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(count)
+    }.await;
+
+    match result {
+        Ok(count) => (StatusCode::CREATED, Json(serde_json::json!({
+            "document": {
+                "doc_id": count - 1,
+                "key": key,
+                "content_type": file.content_type,
+                "size": file.size,
+                "cid": cid,
+                "ipfs_url": cid.as_deref().zip(ipfs.as_ref()).map(|(cid, ipfs)| ipfs.gateway_url(cid))
+            },
+            "message": "Document ajouté avec succès"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour obtenir une URL de téléchargement signée d'un document.
+/// `doc_id` est l'index du document dans la liste `documents` de la propriété.
+///
+/// Accès : l'admin et le créateur ; les autres utilisateurs uniquement pour
+/// une propriété validée et publiée.
+pub async fn download_document(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+    Path((property_id, doc_id)): Path<(Uuid, usize)>,
+) -> impl IntoResponse {
+    let property = match sqlx::query!(
+        r#"SELECT created_by, status as "status: PropertyStatus", published_at, documents
+           FROM properties WHERE id = $1"#,
+        property_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        // Les brouillons des autres restent invisibles
+        Ok(Some(prop)) if !matches!(prop.status, PropertyStatus::Draft) || prop.created_by == user.id => prop,
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    let is_public = matches!(property.status, PropertyStatus::Validated) && property.published_at.is_some();
+    if !is_public && property.created_by != user.id && !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Accès aux documents non autorisé"
+        }))).into_response();
+    }
+
+    let document_key = match property.documents.as_ref().and_then(|docs| docs.get(doc_id)) {
+        Some(key) => key.trim().to_string(),
+        None => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Document non trouvé"
+        }))).into_response(),
+    };
+
+    // Les anciennes URL absolues ne sont pas dans le bucket privé
+    if document_key.starts_with("http://") || document_key.starts_with("https://") {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+            "error": "Document externe : aucune URL signée ne peut être générée"
+        }))).into_response();
+    }
+
+    let storage = match storage {
+        Some(storage) => storage,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Stockage des documents non configuré"
+        }))).into_response(),
+    };
+
+    let cid = match sqlx::query_scalar!(
+        "SELECT cid FROM document_pins WHERE property_id = $1 AND document_key = $2",
+        property_id,
+        document_key
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(cid) => cid,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    let now = Utc::now();
+    let url = storage.presign_get(&document_key, now);
+    let expires_at = now + Duration::seconds(storage.url_ttl_secs as i64);
+
+    // Journal d'audit : une ligne par URL délivrée
+    if let Err(e) = sqlx::query!(
+        r#"INSERT INTO document_downloads (property_id, document_index, document_key, user_id)
+           VALUES ($1, $2, $3, $4)"#,
+        property_id,
+        doc_id as i32,
+        document_key,
+        user.id
+    )
+    .execute(&pool)
+    .await {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la journalisation: {}", e)
+        }))).into_response();
+    }
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "url": url,
+        "expires_at": expires_at,
+        "expires_in": storage.url_ttl_secs,
+        "cid": cid
+    }))).into_response()
+}
+
+/// Route pour consulter le journal des téléchargements d'une propriété (créateur ou admin)
+pub async fn get_document_downloads(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match sqlx::query!(
+        r#"SELECT created_by, status as "status: PropertyStatus" FROM properties WHERE id = $1 AND tenant_id = $2"#,
+        property_id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(prop)) if prop.created_by == user.id
+            || (matches!(user.role, UserRole::Admin) && !matches!(prop.status, PropertyStatus::Draft)) => {},
+        Ok(Some(prop)) if !matches!(prop.status, PropertyStatus::Draft) => return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul le créateur ou l'admin peut consulter le journal"
+        }))).into_response(),
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    }
+
+    match sqlx::query!(
+        r#"SELECT d.id, d.document_index, d.document_key, d.user_id, u.wallet, d.created_at
+           FROM document_downloads d
+           JOIN users u ON u.id = d.user_id
+           WHERE d.property_id = $1
+           ORDER BY d.created_at DESC"#,
+        property_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => {
+            let downloads: Vec<serde_json::Value> = rows.into_iter().map(|row| {
+                serde_json::json!({
+                    "id": row.id,
+                    "document_index": row.document_index,
+                    "document_key": row.document_key,
+                    "user_id": row.user_id,
+                    "wallet": row.wallet,
+                    "created_at": row.created_at
+                })
+            }).collect();
+
+            envelope::list("downloads", &downloads).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,394 @@
+// dual_write.rs
+//
+// Double écriture pendant la bascule de l'ancienne base vers Supabase. Quand
+// `DUAL_WRITE_DATABASE_URL` désigne l'autre base, les connexions de l'API
+// activent le réglage de session `pa.capture_changes` : un trigger consigne
+// alors chaque écriture sur users, properties et investments dans
+// `row_changes`, dans la transaction qui la fait. Les workers rejouent ces
+// écritures dans l'ordre sur l'autre base ; avant chacune, la ligne de l'autre
+// base est comparée à l'état attendu, et tout écart (ligne absente, inattendue
+// ou différente, écriture refusée) est enregistré dans
+// `dual_write_divergences` puis signalé aux admins. Une écriture refusée
+// bloque les suivantes et est retentée à chaque passe.
+//
+// `DUAL_WRITE_SERVE_FROM=secondary` inverse les rôles : l'API lit et écrit sur
+// la base de `DUAL_WRITE_DATABASE_URL` et copie vers celle de `DATABASE_URL`.
+// La bascule se fait donc par un redémarrage, et se défait de même tant que
+// la double écriture reste active. Les deux bases doivent avoir le même
+// schéma (migrations/supabase_migration.sql) ; les écritures faites hors de
+// l'API ne sont pas consignées.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use serde::Serialize;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Executor, PgPool};
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::error_codes::ErrorCode;
+use crate::models::{DualWriteDivergence, UserRole};
+use crate::notifications::notify_admins;
+use crate::tenants::DEFAULT_TENANT_ID;
+use crate::worker;
+
+/// Tables copiées sur l'autre base (triggers `*_row_changes`)
+pub const MIRRORED_TABLES: &[&str] = &["users", "properties", "investments"];
+
+/// Écritures rejouées par passe
+const BATCH_SIZE: i64 = 500;
+
+/// Les écritures rejouées sont supprimées au-delà
+const APPLIED_RETENTION_DAYS: i32 = 7;
+
+/// Écarts renvoyés par la route d'état
+const RECENT_DIVERGENCES: i64 = 20;
+
+/// Base servie par l'API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServeFrom {
+    /// `DATABASE_URL`, copiée vers `DUAL_WRITE_DATABASE_URL`
+    Primary,
+    /// `DUAL_WRITE_DATABASE_URL`, copiée vers `DATABASE_URL`
+    Secondary,
+}
+
+#[derive(Clone)]
+pub struct DualWrite {
+    serve_from: ServeFrom,
+    serving_url: String,
+    /// Base qui reçoit la copie des écritures
+    mirror: PgPool,
+}
+
+impl DualWrite {
+    /// Configuration via `DUAL_WRITE_DATABASE_URL` et `DUAL_WRITE_SERVE_FROM`
+    /// (`primary` par défaut) ; `None` si la double écriture n'est pas active
+    pub fn from_env() -> Option<Self> {
+        let secondary_url = env::var("DUAL_WRITE_DATABASE_URL").ok().filter(|v| !v.trim().is_empty())?;
+        let primary_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let serve_from = match env::var("DUAL_WRITE_SERVE_FROM").unwrap_or_default().trim().to_lowercase().as_str() {
+            "" | "primary" => ServeFrom::Primary,
+            "secondary" => ServeFrom::Secondary,
+            other => panic!("DUAL_WRITE_SERVE_FROM invalide : {} (primary ou secondary)", other),
+        };
+        let (serving_url, mirror_url) = match serve_from {
+            ServeFrom::Primary => (primary_url, secondary_url),
+            ServeFrom::Secondary => (secondary_url, primary_url),
+        };
+
+        let mirror = PgPoolOptions::new()
+            .max_connections(2)
+            .acquire_timeout(Duration::from_secs(5))
+            .connect_lazy(&mirror_url)
+            .expect("Invalid DUAL_WRITE_DATABASE_URL");
+
+        Some(Self { serve_from, serving_url, mirror })
+    }
+
+    pub fn serve_from(&self) -> ServeFrom {
+        self.serve_from
+    }
+
+    /// URL de la base servie par l'API
+    pub fn serving_url(&self) -> &str {
+        &self.serving_url
+    }
+}
+
+/// Active la consignation des écritures sur une connexion de l'API
+pub fn capture_options(options: PgPoolOptions) -> PgPoolOptions {
+    options.after_connect(|conn, _| Box::pin(async move {
+        conn.execute("SET pa.capture_changes = 'on'").await?;
+        Ok(())
+    }))
+}
+
+/// Identifiant SQL entre guillemets
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+struct RowChange {
+    id: i64,
+    table_name: String,
+    operation: String,
+    row_id: Uuid,
+    old_row: Option<String>,
+    new_row: Option<String>,
+    attempts: i32,
+}
+
+/// Écart constaté avant de rejouer une écriture
+struct Divergence {
+    kind: &'static str,
+    actual: Option<String>,
+    error: Option<String>,
+}
+
+/// Rejoue une écriture sur l'autre base, après comparaison de la ligne avec
+/// l'état attendu. `columns` : colonnes écrites (hors colonnes générées)
+async fn apply(mirror: &PgPool, change: &RowChange, columns: &[String]) -> Result<Option<Divergence>, sqlx::Error> {
+    let table = quoted(&change.table_name);
+    let mut tx = mirror.begin().await?;
+    // Les triggers de l'autre base ne recalculent pas ce qui arrive avec la copie
+    sqlx::query("SELECT set_config('pa.replaying', 'on', true)").execute(&mut tx).await?;
+
+    let current: Option<(String, Option<bool>)> = sqlx::query_as(&format!(
+        "SELECT to_jsonb(t)::text, to_jsonb(t) = $2::jsonb FROM {} t WHERE id = $1 FOR UPDATE",
+        table
+    ))
+    .bind(change.row_id)
+    .bind(&change.old_row)
+    .fetch_optional(&mut tx)
+    .await?;
+    let kind = match (&change.old_row, &current) {
+        (None, None) | (Some(_), Some((_, Some(true)))) => None,
+        (Some(_), None) => Some("missing"),
+        (None, Some(_)) => Some("unexpected"),
+        (Some(_), Some(_)) => Some("mismatch"),
+    };
+    let divergence = kind.map(|kind| Divergence { kind, actual: current.map(|(row, _)| row), error: None });
+
+    match &change.new_row {
+        Some(new_row) => {
+            let list = columns.iter().map(|c| quoted(c)).collect::<Vec<_>>().join(", ");
+            let updates = columns.iter()
+                .filter(|c| c.as_str() != "id")
+                .map(|c| format!("{c} = EXCLUDED.{c}", c = quoted(c)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            sqlx::query(&format!(
+                "INSERT INTO {table} ({list}) SELECT {list} FROM jsonb_populate_record(NULL::{table}, $1::jsonb)
+                 ON CONFLICT (id) DO UPDATE SET {updates}",
+                table = table,
+                list = list,
+                updates = updates
+            ))
+            .bind(new_row)
+            .execute(&mut tx)
+            .await?;
+        },
+        None => {
+            sqlx::query(&format!("DELETE FROM {} WHERE id = $1", table))
+                .bind(change.row_id)
+                .execute(&mut tx)
+                .await?;
+        },
+    }
+    tx.commit().await?;
+    Ok(divergence)
+}
+
+async fn record_divergence(
+    pool: &PgPool,
+    change: &RowChange,
+    divergence: &Divergence,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"INSERT INTO dual_write_divergences (change_id, table_name, row_id, kind, expected, actual, error)
+           VALUES ($1, $2, $3, $4, $5::text::jsonb, $6::text::jsonb, $7)"#,
+        change.id,
+        change.table_name,
+        change.row_id,
+        divergence.kind,
+        change.old_row,
+        divergence.actual,
+        divergence.error
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Résultat d'une passe
+#[derive(Debug, Default)]
+struct Pass {
+    applied: usize,
+    divergences: Vec<(String, &'static str)>,
+    blocked: Option<String>,
+}
+
+/// Rejoue les écritures en attente, dans l'ordre. Une écriture refusée par
+/// l'autre base arrête la passe ; une base injoignable la fait échouer.
+async fn replicate(pool: &PgPool, dual_write: &DualWrite) -> Result<Pass, sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM row_changes WHERE applied_at < NOW() - make_interval(days => $1)",
+        APPLIED_RETENTION_DAYS
+    )
+    .execute(pool)
+    .await?;
+
+    let changes = sqlx::query_as!(
+        RowChange,
+        r#"SELECT id, table_name, operation, row_id, old_row::text as old_row, new_row::text as new_row, attempts
+           FROM row_changes
+           WHERE applied_at IS NULL
+           ORDER BY id
+           LIMIT $1"#,
+        BATCH_SIZE
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut pass = Pass::default();
+    if changes.is_empty() {
+        return Ok(pass);
+    }
+
+    let columns: Vec<(String, String)> = sqlx::query_as(
+        r#"SELECT table_name::text, column_name::text FROM information_schema.columns
+           WHERE table_schema = 'public' AND table_name = ANY($1) AND is_generated = 'NEVER'
+           ORDER BY ordinal_position"#
+    )
+    .bind(MIRRORED_TABLES)
+    .fetch_all(&dual_write.mirror)
+    .await?;
+    let mut columns_by_table: HashMap<String, Vec<String>> = HashMap::new();
+    for (table, column) in columns {
+        columns_by_table.entry(table).or_default().push(column);
+    }
+
+    for change in &changes {
+        let columns = columns_by_table.get(&change.table_name).map(Vec::as_slice).unwrap_or_default();
+        match apply(&dual_write.mirror, change, columns).await {
+            Ok(divergence) => {
+                if let Some(divergence) = &divergence {
+                    record_divergence(pool, change, divergence).await?;
+                    pass.divergences.push((change.table_name.clone(), divergence.kind));
+                }
+                sqlx::query!(
+                    "UPDATE row_changes SET applied_at = NOW(), attempts = attempts + 1, last_error = NULL WHERE id = $1",
+                    change.id
+                )
+                .execute(pool)
+                .await?;
+                pass.applied += 1;
+            },
+            // Écriture refusée par l'autre base : signalée une fois, retentée ensuite
+            Err(sqlx::Error::Database(e)) => {
+                let error = e.to_string();
+                if change.attempts == 0 {
+                    let divergence = Divergence { kind: "apply_failed", actual: None, error: Some(error.clone()) };
+                    record_divergence(pool, change, &divergence).await?;
+                    pass.divergences.push((change.table_name.clone(), divergence.kind));
+                }
+                sqlx::query!(
+                    "UPDATE row_changes SET attempts = attempts + 1, last_error = $2 WHERE id = $1",
+                    change.id,
+                    error
+                )
+                .execute(pool)
+                .await?;
+                pass.blocked = Some(format!("{} {} {} : {}", change.operation, change.table_name, change.row_id, error));
+                break;
+            },
+            Err(e) => return Err(e),
+        }
+    }
+
+    if !pass.divergences.is_empty() {
+        notify_admins(pool, "dual_write_divergence",
+            &format!("Double écriture : {} écart(s) avec l'autre base", pass.divergences.len()),
+            serde_json::json!({
+                "divergences": pass.divergences.iter()
+                    .map(|(table, kind)| serde_json::json!({ "table": table, "kind": kind }))
+                    .collect::<Vec<_>>()
+            })).await?;
+    }
+    Ok(pass)
+}
+
+/// Lance la copie des écritures, toutes les `DUAL_WRITE_INTERVAL_SECS`
+/// secondes (2 par défaut)
+pub fn spawn(pool: PgPool, dual_write: DualWrite) {
+    let interval_secs = env::var("DUAL_WRITE_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(2);
+
+    tokio::spawn(async move {
+        let period = Duration::from_secs(interval_secs);
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match worker::run_exclusive(&pool, "dual_write", period, replicate(&pool, &dual_write)).await {
+                Some(Ok(pass)) => {
+                    if let Some(blocked) = pass.blocked {
+                        tracing::warn!(applied = pass.applied, "Double écriture bloquée: {}", blocked);
+                    }
+                },
+                None => {},
+                Some(Err(e)) => tracing::error!("Erreur de la double écriture: {}", e),
+            }
+        }
+    });
+}
+
+/// La copie porte sur toutes les plateformes : réservée aux admins de la
+/// plateforme par défaut
+fn platform_admin_only(role: &UserRole, tenant_id: Uuid) -> Option<Response> {
+    (!matches!(role, UserRole::Admin) || tenant_id != DEFAULT_TENANT_ID).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins de la plateforme principale peuvent consulter la double écriture",
+        "code": ErrorCode::Forbidden
+    }))).into_response())
+}
+
+/// Route admin : état de la double écriture (retard de la copie, derniers écarts)
+pub async fn get_dual_write_status(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(dual_write): Extension<Option<DualWrite>>,
+) -> impl IntoResponse {
+    if let Some(response) = platform_admin_only(&user.role, user.tenant_id) {
+        return response;
+    }
+
+    let result = async {
+        let pending = sqlx::query!(
+            r#"SELECT COUNT(*) as "count!", MIN(created_at) as oldest,
+                      (SELECT last_error FROM row_changes WHERE applied_at IS NULL ORDER BY id LIMIT 1) as last_error
+               FROM row_changes WHERE applied_at IS NULL"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        let divergences = sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!" FROM dual_write_divergences"#)
+            .fetch_one(&pool)
+            .await?;
+        let recent = sqlx::query_as!(
+            DualWriteDivergence,
+            "SELECT * FROM dual_write_divergences ORDER BY created_at DESC LIMIT $1",
+            RECENT_DIVERGENCES
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok::<_, sqlx::Error>((pending, divergences, recent))
+    }.await;
+
+    match result {
+        Ok((pending, divergences, recent)) => (StatusCode::OK, Json(serde_json::json!({
+            "enabled": dual_write.is_some(),
+            "serve_from": dual_write.as_ref().map(DualWrite::serve_from),
+            "mirrored_tables": MIRRORED_TABLES,
+            "pending": pending.count,
+            "oldest_pending_at": pending.oldest,
+            "last_error": pending.last_error,
+            "divergences": divergences,
+            "recent_divergences": recent,
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la lecture de la double écriture: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
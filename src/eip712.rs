@@ -0,0 +1,252 @@
+// eip712.rs
+//
+// Données typées EIP-712 des intentions d'investissement : construction du
+// payload à signer par le wallet (eth_signTypedData_v4), calcul du digest et
+// récupération de l'adresse signataire (ECDSA secp256k1).
+
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+use std::env;
+
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const INTENT_TYPE: &str = "InvestmentIntent(address investor,string propertyId,uint256 shares,uint256 pricePerShare,uint256 nonce,uint256 deadline)";
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// Adresse Ethereum (20 octets) depuis sa forme hexadécimale `0x...`
+pub fn parse_address(raw: &str) -> Option<[u8; 20]> {
+    let hex_part = raw.trim().strip_prefix("0x").or_else(|| raw.trim().strip_prefix("0X"))?;
+    hex::decode(hex_part).ok()?.try_into().ok()
+}
+
+/// Adresse au format `0x` + hexadécimal en minuscules
+pub fn format_address(address: &[u8; 20]) -> String {
+    format!("0x{}", hex::encode(address))
+}
+
+pub fn encode_address(address: &[u8; 20]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+pub fn encode_uint(value: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Domaine de signature, lu depuis l'environnement
+#[derive(Clone)]
+pub struct Eip712Domain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: [u8; 20],
+}
+
+impl Eip712Domain {
+    /// Renvoie `None` si l'adresse du contrat n'est pas configurée
+    pub fn from_env() -> Option<Self> {
+        let verifying_contract = parse_address(&env::var("INVESTMENT_CONTRACT_ADDRESS").ok()?)?;
+        Some(Self {
+            name: env::var("EIP712_DOMAIN_NAME").unwrap_or_else(|_| "PropertyInvestment".to_string()),
+            version: env::var("EIP712_DOMAIN_VERSION").unwrap_or_else(|_| "1".to_string()),
+            chain_id: env::var("CHAIN_ID").ok().and_then(|v| v.parse().ok()).unwrap_or(1),
+            verifying_contract,
+        })
+    }
+
+    fn separator(&self) -> [u8; 32] {
+        let mut encoded = Vec::with_capacity(32 * 5);
+        encoded.extend_from_slice(&keccak256(DOMAIN_TYPE.as_bytes()));
+        encoded.extend_from_slice(&keccak256(self.name.as_bytes()));
+        encoded.extend_from_slice(&keccak256(self.version.as_bytes()));
+        encoded.extend_from_slice(&encode_uint(self.chain_id as u128));
+        encoded.extend_from_slice(&encode_address(&self.verifying_contract));
+        keccak256(&encoded)
+    }
+}
+
+/// Message signé par l'investisseur : la cotation qu'il pré-autorise
+pub struct InvestmentIntentMessage {
+    pub investor: [u8; 20],
+    pub property_id: String, // onchain_id de la propriété
+    pub shares: u64,
+    pub price_per_share_wei: u128,
+    pub nonce: u64,
+    pub deadline: u64, // timestamp Unix
+}
+
+impl InvestmentIntentMessage {
+    fn struct_hash(&self) -> [u8; 32] {
+        let mut encoded = Vec::with_capacity(32 * 7);
+        encoded.extend_from_slice(&keccak256(INTENT_TYPE.as_bytes()));
+        encoded.extend_from_slice(&encode_address(&self.investor));
+        encoded.extend_from_slice(&keccak256(self.property_id.as_bytes()));
+        encoded.extend_from_slice(&encode_uint(self.shares as u128));
+        encoded.extend_from_slice(&encode_uint(self.price_per_share_wei));
+        encoded.extend_from_slice(&encode_uint(self.nonce as u128));
+        encoded.extend_from_slice(&encode_uint(self.deadline as u128));
+        keccak256(&encoded)
+    }
+
+    /// Digest signé : keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))
+    pub fn digest(&self, domain: &Eip712Domain) -> [u8; 32] {
+        let mut encoded = Vec::with_capacity(66);
+        encoded.extend_from_slice(b"\x19\x01");
+        encoded.extend_from_slice(&domain.separator());
+        encoded.extend_from_slice(&self.struct_hash());
+        keccak256(&encoded)
+    }
+
+    /// Payload au format attendu par `eth_signTypedData_v4`
+    /// (les uint256 sont transmis en chaînes décimales)
+    pub fn typed_data(&self, domain: &Eip712Domain) -> serde_json::Value {
+        serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "InvestmentIntent": [
+                    { "name": "investor", "type": "address" },
+                    { "name": "propertyId", "type": "string" },
+                    { "name": "shares", "type": "uint256" },
+                    { "name": "pricePerShare", "type": "uint256" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" }
+                ]
+            },
+            "primaryType": "InvestmentIntent",
+            "domain": {
+                "name": domain.name,
+                "version": domain.version,
+                "chainId": domain.chain_id,
+                "verifyingContract": format_address(&domain.verifying_contract)
+            },
+            "message": {
+                "investor": format_address(&self.investor),
+                "propertyId": self.property_id,
+                "shares": self.shares.to_string(),
+                "pricePerShare": self.price_per_share_wei.to_string(),
+                "nonce": self.nonce.to_string(),
+                "deadline": self.deadline.to_string()
+            }
+        })
+    }
+}
+
+/// Digest d'un message signé avec `personal_sign` (EIP-191) :
+/// keccak256("\x19Ethereum Signed Message:\n" ‖ longueur ‖ message)
+pub fn personal_message_digest(message: &str) -> [u8; 32] {
+    let mut encoded = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    encoded.extend_from_slice(message.as_bytes());
+    keccak256(&encoded)
+}
+
+/// Retrouve l'adresse qui a signé le digest (signature `0x` r ‖ s ‖ v sur 65 octets)
+pub fn recover_signer(digest: &[u8; 32], signature_hex: &str) -> Result<[u8; 20], String> {
+    let raw = signature_hex.trim();
+    let bytes = hex::decode(raw.strip_prefix("0x").unwrap_or(raw))
+        .map_err(|_| "Signature non hexadécimale".to_string())?;
+    if bytes.len() != 65 {
+        return Err("La signature doit faire 65 octets".to_string());
+    }
+
+    // v vaut 27/28 (format Ethereum) ou 0/1
+    let v = match bytes[64] {
+        27 | 28 => bytes[64] - 27,
+        0 | 1 => bytes[64],
+        _ => return Err("Valeur v de la signature invalide".to_string()),
+    };
+    let mut signature = Signature::from_slice(&bytes[..64]).map_err(|_| "Signature invalide".to_string())?;
+    let mut recovery_id = RecoveryId::from_byte(v).ok_or_else(|| "Signature invalide".to_string())?;
+    // ecrecover accepte les s « hauts » : on normalise avant la récupération
+    if let Some(normalized) = signature.normalize_s() {
+        signature = normalized;
+        recovery_id = RecoveryId::new(!recovery_id.is_y_odd(), recovery_id.is_x_reduced());
+    }
+
+    let key = VerifyingKey::recover_from_prehash(digest, &signature, recovery_id)
+        .map_err(|_| "Signature invalide".to_string())?;
+    let point = key.to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Exemple « Mail » de la spécification EIP-712
+    const MAIL_TYPE: &str = "Mail(Person from,Person to,string contents)Person(string name,address wallet)";
+    const PERSON_TYPE: &str = "Person(string name,address wallet)";
+
+    fn person_hash(name: &str, wallet: &str) -> [u8; 32] {
+        let mut encoded = Vec::with_capacity(32 * 3);
+        encoded.extend_from_slice(&keccak256(PERSON_TYPE.as_bytes()));
+        encoded.extend_from_slice(&keccak256(name.as_bytes()));
+        encoded.extend_from_slice(&encode_address(&parse_address(wallet).unwrap()));
+        keccak256(&encoded)
+    }
+
+    fn mail_domain() -> Eip712Domain {
+        Eip712Domain {
+            name: "Ether Mail".to_string(),
+            version: "1".to_string(),
+            chain_id: 1,
+            verifying_contract: parse_address("0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC").unwrap(),
+        }
+    }
+
+    fn mail_digest() -> [u8; 32] {
+        let mut mail = Vec::with_capacity(32 * 4);
+        mail.extend_from_slice(&keccak256(MAIL_TYPE.as_bytes()));
+        mail.extend_from_slice(&person_hash("Cow", "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"));
+        mail.extend_from_slice(&person_hash("Bob", "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"));
+        mail.extend_from_slice(&keccak256(b"Hello, Bob!"));
+        assert_eq!(
+            hex::encode(keccak256(&mail)),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+
+        let mut encoded = Vec::with_capacity(66);
+        encoded.extend_from_slice(b"\x19\x01");
+        encoded.extend_from_slice(&mail_domain().separator());
+        encoded.extend_from_slice(&keccak256(&mail));
+        keccak256(&encoded)
+    }
+
+    #[test]
+    fn domain_separator_matches_spec() {
+        assert_eq!(
+            hex::encode(mail_domain().separator()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+    }
+
+    #[test]
+    fn mail_digest_matches_spec() {
+        assert_eq!(
+            hex::encode(mail_digest()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn recovers_spec_signer() {
+        // Signature de l'exemple par la clé keccak256("cow")
+        let signature = "0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d\
+                         07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562\
+                         1c";
+        let signer = recover_signer(&mail_digest(), signature).unwrap();
+        assert_eq!(format_address(&signer), "0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826");
+    }
+}
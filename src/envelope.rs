@@ -0,0 +1,193 @@
+// envelope.rs
+//
+// Forme commune des réponses JSON : `{data, meta, error}`. Les listes passent
+// par `list(...)`, qui indique le champ portant les éléments ; le middleware
+// `shape_responses` place ce champ dans `data` et le nombre d'éléments (avec
+// `page` et `total`, et les autres champs de la réponse) dans `meta`. Les
+// autres réponses sont placées telles quelles dans `data`, et les erreurs
+// (`{"error": ..., "code": ...}`) dans `error` sous la forme `{code, message}`
+// (voir error_codes.rs).
+//
+// Pendant la transition, l'ancienne forme (`{properties, count}`, objets nus)
+// reste disponible : par requête avec l'en-tête `X-Response-Shape: legacy`, ou
+// par défaut pour tout le déploiement avec `LEGACY_RESPONSE_SHAPE=true`
+// (`X-Response-Shape: envelope` pour la nouvelle forme). Les routes publiques
+// versionnées (`/public/v1`) et `/health` gardent leur forme.
+
+use axum::{
+    body::{self, Body},
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::env;
+
+use crate::pagination::PageInfo;
+
+/// En-tête de choix de la forme des réponses, par requête
+const SHAPE_HEADER: &str = "x-response-shape";
+
+/// Au-delà, la réponse est transmise telle quelle
+const MAX_SHAPED_BODY: usize = 8 * 1024 * 1024;
+
+/// Routes dont la forme ne change pas
+const UNSHAPED_PREFIXES: &[&str] = &["/public/", "/health"];
+
+/// Champ d'une réponse de liste portant les éléments (posé par `list`)
+#[derive(Clone, Copy)]
+struct ListField(&'static str);
+
+/// Réponse de liste : `{<champ>: [...], count, ...}` dans l'ancienne forme,
+/// `{data: [...], meta: {count, page, total, ...}}` sinon (`page` nul pour
+/// une liste non paginée ou paginée par curseur, `total` nul s'il n'est pas compté)
+pub struct ListResponse {
+    field: &'static str,
+    items: Value,
+    count: usize,
+    extra: Map<String, Value>,
+}
+
+/// Liste d'éléments, sous le champ `field` dans l'ancienne forme
+pub fn list<T: Serialize>(field: &'static str, items: &[T]) -> ListResponse {
+    ListResponse {
+        field,
+        items: serde_json::to_value(items).unwrap_or_else(|_| Value::Array(Vec::new())),
+        count: items.len(),
+        extra: Map::new(),
+    }
+}
+
+impl ListResponse {
+    /// Page renvoyée (`page`, `per_page`, `has_more`, `next_cursor`)
+    pub fn paginated(self, page: PageInfo) -> Self {
+        self.with("page", page.page)
+            .with("per_page", page.per_page)
+            .with("has_more", page.has_more)
+            .with("next_cursor", page.next_cursor)
+    }
+
+    /// Champ supplémentaire, placé dans `meta` dans la nouvelle forme
+    pub fn with(mut self, name: &str, value: impl Serialize) -> Self {
+        self.extra.insert(name.to_string(), serde_json::to_value(value).unwrap_or(Value::Null));
+        self
+    }
+}
+
+impl IntoResponse for ListResponse {
+    fn into_response(self) -> Response {
+        let mut body = self.extra;
+        body.insert(self.field.to_string(), self.items);
+        body.insert("count".to_string(), self.count.into());
+
+        let mut response = (StatusCode::OK, Json(Value::Object(body))).into_response();
+        response.extensions_mut().insert(ListField(self.field));
+        response
+    }
+}
+
+/// Forme des réponses par défaut (`LEGACY_RESPONSE_SHAPE`)
+#[derive(Clone, Copy)]
+pub struct EnvelopeConfig {
+    legacy_default: bool,
+}
+
+impl EnvelopeConfig {
+    pub fn from_env() -> Self {
+        let legacy_default = env::var("LEGACY_RESPONSE_SHAPE")
+            .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        Self { legacy_default }
+    }
+
+    fn wants_legacy(&self, headers: &HeaderMap) -> bool {
+        match headers.get(SHAPE_HEADER).and_then(|v| v.to_str().ok()).map(str::trim) {
+            Some(shape) if shape.eq_ignore_ascii_case("legacy") => true,
+            Some(shape) if shape.eq_ignore_ascii_case("envelope") => false,
+            _ => self.legacy_default,
+        }
+    }
+}
+
+/// Réponse dans la nouvelle forme, à partir du corps de l'ancienne
+fn wrap(status: StatusCode, body: Value, list_field: Option<&str>) -> Value {
+    let envelope = |data: Value, meta: Value, error: Value| serde_json::json!({
+        "data": data,
+        "meta": meta,
+        "error": error
+    });
+
+    match (body, list_field) {
+        (Value::Object(mut body), _) if status.is_client_error() || status.is_server_error() => {
+            let message = body.remove("error").unwrap_or(Value::Null);
+            let error = match body.remove("code") {
+                Some(code) => serde_json::json!({ "code": code, "message": message }),
+                None => message,
+            };
+            // Détails accompagnant l'erreur (défi de confirmation, champs invalides...)
+            let data = match body.is_empty() {
+                true => Value::Null,
+                false => Value::Object(body),
+            };
+            envelope(data, Value::Null, error)
+        },
+        (Value::Object(mut body), Some(field)) => {
+            let data = body.remove(field).unwrap_or(Value::Array(Vec::new()));
+            let mut meta = Map::new();
+            for key in ["count", "page", "total"] {
+                meta.insert(key.to_string(), body.remove(key).unwrap_or(Value::Null));
+            }
+            meta.extend(body);
+            envelope(data, Value::Object(meta), Value::Null)
+        },
+        (body, _) => envelope(body, Value::Null, Value::Null),
+    }
+}
+
+/// Middleware : réponses JSON placées dans l'enveloppe `{data, meta, error}`,
+/// sauf ancienne forme demandée
+pub async fn shape_responses(
+    State(config): State<EnvelopeConfig>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let path = request.uri().path();
+    if config.wants_legacy(request.headers()) || UNSHAPED_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) {
+        return next.run(request).await;
+    }
+
+    let response = next.run(request).await;
+    let is_json = response.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, payload) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(payload).await {
+        Ok(bytes) => bytes,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "data": null,
+            "meta": null,
+            "error": format!("Erreur lors de la lecture de la réponse: {}", e)
+        }))).into_response(),
+    };
+    let shaped = match bytes.len() <= MAX_SHAPED_BODY {
+        true => serde_json::from_slice::<Value>(&bytes).ok(),
+        false => None,
+    };
+    let Some(value) = shaped else {
+        return Response::from_parts(parts, body::boxed(Body::from(bytes)));
+    };
+
+    let list_field = parts.extensions.get::<ListField>().map(|field| field.0);
+    let shaped = wrap(parts.status, value, list_field).to_string();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    Response::from_parts(parts, body::boxed(Body::from(shaped)))
+}
@@ -0,0 +1,237 @@
+// error_codes.rs
+//
+// Codes d'erreur stables, lisibles par les clients sans dépendre du message
+// (en français, susceptible de changer). Les échecs de routes.rs portent un
+// code précis (`"code": "PROPERTY_NOT_FOUND"` à côté de `"error"`) ; toute
+// autre réponse d'erreur reçoit du middleware `add_error_codes` le code
+// générique de son statut HTTP (`NOT_FOUND`, `CONFLICT`...), les erreurs en
+// texte brut étant converties en JSON. Le catalogue est servi par
+// `GET /api/meta/error-codes`.
+
+use axum::{
+    body::{self, Body},
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+use crate::envelope;
+
+/// Au-delà, la réponse d'erreur est transmise telle quelle
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+/// Déclare les codes avec leur statut HTTP et leur description, dans l'ordre du catalogue
+macro_rules! error_codes {
+    ($($variant:ident => ($code:literal, $status:ident, $description:literal),)+) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ErrorCode {
+            $($variant,)+
+        }
+
+        impl ErrorCode {
+            pub const ALL: &'static [ErrorCode] = &[$(ErrorCode::$variant,)+];
+
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $code,)+
+                }
+            }
+
+            pub fn status(self) -> StatusCode {
+                match self {
+                    $(ErrorCode::$variant => StatusCode::$status,)+
+                }
+            }
+
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $description,)+
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    // Codes génériques, par statut HTTP
+    BadRequest => ("BAD_REQUEST", BAD_REQUEST, "Requête invalide"),
+    Unauthorized => ("UNAUTHORIZED", UNAUTHORIZED, "Authentification absente ou invalide"),
+    Forbidden => ("FORBIDDEN", FORBIDDEN, "Action non autorisée pour cet utilisateur"),
+    NotFound => ("NOT_FOUND", NOT_FOUND, "Ressource introuvable"),
+    Conflict => ("CONFLICT", CONFLICT, "Conflit avec l'état actuel de la ressource"),
+    Gone => ("GONE", GONE, "Ressource expirée ou retirée"),
+    PayloadTooLarge => ("PAYLOAD_TOO_LARGE", PAYLOAD_TOO_LARGE, "Corps de requête trop volumineux"),
+    UnprocessableEntity => ("UNPROCESSABLE_ENTITY", UNPROCESSABLE_ENTITY, "Requête bien formée mais refusée"),
+    PreconditionRequired => ("PRECONDITION_REQUIRED", PRECONDITION_REQUIRED, "Confirmation requise avant l'action"),
+    TooManyRequests => ("TOO_MANY_REQUESTS", TOO_MANY_REQUESTS, "Trop de requêtes, réessayer plus tard"),
+    InternalError => ("INTERNAL_ERROR", INTERNAL_SERVER_ERROR, "Erreur interne du serveur"),
+    BadGateway => ("BAD_GATEWAY", BAD_GATEWAY, "Service externe en erreur"),
+    ServiceUnavailable => ("SERVICE_UNAVAILABLE", SERVICE_UNAVAILABLE, "Service indisponible ou non configuré"),
+
+    // Base de données
+    DatabaseError => ("DATABASE_ERROR", INTERNAL_SERVER_ERROR, "Erreur de la base de données"),
+
+    // Paramètres de requête
+    InvalidId => ("INVALID_ID", BAD_REQUEST, "Identifiant invalide dans `?ids=`"),
+    TooManyIds => ("TOO_MANY_IDS", BAD_REQUEST, "Trop d'identifiants dans `?ids=`"),
+    InvalidInclude => ("INVALID_INCLUDE", BAD_REQUEST, "Relation inconnue dans `?include=`"),
+    InvalidFields => ("INVALID_FIELDS", BAD_REQUEST, "Champ inconnu dans `?fields=`"),
+    UnknownTags => ("UNKNOWN_TAGS", BAD_REQUEST, "Tags inexistants"),
+    UnknownParameter => ("UNKNOWN_PARAMETER", BAD_REQUEST, "Paramètre de requête non reconnu par la route"),
+    InvalidFilter => ("INVALID_FILTER", BAD_REQUEST, "Valeur de filtre invalide"),
+    InvalidSort => ("INVALID_SORT", BAD_REQUEST, "Clé de tri non autorisée dans `?sort=`"),
+    InvalidPagination => ("INVALID_PAGINATION", BAD_REQUEST, "Page, taille de page ou curseur invalide"),
+
+    // Utilisateurs
+    AdminRequired => ("ADMIN_REQUIRED", FORBIDDEN, "Action réservée aux admins"),
+    ManagerRequired => ("MANAGER_REQUIRED", FORBIDDEN, "Action réservée aux managers et admins"),
+    UserNotFound => ("USER_NOT_FOUND", NOT_FOUND, "Utilisateur inexistant"),
+    WalletAlreadyRegistered => ("WALLET_ALREADY_REGISTERED", CONFLICT, "Wallet déjà rattaché à un compte"),
+    SelfRoleChange => ("SELF_ROLE_CHANGE", FORBIDDEN, "Un utilisateur ne peut pas modifier son propre rôle"),
+
+    // Propriétés
+    PropertyNotFound => ("PROPERTY_NOT_FOUND", NOT_FOUND, "Propriété inexistante ou non visible"),
+    PropertyLocked => ("PROPERTY_LOCKED", FORBIDDEN, "Propriété validée : modification et suppression impossibles"),
+    PropertyHasInvestments => ("PROPERTY_HAS_INVESTMENTS", CONFLICT, "Propriété avec investissements : suppression en cascade requise (`?cascade=true`)"),
+    PropertyHasConfirmedInvestments => ("PROPERTY_HAS_CONFIRMED_INVESTMENTS", CONFLICT, "Propriété avec investissements confirmés : suppression impossible, même en cascade"),
+    PropertyNotDraft => ("PROPERTY_NOT_DRAFT", CONFLICT, "Seul un brouillon peut être soumis"),
+    PropertyIncomplete => ("PROPERTY_INCOMPLETE", UNPROCESSABLE_ENTITY, "Informations manquantes pour soumettre la propriété"),
+    PropertyNotSubmitted => ("PROPERTY_NOT_SUBMITTED", CONFLICT, "La propriété doit d'abord être soumise"),
+    PropertyNotValidated => ("PROPERTY_NOT_VALIDATED", FORBIDDEN, "Investissement impossible dans une propriété non validée"),
+    PropertyNotPublished => ("PROPERTY_NOT_PUBLISHED", FORBIDDEN, "Propriété validée mais pas encore publiée"),
+    InvalidStatusTransition => ("INVALID_STATUS_TRANSITION", BAD_REQUEST, "Changement de statut non permis"),
+    SharesBelowSold => ("SHARES_BELOW_SOLD", CONFLICT, "Parts émises inférieures aux parts déjà vendues"),
+    InvalidOnchainId => ("INVALID_ONCHAIN_ID", BAD_REQUEST, "Identifiant on-chain invalide (1 à 64 caractères : lettres, chiffres, - et _)"),
+    OnchainIdTaken => ("ONCHAIN_ID_TAKEN", CONFLICT, "Identifiant on-chain déjà attribué à une autre propriété"),
+    InvalidContractAddress => ("INVALID_CONTRACT_ADDRESS", BAD_REQUEST, "Adresse de contrat invalide"),
+    ContractAddressMismatch => ("CONTRACT_ADDRESS_MISMATCH", UNPROCESSABLE_ENTITY, "Adresse différente du contrat registre configuré"),
+    OnchainBindingLocked => ("ONCHAIN_BINDING_LOCKED", CONFLICT, "Propriété enregistrée on-chain : identifiant et contrat non modifiables"),
+
+    // Co-gestionnaires
+    PropertyManagersForbidden => ("PROPERTY_MANAGERS_FORBIDDEN", FORBIDDEN, "Seuls le créateur, les co-gestionnaires owner et l'admin gèrent les co-gestionnaires"),
+    PropertyManagerNotFound => ("PROPERTY_MANAGER_NOT_FOUND", NOT_FOUND, "Co-gestionnaire inexistant"),
+    InvalidPropertyManager => ("INVALID_PROPERTY_MANAGER", UNPROCESSABLE_ENTITY, "Seul un manager actif de la plateforme peut être co-gestionnaire"),
+    PropertyCreatorManager => ("PROPERTY_CREATOR_MANAGER", CONFLICT, "Le créateur gère déjà la propriété"),
+
+    // Investissements
+    InvestmentNotFound => ("INVESTMENT_NOT_FOUND", NOT_FOUND, "Investissement inexistant"),
+    InvestmentForbidden => ("INVESTMENT_FORBIDDEN", FORBIDDEN, "Investissement d'un autre utilisateur"),
+    InvestmentRefunded => ("INVESTMENT_REFUNDED", CONFLICT, "Investissement remboursé, non modifiable"),
+    InvestmentSettled => ("INVESTMENT_SETTLED", FORBIDDEN, "Investissement réglé on-chain, corrigeable par un admin uniquement"),
+    ReasonRequired => ("REASON_REQUIRED", BAD_REQUEST, "Motif obligatoire pour corriger un investissement réglé"),
+    InvalidAmount => ("INVALID_AMOUNT", BAD_REQUEST, "Montant ou nombre de parts non positif"),
+    InvalidTxHash => ("INVALID_TX_HASH", BAD_REQUEST, "Hash de transaction invalide"),
+    TxHashSetAtSettlement => ("TX_HASH_SET_AT_SETTLEMENT", BAD_REQUEST, "Hash de transaction renseigné au règlement uniquement"),
+    AmountBelowSharePrice => ("AMOUNT_BELOW_SHARE_PRICE", UNPROCESSABLE_ENTITY, "Montant inférieur au prix d'une part"),
+
+    // Intentions d'investissement
+    IntentNotFound => ("INTENT_NOT_FOUND", NOT_FOUND, "Intention inexistante"),
+    IntentNotSigned => ("INTENT_NOT_SIGNED", CONFLICT, "Intention pas encore signée"),
+    IntentAlreadyUsed => ("INTENT_ALREADY_USED", CONFLICT, "Intention déjà utilisée"),
+    IntentMismatch => ("INTENT_MISMATCH", CONFLICT, "Investissement différent de la cotation signée"),
+
+    // Investissements observés on-chain
+    InvestmentAlreadyRecorded => ("INVESTMENT_ALREADY_RECORDED", CONFLICT, "Transaction déjà enregistrée pour cet investissement"),
+    OnchainInvestmentNotFound => ("ONCHAIN_INVESTMENT_NOT_FOUND", NOT_FOUND, "Événement on-chain inexistant"),
+    OnchainInvestmentNotPending => ("ONCHAIN_INVESTMENT_NOT_PENDING", CONFLICT, "Événement on-chain déjà rapproché ou écarté"),
+    OnchainInvestmentUnmatched => ("ONCHAIN_INVESTMENT_UNMATCHED", UNPROCESSABLE_ENTITY, "Utilisateur ou propriété de l'événement introuvable"),
+
+    // File de tâches
+    JobNotFound => ("JOB_NOT_FOUND", NOT_FOUND, "Tâche inexistante"),
+    JobNotFailed => ("JOB_NOT_FAILED", CONFLICT, "Seule une tâche en échec définitif peut être relancée"),
+
+    // Sauvegardes
+    BackupNotFound => ("BACKUP_NOT_FOUND", NOT_FOUND, "Sauvegarde inexistante"),
+
+    // Distributions
+    InvalidPaidThrough => ("INVALID_PAID_THROUGH", BAD_REQUEST, "Fin de période versée dans le futur"),
+    DistributionRunOutOfOrder => ("DISTRIBUTION_RUN_OUT_OF_ORDER", CONFLICT, "Fin de période antérieure ou égale au dernier versement"),
+}
+
+impl ErrorCode {
+    /// Code générique d'un statut HTTP d'erreur
+    pub fn for_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+            StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::CONFLICT => ErrorCode::Conflict,
+            StatusCode::GONE => ErrorCode::Gone,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::UNPROCESSABLE_ENTITY => ErrorCode::UnprocessableEntity,
+            StatusCode::PRECONDITION_REQUIRED => ErrorCode::PreconditionRequired,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::TooManyRequests,
+            StatusCode::BAD_GATEWAY => ErrorCode::BadGateway,
+            StatusCode::SERVICE_UNAVAILABLE => ErrorCode::ServiceUnavailable,
+            status if status.is_server_error() => ErrorCode::InternalError,
+            _ => ErrorCode::BadRequest,
+        }
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Route publique : catalogue des codes d'erreur
+pub async fn get_error_codes() -> impl IntoResponse {
+    let catalog = ErrorCode::ALL.iter().map(|code| serde_json::json!({
+        "code": code,
+        "status": code.status().as_u16(),
+        "description": code.description()
+    })).collect::<Vec<_>>();
+    envelope::list("error_codes", &catalog)
+}
+
+/// Middleware : code générique ajouté aux réponses d'erreur JSON qui n'en ont
+/// pas ; les erreurs en texte brut (extracteurs d'authentification, rejets
+/// d'axum) ou sans corps deviennent `{"error": <texte>, "code": ...}`
+pub async fn add_error_codes(request: Request<Body>, next: Next<Body>) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+    let content_type = response.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let is_json = content_type.as_deref().is_some_and(|v| v.starts_with("application/json"));
+    let is_text = content_type.as_deref().is_none_or(|v| v.starts_with("text/plain"));
+    if !is_json && !is_text {
+        return response;
+    }
+
+    let (mut parts, payload) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(payload).await {
+        Ok(bytes) => bytes,
+        Err(_) => return status.into_response(),
+    };
+    if bytes.len() > MAX_ERROR_BODY {
+        return Response::from_parts(parts, body::boxed(Body::from(bytes)));
+    }
+    let mut error = if is_json {
+        match serde_json::from_slice::<Value>(&bytes) {
+            Ok(Value::Object(error)) if !error.contains_key("code") => error,
+            _ => return Response::from_parts(parts, body::boxed(Body::from(bytes))),
+        }
+    } else {
+        let text = String::from_utf8_lossy(&bytes).trim().to_string();
+        let message = match text.is_empty() {
+            true => status.canonical_reason().unwrap_or("Erreur").to_string(),
+            false => text,
+        };
+        let mut error = serde_json::Map::new();
+        error.insert("error".to_string(), Value::String(message));
+        error
+    };
+
+    error.insert("code".to_string(), serde_json::json!(ErrorCode::for_status(status)));
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    Response::from_parts(parts, body::boxed(Body::from(Value::Object(error).to_string())))
+}
@@ -0,0 +1,301 @@
+// exposure.rs
+//
+// Limites d'exposition des investisseurs, en ETH ou en pourcentage du prix
+// total d'une propriété. Les limites générales (globales ou par propriété) sont
+// fixées par un admin pour sa plateforme, qui peut y déroger pour un
+// utilisateur. La vérification
+// se fait dans la transaction de création de l'investissement, la ligne de
+// l'utilisateur étant verrouillée pour sérialiser ses investissements.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use bigdecimal::{BigDecimal, Zero};
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::envelope;
+use crate::money::TokenAmount;
+use crate::models::{ExposureLimit, ExposureLimitListQuery, SetExposureLimitRequest, UserRole};
+
+/// Limite qu'un investissement dépasserait, avec la capacité restante
+#[derive(Debug, Serialize)]
+pub struct ExposureBreach {
+    pub scope: &'static str, // "global" ou "property"
+    pub limit: &'static str, // "max_eth" ou "max_pct"
+    pub max: BigDecimal,
+    pub overridden: bool, // Dérogation propre à l'utilisateur
+    pub current_eth: BigDecimal,
+    pub requested_eth: BigDecimal,
+    pub remaining_eth: BigDecimal,
+}
+
+impl IntoResponse for ExposureBreach {
+    fn into_response(self) -> Response {
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+            "error": format!(
+                "Limite d'exposition dépassée : vous pouvez encore investir {} ETH",
+                self.remaining_eth.normalized()
+            ),
+            "exposure": self
+        }))).into_response()
+    }
+}
+
+/// Vérifie qu'un investissement de `amount_eth` reste dans les limites de
+/// l'utilisateur. À appeler dans la transaction qui crée l'investissement :
+/// la ligne de l'utilisateur reste verrouillée jusqu'à sa fin.
+pub async fn check_exposure(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    property_id: Uuid,
+    amount_eth: &TokenAmount,
+) -> Result<Option<ExposureBreach>, sqlx::Error> {
+    sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", user_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    // Pour chaque portée, la dérogation de l'utilisateur l'emporte sur la limite générale
+    let limits = sqlx::query!(
+        r#"SELECT DISTINCT ON (property_id IS NULL)
+                  property_id IS NULL as "global!", user_id IS NOT NULL as "overridden!", max_eth, max_pct
+           FROM exposure_limits
+           WHERE (user_id = $1 OR user_id IS NULL) AND (property_id = $2 OR property_id IS NULL)
+           AND tenant_id = (SELECT tenant_id FROM users WHERE id = $1)
+           ORDER BY property_id IS NULL, user_id IS NULL"#,
+        user_id,
+        property_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    if limits.is_empty() {
+        return Ok(None);
+    }
+
+    let exposure = sqlx::query!(
+        r#"SELECT COALESCE(SUM(amount_eth), 0) as "total_eth!",
+                  COALESCE(SUM(amount_eth) FILTER (WHERE property_id = $2), 0) as "property_eth!",
+                  (SELECT total_price FROM properties WHERE id = $2) as "total_price?"
+           FROM investments
+           WHERE user_id = $1 AND status <> 'refunded'"#,
+        user_id,
+        property_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let property_limit = limits.iter().find(|limit| !limit.global);
+    let global_limit = limits.iter().find(|limit| limit.global);
+    let pct_of_property = |pct: &BigDecimal| {
+        exposure.total_price.as_ref().map(|total| total * pct / BigDecimal::from(100))
+    };
+
+    // (portée, limite, plafond, dérogation, exposition actuelle, plafond en ETH)
+    let mut caps: Vec<(&'static str, &'static str, BigDecimal, bool, &BigDecimal, BigDecimal)> = Vec::new();
+    if let Some(limit) = property_limit {
+        if let Some(max_eth) = &limit.max_eth {
+            caps.push(("property", "max_eth", max_eth.clone(), limit.overridden, &exposure.property_eth, max_eth.clone()));
+        }
+        if let Some((max_pct, cap)) = limit.max_pct.as_ref().and_then(|pct| Some((pct, pct_of_property(pct)?))) {
+            caps.push(("property", "max_pct", max_pct.clone(), limit.overridden, &exposure.property_eth, cap));
+        }
+    }
+    if let Some(limit) = global_limit {
+        if let Some(max_eth) = &limit.max_eth {
+            caps.push(("global", "max_eth", max_eth.clone(), limit.overridden, &exposure.total_eth, max_eth.clone()));
+        }
+        // Pourcentage par défaut, sauf si la propriété a sa propre limite
+        if property_limit.is_none() {
+            if let Some((max_pct, cap)) = limit.max_pct.as_ref().and_then(|pct| Some((pct, pct_of_property(pct)?))) {
+                caps.push(("global", "max_pct", max_pct.clone(), limit.overridden, &exposure.property_eth, cap));
+            }
+        }
+    }
+
+    // La limite la plus contraignante détermine la capacité restante
+    let tightest = caps
+        .into_iter()
+        .map(|(scope, limit, max, overridden, current, cap)| {
+            let remaining = (&cap - current).max(BigDecimal::zero());
+            (scope, limit, max, overridden, current, remaining)
+        })
+        .min_by(|a, b| a.5.cmp(&b.5));
+
+    Ok(match tightest {
+        Some((scope, limit, max, overridden, current, remaining)) if *amount_eth.as_decimal() > remaining => Some(ExposureBreach {
+            scope,
+            limit,
+            max,
+            overridden,
+            current_eth: current.clone(),
+            requested_eth: amount_eth.as_decimal().clone(),
+            remaining_eth: remaining,
+        }),
+        _ => None,
+    })
+}
+
+/// Vérification préalable, hors transaction (intentions et paiements en euros) :
+/// la vérification définitive a lieu à la création de l'investissement
+pub async fn ensure_within_limits(pool: &PgPool, user_id: Uuid, property_id: Uuid, amount_eth: &TokenAmount) -> Result<(), Response> {
+    let result = async {
+        let mut conn = pool.acquire().await?;
+        check_exposure(&mut conn, user_id, property_id, amount_eth).await
+    }
+    .await;
+
+    match result {
+        Ok(None) => Ok(()),
+        Ok(Some(breach)) => Err(breach.into_response()),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification des limites d'exposition: {}", e)
+        }))).into_response()),
+    }
+}
+
+fn admin_only(user: &SessionUser) -> Option<Response> {
+    (!matches!(user.role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer les limites d'exposition"
+    }))).into_response())
+}
+
+/// Enregistre la limite d'une portée (remplace la limite existante) ;
+/// l'utilisateur et la propriété visés sont ceux de la plateforme de l'admin
+async fn upsert_limit(pool: &PgPool, admin: &SessionUser, user_id: Option<Uuid>, payload: SetExposureLimitRequest) -> Response {
+    if payload.max_eth.as_ref().is_some_and(|max| *max <= BigDecimal::zero()) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "max_eth doit être positif"
+        }))).into_response();
+    }
+    if payload.max_pct.as_ref().is_some_and(|pct| *pct <= BigDecimal::zero() || *pct > BigDecimal::from(100)) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "max_pct doit être compris entre 0 (exclu) et 100"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        ExposureLimit,
+        r#"INSERT INTO exposure_limits (tenant_id, user_id, property_id, max_eth, max_pct, updated_by)
+           SELECT $6, $1, $2, $3, $4, $5
+           WHERE ($1::uuid IS NULL OR EXISTS(SELECT 1 FROM users WHERE id = $1 AND tenant_id = $6))
+           AND ($2::uuid IS NULL OR EXISTS(SELECT 1 FROM properties WHERE id = $2 AND tenant_id = $6))
+           ON CONFLICT (tenant_id,
+                        (COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid)),
+                        (COALESCE(property_id, '00000000-0000-0000-0000-000000000000'::uuid)))
+           DO UPDATE SET max_eth = EXCLUDED.max_eth, max_pct = EXCLUDED.max_pct,
+                         updated_by = EXCLUDED.updated_by, updated_at = NOW()
+           RETURNING id, user_id, property_id, max_eth, max_pct, updated_by, updated_at"#,
+        user_id,
+        payload.property_id,
+        payload.max_eth,
+        payload.max_pct,
+        admin.id,
+        admin.tenant_id
+    )
+    .fetch_optional(pool)
+    .await {
+        Ok(Some(limit)) => (StatusCode::OK, Json(serde_json::json!({
+            "limit": limit,
+            "message": "Limite d'exposition enregistrée"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Utilisateur ou propriété non trouvé"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : limites et dérogations de la plateforme (`?user_id=` et
+/// `?property_id=` pour filtrer)
+pub async fn get_exposure_limits(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<ExposureLimitListQuery>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        ExposureLimit,
+        r#"SELECT id, user_id, property_id, max_eth, max_pct, updated_by, updated_at
+           FROM exposure_limits
+           WHERE ($1::uuid IS NULL OR user_id = $1)
+           AND ($2::uuid IS NULL OR property_id = $2)
+           AND tenant_id = $3
+           ORDER BY user_id IS NOT NULL, property_id IS NOT NULL, updated_at DESC"#,
+        query.user_id,
+        query.property_id,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(limits) => envelope::list("limits", &limits).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : fixer la limite générale, globale ou d'une propriété (`property_id`)
+pub async fn set_exposure_limit(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<SetExposureLimitRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+    if payload.max_eth.is_none() && payload.max_pct.is_none() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Au moins une limite est requise (max_eth ou max_pct)"
+        }))).into_response();
+    }
+    upsert_limit(&pool, &user, None, payload).await
+}
+
+/// Route admin : dérogation pour un utilisateur. Elle remplace la limite générale
+/// de même portée ; sans `max_eth` ni `max_pct`, l'utilisateur en est exempté.
+pub async fn set_user_exposure_limit(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<SetExposureLimitRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+    upsert_limit(&pool, &user, Some(user_id), payload).await
+}
+
+/// Route admin : supprimer une limite ou une dérogation
+pub async fn delete_exposure_limit(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(limit_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+
+    match sqlx::query!("DELETE FROM exposure_limits WHERE id = $1 AND tenant_id = $2", limit_id, user.tenant_id)
+        .execute(&pool)
+        .await {
+        Ok(result) if result.rows_affected() > 0 => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Limite d'exposition supprimée"
+        }))).into_response(),
+        Ok(_) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Limite d'exposition non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
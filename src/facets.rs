@@ -0,0 +1,190 @@
+// facets.rs
+//
+// Filtres à facettes des propriétés : `GET /api/properties` filtre par pays,
+// région et ville (géocodés, voir geocoding.rs), par type et par tranche de
+// rendement annuel (fonction SQL `property_yield_bucket`), et
+// `GET /api/properties/facets` compte, en une requête, les propriétés visibles
+// par valeur de chacune de ces facettes. Chaque facette est comptée avec tous
+// les filtres demandés sauf le sien : le frontend affiche ainsi, pour chaque
+// valeur, le nombre de résultats qu'il obtiendrait en la choisissant.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::PgPool;
+
+use crate::auth::BearerAuthUser;
+use crate::error_codes::ErrorCode;
+use crate::models::{FacetCount, PropertyFacetQuery, PropertyFacets, UserRole};
+use crate::tags;
+use crate::taxes;
+
+/// Tranches de rendement annuel (en %), dans l'ordre, telles que renvoyées par
+/// `property_yield_bucket`
+pub const YIELD_BUCKETS: &[&str] = &["0-3", "3-5", "5-8", "8+"];
+
+/// Filtres de localisation, de type et de rendement, communs à la liste des
+/// propriétés et aux facettes
+#[derive(Debug, Default)]
+pub struct PropertyFilters {
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub property_type: Option<String>,
+    pub yield_bucket: Option<String>,
+}
+
+impl PropertyFilters {
+    /// Valeurs vides ignorées ; pays en ISO 3166-1 alpha-2 (casse indifférente)
+    pub fn parse(
+        country: Option<&str>,
+        region: Option<&str>,
+        city: Option<&str>,
+        property_type: Option<&str>,
+        yield_bucket: Option<&str>,
+    ) -> Result<Self, String> {
+        let value = |v: Option<&str>| v.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+
+        let country = match value(country) {
+            Some(country) => Some(taxes::normalize_country(&country)
+                .ok_or_else(|| format!("country invalide: {} (code ISO 3166-1 alpha-2 attendu)", country))?),
+            None => None,
+        };
+        let yield_bucket = value(yield_bucket);
+        if let Some(bucket) = &yield_bucket {
+            if !YIELD_BUCKETS.contains(&bucket.as_str()) {
+                return Err(format!("yield_bucket invalide: {} (valeurs: {})", bucket, YIELD_BUCKETS.join(", ")));
+            }
+        }
+
+        Ok(Self {
+            country,
+            region: value(region),
+            city: value(city),
+            property_type: value(property_type),
+            yield_bucket,
+        })
+    }
+}
+
+/// Route : facettes des propriétés visibles, pour les filtres demandés
+///
+/// Visibilité identique à `GET /api/properties` : l'admin voit les propriétés
+/// de sa plateforme hors brouillons des autres, le manager celles qu'il a
+/// créées ou co-gère, l'investisseur celles dans lesquelles il a investi. Les
+/// propriétés non géocodées ne comptent dans aucune valeur de pays, région ou
+/// ville.
+pub async fn get_property_facets(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<PropertyFacetQuery>,
+) -> impl IntoResponse {
+    let filters = match PropertyFilters::parse(
+        query.country.as_deref(),
+        query.region.as_deref(),
+        query.city.as_deref(),
+        query.property_type.as_deref(),
+        query.yield_bucket.as_deref(),
+    ) {
+        Ok(filters) => filters,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidFilter
+        }))).into_response(),
+    };
+    let tag_filter = tags::parse_tag_filter(query.tags.as_deref());
+
+    let rows = sqlx::query!(
+        r#"WITH visible AS (
+               SELECT p.country, p.region, p.city, p.type,
+                      property_yield_bucket(p.annual_yield) as yield_bucket
+               FROM properties p
+               WHERE CASE
+                   WHEN $2 THEN (p.status <> 'draft' OR p.created_by = $1) AND p.tenant_id = $4
+                   WHEN $3 THEN p.created_by = $1
+                       OR p.id IN (SELECT property_id FROM property_managers WHERE user_id = $1)
+                   ELSE p.id IN (SELECT property_id FROM investments WHERE user_id = $1 AND status <> 'refunded')
+               END
+               AND ($5::text[] IS NULL OR p.id IN (SELECT pt.property_id FROM property_tags pt
+                   JOIN tags t ON t.id = pt.tag_id
+                   WHERE t.slug = ANY($5)
+                   GROUP BY pt.property_id
+                   HAVING COUNT(*) = cardinality($5)))
+           ),
+           matched AS (
+               SELECT *,
+                      ($6::text IS NULL OR country = $6) as by_country,
+                      ($7::text IS NULL OR region = $7) as by_region,
+                      ($8::text IS NULL OR city = $8) as by_city,
+                      ($9::text IS NULL OR type = $9) as by_type,
+                      ($10::text IS NULL OR yield_bucket = $10) as by_yield
+               FROM visible
+           )
+           SELECT 'country' as "facet!", country as value, COUNT(*) as "count!" FROM matched
+               WHERE country IS NOT NULL AND by_region AND by_city AND by_type AND by_yield GROUP BY country
+           UNION ALL
+           SELECT 'region', region, COUNT(*) FROM matched
+               WHERE region IS NOT NULL AND by_country AND by_city AND by_type AND by_yield GROUP BY region
+           UNION ALL
+           SELECT 'city', city, COUNT(*) FROM matched
+               WHERE city IS NOT NULL AND by_country AND by_region AND by_type AND by_yield GROUP BY city
+           UNION ALL
+           SELECT 'property_type', type, COUNT(*) FROM matched
+               WHERE by_country AND by_region AND by_city AND by_yield GROUP BY type
+           UNION ALL
+           SELECT 'yield_bucket', yield_bucket, COUNT(*) FROM matched
+               WHERE by_country AND by_region AND by_city AND by_type GROUP BY yield_bucket
+           UNION ALL
+           SELECT 'total', NULL, COUNT(*) FROM matched
+               WHERE by_country AND by_region AND by_city AND by_type AND by_yield
+           ORDER BY 3 DESC, 2"#,
+        user.id,
+        matches!(user.role, UserRole::Admin),
+        matches!(user.role, UserRole::Manager),
+        user.tenant_id,
+        tag_filter.as_deref(),
+        filters.country,
+        filters.region,
+        filters.city,
+        filters.property_type,
+        filters.yield_bucket
+    )
+    .fetch_all(&pool)
+    .await;
+
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du calcul des facettes: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    };
+
+    // Toutes les tranches de rendement, dans l'ordre, y compris les vides
+    let mut facets = PropertyFacets {
+        yield_bucket: YIELD_BUCKETS.iter()
+            .map(|bucket| FacetCount { value: bucket.to_string(), count: 0 })
+            .collect(),
+        ..Default::default()
+    };
+    for row in rows {
+        let Some(value) = row.value else {
+            facets.total = row.count;
+            continue;
+        };
+        match row.facet.as_str() {
+            "country" => facets.country.push(FacetCount { value, count: row.count }),
+            "region" => facets.region.push(FacetCount { value, count: row.count }),
+            "city" => facets.city.push(FacetCount { value, count: row.count }),
+            "property_type" => facets.property_type.push(FacetCount { value, count: row.count }),
+            _ => if let Some(bucket) = facets.yield_bucket.iter_mut().find(|b| b.value == value) {
+                bucket.count = row.count;
+            },
+        }
+    }
+
+    (StatusCode::OK, Json(facets)).into_response()
+}
@@ -0,0 +1,231 @@
+// feeds.rs
+//
+// Sitemap et flux Atom des propriétés validées de la plateforme (les slugs ne
+// sont uniques que par plateforme), générés à la demande puis conservés en
+// cache jusqu'au prochain changement de statut. Les changements
+// faits par un worker séparé (publication programmée, enregistrement on-chain)
+// sont signalés aux processus de l'API par `NOTIFY` (voir worker.rs).
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use chrono::{DateTime, Utc};
+use moka::sync::Cache;
+use sqlx::postgres::{PgExecutor, PgListener};
+use sqlx::PgPool;
+use std::env;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::models::Tenant;
+
+const SITEMAP_KEY: &str = "sitemap";
+const FEED_KEY: &str = "feed";
+
+/// Canal Postgres des invalidations entre processus
+const INVALIDATION_CHANNEL: &str = "feed_cache_invalidated";
+
+/// Cache des documents XML générés, par plateforme
+#[derive(Clone)]
+pub struct FeedCache {
+    inner: Cache<(&'static str, Uuid), String>,
+}
+
+impl Default for FeedCache {
+    fn default() -> Self {
+        Self {
+            // Filet de sécurité si une invalidation est manquée
+            inner: Cache::builder()
+                .time_to_live(Duration::from_secs(3600))
+                .build(),
+        }
+    }
+}
+
+impl FeedCache {
+    /// À appeler dès que la liste des propriétés publiques peut avoir changé
+    pub fn invalidate(&self) {
+        self.inner.invalidate_all();
+    }
+
+    /// Invalide le cache de ce processus et celui des autres processus
+    pub async fn invalidate_everywhere(&self, pool: &PgPool) {
+        self.invalidate();
+        if let Err(e) = notify_invalidation(pool).await {
+            tracing::warn!("Invalidation du cache des flux non diffusée: {}", e);
+        }
+    }
+
+    /// Suit les invalidations diffusées par les autres processus
+    pub fn listen(&self, pool: PgPool) {
+        let cache = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let e = follow_invalidations(&pool, &cache).await;
+                tracing::warn!("Écoute des invalidations du cache des flux interrompue: {}", e);
+                // Des invalidations ont pu être manquées pendant la coupure
+                cache.invalidate();
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+}
+
+/// Signale aux autres processus que les flux ont changé ; dans une
+/// transaction, le signal part à la validation
+pub async fn notify_invalidation<'e>(db: impl PgExecutor<'e>) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_notify($1, '')")
+        .bind(INVALIDATION_CHANNEL)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+async fn follow_invalidations(pool: &PgPool, cache: &FeedCache) -> sqlx::Error {
+    let mut listener = match PgListener::connect_with(pool).await {
+        Ok(listener) => listener,
+        Err(e) => return e,
+    };
+    if let Err(e) = listener.listen(INVALIDATION_CHANNEL).await {
+        return e;
+    }
+    loop {
+        match listener.recv().await {
+            Ok(_) => cache.invalidate(),
+            Err(e) => return e,
+        }
+    }
+}
+
+struct FeedEntry {
+    slug: String,
+    name: String,
+    location: String,
+    description: Option<String>,
+    updated_at: DateTime<Utc>,
+}
+
+/// URL publique du site frontend, utilisée pour construire les liens
+fn site_url() -> String {
+    env::var("PUBLIC_SITE_URL")
+        .unwrap_or_else(|_| "http://localhost:5173".to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+pub fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+async fn fetch_entries(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<FeedEntry>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"SELECT slug, name, location, description,
+           COALESCE(status_updated_at, created_at) as "updated_at!"
+           FROM properties
+           WHERE tenant_id = $1 AND status = 'validated' AND published_at IS NOT NULL
+           ORDER BY COALESCE(status_updated_at, created_at) DESC"#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| FeedEntry {
+        slug: row.slug,
+        name: row.name,
+        location: row.location,
+        description: row.description,
+        updated_at: row.updated_at,
+    }).collect())
+}
+
+fn property_url(base: &str, entry: &FeedEntry) -> String {
+    format!("{}/properties/{}", base, entry.slug)
+}
+
+fn render_sitemap(entries: &[FeedEntry]) -> String {
+    let base = site_url();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for entry in entries {
+        xml.push_str(&format!(
+            "  <url>\n    <loc>{}</loc>\n    <lastmod>{}</lastmod>\n  </url>\n",
+            xml_escape(&property_url(&base, entry)),
+            entry.updated_at.format("%Y-%m-%d")
+        ));
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+fn render_feed(entries: &[FeedEntry]) -> String {
+    let base = site_url();
+    let updated = entries.first().map(|e| e.updated_at).unwrap_or_else(Utc::now);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str("  <title>Propriétés disponibles à l'investissement</title>\n");
+    xml.push_str(&format!("  <id>{}/feed.xml</id>\n", xml_escape(&base)));
+    xml.push_str(&format!("  <link href=\"{}/feed.xml\" rel=\"self\"/>\n", xml_escape(&base)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+    for entry in entries {
+        let url = xml_escape(&property_url(&base, entry));
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", xml_escape(&entry.name)));
+        xml.push_str(&format!("    <id>{}</id>\n", url));
+        xml.push_str(&format!("    <link href=\"{}\"/>\n", url));
+        xml.push_str(&format!("    <updated>{}</updated>\n", entry.updated_at.to_rfc3339()));
+        let summary = entry.description.as_deref().unwrap_or(&entry.location);
+        xml.push_str(&format!("    <summary>{}</summary>\n", xml_escape(summary)));
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// Sert un document depuis le cache ou le génère
+async fn serve_cached(
+    pool: &PgPool,
+    cache: &FeedCache,
+    tenant_id: Uuid,
+    key: &'static str,
+    content_type: &'static str,
+    render: fn(&[FeedEntry]) -> String,
+) -> Response {
+    if let Some(xml) = cache.inner.get(&(key, tenant_id)) {
+        return ([(header::CONTENT_TYPE, content_type)], xml).into_response();
+    }
+
+    match fetch_entries(pool, tenant_id).await {
+        Ok(entries) => {
+            let xml = render(&entries);
+            cache.inner.insert((key, tenant_id), xml.clone());
+            ([(header::CONTENT_TYPE, content_type)], xml).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Erreur lors de la génération: {}", e)).into_response(),
+    }
+}
+
+/// Route publique `GET /sitemap.xml`
+pub async fn sitemap(
+    State(pool): State<PgPool>,
+    Extension(cache): Extension<FeedCache>,
+    Extension(tenant): Extension<Tenant>,
+) -> impl IntoResponse {
+    serve_cached(&pool, &cache, tenant.id, SITEMAP_KEY, "application/xml; charset=utf-8", render_sitemap).await
+}
+
+/// Route publique `GET /feed.xml` (Atom)
+pub async fn feed(
+    State(pool): State<PgPool>,
+    Extension(cache): Extension<FeedCache>,
+    Extension(tenant): Extension<Tenant>,
+) -> impl IntoResponse {
+    serve_cached(&pool, &cache, tenant.id, FEED_KEY, "application/atom+xml; charset=utf-8", render_feed).await
+}
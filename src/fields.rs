@@ -0,0 +1,60 @@
+// fields.rs
+//
+// Sélection partielle des champs via `?fields=` sur les routes de liste.
+
+// Champs sélectionnables par ressource
+pub const PROPERTY_FIELDS: &[&str] = &[
+    "id", "onchain_id", "slug", "name", "location", "property_type", "description",
+    "total_price", "token_price", "annual_yield", "image_url", "documents",
+    "created_by", "created_at", "status", "status_updated_at", "status_updated_by",
+    "publish_at", "published_at", "amenities", "requires_accreditation", "contract_address",
+];
+pub const INVESTMENT_FIELDS: &[&str] = &[
+    "id", "user_id", "property_id", "amount_eth", "amount_wei", "shares", "tx_hash", "status",
+    "settled_at", "created_at", "fiat",
+];
+
+/// Analyse `?fields=a,b` et vérifie chaque champ contre la liste autorisée.
+/// Renvoie `None` si aucun filtre n'est demandé.
+pub fn parse_fields(raw: Option<&str>, allowed: &[&str]) -> Result<Option<Vec<String>>, String> {
+    let raw = match raw {
+        Some(raw) if !raw.trim().is_empty() => raw,
+        _ => return Ok(None),
+    };
+
+    let mut fields = Vec::new();
+    for field in raw.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if !allowed.contains(&field) {
+            return Err(format!(
+                "Champ inconnu: '{}' (champs possibles: {})",
+                field,
+                allowed.join(", ")
+            ));
+        }
+        if !fields.iter().any(|f| f == field) {
+            fields.push(field.to_string());
+        }
+    }
+    Ok(Some(fields))
+}
+
+/// Ne conserve que les champs demandés (et les relations `keep`, ex. issues de `?include=`)
+pub fn select_fields(
+    items: Vec<serde_json::Value>,
+    fields: Option<&[String]>,
+    keep: &[String],
+) -> Vec<serde_json::Value> {
+    let fields = match fields {
+        Some(fields) => fields,
+        None => return items,
+    };
+
+    items.into_iter().map(|item| match item {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter()
+                .filter(|(key, _)| fields.contains(key) || keep.contains(key))
+                .collect(),
+        ),
+        other => other,
+    }).collect()
+}
@@ -0,0 +1,216 @@
+// fixtures.rs
+//
+// Capture de fixtures : sur les routes listées dans `FIXTURE_CAPTURE_ROUTES`,
+// chaque requête et sa réponse sont enregistrées dans un fichier JSON sous
+// `FIXTURE_CAPTURE_DIR/<AAAAMMJJTHHMMSSZ>/` (un dossier par démarrage, fichiers
+// numérotés dans l'ordre d'arrivée). Les champs secrets sont expurgés comme
+// dans le journal des requêtes (voir request_log.rs) ; seuls les en-têtes
+// utiles au rejeu sont conservés. Une requête dont un élément a dû être
+// expurgé (signature, jeton autre qu'un wallet, cookie de session, corps non
+// JSON) est marquée `replayable: false`. Le binaire `replay`
+// (scripts/replay.rs) rejoue un dossier contre une base de test neuve et
+// compare les réponses : des tests de non-régression tirés du trafic réel.
+//
+//   FIXTURE_CAPTURE_ROUTES="POST /api/investments; GET /api/properties/:id; * /api/me/*"
+
+use axum::{
+    body::{self, Body, HttpBody},
+    extract::{MatchedPath, State},
+    http::{header, HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use serde_json::Value;
+use std::env;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::request_log::redact;
+use crate::wallet_address::WalletAddress;
+
+/// Au-delà, le corps n'est pas conservé et la requête n'est pas rejouable
+const MAX_CAPTURED_BODY: u64 = 256 * 1024;
+
+/// En-têtes conservés, en plus de `Authorization`
+const KEPT_HEADERS: &[&str] = &["content-type", "accept", "accept-language", "x-response-shape", "x-tenant"];
+
+/// Valeur des éléments expurgés
+const REDACTED: &str = "[expurgé]";
+
+#[derive(Clone)]
+pub struct FixtureCapture {
+    dir: PathBuf,
+    /// Routes capturées : méthode (`None` pour toutes) et route déclarée (`*` final pour un préfixe)
+    routes: Arc<Vec<(Option<String>, String)>>,
+    next_seq: Arc<AtomicU64>,
+}
+
+impl FixtureCapture {
+    /// `None` si `FIXTURE_CAPTURE_DIR` ou `FIXTURE_CAPTURE_ROUTES` manque
+    pub fn from_env() -> Option<Self> {
+        let dir = env::var("FIXTURE_CAPTURE_DIR").ok().filter(|v| !v.trim().is_empty())?;
+        let mut routes = Vec::new();
+        for raw in env::var("FIXTURE_CAPTURE_ROUTES").unwrap_or_default().split(';').map(str::trim).filter(|r| !r.is_empty()) {
+            match raw.split_once(char::is_whitespace) {
+                Some((method, route)) if route.trim().starts_with('/') => routes.push((
+                    (method != "*").then(|| method.to_uppercase()),
+                    route.trim().to_string(),
+                )),
+                _ => println!("⚠️  FIXTURE_CAPTURE_ROUTES : route ignorée (`<MÉTHODE> <route>` attendu) : {}", raw),
+            }
+        }
+        if routes.is_empty() {
+            return None;
+        }
+
+        let dir = PathBuf::from(dir).join(Utc::now().format("%Y%m%dT%H%M%SZ").to_string());
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            println!("⚠️  Capture de fixtures désactivée : création de {} impossible : {}", dir.display(), e);
+            return None;
+        }
+        Some(Self { dir, routes: Arc::new(routes), next_seq: Arc::new(AtomicU64::new(1)) })
+    }
+
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    fn captures(&self, method: &str, route: &str) -> bool {
+        self.routes.iter().any(|(m, pattern)| {
+            m.as_deref().is_none_or(|m| m == method)
+                && match pattern.strip_suffix('*') {
+                    Some(prefix) => route.starts_with(prefix),
+                    None => route == pattern,
+                }
+        })
+    }
+}
+
+/// En-têtes conservés ; `false` si un élément d'authentification a été expurgé
+fn kept_headers(headers: &HeaderMap) -> (serde_json::Map<String, Value>, bool) {
+    let mut kept = serde_json::Map::new();
+    let mut replayable = !headers.contains_key(header::COOKIE);
+    for name in KEPT_HEADERS {
+        if let Some(value) = headers.get(*name).and_then(|v| v.to_str().ok()) {
+            kept.insert(name.to_string(), value.into());
+        }
+    }
+    if let Some(value) = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()) {
+        // Wallet conservé pour rejouer la requête sous le même compte ; jetons expurgés
+        let wallet = value.strip_prefix("Bearer ").filter(|token| WalletAddress::parse(token.trim()).is_some());
+        match wallet {
+            Some(_) => kept.insert("authorization".to_string(), value.into()),
+            None => {
+                replayable = false;
+                kept.insert("authorization".to_string(), REDACTED.into())
+            },
+        };
+    }
+    (kept, replayable)
+}
+
+/// Corps JSON expurgé ; `None` si le corps est vide, trop gros ou pas du JSON
+fn json_body(headers: &HeaderMap, bytes: &[u8]) -> Option<Value> {
+    let is_json = headers.get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    (is_json && !bytes.is_empty())
+        .then(|| serde_json::from_slice::<Value>(bytes).ok())
+        .flatten()
+        .map(redact)
+}
+
+fn contains_redacted(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s == REDACTED,
+        Value::Array(items) => items.iter().any(contains_redacted),
+        Value::Object(map) => map.values().any(contains_redacted),
+        _ => false,
+    }
+}
+
+/// Middleware de capture ; l'état est la configuration, `None` si inactive
+pub async fn capture_fixtures(
+    State(capture): State<Option<FixtureCapture>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let Some(capture) = capture else {
+        return next.run(request).await;
+    };
+    let method = request.method().to_string();
+    let route = request.extensions().get::<MatchedPath>()
+        .map(|m| m.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    if !capture.captures(&method, &route) {
+        return next.run(request).await;
+    }
+    let seq = capture.next_seq.fetch_add(1, Ordering::Relaxed);
+    let path = request.uri().path_and_query().map(|p| p.as_str().to_string()).unwrap_or_default();
+    let (headers, mut replayable) = kept_headers(request.headers());
+
+    // Corps de taille connue et raisonnable uniquement, relu puis transmis au handler
+    let content_length = request.headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let (request, request_body) = match content_length {
+        Some(0) | None if request.body().is_end_stream() => (request, None),
+        Some(len) if len <= MAX_CAPTURED_BODY => {
+            let (parts, payload) = request.into_parts();
+            let bytes = match hyper::body::to_bytes(payload).await {
+                Ok(bytes) => bytes,
+                Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                    "error": format!("Erreur lors de la lecture de la requête: {}", e)
+                }))).into_response(),
+            };
+            let captured = json_body(&parts.headers, &bytes);
+            replayable &= captured.as_ref().is_some_and(|body| !contains_redacted(body));
+            (Request::from_parts(parts, Body::from(bytes)), captured)
+        },
+        _ => {
+            replayable = false;
+            (request, None)
+        },
+    };
+
+    let response = next.run(request).await;
+    let (parts, payload) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(payload).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Capture de fixture impossible (corps de la réponse illisible): {}", e);
+            return Response::from_parts(parts, body::boxed(Body::empty()));
+        },
+    };
+
+    let fixture = serde_json::json!({
+        "seq": seq,
+        "method": method,
+        "route": route,
+        "path": path,
+        "headers": headers,
+        "request": request_body,
+        "status": parts.status.as_u16(),
+        "response": json_body(&parts.headers, &bytes),
+        "replayable": replayable,
+        "captured_at": Utc::now(),
+    });
+    let file = capture.dir.join(format!(
+        "{:06}-{}-{}.json",
+        seq,
+        method.to_lowercase(),
+        route.trim_matches('/').replace(['/', ':'], "_")
+    ));
+    tokio::spawn(async move {
+        let content = serde_json::to_vec_pretty(&fixture).unwrap_or_default();
+        if let Err(e) = tokio::fs::write(&file, content).await {
+            tracing::error!("Écriture de la fixture {} impossible: {}", file.display(), e);
+        }
+    });
+
+    Response::from_parts(parts, body::boxed(Body::from(bytes)))
+}
@@ -0,0 +1,260 @@
+// flags.rs
+//
+// Drapeaux de fonctionnalités : la table `feature_flags` est gardée en mémoire
+// et relue périodiquement (et après chaque modification par un admin), pour
+// que les handlers puissent tester un drapeau sans requête SQL :
+// `flags.enabled_for("fiat_payments", user.role)`. Un drapeau inconnu est
+// considéré comme désactivé.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{FeatureFlag, UpsertFeatureFlagRequest, UserRole};
+
+const ROLES: &[&str] = &["user", "manager", "admin"];
+
+#[derive(Clone)]
+pub struct FeatureFlags {
+    inner: Arc<RwLock<HashMap<String, FeatureFlag>>>,
+    environment: String,
+}
+
+impl FeatureFlags {
+    /// Environnement courant via `APP_ENV` (`development` par défaut)
+    pub fn from_env() -> Self {
+        let environment = env::var("APP_ENV")
+            .ok()
+            .map(|v| v.trim().to_lowercase())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "development".to_string());
+
+        Self {
+            inner: Arc::new(RwLock::new(HashMap::new())),
+            environment,
+        }
+    }
+
+    pub fn environment(&self) -> &str {
+        &self.environment
+    }
+
+    /// Recharge tous les drapeaux depuis la base
+    pub async fn refresh(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let flags = fetch_flags(pool).await?;
+        let flags = flags.into_iter().map(|flag| (flag.key.clone(), flag)).collect();
+        *self.inner.write().unwrap_or_else(|e| e.into_inner()) = flags;
+        Ok(())
+    }
+
+    /// Relecture périodique, intervalle configurable via
+    /// `FEATURE_FLAGS_REFRESH_SECS` (30s par défaut)
+    pub fn spawn(&self, pool: PgPool) {
+        let interval_secs = env::var("FEATURE_FLAGS_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(30);
+
+        let flags = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+            loop {
+                interval.tick().await;
+                if let Err(e) = flags.refresh(&pool).await {
+                    tracing::error!("Erreur lors du rechargement des drapeaux de fonctionnalités: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Drapeau actif dans cet environnement, sans restriction de rôle
+    #[allow(dead_code)]
+    pub fn enabled(&self, key: &str) -> bool {
+        self.check(key, None)
+    }
+
+    /// Drapeau actif dans cet environnement pour un utilisateur de ce rôle
+    pub fn enabled_for(&self, key: &str, role: UserRole) -> bool {
+        self.check(key, Some(role))
+    }
+
+    /// Clés des drapeaux actifs pour un rôle
+    fn enabled_keys(&self, role: UserRole) -> Vec<String> {
+        let flags = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        let mut keys: Vec<String> = flags.values()
+            .filter(|flag| self.is_active(flag, Some(role)))
+            .map(|flag| flag.key.clone())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    fn check(&self, key: &str, role: Option<UserRole>) -> bool {
+        let flags = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        flags.get(key).is_some_and(|flag| self.is_active(flag, role))
+    }
+
+    fn is_active(&self, flag: &FeatureFlag, role: Option<UserRole>) -> bool {
+        flag.enabled
+            && (flag.environments.is_empty() || flag.environments.contains(&self.environment))
+            && (flag.roles.is_empty() || role.is_some_and(|role| flag.roles.contains(&role.to_string())))
+    }
+}
+
+async fn fetch_flags(pool: &PgPool) -> Result<Vec<FeatureFlag>, sqlx::Error> {
+    sqlx::query_as!(
+        FeatureFlag,
+        r#"SELECT key, description, enabled, environments, roles::text[] as "roles!", updated_by, updated_at, created_at
+           FROM feature_flags
+           ORDER BY key"#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+fn admin_only(role: &UserRole) -> Option<Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer les drapeaux de fonctionnalités"
+    }))).into_response())
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+        "error": message
+    }))).into_response()
+}
+
+/// Route : drapeaux actifs pour l'utilisateur connecté (affichage côté frontend)
+pub async fn get_my_feature_flags(
+    BearerAuthUser(user): BearerAuthUser,
+    Extension(flags): Extension<FeatureFlags>,
+) -> impl IntoResponse {
+    (StatusCode::OK, Json(serde_json::json!({
+        "environment": flags.environment(),
+        "enabled": flags.enabled_keys(user.role)
+    })))
+}
+
+/// Route admin : tous les drapeaux
+pub async fn get_feature_flags(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(flags): Extension<FeatureFlags>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    match fetch_flags(&pool).await {
+        Ok(list) => envelope::list("flags", &list).with("environment", flags.environment()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : créer ou modifier un drapeau
+pub async fn upsert_feature_flag(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(flags): Extension<FeatureFlags>,
+    Path(key): Path<String>,
+    Json(payload): Json<UpsertFeatureFlagRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+    if key.is_empty() || key.len() > 64 || !key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return bad_request("La clé ne doit contenir que des minuscules, chiffres et '_' (64 caractères au maximum)");
+    }
+    let environments = payload.environments.map(|list| {
+        list.into_iter()
+            .map(|env| env.trim().to_lowercase())
+            .filter(|env| !env.is_empty())
+            .collect::<Vec<_>>()
+    });
+    let roles = payload.roles.map(|list| list.into_iter().map(|role| role.trim().to_lowercase()).collect::<Vec<_>>());
+    if let Some(invalid) = roles.iter().flatten().find(|role| !ROLES.contains(&role.as_str())) {
+        return bad_request(&format!("Rôle inconnu : {}", invalid));
+    }
+
+    let result = sqlx::query_as!(
+        FeatureFlag,
+        r#"INSERT INTO feature_flags (key, description, enabled, environments, roles, updated_by)
+           VALUES ($1, $2, $3, COALESCE($4::text[], '{}'), COALESCE($5::text[]::user_role[], '{}'), $6)
+           ON CONFLICT (key) DO UPDATE SET
+               enabled = EXCLUDED.enabled,
+               description = COALESCE($2, feature_flags.description),
+               environments = COALESCE($4::text[], feature_flags.environments),
+               roles = COALESCE($5::text[]::user_role[], feature_flags.roles),
+               updated_by = $6,
+               updated_at = NOW()
+           RETURNING key, description, enabled, environments, roles::text[] as "roles!", updated_by, updated_at, created_at"#,
+        key,
+        payload.description,
+        payload.enabled,
+        environments.as_deref(),
+        roles.as_deref(),
+        user.id
+    )
+    .fetch_one(&pool)
+    .await;
+
+    match result {
+        Ok(flag) => {
+            if let Err(e) = flags.refresh(&pool).await {
+                tracing::error!("Erreur lors du rechargement des drapeaux de fonctionnalités: {}", e);
+            }
+            (StatusCode::OK, Json(serde_json::json!({
+                "flag": flag,
+                "message": "Drapeau enregistré"
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : supprimer un drapeau (la fonctionnalité est alors désactivée)
+pub async fn delete_feature_flag(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(flags): Extension<FeatureFlags>,
+    Path(key): Path<String>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    match sqlx::query!("DELETE FROM feature_flags WHERE key = $1", key)
+        .execute(&pool)
+        .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Drapeau non trouvé"
+        }))).into_response(),
+        Ok(_) => {
+            if let Err(e) = flags.refresh(&pool).await {
+                tracing::error!("Erreur lors du rechargement des drapeaux de fonctionnalités: {}", e);
+            }
+            (StatusCode::OK, Json(serde_json::json!({
+                "message": "Drapeau supprimé"
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,263 @@
+// forwarded.rs
+//
+// Adresse et protocole réels du client derrière un reverse proxy (Nginx, Fly,
+// Render). Les en-têtes `X-Forwarded-For`, `X-Real-IP` et `X-Forwarded-Proto`
+// ne sont crus que s'ils sont posés par un proxy de confiance
+// (`TRUSTED_PROXIES`) : l'adresse du client est la plus à droite de
+// `X-Forwarded-For` qui n'est pas un proxy de confiance. Le middleware réécrit
+// ensuite `X-Real-IP` et `X-Forwarded-Proto` avec les valeurs retenues (et
+// retire `X-Forwarded-For`), que lisent la limitation des connexions, les
+// journaux d'audit et les échecs d'authentification (voir `risk::client_ip`).
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+
+/// Protocole retenu quand aucun proxy de confiance ne l'indique
+const DEFAULT_SCHEME: &str = "http";
+
+/// Réseaux privés et boucle locale, où tournent habituellement les proxies
+const PRIVATE_NETWORKS: &[&str] = &[
+    "127.0.0.0/8", "10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16", "::1/128", "fc00::/7",
+];
+
+/// Proxies dont les en-têtes `X-Forwarded-*` sont crus
+#[derive(Clone)]
+pub struct TrustedProxies {
+    any: bool,
+    networks: Vec<(IpAddr, u8)>,
+    /// Protocole de la connexion directe (`https` si le serveur termine TLS)
+    default_scheme: &'static str,
+}
+
+/// Réseau au format CIDR (`10.0.0.0/8`) ou adresse seule
+fn parse_network(value: &str) -> Option<(IpAddr, u8)> {
+    let (ip, prefix) = match value.split_once('/') {
+        Some((ip, prefix)) => (ip, Some(prefix)),
+        None => (value, None),
+    };
+    let ip: IpAddr = ip.trim().parse().ok()?;
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    let prefix = match prefix {
+        None => max,
+        Some(prefix) => prefix.trim().parse().ok().filter(|p| *p <= max)?,
+    };
+    Some((ip, prefix))
+}
+
+fn in_network(ip: IpAddr, (network, prefix): (IpAddr, u8)) -> bool {
+    match (ip.to_canonical(), network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        },
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        },
+        _ => false,
+    }
+}
+
+impl TrustedProxies {
+    /// `TRUSTED_PROXIES` : adresses ou réseaux CIDR séparés par des virgules,
+    /// `*` pour croire tout pair ; par défaut, boucle locale et réseaux privés
+    pub fn from_env() -> Self {
+        let value = env::var("TRUSTED_PROXIES").ok().filter(|v| !v.trim().is_empty());
+        let entries: Vec<&str> = match value.as_deref() {
+            Some(value) => value.split(',').map(str::trim).filter(|v| !v.is_empty()).collect(),
+            None => PRIVATE_NETWORKS.to_vec(),
+        };
+        Self::from_entries(&entries)
+    }
+
+    /// Liste déjà découpée ; une entrée illisible est ignorée
+    fn from_entries(entries: &[&str]) -> Self {
+        let mut networks = Vec::new();
+        for entry in entries.iter().filter(|e| **e != "*") {
+            match parse_network(entry) {
+                Some(network) => networks.push(network),
+                None => tracing::warn!("TRUSTED_PROXIES : entrée ignorée ({})", entry),
+            }
+        }
+        Self { any: entries.contains(&"*"), networks, default_scheme: DEFAULT_SCHEME }
+    }
+
+    /// Le serveur termine lui-même TLS (voir tls.rs) : les connexions directes sont en HTTPS
+    pub fn serving_tls(mut self, tls: bool) -> Self {
+        if tls {
+            self.default_scheme = "https";
+        }
+        self
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        self.any || self.networks.iter().any(|network| in_network(ip, *network))
+    }
+
+    /// Adresse et protocole du client ; sans adresse du pair (requête hors
+    /// serveur), les en-têtes sont crus
+    fn resolve(&self, peer: Option<IpAddr>, headers: &HeaderMap) -> (Option<IpAddr>, String) {
+        if let Some(peer) = peer.filter(|peer| !self.contains(*peer)) {
+            return (Some(peer), self.default_scheme.to_string());
+        }
+
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let chain: Vec<&str> = headers.get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .collect();
+        // De droite à gauche, on passe les proxies de confiance ; une entrée
+        // illisible arrête la remontée (adresse du pair retenue)
+        let mut forwarded = None;
+        for entry in chain.iter().rev() {
+            match entry.parse::<IpAddr>() {
+                Ok(ip) => {
+                    forwarded = Some(ip);
+                    if !self.contains(ip) {
+                        break;
+                    }
+                },
+                Err(_) => {
+                    forwarded = None;
+                    break;
+                },
+            }
+        }
+        let ip = match chain.is_empty() {
+            true => header("x-real-ip").and_then(|ip| ip.trim().parse().ok()).or(peer),
+            false => forwarded.or(peer),
+        };
+
+        let scheme = header("x-forwarded-proto")
+            .and_then(|v| v.split(',').next())
+            .map(|v| v.trim().to_ascii_lowercase())
+            .filter(|v| v == "http" || v == "https")
+            .unwrap_or_else(|| self.default_scheme.to_string());
+        (ip, scheme)
+    }
+}
+
+/// Protocole du client retenu par le middleware (`http` ou `https`)
+pub fn client_scheme(headers: &HeaderMap) -> String {
+    headers.get("x-forwarded-proto")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or(DEFAULT_SCHEME)
+        .to_string()
+}
+
+/// Middleware : remplace les en-têtes transmis par l'adresse et le protocole
+/// retenus, avant tout autre traitement de la requête
+pub async fn normalize_forwarded_headers(
+    State(proxies): State<TrustedProxies>,
+    mut request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip().to_canonical());
+    let (ip, scheme) = proxies.resolve(peer, request.headers());
+
+    let headers = request.headers_mut();
+    headers.remove("x-forwarded-for");
+    headers.remove("x-real-ip");
+    if let Some(ip) = ip.and_then(|ip| HeaderValue::from_str(&ip.to_string()).ok()) {
+        headers.insert("x-real-ip", ip);
+    }
+    if let Ok(scheme) = HeaderValue::from_str(&scheme) {
+        headers.insert("x-forwarded-proto", scheme);
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(raw: &str) -> IpAddr {
+        raw.parse().unwrap()
+    }
+
+    fn forwarded_for(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn parses_cidr_entries() {
+        assert_eq!(parse_network("10.0.0.0/8"), Some((ip("10.0.0.0"), 8)));
+        assert_eq!(parse_network(" 192.168.1.7 "), Some((ip("192.168.1.7"), 32)));
+        assert_eq!(parse_network("0.0.0.0/0"), Some((ip("0.0.0.0"), 0)));
+        assert_eq!(parse_network("fc00::/7"), Some((ip("fc00::"), 7)));
+        assert_eq!(parse_network("::1"), Some((ip("::1"), 128)));
+
+        for malformed in ["10.0.0.0/33", "::/129", "10.0.0/8", "10.0.0.0/", "10.0.0.0/-1", "10.0.0.0/abc", "proxy.local", ""] {
+            assert_eq!(parse_network(malformed), None, "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn matches_ipv4_prefixes() {
+        let net = parse_network("10.1.0.0/16").unwrap();
+        assert!(in_network(ip("10.1.255.3"), net));
+        assert!(!in_network(ip("10.2.0.1"), net));
+
+        let host = parse_network("203.0.113.9/32").unwrap();
+        assert!(in_network(ip("203.0.113.9"), host));
+        assert!(!in_network(ip("203.0.113.10"), host));
+
+        let all = parse_network("0.0.0.0/0").unwrap();
+        assert!(in_network(ip("198.51.100.1"), all));
+        assert!(!in_network(ip("2001:db8::1"), all));
+    }
+
+    #[test]
+    fn matches_ipv6_prefixes() {
+        let net = parse_network("fc00::/7").unwrap();
+        assert!(in_network(ip("fd12:3456::1"), net));
+        assert!(!in_network(ip("2001:db8::1"), net));
+        assert!(in_network(ip("2001:db8::1"), parse_network("::/0").unwrap()));
+        assert!(!in_network(ip("::2"), parse_network("::1/128").unwrap()));
+        // Adresse IPv4 mappée en IPv6 : comparée comme IPv4
+        assert!(in_network(ip("::ffff:10.0.0.1"), parse_network("10.0.0.0/8").unwrap()));
+    }
+
+    #[test]
+    fn malformed_entries_are_skipped() {
+        let proxies = TrustedProxies::from_entries(&["10.0.0.0/8", "not-an-ip", "10.0.0.0/40"]);
+        assert_eq!(proxies.networks, vec![(ip("10.0.0.0"), 8)]);
+        assert!(!proxies.any);
+        assert!(TrustedProxies::from_entries(&["*"]).contains(ip("203.0.113.9")));
+    }
+
+    #[test]
+    fn untrusted_peer_cannot_spoof_forwarded_for() {
+        let proxies = TrustedProxies::from_entries(&["10.0.0.0/8"]);
+        let headers = forwarded_for("1.2.3.4");
+
+        let (client, scheme) = proxies.resolve(Some(ip("198.51.100.7")), &headers);
+        assert_eq!(client, Some(ip("198.51.100.7")));
+        assert_eq!(scheme, "http");
+    }
+
+    #[test]
+    fn trusted_proxy_chain_yields_rightmost_untrusted_address() {
+        let proxies = TrustedProxies::from_entries(&["10.0.0.0/8"]);
+        // Le client a préfixé une fausse adresse : seule la plus à droite non
+        // fiable compte
+        let headers = forwarded_for("1.2.3.4, 198.51.100.7, 10.0.0.2");
+
+        let (client, _) = proxies.resolve(Some(ip("10.0.0.1")), &headers);
+        assert_eq!(client, Some(ip("198.51.100.7")));
+
+        let (client, _) = proxies.resolve(Some(ip("10.0.0.1")), &forwarded_for("garbage, 10.0.0.2"));
+        assert_eq!(client, Some(ip("10.0.0.1")));
+    }
+}
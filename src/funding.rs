@@ -0,0 +1,42 @@
+// funding.rs
+//
+// Compteur de parts vendues par propriété. `properties.shares_sold` est tenu
+// à jour par un trigger sur `investments` (investissements non remboursés) et
+// borné par la contrainte `properties_shares_sold_check` (0 ≤ vendues ≤
+// `total_shares`) : la survente est impossible, même sous requêtes
+// concurrentes ou en écriture directe sur la base. La violation de cette
+// contrainte est renvoyée en 409.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+/// Contrainte bornant les parts vendues d'une propriété
+const SHARES_SOLD_CONSTRAINT: &str = "properties_shares_sold_check";
+
+/// Vrai si l'erreur vient d'une survente (ou d'un prix total ramené sous les parts vendues)
+pub fn is_oversold(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Database(db)
+        if db.code().as_deref() == Some("23514") && db.constraint() == Some(SHARES_SOLD_CONSTRAINT))
+}
+
+/// Parts encore disponibles (`None` si la propriété n'existe pas)
+pub async fn available_shares<'e, E: sqlx::PgExecutor<'e>>(executor: E, property_id: Uuid) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT total_shares - shares_sold as "available!" FROM properties WHERE id = $1"#,
+        property_id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+/// Réponse 409 d'une demande dépassant les parts disponibles
+pub fn sold_out_response(available: Option<i64>) -> Response {
+    (StatusCode::CONFLICT, Json(serde_json::json!({
+        "error": "Plus assez de parts disponibles pour cette propriété",
+        "available_shares": available
+    }))).into_response()
+}
@@ -0,0 +1,136 @@
+// geocoding.rs
+//
+// Localisation normalisée des propriétés : `location` est un texte libre saisi
+// par le manager ; un service de géocodage compatible Nominatim
+// (`/search?format=jsonv2&addressdetails=1`) en déduit le pays (ISO 3166-1
+// alpha-2), la région et la ville, enregistrés dans `properties.country`,
+// `region` et `city`. Ces colonnes servent les filtres de `GET /api/properties`
+// et les facettes (voir facets.rs). Le géocodage passe par la file de tâches
+// (`geocode_property`), mise en file à la création de la propriété et à chaque
+// changement de `location`. Optionnel : sans `GEOCODING_URL`, les colonnes
+// restent vides.
+
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::models::Job;
+use crate::outbound::{Outbound, OutboundClient};
+
+/// Agent envoyé par défaut (Nominatim exige un agent identifiant l'application)
+const DEFAULT_USER_AGENT: &str = "pa-backend-rust";
+
+#[derive(Clone)]
+pub struct Geocoder {
+    api_url: String,
+    user_agent: String,
+    client: OutboundClient,
+}
+
+/// Lieu déduit d'une localisation
+#[derive(Debug, Default)]
+struct Place {
+    country: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SearchResult {
+    #[serde(default)]
+    address: SearchAddress,
+}
+
+#[derive(Default, Deserialize)]
+struct SearchAddress {
+    country_code: Option<String>,
+    state: Option<String>,
+    region: Option<String>,
+    province: Option<String>,
+    county: Option<String>,
+    city: Option<String>,
+    town: Option<String>,
+    village: Option<String>,
+    municipality: Option<String>,
+}
+
+impl Geocoder {
+    /// Renvoie `None` si `GEOCODING_URL` manque (ex:
+    /// `https://nominatim.openstreetmap.org`) ; `GEOCODING_USER_AGENT`
+    /// identifie l'application auprès du service
+    pub fn from_env(outbound: &Outbound) -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+        Some(Self {
+            api_url: var("GEOCODING_URL")?.trim_end_matches('/').to_string(),
+            user_agent: var("GEOCODING_USER_AGENT").unwrap_or_else(|| DEFAULT_USER_AGENT.to_string()),
+            client: outbound.client("geocoding")?,
+        })
+    }
+
+    /// Premier résultat du service pour `location` ; `None` si aucun
+    async fn lookup(&self, location: &str) -> Result<Option<Place>, String> {
+        let request = self.client.get(format!("{}/search", self.api_url))
+            .query(&[("q", location), ("format", "jsonv2"), ("addressdetails", "1"), ("limit", "1")])
+            .header(reqwest::header::USER_AGENT, &self.user_agent);
+        let response = self.client.send(request).await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("le service de géocodage a répondu {}", response.status()));
+        }
+        let results: Vec<SearchResult> = response.json().await.map_err(|e| e.to_string())?;
+
+        Ok(results.into_iter().next().map(|result| {
+            let address = result.address;
+            Place {
+                country: address.country_code
+                    .map(|code| code.trim().to_uppercase())
+                    .filter(|code| code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase())),
+                region: address.state.or(address.region).or(address.province).or(address.county),
+                city: address.city.or(address.town).or(address.village).or(address.municipality),
+            }
+        }))
+    }
+}
+
+/// Exécute une tâche `geocode_property` (`{"property_id": ...}`). Une
+/// localisation introuvable laisse les colonnes vides ; le résultat n'est pas
+/// enregistré si `location` a changé entre-temps (une nouvelle tâche suit).
+pub async fn run_job(pool: &PgPool, geocoder: &Geocoder, job: &Job) -> Result<(), String> {
+    let property_id = job.payload.get("property_id")
+        .and_then(|v| v.as_str())
+        .and_then(|v| v.parse::<Uuid>().ok())
+        .ok_or("property_id manquant ou invalide")?;
+
+    let location = sqlx::query_scalar!("SELECT location FROM properties WHERE id = $1", property_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let location = match location {
+        Some(location) => location,
+        // Propriété supprimée entre-temps
+        None => return Ok(()),
+    };
+
+    let place = match geocoder.lookup(&location).await? {
+        Some(place) => place,
+        None => {
+            tracing::warn!(%property_id, %location, "Localisation introuvable par le géocodage");
+            Place::default()
+        },
+    };
+
+    sqlx::query!(
+        r#"UPDATE properties SET country = $3, region = $4, city = $5, geocoded_at = NOW()
+           WHERE id = $1 AND location = $2"#,
+        property_id,
+        location,
+        place.country,
+        place.region,
+        place.city
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
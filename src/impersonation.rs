@@ -0,0 +1,375 @@
+// impersonation.rs
+//
+// Impersonation par le support : un admin obtient, avec un motif obligatoire
+// et son second facteur, un jeton `imp_...` qui authentifie ses requêtes
+// comme l'utilisateur ciblé (même plateforme, jamais un autre admin). Le
+// jeton est limité à la lecture (GET/HEAD), expire après
+// `IMPERSONATION_TTL_SECS` secondes et chaque requête effectuée avec lui est
+// journalisée dans `impersonation_actions`. `GET /api/me` signale la session
+// en cours pour que le front affiche un bandeau.
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{HeaderMap, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::envelope;
+use crate::models::{ImpersonateRequest, ImpersonationListQuery, UserRole};
+use crate::pagination::{Cursor, Pagination};
+use crate::risk;
+use crate::two_factor;
+use crate::wallet_address::WalletAddress;
+
+// Préfixe des jetons d'impersonation : les distingue d'un wallet ou d'un jeton personnel
+pub const TOKEN_PREFIX: &str = "imp_";
+
+fn ttl_secs() -> i64 {
+    env::var("IMPERSONATION_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(1800)
+}
+
+/// Hash SHA-256 d'un jeton : seule cette empreinte est stockée en base
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", TOKEN_PREFIX, hex::encode(bytes))
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|v| v.starts_with(TOKEN_PREFIX))
+}
+
+/// Admin authentifié par son propre wallet : une session d'impersonation ne
+/// peut pas administrer les impersonations
+fn is_acting_admin(user: &SessionUser) -> bool {
+    matches!(user.role, UserRole::Admin) && user.impersonation_id.is_none()
+}
+
+fn forbidden() -> Response {
+    (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer les sessions d'impersonation"
+    }))).into_response()
+}
+
+/// Authentifie un jeton d'impersonation pour cette requête (appelé par BearerAuthUser)
+pub async fn authenticate(
+    pool: &PgPool,
+    token: &str,
+    method: &Method,
+) -> Result<SessionUser, (StatusCode, &'static str)> {
+    let row = sqlx::query!(
+        r#"SELECT s.id as session_id, u.id, u.tenant_id, u.wallet as "wallet: WalletAddress", u.name, u.role as "role: UserRole", u.created_at
+           FROM impersonation_sessions s
+           JOIN users u ON u.id = s.user_id
+           WHERE s.token_hash = $1 AND s.ended_at IS NULL AND s.expires_at > NOW()
+           AND u.deactivated_at IS NULL"#,
+        hash_token(token)
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| (StatusCode::UNAUTHORIZED, "Erreur de base de données"))?
+    .ok_or((StatusCode::UNAUTHORIZED, "Session d'impersonation invalide, expirée ou terminée"))?;
+
+    if method != Method::GET && method != Method::HEAD {
+        return Err((StatusCode::FORBIDDEN, "Session d'impersonation en lecture seule"));
+    }
+
+    Ok(SessionUser {
+        id: row.id,
+        tenant_id: row.tenant_id,
+        wallet: row.wallet,
+        name: row.name,
+        role: row.role,
+        created_at: row.created_at,
+        impersonation_id: Some(row.session_id),
+    })
+}
+
+/// Middleware : journalise chaque requête portant un jeton d'impersonation
+/// (refusées comprises), avec le statut de la réponse
+pub async fn audit_impersonated_requests(request: Request<Body>, next: Next<Body>) -> Response {
+    let Some(token_hash) = bearer_token(request.headers()).map(hash_token) else {
+        return next.run(request).await;
+    };
+    let Some(pool) = request.extensions().get::<PgPool>().cloned() else {
+        return next.run(request).await;
+    };
+    let method = request.method().to_string();
+    let path = request.uri()
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let ip = risk::client_ip(request.headers());
+
+    let response = next.run(request).await;
+    let status = response.status().as_u16() as i32;
+
+    tokio::spawn(async move {
+        let _ = sqlx::query!(
+            r#"INSERT INTO impersonation_actions (session_id, method, path, status, ip)
+               SELECT id, $2, $3, $4, $5 FROM impersonation_sessions WHERE token_hash = $1"#,
+            token_hash,
+            method,
+            path,
+            status,
+            ip
+        )
+        .execute(&pool)
+        .await;
+    });
+
+    response
+}
+
+/// Bandeau de `GET /api/me` : session d'impersonation en cours, le cas échéant
+pub async fn banner(pool: &PgPool, user: &SessionUser) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    let Some(session_id) = user.impersonation_id else {
+        return Ok(None);
+    };
+    let session = sqlx::query!(
+        r#"SELECT s.id, s.admin_id, a.wallet as admin_wallet, a.name as admin_name, s.reason, s.expires_at, s.created_at
+           FROM impersonation_sessions s
+           JOIN users a ON a.id = s.admin_id
+           WHERE s.id = $1"#,
+        session_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(session.map(|s| serde_json::json!({
+        "session_id": s.id,
+        "admin_id": s.admin_id,
+        "admin_wallet": s.admin_wallet,
+        "admin_name": s.admin_name,
+        "reason": s.reason,
+        "started_at": s.created_at,
+        "expires_at": s.expires_at,
+        "read_only": true
+    })))
+}
+
+/// Route admin : ouvrir une session d'impersonation (second facteur requis).
+/// Le jeton n'est renvoyé qu'une seule fois.
+pub async fn impersonate_user(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(payload): Json<ImpersonateRequest>,
+) -> impl IntoResponse {
+    if !is_acting_admin(&admin) {
+        return forbidden();
+    }
+    let reason = payload.reason.trim();
+    if reason.is_empty() || reason.len() > 500 {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le motif est requis (500 caractères au maximum)"
+        }))).into_response();
+    }
+
+    let target = match sqlx::query!(
+        r#"SELECT id, wallet, role as "role: UserRole" FROM users WHERE id = $1 AND tenant_id = $2"#,
+        user_id,
+        admin.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(target)) => target,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Utilisateur non trouvé"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération de l'utilisateur: {}", e)
+        }))).into_response(),
+    };
+    if matches!(target.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Impossible d'impersonner un admin"
+        }))).into_response();
+    }
+
+    if let Err(response) = two_factor::ensure_second_factor(&pool, &admin, &headers).await {
+        return response;
+    }
+
+    let token = generate_token();
+    let expires_at = Utc::now() + Duration::seconds(ttl_secs());
+    match sqlx::query_scalar!(
+        r#"INSERT INTO impersonation_sessions (admin_id, user_id, token_hash, reason, expires_at)
+           VALUES ($1, $2, $3, $4, $5)
+           RETURNING id"#,
+        admin.id,
+        target.id,
+        hash_token(&token),
+        reason,
+        expires_at
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(session_id) => (StatusCode::CREATED, Json(serde_json::json!({
+            "session_id": session_id,
+            "token": token,
+            "user_id": target.id,
+            "wallet": target.wallet,
+            "expires_at": expires_at,
+            "read_only": true,
+            "message": "Session d'impersonation ouverte : toutes les requêtes sont journalisées"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création de la session: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : sessions d'impersonation de la plateforme (`?admin_id=`,
+/// `?user_id=` pour filtrer), avec le nombre de requêtes journalisées
+pub async fn get_impersonations(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<ImpersonationListQuery>,
+    pagination: Pagination,
+) -> impl IntoResponse {
+    if !is_acting_admin(&user) {
+        return forbidden();
+    }
+    let (after_at, after_id) = pagination.cursor_bounds();
+
+    match sqlx::query!(
+        r#"SELECT s.id, s.admin_id, a.wallet as admin_wallet, s.user_id, u.wallet as user_wallet,
+                  s.reason, s.expires_at, s.ended_at, s.created_at,
+                  (SELECT COUNT(*) FROM impersonation_actions ia WHERE ia.session_id = s.id) as "actions!"
+           FROM impersonation_sessions s
+           JOIN users a ON a.id = s.admin_id
+           JOIN users u ON u.id = s.user_id
+           WHERE u.tenant_id = $1
+           AND ($2::uuid IS NULL OR s.admin_id = $2)
+           AND ($3::uuid IS NULL OR s.user_id = $3)
+           AND ($4::timestamptz IS NULL OR (s.created_at, s.id) < ($4, $5))
+           ORDER BY s.created_at DESC, s.id DESC
+           LIMIT $6 OFFSET $7"#,
+        user.tenant_id,
+        query.admin_id,
+        query.user_id,
+        after_at,
+        after_id,
+        pagination.limit(),
+        pagination.offset()
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(mut rows) => {
+            let page = pagination.finish(&mut rows, |row| Cursor::new(row.created_at, row.id));
+            let now = Utc::now();
+            let sessions = rows.into_iter().map(|row| serde_json::json!({
+                "id": row.id,
+                "admin_id": row.admin_id,
+                "admin_wallet": row.admin_wallet,
+                "user_id": row.user_id,
+                "user_wallet": row.user_wallet,
+                "reason": row.reason,
+                "active": row.ended_at.is_none() && row.expires_at > now,
+                "expires_at": row.expires_at,
+                "ended_at": row.ended_at,
+                "created_at": row.created_at,
+                "actions": row.actions
+            })).collect::<Vec<_>>();
+            envelope::list("sessions", &sessions).paginated(page).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : requêtes effectuées pendant une session d'impersonation
+pub async fn get_impersonation_actions(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(session_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !is_acting_admin(&user) {
+        return forbidden();
+    }
+
+    match sqlx::query!(
+        r#"SELECT ia.id, ia.method, ia.path, ia.status, ia.ip, ia.created_at
+           FROM impersonation_actions ia
+           JOIN impersonation_sessions s ON s.id = ia.session_id
+           JOIN users u ON u.id = s.user_id
+           WHERE ia.session_id = $1 AND u.tenant_id = $2
+           ORDER BY ia.created_at"#,
+        session_id,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => {
+            let actions = rows.into_iter().map(|row| serde_json::json!({
+                "id": row.id,
+                "method": row.method,
+                "path": row.path,
+                "status": row.status,
+                "ip": row.ip,
+                "created_at": row.created_at
+            })).collect::<Vec<_>>();
+            envelope::list("actions", &actions).with("session_id", session_id).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : terminer une session d'impersonation avant son expiration
+pub async fn end_impersonation(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(session_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !is_acting_admin(&user) {
+        return forbidden();
+    }
+
+    match sqlx::query!(
+        r#"UPDATE impersonation_sessions s SET ended_at = NOW()
+           FROM users u
+           WHERE s.id = $1 AND u.id = s.user_id AND u.tenant_id = $2 AND s.ended_at IS NULL"#,
+        session_id,
+        user.tenant_id
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Session non trouvée ou déjà terminée"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Session d'impersonation terminée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la clôture: {}", e)
+        }))).into_response(),
+    }
+}
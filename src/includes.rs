@@ -0,0 +1,246 @@
+// includes.rs
+//
+// Expansion des ressources liées via `?include=` : les données demandées sont
+// chargées en une requête groupée par type de relation, jamais une par ligne.
+
+use bigdecimal::BigDecimal;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{Amenities, Investment, MediaKind, Property, PropertyMedia, PropertyStatus, UserRole};
+
+// Relations disponibles par ressource
+pub const PROPERTY_INCLUDES: &[&str] = &["investments_summary", "manager", "tags", "media", "document_pins", "place"];
+pub const INVESTMENT_INCLUDES: &[&str] = &["property"];
+
+/// Analyse `?include=a,b` et vérifie chaque valeur contre la liste autorisée
+pub fn parse_includes(raw: Option<&str>, allowed: &[&str]) -> Result<Vec<String>, String> {
+    let mut includes = Vec::new();
+    for item in raw.unwrap_or("").split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if !allowed.contains(&item) {
+            return Err(format!(
+                "Valeur include inconnue: '{}' (valeurs possibles: {})",
+                item,
+                allowed.join(", ")
+            ));
+        }
+        if !includes.iter().any(|i| i == item) {
+            includes.push(item.to_string());
+        }
+    }
+    Ok(includes)
+}
+
+/// Sérialise les propriétés en ajoutant les relations demandées
+pub async fn expand_properties(
+    pool: &PgPool,
+    properties: Vec<Property>,
+    includes: &[String],
+) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+    expand_properties_on(&mut *pool.acquire().await?, properties, includes).await
+}
+
+/// `expand_properties` sur une connexion donnée, par exemple celle d'une
+/// transaction en cours
+pub async fn expand_properties_on(
+    conn: &mut PgConnection,
+    properties: Vec<Property>,
+    includes: &[String],
+) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+    let property_ids: Vec<Uuid> = properties.iter().map(|p| p.id).collect();
+    let manager_ids: Vec<Uuid> = properties.iter().map(|p| p.created_by).collect();
+
+    let mut summaries = HashMap::new();
+    if includes.iter().any(|i| i == "investments_summary") {
+        let rows = sqlx::query!(
+            r#"SELECT property_id,
+               COUNT(*) as "investments_count!",
+               COUNT(DISTINCT user_id) as "investors_count!",
+               COALESCE(SUM(shares), 0) as "total_shares!",
+               COALESCE(SUM(amount_eth), 0) as "total_amount_eth!"
+               FROM investments
+               WHERE property_id = ANY($1) AND status <> 'refunded'
+               GROUP BY property_id"#,
+            &property_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for row in rows {
+            summaries.insert(row.property_id, serde_json::json!({
+                "investments_count": row.investments_count,
+                "investors_count": row.investors_count,
+                "total_shares": row.total_shares,
+                "total_amount_eth": row.total_amount_eth
+            }));
+        }
+    }
+
+    let mut managers = HashMap::new();
+    if includes.iter().any(|i| i == "manager") {
+        let rows = sqlx::query!(
+            r#"SELECT id, wallet, name, role as "role: UserRole"
+               FROM users
+               WHERE id = ANY($1)"#,
+            &manager_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for row in rows {
+            managers.insert(row.id, serde_json::json!({
+                "id": row.id,
+                "wallet": row.wallet,
+                "name": row.name,
+                "role": row.role
+            }));
+        }
+    }
+
+    let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+    if includes.iter().any(|i| i == "tags") {
+        let rows = sqlx::query!(
+            r#"SELECT pt.property_id, t.slug
+               FROM property_tags pt
+               JOIN tags t ON t.id = pt.tag_id
+               WHERE pt.property_id = ANY($1)
+               ORDER BY t.slug"#,
+            &property_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for row in rows {
+            tags.entry(row.property_id).or_default().push(row.slug);
+        }
+    }
+
+    let mut media: HashMap<Uuid, Vec<PropertyMedia>> = HashMap::new();
+    if includes.iter().any(|i| i == "media") {
+        let rows = sqlx::query_as!(
+            PropertyMedia,
+            r#"SELECT id, property_id, kind as "kind: MediaKind", url, caption, position, is_cover, created_at
+               FROM property_media
+               WHERE property_id = ANY($1)
+               ORDER BY position, created_at"#,
+            &property_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for row in rows {
+            media.entry(row.property_id).or_default().push(row);
+        }
+    }
+
+    // CID IPFS des documents encore référencés par la propriété
+    let mut pins: HashMap<Uuid, Vec<serde_json::Value>> = HashMap::new();
+    if includes.iter().any(|i| i == "document_pins") {
+        let rows = sqlx::query!(
+            r#"SELECT dp.property_id, dp.document_key, dp.cid, dp.pinned_at
+               FROM document_pins dp
+               JOIN properties p ON p.id = dp.property_id
+               WHERE dp.property_id = ANY($1) AND dp.document_key = ANY(p.documents)
+               ORDER BY dp.pinned_at"#,
+            &property_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for row in rows {
+            pins.entry(row.property_id).or_default().push(serde_json::json!({
+                "document_key": row.document_key,
+                "cid": row.cid,
+                "pinned_at": row.pinned_at
+            }));
+        }
+    }
+
+    // Localisation géocodée (voir geocoding.rs)
+    let mut places = HashMap::new();
+    if includes.iter().any(|i| i == "place") {
+        let rows = sqlx::query!(
+            "SELECT id, country, region, city, geocoded_at FROM properties WHERE id = ANY($1)",
+            &property_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for row in rows {
+            places.insert(row.id, serde_json::json!({
+                "country": row.country,
+                "region": row.region,
+                "city": row.city,
+                "geocoded_at": row.geocoded_at
+            }));
+        }
+    }
+
+    Ok(properties.into_iter().map(|property| {
+        let id = property.id;
+        let created_by = property.created_by;
+        let mut value = serde_json::json!(property);
+        if includes.iter().any(|i| i == "investments_summary") {
+            value["investments_summary"] = summaries.remove(&id).unwrap_or_else(|| serde_json::json!({
+                "investments_count": 0,
+                "investors_count": 0,
+                "total_shares": 0,
+                "total_amount_eth": BigDecimal::from(0)
+            }));
+        }
+        if includes.iter().any(|i| i == "manager") {
+            value["manager"] = managers.get(&created_by).cloned().unwrap_or(serde_json::Value::Null);
+        }
+        if includes.iter().any(|i| i == "tags") {
+            value["tags"] = serde_json::json!(tags.remove(&id).unwrap_or_default());
+        }
+        if includes.iter().any(|i| i == "media") {
+            value["media"] = serde_json::json!(media.remove(&id).unwrap_or_default());
+        }
+        if includes.iter().any(|i| i == "document_pins") {
+            value["document_pins"] = serde_json::json!(pins.remove(&id).unwrap_or_default());
+        }
+        if includes.iter().any(|i| i == "place") {
+            value["place"] = places.remove(&id).unwrap_or(serde_json::Value::Null);
+        }
+        value
+    }).collect())
+}
+
+/// Sérialise les investissements en ajoutant les relations demandées
+pub async fn expand_investments(
+    pool: &PgPool,
+    investments: Vec<Investment>,
+    includes: &[String],
+) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+    let mut properties = HashMap::new();
+    if includes.iter().any(|i| i == "property") {
+        let property_ids: Vec<Uuid> = investments.iter().map(|i| i.property_id).collect();
+        let rows = sqlx::query_as!(
+            Property,
+            r#"SELECT id, onchain_id, slug, name, location, type as property_type, description,
+               total_price, token_price, annual_yield, image_url, documents,
+               created_by, created_at, status as "status: PropertyStatus",
+               status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address
+               FROM properties
+               WHERE id = ANY($1)"#,
+            &property_ids
+        )
+        .fetch_all(pool)
+        .await?;
+
+        for property in rows {
+            properties.insert(property.id, serde_json::json!(property));
+        }
+    }
+
+    Ok(investments.into_iter().map(|investment| {
+        let property_id = investment.property_id;
+        let mut value = serde_json::json!(investment);
+        if includes.iter().any(|i| i == "property") {
+            value["property"] = properties.get(&property_id).cloned().unwrap_or(serde_json::Value::Null);
+        }
+        value
+    }).collect())
+}
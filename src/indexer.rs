@@ -0,0 +1,292 @@
+// indexer.rs
+//
+// Indexation des événements des contrats suivis : les transferts de jetons des
+// propriétés (mint et burn compris) sont enregistrés dans `token_transfers` et
+// la progression de chaque source dans `indexer_cursors`. Les webhooks des
+// fournisseurs (Alchemy, Moralis Streams) alimentent ce pipeline quand une
+// souscription RPC en continu n'est pas possible. Les événements `Invested` du
+// contrat d'investissement y créent aussi les investissements, si ce mode est
+// activé (voir onchain_investments.rs).
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use bigdecimal::BigDecimal;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::env;
+use std::str::FromStr;
+
+use crate::chain::{uint256_to_decimal, TxLog};
+use crate::eip712::keccak256;
+use crate::onchain_investments;
+use crate::risk::RiskRules;
+use crate::token::decode_transfers;
+
+/// Bilan d'une ingestion
+#[derive(Default)]
+pub struct IngestStats {
+    pub inserted: u64,
+    pub removed: u64,
+    pub skipped: u64,
+}
+
+/// Enregistre les transferts émis par le contrat des jetons. Idempotent : un
+/// événement déjà connu est ignoré, un événement retiré par une réorganisation
+/// est supprimé. Le curseur de la source avance jusqu'à `last_block` (ou au
+/// dernier bloc des événements reçus).
+pub async fn ingest(
+    pool: &PgPool,
+    source: &str,
+    token_address: &str,
+    logs: &[TxLog],
+    last_block: Option<u64>,
+) -> Result<IngestStats, sqlx::Error> {
+    let mut stats = IngestStats::default();
+    let mut tx = pool.begin().await?;
+
+    for log in logs.iter().filter(|log| log.address == token_address) {
+        let (block_number, tx_hash, log_index) = match (log.block_number, &log.tx_hash, log.log_index) {
+            (Some(block_number), Some(tx_hash), Some(log_index)) => (block_number, tx_hash, log_index as i32),
+            _ => {
+                stats.skipped += 1;
+                continue;
+            },
+        };
+
+        if log.removed {
+            let result = sqlx::query!(
+                "DELETE FROM token_transfers WHERE tx_hash = $1 AND log_index = $2",
+                tx_hash,
+                log_index
+            )
+            .execute(&mut tx)
+            .await?;
+            stats.removed += result.rows_affected();
+            continue;
+        }
+
+        let transfers = match decode_transfers(log) {
+            Ok(transfers) => transfers,
+            Err(e) => {
+                tracing::warn!("Indexation: événement {}#{} ignoré: {}", tx_hash, log_index, e);
+                stats.skipped += 1;
+                continue;
+            },
+        };
+        for (batch_index, transfer) in transfers.iter().enumerate() {
+            let result = sqlx::query!(
+                r#"INSERT INTO token_transfers
+                   (property_id, token_id, from_address, to_address, amount, block_number, tx_hash, log_index, batch_index, source)
+                   VALUES ((SELECT id FROM properties WHERE onchain_id = $1), $1, $2, $3, $4, $5, $6, $7, $8, $9)
+                   ON CONFLICT (tx_hash, log_index, batch_index) DO NOTHING"#,
+                uint256_to_decimal(&transfer.token_id),
+                transfer.from,
+                transfer.to,
+                BigDecimal::from_str(&transfer.value.to_string()).unwrap_or_default(),
+                block_number as i64,
+                tx_hash,
+                log_index,
+                batch_index as i32,
+                source
+            )
+            .execute(&mut tx)
+            .await?;
+            stats.inserted += result.rows_affected();
+        }
+    }
+
+    if let Some(last_block) = logs.iter().filter_map(|log| log.block_number).chain(last_block).max() {
+        sqlx::query!(
+            r#"INSERT INTO indexer_cursors (source, last_block) VALUES ($1, $2)
+               ON CONFLICT (source) DO UPDATE
+               SET last_block = GREATEST(indexer_cursors.last_block, EXCLUDED.last_block), updated_at = NOW()"#,
+            source,
+            last_block as i64
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(stats)
+}
+
+/// Secrets des webhooks et contrat suivi, lus depuis l'environnement
+#[derive(Clone)]
+pub struct ChainWebhooks {
+    token_address: String,
+    investment_address: Option<String>, // Création des investissements depuis la chaîne
+    alchemy_signing_key: Option<String>,
+    moralis_secret: Option<String>,
+}
+
+impl ChainWebhooks {
+    /// Renvoie `None` si le contrat des jetons ou aucun secret n'est configuré
+    pub fn from_env() -> Option<Self> {
+        let secret = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let webhooks = Self {
+            token_address: secret("PROPERTY_TOKEN_ADDRESS")?.trim().to_lowercase(),
+            investment_address: onchain_investments::investment_address_from_env(),
+            alchemy_signing_key: secret("ALCHEMY_WEBHOOK_SIGNING_KEY"),
+            moralis_secret: secret("MORALIS_STREAMS_SECRET"),
+        };
+        (webhooks.alchemy_signing_key.is_some() || webhooks.moralis_secret.is_some()).then_some(webhooks)
+    }
+}
+
+/// Alchemy : HMAC-SHA256 du corps brut avec la clé de signature du webhook
+fn verify_alchemy(signing_key: &str, body: &[u8], signature: &str) -> bool {
+    let expected = match hex::decode(signature.trim()) {
+        Ok(expected) => expected,
+        Err(_) => return false,
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(signing_key.as_bytes()).expect("HMAC accepte toute taille de clé");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Moralis Streams : keccak256(corps ‖ secret)
+fn verify_moralis(secret: &str, body: &[u8], signature: &str) -> bool {
+    let mut signed = body.to_vec();
+    signed.extend_from_slice(secret.as_bytes());
+    let expected = keccak256(&signed);
+    match hex::decode(signature.trim().trim_start_matches("0x")) {
+        // Comparaison en temps constant
+        Ok(received) => received.len() == expected.len()
+            && received.iter().zip(expected.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0,
+        Err(_) => false,
+    }
+}
+
+fn lowercase(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_lowercase()
+}
+
+/// Événements d'un webhook Alchemy : « Address Activity » (journaux au format
+/// `eth_getLogs`) ou webhook personnalisé GraphQL (bloc et journaux sélectionnés)
+fn alchemy_logs(payload: &Value) -> (Vec<TxLog>, Option<u64>) {
+    let event = &payload["event"];
+    let mut logs: Vec<TxLog> = event["activity"].as_array()
+        .map(|activity| activity.iter().filter(|a| a["log"].is_object()).map(|a| TxLog::from_json(&a["log"])).collect())
+        .unwrap_or_default();
+
+    let block = &event["data"]["block"];
+    let block_number = block["number"].as_u64();
+    if let Some(block_logs) = block["logs"].as_array() {
+        logs.extend(block_logs.iter().map(|log| TxLog {
+            address: lowercase(&log["account"]["address"]),
+            topics: log["topics"].as_array().map(|t| t.iter().map(lowercase).collect()).unwrap_or_default(),
+            data: lowercase(&log["data"]),
+            block_number,
+            tx_hash: log["transaction"]["hash"].as_str().map(str::to_lowercase),
+            log_index: log["index"].as_u64(),
+            removed: false,
+        }));
+    }
+    (logs, block_number)
+}
+
+/// Événements d'un webhook Moralis Streams ; `None` tant que le bloc n'est pas
+/// confirmé (Moralis renvoie le même bloc une fois confirmé)
+fn moralis_logs(payload: &Value) -> Option<(Vec<TxLog>, Option<u64>)> {
+    if !payload["confirmed"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    let block_number = payload["block"]["number"].as_str().and_then(|n| n.parse().ok());
+    let logs = payload["logs"].as_array()
+        .map(|logs| logs.iter().map(|log| TxLog {
+            address: lowercase(&log["address"]),
+            topics: ["topic0", "topic1", "topic2", "topic3"].iter()
+                .filter_map(|name| log[*name].as_str().map(str::to_lowercase))
+                .collect(),
+            data: lowercase(&log["data"]),
+            block_number,
+            tx_hash: log["transactionHash"].as_str().map(str::to_lowercase),
+            log_index: log["logIndex"].as_str().and_then(|i| i.parse().ok()),
+            removed: false,
+        }).collect())
+        .unwrap_or_default();
+    Some((logs, block_number))
+}
+
+/// Route publique : événements poussés par Alchemy (`X-Alchemy-Signature`) ou
+/// Moralis Streams (`x-signature`), authentifiés par leur signature
+pub async fn chain_webhook(
+    State(pool): State<PgPool>,
+    Extension(webhooks): Extension<Option<ChainWebhooks>>,
+    Extension(rules): Extension<RiskRules>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let webhooks = match webhooks {
+        Some(webhooks) => webhooks,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Webhooks on-chain non configurés (PROPERTY_TOKEN_ADDRESS, ALCHEMY_WEBHOOK_SIGNING_KEY ou MORALIS_STREAMS_SECRET)"
+        }))).into_response(),
+    };
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let source = match (header("x-alchemy-signature"), header("x-signature")) {
+        (Some(signature), _) if webhooks.alchemy_signing_key.as_deref()
+            .is_some_and(|key| verify_alchemy(key, &body, signature)) => "alchemy",
+        (_, Some(signature)) if webhooks.moralis_secret.as_deref()
+            .is_some_and(|secret| verify_moralis(secret, &body, signature)) => "moralis",
+        _ => return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({
+            "error": "Signature du webhook invalide"
+        }))).into_response(),
+    };
+
+    let payload: Value = match serde_json::from_slice(&body) {
+        Ok(payload) => payload,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Corps JSON invalide: {}", e)
+        }))).into_response(),
+    };
+
+    let (logs, last_block) = match source {
+        "alchemy" => alchemy_logs(&payload),
+        _ => match moralis_logs(&payload) {
+            Some(parsed) => parsed,
+            None => return (StatusCode::OK, Json(serde_json::json!({
+                "message": "Bloc non confirmé ignoré"
+            }))).into_response(),
+        },
+    };
+
+    let stats = match ingest(&pool, source, &webhooks.token_address, &logs, last_block).await {
+        Ok(stats) => stats,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'indexation: {}", e)
+        }))).into_response(),
+    };
+    let investments = match &webhooks.investment_address {
+        Some(address) => onchain_investments::ingest(&pool, &rules, source, address, &logs).await.map(Some),
+        None => Ok(None),
+    };
+
+    match investments {
+        Ok(investments) => (StatusCode::OK, Json(serde_json::json!({
+            "source": source,
+            "received": logs.len(),
+            "inserted": stats.inserted,
+            "removed": stats.removed,
+            "skipped": stats.skipped,
+            "investments": investments.map(|investments| serde_json::json!({
+                "created": investments.inserted,
+                "removed": investments.removed,
+                "skipped": investments.skipped
+            }))
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'indexation: {}", e)
+        }))).into_response(),
+    }
+}
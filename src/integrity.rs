@@ -0,0 +1,281 @@
+// integrity.rs
+//
+// Contrôle de cohérence des données : une série de requêtes recherche les
+// anomalies que les contraintes de la base ne couvrent pas, ou plus (données
+// importées, écritures directes) : investissements sans propriété ou antérieurs
+// à sa validation, auteurs de changement de statut disparus, montants non
+// positifs, hash de transaction en double, parts vendues désynchronisées. Le
+// contrôle se lance à la demande par un admin ou périodiquement par les
+// workers, qui alertent les admins si une anomalie est trouvée.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::env;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::error_codes::ErrorCode;
+use crate::models::UserRole;
+use crate::notifications::notify_admins;
+use crate::tenants::DEFAULT_TENANT_ID;
+use crate::worker;
+
+/// Exemples renvoyés par contrôle ; `count` donne le nombre total d'anomalies
+const SAMPLE_LIMIT: i64 = 20;
+
+/// Résultat d'un contrôle
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub check: &'static str,
+    pub description: &'static str,
+    pub count: i64,
+    pub samples: Vec<Value>,
+}
+
+/// Rapport d'un passage de contrôle
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    pub ok: bool,
+    pub issues: i64,
+    pub checks: Vec<CheckResult>,
+}
+
+fn result(check: &'static str, description: &'static str, rows: Vec<(Value, i64)>) -> CheckResult {
+    CheckResult {
+        check,
+        description,
+        count: rows.first().map(|(_, total)| *total).unwrap_or(0),
+        samples: rows.into_iter().map(|(sample, _)| sample).collect(),
+    }
+}
+
+/// Exécute tous les contrôles
+pub async fn run_checks(pool: &PgPool) -> Result<IntegrityReport, sqlx::Error> {
+    let mut checks = Vec::new();
+
+    let rows = sqlx::query!(
+        r#"SELECT jsonb_build_object('investment_id', i.id, 'property_id', i.property_id) as "sample!",
+                  COUNT(*) OVER () as "total!"
+           FROM investments i
+           WHERE NOT EXISTS (SELECT 1 FROM properties p WHERE p.id = i.property_id)
+           ORDER BY i.created_at
+           LIMIT $1"#,
+        SAMPLE_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+    checks.push(result(
+        "investments_missing_property",
+        "Investissements dont la propriété n'existe pas",
+        rows.into_iter().map(|r| (r.sample, r.total)).collect(),
+    ));
+
+    let rows = sqlx::query!(
+        r#"SELECT jsonb_build_object('investment_id', i.id, 'property_id', p.id, 'property_status', p.status) as "sample!",
+                  COUNT(*) OVER () as "total!"
+           FROM investments i
+           JOIN properties p ON p.id = i.property_id
+           WHERE i.status <> 'refunded' AND p.status <> 'validated'
+           ORDER BY i.created_at
+           LIMIT $1"#,
+        SAMPLE_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+    checks.push(result(
+        "investments_in_unvalidated_property",
+        "Investissements non remboursés dans une propriété qui n'est pas validée",
+        rows.into_iter().map(|r| (r.sample, r.total)).collect(),
+    ));
+
+    // Première validation : historique des statuts, à défaut date du statut actuel
+    let rows = sqlx::query!(
+        r#"WITH validations AS (
+               SELECT p.id,
+                      COALESCE(
+                          (SELECT MIN(e.created_at) FROM property_status_events e
+                           WHERE e.property_id = p.id AND e.status = 'validated'),
+                          CASE WHEN p.status = 'validated' THEN p.status_updated_at END
+                      ) as validated_at
+               FROM properties p
+           )
+           SELECT jsonb_build_object('investment_id', i.id, 'property_id', i.property_id,
+                                     'invested_at', i.created_at, 'validated_at', v.validated_at) as "sample!",
+                  COUNT(*) OVER () as "total!"
+           FROM investments i
+           JOIN validations v ON v.id = i.property_id
+           WHERE i.created_at < v.validated_at
+           ORDER BY i.created_at
+           LIMIT $1"#,
+        SAMPLE_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+    checks.push(result(
+        "investments_before_validation",
+        "Investissements antérieurs à la première validation de leur propriété",
+        rows.into_iter().map(|r| (r.sample, r.total)).collect(),
+    ));
+
+    let rows = sqlx::query!(
+        r#"SELECT jsonb_build_object('property_id', p.id, 'status_updated_by', p.status_updated_by) as "sample!",
+                  COUNT(*) OVER () as "total!"
+           FROM properties p
+           WHERE p.status_updated_by IS NOT NULL
+           AND NOT EXISTS (SELECT 1 FROM users u WHERE u.id = p.status_updated_by)
+           ORDER BY p.created_at
+           LIMIT $1"#,
+        SAMPLE_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+    checks.push(result(
+        "orphaned_status_updated_by",
+        "Propriétés dont l'auteur du dernier changement de statut n'existe plus",
+        rows.into_iter().map(|r| (r.sample, r.total)).collect(),
+    ));
+
+    let rows = sqlx::query!(
+        r#"WITH amounts AS (
+               SELECT 'investments' as entity, id, amount_eth, amount_wei, shares::bigint as shares, created_at FROM investments
+               UNION ALL
+               SELECT 'investment_refunds', id, amount_eth, amount_wei, NULL, created_at FROM investment_refunds
+               UNION ALL
+               SELECT 'fiat_payments', id, amount_eth, amount_wei, NULL, created_at FROM fiat_payments
+           )
+           SELECT jsonb_build_object('entity', entity, 'id', id, 'amount_eth', amount_eth, 'shares', shares) as "sample!",
+                  COUNT(*) OVER () as "total!"
+           FROM amounts
+           WHERE amount_eth <= 0 OR amount_wei <= 0 OR shares <= 0
+           ORDER BY created_at
+           LIMIT $1"#,
+        SAMPLE_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+    checks.push(result(
+        "non_positive_amounts",
+        "Investissements, remboursements ou paiements en euros de montant ou de parts non positifs",
+        rows.into_iter().map(|r| (r.sample, r.total)).collect(),
+    ));
+
+    let rows = sqlx::query!(
+        r#"SELECT jsonb_build_object('tx_hash', lower(tx_hash), 'investment_ids', array_agg(id ORDER BY created_at)) as "sample!",
+                  COUNT(*) OVER () as "total!"
+           FROM investments
+           WHERE tx_hash IS NOT NULL
+           GROUP BY lower(tx_hash)
+           HAVING COUNT(*) > 1
+           ORDER BY MIN(created_at)
+           LIMIT $1"#,
+        SAMPLE_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+    checks.push(result(
+        "duplicate_tx_hashes",
+        "Hash de transaction partagés par plusieurs investissements",
+        rows.into_iter().map(|r| (r.sample, r.total)).collect(),
+    ));
+
+    let rows = sqlx::query!(
+        r#"SELECT jsonb_build_object('property_id', p.id, 'shares_sold', p.shares_sold, 'expected', COALESCE(s.shares, 0)) as "sample!",
+                  COUNT(*) OVER () as "total!"
+           FROM properties p
+           LEFT JOIN (SELECT property_id, SUM(shares) as shares FROM investments
+                      WHERE status <> 'refunded' GROUP BY property_id) s ON s.property_id = p.id
+           WHERE p.shares_sold <> COALESCE(s.shares, 0)
+           ORDER BY p.created_at
+           LIMIT $1"#,
+        SAMPLE_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+    checks.push(result(
+        "shares_sold_mismatch",
+        "Parts vendues d'une propriété différentes de la somme de ses investissements non remboursés",
+        rows.into_iter().map(|r| (r.sample, r.total)).collect(),
+    ));
+
+    let issues = checks.iter().map(|check| check.count).sum();
+    Ok(IntegrityReport { checked_at: Utc::now(), ok: issues == 0, issues, checks })
+}
+
+/// Contrôle périodique : alerte les admins si une anomalie est trouvée
+async fn run_and_alert(pool: &PgPool) -> Result<IntegrityReport, sqlx::Error> {
+    let report = run_checks(pool).await?;
+    if !report.ok {
+        let failed: Vec<Value> = report.checks.iter()
+            .filter(|check| check.count > 0)
+            .map(|check| serde_json::json!({ "check": check.check, "count": check.count }))
+            .collect();
+        notify_admins(pool, "integrity_issues",
+            &format!("Contrôle d'intégrité : {} anomalie(s) détectée(s)", report.issues),
+            serde_json::json!({ "checked_at": report.checked_at, "checks": failed })).await?;
+    }
+    Ok(report)
+}
+
+/// Lance le contrôle périodique. Intervalle configurable via
+/// `INTEGRITY_CHECK_INTERVAL_SECS` (24h par défaut).
+pub fn spawn(pool: PgPool) {
+    let interval_secs = env::var("INTEGRITY_CHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(86_400);
+
+    tokio::spawn(async move {
+        let period = Duration::from_secs(interval_secs);
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match worker::run_exclusive(&pool, "integrity_check", period, run_and_alert(&pool)).await {
+                Some(Ok(report)) if !report.ok => tracing::warn!(
+                    "Contrôle d'intégrité: {} anomalie(s) ({})",
+                    report.issues,
+                    report.checks.iter().filter(|c| c.count > 0).map(|c| c.check).collect::<Vec<_>>().join(", ")
+                ),
+                None | Some(Ok(_)) => {},
+                Some(Err(e)) => tracing::error!("Erreur du contrôle d'intégrité: {}", e),
+            }
+        }
+    });
+}
+
+/// Le contrôle porte sur toutes les plateformes : réservé aux admins de la
+/// plateforme par défaut
+fn platform_admin_only(role: &UserRole, tenant_id: Uuid) -> Option<Response> {
+    (!matches!(role, UserRole::Admin) || tenant_id != DEFAULT_TENANT_ID).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins de la plateforme principale peuvent lancer le contrôle d'intégrité",
+        "code": ErrorCode::Forbidden
+    }))).into_response())
+}
+
+/// Route admin : lancer le contrôle d'intégrité et renvoyer le rapport
+pub async fn run_integrity_check(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    if let Some(response) = platform_admin_only(&user.role, user.tenant_id) {
+        return response;
+    }
+
+    match run_checks(&pool).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du contrôle d'intégrité: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
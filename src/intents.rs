@@ -0,0 +1,342 @@
+// intents.rs
+//
+// Intentions d'investissement EIP-712 : le backend construit la cotation
+// (propriété, parts, prix, nonce, échéance) que l'investisseur signe avec son
+// wallet. L'intention signée est ensuite rattachée à l'investissement enregistré
+// après la transaction on-chain, ce qui fige le prix convenu.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::compliance;
+use crate::accreditation;
+use crate::legal;
+use crate::exposure;
+use crate::funding;
+use crate::eip712::{self, Eip712Domain, InvestmentIntentMessage};
+use crate::models::{CreateIntentRequest, InvestmentIntent, PropertyStatus, SignIntentRequest, UserRole};
+use crate::tags::is_unique_violation;
+use crate::money::{TokenAmount, Wei};
+
+/// Durée de validité d'une intention, `INTENT_TTL_SECS` (15 minutes par défaut)
+fn intent_ttl_secs() -> i64 {
+    env::var("INTENT_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(900)
+}
+
+/// Reconstruit le message signé à partir de l'intention enregistrée
+fn message_for(intent: &InvestmentIntent, onchain_id: &str) -> Option<InvestmentIntentMessage> {
+    Some(InvestmentIntentMessage {
+        investor: eip712::parse_address(&intent.investor)?,
+        property_id: onchain_id.to_string(),
+        shares: intent.shares as u64,
+        price_per_share_wei: intent.price_per_share_wei.to_u128()?,
+        nonce: intent.nonce as u64,
+        deadline: intent.deadline.timestamp() as u64,
+    })
+}
+
+fn intent_status(intent: &InvestmentIntent) -> &'static str {
+    if intent.investment_id.is_some() {
+        "consumed"
+    } else if intent.signature.is_some() {
+        "signed"
+    } else if intent.deadline < Utc::now() {
+        "expired"
+    } else {
+        "pending"
+    }
+}
+
+fn intent_json(intent: &InvestmentIntent, typed_data: Option<serde_json::Value>) -> serde_json::Value {
+    let mut value = serde_json::json!(intent);
+    value["status"] = serde_json::json!(intent_status(intent));
+    if let Some(typed_data) = typed_data {
+        value["typed_data"] = typed_data;
+    }
+    value
+}
+
+/// Route pour créer une intention d'investissement à signer (authentification requise)
+pub async fn create_intent(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(domain): Extension<Option<Eip712Domain>>,
+    Json(payload): Json<CreateIntentRequest>,
+) -> impl IntoResponse {
+    let domain = match domain {
+        Some(domain) => domain,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Intentions d'investissement non configurées (INVESTMENT_CONTRACT_ADDRESS)"
+        }))).into_response(),
+    };
+
+    let investor = match eip712::parse_address(user.wallet.as_str()) {
+        Some(address) => address,
+        None => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le wallet de l'utilisateur n'est pas une adresse Ethereum valide"
+        }))).into_response(),
+    };
+
+    if payload.shares <= 0 {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le nombre de parts doit être positif"
+        }))).into_response();
+    }
+
+    if let Err(response) = compliance::ensure_not_flagged(&pool, user.id).await {
+        return response;
+    }
+    if let Err(response) = legal::ensure_terms_accepted(&pool, user.id).await {
+        return response;
+    }
+
+    let property = match sqlx::query!(
+        r#"SELECT onchain_id, token_price, status as "status: PropertyStatus", published_at, requires_accreditation,
+                  total_shares - shares_sold as "available_shares!"
+           FROM properties WHERE id = $1 AND tenant_id = $2"#,
+        payload.property_id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(prop)) if !matches!(prop.status, PropertyStatus::Draft) => prop,
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    // Mêmes conditions que pour l'enregistrement d'un investissement
+    if !matches!(property.status, PropertyStatus::Validated) || property.published_at.is_none() {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Impossible d'investir dans une propriété non validée ou non publiée"
+        }))).into_response();
+    }
+
+    // Offre réservée aux investisseurs accrédités
+    if property.requires_accreditation {
+        if let Err(response) = accreditation::ensure_accredited(&pool, user.id).await {
+            return response;
+        }
+    }
+
+    // Parts disponibles au moment de la cotation (l'insertion de l'investissement reste gardée par la base)
+    if property.available_shares < payload.shares as i64 {
+        return funding::sold_out_response(Some(property.available_shares));
+    }
+
+    // Prix d'une part en wei (le prix stocké est en ETH)
+    let price_per_share = TokenAmount::from_decimal(property.token_price.clone()).wei();
+    let price_per_share_wei = match price_per_share.to_u128() {
+        Some(wei) => wei,
+        None => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": "Prix de la part non convertible en wei"
+        }))).into_response(),
+    };
+    let amount_eth = TokenAmount::for_shares(&property.token_price, payload.shares as i64);
+    let amount_wei = amount_eth.wei();
+    if let Err(response) = exposure::ensure_within_limits(&pool, user.id, payload.property_id, &amount_eth).await {
+        return response;
+    }
+    // Précision à la seconde, comme le champ `deadline` signé
+    let deadline = Utc::now() + Duration::seconds(intent_ttl_secs());
+    let deadline = deadline - Duration::nanoseconds(deadline.timestamp_subsec_nanos() as i64);
+
+    // Nonce croissant par utilisateur (l'unicité est garantie par la base)
+    let nonce = match sqlx::query_scalar!(
+        r#"SELECT COALESCE(MAX(nonce) + 1, 0) as "nonce!" FROM investment_intents WHERE user_id = $1"#,
+        user.id
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(nonce) => nonce,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    };
+
+    let message = InvestmentIntentMessage {
+        investor,
+        property_id: property.onchain_id.clone(),
+        shares: payload.shares as u64,
+        price_per_share_wei,
+        nonce: nonce as u64,
+        deadline: deadline.timestamp() as u64,
+    };
+    let digest = format!("0x{}", hex::encode(message.digest(&domain)));
+
+    match sqlx::query_as!(
+        InvestmentIntent,
+        r#"INSERT INTO investment_intents
+           (user_id, property_id, investor, shares, price_per_share_wei, amount_eth, amount_wei, nonce, deadline, digest)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+           RETURNING id, user_id, property_id, investor, shares, price_per_share_wei as "price_per_share_wei: Wei", amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei",
+                     nonce, deadline, digest, signature, signed_at, investment_id, created_at"#,
+        user.id,
+        payload.property_id,
+        eip712::format_address(&investor),
+        payload.shares,
+        price_per_share.as_decimal(),
+        amount_eth.as_decimal(),
+        amount_wei.as_decimal(),
+        nonce,
+        deadline,
+        digest
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(intent) => (StatusCode::CREATED, Json(serde_json::json!({
+            "intent": intent_json(&intent, Some(message.typed_data(&domain))),
+            "message": "Intention créée : signez typed_data avec eth_signTypedData_v4"
+        }))).into_response(),
+        Err(e) if is_unique_violation(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Une autre intention vient d'être créée, veuillez réessayer"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Charge une intention avec l'onchain_id de sa propriété
+async fn load_intent(pool: &PgPool, intent_id: Uuid) -> Result<Option<(InvestmentIntent, String)>, sqlx::Error> {
+    let intent = sqlx::query_as!(
+        InvestmentIntent,
+        r#"SELECT id, user_id, property_id, investor, shares, price_per_share_wei as "price_per_share_wei: Wei", amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei",
+                  nonce, deadline, digest, signature, signed_at, investment_id, created_at
+           FROM investment_intents WHERE id = $1"#,
+        intent_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    match intent {
+        Some(intent) => {
+            let onchain_id = sqlx::query_scalar!("SELECT onchain_id FROM properties WHERE id = $1", intent.property_id)
+                .fetch_one(pool)
+                .await?;
+            Ok(Some((intent, onchain_id)))
+        },
+        None => Ok(None),
+    }
+}
+
+/// Route pour consulter une intention et son payload (investisseur ou admin)
+pub async fn get_intent(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(domain): Extension<Option<Eip712Domain>>,
+    Path(intent_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match load_intent(&pool, intent_id).await {
+        Ok(Some((intent, onchain_id))) if intent.user_id == user.id || matches!(user.role, UserRole::Admin) => {
+            let typed_data = domain
+                .as_ref()
+                .zip(message_for(&intent, &onchain_id))
+                .map(|(domain, message)| message.typed_data(domain));
+            (StatusCode::OK, Json(serde_json::json!({
+                "intent": intent_json(&intent, typed_data)
+            }))).into_response()
+        },
+        Ok(_) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Intention non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour enregistrer la signature d'une intention (investisseur uniquement).
+/// La signature doit provenir du wallet de l'investisseur et arriver avant l'échéance.
+pub async fn sign_intent(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(domain): Extension<Option<Eip712Domain>>,
+    Path(intent_id): Path<Uuid>,
+    Json(payload): Json<SignIntentRequest>,
+) -> impl IntoResponse {
+    let domain = match domain {
+        Some(domain) => domain,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Intentions d'investissement non configurées (INVESTMENT_CONTRACT_ADDRESS)"
+        }))).into_response(),
+    };
+
+    let (intent, onchain_id) = match load_intent(&pool, intent_id).await {
+        Ok(Some((intent, onchain_id))) if intent.user_id == user.id => (intent, onchain_id),
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Intention non trouvée"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    };
+
+    if intent.signature.is_some() {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Cette intention est déjà signée"
+        }))).into_response();
+    }
+    if intent.deadline < Utc::now() {
+        return (StatusCode::GONE, Json(serde_json::json!({
+            "error": "Cette intention a expiré, veuillez en créer une nouvelle"
+        }))).into_response();
+    }
+
+    // Le digest est recalculé plutôt que relu, pour refléter le domaine courant
+    let digest = match message_for(&intent, &onchain_id) {
+        Some(message) => message.digest(&domain),
+        None => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": "Intention corrompue"
+        }))).into_response(),
+    };
+    match eip712::recover_signer(&digest, &payload.signature) {
+        Ok(signer) if eip712::format_address(&signer) == intent.investor => {},
+        Ok(signer) => return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({
+            "error": format!("Signature émise par {} au lieu de {}", eip712::format_address(&signer), intent.investor)
+        }))).into_response(),
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e
+        }))).into_response(),
+    }
+
+    match sqlx::query_as!(
+        InvestmentIntent,
+        r#"UPDATE investment_intents SET signature = $2, signed_at = NOW()
+           WHERE id = $1 AND signature IS NULL
+           RETURNING id, user_id, property_id, investor, shares, price_per_share_wei as "price_per_share_wei: Wei", amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei",
+                     nonce, deadline, digest, signature, signed_at, investment_id, created_at"#,
+        intent_id,
+        payload.signature.trim().to_lowercase()
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(intent)) => (StatusCode::OK, Json(serde_json::json!({
+            "intent": intent_json(&intent, None),
+            "message": "Intention signée avec succès"
+        }))).into_response(),
+        Ok(None) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Cette intention est déjà signée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement: {}", e)
+        }))).into_response(),
+    }
+}
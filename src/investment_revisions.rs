@@ -0,0 +1,84 @@
+// investment_revisions.rs
+//
+// Historique des modifications des investissements. Chaque changement de
+// montant, de parts, de hash de transaction ou de statut est enregistré avec
+// son auteur et son motif. Une fois réglé on-chain (`settled`), un
+// investissement n'est plus modifiable que par un admin, avec un motif
+// obligatoire ; remboursé, il ne l'est plus du tout (voir routes::update_investment).
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{InvestmentRevision, UserRole};
+
+/// Enregistre une modification (`changes` : {"champ": {"from": ..., "to": ...}})
+pub async fn record<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    investment_id: Uuid,
+    changed_by: Option<Uuid>,
+    reason: Option<&str>,
+    changes: Value,
+) -> Result<Uuid, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"INSERT INTO investment_revisions (investment_id, changed_by, reason, changes)
+           VALUES ($1, $2, $3, $4)
+           RETURNING id"#,
+        investment_id,
+        changed_by,
+        reason,
+        changes
+    )
+    .fetch_one(executor)
+    .await
+}
+
+/// Route : historique des modifications d'un investissement (propriétaire ou admin)
+pub async fn get_investment_revisions(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(investment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let owner = match sqlx::query_scalar!(
+        "SELECT user_id FROM investments WHERE id = $1 AND tenant_id = $2",
+        investment_id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(owner)) => owner,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Investissement non trouvé"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    if !matches!(user.role, UserRole::Admin) && owner != user.id {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin ou le propriétaire peut consulter l'historique de cet investissement"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        InvestmentRevision,
+        "SELECT * FROM investment_revisions WHERE investment_id = $1 ORDER BY created_at DESC",
+        investment_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(revisions) => envelope::list("revisions", &revisions).with("investment_id", investment_id).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
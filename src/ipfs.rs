@@ -0,0 +1,77 @@
+// ipfs.rs
+//
+// Épinglage optionnel des documents envoyés sur IPFS via un service de pinning
+// (API `pinFileToIPFS` de Pinata ou `/api/v0/add` d'un nœud Kubo). Le CID
+// obtenu est immuable : les métadonnées on-chain peuvent y faire référence.
+
+use reqwest::multipart::{Form, Part};
+use std::env;
+use std::path::Path;
+use std::time::Duration;
+use tokio_util::io::ReaderStream;
+
+/// Configuration du service de pinning, lue depuis l'environnement
+#[derive(Clone)]
+pub struct IpfsConfig {
+    pin_endpoint: String,
+    pin_token: Option<String>,
+    gateway_url: String,
+    client: reqwest::Client,
+}
+
+impl IpfsConfig {
+    /// Renvoie `None` si l'épinglage n'est pas activé
+    pub fn from_env() -> Option<Self> {
+        let pin_endpoint = env::var("IPFS_PIN_ENDPOINT").ok().filter(|v| !v.trim().is_empty())?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .ok()?;
+
+        Some(Self {
+            pin_endpoint,
+            pin_token: env::var("IPFS_PIN_TOKEN").ok().filter(|v| !v.trim().is_empty()),
+            gateway_url: env::var("IPFS_GATEWAY_URL")
+                .map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or_else(|_| "https://ipfs.io/ipfs".to_string()),
+            client,
+        })
+    }
+
+    /// URL de consultation d'un CID via la passerelle HTTP
+    pub fn gateway_url(&self, cid: &str) -> String {
+        format!("{}/{}", self.gateway_url, cid)
+    }
+
+    /// Envoie le fichier au service de pinning et renvoie son CID
+    pub async fn pin_file(&self, file: &Path, file_name: &str, content_type: &str) -> Result<String, String> {
+        let file = tokio::fs::File::open(file).await.map_err(|e| e.to_string())?;
+        let length = file.metadata().await.map_err(|e| e.to_string())?.len();
+
+        // Envoi en flux, comme pour le dépôt dans le bucket
+        let part = Part::stream_with_length(reqwest::Body::wrap_stream(ReaderStream::new(file)), length)
+            .file_name(file_name.to_string())
+            .mime_str(content_type)
+            .map_err(|e| e.to_string())?;
+
+        let mut request = self.client
+            .post(&self.pin_endpoint)
+            .multipart(Form::new().part("file", part));
+        if let Some(token) = &self.pin_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("le service de pinning a répondu {}", response.status()));
+        }
+
+        let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+        // Pinata renvoie `IpfsHash`, Kubo `Hash`
+        body.get("IpfsHash")
+            .or_else(|| body.get("Hash"))
+            .and_then(|cid| cid.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| "CID absent de la réponse du service de pinning".to_string())
+    }
+}
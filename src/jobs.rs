@@ -0,0 +1,313 @@
+// jobs.rs
+//
+// File de tâches durable dans Postgres (`jobs`), consommée par les workers
+// (voir worker.rs) avec `FOR UPDATE SKIP LOCKED` : une tâche n'est prise que
+// par un worker à la fois. Une tâche en échec est retentée avec un délai
+// exponentiel (`JOBS_RETRY_BASE_SECS`, 30s, doublé à chaque tentative jusqu'à
+// `JOBS_RETRY_MAX_SECS`, 1h) ; après `JOBS_MAX_ATTEMPTS` tentatives (5) elle
+// passe en `dead`, visible par `GET /api/admin/jobs/failed` et relançable par
+// un admin. Chaque type de tâche (`kind`) a son exécutant dans `execute`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::Value;
+use sqlx::postgres::PgExecutor;
+use sqlx::{PgPool, QueryBuilder};
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::backups;
+use crate::envelope;
+use crate::error_codes::ErrorCode;
+use crate::geocoding::{self, Geocoder};
+use crate::models::{Job, JobStatus, UserRole};
+use crate::pagination::{Cursor, Pagination};
+use crate::query_builder::{Filter, FilterKind, FilterOp, ListSpec, SortDirection};
+use crate::reports;
+use crate::storage::StorageConfig;
+use crate::worker;
+
+/// Génération d'un rapport (`{"report_id": ...}`, voir reports.rs)
+pub const GENERATE_REPORT: &str = "generate_report";
+
+/// Export d'une sauvegarde logique (`{"backup_id": ...}`, voir backups.rs)
+pub const EXPORT_BACKUP: &str = "export_backup";
+
+/// Géocodage de la localisation d'une propriété (`{"property_id": ...}`, voir geocoding.rs)
+pub const GEOCODE_PROPERTY: &str = "geocode_property";
+
+/// Une tâche en cours depuis plus longtemps est considérée comme interrompue
+/// (worker arrêté) et reprise, la tentative étant comptée
+const STALE_RUNNING_SECS: f64 = 1800.0;
+
+/// Filtres et tri des tâches en échec définitif
+static FAILED_JOB_LIST: ListSpec = ListSpec {
+    sorts: &[("created_at", "created_at"), ("updated_at", "updated_at"), ("kind", "kind")],
+    default_sort: ("created_at", SortDirection::Desc),
+    filters: &[
+        Filter { param: "kind", column: "kind", op: FilterOp::Eq, kind: FilterKind::Text },
+    ],
+    passthrough: &[],
+};
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).filter(|v| *v > 0).unwrap_or(default)
+}
+
+/// Met une tâche en file ; dans une transaction, elle n'est visible des
+/// workers qu'à la validation
+pub async fn enqueue<'e>(db: impl PgExecutor<'e>, kind: &str, payload: Value) -> Result<Uuid, sqlx::Error> {
+    sqlx::query_scalar!(
+        "INSERT INTO jobs (kind, payload, max_attempts) VALUES ($1, $2, $3) RETURNING id",
+        kind,
+        payload,
+        env_u64("JOBS_MAX_ATTEMPTS", 5) as i32
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Exécutant des tâches d'un worker, avec les services dont elles ont besoin
+#[derive(Clone)]
+pub struct JobRunner {
+    storage: Option<StorageConfig>,
+    geocoder: Option<Geocoder>,
+    retry_base_secs: u64,
+    retry_max_secs: u64,
+}
+
+impl JobRunner {
+    pub fn from_env(storage: Option<StorageConfig>, geocoder: Option<Geocoder>) -> Self {
+        Self {
+            storage,
+            geocoder,
+            retry_base_secs: env_u64("JOBS_RETRY_BASE_SECS", 30),
+            retry_max_secs: env_u64("JOBS_RETRY_MAX_SECS", 3600),
+        }
+    }
+
+    /// Lance la consommation de la file. Intervalle configurable via
+    /// `JOBS_INTERVAL_SECS` (2s par défaut).
+    pub fn spawn(self, pool: PgPool) {
+        let interval_secs = env_u64("JOBS_INTERVAL_SECS", 2);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+            loop {
+                interval.tick().await;
+                // Vide la file avant d'attendre le prochain tour
+                loop {
+                    match self.run_next(&pool).await {
+                        Ok(true) => continue,
+                        Ok(false) => break,
+                        Err(e) => {
+                            tracing::error!("Erreur de la file de tâches: {}", e);
+                            break;
+                        },
+                    }
+                }
+            }
+        });
+    }
+
+    /// Exécute la plus ancienne tâche due ; renvoie `false` si la file est vide
+    async fn run_next(&self, pool: &PgPool) -> Result<bool, sqlx::Error> {
+        let job = sqlx::query_as!(
+            Job,
+            r#"UPDATE jobs SET status = 'running', attempts = attempts + 1, locked_by = $2,
+                   locked_at = NOW(), updated_at = NOW()
+               WHERE id = (
+                   SELECT id FROM jobs
+                   WHERE (status = 'queued' AND run_at <= NOW())
+                      OR (status = 'running' AND locked_at < NOW() - make_interval(secs => $1))
+                   ORDER BY run_at
+                   LIMIT 1
+                   FOR UPDATE SKIP LOCKED
+               )
+               RETURNING id, kind, payload, status as "status: JobStatus", attempts, max_attempts, run_at,
+                         locked_by, locked_at, last_error, created_at, updated_at, completed_at"#,
+            STALE_RUNNING_SECS,
+            worker::worker_id()
+        )
+        .fetch_optional(pool)
+        .await?;
+        let job = match job {
+            Some(job) => job,
+            None => return Ok(false),
+        };
+
+        match self.execute(pool, &job).await {
+            Ok(()) => {
+                sqlx::query!(
+                    r#"UPDATE jobs SET status = 'succeeded', locked_by = NULL, last_error = NULL,
+                       completed_at = NOW(), updated_at = NOW()
+                       WHERE id = $1"#,
+                    job.id
+                )
+                .execute(pool)
+                .await?;
+            },
+            Err(e) if job.attempts >= job.max_attempts => {
+                tracing::error!(job_id = %job.id, kind = %job.kind, attempts = job.attempts, "Tâche abandonnée: {}", e);
+                sqlx::query!(
+                    r#"UPDATE jobs SET status = 'dead', locked_by = NULL, last_error = $2,
+                       completed_at = NOW(), updated_at = NOW()
+                       WHERE id = $1"#,
+                    job.id,
+                    e
+                )
+                .execute(pool)
+                .await?;
+            },
+            Err(e) => {
+                let delay = self.retry_base_secs
+                    .saturating_mul(1u64 << (job.attempts - 1).clamp(0, 30))
+                    .min(self.retry_max_secs);
+                tracing::warn!(job_id = %job.id, kind = %job.kind, attempts = job.attempts, delay, "Tâche en échec, nouvel essai prévu: {}", e);
+                sqlx::query!(
+                    r#"UPDATE jobs SET status = 'queued', locked_by = NULL, last_error = $2,
+                       run_at = NOW() + make_interval(secs => $3), updated_at = NOW()
+                       WHERE id = $1"#,
+                    job.id,
+                    e,
+                    delay as f64
+                )
+                .execute(pool)
+                .await?;
+            },
+        }
+        Ok(true)
+    }
+
+    async fn execute(&self, pool: &PgPool, job: &Job) -> Result<(), String> {
+        match job.kind.as_str() {
+            GENERATE_REPORT => {
+                let storage = self.storage.as_ref().ok_or("Stockage des documents non configuré")?;
+                reports::run_job(pool, storage, job).await
+            },
+            EXPORT_BACKUP => {
+                let storage = self.storage.as_ref().ok_or("Stockage des documents non configuré")?;
+                backups::run_job(pool, storage, job).await
+            },
+            GEOCODE_PROPERTY => {
+                let geocoder = self.geocoder.as_ref().ok_or("Géocodage non configuré")?;
+                geocoding::run_job(pool, geocoder, job).await
+            },
+            kind => Err(format!("Type de tâche inconnu: {}", kind)),
+        }
+    }
+}
+
+fn admin_only(role: &UserRole) -> Option<axum::response::Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer la file de tâches",
+        "code": ErrorCode::AdminRequired
+    }))).into_response())
+}
+
+/// Route admin : tâches en échec définitif (`?kind=` pour filtrer)
+pub async fn get_failed_jobs(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<HashMap<String, String>>,
+    pagination: Pagination,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    let list = match FAILED_JOB_LIST.parse(&params, pagination) {
+        Ok(list) => list,
+        Err(e) => return (e.code.status(), Json(serde_json::json!({
+            "error": e.message,
+            "code": e.code
+        }))).into_response(),
+    };
+
+    let mut builder = QueryBuilder::new(
+        "SELECT id, kind, payload, status, attempts, max_attempts, run_at, locked_by, locked_at, last_error, \
+         created_at, updated_at, completed_at \
+         FROM jobs WHERE status = 'dead'"
+    );
+    list.push_filters(&mut builder);
+    list.push_order_and_page(&mut builder);
+    match builder.build_query_as::<Job>().fetch_all(&pool).await {
+        Ok(mut jobs) => {
+            let page = pagination.finish(&mut jobs, |job| Cursor::new(job.created_at, job.id));
+            envelope::list("jobs", &jobs).paginated(page).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
+
+/// Route admin : relancer une tâche en échec définitif, avec toutes ses tentatives
+pub async fn retry_job(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(job_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    let status = match sqlx::query_scalar!(
+        r#"SELECT status as "status: JobStatus" FROM jobs WHERE id = $1"#,
+        job_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(status)) => status,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Tâche non trouvée",
+            "code": ErrorCode::JobNotFound
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    };
+    if status != JobStatus::Dead {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Seule une tâche en échec définitif peut être relancée",
+            "code": ErrorCode::JobNotFailed
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        Job,
+        r#"UPDATE jobs SET status = 'queued', attempts = 0, run_at = NOW(), completed_at = NULL, updated_at = NOW()
+           WHERE id = $1 AND status = 'dead'
+           RETURNING id, kind, payload, status as "status: JobStatus", attempts, max_attempts, run_at,
+                     locked_by, locked_at, last_error, created_at, updated_at, completed_at"#,
+        job_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(job)) => {
+            tracing::info!(job_id = %job.id, kind = %job.kind, admin_id = %user.id, "Tâche relancée");
+            Json(serde_json::json!({
+                "job": job,
+                "message": "Tâche remise en file"
+            })).into_response()
+        },
+        // Relancée entre-temps par un autre admin
+        Ok(None) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Seule une tâche en échec définitif peut être relancée",
+            "code": ErrorCode::JobNotFailed
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la relance: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
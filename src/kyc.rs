@@ -0,0 +1,468 @@
+// kyc.rs
+//
+// Vérification d'identité (KYC) via Sumsub : le backend crée le dossier
+// (applicant) chez le prestataire et fournit au frontend le jeton du SDK Web,
+// puis les webhooks signés du prestataire mettent à jour le statut. Un admin
+// peut à tout moment imposer une décision manuelle.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::{Sha256, Sha512};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{KycListQuery, KycOverrideRequest, KycStatus, KycVerification, UserRole};
+use crate::notifications::notify;
+use crate::outbound::{Outbound, OutboundClient, OutboundError};
+
+/// Durée de validité du jeton du SDK
+const SDK_TOKEN_TTL_SECS: u64 = 600;
+
+/// Identifiants de l'API Sumsub, lus depuis l'environnement
+#[derive(Clone)]
+pub struct KycProvider {
+    app_token: String,
+    secret_key: String,
+    webhook_secret: String,
+    level_name: String,
+    api_base: String,
+    client: OutboundClient,
+}
+
+impl KycProvider {
+    /// Renvoie `None` si le jeton d'application, la clé secrète ou le secret
+    /// des webhooks manque
+    pub fn from_env(outbound: &Outbound) -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+        Some(Self {
+            app_token: var("SUMSUB_APP_TOKEN")?,
+            secret_key: var("SUMSUB_SECRET_KEY")?,
+            webhook_secret: var("SUMSUB_WEBHOOK_SECRET")?,
+            level_name: var("SUMSUB_LEVEL_NAME").unwrap_or_else(|| "basic-kyc-level".to_string()),
+            api_base: var("SUMSUB_API_BASE")
+                .map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| "https://api.sumsub.com".to_string()),
+            client: outbound.client("kyc")?,
+        })
+    }
+
+    /// Appel signé : HMAC-SHA256(horodatage ‖ méthode ‖ chemin ‖ corps).
+    /// Renvoie le statut HTTP et le corps JSON de la réponse.
+    async fn call(&self, method: reqwest::Method, path: &str, body: Option<Value>) -> Result<(u16, Value), OutboundError> {
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        let ts = chrono::Utc::now().timestamp().to_string();
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes()).expect("HMAC accepte toute taille de clé");
+        mac.update(ts.as_bytes());
+        mac.update(method.as_str().as_bytes());
+        mac.update(path.as_bytes());
+        mac.update(body.as_bytes());
+
+        let mut request = self.client
+            .request(method, format!("{}{}", self.api_base, path))
+            .header("X-App-Token", &self.app_token)
+            .header("X-App-Access-Ts", ts)
+            .header("X-App-Access-Sig", hex::encode(mac.finalize().into_bytes()));
+        if !body.is_empty() {
+            request = request.header(reqwest::header::CONTENT_TYPE, "application/json").body(body);
+        }
+
+        let response = self.client.send(request).await?;
+        let status = response.status().as_u16();
+        let json = response.json().await.map_err(|e| e.to_string())?;
+        Ok((status, json))
+    }
+
+    /// Crée le dossier de l'utilisateur (ou retrouve celui déjà créé) et renvoie
+    /// son identifiant chez le prestataire
+    async fn create_applicant(&self, user_id: Uuid) -> Result<String, OutboundError> {
+        let path = format!("/resources/applicants?levelName={}", self.level_name);
+        let (status, body) = self.call(reqwest::Method::POST, &path, Some(serde_json::json!({
+            "externalUserId": user_id.to_string()
+        }))).await?;
+
+        let body = match status {
+            200..=299 => body,
+            // Dossier déjà existant pour cet externalUserId
+            409 => self.call(reqwest::Method::GET, &format!("/resources/applicants/-;externalUserId={}/one", user_id), None).await?.1,
+            _ => return Err(body["description"].as_str().unwrap_or("réponse invalide").to_string().into()),
+        };
+        body["id"].as_str().map(str::to_string).ok_or_else(|| "Dossier sans identifiant".to_string().into())
+    }
+
+    /// Jeton d'accès du SDK Web pour l'utilisateur
+    async fn sdk_token(&self, user_id: Uuid) -> Result<String, OutboundError> {
+        let (status, body) = self.call(reqwest::Method::POST, "/resources/accessTokens/sdk", Some(serde_json::json!({
+            "userId": user_id.to_string(),
+            "levelName": self.level_name,
+            "ttlInSecs": SDK_TOKEN_TTL_SECS
+        }))).await?;
+        match (status, body["token"].as_str()) {
+            (200..=299, Some(token)) => Ok(token.to_string()),
+            _ => Err(body["description"].as_str().unwrap_or("réponse invalide").to_string().into()),
+        }
+    }
+}
+
+/// Vérifie l'en-tête `X-Payload-Digest` : HMAC hexadécimal du corps brut avec
+/// le secret des webhooks (SHA-256 par défaut, SHA-512 si annoncé)
+fn verify_digest(secret: &str, body: &[u8], digest: &str, algorithm: Option<&str>) -> bool {
+    let expected = match hex::decode(digest.trim()) {
+        Ok(expected) => expected,
+        Err(_) => return false,
+    };
+    match algorithm.unwrap_or("HMAC_SHA256_HEX") {
+        "HMAC_SHA256_HEX" => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepte toute taille de clé");
+            mac.update(body);
+            mac.verify_slice(&expected).is_ok()
+        },
+        "HMAC_SHA512_HEX" => {
+            let mut mac = Hmac::<Sha512>::new_from_slice(secret.as_bytes()).expect("HMAC accepte toute taille de clé");
+            mac.update(body);
+            mac.verify_slice(&expected).is_ok()
+        },
+        _ => false,
+    }
+}
+
+/// Statut correspondant à un événement du prestataire (`None` : événement ignoré)
+fn status_for_event(event: &Value) -> Option<KycStatus> {
+    match event["type"].as_str()? {
+        "applicantReset" => Some(KycStatus::NotStarted),
+        "applicantPending" | "applicantOnHold" => Some(KycStatus::Pending),
+        "applicantReviewed" => match (
+            event["reviewResult"]["reviewAnswer"].as_str()?,
+            event["reviewResult"]["reviewRejectType"].as_str(),
+        ) {
+            ("GREEN", _) => Some(KycStatus::Approved),
+            ("RED", Some("RETRY")) => Some(KycStatus::ResubmissionRequested),
+            ("RED", _) => Some(KycStatus::Rejected),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn status_message(status: KycStatus) -> &'static str {
+    match status {
+        KycStatus::NotStarted => "Votre vérification d'identité a été réinitialisée",
+        KycStatus::Pending => "Vos documents d'identité sont en cours d'examen",
+        KycStatus::Approved => "Votre identité a été vérifiée",
+        KycStatus::Rejected => "Votre vérification d'identité a été refusée",
+        KycStatus::ResubmissionRequested => "Des documents d'identité complémentaires sont nécessaires",
+    }
+}
+
+async fn load_verification(pool: &PgPool, user_id: Uuid) -> Result<Option<KycVerification>, sqlx::Error> {
+    sqlx::query_as!(
+        KycVerification,
+        r#"SELECT user_id, provider, applicant_id, status as "status: KycStatus", review_answer, reject_labels,
+                  moderation_comment, reviewed_at, overridden_by, override_reason, created_at, updated_at
+           FROM kyc_verifications WHERE user_id = $1"#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Route pour consulter son statut KYC
+pub async fn get_my_kyc(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match load_verification(&pool, user.id).await {
+        Ok(verification) => (StatusCode::OK, Json(serde_json::json!({
+            "status": verification.as_ref().map(|v| v.status).unwrap_or(KycStatus::NotStarted),
+            "verification": verification
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Repli proposé quand le prestataire est indisponible : l'état connu de la
+/// vérification, consultable via `GET /api/kyc`
+fn verification_fallback(verification: &Option<KycVerification>) -> Value {
+    serde_json::json!({
+        "verification": verification,
+        "message": "Vérification d'identité momentanément indisponible : réessayez plus tard"
+    })
+}
+
+/// Route pour démarrer (ou reprendre) sa vérification : crée le dossier chez
+/// le prestataire si besoin et renvoie le jeton du SDK Web
+pub async fn create_kyc_session(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(provider): Extension<Option<KycProvider>>,
+) -> impl IntoResponse {
+    let provider = match provider {
+        Some(provider) => provider,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Prestataire KYC non configuré (SUMSUB_APP_TOKEN, SUMSUB_SECRET_KEY, SUMSUB_WEBHOOK_SECRET)"
+        }))).into_response(),
+    };
+
+    let existing = match load_verification(&pool, user.id).await {
+        Ok(existing) => existing,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    };
+
+    if existing.as_ref().and_then(|v| v.applicant_id.as_ref()).is_none() {
+        let applicant_id = match provider.create_applicant(user.id).await {
+            Ok(applicant_id) => applicant_id,
+            Err(e) => return e.response("Erreur lors de la création du dossier KYC", Some(verification_fallback(&existing))),
+        };
+        // Une décision manuelle antérieure est conservée
+        if let Err(e) = sqlx::query!(
+            r#"INSERT INTO kyc_verifications (user_id, provider, applicant_id) VALUES ($1, 'sumsub', $2)
+               ON CONFLICT (user_id) DO UPDATE SET provider = 'sumsub', applicant_id = $2, updated_at = NOW()"#,
+            user.id,
+            applicant_id
+        )
+        .execute(&pool)
+        .await
+        {
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Erreur lors de l'enregistrement du dossier KYC: {}", e)
+            }))).into_response();
+        }
+    }
+
+    let token = match provider.sdk_token(user.id).await {
+        Ok(token) => token,
+        Err(e) => {
+            let existing = load_verification(&pool, user.id).await.ok().flatten();
+            return e.response("Erreur lors de la création du jeton KYC", Some(verification_fallback(&existing)));
+        },
+    };
+
+    match load_verification(&pool, user.id).await {
+        Ok(verification) => (StatusCode::OK, Json(serde_json::json!({
+            "provider": "sumsub",
+            "level_name": provider.level_name,
+            "access_token": token,
+            "expires_in": SDK_TOKEN_TTL_SECS,
+            "verification": verification
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route publique : événements du prestataire, authentifiés par `X-Payload-Digest`
+pub async fn kyc_webhook(
+    State(pool): State<PgPool>,
+    Extension(provider): Extension<Option<KycProvider>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let provider = match provider {
+        Some(provider) => provider,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Prestataire KYC non configuré"
+        }))).into_response(),
+    };
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let verified = header("x-payload-digest")
+        .is_some_and(|digest| verify_digest(&provider.webhook_secret, &body, digest, header("x-payload-digest-alg")));
+    if !verified {
+        return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({
+            "error": "Signature du webhook invalide"
+        }))).into_response();
+    }
+
+    let event: Value = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Corps JSON invalide: {}", e)
+        }))).into_response(),
+    };
+    let event_type = event["type"].as_str().unwrap_or_default().to_string();
+    let status = match status_for_event(&event) {
+        Some(status) => status,
+        // Les autres événements sont acquittés sans traitement
+        None => return (StatusCode::OK, Json(serde_json::json!({
+            "event": event_type,
+            "ignored": true
+        }))).into_response(),
+    };
+
+    let review = &event["reviewResult"];
+    let reject_labels: Vec<String> = review["rejectLabels"].as_array()
+        .map(|labels| labels.iter().filter_map(|l| l.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    let reviewed = event_type == "applicantReviewed";
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let previous = sqlx::query!(
+            r#"SELECT user_id, status as "status: KycStatus" FROM kyc_verifications
+               WHERE applicant_id = $1 FOR UPDATE"#,
+            event["applicantId"].as_str().unwrap_or_default()
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        let previous = match previous {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+
+        // La décision du prestataire remplace une éventuelle décision manuelle
+        sqlx::query!(
+            r#"UPDATE kyc_verifications
+               SET status = $2,
+                   review_answer = CASE WHEN $3 THEN $4 ELSE review_answer END,
+                   reject_labels = CASE WHEN $3 THEN $5 ELSE reject_labels END,
+                   moderation_comment = CASE WHEN $3 THEN $6 ELSE moderation_comment END,
+                   reviewed_at = CASE WHEN $3 THEN NOW() ELSE reviewed_at END,
+                   overridden_by = NULL, override_reason = NULL, updated_at = NOW()
+               WHERE user_id = $1"#,
+            previous.user_id,
+            status as KycStatus,
+            reviewed,
+            review["reviewAnswer"].as_str(),
+            &reject_labels,
+            review["moderationComment"].as_str()
+        )
+        .execute(&mut tx)
+        .await?;
+
+        if previous.status != status {
+            notify(&mut tx, previous.user_id, "kyc_updated", status_message(status), serde_json::json!({
+                "status": status,
+                "reject_labels": reject_labels
+            }))
+            .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(previous.user_id))
+    }
+    .await;
+
+    match result {
+        Ok(user_id) => (StatusCode::OK, Json(serde_json::json!({
+            "event": event_type,
+            "status": status,
+            "user_id": user_id
+        }))).into_response(),
+        // Le prestataire relivre l'événement tant que la réponse n'est pas un succès
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour du statut KYC: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : dossiers KYC, les plus récemment mis à jour d'abord (`?status=`)
+pub async fn get_kyc_verifications(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<KycListQuery>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent consulter les dossiers KYC"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        KycVerification,
+        r#"SELECT user_id, provider, applicant_id, status as "status: KycStatus", review_answer, reject_labels,
+                  moderation_comment, reviewed_at, overridden_by, override_reason, created_at, updated_at
+           FROM kyc_verifications
+           WHERE ($1::kyc_status IS NULL OR status = $1)
+           AND user_id IN (SELECT id FROM users WHERE tenant_id = $2)
+           ORDER BY updated_at DESC"#,
+        query.status as Option<KycStatus>,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(verifications) => envelope::list("verifications", &verifications).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : impose le statut KYC d'un utilisateur (motif obligatoire)
+pub async fn override_kyc(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<KycOverrideRequest>,
+) -> impl IntoResponse {
+    if !matches!(admin.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent modifier un statut KYC"
+        }))).into_response();
+    }
+    let reason = payload.reason.trim();
+    if reason.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le motif de la décision est obligatoire"
+        }))).into_response();
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let exists = sqlx::query_scalar!("SELECT 1 FROM users WHERE id = $1 AND tenant_id = $2", user_id, admin.tenant_id)
+            .fetch_optional(&mut tx)
+            .await?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+        let verification = sqlx::query_as!(
+            KycVerification,
+            r#"INSERT INTO kyc_verifications (user_id, provider, status, overridden_by, override_reason)
+               VALUES ($1, 'manual', $2, $3, $4)
+               ON CONFLICT (user_id) DO UPDATE
+               SET status = $2, overridden_by = $3, override_reason = $4, updated_at = NOW()
+               RETURNING user_id, provider, applicant_id, status as "status: KycStatus", review_answer, reject_labels,
+                         moderation_comment, reviewed_at, overridden_by, override_reason, created_at, updated_at"#,
+            user_id,
+            payload.status as KycStatus,
+            admin.id,
+            reason
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        notify(&mut tx, user_id, "kyc_updated", status_message(verification.status), serde_json::json!({
+            "status": verification.status
+        }))
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Some(verification))
+    }
+    .await;
+
+    match result {
+        Ok(Some(verification)) => (StatusCode::OK, Json(serde_json::json!({
+            "verification": verification,
+            "message": "Statut KYC mis à jour"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Utilisateur non trouvé"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,244 @@
+// legal.rs
+//
+// Documents légaux versionnés (CGU, avertissement sur les risques) : un admin
+// publie une nouvelle version, qui devient celle en vigueur pour son type.
+// Un utilisateur doit avoir accepté toutes les versions en vigueur avant de
+// pouvoir investir ; chaque acceptation est horodatée avec l'IP du client.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{AcceptTermsRequest, CreateLegalDocumentRequest, LegalDocument, LegalDocumentKind, UserRole};
+use crate::risk::client_ip;
+use crate::tags::is_unique_violation;
+
+/// Version en vigueur d'un document et son acceptation par l'utilisateur
+#[derive(Debug, Serialize)]
+pub struct TermsStatus {
+    pub id: Uuid,
+    pub kind: LegalDocumentKind,
+    pub version: String,
+    pub title: String,
+    pub published_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+}
+
+/// Version d'un document avec son nombre d'acceptations (vue admin)
+#[derive(Debug, Serialize)]
+pub struct LegalDocumentSummary {
+    pub id: Uuid,
+    pub kind: LegalDocumentKind,
+    pub version: String,
+    pub title: String,
+    pub created_by: Option<Uuid>,
+    pub published_at: DateTime<Utc>,
+    pub acceptances: i64,
+}
+
+async fn terms_status(pool: &PgPool, user_id: Uuid) -> Result<Vec<TermsStatus>, sqlx::Error> {
+    sqlx::query_as!(
+        TermsStatus,
+        r#"SELECT d.id, d.kind as "kind: LegalDocumentKind", d.version, d.title, d.published_at,
+                  a.accepted_at as "accepted_at?"
+           FROM (SELECT DISTINCT ON (kind) id, kind, version, title, published_at
+                 FROM legal_documents ORDER BY kind, published_at DESC) d
+           LEFT JOIN user_acceptances a ON a.document_id = d.id AND a.user_id = $1
+           ORDER BY d.kind"#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Refuse (403) toute création d'investissement tant que l'utilisateur n'a pas
+/// accepté les versions en vigueur des documents légaux
+pub async fn ensure_terms_accepted(pool: &PgPool, user_id: Uuid) -> Result<(), Response> {
+    let documents = terms_status(pool, user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification des conditions: {}", e)
+        }))).into_response())?;
+
+    let pending: Vec<_> = documents.into_iter().filter(|doc| doc.accepted_at.is_none()).collect();
+    if !pending.is_empty() {
+        return Err((StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Vous devez accepter les documents légaux en vigueur avant d'investir (POST /api/me/accept-terms)",
+            "pending_documents": pending
+        }))).into_response());
+    }
+    Ok(())
+}
+
+/// Route publique : versions en vigueur des documents légaux
+pub async fn get_current_legal_documents(State(pool): State<PgPool>) -> impl IntoResponse {
+    match sqlx::query_as!(
+        LegalDocument,
+        r#"SELECT DISTINCT ON (kind) id, kind as "kind: LegalDocumentKind", version, title, content,
+                  created_by, published_at
+           FROM legal_documents
+           ORDER BY kind, published_at DESC"#
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(documents) => envelope::list("documents", &documents).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour consulter l'état de ses acceptations (`all_accepted` conditionne
+/// la création d'investissements)
+pub async fn get_my_terms(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match terms_status(&pool, user.id).await {
+        Ok(documents) => (StatusCode::OK, Json(serde_json::json!({
+            "all_accepted": documents.iter().all(|doc| doc.accepted_at.is_some()),
+            "documents": documents
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour accepter les versions en vigueur (toutes, ou celles de `document_ids`)
+pub async fn accept_terms(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+    Json(payload): Json<AcceptTermsRequest>,
+) -> impl IntoResponse {
+    let current = match terms_status(&pool, user.id).await {
+        Ok(current) => current,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    };
+
+    let document_ids: Vec<Uuid> = match payload.document_ids {
+        Some(ids) => {
+            if ids.is_empty() || ids.iter().any(|id| !current.iter().any(|doc| doc.id == *id)) {
+                return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                    "error": "Seules les versions en vigueur des documents peuvent être acceptées"
+                }))).into_response();
+            }
+            ids
+        },
+        None => current.iter().map(|doc| doc.id).collect(),
+    };
+
+    let user_agent = headers.get("user-agent").and_then(|v| v.to_str().ok());
+    if let Err(e) = sqlx::query!(
+        r#"INSERT INTO user_acceptances (user_id, document_id, ip, user_agent)
+           SELECT $1, document_id, $3, $4 FROM UNNEST($2::uuid[]) AS document_id
+           ON CONFLICT (user_id, document_id) DO NOTHING"#,
+        user.id,
+        &document_ids,
+        client_ip(&headers),
+        user_agent
+    )
+    .execute(&pool)
+    .await {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement: {}", e)
+        }))).into_response();
+    }
+
+    match terms_status(&pool, user.id).await {
+        Ok(documents) => (StatusCode::OK, Json(serde_json::json!({
+            "all_accepted": documents.iter().all(|doc| doc.accepted_at.is_some()),
+            "documents": documents,
+            "message": "Conditions acceptées"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : toutes les versions des documents, avec leur nombre d'acceptations
+pub async fn get_legal_documents(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent gérer les documents légaux"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        LegalDocumentSummary,
+        r#"SELECT d.id, d.kind as "kind: LegalDocumentKind", d.version, d.title, d.created_by, d.published_at,
+                  COUNT(a.id) as "acceptances!"
+           FROM legal_documents d
+           LEFT JOIN user_acceptances a ON a.document_id = d.id
+           GROUP BY d.id
+           ORDER BY d.kind, d.published_at DESC"#
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(documents) => envelope::list("documents", &documents).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : publier une nouvelle version d'un document, qui devient celle
+/// en vigueur (les utilisateurs doivent l'accepter avant leur prochain investissement)
+pub async fn publish_legal_document(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateLegalDocumentRequest>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent gérer les documents légaux"
+        }))).into_response();
+    }
+    let (version, title, content) = (payload.version.trim(), payload.title.trim(), payload.content.trim());
+    if version.is_empty() || title.is_empty() || content.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "La version, le titre et le contenu sont requis"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        LegalDocument,
+        r#"INSERT INTO legal_documents (kind, version, title, content, created_by)
+           VALUES ($1, $2, $3, $4, $5)
+           RETURNING id, kind as "kind: LegalDocumentKind", version, title, content, created_by, published_at"#,
+        payload.kind as LegalDocumentKind,
+        version,
+        title,
+        content,
+        user.id
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(document) => (StatusCode::CREATED, Json(serde_json::json!({
+            "document": document,
+            "message": "Nouvelle version publiée"
+        }))).into_response(),
+        Err(e) if is_unique_violation(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Cette version existe déjà pour ce document"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la publication: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,71 @@
+// mailer.rs
+//
+// Service d'envoi d'e-mails via l'API HTTP d'un prestataire transactionnel
+// (format Resend : POST JSON `from`, `to`, `subject`, `text`, `html`,
+// `headers`, authentifié par `Authorization: Bearer`). Optionnel : sans
+// `EMAIL_API_KEY` ni `EMAIL_FROM`, aucun e-mail n'est envoyé.
+
+use std::env;
+
+use crate::outbound::{Outbound, OutboundClient, OutboundError};
+
+/// Message prêt à l'envoi
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+    /// Lien de désinscription (en-têtes List-Unsubscribe)
+    pub unsubscribe_url: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Mailer {
+    api_url: String,
+    api_key: String,
+    from: String,
+    client: OutboundClient,
+}
+
+impl Mailer {
+    /// Renvoie `None` si la clé d'API ou l'expéditeur manque
+    pub fn from_env(outbound: &Outbound) -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+        Some(Self {
+            api_url: var("EMAIL_API_URL").unwrap_or_else(|| "https://api.resend.com/emails".to_string()),
+            api_key: var("EMAIL_API_KEY")?,
+            from: var("EMAIL_FROM")?,
+            client: outbound.client("email")?,
+        })
+    }
+
+    pub async fn send(&self, email: &Email) -> Result<(), OutboundError> {
+        let mut headers = serde_json::Map::new();
+        if let Some(url) = &email.unsubscribe_url {
+            headers.insert("List-Unsubscribe".to_string(), format!("<{}>", url).into());
+            headers.insert("List-Unsubscribe-Post".to_string(), "List-Unsubscribe=One-Click".into());
+        }
+
+        let request = self.client
+            .post(&self.api_url)
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "from": self.from,
+                "to": [email.to],
+                "subject": email.subject,
+                "text": email.text,
+                "html": email.html,
+                "headers": headers
+            }));
+        let response = self.client.send(request).await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("{}: {}", status, body.chars().take(300).collect::<String>()).into())
+        }
+    }
+}
@@ -1,19 +1,112 @@
 // src/main.rs
 
 use axum::{
+    Extension,
+    handler::Handler,
+    middleware,
     Router, 
-    routing::{get, post, put}, 
+    routing::{delete, get, post, put}, 
     Server,
 };
+use axum::http::Method;
 use dotenvy::dotenv;
 use std::{env, net::SocketAddr};
-use tower_http::trace::TraceLayer;
+use tower_http::{cors::{Any, CorsLayer}, trace::TraceLayer};
 use sqlx::PgPool;
 
 mod db;
 mod routes;
 mod models;
 mod auth;
+mod cache;
+mod includes;
+mod fields;
+mod api_keys;
+mod public;
+mod feeds;
+mod slug;
+mod scheduler;
+mod tags;
+mod media;
+mod storage;
+mod documents;
+mod ipfs;
+mod upload;
+mod eip712;
+mod intents;
+mod chain;
+mod relayer;
+mod registry;
+mod token;
+mod indexer;
+mod stripe;
+mod notifications;
+mod refunds;
+mod kyc;
+mod compliance;
+mod risk;
+mod legal;
+mod accreditation;
+mod exposure;
+mod stats;
+mod reports;
+mod retention;
+mod request_log;
+mod flags;
+mod tenants;
+mod wallets;
+mod two_factor;
+mod confirmations;
+mod user_tokens;
+mod brute_force;
+mod signed_requests;
+mod impersonation;
+mod role_approvals;
+mod activity;
+mod notification_preferences;
+mod mailer;
+mod digest;
+mod contents;
+mod announcements;
+mod investment_revisions;
+mod funding;
+mod rates;
+mod currency;
+mod taxes;
+mod certificates;
+mod user_merges;
+mod trash;
+mod forwarded;
+mod tls;
+mod envelope;
+mod error_codes;
+mod query_builder;
+mod pagination;
+mod worker;
+mod jobs;
+mod outbox;
+mod wallet_address;
+mod money;
+mod onchain_investments;
+mod integrity;
+mod dual_write;
+mod shadow_reads;
+mod db_tx;
+mod outbound;
+mod chaos;
+mod fixtures;
+mod mock_chain;
+mod scenarios;
+mod backups;
+mod deletion_impact;
+mod property_managers;
+mod suggest;
+mod geocoding;
+mod facets;
+mod calculator;
+mod distributions;
+#[cfg(test)]
+mod test_db;
 
 #[tokio::main]
 async fn main() {
@@ -23,10 +116,282 @@ async fn main() {
     // Charger les variables d'environnement
     dotenv().ok();
 
-    // Connexion à la base de données
-    let pool: PgPool = db::init_db().await;
+    // Rôle du processus : `api`, `worker` ou les deux (voir worker.rs)
+    let role = worker::ProcessRole::from_args();
+    match role {
+        worker::ProcessRole::Api => println!("🌐 Processus API : tâches de fond laissées aux workers (my-api worker)"),
+        worker::ProcessRole::Worker => println!("⚙️  Processus worker : tâches de fond uniquement, sans routes HTTP"),
+        worker::ProcessRole::All => {},
+    }
 
-    println!("✅ Connexion à la base de données établie");
+    // Connexion à la base de données, établie en tâche de fond (voir db.rs) :
+    // le serveur répond à /health pendant les nouvelles tentatives
+    let dual_write = dual_write::DualWrite::from_env();
+    let (pool, db_health): (PgPool, db::DbHealth) = db::init_db(dual_write.as_ref());
+
+    println!("⏳ Connexion à la base de données en cours");
+
+    // Lectures fantômes sur les routes choisies, comparées à la lecture servie
+    let shadow_reads = shadow_reads::ShadowReads::from_env(&pool);
+    if let Some(shadow_reads) = &shadow_reads {
+        println!(
+            "🔍 Lectures fantômes actives ({}){}",
+            shadow_reads.endpoints().join(", "),
+            if shadow_reads.has_replica() { ", sur le réplica" } else { "" }
+        );
+    }
+
+    // Cache des utilisateurs authentifiés (évite une requête SQL par appel Bearer)
+    let user_cache = cache::UserCache::from_env();
+
+    // Cache du sitemap et du flux Atom (invalidé lors des changements de propriétés)
+    let feed_cache = feeds::FeedCache::default();
+
+    // Bucket privé des documents (URL signées), optionnel
+    let storage = storage::StorageConfig::from_env();
+    if storage.is_none() {
+        println!("⚠️  Stockage des documents non configuré : téléchargements signés désactivés");
+    }
+
+    // Épinglage IPFS des documents envoyés, optionnel
+    let ipfs = ipfs::IpfsConfig::from_env();
+    if ipfs.is_some() {
+        println!("📌 Épinglage IPFS des documents activé");
+    }
+
+    // Domaine EIP-712 des intentions d'investissement, optionnel
+    let eip712_domain = eip712::Eip712Domain::from_env();
+    if eip712_domain.is_none() {
+        println!("⚠️  INVESTMENT_CONTRACT_ADDRESS non configurée : intentions EIP-712 désactivées");
+    }
+
+    // Appels sortants : délais, nouvelles tentatives et disjoncteur par service
+    let outbound = outbound::Outbound::from_env();
+
+    // Nœud Ethereum utilisé pour les lectures et les envois on-chain, optionnel
+    let chain_rpc = chain::ChainRpc::from_env(&outbound);
+    if let Some(mock) = chain_rpc.as_ref().and_then(chain::ChainRpc::mock) {
+        println!("🧪 Chaîne simulée active (CHAIN_MODE=mock, chain id {}) : ne pas utiliser en production", mock.chain_id());
+    }
+    let chain_status_cache = chain::ChainStatusCache::from_env();
+
+    // Cache du classement public (investisseurs volontaires et propriétés)
+    let leaderboard_cache = stats::LeaderboardCache::from_env();
+
+    // Cache des statistiques d'investissement par propriété
+    let property_stats_cache = stats::PropertyStatsCache::from_env();
+
+    // Drapeaux de fonctionnalités, gardés en mémoire et relus périodiquement
+    let feature_flags = flags::FeatureFlags::from_env();
+
+    // Plateformes (marques) servies par l'instance, résolues par hôte ou en-tête
+    let tenant_registry = tenants::TenantRegistry::default();
+
+    // Premier chargement dès que la base est joignable, puis relecture périodique
+    {
+        let (pool, db_health) = (pool.clone(), db_health.clone());
+        let (feature_flags, tenant_registry) = (feature_flags.clone(), tenant_registry.clone());
+        tokio::spawn(async move {
+            db_health.wait_up().await;
+            println!("✅ Connexion à la base de données établie");
+            match feature_flags.refresh(&pool).await {
+                Ok(()) => println!("🚩 Drapeaux de fonctionnalités chargés (environnement : {})", feature_flags.environment()),
+                Err(e) => println!("⚠️  Drapeaux de fonctionnalités non chargés : {}", e),
+            }
+            match tenant_registry.refresh(&pool).await {
+                Ok(count) => println!("🏷️  {} plateforme(s) chargée(s)", count),
+                Err(e) => println!("⚠️  Plateformes non chargées (plateforme par défaut uniquement) : {}", e),
+            }
+        });
+    }
+    feature_flags.spawn(pool.clone());
+    tenant_registry.spawn(pool.clone());
+
+    // Relayer : wallet du serveur pour les actions on-chain de l'admin, optionnel
+    let relayer = relayer::Relayer::from_env(chain_rpc.clone(), feed_cache.clone());
+    match &relayer {
+        Some(relayer) => {
+            println!("⛓️  Relayer actif depuis {}", relayer.address);
+            if role.runs_jobs() {
+                relayer.clone().spawn(pool.clone());
+            }
+        },
+        None => println!("⚠️  Relayer non configuré (CHAIN_RPC_URL, RELAYER_PRIVATE_KEY) : actions on-chain désactivées"),
+    }
+
+    // Enregistrement des propriétés validées sur le contrat registre, via le relayer
+    let registry = relayer.as_ref().and(registry::Registry::from_env());
+    match &registry {
+        Some(registry) => println!("📒 Enregistrement on-chain des propriétés validées sur {}", registry.address),
+        None => println!("⚠️  REGISTRY_CONTRACT_ADDRESS ou relayer non configuré : pas d'enregistrement on-chain à la validation"),
+    }
+
+    // Webhooks des fournisseurs d'événements on-chain (Alchemy, Moralis), optionnels
+    let chain_webhooks = indexer::ChainWebhooks::from_env();
+    match (&chain_webhooks, onchain_investments::investment_address_from_env()) {
+        (None, _) => println!("⚠️  Webhooks on-chain non configurés (PROPERTY_TOKEN_ADDRESS, ALCHEMY_WEBHOOK_SIGNING_KEY ou MORALIS_STREAMS_SECRET)"),
+        (Some(_), Some(address)) => println!("🧾 Investissements créés depuis les événements Invested de {}", address),
+        (Some(_), None) => {},
+    }
+
+    // Paiement des investissements en euros via Stripe, optionnel
+    let stripe = stripe::StripeConfig::from_env(&outbound);
+    if stripe.is_none() {
+        println!("⚠️  Stripe non configuré (STRIPE_SECRET_KEY, STRIPE_WEBHOOK_SECRET, FIAT_EUR_PER_ETH) : paiement en euros désactivé");
+    }
+
+    // Prestataire de vérification d'identité (Sumsub), optionnel
+    let kyc_provider = kyc::KycProvider::from_env(&outbound);
+    if kyc_provider.is_none() {
+        println!("⚠️  Prestataire KYC non configuré (SUMSUB_APP_TOKEN, SUMSUB_SECRET_KEY, SUMSUB_WEBHOOK_SECRET) : décisions KYC manuelles uniquement");
+    }
+
+    // Filtrage AML des wallets (liste de sanctions, oracle Chainalysis), optionnel
+    let screener = compliance::SanctionsScreener::from_env(chain_rpc.clone());
+    match &screener {
+        Some(screener) => if role.runs_jobs() {
+            compliance::spawn(pool.clone(), screener.clone());
+        },
+        None => println!("⚠️  Filtrage AML non configuré (SANCTIONS_LIST_FILE ou SANCTIONS_ORACLE_ADDRESS) : wallets non filtrés"),
+    }
+
+    // Scénarios des tests de bout en bout, hors build release
+    let scenarios = scenarios::Scenarios::from_env();
+    if scenarios.is_some() {
+        println!("🧪 Scénarios de test actifs (POST /dev/scenarios/:name vide la base) : ne pas utiliser en production");
+    }
+
+    // Injection de fautes pour les tests de résistance, hors build release
+    let chaos = chaos::Chaos::from_env();
+    if let Some(chaos) = &chaos {
+        println!("🧪 Injection de fautes active ({} règle(s)) : ne pas utiliser en production", chaos.rule_count());
+    }
+
+    // Capture des requêtes et réponses de routes choisies, rejouables par `replay`
+    let fixture_capture = fixtures::FixtureCapture::from_env();
+    if let Some(capture) = &fixture_capture {
+        println!("🎞️  Capture de fixtures dans {}", capture.dir().display());
+    }
+
+    // Règles de détection d'activité suspecte (seuils configurables)
+    let risk_rules = risk::RiskRules::from_env();
+
+    // Terminaison TLS par le serveur (TLS_CERT_PATH / TLS_KEY_PATH), sinon HTTP
+    let tls = tls::TlsConfig::from_env();
+    if tls.is_none() {
+        println!("⚠️  TLS_CERT_PATH / TLS_KEY_PATH non définis, écoute en HTTP (TLS à terminer par un reverse proxy)");
+    }
+
+    // Proxies de confiance pour X-Forwarded-For / X-Forwarded-Proto
+    let trusted_proxies = forwarded::TrustedProxies::from_env().serving_tls(tls.is_some());
+
+    // Taille des pages des routes de liste (PAGINATION_DEFAULT_PER_PAGE / PAGINATION_MAX_PER_PAGE)
+    let pagination_config = pagination::PaginationConfig::from_env();
+
+    // Forme des réponses JSON (enveloppe, ou ancienne forme avec LEGACY_RESPONSE_SHAPE)
+    let envelope_config = envelope::EnvelopeConfig::from_env();
+
+    // Blocage temporaire après des échecs d'authentification répétés
+    let auth_lockout = brute_force::AuthLockout::from_env();
+
+    // Contrat des jetons des propriétés (snapshots des détenteurs), optionnel
+    let property_token = token::PropertyToken::from_env(chain_rpc.clone());
+    if property_token.is_none() {
+        println!("⚠️  PROPERTY_TOKEN_ADDRESS ou CHAIN_RPC_URL non configuré : snapshots des détenteurs désactivés");
+    }
+
+    // Géocodage des localisations (pays, région, ville des facettes), optionnel
+    let geocoder = geocoding::Geocoder::from_env(&outbound);
+    if geocoder.is_none() {
+        println!("⚠️  GEOCODING_URL non configurée : localisations des propriétés non géocodées");
+    }
+
+    // Tâches de fond, dans les processus worker
+    if role.runs_jobs() {
+        // Publication automatique des propriétés programmées
+        scheduler::spawn(pool.clone(), feed_cache.clone());
+
+        // File de tâches durable (génération des rapports, déposés dans le bucket privé)
+        jobs::JobRunner::from_env(storage.clone(), geocoder.clone()).spawn(pool.clone());
+
+        // Publication des événements métier de l'outbox (notifications)
+        outbox::spawn(pool.clone());
+
+        // Archivage des lignes dépassant leur durée de conservation
+        retention::spawn(pool.clone());
+
+        // Contrôle périodique de la cohérence des données, avec alerte des admins
+        integrity::spawn(pool.clone());
+
+        // Copie des écritures vers l'autre base pendant une bascule
+        if let Some(dual_write) = &dual_write {
+            dual_write::spawn(pool.clone(), dual_write.clone());
+        }
+
+        // Résumés hebdomadaires par e-mail, si un service d'envoi est configuré
+        match mailer::Mailer::from_env(&outbound) {
+            Some(mailer) => digest::spawn(pool.clone(), mailer),
+            None => println!("⚠️  Service e-mail non configuré (EMAIL_API_KEY, EMAIL_FROM) : résumés hebdomadaires désactivés"),
+        }
+
+        // Relevé périodique des cours ETH/EUR et ETH/USD, optionnel
+        match rates::RatesFeed::from_env(&outbound) {
+            Some(feed) => rates::spawn(pool.clone(), feed),
+            None => println!("⚠️  RATES_API_URL non configurée : cours de l'ETH non relevés"),
+        }
+    }
+
+    // Processus worker : pas de routes HTTP
+    if !role.serves_api() {
+        worker::wait_for_shutdown().await;
+        return;
+    }
+
+    // Invalidations du cache des flux faites par les workers
+    feed_cache.listen(pool.clone());
+    // Invalidations du cache des utilisateurs faites par les autres processus
+    user_cache.listen(pool.clone());
+
+    // Widget embarquable : CORS ouvert (`*`) uniquement sur cette route
+    let widget_routes = Router::new()
+        .route("/public/v1/widget/:property_id", get(public::get_property_widget))
+        .layer(CorsLayer::new().allow_origin(Any).allow_methods([Method::GET]));
+
+    // Routes financièrement sensibles : requêtes journalisées dans request_logs,
+    // par groupe (voir request_log.rs)
+    let investment_routes = Router::new()
+        .route("/api/investments",
+            get(routes::get_all_investments)
+            // Requête signée par le wallet optionnelle (obligatoire avec SIGNED_REQUESTS_REQUIRED)
+            .post(routes::create_investment.layer(middleware::from_fn(signed_requests::verify_signed_request)))
+        )
+        .route("/api/investments/intent", post(intents::create_intent))
+        .route("/api/investments/intent/:id", get(intents::get_intent))
+        .route("/api/investments/intent/:id/signature", post(intents::sign_intent))
+        .route("/api/investments/fiat-intent", post(stripe::create_fiat_intent))
+        .route("/api/investments/:id",
+            get(routes::get_investment_by_id)
+            .put(routes::update_investment)
+            .delete(routes::delete_investment)
+        )
+        .route("/api/investments/:id/refund-request", post(refunds::request_refund))
+        .route("/api/investments/:id/revisions", get(investment_revisions::get_investment_revisions))
+        .route("/api/investments/:id/certificate", get(certificates::get_investment_certificate))
+        .route_layer(middleware::from_fn_with_state("investments", request_log::log_requests));
+    let settlement_routes = Router::new()
+        .route("/api/admin/investments/:id/settle", post(stripe::settle_investment))
+        .route("/api/admin/refunds/:id/approve", post(refunds::approve_refund))
+        .route("/api/admin/refunds/:id/reject", post(refunds::reject_refund))
+        .route("/api/admin/refunds/:id/paid", post(refunds::mark_refund_paid))
+        .route_layer(middleware::from_fn_with_state("settlements", request_log::log_requests));
+    let role_routes = Router::new()
+        .route("/api/users/:id/role", put(routes::update_user_role))
+        .route("/api/admin/role-requests/:id/approve", post(role_approvals::approve_role_change))
+        .route("/api/admin/role-requests/:id/reject", post(role_approvals::reject_role_change))
+        .route_layer(middleware::from_fn_with_state("roles", request_log::log_requests));
+    let property_status_routes = Router::new()
+        .route("/api/properties/:id/status", put(routes::update_property_status))
+        .route_layer(middleware::from_fn_with_state("property_status", request_log::log_requests));
 
     // Configuration des routes avec authentification Bearer Token
     let app = Router::new()
@@ -36,75 +401,571 @@ async fn main() {
         
         // Health check (publique)
         .route("/health", get(routes::health_check))
+        .route("/health/ready", get(routes::readiness_check))
+        .route("/metrics", get(db::get_metrics))
+
+        // Catalogue des codes d'erreur (publique)
+        .route("/api/meta/error-codes", get(error_codes::get_error_codes))
+
+        // État du réseau pour les frontends (publique)
+        .route("/api/chain/status", get(chain::get_chain_status))
+
+        // Webhooks des fournisseurs on-chain (authentifiés par signature)
+        .route("/webhooks/chain", post(indexer::chain_webhook))
+        .route("/webhooks/stripe", post(stripe::stripe_webhook))
+        .route("/webhooks/kyc", post(kyc::kyc_webhook))
+
+        // Chaîne simulée (CHAIN_MODE=mock) : investissement et minage manuels
+        .route("/dev/chain/invest", post(mock_chain::simulate_investment))
+        .route("/dev/chain/mine", post(mock_chain::mine_blocks))
+
+        // Scénarios des tests de bout en bout (SCENARIOS_ENABLED) : vident puis remplissent la base
+        .route("/dev/scenarios", get(scenarios::list_scenarios))
+        .route("/dev/scenarios/:name", post(scenarios::load_scenario))
         
+        // Sitemap et flux Atom des propriétés validées (publiques)
+        .route("/sitemap.xml", get(feeds::sitemap))
+        .route("/feed.xml", get(feeds::feed))
+
+        // Classement de la page communauté (public) et participation de l'utilisateur
+        .route("/api/stats/leaderboard", get(stats::get_leaderboard))
+        .route("/api/me/leaderboard", put(stats::set_leaderboard_opt_in))
+
         // Routes utilisateurs
         .route("/users", post(routes::create_user))
+
+        // Wallets rattachés au compte (vérifiés par signature)
+        .route("/api/me/wallets", get(wallets::get_my_wallets).post(wallets::link_wallet))
+        .route("/api/me/wallets/:wallet", delete(wallets::unlink_wallet))
+        .route("/api/me/wallets/:wallet/verify", post(wallets::verify_wallet))
+        .route("/api/me/wallets/:wallet/primary", put(wallets::set_primary_wallet))
+
+        // Double authentification TOTP (exigée pour les opérations d'administration destructrices)
+        .route("/api/me/2fa", get(two_factor::get_two_factor_status).delete(two_factor::disable_two_factor))
+        .route("/api/me/2fa/setup", post(two_factor::setup_two_factor))
+        .route("/api/me/2fa/enable", post(two_factor::enable_two_factor))
+        .route("/api/me/2fa/step-up", post(two_factor::step_up))
+
+        // Confirmation des opérations dangereuses (défi renvoyé en 428)
+        .route("/api/confirmations", post(confirmations::create_confirmation))
+
+        // Utilisateur connecté, avec le bandeau d'impersonation du support
+        .route("/api/me", get(auth::get_me))
+        .route("/api/me/activity", get(activity::get_my_activity))
+
+        // Jetons d'API personnels en lecture seule (tableurs, bots)
+        .route("/api/me/tokens", get(user_tokens::get_my_tokens).post(user_tokens::create_my_token))
+        .route("/api/me/tokens/:id", delete(user_tokens::revoke_my_token))
         
         // Routes utilisateurs protégées (admin seulement)
         .route("/api/users", get(routes::get_all_users))
-        .route("/api/users/:id/role", put(routes::update_user_role))
+        .merge(role_routes)
+        .route("/api/admin/role-requests", get(role_approvals::get_role_change_requests))
+        .route("/api/users/:id/activity", get(activity::get_user_activity))
+        .route("/api/users/:id/kyc", put(kyc::override_kyc))
+        .route("/api/admin/kyc", get(kyc::get_kyc_verifications))
+
+        // Conformité : signalements du filtrage AML (admin seulement)
+        .route("/api/admin/compliance/flags", get(compliance::get_compliance_flags))
+        .route("/api/admin/compliance/flags/:id", put(compliance::review_compliance_flag))
+        .route("/api/admin/compliance/screening", post(compliance::run_screening))
+        .route("/api/admin/alerts", get(risk::get_alerts))
+        .route("/api/admin/alerts/:id", get(risk::get_alert))
+        .route("/api/admin/alerts/:id/acknowledge", post(risk::acknowledge_alert))
+        .route("/api/admin/alerts/:id/notes", post(risk::add_alert_note))
+        .route("/api/admin/auth-attempts", get(brute_force::get_auth_attempts))
+        .route("/api/admin/investments/:id/signed-request", get(signed_requests::get_investment_signed_request))
+        .route("/api/admin/impersonate/:user_id", post(impersonation::impersonate_user))
+        .route("/api/admin/impersonations", get(impersonation::get_impersonations))
+        .route("/api/admin/impersonations/:id/actions", get(impersonation::get_impersonation_actions))
+        .route("/api/admin/impersonations/:id/end", post(impersonation::end_impersonation))
+
+        // Statistiques : séries temporelles de l'admin, tableau de bord des managers
+        .route("/api/rates/history", get(rates::get_rate_history))
+        .route("/api/units/convert", get(money::convert_units))
+        .route("/api/admin/analytics", get(stats::get_analytics))
+        .route("/api/manager/stats", get(stats::get_manager_stats))
+
+        // Rapports d'export générés en tâche de fond (admin seulement)
+        .route("/api/admin/reports", get(reports::get_reports).post(reports::create_report))
+        .route("/api/admin/reports/:id", get(reports::get_report))
+
+        // Sauvegardes logiques exportées en tâche de fond (admin seulement)
+        .route("/api/admin/backups", get(backups::get_backups).post(backups::create_backup))
+        .route("/api/admin/backups/:id", get(backups::get_backup))
+        .route("/api/admin/jobs/failed", get(jobs::get_failed_jobs))
+        .route("/api/admin/jobs/:id/retry", post(jobs::retry_job))
+        .route("/api/admin/onchain-investments", get(onchain_investments::get_onchain_investments))
+        .route("/api/admin/onchain-investments/:id/resolve", post(onchain_investments::resolve_onchain_investment))
+        .route("/api/admin/onchain-investments/:id/dismiss", post(onchain_investments::dismiss_onchain_investment))
+
+        // Conservation des données : durées par table et archivage (admin seulement)
+        .route("/api/admin/retention", get(retention::get_retention_policies))
+        .route("/api/admin/retention/run", post(retention::run_retention))
+        .route("/api/admin/integrity-check", post(integrity::run_integrity_check))
+        .route("/api/admin/dual-write", get(dual_write::get_dual_write_status))
+        .route("/api/admin/shadow-reads", get(shadow_reads::get_shadow_reads))
+        .route("/api/admin/retention/:entity", put(retention::update_retention_policy))
+
+        // Drapeaux de fonctionnalités (gestion réservée aux admins)
+        .route("/api/me/feature-flags", get(flags::get_my_feature_flags))
+        .route("/api/admin/feature-flags", get(flags::get_feature_flags))
+        .route("/api/admin/feature-flags/:key",
+            put(flags::upsert_feature_flag).delete(flags::delete_feature_flag)
+        )
+
+        // Plateformes : habillage public, gestion réservée aux admins de la plateforme principale
+        .route("/api/tenant", get(tenants::get_current_tenant))
+        .route("/api/admin/tenants", get(tenants::get_tenants).post(tenants::create_tenant))
+        .route("/api/admin/tenants/:id", put(tenants::update_tenant))
+
+        // Journal des requêtes sensibles (admin seulement)
+        .route("/api/admin/request-logs", get(request_log::get_request_logs))
+
+        // Documents légaux (CGU, avertissement sur les risques) et acceptations
+        .route("/api/legal-documents", get(legal::get_current_legal_documents))
+        .route("/api/me/terms", get(legal::get_my_terms))
+        .route("/api/me/accept-terms", post(legal::accept_terms))
+        .route("/api/admin/legal-documents",
+            get(legal::get_legal_documents)
+            .post(legal::publish_legal_document)
+        )
+        // Contenus éditoriaux (FAQ, avertissements, annonces), gérés par les admins
+        .route("/api/admin/contents", get(contents::get_contents).post(contents::create_content))
+        .route("/api/admin/contents/:id",
+            get(contents::get_content)
+            .put(contents::update_content)
+            .delete(contents::delete_content)
+        )
+        // Bannières d'annonce ciblées par rôle
+        .route("/api/me/announcements", get(announcements::get_my_announcements))
+        .route("/api/me/announcements/:id/dismiss", post(announcements::dismiss_announcement))
+        .route("/api/admin/announcements", get(announcements::get_announcements).post(announcements::create_announcement))
+        .route("/api/admin/announcements/:id",
+            put(announcements::update_announcement)
+            .delete(announcements::delete_announcement)
+        )
+
+        // Accréditation des investisseurs qualifiés
+        .route("/api/accreditation", get(accreditation::get_my_accreditation))
+        .route("/api/accreditation/documents", post(accreditation::upload_accreditation_document))
+        .route("/api/admin/accreditations", get(accreditation::get_accreditations))
+        .route("/api/admin/accreditations/:user_id/documents/:doc_id",
+            get(accreditation::download_accreditation_document)
+        )
+        .route("/api/users/:id/accreditation", put(accreditation::review_accreditation))
+
+        // Limites d'exposition des investisseurs (admin seulement)
+        .route("/api/admin/exposure-limits",
+            get(exposure::get_exposure_limits).put(exposure::set_exposure_limit)
+        )
+        .route("/api/admin/exposure-limits/:id", delete(exposure::delete_exposure_limit))
+        .route("/api/users/:id/exposure-limits", put(exposure::set_user_exposure_limit))
+
+        // Vérification d'identité de l'utilisateur connecté
+        .route("/api/kyc", get(kyc::get_my_kyc))
+        .route("/api/kyc/session", post(kyc::create_kyc_session))
+
+        // Gestion des clés d'API partenaires (admin seulement)
+        .route("/api/admin/api-keys",
+            get(api_keys::get_api_keys)
+            .post(api_keys::create_api_key)
+        )
+        .route("/api/admin/api-keys/:id",
+            put(api_keys::update_api_key)
+            .delete(api_keys::delete_api_key)
+        )
+        
+        // Vocabulaire de tags (lecture : authentifié, gestion : admin seulement)
+        .route("/api/tags", get(tags::get_tags))
+        .route("/api/admin/tags", post(tags::create_tag))
+        .route("/api/admin/tags/:id",
+            put(tags::update_tag)
+            .delete(tags::delete_tag)
+        )
+        
+        // Relayer : état et file des transactions on-chain (admin seulement)
+        .route("/api/admin/relayer", get(relayer::get_relayer_status))
+        .route("/api/admin/relayer/txs",
+            get(relayer::get_relayer_txs)
+            .post(relayer::create_relayer_tx)
+        )
+        .route("/api/admin/relayer/txs/:id/retry", post(relayer::retry_relayer_tx))
+
+        // Règlement on-chain des investissements payés en euros et traitement
+        // des remboursements (admin seulement, journalisés)
+        .route("/api/admin/investments/pending-settlement", get(stripe::get_pending_settlements))
+        .merge(settlement_routes)
         
         // Routes properties avec authentification Bearer Token
         // Routes publiques (anciennes pour compatibilité)
         .route("/properties/public", get(routes::get_properties))
         
         // Routes protégées par Bearer Token
+        // Création et modification : transaction par requête (voir db_tx.rs)
         .route("/api/properties", 
             get(routes::get_all_properties)
-            .post(routes::create_property)
+            .post(routes::create_property.layer(middleware::from_fn_with_state(pool.clone(), db_tx::transactional)))
         )
+        .route("/api/properties/suggest", get(suggest::suggest_properties))
+        .route("/api/properties/facets", get(facets::get_property_facets))
         .route("/api/properties/:id", 
             get(routes::get_property_by_id)
-            .put(routes::update_property)
+            .put(routes::update_property.layer(middleware::from_fn_with_state(pool.clone(), db_tx::transactional)))
             .delete(routes::delete_property)
         )
-        .route("/api/properties/:id/status", 
-            put(routes::update_property_status)
+        .route("/api/properties/:id/stats", get(stats::get_property_stats))
+        .route("/api/properties/:id/calculator", get(calculator::get_yield_calculation))
+        .route("/api/properties/:id/deletion-impact", get(deletion_impact::get_deletion_impact))
+        .route("/api/properties/:id/managers",
+            get(property_managers::get_property_managers)
+            .post(property_managers::add_property_manager))
+        .route("/api/properties/:id/managers/:user_id", delete(property_managers::remove_property_manager))
+        .route("/api/properties/:id/distribution-runs",
+            get(distributions::get_distribution_runs)
+            .post(distributions::record_distribution_run))
+        .route("/api/properties/by-slug/:slug",
+            get(routes::get_property_by_slug)
         )
-        
-        // Routes investissements protégées par Bearer Token
-        .route("/api/investments",
-            get(routes::get_all_investments)
-            .post(routes::create_investment)
+        .route("/api/properties/by-onchain/:id",
+            get(routes::get_property_by_onchain_id)
         )
-        .route("/api/investments/:id",
-            get(routes::get_investment_by_id)
-            .put(routes::update_investment)
-            .delete(routes::delete_investment)
+        .route("/api/properties/:id/submit",
+            post(routes::submit_property)
+        )
+        .route("/api/properties/:id/schedule",
+            get(scheduler::get_schedule)
+            .put(scheduler::set_schedule)
+            .delete(scheduler::clear_schedule)
+        )
+        .route("/api/properties/:id/media",
+            get(media::get_media)
+            .post(media::add_media)
+        )
+        .route("/api/properties/:id/media/upload",
+            post(media::upload_media)
+        )
+        .route("/api/properties/:id/media/order",
+            put(media::reorder_media)
+        )
+        .route("/api/properties/:id/media/:media_id",
+            put(media::update_media)
+            .delete(media::delete_media)
+        )
+        .route("/api/properties/:id/documents",
+            post(documents::upload_document)
         )
+        .route("/api/properties/:id/documents/:doc_id/download",
+            get(documents::download_document)
+        )
+        .route("/api/properties/:id/documents/downloads",
+            get(documents::get_document_downloads)
+        )
+        .merge(property_status_routes)
+        .route("/api/properties/:id/holders",
+            get(token::get_property_holders)
+        )
+        
+        // Routes investissements protégées par Bearer Token (journalisées)
+        .merge(investment_routes)
+        .route("/api/refunds", get(refunds::get_refunds))
+
+        // Notifications in-app de l'utilisateur connecté
+        .route("/api/notifications", get(notifications::get_notifications))
+        .route("/api/notifications/read-all", post(notifications::mark_all_notifications_read))
+        .route("/api/notifications/:id/read", post(notifications::mark_notification_read))
+        .route("/api/me/notification-preferences", get(notification_preferences::get_notification_preferences)
+            .put(notification_preferences::update_notification_preferences))
+        .route("/api/me/email", put(notification_preferences::update_my_email))
+        .route("/api/me/currency", get(currency::get_my_currency).put(currency::update_my_currency))
+        .route("/api/me/tax", get(taxes::get_my_tax))
+        .route("/api/me/distributions/accrual", get(distributions::get_my_accrual))
+        .route("/api/me/country", put(taxes::update_my_country))
+        .route("/api/admin/tax-rules", get(taxes::get_tax_rules).post(taxes::create_tax_rule))
+        .route("/api/admin/tax-rules/:id", put(taxes::update_tax_rule).delete(taxes::delete_tax_rule))
+        .route("/api/admin/users/merge", post(user_merges::merge_users))
+        .route("/api/admin/trash", get(trash::get_trash))
+        .route("/api/admin/trash/:entity/:id/restore", post(trash::restore_from_trash))
+        .route("/api/admin/trash/:entity/:id/purge", delete(trash::purge_from_trash))
+        // Désinscription des e-mails depuis le lien de pied de page (publique, jeton)
+        .route("/notifications/unsubscribe", get(notification_preferences::unsubscribe).post(notification_preferences::unsubscribe))
+
+        // API publique partenaires (clé d'API + quota journalier)
+        .route("/public/v1/properties", get(public::get_public_properties))
+        .route("/public/v1/stats", get(public::get_public_stats))
+        // Contenus publiés, servis au frontend (sans clé d'API)
+        .route("/public/v1/content/:slug", get(contents::get_public_content))
+        // Vérification des certificats d'investissement (sans clé d'API)
+        .route("/public/v1/certificates/:hash/verify", get(certificates::verify_certificate))
+        .merge(widget_routes)
         
         // Layers
+        // Résolution de la plateforme et CORS par plateforme (voir tenants.rs),
+        // placée sous les extensions pour y accéder
+        // Fautes injectées par route (voir chaos.rs), sous les couches qui mettent en forme les réponses
+        .layer(middleware::from_fn_with_state(chaos, chaos::inject_faults))
+        // Journal des requêtes effectuées en impersonation (voir impersonation.rs)
+        .layer(middleware::from_fn(impersonation::audit_impersonated_requests))
+        // Code d'erreur générique des réponses d'erreur sans code précis (voir error_codes.rs)
+        .layer(middleware::from_fn(error_codes::add_error_codes))
+        // Enveloppe `{data, meta, error}` des réponses JSON (voir envelope.rs)
+        .layer(middleware::from_fn_with_state(envelope_config, envelope::shape_responses))
+        // Fixtures capturées telles que reçues par le client (voir fixtures.rs)
+        .layer(middleware::from_fn_with_state(fixture_capture, fixtures::capture_fixtures))
+        .layer(middleware::from_fn(tenants::resolve_tenant))
+        .layer(Extension(pool.clone()))
+        .layer(Extension(db_health))
+        .layer(Extension(outbound))
+        .layer(Extension(user_cache))
+        .layer(Extension(feed_cache))
+        .layer(Extension(storage))
+        .layer(Extension(ipfs))
+        .layer(Extension(eip712_domain))
+        .layer(Extension(relayer))
+        .layer(Extension(registry))
+        .layer(Extension(property_token))
+        .layer(Extension(geocoder))
+        .layer(Extension(chain_rpc))
+        .layer(Extension(scenarios))
+        .layer(Extension(chain_status_cache))
+        .layer(Extension(leaderboard_cache))
+        .layer(Extension(property_stats_cache))
+        .layer(Extension(feature_flags))
+        .layer(Extension(tenant_registry))
+        .layer(Extension(chain_webhooks))
+        .layer(Extension(stripe))
+        .layer(Extension(kyc_provider))
+        .layer(Extension(screener))
+        .layer(Extension(dual_write))
+        .layer(Extension(shadow_reads))
+        .layer(Extension(risk_rules))
+        .layer(Extension(auth_lockout))
+        .layer(Extension(pagination_config))
         .layer(TraceLayer::new_for_http())
+        // Adresse et protocole réels du client derrière un reverse proxy (voir forwarded.rs),
+        // couche la plus externe pour être vue de tous les middlewares
+        .layer(middleware::from_fn_with_state(trusted_proxies, forwarded::normalize_forwarded_headers))
         .with_state(pool.clone());
 
     // Détermination de l'adresse d'écoute
     let port = env::var("PORT").unwrap_or_else(|_| "3000".to_string());
     let addr: SocketAddr = format!("0.0.0.0:{}", port).parse().expect("Invalid address");
 
-    println!("🚀 Server running on http://{}", addr);
+    println!("🚀 Server running on {}://{}", if tls.is_some() { "https" } else { "http" }, addr);
+    if let Some(redirect_port) = tls.as_ref().and_then(|tls| tls.redirect_port) {
+        println!("↪️  Redirection HTTP vers HTTPS sur le port {}", redirect_port);
+    }
     println!("📋 Routes disponibles:");
     println!("  - POST /auth/login (connexion par wallet)");
     println!("  - POST /auth/logout (déconnexion)");
     println!("  - GET  /health (vérification santé)");
+    println!("  - GET  /health/ready (disponibilité : base et disjoncteurs des services externes)");
+    println!("  - GET  /api/meta/error-codes (catalogue des codes d'erreur - publique)");
+    println!("  - GET  /metrics (métriques Prometheus de la base et du pool, METRICS_TOKEN si défini)");
+    println!("  - GET  /api/chain/status (état du réseau et frais suggérés - publique)");
+    println!("  - POST /webhooks/chain (événements Alchemy / Moralis - signature du fournisseur)");
+    println!("  - POST /webhooks/stripe (événements de paiement Stripe - signature Stripe)");
+    println!("  - POST /webhooks/kyc (événements du prestataire KYC - signature Sumsub)");
+    println!("  - POST /dev/chain/invest (transaction d'investissement simulée - CHAIN_MODE=mock, Bearer Token requis)");
+    println!("  - POST /dev/chain/mine (mine et indexe les transactions simulées - CHAIN_MODE=mock)");
+    println!("  - GET  /dev/scenarios (scénarios de test disponibles - SCENARIOS_ENABLED)");
+    println!("  - POST /dev/scenarios/:name (vide la base et charge un scénario - SCENARIOS_ENABLED)");
+    println!("  - GET  /sitemap.xml (sitemap des propriétés validées - publique)");
+    println!("  - GET  /feed.xml (flux Atom des propriétés validées - publique)");
+    println!("  - GET  /api/stats/leaderboard (classement des investisseurs et des propriétés - publique)");
+    println!("  - PUT  /api/me/leaderboard (apparaître ou non dans le classement - Bearer Token requis)");
     println!("  - POST /users (création utilisateur)");
+    println!("  - GET  /api/me/wallets (wallet principal et wallets rattachés - Bearer Token requis)");
+    println!("  - POST /api/me/wallets (demander le rattachement d'un wallet, message à signer - Bearer Token requis)");
+    println!("  - POST /api/me/wallets/:wallet/verify (vérifier la signature et rattacher, fusion du compte existant - Bearer Token requis)");
+    println!("  - PUT  /api/me/wallets/:wallet/primary (choisir le wallet principal - Bearer Token requis)");
+    println!("  - DELETE /api/me/wallets/:wallet (détacher un wallet - Bearer Token requis)");
+    println!("  - GET  /api/me/2fa (état de la double authentification - Bearer Token requis)");
+    println!("  - POST /api/me/2fa/setup (secret TOTP, QR code et codes de secours - Bearer Token requis)");
+    println!("  - POST /api/me/2fa/enable (confirmer l'enrôlement avec un code - Bearer Token requis)");
+    println!("  - POST /api/me/2fa/step-up (jeton de double authentification de quelques minutes - Bearer Token requis)");
+    println!("  - DELETE /api/me/2fa (désactiver la double authentification, code requis - Bearer Token requis)");
+    println!("  - POST /api/confirmations (échanger un défi 428 contre un jeton de confirmation - Bearer Token requis)");
+    println!("  - GET  /api/me (utilisateur connecté et bandeau d'impersonation - Bearer Token requis)");
+    println!("  - GET  /api/me/activity (sa chronologie d'activité, ?kind=&before=&limit= - Bearer Token requis)");
+    println!("  - GET  /api/me/tokens (jetons d'API personnels - Bearer Token requis)");
+    println!("  - POST /api/me/tokens (créer un jeton limité à read:portfolio / read:properties - Bearer Token requis)");
+    println!("  - DELETE /api/me/tokens/:id (révoquer un jeton - Bearer Token requis)");
     println!("  - GET  /api/users (liste utilisateurs - Admin Bearer Token uniquement)");
-    println!("  - PUT  /api/users/:id/role (modifier rôle utilisateur - Admin Bearer Token uniquement)");
-    println!("  - GET  /properties/public (propriétés validées - publique)");
-    println!("  - GET  /api/properties (propriétés filtrées par rôle - Bearer Token requis)");
-    println!("  - POST /api/properties (créer propriété - Manager/Admin Bearer Token)");
+    println!("  - PUT  /api/users/:id/role (modifier rôle utilisateur, confirmation requise, approbation d'un second admin pour le rôle admin - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/users/:id/activity (chronologie d'activité d'un utilisateur - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/role-requests (changements de rôle en attente ou traités, ?status= - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/role-requests/:id/approve (approuver un changement de rôle, second admin - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/role-requests/:id/reject (refuser ou annuler un changement de rôle - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/users/:id/kyc (imposer le statut KYC - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/kyc (dossiers KYC, ?status= pour filtrer - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/compliance/flags (signalements AML, ?status= pour filtrer - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/admin/compliance/flags/:id (lever ou confirmer un signalement - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/compliance/screening (lancer le filtrage AML - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/alerts (alertes d'activité suspecte, ?status= et ?severity= pour filtrer - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/alerts/:id (détail d'une alerte et de ses notes - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/alerts/:id/acknowledge (prendre en compte une alerte - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/alerts/:id/notes (annoter une alerte - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/investments/:id/signed-request (preuve signée de la création d'un investissement - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/impersonate/:user_id (jeton d'impersonation en lecture seule, motif et second facteur requis - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/impersonations (sessions d'impersonation et nombre de requêtes - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/impersonations/:id/actions (requêtes effectuées pendant une session - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/impersonations/:id/end (terminer une session d'impersonation - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/auth-attempts (échecs d'authentification récents et blocages en cours - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/rates/history (historique des cours ETH/EUR et ETH/USD, ?granularity=&from=&to= - Bearer Token requis)");
+    println!("  - GET  /api/units/convert (conversion entre ETH et wei, ?amount_eth= ou ?amount_wei= - Bearer Token requis)");
+    println!("  - GET  /api/admin/analytics (série temporelle d'un indicateur, ?metric=&granularity=&from=&to= - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/manager/stats (tableau de bord de ses propriétés - Manager Bearer Token requis)");
+    println!("  - POST /api/admin/reports (demander un rapport CSV ou PDF, généré en tâche de fond - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/reports (rapports récents - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/reports/:id (état d'un rapport et lien de téléchargement signé - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/backups (demander une sauvegarde des tables principales, exportée en tâche de fond - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/backups (sauvegardes récentes et liens de téléchargement signés - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/backups/:id (état d'une sauvegarde et lien de téléchargement signé - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/jobs/failed (tâches de fond en échec définitif, ?kind= - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/jobs/:id/retry (relancer une tâche en échec définitif - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/onchain-investments (événements Invested observés on-chain, ?status=unmatched - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/onchain-investments/:id/resolve (rapprocher un événement et créer l'investissement - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/onchain-investments/:id/dismiss (écarter un événement non rapproché - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/retention (durées de conservation et taille des tables archivées - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/admin/retention/:entity (modifier la durée de conservation d'une table - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/retention/run (lancer l'archivage immédiatement - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/integrity-check (contrôle de cohérence des données - Admin de la plateforme par défaut)");
+    println!("  - GET  /api/admin/dual-write (état de la double écriture vers l'autre base - Admin de la plateforme par défaut)");
+    println!("  - GET  /api/admin/shadow-reads (écarts des lectures fantômes - Admin de la plateforme par défaut)");
+    println!("  - GET  /api/me/feature-flags (drapeaux de fonctionnalités actifs pour soi - Bearer Token requis)");
+    println!("  - GET  /api/admin/feature-flags (drapeaux de fonctionnalités - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/admin/feature-flags/:key (créer ou modifier un drapeau - Admin Bearer Token uniquement)");
+    println!("  - DELETE /api/admin/feature-flags/:key (supprimer un drapeau - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/tenant (nom et habillage de la plateforme résolue - publique)");
+    println!("  - GET  /api/admin/tenants (plateformes - Admin de la plateforme principale uniquement)");
+    println!("  - POST /api/admin/tenants (créer une plateforme - Admin de la plateforme principale uniquement)");
+    println!("  - PUT  /api/admin/tenants/:id (modifier hôtes, CORS ou habillage - Admin de la plateforme principale uniquement)");
+    println!("  - GET  /api/admin/request-logs (journal des requêtes sensibles, ?group=&actor_id=&path=&status=&from=&to= - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/legal-documents (versions en vigueur des documents légaux - publique)");
+    println!("  - GET  /api/me/terms (état de ses acceptations des documents légaux - Bearer Token requis)");
+    println!("  - POST /api/me/accept-terms (accepter les documents légaux en vigueur - Bearer Token requis)");
+    println!("  - GET  /api/admin/legal-documents (versions des documents légaux et acceptations - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/legal-documents (publier une nouvelle version d'un document légal - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/contents (contenus éditoriaux, ?locale=&published= - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/contents (créer un contenu en Markdown - Admin Bearer Token uniquement)");
+    println!("  - GET/PUT/DELETE /api/admin/contents/:id (consulter, modifier, supprimer un contenu - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/me/announcements (annonces en cours pour son rôle - Bearer Token requis)");
+    println!("  - POST /api/me/announcements/:id/dismiss (masquer une annonce - Bearer Token requis)");
+    println!("  - GET  /api/admin/announcements (annonces et masquages, ?active=true - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/announcements (publier une annonce ciblée - Admin Bearer Token uniquement)");
+    println!("  - PUT/DELETE /api/admin/announcements/:id (modifier, supprimer une annonce - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/accreditation (état de son accréditation d'investisseur qualifié - Bearer Token requis)");
+    println!("  - POST /api/accreditation/documents (envoyer un justificatif d'accréditation, ?filename= - Bearer Token requis)");
+    println!("  - GET  /api/admin/accreditations (demandes d'accréditation, ?status= pour filtrer - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/accreditations/:user_id/documents/:doc_id (URL signée d'un justificatif - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/users/:id/accreditation (approuver ou refuser une accréditation - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/exposure-limits (limites d'exposition, ?user_id= et ?property_id= pour filtrer - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/admin/exposure-limits (fixer une limite globale ou par propriété - Admin Bearer Token uniquement)");
+    println!("  - DELETE /api/admin/exposure-limits/:id (supprimer une limite - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/users/:id/exposure-limits (dérogation aux limites d'exposition - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/kyc (statut KYC de l'utilisateur - Bearer Token requis)");
+    println!("  - POST /api/kyc/session (dossier et jeton du SDK KYC - Bearer Token requis)");
+    println!("  - GET  /api/admin/api-keys (liste des clés d'API - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/api-keys (créer une clé d'API - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/admin/api-keys/:id (modifier une clé d'API - Admin Bearer Token uniquement)");
+    println!("  - DELETE /api/admin/api-keys/:id (supprimer une clé d'API - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/tags (vocabulaire de tags - Bearer Token requis)");
+    println!("  - POST /api/admin/tags (créer un tag - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/admin/tags/:id (modifier un tag - Admin Bearer Token uniquement)");
+    println!("  - DELETE /api/admin/tags/:id (supprimer un tag - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/relayer (état du relayer et de sa file - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/relayer/txs (transactions du relayer - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/relayer/txs (mettre en file un appel de contrat - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/relayer/txs/:id/retry (relancer une transaction en échec - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/investments/pending-settlement (investissements payés en euros à régler on-chain - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/investments/:id/settle (enregistrer le règlement on-chain - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/refunds/:id/approve (approuver un remboursement - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/refunds/:id/reject (refuser un remboursement - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/refunds/:id/paid (enregistrer le paiement d'un remboursement - Admin Bearer Token uniquement)");
+    println!("  - GET  /properties/public (propriétés validées, ?tags= pour filtrer - publique)");
+    println!("  - GET  /api/properties (propriétés filtrées par rôle, ?ids= pour un lot, ?tags=, ?country=, ?region=, ?city=, ?property_type=, ?yield_bucket= pour filtrer - Bearer Token requis)");
+    println!("  - POST /api/properties (créer propriété en brouillon - Manager/Admin Bearer Token)");
+    println!("  - GET  /api/properties/suggest?q= (autocomplétion nom/localisation selon le rôle - Bearer Token requis)");
+    println!("  - GET  /api/properties/facets (comptes par pays, région, ville, type et rendement selon les filtres - Bearer Token requis)");
     println!("  - GET  /api/properties/:id (détail propriété - Bearer Token requis)");
+    println!("  - GET  /api/properties/:id/stats (statistiques d'investissement par période, ?granularity=&from=&to= - Bearer Token requis)");
+    println!("  - GET  /api/properties/:id/calculator?amount_eth= (parts, revenus projetés et retour sur investissement - Bearer Token requis)");
+    println!("  - GET  /api/properties/by-slug/:slug (détail propriété par slug - Bearer Token requis)");
+    println!("  - GET  /api/properties/by-onchain/:id (propriété validée par identifiant on-chain - publique)");
     println!("  - PUT  /api/properties/:id (modifier propriété - Manager/Admin Bearer Token)");
+    println!("  - POST /api/properties/:id/submit (soumettre un brouillon à validation - Créateur Bearer Token)");
+    println!("  - GET  /api/properties/:id/schedule (date de publication et compte à rebours - Créateur/Admin Bearer Token)");
+    println!("  - PUT  /api/properties/:id/schedule (programmer la publication - Créateur/Admin Bearer Token)");
+    println!("  - DELETE /api/properties/:id/schedule (annuler la programmation - Créateur/Admin Bearer Token)");
+    println!("  - GET  /api/properties/:id/media (galerie de la propriété - Bearer Token requis)");
+    println!("  - POST /api/properties/:id/media (ajouter un média - Créateur/Admin Bearer Token)");
+    println!("  - POST /api/properties/:id/media/upload (envoyer une image - Créateur/Admin Bearer Token)");
+    println!("  - PUT  /api/properties/:id/media/order (réordonner la galerie - Créateur/Admin Bearer Token)");
+    println!("  - PUT  /api/properties/:id/media/:media_id (modifier un média - Créateur/Admin Bearer Token)");
+    println!("  - DELETE /api/properties/:id/media/:media_id (supprimer un média - Créateur/Admin Bearer Token)");
+    println!("  - POST /api/properties/:id/documents (envoyer un document - Créateur/Admin Bearer Token)");
+    println!("  - GET  /api/properties/:id/documents/:doc_id/download (URL de téléchargement signée - Bearer Token requis)");
+    println!("  - GET  /api/properties/:id/documents/downloads (journal des téléchargements - Créateur/Admin Bearer Token)");
     println!("  - PUT  /api/properties/:id/status (modifier statut - Admin Bearer Token uniquement)");
-    println!("  - DELETE /api/properties/:id (supprimer propriété - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/properties/:id/holders (détenteurs des jetons à un bloc, ?block= - Créateur/Admin Bearer Token)");
+    println!("  - GET  /api/properties/:id/managers (créateur et co-gestionnaires - Créateur/Co-gestionnaire/Admin Bearer Token)");
+    println!("  - POST /api/properties/:id/managers (ajouter un co-gestionnaire ou changer son rôle - Créateur/Owner/Admin Bearer Token)");
+    println!("  - DELETE /api/properties/:id/managers/:user_id (retirer un co-gestionnaire - Créateur/Owner/Admin Bearer Token)");
+    println!("  - GET  /api/properties/:id/distribution-runs (versements enregistrés - Admin Bearer Token)");
+    println!("  - POST /api/properties/:id/distribution-runs (enregistrer un versement des revenus - Admin Bearer Token)");
+    println!("  - GET  /api/properties/:id/deletion-impact (lignes rattachées et suppression possible - Admin Bearer Token uniquement)");
+    println!("  - DELETE /api/properties/:id (supprimer propriété, ?cascade=true avec ses investissements, confirmation requise - Admin Bearer Token uniquement)");
     println!("  - GET  /api/investments (investissements filtrés par rôle - Bearer Token requis)");
-    println!("  - POST /api/investments (créer investissement - Bearer Token requis)");
+    println!("  - POST /api/investments (créer investissement, requête signée optionnelle - Bearer Token requis)");
+    println!("  - POST /api/investments/intent (créer une intention EIP-712 à signer - Bearer Token requis)");
+    println!("  - GET  /api/investments/intent/:id (détail d'une intention - Investisseur/Admin Bearer Token)");
+    println!("  - POST /api/investments/intent/:id/signature (signer une intention - Investisseur Bearer Token)");
+    println!("  - POST /api/investments/fiat-intent (payer un investissement en euros via Stripe - Bearer Token requis)");
     println!("  - GET  /api/investments/:id (détail investissement - Bearer Token requis)");
-    println!("  - PUT  /api/investments/:id (modifier investissement - Admin/Propriétaire Bearer Token)");
+    println!("  - PUT  /api/investments/:id (modifier investissement, correction admin motivée une fois réglé - Admin/Propriétaire Bearer Token)");
+    println!("  - GET  /api/investments/:id/revisions (historique des modifications - Admin/Propriétaire Bearer Token)");
+    println!("  - GET  /api/investments/:id/certificate (certificat vérifiable de l'investissement - Admin/Propriétaire Bearer Token)");
     println!("  - DELETE /api/investments/:id (supprimer investissement - Admin/Propriétaire Bearer Token)");
+    println!("  - POST /api/investments/:id/refund-request (demander un remboursement - Investisseur Bearer Token)");
+    println!("  - GET  /api/refunds (remboursements, tous pour l'admin - Bearer Token requis)");
+    println!("  - GET  /api/notifications (notifications de l'utilisateur - Bearer Token requis)");
+    println!("  - POST /api/notifications/:id/read (marquer une notification comme lue - Bearer Token requis)");
+    println!("  - POST /api/notifications/read-all (tout marquer comme lu - Bearer Token requis)");
+    println!("  - GET  /api/me/notification-preferences (préférences par type d'événement et par canal - Bearer Token requis)");
+    println!("  - PUT  /api/me/notification-preferences (modifier ses préférences de notification - Bearer Token requis)");
+    println!("  - PUT  /api/me/email (adresse des e-mails, résumé hebdomadaire - Bearer Token requis)");
+    println!("  - GET  /api/me/currency (devise préférée, format d'affichage et dernier cours - Bearer Token requis)");
+    println!("  - PUT  /api/me/currency (changer de devise préférée, EUR ou USD - Bearer Token requis)");
+    println!("  - GET  /api/me/tax (pays de résidence fiscale et taux de retenue applicables - Bearer Token requis)");
+    println!("  - GET  /api/me/distributions/accrual (revenus courus non versés par position - Bearer Token requis)");
+    println!("  - PUT  /api/me/country (pays de résidence fiscale, ISO 3166-1 alpha-2 - Bearer Token requis)");
+    println!("  - GET  /api/admin/tax-rules (règles de retenue à la source, ?country= - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/tax-rules (créer une règle de retenue pour un pays - Admin Bearer Token uniquement)");
+    println!("  - PUT  /api/admin/tax-rules/:id (modifier le taux d'une règle - Admin Bearer Token uniquement)");
+    println!("  - DELETE /api/admin/tax-rules/:id (supprimer une règle - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/users/merge (fusionner un doublon dans le compte conservé, confirmation requise - Admin Bearer Token uniquement)");
+    println!("  - GET  /api/admin/trash (corbeille, ?entity=properties|investments - Admin Bearer Token uniquement)");
+    println!("  - POST /api/admin/trash/:entity/:id/restore (restaurer un élément supprimé - Admin Bearer Token uniquement)");
+    println!("  - DELETE /api/admin/trash/:entity/:id/purge (supprimer définitivement, confirmation requise - Admin Bearer Token uniquement)");
+    println!("  - GET/POST /notifications/unsubscribe (désinscription des e-mails, ?token=&kind= - publique)");
+    println!("  - GET  /public/v1/properties (propriétés validées - clé d'API X-API-Key)");
+    println!("  - GET  /public/v1/stats (statistiques agrégées - clé d'API X-API-Key)");
+    println!("  - GET  /public/v1/content/:slug (contenu publié, ?locale= - publique)");
+    println!("  - GET  /public/v1/certificates/:hash/verify (vérifier un certificat d'investissement - publique)");
+    println!("  - GET  /public/v1/widget/:property_id (données du widget embarquable - publique, CORS *)");
 
     // Démarrer le serveur
-    Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await
-        .expect("Failed to start server");
+    match tls {
+        Some(tls) => {
+            if let Some(redirect_port) = tls.redirect_port {
+                tls::spawn_redirect(redirect_port, addr.port());
+            }
+            tls::serve(app, addr, tls).await.expect("Failed to start server");
+        },
+        None => Server::bind(&addr)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+            .expect("Failed to start server"),
+    }
 }
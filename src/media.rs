@@ -0,0 +1,428 @@
+// media.rs
+//
+// Galerie de médias des propriétés : images (dont une couverture), vidéos et
+// visites virtuelles, avec légendes et ordre d'affichage.
+
+use axum::{
+    extract::{BodyStream, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use sqlx::PgPool;
+use url::Url;
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::envelope;
+use crate::models::{
+    CreateMediaRequest, MediaKind, PropertyManagerRole, PropertyMedia, PropertyStatus, ReorderMediaRequest,
+    UpdateMediaRequest, UploadQuery, UserRole,
+};
+use crate::property_managers;
+use crate::storage::StorageConfig;
+use crate::upload::{self, IMAGE_POLICY};
+
+// Hébergeurs vidéo acceptés en plus des fichiers vidéo directs
+const VIDEO_HOSTS: &[&str] = &[
+    "youtube.com", "www.youtube.com", "youtu.be", "vimeo.com", "player.vimeo.com",
+];
+const VIDEO_EXTENSIONS: &[&str] = &[".mp4", ".webm"];
+
+/// Vérifie qu'une URL est cohérente avec le type de média
+pub fn validate_media_url(kind: MediaKind, raw: &str) -> Result<(), String> {
+    let url = Url::parse(raw.trim()).map_err(|_| format!("URL invalide: {}", raw))?;
+    let host = match url.host_str() {
+        Some(host) if matches!(url.scheme(), "http" | "https") => host.to_lowercase(),
+        _ => return Err("L'URL doit être en http(s)".to_string()),
+    };
+
+    match kind {
+        MediaKind::Image => Ok(()),
+        MediaKind::Video => {
+            let path = url.path().to_lowercase();
+            if VIDEO_HOSTS.contains(&host.as_str()) || VIDEO_EXTENSIONS.iter().any(|ext| path.ends_with(ext)) {
+                Ok(())
+            } else {
+                Err("Vidéo non supportée : YouTube, Vimeo ou fichier .mp4/.webm attendu".to_string())
+            }
+        },
+        // Les visites virtuelles sont intégrées en iframe : HTTPS obligatoire
+        MediaKind::VirtualTour if url.scheme() == "https" => Ok(()),
+        MediaKind::VirtualTour => Err("Une visite virtuelle doit être servie en HTTPS".to_string()),
+    }
+}
+
+/// Galerie d'une propriété, dans l'ordre d'affichage
+pub async fn list_media(pool: &PgPool, property_id: Uuid) -> Result<Vec<PropertyMedia>, sqlx::Error> {
+    sqlx::query_as!(
+        PropertyMedia,
+        r#"SELECT id, property_id, kind as "kind: MediaKind", url, caption, position, is_cover, created_at
+           FROM property_media
+           WHERE property_id = $1
+           ORDER BY position, created_at"#,
+        property_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Vérifie l'accès à la propriété (les brouillons des autres restent invisibles,
+/// sauf pour ses co-gestionnaires). En écriture, seuls le créateur, les
+/// co-gestionnaires owner ou editor et l'admin sont autorisés, et une propriété
+/// validée n'est modifiable que par l'admin.
+pub async fn check_access(
+    pool: &PgPool,
+    user: &SessionUser,
+    property_id: Uuid,
+    write: bool,
+) -> Result<(), Response> {
+    let property = sqlx::query!(
+        r#"SELECT p.created_by, p.status as "status: PropertyStatus", pm.role as "manager_role?: PropertyManagerRole"
+           FROM properties p
+           LEFT JOIN property_managers pm ON pm.property_id = p.id AND pm.user_id = $2
+           WHERE p.id = $1"#,
+        property_id,
+        user.id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de la vérification: {}", e)
+    }))).into_response())?;
+
+    let property = match property {
+        Some(prop) if !matches!(prop.status, PropertyStatus::Draft) || prop.created_by == user.id || prop.manager_role.is_some() => prop,
+        _ => return Err((StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response()),
+    };
+
+    if !write {
+        return Ok(());
+    }
+
+    let is_admin = matches!(user.role, UserRole::Admin);
+    if !property_managers::can_edit(user, property.created_by, property.manager_role) {
+        return Err((StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls le créateur, ses co-gestionnaires owner ou editor et l'admin peuvent gérer la galerie"
+        }))).into_response());
+    }
+    if matches!(property.status, PropertyStatus::Validated) && !is_admin {
+        return Err((StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Impossible de modifier une propriété validée par l'admin"
+        }))).into_response());
+    }
+
+    Ok(())
+}
+
+/// Retire la couverture actuelle avant d'en désigner une nouvelle
+async fn clear_cover(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, property_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE property_media SET is_cover = FALSE WHERE property_id = $1 AND is_cover",
+        property_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+/// Ajoute un média en fin de galerie (en retirant l'ancienne couverture si besoin)
+async fn insert_media(
+    pool: &PgPool,
+    property_id: Uuid,
+    kind: MediaKind,
+    url: &str,
+    caption: Option<String>,
+    is_cover: bool,
+) -> Result<PropertyMedia, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    if is_cover {
+        clear_cover(&mut tx, property_id).await?;
+    }
+    let media = sqlx::query_as!(
+        PropertyMedia,
+        r#"INSERT INTO property_media (property_id, kind, url, caption, is_cover, position)
+           VALUES ($1, $2, $3, $4, $5,
+                   (SELECT COALESCE(MAX(position) + 1, 0) FROM property_media WHERE property_id = $1))
+           RETURNING id, property_id, kind as "kind: MediaKind", url, caption, position, is_cover, created_at"#,
+        property_id,
+        kind as MediaKind,
+        url,
+        caption,
+        is_cover
+    )
+    .fetch_one(&mut tx)
+    .await?;
+    tx.commit().await?;
+    Ok(media)
+}
+
+/// Route pour récupérer la galerie d'une propriété (authentification requise)
+pub async fn get_media(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Err(response) = check_access(&pool, &user, property_id, false).await {
+        return response;
+    }
+
+    match list_media(&pool, property_id).await {
+        Ok(media) => envelope::list("media", &media).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour ajouter un média en fin de galerie (créateur ou admin)
+pub async fn add_media(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+    Json(payload): Json<CreateMediaRequest>,
+) -> impl IntoResponse {
+    if let Err(response) = check_access(&pool, &user, property_id, true).await {
+        return response;
+    }
+
+    if let Err(e) = validate_media_url(payload.kind, &payload.url) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e
+        }))).into_response();
+    }
+
+    let is_cover = payload.is_cover.unwrap_or(false);
+    if is_cover && payload.kind != MediaKind::Image {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Seule une image peut servir de couverture"
+        }))).into_response();
+    }
+
+    match insert_media(&pool, property_id, payload.kind, payload.url.trim(), payload.caption, is_cover).await {
+        Ok(media) => (StatusCode::CREATED, Json(serde_json::json!({
+            "media": media,
+            "message": "Média ajouté avec succès"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'ajout: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour envoyer une image dans la galerie (créateur ou admin).
+/// Le corps de la requête est le fichier brut ; `?filename=` est requis.
+pub async fn upload_media(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+    Path(property_id): Path<Uuid>,
+    Query(query): Query<UploadQuery>,
+    headers: HeaderMap,
+    body: BodyStream,
+) -> impl IntoResponse {
+    if let Err(response) = check_access(&pool, &user, property_id, true).await {
+        return response;
+    }
+
+    let storage = match storage {
+        Some(storage) => storage,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Stockage des médias non configuré"
+        }))).into_response(),
+    };
+
+    let file = match upload::receive(&IMAGE_POLICY, &query.filename, &headers, body).await {
+        Ok(file) => file,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    let key = format!("properties/{}/media/{}-{}", property_id, Uuid::new_v4(), file.file_name);
+    let url = match storage.put_media(&key, &file.path, file.content_type).await {
+        Some(Ok(url)) => url,
+        Some(Err(e)) => return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({
+            "error": format!("Erreur lors du dépôt du fichier: {}", e)
+        }))).into_response(),
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Stockage des médias non configuré"
+        }))).into_response(),
+    };
+
+    match insert_media(&pool, property_id, MediaKind::Image, &url, query.caption, query.is_cover.unwrap_or(false)).await {
+        Ok(media) => (StatusCode::CREATED, Json(serde_json::json!({
+            "media": media,
+            "message": "Image ajoutée avec succès"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'ajout: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour modifier un média : URL, légende, couverture (créateur ou admin)
+pub async fn update_media(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path((property_id, media_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateMediaRequest>,
+) -> impl IntoResponse {
+    if let Err(response) = check_access(&pool, &user, property_id, true).await {
+        return response;
+    }
+
+    let kind = match sqlx::query_scalar!(
+        r#"SELECT kind as "kind: MediaKind" FROM property_media WHERE id = $1 AND property_id = $2"#,
+        media_id,
+        property_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(kind)) => kind,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Média non trouvé"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    if let Some(url) = payload.url.as_deref() {
+        if let Err(e) = validate_media_url(kind, url) {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "error": e
+            }))).into_response();
+        }
+    }
+
+    if payload.is_cover == Some(true) && kind != MediaKind::Image {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Seule une image peut servir de couverture"
+        }))).into_response();
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        if payload.is_cover == Some(true) {
+            clear_cover(&mut tx, property_id).await?;
+        }
+        let media = sqlx::query_as!(
+            PropertyMedia,
+            r#"UPDATE property_media SET
+               url = COALESCE($3, url),
+               caption = COALESCE($4, caption),
+               is_cover = COALESCE($5, is_cover)
+               WHERE id = $1 AND property_id = $2
+               RETURNING id, property_id, kind as "kind: MediaKind", url, caption, position, is_cover, created_at"#,
+            media_id,
+            property_id,
+            payload.url.as_deref().map(str::trim),
+            payload.caption,
+            payload.is_cover
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(media)
+    }.await;
+
+    match result {
+        Ok(media) => (StatusCode::OK, Json(serde_json::json!({
+            "media": media,
+            "message": "Média mis à jour avec succès"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour supprimer un média de la galerie (créateur ou admin)
+pub async fn delete_media(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path((property_id, media_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    if let Err(response) = check_access(&pool, &user, property_id, true).await {
+        return response;
+    }
+
+    match sqlx::query!(
+        "DELETE FROM property_media WHERE id = $1 AND property_id = $2",
+        media_id,
+        property_id
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Média non trouvé"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Média supprimé avec succès"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour réordonner la galerie (créateur ou admin).
+/// `media_ids` doit contenir exactement tous les médias de la propriété.
+pub async fn reorder_media(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+    Json(payload): Json<ReorderMediaRequest>,
+) -> impl IntoResponse {
+    if let Err(response) = check_access(&pool, &user, property_id, true).await {
+        return response;
+    }
+
+    let existing = match sqlx::query_scalar!(
+        "SELECT id FROM property_media WHERE property_id = $1",
+        property_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(ids) => ids,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    let mut requested = payload.media_ids.clone();
+    requested.sort();
+    requested.dedup();
+    let mut expected = existing;
+    expected.sort();
+    if requested.len() != payload.media_ids.len() || requested != expected {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "media_ids doit lister chaque média de la propriété exactement une fois"
+        }))).into_response();
+    }
+
+    match sqlx::query!(
+        r#"UPDATE property_media m SET position = (o.ord - 1)::int
+           FROM UNNEST($2::uuid[]) WITH ORDINALITY AS o(id, ord)
+           WHERE m.id = o.id AND m.property_id = $1"#,
+        property_id,
+        &payload.media_ids
+    )
+    .execute(&pool)
+    .await {
+        Ok(_) => match list_media(&pool, property_id).await {
+            Ok(media) => (StatusCode::OK, Json(serde_json::json!({
+                "media": media,
+                "message": "Galerie réordonnée avec succès"
+            }))).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Erreur lors de la récupération: {}", e)
+            }))).into_response(),
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du réordonnancement: {}", e)
+        }))).into_response(),
+    }
+}
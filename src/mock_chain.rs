@@ -0,0 +1,484 @@
+// mock_chain.rs
+//
+// Chaîne simulée pour le développement local : avec `CHAIN_MODE=mock`, le
+// client JSON-RPC (voir chain.rs) est servi par une chaîne en mémoire au lieu
+// d'un nœud, pour dérouler le parcours d'investissement sans testnet. Le
+// relayer y diffuse ses transactions signées comme sur un vrai nœud (nonces,
+// remplacements, reçus) ; un appel `registerProperty` au contrat registre
+// émet `PropertyRegistered` avec un identifiant d'actif incrémental.
+// `POST /dev/chain/invest` simule la transaction d'investissement du wallet
+// connecté (hash déterministe, dérivé du wallet et de son nonce), à
+// enregistrer ensuite par `POST /api/investments` comme après une vraie
+// transaction. Rien n'est miné sans `POST /dev/chain/mine` : les transactions
+// en attente entrent dans le bloc suivant et leurs événements (`Invested`,
+// `TransferSingle`) sont indexés comme ceux reçus par webhook.
+//
+// L'état est propre au processus et perdu au redémarrage : à utiliser avec un
+// seul processus (ni `api` ni `worker` séparés). Réservée aux builds de
+// développement, sauf compilé avec la feature `mock-chain`.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+
+use crate::auth::BearerAuthUser;
+use crate::chain::{ChainRpc, TxLog};
+use crate::eip712::{encode_address, encode_uint, format_address, keccak256, parse_address};
+use crate::error_codes::ErrorCode;
+use crate::indexer;
+use crate::models::{MockInvestRequest, MockMineRequest};
+use crate::onchain_investments::{self, INVESTED_EVENT};
+use crate::registry::{REGISTERED_EVENT, REGISTER_SIGNATURE};
+use crate::relayer::rlp_list;
+use crate::risk::RiskRules;
+use crate::token::{event_topic, parse_token_id, TRANSFER_SINGLE_EVENT};
+
+/// Identifiant de chaîne par défaut (celui des nœuds de développement Hardhat / Anvil)
+const DEFAULT_CHAIN_ID: u64 = 31337;
+const GWEI: u128 = 1_000_000_000;
+/// Solde de chaque adresse : 1000 ETH
+const BALANCE_WEI: u128 = 1000 * GWEI * GWEI;
+/// Blocs minés au plus par appel de `POST /dev/chain/mine`
+const MAX_BLOCKS_PER_CALL: u64 = 1000;
+
+/// Événement émis par une transaction simulée
+struct MockLog {
+    address: String,
+    topics: Vec<String>,
+    data: String,
+}
+
+/// Transaction en attente de minage
+struct MockTx {
+    hash: String,
+    from: String,
+    nonce: u64,
+    logs: Vec<MockLog>,
+}
+
+#[derive(Default)]
+struct MockState {
+    block_number: u64,
+    pending: Vec<MockTx>,
+    /// Transactions minées par expéditeur
+    mined_nonces: HashMap<String, u64>,
+    receipts: HashMap<String, Value>,
+    logs: Vec<Value>,
+    next_asset_id: u64,
+}
+
+impl MockState {
+    /// Nonce attendu de l'expéditeur, transactions en attente comprises ou non
+    fn nonce(&self, address: &str, pending: bool) -> u64 {
+        let mined = self.mined_nonces.get(address).copied().unwrap_or(0);
+        let queued = self.pending.iter().filter(|tx| pending && tx.from == address).map(|tx| tx.nonce + 1).max();
+        queued.unwrap_or(0).max(mined)
+    }
+
+    /// Ajoute la transaction ; une transaction en attente de même nonce est remplacée
+    fn submit(&mut self, tx: MockTx) -> Result<String, String> {
+        if self.receipts.contains_key(&tx.hash) || self.pending.iter().any(|p| p.hash == tx.hash) {
+            return Err("already known".to_string());
+        }
+        if tx.nonce < self.nonce(&tx.from, false) {
+            return Err(format!("nonce too low: {} déjà miné pour {}", tx.nonce, tx.from));
+        }
+        self.pending.retain(|p| !(p.from == tx.from && p.nonce == tx.nonce));
+        let hash = tx.hash.clone();
+        self.pending.push(tx);
+        Ok(hash)
+    }
+}
+
+/// Chaîne simulée, partagée par les clones du client JSON-RPC
+#[derive(Clone)]
+pub struct MockChain {
+    chain_id: u64,
+    registry: Option<String>,
+    token: Option<String>,
+    investment: Option<String>,
+    state: Arc<Mutex<MockState>>,
+}
+
+/// Adresse de contrat lue dans l'environnement, en minuscules
+fn contract_from_env(name: &str) -> Option<String> {
+    env::var(name).ok().and_then(|v| parse_address(&v)).map(|address| format_address(&address))
+}
+
+fn quantity(value: impl Into<u128>) -> Value {
+    Value::String(format!("0x{:x}", value.into()))
+}
+
+fn word(bytes: [u8; 32]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Élément RLP en tête de `bytes` : contenu et longueur encodée totale
+fn rlp_item(bytes: &[u8]) -> Option<(&[u8], usize)> {
+    let first = *bytes.first()?;
+    let long_length = |size: usize| -> Option<usize> {
+        bytes.get(1..1 + size)?.iter().try_fold(0usize, |acc, b| acc.checked_mul(256)?.checked_add(*b as usize))
+    };
+    let (offset, len) = match first {
+        0x00..=0x7f => return Some((&bytes[..1], 1)),
+        0x80..=0xb7 => (1, (first - 0x80) as usize),
+        0xb8..=0xbf => (1 + (first - 0xb7) as usize, long_length((first - 0xb7) as usize)?),
+        0xc0..=0xf7 => (1, (first - 0xc0) as usize),
+        _ => (1 + (first - 0xf7) as usize, long_length((first - 0xf7) as usize)?),
+    };
+    Some((bytes.get(offset..offset + len)?, offset + len))
+}
+
+fn be_u64(bytes: &[u8]) -> Option<u64> {
+    (bytes.len() <= 8).then(|| bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+}
+
+/// Transaction EIP-1559 signée (celles du relayer) : expéditeur retrouvé par
+/// la signature, nonce, destinataire et données d'appel
+fn decode_raw(raw: &[u8]) -> Result<(String, u64, Option<String>, Vec<u8>), String> {
+    let invalid = || "transaction signée mal formée".to_string();
+    if raw.first() != Some(&0x02) {
+        return Err("seules les transactions EIP-1559 (type 2) sont simulées".to_string());
+    }
+    let (payload, _) = rlp_item(&raw[1..]).ok_or_else(invalid)?;
+    let mut items = Vec::new();
+    let mut offset = 0;
+    while offset < payload.len() {
+        let (content, len) = rlp_item(&payload[offset..]).ok_or_else(invalid)?;
+        items.push((content, offset, len));
+        offset += len;
+    }
+    if items.len() != 12 {
+        return Err(invalid());
+    }
+
+    // Signature sur 0x02 || rlp([chain_id, ..., access_list])
+    let unsigned_end = items[8].1 + items[8].2;
+    let mut unsigned = vec![0x02];
+    unsigned.extend(rlp_list(&[payload[..unsigned_end].to_vec()]));
+    let scalar = |bytes: &[u8]| -> Option<[u8; 32]> {
+        let mut padded = [0u8; 32];
+        padded.get_mut(32usize.checked_sub(bytes.len())?..)?.copy_from_slice(bytes);
+        Some(padded)
+    };
+    let signature = Signature::from_scalars(scalar(items[10].0).ok_or_else(invalid)?, scalar(items[11].0).ok_or_else(invalid)?)
+        .map_err(|_| invalid())?;
+    let recovery_id = RecoveryId::from_byte(be_u64(items[9].0).ok_or_else(invalid)? as u8).ok_or_else(invalid)?;
+    let key = VerifyingKey::recover_from_prehash(&keccak256(&unsigned), &signature, recovery_id)
+        .map_err(|_| "signature invalide".to_string())?;
+    let point = key.to_encoded_point(false);
+    let from: [u8; 20] = keccak256(&point.as_bytes()[1..])[12..].try_into().map_err(|_| invalid())?;
+
+    let to = <[u8; 20]>::try_from(items[5].0).ok().map(|to| format_address(&to));
+    Ok((format_address(&from), be_u64(items[1].0).ok_or_else(invalid)?, to, items[7].0.to_vec()))
+}
+
+impl MockChain {
+    /// `None` sans `CHAIN_MODE=mock`, ou dans un build release compilé sans la
+    /// feature `mock-chain`
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("CHAIN_MODE").is_ok_and(|v| v.trim().eq_ignore_ascii_case("mock"));
+        if !enabled {
+            return None;
+        }
+        if !cfg!(any(debug_assertions, feature = "mock-chain")) {
+            println!("⚠️  CHAIN_MODE=mock ignoré : build release compilé sans la feature `mock-chain`");
+            return None;
+        }
+
+        Some(Self {
+            chain_id: env::var("CHAIN_ID").ok().and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_CHAIN_ID),
+            registry: contract_from_env("REGISTRY_CONTRACT_ADDRESS"),
+            token: contract_from_env("PROPERTY_TOKEN_ADDRESS"),
+            investment: contract_from_env("INVESTMENT_CONTRACT_ADDRESS"),
+            state: Arc::new(Mutex::new(MockState { next_asset_id: 1, ..Default::default() })),
+        })
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Méthode JSON-RPC servie par la chaîne simulée
+    pub fn call(&self, method: &str, params: &Value) -> Result<Value, String> {
+        let param = |index: usize| params.get(index).unwrap_or(&Value::Null);
+        let address = |value: &Value| value.as_str().unwrap_or_default().to_lowercase();
+        match method {
+            "eth_chainId" => Ok(quantity(self.chain_id)),
+            "eth_blockNumber" => Ok(quantity(self.state().block_number)),
+            "eth_getTransactionCount" => {
+                let pending = param(1).as_str() == Some("pending");
+                Ok(quantity(self.state().nonce(&address(param(0)), pending)))
+            },
+            "eth_getBalance" => Ok(quantity(BALANCE_WEI)),
+            "eth_getBlockByNumber" => Ok(serde_json::json!({
+                "number": quantity(self.state().block_number),
+                "baseFeePerGas": quantity(GWEI)
+            })),
+            "eth_gasPrice" => Ok(quantity(GWEI * 5 / 2)),
+            "eth_maxPriorityFeePerGas" => Ok(quantity(GWEI * 3 / 2)),
+            "eth_estimateGas" => Ok(quantity(100_000u64)),
+            // Aucun contrat n'est exécuté : un appel en lecture renvoie un mot nul
+            // (ex. oracle de sanctions : wallet non listé)
+            "eth_call" => Ok(word([0u8; 32]).into()),
+            "eth_sendRawTransaction" => {
+                let raw = hex::decode(param(0).as_str().unwrap_or_default().trim_start_matches("0x"))
+                    .map_err(|_| "transaction signée invalide".to_string())?;
+                self.send_raw(&raw).map(Value::String)
+            },
+            "eth_getTransactionReceipt" => {
+                Ok(self.state().receipts.get(&address(param(0))).cloned().unwrap_or(Value::Null))
+            },
+            "eth_getLogs" => {
+                let filter = param(0);
+                let block = |key: &str| filter[key].as_str().and_then(|v| u64::from_str_radix(v.trim_start_matches("0x"), 16).ok());
+                let (from_block, to_block) = (block("fromBlock").unwrap_or(0), block("toBlock").unwrap_or(u64::MAX));
+                let topics: Vec<String> = filter["topics"][0].as_array()
+                    .map(|topics| topics.iter().map(address).collect())
+                    .unwrap_or_default();
+                let contract = address(&filter["address"]);
+                let logs = self.state().logs.iter()
+                    .filter(|log| log["address"] == contract.as_str())
+                    .filter(|log| topics.is_empty() || topics.iter().any(|t| log["topics"][0] == t.as_str()))
+                    .filter(|log| TxLog::from_json(log).block_number.is_some_and(|b| (from_block..=to_block).contains(&b)))
+                    .cloned()
+                    .collect();
+                Ok(Value::Array(logs))
+            },
+            _ => Err(format!("méthode non simulée par CHAIN_MODE=mock: {}", method)),
+        }
+    }
+
+    fn send_raw(&self, raw: &[u8]) -> Result<String, String> {
+        let (from, nonce, to, data) = decode_raw(raw)?;
+        let mut state = self.state();
+
+        let mut logs = Vec::new();
+        let registers = data.starts_with(&keccak256(REGISTER_SIGNATURE.as_bytes())[..4]);
+        if let (Some(registry), true) = (&self.registry, registers) {
+            if to.as_ref() == Some(registry) {
+                // PropertyRegistered(uint256 indexed assetId, string onchainId) : la
+                // chaîne est encodée comme dans l'appel
+                logs.push(MockLog {
+                    address: registry.clone(),
+                    topics: vec![event_topic(REGISTERED_EVENT), word(encode_uint(state.next_asset_id as u128))],
+                    data: format!("0x{}", hex::encode(&data[4..])),
+                });
+                state.next_asset_id += 1;
+            }
+        }
+
+        let hash = format!("0x{}", hex::encode(keccak256(raw)));
+        state.submit(MockTx { hash, from, nonce, logs })
+    }
+
+    /// Transaction d'investissement de `wallet` : `Invested` sur le contrat
+    /// d'investissement et mint des jetons (`TransferSingle`), selon les
+    /// contrats configurés. Le hash est dérivé du wallet et de son nonce.
+    pub fn invest(&self, wallet: &str, token_id: [u8; 32], shares: u32) -> Result<(String, u64), String> {
+        let wallet_bytes = parse_address(wallet).ok_or_else(|| "wallet invalide".to_string())?;
+        let mut state = self.state();
+        let nonce = state.nonce(wallet, true);
+
+        let mut logs = Vec::new();
+        if let Some(investment) = &self.investment {
+            logs.push(MockLog {
+                address: investment.clone(),
+                topics: vec![event_topic(INVESTED_EVENT), word(encode_address(&wallet_bytes)), word(token_id)],
+                data: word(encode_uint(shares as u128)),
+            });
+        }
+        if let Some(token) = &self.token {
+            let operator = self.investment.as_deref().and_then(parse_address).unwrap_or(wallet_bytes);
+            logs.push(MockLog {
+                address: token.clone(),
+                topics: vec![
+                    event_topic(TRANSFER_SINGLE_EVENT),
+                    word(encode_address(&operator)),
+                    word([0u8; 32]),
+                    word(encode_address(&wallet_bytes)),
+                ],
+                data: format!("0x{}{}", hex::encode(token_id), hex::encode(encode_uint(shares as u128))),
+            });
+        }
+
+        let hash = format!("0x{}", hex::encode(keccak256(format!("mock-tx:{}:{}", wallet, nonce).as_bytes())));
+        state.submit(MockTx { hash: hash.clone(), from: wallet.to_string(), nonce, logs })?;
+        Ok((hash, nonce))
+    }
+
+    /// Mine les transactions en attente dans le bloc suivant, puis `blocks - 1`
+    /// blocs vides. Renvoie le dernier bloc, les transactions minées et leurs événements.
+    pub fn mine(&self, blocks: u64) -> (u64, Vec<String>, Vec<TxLog>) {
+        let mut state = self.state();
+        let block_number = state.block_number + 1;
+        let mut pending = std::mem::take(&mut state.pending);
+        pending.sort_by_key(|tx| tx.nonce);
+
+        let (mut mined, mut logs, mut log_index) = (Vec::new(), Vec::new(), 0u64);
+        for tx in pending {
+            // Une transaction dont un nonce précédent manque reste en attente
+            if tx.nonce != state.nonce(&tx.from, false) {
+                state.pending.push(tx);
+                continue;
+            }
+            let tx_logs: Vec<Value> = tx.logs.iter().map(|log| {
+                let json = serde_json::json!({
+                    "address": log.address,
+                    "topics": log.topics,
+                    "data": log.data,
+                    "blockNumber": quantity(block_number),
+                    "transactionHash": tx.hash,
+                    "logIndex": quantity(log_index),
+                    "removed": false
+                });
+                log_index += 1;
+                json
+            }).collect();
+
+            state.receipts.insert(tx.hash.clone(), serde_json::json!({
+                "transactionHash": tx.hash,
+                "status": "0x1",
+                "blockNumber": quantity(block_number),
+                "logs": tx_logs
+            }));
+            state.mined_nonces.insert(tx.from.clone(), tx.nonce + 1);
+            logs.extend(tx_logs.iter().map(TxLog::from_json));
+            state.logs.extend(tx_logs);
+            mined.push(tx.hash);
+        }
+
+        state.block_number += blocks.max(1);
+        (state.block_number, mined, logs)
+    }
+}
+
+fn error_response(status: StatusCode, code: ErrorCode, error: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": error, "code": code }))).into_response()
+}
+
+/// Réponse des routes `/dev/chain/*` sans `CHAIN_MODE=mock`
+fn inactive() -> Response {
+    error_response(StatusCode::NOT_FOUND, ErrorCode::NotFound, "Chaîne simulée inactive (CHAIN_MODE=mock)")
+}
+
+/// POST /dev/chain/invest - Transaction d'investissement simulée, depuis le
+/// wallet connecté (ou `from`), en attente de minage
+pub async fn simulate_investment(
+    State(pool): State<PgPool>,
+    Extension(rpc): Extension<Option<ChainRpc>>,
+    BearerAuthUser(user): BearerAuthUser,
+    Json(payload): Json<MockInvestRequest>,
+) -> Response {
+    let Some(mock) = rpc.as_ref().and_then(ChainRpc::mock) else {
+        return inactive();
+    };
+    let shares = match u32::try_from(payload.shares) {
+        Ok(shares) if shares > 0 => shares,
+        _ => return error_response(StatusCode::BAD_REQUEST, ErrorCode::InvalidAmount, "Nombre de parts non positif"),
+    };
+
+    let onchain_id = match sqlx::query_scalar!("SELECT onchain_id FROM properties WHERE id = $1", payload.property_id)
+        .fetch_optional(&pool)
+        .await
+    {
+        Ok(Some(onchain_id)) => onchain_id,
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, ErrorCode::PropertyNotFound, "Propriété non trouvée"),
+        Err(e) => return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::DatabaseError,
+            &format!("Erreur lors de la lecture de la propriété: {}", e),
+        ),
+    };
+    // Le contrat identifie la propriété par son actif (uint256) : attribué par le registre
+    let Some(token_id) = parse_token_id(&onchain_id) else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidOnchainId,
+            &format!("Identifiant on-chain non numérique ({}) : propriété pas encore enregistrée sur le registre", onchain_id),
+        );
+    };
+
+    let wallet = payload.from.unwrap_or(user.wallet);
+    match mock.invest(wallet.as_str(), token_id, shares) {
+        Ok((tx_hash, nonce)) => (StatusCode::CREATED, Json(serde_json::json!({
+            "tx_hash": tx_hash,
+            "from": wallet,
+            "nonce": nonce,
+            "onchain_id": onchain_id,
+            "shares": shares,
+            "status": "pending"
+        }))).into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, ErrorCode::BadRequest, &e),
+    }
+}
+
+/// POST /dev/chain/mine - Mine les transactions en attente et indexe leurs événements
+pub async fn mine_blocks(
+    State(pool): State<PgPool>,
+    Extension(rpc): Extension<Option<ChainRpc>>,
+    Extension(rules): Extension<RiskRules>,
+    payload: Option<Json<MockMineRequest>>,
+) -> Response {
+    let Some(mock) = rpc.as_ref().and_then(ChainRpc::mock) else {
+        return inactive();
+    };
+    let blocks = payload.and_then(|Json(p)| p.blocks).map_or(1, u64::from);
+    if !(1..=MAX_BLOCKS_PER_CALL).contains(&blocks) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::BadRequest,
+            &format!("blocks doit être compris entre 1 et {}", MAX_BLOCKS_PER_CALL),
+        );
+    }
+
+    let (block_number, mined, logs) = mock.mine(blocks);
+
+    // Indexation, comme pour les événements reçus par webhook
+    let transfers = match &mock.token {
+        Some(token) => match indexer::ingest(&pool, "mock", token, &logs, Some(block_number)).await {
+            Ok(stats) => Some(stats.inserted),
+            Err(e) => return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DatabaseError,
+                &format!("Blocs minés mais erreur lors de l'indexation: {}", e),
+            ),
+        },
+        None => None,
+    };
+    let investments = match onchain_investments::investment_address_from_env() {
+        Some(address) => match onchain_investments::ingest(&pool, &rules, "mock", &address, &logs).await {
+            Ok(stats) => Some(stats.inserted),
+            Err(e) => return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::DatabaseError,
+                &format!("Blocs minés mais erreur lors de l'indexation: {}", e),
+            ),
+        },
+        None => None,
+    };
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "block_number": block_number,
+        "transactions": mined,
+        "events": logs.len(),
+        "indexed": {
+            "transfers": transfers,
+            "investments": investments
+        }
+    }))).into_response()
+}
@@ -1,109 +1,772 @@
 // models.rs
 
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use bigdecimal::BigDecimal;
 
-// Enum pour les rôles utilisateur
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "user_role", rename_all = "lowercase")]
-pub enum UserRole {
-    User,
-    Manager,
-    Admin,
-}
-
-impl std::fmt::Display for UserRole {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            UserRole::User => write!(f, "user"),
-            UserRole::Manager => write!(f, "manager"),
-            UserRole::Admin => write!(f, "admin"),
-        }
-    }
-}
-
-impl From<String> for UserRole {
-    fn from(s: String) -> Self {
-        match s.to_lowercase().as_str() {
-            "manager" => UserRole::Manager,
-            "admin" => UserRole::Admin,
-            _ => UserRole::User,
-        }
-    }
-}
-
-// Enum pour le statut des propriétés
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "property_status", rename_all = "lowercase")]
-pub enum PropertyStatus {
-    Pending,
-    Validated,
+use crate::money::{AmountInput, Money, TokenAmount, Wei};
+use crate::wallet_address::WalletAddress;
+
+// Ressources partagées avec les clients (crate `pa-backend-types`)
+pub use pa_backend_types::models::{Amenities, Investment, InvestmentStatus, Property, PropertyStatus, UserRole};
+
+// Enum pour le type de média de la galerie
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "media_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    Image,
+    Video,
+    VirtualTour,
+}
+
+// Enum pour le rôle d'un co-gestionnaire de propriété
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "property_manager_role", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyManagerRole {
+    Owner,
+    Editor,
+    Viewer,
+}
+
+// Enum pour l'état d'une transaction du relayer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "relay_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RelayStatus {
+    Queued,    // En attente d'envoi (ou de nouvel essai)
+    Submitted, // Diffusée, en attente de minage
+    Confirmed, // Minée avec succès
+    Failed,    // Abandonnée ou annulée par le contrat (revert)
+}
+
+// Enum pour le suivi d'une demande de remboursement
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "refund_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RefundStatus {
+    Requested, // Demandé par l'investisseur
+    Approved,  // Approuvé par un admin, en attente du paiement
+    Rejected,  // Refusé par un admin
+    Paid,      // Remboursé (tx_hash renseigné)
+}
+
+// Enum pour la vérification d'identité (KYC)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "kyc_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum KycStatus {
+    NotStarted,            // Dossier créé, documents non envoyés
+    Pending,               // Documents envoyés, en cours d'examen
+    Approved,
+    Rejected,              // Refus définitif
+    ResubmissionRequested, // Refus temporaire : documents à renvoyer
+}
+
+// Enum pour la revue d'un signalement de conformité
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "flag_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum FlagStatus {
+    Open,      // À examiner (investissements bloqués)
+    Cleared,   // Faux positif, levé par un admin
+    Confirmed, // Confirmé (investissements bloqués)
+}
+
+// Enum pour la gravité des alertes d'activité suspecte
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "alert_severity", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+// Enum pour le traitement des alertes d'activité suspecte
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "alert_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AlertStatus {
+    Open,         // À traiter
+    Acknowledged, // Pris en compte par un admin
+}
+
+// Enum pour les documents légaux à accepter avant d'investir
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "legal_document_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LegalDocumentKind {
+    Terms,          // Conditions générales d'utilisation
+    RiskDisclosure, // Avertissement sur les risques
+}
+
+// Enum pour l'accréditation des investisseurs qualifiés
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "accreditation_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AccreditationStatus {
+    Pending,  // Justificatifs en attente de revue
+    Approved, // Accrédité jusqu'à expires_at
     Rejected,
+    Expired,  // Approuvé mais échu (déduit de expires_at)
+}
+
+// Enums des rapports générés en tâche de fond
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "report_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    Investments,
+    Users,
+    Properties,
+    Refunds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "report_format", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Csv,
+    Pdf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "report_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
 }
 
-impl std::fmt::Display for PropertyStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            PropertyStatus::Pending => write!(f, "pending"),
-            PropertyStatus::Validated => write!(f, "validated"),
-            PropertyStatus::Rejected => write!(f, "rejected"),
-        }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "job_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Dead,
 }
 
-impl From<String> for PropertyStatus {
-    fn from(s: String) -> Self {
-        match s.to_lowercase().as_str() {
-            "validated" => PropertyStatus::Validated,
-            "rejected" => PropertyStatus::Rejected,
-            _ => PropertyStatus::Pending,
-        }
-    }
+// Enum du rapprochement des investissements observés on-chain
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "onchain_investment_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OnchainInvestmentStatus {
+    Matched,   // Investissement créé (ou déjà enregistré) à la réception
+    Unmatched, // Wallet ou propriété non rapprochés, en attente d'un admin
+    Resolved,  // Rapproché par un admin
+    Dismissed, // Écarté par un admin
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+// Enum des tables soumises à une durée de conservation (archivage)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "retention_entity", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionEntity {
+    Notifications,
+    DocumentDownloads,
+    AuthFailures,
+    RequestLogs,
+}
+
+/// Entités dont la suppression passe par la corbeille (voir trash.rs)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashEntity {
+    Properties,
+    Investments,
+}
+
+// Enum pour l'état d'un paiement en euros (PaymentIntent Stripe)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, TS)]
+#[ts(export)]
+#[sqlx(type_name = "fiat_payment_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum FiatPaymentStatus {
+    RequiresPayment,
+    Succeeded,
+    Failed,
+    Canceled,
+    Oversold, // Paiement reçu alors que les parts étaient épuisées, à rembourser
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
 pub struct User {
     pub id: Uuid,
-    pub wallet: String,
+    pub tenant_id: Uuid,
+    pub wallet: WalletAddress,
     pub name: Option<String>,
     pub role: UserRole,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
-pub struct Property {
+/// Wallet secondaire rattaché à un utilisateur (voir wallets.rs)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct UserWallet {
+    pub wallet: WalletAddress,
+    pub verified_at: Option<DateTime<Utc>>, // None : signature attendue
+    pub created_at: DateTime<Utc>,
+}
+
+/// Élément de la galerie d'une propriété
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct PropertyMedia {
+    pub id: Uuid,
+    pub property_id: Uuid,
+    pub kind: MediaKind,
+    pub url: String,
+    pub caption: Option<String>,
+    pub position: i32,
+    pub is_cover: bool,        // Une seule couverture (image) par propriété
+    pub created_at: DateTime<Utc>,
+}
+
+/// Tag du vocabulaire géré par l'admin (ex: "seafront", "renovated")
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct Tag {
+    pub id: Uuid,
+    pub slug: String,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Demande de remboursement d'un investissement
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct InvestmentRefund {
+    pub id: Uuid,
+    pub investment_id: Uuid,
+    pub user_id: Uuid,
+    pub reason: Option<String>,
+    pub status: RefundStatus,
+    pub amount_eth: TokenAmount,
+    pub amount_wei: Wei,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_comment: Option<String>,
+    pub tx_hash: Option<String>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Vérification d'identité (KYC) d'un utilisateur
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct KycVerification {
+    pub user_id: Uuid,
+    pub provider: String,
+    pub applicant_id: Option<String>,
+    pub status: KycStatus,
+    pub review_answer: Option<String>,
+    pub reject_labels: Vec<String>,
+    pub moderation_comment: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub overridden_by: Option<Uuid>,
+    pub override_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Signalement du filtrage des wallets (sanctions, listes de blocage)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct ComplianceFlag {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub wallet: String,
+    pub source: String,
+    pub reason: String,
+    pub status: FlagStatus,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Alerte d'activité suspecte levée par le moteur de règles
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct RiskAlert {
+    pub id: Uuid,
+    pub rule: String,
+    pub severity: AlertSeverity,
+    pub subject: String,
+    pub user_id: Option<Uuid>,
+    pub details: serde_json::Value,
+    pub occurrences: i32,
+    pub status: AlertStatus,
+    pub acknowledged_by: Option<Uuid>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+/// Note d'un admin sur une alerte
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct RiskAlertNote {
     pub id: Uuid,
+    pub alert_id: Uuid,
+    pub author_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Version d'un document légal
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct LegalDocument {
+    pub id: Uuid,
+    pub kind: LegalDocumentKind,
+    pub version: String,
+    pub title: String,
+    pub content: String,
+    pub created_by: Option<Uuid>,
+    pub published_at: DateTime<Utc>,
+}
+
+/// Contenu éditorial (FAQ, avertissement, annonce) rédigé en Markdown
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct Content {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub slug: String,
+    pub locale: String,
+    pub title: String,
+    pub body: String,
+    pub published: bool,
+    pub created_by: Option<Uuid>,
+    pub updated_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Modification enregistrée d'un investissement
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct InvestmentRevision {
+    pub id: Uuid,
+    pub investment_id: Uuid,
+    pub changed_by: Option<Uuid>,
+    pub reason: Option<String>,
+    pub changes: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Bannière d'annonce ciblée (`audience` : all, investors, managers)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct Announcement {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub title: String,
+    pub body: String,
+    pub audience: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Certificat d'un investissement confirmé (voir certificates.rs)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct InvestmentCertificate {
+    pub id: Uuid,
+    pub investment_id: Uuid,
+    pub hash: String,
+    pub wallet: String,
     pub onchain_id: String,
-    pub name: String,
-    pub location: String,
-    pub property_type: String,  // Mappé depuis la colonne "type"
+    pub shares: i32,
+    pub tx_hash: String,
+    pub issued_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Fusion de deux comptes enregistrée (voir user_merges.rs)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct UserMerge {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub survivor_id: Uuid,
+    pub duplicate_id: Option<Uuid>,
+    pub duplicate_wallet: String,
+    pub merged_by: Option<Uuid>,
+    pub reason: Option<String>,
+    pub moved: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Taux de retenue à la source d'un pays pour un type de revenu
+/// (`applies_to` : distributions, capital_gains)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct TaxRule {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub country: String,
+    pub rate: BigDecimal, // Pourcentage
+    pub applies_to: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Accréditation d'un investisseur qualifié
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct Accreditation {
+    pub user_id: Uuid,
+    pub status: AccreditationStatus,
+    pub documents: Vec<String>,
+    pub submitted_at: DateTime<Utc>,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_note: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Rapport demandé par un admin (fichier déposé dans le bucket privé une fois généré)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct Report {
+    pub id: Uuid,
+    pub kind: ReportKind,
+    pub format: ReportFormat,
+    pub from_date: Option<DateTime<Utc>>,
+    pub to_date: Option<DateTime<Utc>>,
+    pub status: ReportStatus,
+    pub storage_key: Option<String>,
+    pub row_count: Option<i32>,
+    pub size: Option<i64>,
+    pub error: Option<String>,
+    pub requested_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Sauvegarde logique demandée par un admin (fichier SQL déposé dans le bucket privé une fois exporté)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct Backup {
+    pub id: Uuid,
+    pub status: ReportStatus,
+    pub comment: Option<String>,
+    pub tables: Vec<String>,
+    pub storage_key: Option<String>,
+    pub row_count: Option<i64>,
+    pub size: Option<i64>,
+    pub error: Option<String>,
+    pub requested_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Tâche de la file durable (voir jobs.rs)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: JobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+    pub locked_by: Option<String>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Événement `Invested` observé on-chain (voir onchain_investments.rs)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct OnchainInvestment {
+    pub id: Uuid,
+    pub tx_hash: String,
+    pub log_index: i32,
+    pub block_number: i64,
+    pub wallet: String,
+    pub onchain_property_id: String,
+    pub shares: i32,
+    pub source: String,
+    pub status: OnchainInvestmentStatus,
+    pub reason: Option<String>,
+    pub investment_id: Option<Uuid>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Requête journalisée sur une route financièrement sensible
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct RequestLog {
+    pub id: Uuid,
+    pub route_group: String,
+    pub method: String,
+    pub path: String,
+    pub route: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub status: i32,
+    pub request_body: Option<serde_json::Value>,
+    pub ip: Option<String>,
+    pub scheme: Option<String>,
+    pub duration_ms: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Écart constaté par la double écriture (voir dual_write.rs)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct DualWriteDivergence {
+    pub id: Uuid,
+    pub change_id: i64,
+    pub table_name: String,
+    pub row_id: Uuid,
+    pub kind: String,
+    pub expected: Option<serde_json::Value>,
+    pub actual: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Écart constaté par une lecture fantôme (voir shadow_reads.rs)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct ShadowReadDiff {
+    pub id: Uuid,
+    pub endpoint: String,
+    pub request_key: String,
+    pub differences: serde_json::Value,
+    pub error: Option<String>,
+    pub legacy_ms: i32,
+    pub shadow_ms: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Drapeau de fonctionnalité (voir flags.rs)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct FeatureFlag {
+    pub key: String,
     pub description: Option<String>,
-    pub total_price: BigDecimal,  // NOT NULL dans la DB
-    pub token_price: BigDecimal,  // NOT NULL dans la DB  
-    pub annual_yield: BigDecimal, // NOT NULL dans la DB
-    pub image_url: Option<String>,
-    pub documents: Option<Vec<String>>,
-    pub created_by: Uuid,         // NOT NULL dans la DB
+    pub enabled: bool,
+    pub environments: Vec<String>, // Vide : tous les environnements
+    pub roles: Vec<String>,        // 'user', 'manager', 'admin' ; vide : tous les rôles
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
-    pub status: PropertyStatus,
-    pub status_updated_at: Option<DateTime<Utc>>,
-    pub status_updated_by: Option<Uuid>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
-pub struct Investment {
+/// Plateforme (marque) servie par l'instance (voir tenants.rs)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct Tenant {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub hostnames: Vec<String>,    // Noms d'hôte résolus vers cette plateforme
+    pub cors_origins: Vec<String>, // Origines autorisées ; "*" : toutes
+    pub branding: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Durée de conservation d'une table avant archivage
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct RetentionPolicy {
+    pub entity: RetentionEntity,
+    pub retention_days: i32,
+    pub enabled: bool,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_archived: Option<i32>,
+}
+
+/// Limite d'exposition : générale (`user_id` absent) ou dérogation individuelle,
+/// globale (`property_id` absent) ou propre à une propriété
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct ExposureLimit {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub property_id: Option<Uuid>,
+    pub max_eth: Option<BigDecimal>,
+    pub max_pct: Option<BigDecimal>,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Notification in-app d'un utilisateur
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub message: String,
+    pub data: serde_json::Value,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Paiement en euros d'un investissement via Stripe
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct FiatPayment {
     pub id: Uuid,
     pub user_id: Uuid,
     pub property_id: Uuid,
-    pub amount_eth: BigDecimal,
     pub shares: i32,
-    pub tx_hash: String,
+    pub amount_eur: Money,
+    pub amount_eth: TokenAmount,
+    pub amount_wei: Wei,
+    pub eur_per_eth: BigDecimal,
+    pub stripe_payment_intent_id: Option<String>,
+    pub status: FiatPaymentStatus,
+    pub investment_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Intention d'investissement EIP-712 (cotation pré-autorisée par l'investisseur)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct InvestmentIntent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub property_id: Uuid,
+    pub investor: String,
+    pub shares: i32,
+    pub price_per_share_wei: Wei,
+    pub amount_eth: TokenAmount,
+    pub amount_wei: Wei,
+    pub nonce: i64,
+    pub deadline: DateTime<Utc>,
+    pub digest: String,
+    pub signature: Option<String>,
+    pub signed_at: Option<DateTime<Utc>>,
+    pub investment_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Transaction de la file du relayer
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct PendingTx {
+    pub id: Uuid,
+    pub kind: String,
+    pub property_id: Option<Uuid>,
+    pub to_address: String,
+    pub data: String,
+    pub value_wei: BigDecimal,
+    pub status: RelayStatus,
+    pub from_address: Option<String>,
+    pub nonce: Option<i64>,
+    pub gas_limit: Option<i64>,
+    pub max_fee_per_gas: Option<BigDecimal>,
+    pub max_priority_fee_per_gas: Option<BigDecimal>,
+    pub tx_hash: Option<String>,
+    pub tx_hashes: Vec<String>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub block_number: Option<i64>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Clé d'API partenaire (le hash de la clé n'est jamais exposé)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub key_prefix: String,
+    pub daily_quota: i32,
+    pub is_active: bool,
+    pub created_by: Uuid,
     pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+/// Jeton d'API personnel (la valeur en clair n'est jamais stockée)
+#[derive(Debug, Serialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct UserToken {
+    pub id: Uuid,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Demande de changement de rôle en attente d'un second admin
+#[derive(Debug, Serialize, sqlx::FromRow, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct RoleChangeRequest {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub previous_role: UserRole,
+    pub requested_role: UserRole,
+    pub requested_by: Option<Uuid>,
+    pub status: String,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
 pub struct Session {
     pub token: Uuid,
     pub user_id: Uuid,
@@ -112,21 +775,50 @@ pub struct Session {
 
 // Structures pour les requêtes API
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
 pub struct CreateUserRequest {
-    pub wallet: String,
+    pub wallet: WalletAddress,
     pub name: String,
     pub role: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
 pub struct UpdateUserRoleRequest {
     pub role: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct LinkWalletRequest {
+    pub wallet: WalletAddress,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct TwoFactorCodeRequest {
+    pub code: String, // Code TOTP à 6 chiffres ou code de secours
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ConfirmActionRequest {
+    pub challenge_id: Uuid,
+    pub signature: Option<String>, // personal_sign du message du défi (si CONFIRMATIONS_REQUIRE_SIGNATURE)
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct VerifyWalletRequest {
+    pub signature: String, // personal_sign du message de défi
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
 pub struct CreatePropertyRequest {
     pub onchain_id: String,
+    pub contract_address: Option<String>, // Doit être le contrat registre configuré ; inchangé en modification si absent
     pub name: String,
     pub location: String,
     pub property_type: String,
@@ -136,25 +828,798 @@ pub struct CreatePropertyRequest {
     pub annual_yield: BigDecimal,       // Requis
     pub image_url: Option<String>,
     pub documents: Option<serde_json::Value>,
+    pub amenities: Option<Amenities>,   // Conservés tels quels si absents lors d'une modification
+    pub requires_accreditation: Option<bool>, // Faux à la création, inchangé en modification si absent
+    pub tags: Option<Vec<String>>,      // Slugs du vocabulaire ; remplacent les tags existants
+}
+
+/// Paramètres de requête pour `GET /api/properties`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct PropertyListQuery {
+    pub ids: Option<String>,     // Liste d'UUID séparés par des virgules
+    pub include: Option<String>, // Relations à inclure (investments_summary, manager, tags, media)
+    pub fields: Option<String>,  // Champs à renvoyer (ex: id,name,token_price)
+    pub tags: Option<String>,    // Slugs de tags séparés par des virgules (tous requis)
+    pub country: Option<String>, // Pays géocodé (ISO 3166-1 alpha-2, ex: FR)
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub property_type: Option<String>,
+    pub yield_bucket: Option<String>, // Tranche de rendement annuel : 0-3, 3-5, 5-8 ou 8+
+}
+
+/// Paramètres de requête pour `GET /api/properties/by-onchain/:id`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct OnchainLookupQuery {
+    pub contract: Option<String>, // Contrat registre attendu (optionnel)
+}
+
+/// Paramètres de requête pour `GET /properties/public`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct PublicPropertyQuery {
+    pub tags: Option<String>,
+}
+
+/// Paramètres de requête pour `GET /api/properties/facets` : les filtres de
+/// `GET /api/properties`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct PropertyFacetQuery {
+    pub tags: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub property_type: Option<String>,
+    pub yield_bucket: Option<String>,
+}
+
+/// Valeur d'une facette et nombre de propriétés correspondantes
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Facettes des propriétés visibles (voir facets.rs) : chacune est comptée avec
+/// tous les filtres demandés sauf le sien ; `total` applique tous les filtres
+#[derive(Debug, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PropertyFacets {
+    pub country: Vec<FacetCount>,
+    pub region: Vec<FacetCount>,
+    pub city: Vec<FacetCount>,
+    pub property_type: Vec<FacetCount>,
+    pub yield_bucket: Vec<FacetCount>,
+    pub total: i64,
+}
+
+/// Projection d'un investissement dans une propriété (voir calculator.rs)
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct YieldCalculation {
+    pub property_id: Uuid,
+    pub amount_eth: TokenAmount,          // Montant saisi
+    pub token_price: BigDecimal,
+    pub shares: i64,                      // Parts entières achetables
+    pub invested_eth: TokenAmount,        // Prix des parts (exact)
+    pub remainder_eth: TokenAmount,       // Reste non investi (exact)
+    pub available_shares: i64,
+    pub exceeds_available: bool,          // Plus de parts que celles encore disponibles
+    pub annual_yield: BigDecimal,         // Pourcentage
+    pub annual_income_eth: TokenAmount,   // Estimation, 6 décimales
+    pub monthly_income_eth: TokenAmount,  // Estimation, 6 décimales
+    pub withholding_rate: Option<BigDecimal>, // Retenue à la source du pays de l'utilisateur (%)
+    pub net_annual_income_eth: TokenAmount,
+    pub payback_years: Option<BigDecimal>, // 2 décimales ; nul sans rendement
+    pub payback_months: Option<i64>,      // Mois entiers, arrondi supérieur
+    pub fiat: serde_json::Value,          // Contre-valeurs dans la devise préférée, nulles sans cours
+}
+
+/// Versement des revenus d'une propriété jusqu'à `paid_through` (voir distributions.rs)
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, TS)]
+#[ts(export)]
+pub struct DistributionRun {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub property_id: Uuid,
+    pub paid_through: DateTime<Utc>,
+    pub recorded_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct RecordDistributionRunRequest {
+    pub paid_through: Option<DateTime<Utc>>, // Maintenant par défaut
+}
+
+/// Revenus courus et non versés d'une position de l'investisseur
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct HoldingAccrual {
+    pub property_id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub shares: i64,
+    pub amount_eth: TokenAmount,
+    pub annual_yield: BigDecimal,                   // Pourcentage
+    pub last_distribution_at: Option<DateTime<Utc>>, // Fin de la dernière période versée
+    pub accrual_start: DateTime<Utc>,               // Début du plus ancien investissement couru
+    pub daily_accrual_eth: TokenAmount,             // Estimation, 6 décimales
+    pub accrued_eth: TokenAmount,                   // Estimation, 6 décimales
+    pub net_accrued_eth: TokenAmount,               // Après retenue à la source
+}
+
+/// Paramètres de requête pour `GET /api/properties/suggest`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct SuggestQuery {
+    pub q: String,
+    pub limit: Option<u32>, // 8 par défaut, 20 au plus
+}
+
+/// Suggestion d'autocomplétion : de quoi afficher et ouvrir la propriété
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PropertySuggestion {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub location: String,
+}
+
+/// Paramètres de requête pour `GET /api/investments`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct InvestmentListQuery {
+    pub include: Option<String>,
+    pub fields: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Paramètre `?include=` pour les routes de détail
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct IncludeQuery {
+    pub include: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateTagRequest {
+    pub label: String,
+    pub slug: Option<String>, // Dérivé du libellé si absent
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateTagRequest {
+    pub label: Option<String>,
+    pub slug: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateMediaRequest {
+    pub kind: MediaKind,
+    pub url: String,
+    pub caption: Option<String>,
+    pub is_cover: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateMediaRequest {
+    pub url: Option<String>,
+    pub caption: Option<String>,
+    pub is_cover: Option<bool>,
+}
+
+/// Nouvel ordre de la galerie : tous les identifiants de médias de la propriété
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ReorderMediaRequest {
+    pub media_ids: Vec<Uuid>,
+}
+
+/// Paramètres des routes d'envoi de fichier (le corps de la requête est le fichier brut)
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UploadQuery {
+    pub filename: String,
+    pub caption: Option<String>, // Galerie uniquement
+    pub is_cover: Option<bool>,  // Galerie uniquement
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct SchedulePublicationRequest {
+    pub publish_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
 pub struct CreateInvestmentRequest {
     pub property_id: Uuid,
-    pub amount_eth: BigDecimal,
+    #[serde(flatten)]
+    pub amount: AmountInput, // amount_eth ou amount_wei
     pub shares: i32,
     pub tx_hash: String,
+    pub intent_id: Option<Uuid>, // Intention EIP-712 signée correspondant à la transaction
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateInvestmentRequest {
-    pub amount_eth: BigDecimal,
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateIntentRequest {
+    pub property_id: Uuid,
     pub shares: i32,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateFiatIntentRequest {
+    pub property_id: Uuid,
+    pub amount_eur: Money, // Arrondi au nombre entier de parts inférieur
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct SettleInvestmentRequest {
+    pub tx_hash: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateRefundRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ReviewRefundRequest {
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct RefundPaidRequest {
     pub tx_hash: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct RefundListQuery {
+    pub status: Option<RefundStatus>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct OnchainInvestmentListQuery {
+    pub status: Option<OnchainInvestmentStatus>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ResolveOnchainInvestmentRequest {
+    pub user_id: Option<Uuid>,     // À défaut, rapproché du wallet de l'événement
+    pub property_id: Option<Uuid>, // À défaut, rapprochée de son onchain_id
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct DismissOnchainInvestmentRequest {
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct KycOverrideRequest {
+    pub status: KycStatus,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct KycListQuery {
+    pub status: Option<KycStatus>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct FlagListQuery {
+    pub status: Option<FlagStatus>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ReviewFlagRequest {
+    pub status: FlagStatus,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct AlertListQuery {
+    pub status: Option<AlertStatus>,
+    pub severity: Option<AlertSeverity>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ActivityQuery {
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct RoleChangeRequestListQuery {
+    pub status: Option<String>, // pending, approved, rejected, cancelled, expired
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct AuthAttemptListQuery {
+    pub ip: Option<String>,
+    pub wallet: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct AcknowledgeAlertRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct AlertNoteRequest {
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateLegalDocumentRequest {
+    pub kind: LegalDocumentKind,
+    pub version: String,
+    pub title: String,
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateContentRequest {
+    pub slug: String,
+    pub locale: Option<String>, // "fr" par défaut
+    pub title: String,
+    pub body: String,
+    pub published: Option<bool>, // brouillon par défaut
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateContentRequest {
+    pub slug: Option<String>,
+    pub locale: Option<String>,
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub published: Option<bool>,
+}
+
+/// Paramètres de requête pour `GET /api/admin/contents`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ContentListQuery {
+    pub locale: Option<String>,
+    pub published: Option<bool>,
+}
+
+/// Paramètre `?locale=` de `GET /public/v1/content/:slug`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ContentQuery {
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateAnnouncementRequest {
+    pub title: String,
+    pub body: String,
+    pub audience: Option<String>, // "all" par défaut
+    pub starts_at: Option<DateTime<Utc>>, // immédiatement par défaut
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateAnnouncementRequest {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub audience: Option<String>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateTaxRuleRequest {
+    pub country: String, // ISO 3166-1 alpha-2
+    pub rate: BigDecimal,
+    pub applies_to: Option<String>, // distributions par défaut
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateTaxRuleRequest {
+    pub rate: BigDecimal,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct TaxRuleListQuery {
+    pub country: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateCountryRequest {
+    pub country: Option<String>, // null pour supprimer le pays
+}
+
+/// Fusion d'un doublon dans le compte conservé (voir user_merges.rs)
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct MergeUsersRequest {
+    pub survivor_id: Uuid,
+    pub duplicate_id: Uuid,
+    pub reason: Option<String>,
+}
+
+/// Paramètre `?active=true` de `GET /api/admin/announcements`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct AnnouncementListQuery {
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct AcceptTermsRequest {
+    pub document_ids: Option<Vec<Uuid>>, // Toutes les versions en vigueur si absent
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct AccreditationListQuery {
+    pub status: Option<AccreditationStatus>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ReviewAccreditationRequest {
+    pub status: AccreditationStatus,          // approved ou rejected
+    pub expires_at: Option<DateTime<Utc>>,    // Approbation : ACCREDITATION_VALIDITY_DAYS par défaut
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ExposureLimitListQuery {
+    pub user_id: Option<Uuid>,
+    pub property_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct SetExposureLimitRequest {
+    pub property_id: Option<Uuid>, // Limite globale si absent
+    pub max_eth: Option<BigDecimal>,
+    pub max_pct: Option<BigDecimal>, // Pourcentage du prix total de la propriété
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct NotificationListQuery {
+    pub unread: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct NotificationPreferenceUpdate {
+    pub kind: String,
+    pub channel: String, // in_app, email, webhook
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateNotificationPreferencesRequest {
+    pub preferences: Vec<NotificationPreferenceUpdate>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateEmailRequest {
+    pub email: Option<String>, // null pour supprimer l'adresse
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateCurrencyRequest {
+    pub currency: String, // EUR ou USD
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UnsubscribeQuery {
+    pub token: String,
+    pub kind: Option<String>, // Absent : tous les types d'événement
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct RelayTxRequest {
+    pub to: String,
+    pub data: String,
+    pub value_wei: Option<String>, // Décimal, 0 par défaut
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct RelayTxListQuery {
+    pub status: Option<RelayStatus>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct SignIntentRequest {
+    pub signature: String, // 0x + r ‖ s ‖ v (65 octets)
+}
+
+/// Paramètre `?block=` du snapshot des détenteurs (dernier bloc par défaut)
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct HoldersQuery {
+    pub block: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateInvestmentRequest {
+    #[serde(flatten)]
+    pub amount: AmountInput, // amount_eth ou amount_wei
+    pub shares: Option<i32>,
+    pub tx_hash: Option<String>,
+    pub reason: Option<String>, // obligatoire pour corriger un investissement réglé on-chain
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
 pub struct UpdatePropertyStatusRequest {
     pub status: PropertyStatus,
     pub comment: Option<String>, // Optionnel : commentaire pour le changement de statut
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub daily_quota: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateApiKeyRequest {
+    pub name: Option<String>,
+    pub daily_quota: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ImpersonateRequest {
+    pub reason: String, // Motif obligatoire (ticket support, etc.)
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct ImpersonationListQuery {
+    pub admin_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateUserTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>, // read:portfolio, read:properties
+    pub expires_in_days: Option<i64>, // Sans expiration si absent
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct LeaderboardOptInRequest {
+    pub opt_in: bool,
+}
+
+// Indicateurs disponibles pour les séries temporelles de l'admin
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsMetric {
+    Signups,
+    InvestmentsCount,
+    InvestmentsVolume,
+    PropertiesSubmitted,
+    PropertiesValidated,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalyticsGranularity {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct AnalyticsQuery {
+    pub metric: AnalyticsMetric,
+    pub granularity: Option<AnalyticsGranularity>, // Semaine par défaut
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct PropertyStatsQuery {
+    pub granularity: Option<AnalyticsGranularity>, // Jour par défaut
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct RateHistoryQuery {
+    pub granularity: Option<AnalyticsGranularity>, // Jour par défaut
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateReportRequest {
+    pub kind: ReportKind,
+    pub format: ReportFormat,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateBackupRequest {
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateRetentionPolicyRequest {
+    pub retention_days: Option<i32>,
+    pub enabled: Option<bool>,
+}
+
+/// Paramètre `?entity=` de `GET /api/admin/trash`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct TrashListQuery {
+    pub entity: TrashEntity,
+}
+
+/// Co-gestionnaire d'une propriété (voir property_managers.rs)
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PropertyManager {
+    pub property_id: Uuid,
+    pub user_id: Uuid,
+    pub wallet: String,
+    pub name: Option<String>,
+    pub role: PropertyManagerRole,
+    pub added_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct AddPropertyManagerRequest {
+    pub wallet: String,
+    pub role: PropertyManagerRole,
+}
+
+/// Paramètre `?cascade=` de `DELETE /api/properties/:id`
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct DeletePropertyQuery {
+    pub cascade: Option<bool>, // Met aussi les investissements à la corbeille ; faux par défaut
+}
+
+/// Investissements d'une propriété par statut
+#[derive(Debug, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct InvestmentCounts {
+    pub total: i64,
+    pub pending_settlement: i64,
+    pub settled: i64,
+    pub refunded: i64,
+}
+
+/// Lignes rattachées à une propriété et touchées par sa suppression (voir deletion_impact.rs)
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PropertyDependents {
+    pub investments: InvestmentCounts, // Mis à la corbeille avec la propriété (suppression en cascade) ; les réglés l'empêchent
+    pub distributions: i64,            // Investisseurs dont les distributions estimées s'arrêtent
+    pub documents: i64,                // Conservés avec la propriété dans la corbeille
+    pub document_pins: i64,            // Supprimés
+    pub media: i64,                    // Supprimés
+    pub intents: i64,                  // Intentions d'investissement supprimées
+}
+
+/// Aperçu de la suppression d'une propriété
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PropertyDeletionImpact {
+    pub property_id: Uuid,
+    pub status: PropertyStatus,
+    pub dependents: PropertyDependents,
+    pub deletable: bool,         // Suppression simple possible
+    pub cascade_deletable: bool, // Suppression possible avec `?cascade=true`
+    pub blockers: Vec<String>,   // Codes d'erreur (voir error_codes.rs) empêchant la suppression simple
+}
+
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpsertFeatureFlagRequest {
+    pub enabled: bool,
+    pub description: Option<String>,
+    pub environments: Option<Vec<String>>,
+    pub roles: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct CreateTenantRequest {
+    pub slug: String,
+    pub name: String,
+    pub hostnames: Option<Vec<String>>,
+    pub cors_origins: Option<Vec<String>>,
+    pub branding: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct UpdateTenantRequest {
+    pub name: Option<String>,
+    pub hostnames: Option<Vec<String>>,
+    pub cors_origins: Option<Vec<String>>,
+    pub branding: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct MockInvestRequest {
+    pub property_id: Uuid,
+    pub shares: i32,
+    pub from: Option<WalletAddress>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export, optional_fields = nullable)]
+pub struct MockMineRequest {
+    pub blocks: Option<u32>,
+}
@@ -0,0 +1,26 @@
+// money.rs
+//
+// Montants et politique d'arrondi : les types (`TokenAmount`, `Wei`, `Money`)
+// sont définis dans le crate `pa-backend-types` (types/src/money.rs), partagé
+// avec les clients ; ce module les réexporte et expose la conversion entre
+// ETH et wei.
+
+use axum::{extract::Query, http::StatusCode, response::IntoResponse, Json};
+
+pub use pa_backend_types::money::*;
+
+/// GET /api/units/convert?amount_eth=… ou ?amount_wei=… - Conversion entre ETH et wei
+pub async fn convert_units(Query(query): Query<AmountInput>) -> impl IntoResponse {
+    match query.resolve() {
+        Ok(Some(amount)) => (StatusCode::OK, Json(serde_json::json!({
+            "amount_eth": amount,
+            "amount_wei": amount.wei()
+        }))).into_response(),
+        Ok(None) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Paramètre amount_eth ou amount_wei requis"
+        }))).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,237 @@
+// notification_preferences.rs
+//
+// Préférences de notification : pour chaque type d'événement, l'utilisateur
+// active ou coupe chaque canal (`in_app`, `email`, `webhook`). Sans choix
+// explicite, un canal est actif. Les notifications in-app (notifications.rs)
+// en tiennent compte ; les canaux e-mail et webhook sont enregistrés pour les
+// envois externes. Le pied des e-mails porte un lien de désinscription
+// (`/notifications/unsubscribe?token=...`) utilisable sans être connecté.
+// L'adresse de destination se renseigne via `PUT /api/me/email`.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use rand::RngCore;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::activity;
+use crate::auth::BearerAuthUser;
+use crate::models::{UnsubscribeQuery, UpdateEmailRequest, UpdateNotificationPreferencesRequest};
+
+pub const CHANNELS: &[&str] = &["in_app", "email", "webhook"];
+
+/// Types d'événement notifiés et leur description
+pub const KINDS: &[(&str, &str)] = &[
+    ("accreditation_submitted", "Demande d'accréditation à examiner (admin)"),
+    ("accreditation_approved", "Accréditation approuvée"),
+    ("accreditation_rejected", "Accréditation refusée"),
+    ("backup_completed", "Sauvegarde de la base prête à être téléchargée (admin)"),
+    ("backup_failed", "Échec d'une sauvegarde de la base (admin)"),
+    ("compliance_flag", "Signalement de conformité à examiner (admin)"),
+    ("fiat_payment_oversold", "Paiement en euros reçu sans parts disponibles (admin)"),
+    ("dual_write_divergence", "Écarts constatés par la double écriture entre les bases (admin)"),
+    ("integrity_issues", "Anomalies détectées par le contrôle d'intégrité (admin)"),
+    ("investment_received", "Nouvel investissement dans une de ses propriétés (manager)"),
+    ("kyc_updated", "Évolution de la vérification d'identité"),
+    ("onchain_investment_unmatched", "Investissement on-chain non rapproché (admin)"),
+    ("property_manager_added", "Ajout comme co-gestionnaire d'une propriété (manager)"),
+    ("property_validated", "Propriété validée par un admin (manager)"),
+    ("refund_requested", "Demande de remboursement enregistrée"),
+    ("refund_approved", "Remboursement approuvé"),
+    ("refund_rejected", "Remboursement refusé"),
+    ("refund_paid", "Remboursement versé"),
+    ("report_completed", "Rapport prêt à être téléchargé"),
+    ("report_failed", "Échec de la génération d'un rapport"),
+    ("risk_alert", "Alerte d'activité suspecte (admin)"),
+    ("role_change_requested", "Changement de rôle à approuver (admin)"),
+    ("role_change_approved", "Changement de rôle approuvé (admin)"),
+    ("role_change_rejected", "Changement de rôle refusé (admin)"),
+    ("weekly_digest", "Résumé hebdomadaire (e-mail)"),
+];
+
+/// Jeton de désinscription de l'utilisateur, créé au premier besoin
+pub async fn unsubscribe_token<'e, E: sqlx::PgExecutor<'e>>(executor: E, user_id: Uuid) -> Result<String, sqlx::Error> {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    sqlx::query_scalar!(
+        r#"INSERT INTO notification_unsubscribe_tokens (user_id, token) VALUES ($1, $2)
+           ON CONFLICT (user_id) DO UPDATE SET user_id = EXCLUDED.user_id
+           RETURNING token"#,
+        user_id,
+        hex::encode(bytes)
+    )
+    .fetch_one(executor)
+    .await
+}
+
+async fn preferences_matrix(pool: &PgPool, user_id: Uuid) -> Result<serde_json::Value, sqlx::Error> {
+    let explicit = sqlx::query!(
+        "SELECT kind, channel, enabled FROM notification_preferences WHERE user_id = $1",
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+    let token = unsubscribe_token(pool, user_id).await?;
+    let email = sqlx::query_scalar!("SELECT email FROM users WHERE id = $1", user_id)
+        .fetch_one(pool)
+        .await?;
+
+    let preferences = KINDS.iter().map(|(kind, description)| {
+        let channels = CHANNELS.iter().map(|channel| {
+            let enabled = explicit.iter()
+                .find(|p| p.kind == *kind && p.channel == *channel)
+                .is_none_or(|p| p.enabled);
+            (channel.to_string(), serde_json::Value::Bool(enabled))
+        }).collect::<serde_json::Map<_, _>>();
+        serde_json::json!({
+            "kind": kind,
+            "description": description,
+            "channels": channels
+        })
+    }).collect::<Vec<_>>();
+
+    Ok(serde_json::json!({
+        "preferences": preferences,
+        "channels": CHANNELS,
+        "email": email,
+        "unsubscribe_url": format!("/notifications/unsubscribe?token={}", token)
+    }))
+}
+
+/// Route : matrice des préférences (type d'événement × canal)
+pub async fn get_notification_preferences(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match preferences_matrix(&pool, user.id).await {
+        Ok(matrix) => (StatusCode::OK, Json(matrix)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : modifier des préférences ; les couples absents restent inchangés
+pub async fn update_notification_preferences(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<UpdateNotificationPreferencesRequest>,
+) -> impl IntoResponse {
+    if let Some(invalid) = payload.preferences.iter().find(|p| !KINDS.iter().any(|(kind, _)| *kind == p.kind)) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Type d'événement inconnu : {}", invalid.kind)
+        }))).into_response();
+    }
+    if let Some(invalid) = payload.preferences.iter().find(|p| !CHANNELS.contains(&p.channel.as_str())) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Canal inconnu : {}", invalid.channel)
+        }))).into_response();
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        for preference in &payload.preferences {
+            sqlx::query!(
+                r#"INSERT INTO notification_preferences (user_id, kind, channel, enabled)
+                   VALUES ($1, $2, $3, $4)
+                   ON CONFLICT (user_id, kind, channel) DO UPDATE SET enabled = EXCLUDED.enabled, updated_at = NOW()"#,
+                user.id,
+                preference.kind,
+                preference.channel,
+                preference.enabled
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        preferences_matrix(&pool, user.id).await
+    }
+    .await;
+
+    match result {
+        Ok(matrix) => (StatusCode::OK, Json(matrix)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : renseigner (ou supprimer avec `null`) son adresse e-mail
+pub async fn update_my_email(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<UpdateEmailRequest>,
+) -> impl IntoResponse {
+    let email = payload.email.map(|e| e.trim().to_lowercase()).filter(|e| !e.is_empty());
+    if let Some(email) = &email {
+        let valid = email.len() <= 254
+            && !email.chars().any(char::is_whitespace)
+            && email.split_once('@').is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'));
+        if !valid {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "error": "Adresse e-mail invalide"
+            }))).into_response();
+        }
+    }
+
+    match sqlx::query!(
+        "UPDATE users SET email = $1 WHERE id = $2",
+        email,
+        user.id
+    )
+    .execute(&pool)
+    .await {
+        Ok(_) => {
+            activity::record_detached(pool.clone(), user.id, None, "email_changed", serde_json::json!({}), None);
+            (StatusCode::OK, Json(serde_json::json!({
+                "email": email
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route publique : désinscription des e-mails depuis le lien de pied de
+/// page (`?kind=` pour un seul type d'événement, tous sinon). Accepte GET
+/// et POST (désinscription en un clic des clients de messagerie).
+pub async fn unsubscribe(
+    State(pool): State<PgPool>,
+    Query(query): Query<UnsubscribeQuery>,
+) -> impl IntoResponse {
+    let kinds: Vec<String> = match &query.kind {
+        Some(kind) if KINDS.iter().any(|(known, _)| known == kind) => vec![kind.clone()],
+        Some(kind) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Type d'événement inconnu : {}", kind)
+        }))).into_response(),
+        None => KINDS.iter().map(|(kind, _)| kind.to_string()).collect(),
+    };
+
+    match sqlx::query!(
+        r#"INSERT INTO notification_preferences (user_id, kind, channel, enabled)
+           SELECT t.user_id, k.kind, 'email', FALSE
+           FROM notification_unsubscribe_tokens t, UNNEST($2::text[]) AS k(kind)
+           WHERE t.token = $1
+           ON CONFLICT (user_id, kind, channel) DO UPDATE SET enabled = FALSE, updated_at = NOW()"#,
+        query.token,
+        &kinds
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Lien de désinscription invalide"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Vous ne recevrez plus ces notifications par e-mail",
+            "kinds": kinds
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la désinscription: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,191 @@
+// notifications.rs
+//
+// Notifications in-app : les modules métier les créent dans la même transaction
+// que le changement d'état qu'elles annoncent, l'utilisateur les consulte et les
+// marque comme lues. Un utilisateur qui a coupé le canal `in_app` pour un type
+// d'événement (voir notification_preferences.rs) ne reçoit pas la notification.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{Notification, NotificationListQuery};
+use crate::pagination::{Cursor, Pagination};
+
+/// Notifie un utilisateur
+pub async fn notify<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    user_id: Uuid,
+    kind: &str,
+    message: &str,
+    data: Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"INSERT INTO notifications (user_id, kind, message, data)
+           SELECT $1, $2, $3, $4
+           WHERE NOT EXISTS (SELECT 1 FROM notification_preferences
+                             WHERE user_id = $1 AND kind = $2 AND channel = 'in_app' AND NOT enabled)"#,
+        user_id,
+        kind,
+        message,
+        data
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Notifie tous les admins
+pub async fn notify_admins<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    kind: &str,
+    message: &str,
+    data: Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"INSERT INTO notifications (user_id, kind, message, data)
+           SELECT id, $1, $2, $3 FROM users u
+           WHERE role = 'admin'
+           AND NOT EXISTS (SELECT 1 FROM notification_preferences np
+                           WHERE np.user_id = u.id AND np.kind = $1 AND np.channel = 'in_app' AND NOT np.enabled)"#,
+        kind,
+        message,
+        data
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Notifie les admins d'une plateforme, sauf éventuellement l'auteur de l'action
+pub async fn notify_tenant_admins<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+    except: Option<Uuid>,
+    kind: &str,
+    message: &str,
+    data: Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"INSERT INTO notifications (user_id, kind, message, data)
+           SELECT id, $3, $4, $5 FROM users u
+           WHERE role = 'admin' AND tenant_id = $1 AND ($2::uuid IS NULL OR id <> $2)
+           AND NOT EXISTS (SELECT 1 FROM notification_preferences np
+                           WHERE np.user_id = u.id AND np.kind = $3 AND np.channel = 'in_app' AND NOT np.enabled)"#,
+        tenant_id,
+        except,
+        kind,
+        message,
+        data
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Route pour lister ses notifications, les plus récentes d'abord
+/// (`?unread=true` pour les non lues uniquement)
+pub async fn get_notifications(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<NotificationListQuery>,
+    pagination: Pagination,
+) -> impl IntoResponse {
+    let unread_only = query.unread.unwrap_or(false);
+    let (after_at, after_id) = pagination.cursor_bounds();
+
+    let result = async {
+        let notifications = sqlx::query_as!(
+            Notification,
+            r#"SELECT id, user_id, kind, message, data, read_at, created_at
+               FROM notifications
+               WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+               AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4))
+               ORDER BY created_at DESC, id DESC
+               LIMIT $5 OFFSET $6"#,
+            user.id,
+            unread_only,
+            after_at,
+            after_id,
+            pagination.limit(),
+            pagination.offset()
+        )
+        .fetch_all(&pool)
+        .await?;
+        let unread = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM notifications WHERE user_id = $1 AND read_at IS NULL"#,
+            user.id
+        )
+        .fetch_one(&pool)
+        .await?;
+        Ok::<_, sqlx::Error>((notifications, unread))
+    }
+    .await;
+
+    match result {
+        Ok((mut notifications, unread)) => {
+            let page = pagination.finish(&mut notifications, |n| Cursor::new(n.created_at, n.id));
+            envelope::list("notifications", &notifications)
+                .paginated(page)
+                .with("unread", unread)
+                .into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour marquer une notification comme lue
+pub async fn mark_notification_read(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(notification_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match sqlx::query_as!(
+        Notification,
+        r#"UPDATE notifications SET read_at = COALESCE(read_at, NOW())
+           WHERE id = $1 AND user_id = $2
+           RETURNING id, user_id, kind, message, data, read_at, created_at"#,
+        notification_id,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(notification)) => (StatusCode::OK, Json(notification)).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Notification non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour marquer toutes ses notifications comme lues
+pub async fn mark_all_notifications_read(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match sqlx::query!(
+        "UPDATE notifications SET read_at = NOW() WHERE user_id = $1 AND read_at IS NULL",
+        user.id
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) => (StatusCode::OK, Json(serde_json::json!({
+            "updated": result.rows_affected()
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
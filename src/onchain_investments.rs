@@ -0,0 +1,523 @@
+// onchain_investments.rs
+//
+// Investissements créés depuis la chaîne. Avec `INDEXER_INVESTMENTS=true`,
+// chaque événement `Invested(wallet, propertyId, shares)` du contrat
+// d'investissement (`INVESTMENT_CONTRACT_ADDRESS`) reçu par l'indexation (voir
+// indexer.rs) crée l'investissement correspondant, sans attendre que le
+// frontend l'enregistre après la transaction : le wallet est rapproché de son
+// utilisateur (wallet principal ou rattaché vérifié), la propriété de son
+// `onchain_id`, et le montant vaut le prix de la part × parts. Un
+// investissement déjà enregistré par le frontend pour la même transaction est
+// simplement rattaché à l'événement. Un événement non rapproché (wallet ou
+// propriété inconnus, parts épuisées) est conservé et signalé aux admins, qui
+// le rapprochent ou l'écartent.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use sqlx::postgres::PgExecutor;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::env;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::chain::{uint256_to_decimal, TxLog};
+use crate::envelope;
+use crate::error_codes::ErrorCode;
+use crate::funding;
+use crate::indexer::IngestStats;
+use crate::models::{
+    DismissOnchainInvestmentRequest, OnchainInvestment, OnchainInvestmentListQuery, OnchainInvestmentStatus,
+    PropertyStatus, ResolveOnchainInvestmentRequest, UserRole,
+};
+use crate::money::TokenAmount;
+use crate::notifications::notify_admins;
+use crate::outbox::{self, DomainEvent};
+use crate::risk::{self, RiskRules};
+use crate::token::event_topic;
+use crate::wallet_address::WalletAddress;
+
+pub const INVESTED_EVENT: &str = "Invested(address,uint256,uint256)";
+
+/// Contrat d'investissement suivi, si la création depuis la chaîne est activée
+pub fn investment_address_from_env() -> Option<String> {
+    let enabled = env::var("INDEXER_INVESTMENTS").is_ok_and(|v| v.trim() == "true");
+    let address = env::var("INVESTMENT_CONTRACT_ADDRESS").ok().and_then(|v| WalletAddress::parse(&v))?;
+    enabled.then(|| address.as_str().to_string())
+}
+
+/// Événement `Invested` décodé (wallet et identifiant de propriété indexés)
+struct Invested {
+    wallet: String,
+    onchain_property_id: String,
+    shares: i32,
+}
+
+fn decode_invested(log: &TxLog) -> Result<Invested, String> {
+    let invalid = || "Événement Invested mal formé".to_string();
+    let wallet = log.topics.get(1)
+        .and_then(|topic| topic.trim_start_matches("0x").get(24..))
+        .and_then(|hex_part| WalletAddress::parse(&format!("0x{}", hex_part)))
+        .ok_or_else(invalid)?;
+    let property_id = log.topics.get(2)
+        .and_then(|topic| hex::decode(topic.trim_start_matches("0x")).ok())
+        .filter(|word| word.len() == 32)
+        .ok_or_else(invalid)?;
+    let data = hex::decode(log.data.trim_start_matches("0x")).map_err(|_| invalid())?;
+    let shares = data.get(..32)
+        .and_then(|word| uint256_to_decimal(word).parse::<i32>().ok())
+        .filter(|shares| *shares > 0)
+        .ok_or_else(|| "Nombre de parts de l'événement Invested hors limites".to_string())?;
+
+    Ok(Invested {
+        wallet: wallet.as_str().to_string(),
+        onchain_property_id: uint256_to_decimal(&property_id),
+        shares,
+    })
+}
+
+/// Utilisateur actif d'un wallet, principal ou rattaché vérifié (le wallet
+/// d'un compte fusionné désigne le compte conservé)
+async fn user_for_wallet<'e>(db: impl PgExecutor<'e>, wallet: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT id FROM users
+           WHERE deactivated_at IS NULL
+           AND (wallet = $1 OR id = (SELECT user_id FROM user_wallets WHERE wallet = $1 AND verified_at IS NOT NULL))
+           LIMIT 1"#,
+        wallet
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Propriété d'un identifiant on-chain ; `None` si aucune ou plusieurs
+async fn property_for_onchain_id<'e>(db: impl PgExecutor<'e>, onchain_id: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    let ids = sqlx::query_scalar!("SELECT id FROM properties WHERE onchain_id = $1 LIMIT 2", onchain_id)
+        .fetch_all(db)
+        .await?;
+    Ok(match ids.as_slice() {
+        [id] => Some(*id),
+        _ => None,
+    })
+}
+
+/// Investissement déjà enregistré pour cette transaction, cet utilisateur et cette propriété
+pub async fn recorded_investment<'e>(
+    db: impl PgExecutor<'e>,
+    user_id: Uuid,
+    property_id: Uuid,
+    tx_hash: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT id FROM investments
+           WHERE user_id = $1 AND property_id = $2 AND lower(tx_hash) = lower($3) AND status <> 'refunded'
+           LIMIT 1"#,
+        user_id,
+        property_id,
+        tx_hash
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Investissement de l'événement : rattache celui déjà enregistré pour la
+/// transaction, ou le crée. `Ok(Err(motif))` si le rapprochement échoue ; une
+/// survente est renvoyée en erreur (voir `funding::is_oversold`).
+async fn link(
+    tx: &mut Transaction<'_, Postgres>,
+    event: &OnchainInvestment,
+    user_id: Option<Uuid>,
+    property_id: Option<Uuid>,
+) -> Result<Result<(Uuid, bool), &'static str>, sqlx::Error> {
+    let Some(user_id) = user_id else {
+        return Ok(Err("unknown_wallet"));
+    };
+    let Some(property_id) = property_id else {
+        return Ok(Err("unknown_property"));
+    };
+    let Some(property) = sqlx::query!(
+        r#"SELECT status as "status: PropertyStatus", token_price FROM properties WHERE id = $1"#,
+        property_id
+    )
+    .fetch_optional(&mut *tx)
+    .await? else {
+        return Ok(Err("unknown_property"));
+    };
+    if !matches!(property.status, PropertyStatus::Validated) {
+        return Ok(Err("property_not_validated"));
+    }
+
+    if let Some(investment_id) = recorded_investment(&mut *tx, user_id, property_id, &event.tx_hash).await? {
+        return Ok(Ok((investment_id, false)));
+    }
+
+    let amount_eth = TokenAmount::for_shares(&property.token_price, event.shares as i64);
+    let amount_wei = amount_eth.wei();
+    let investment_id = sqlx::query_scalar!(
+        r#"INSERT INTO investments (user_id, property_id, amount_eth, amount_wei, shares, tx_hash, tenant_id)
+           VALUES ($1, $2, $3, $4, $5, $6, (SELECT tenant_id FROM properties WHERE id = $2))
+           RETURNING id"#,
+        user_id,
+        property_id,
+        amount_eth.as_decimal(),
+        amount_wei.as_decimal(),
+        event.shares,
+        event.tx_hash
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    outbox::record(&mut *tx, &DomainEvent::InvestmentCreated {
+        investment_id,
+        user_id,
+        property_id,
+        amount_eth,
+        shares: event.shares,
+    }).await?;
+    Ok(Ok((investment_id, true)))
+}
+
+/// Signale aux admins un événement non rapproché
+async fn notify_unmatched<'e>(db: impl PgExecutor<'e>, event: &OnchainInvestment, reason: &str) -> Result<(), sqlx::Error> {
+    notify_admins(db, "onchain_investment_unmatched",
+        &format!("Investissement on-chain non rapproché ({}) : transaction {}", reason, event.tx_hash),
+        serde_json::json!({
+            "onchain_investment_id": event.id,
+            "tx_hash": event.tx_hash,
+            "wallet": event.wallet,
+            "onchain_property_id": event.onchain_property_id,
+            "shares": event.shares,
+            "reason": reason
+        })).await
+}
+
+/// Enregistre un événement et son investissement ; renvoie l'investissement
+/// créé (`None` si l'événement était déjà connu, non rapproché ou rattaché à
+/// un investissement existant)
+async fn record_event(
+    pool: &PgPool,
+    source: &str,
+    invested: &Invested,
+    tx_hash: &str,
+    log_index: i32,
+    block_number: u64,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let Some(event) = sqlx::query_as!(
+        OnchainInvestment,
+        r#"INSERT INTO onchain_investments (tx_hash, log_index, block_number, wallet, onchain_property_id, shares, source)
+           VALUES ($1, $2, $3, $4, $5, $6, $7)
+           ON CONFLICT (tx_hash, log_index) DO NOTHING
+           RETURNING id, tx_hash, log_index, block_number, wallet, onchain_property_id, shares, source,
+                     status as "status: OnchainInvestmentStatus", reason, investment_id, resolved_by, resolved_at,
+                     comment, created_at"#,
+        tx_hash,
+        log_index,
+        block_number as i64,
+        invested.wallet,
+        invested.onchain_property_id,
+        invested.shares,
+        source
+    )
+    .fetch_optional(&mut tx)
+    .await? else {
+        return Ok(None);
+    };
+
+    let user_id = user_for_wallet(&mut tx, &event.wallet).await?;
+    let property_id = property_for_onchain_id(&mut tx, &event.onchain_property_id).await?;
+    let reason = match link(&mut tx, &event, user_id, property_id).await {
+        Ok(Ok((investment_id, created))) => {
+            sqlx::query!(
+                "UPDATE onchain_investments SET status = 'matched', investment_id = $2 WHERE id = $1",
+                event.id,
+                investment_id
+            )
+            .execute(&mut tx)
+            .await?;
+            tx.commit().await?;
+            return Ok(created.then_some(investment_id));
+        },
+        Ok(Err(reason)) => reason,
+        // Parts épuisées : l'événement est conservé, sans investissement
+        Err(e) if funding::is_oversold(&e) => {
+            tx.rollback().await?;
+            tx = pool.begin().await?;
+            sqlx::query!(
+                r#"INSERT INTO onchain_investments (id, tx_hash, log_index, block_number, wallet, onchain_property_id, shares, source)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                   ON CONFLICT (tx_hash, log_index) DO NOTHING"#,
+                event.id,
+                event.tx_hash,
+                event.log_index,
+                event.block_number,
+                event.wallet,
+                event.onchain_property_id,
+                event.shares,
+                event.source
+            )
+            .execute(&mut tx)
+            .await?;
+            "sold_out"
+        },
+        Err(e) => return Err(e),
+    };
+
+    sqlx::query!("UPDATE onchain_investments SET reason = $2 WHERE id = $1", event.id, reason)
+        .execute(&mut tx)
+        .await?;
+    notify_unmatched(&mut tx, &event, reason).await?;
+    tx.commit().await?;
+    Ok(None)
+}
+
+/// Enregistre les événements `Invested` émis par le contrat d'investissement,
+/// chacun dans sa propre transaction. Idempotent ; un événement retiré par une
+/// réorganisation est supprimé s'il n'a pas été rapproché (un investissement
+/// déjà créé est conservé et signalé dans les journaux).
+pub async fn ingest(
+    pool: &PgPool,
+    rules: &RiskRules,
+    source: &str,
+    investment_address: &str,
+    logs: &[TxLog],
+) -> Result<IngestStats, sqlx::Error> {
+    let topic = event_topic(INVESTED_EVENT);
+    let mut stats = IngestStats::default();
+
+    for log in logs.iter().filter(|log| log.address == investment_address && log.topics.first() == Some(&topic)) {
+        let (block_number, tx_hash, log_index) = match (log.block_number, &log.tx_hash, log.log_index) {
+            (Some(block_number), Some(tx_hash), Some(log_index)) => (block_number, tx_hash, log_index as i32),
+            _ => {
+                stats.skipped += 1;
+                continue;
+            },
+        };
+
+        if log.removed {
+            let removed = sqlx::query_scalar!(
+                r#"DELETE FROM onchain_investments
+                   WHERE tx_hash = $1 AND log_index = $2 AND status IN ('unmatched', 'dismissed')
+                   RETURNING id"#,
+                tx_hash,
+                log_index
+            )
+            .fetch_optional(pool)
+            .await?;
+            match removed {
+                Some(_) => stats.removed += 1,
+                None => tracing::warn!(tx_hash = %tx_hash, log_index, "Événement Invested retiré par une réorganisation : investissement à vérifier"),
+            }
+            continue;
+        }
+
+        let invested = match decode_invested(log) {
+            Ok(invested) => invested,
+            Err(e) => {
+                tracing::warn!("Indexation: événement {}#{} ignoré: {}", tx_hash, log_index, e);
+                stats.skipped += 1;
+                continue;
+            },
+        };
+        if let Some(investment_id) = record_event(pool, source, &invested, tx_hash, log_index, block_number).await? {
+            risk::investment_created(pool.clone(), rules.clone(), investment_id);
+            stats.inserted += 1;
+        }
+    }
+    Ok(stats)
+}
+
+fn admin_only(role: &UserRole) -> Option<Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent traiter les investissements on-chain",
+        "code": ErrorCode::AdminRequired
+    }))).into_response())
+}
+
+/// Route admin : événements observés on-chain (`?status=unmatched` pour ceux à traiter)
+pub async fn get_onchain_investments(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<OnchainInvestmentListQuery>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        OnchainInvestment,
+        r#"SELECT id, tx_hash, log_index, block_number, wallet, onchain_property_id, shares, source,
+                  status as "status: OnchainInvestmentStatus", reason, investment_id, resolved_by, resolved_at,
+                  comment, created_at
+           FROM onchain_investments
+           WHERE ($1::onchain_investment_status IS NULL OR status = $1)
+           ORDER BY created_at DESC
+           LIMIT 500"#,
+        query.status as Option<OnchainInvestmentStatus>
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(events) => envelope::list("onchain_investments", &events).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
+
+/// Événement non rapproché, verrouillé pour sa résolution
+async fn lock_unmatched(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+) -> Result<Result<OnchainInvestment, ErrorCode>, sqlx::Error> {
+    let event = sqlx::query_as!(
+        OnchainInvestment,
+        r#"SELECT id, tx_hash, log_index, block_number, wallet, onchain_property_id, shares, source,
+                  status as "status: OnchainInvestmentStatus", reason, investment_id, resolved_by, resolved_at,
+                  comment, created_at
+           FROM onchain_investments WHERE id = $1 FOR UPDATE"#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    Ok(match event {
+        Some(event) if event.status == OnchainInvestmentStatus::Unmatched => Ok(event),
+        Some(_) => Err(ErrorCode::OnchainInvestmentNotPending),
+        None => Err(ErrorCode::OnchainInvestmentNotFound),
+    })
+}
+
+fn error_response(code: ErrorCode, error: &str) -> Response {
+    (code.status(), Json(serde_json::json!({
+        "error": error,
+        "code": code
+    }))).into_response()
+}
+
+/// Route admin : rapprocher un événement, en désignant au besoin l'utilisateur
+/// et la propriété. L'investissement est créé (ou rattaché s'il existe déjà).
+pub async fn resolve_onchain_investment(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(rules): Extension<RiskRules>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ResolveOnchainInvestmentRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+    let comment = payload.comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let event = match lock_unmatched(&mut tx, id).await? {
+            Ok(event) => event,
+            Err(code) => return Ok(Err(error_response(code, code.description()))),
+        };
+        let user_id = match payload.user_id {
+            Some(user_id) => Some(user_id),
+            None => user_for_wallet(&mut tx, &event.wallet).await?,
+        };
+        let property_id = match payload.property_id {
+            Some(property_id) => Some(property_id),
+            None => property_for_onchain_id(&mut tx, &event.onchain_property_id).await?,
+        };
+
+        let (investment_id, created) = match link(&mut tx, &event, user_id, property_id).await? {
+            Ok(linked) => linked,
+            Err(reason) => return Ok(Err((StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+                "error": format!("Rapprochement impossible : {}", reason),
+                "code": ErrorCode::OnchainInvestmentUnmatched,
+                "reason": reason
+            }))).into_response())),
+        };
+        let event = sqlx::query_as!(
+            OnchainInvestment,
+            r#"UPDATE onchain_investments
+               SET status = 'resolved', investment_id = $2, resolved_by = $3, resolved_at = NOW(), comment = $4
+               WHERE id = $1
+               RETURNING id, tx_hash, log_index, block_number, wallet, onchain_property_id, shares, source,
+                         status as "status: OnchainInvestmentStatus", reason, investment_id, resolved_by, resolved_at,
+                         comment, created_at"#,
+            id,
+            investment_id,
+            user.id,
+            comment
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok((event, created)))
+    }.await;
+
+    match result {
+        Ok(Ok((event, created))) => {
+            if let (Some(investment_id), true) = (event.investment_id, created) {
+                risk::investment_created(pool.clone(), rules, investment_id);
+            }
+            (StatusCode::OK, Json(serde_json::json!({
+                "onchain_investment": event,
+                "message": "Événement rapproché"
+            }))).into_response()
+        },
+        Ok(Err(response)) => response,
+        Err(e) if funding::is_oversold(&e) => {
+            funding::sold_out_response(None)
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du rapprochement: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
+
+/// Route admin : écarter un événement non rapproché (transaction de test, doublon...)
+pub async fn dismiss_onchain_investment(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<DismissOnchainInvestmentRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+    let comment = payload.comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        if let Err(code) = lock_unmatched(&mut tx, id).await? {
+            return Ok(Err(code));
+        }
+        let event = sqlx::query_as!(
+            OnchainInvestment,
+            r#"UPDATE onchain_investments
+               SET status = 'dismissed', resolved_by = $2, resolved_at = NOW(), comment = $3
+               WHERE id = $1
+               RETURNING id, tx_hash, log_index, block_number, wallet, onchain_property_id, shares, source,
+                         status as "status: OnchainInvestmentStatus", reason, investment_id, resolved_by, resolved_at,
+                         comment, created_at"#,
+            id,
+            user.id,
+            comment
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(event))
+    }.await;
+
+    match result {
+        Ok(Ok(event)) => (StatusCode::OK, Json(serde_json::json!({
+            "onchain_investment": event,
+            "message": "Événement écarté"
+        }))).into_response(),
+        Ok(Err(code)) => error_response(code, code.description()),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
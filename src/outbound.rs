@@ -0,0 +1,403 @@
+// outbound.rs
+//
+// Client HTTP partagé des appels sortants (nœud Ethereum, Stripe, Sumsub,
+// e-mails, cours de l'ETH, géocodage). Chaque service a son délai
+// (`OUTBOUND_<SERVICE>_TIMEOUT_SECS`) et son nombre de nouvelles tentatives
+// (`OUTBOUND_<SERVICE>_RETRIES`), puisées dans un budget commun au service
+// pour ne pas amplifier une panne. Un disjoncteur par service s'ouvre après
+// `OUTBOUND_BREAKER_FAILURES` échecs consécutifs : les appels échouent alors
+// aussitôt (503 côté routes, avec un repli propre à chaque service) pendant
+// `OUTBOUND_BREAKER_COOLDOWN_SECS`, puis un appel d'essai décide de sa
+// fermeture. Les disjoncteurs sont propres à chaque processus ; leur état est
+// exposé par `/health/ready`.
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use reqwest::{Method, Request, RequestBuilder};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error_codes::ErrorCode;
+
+/// Services appelés, avec leur délai (secondes) et leurs nouvelles tentatives par défaut
+const SERVICES: &[(&str, u64, u32)] = &[
+    ("rpc", 15, 2),
+    ("stripe", 30, 2),
+    ("kyc", 30, 1),
+    ("email", 30, 1),
+    ("rates", 15, 2),
+    ("geocoding", 15, 2),
+];
+
+/// Réserve maximale de nouvelles tentatives d'un service
+const MAX_RETRY_TOKENS: f64 = 10.0;
+
+/// Attente avant la première nouvelle tentative, doublée à chaque essai
+const RETRY_BACKOFF_MS: u64 = 200;
+
+/// Erreur d'un appel sortant
+#[derive(Debug)]
+pub enum OutboundError {
+    /// Disjoncteur ouvert : le service n'a pas été appelé
+    Unavailable { service: &'static str, retry_after: Duration },
+    Failed(String),
+}
+
+impl fmt::Display for OutboundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutboundError::Unavailable { service, retry_after } => write!(
+                f,
+                "service {} momentanément indisponible (nouvel essai dans {}s)",
+                service,
+                retry_after.as_secs().max(1)
+            ),
+            OutboundError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for OutboundError {
+    fn from(message: String) -> Self {
+        OutboundError::Failed(message)
+    }
+}
+
+impl OutboundError {
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, OutboundError::Unavailable { .. })
+    }
+
+    /// Réponse d'une route : 503 avec `Retry-After` si le disjoncteur est
+    /// ouvert (`fallback` décrit le repli proposé), 502 sinon
+    pub fn response(&self, context: &str, fallback: Option<serde_json::Value>) -> Response {
+        match self {
+            OutboundError::Unavailable { retry_after, .. } => {
+                let mut body = serde_json::json!({
+                    "error": format!("{}: {}", context, self),
+                    "code": ErrorCode::ServiceUnavailable,
+                    "retry_after_secs": retry_after.as_secs().max(1)
+                });
+                if let Some(fallback) = fallback {
+                    body["fallback"] = fallback;
+                }
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    [(header::RETRY_AFTER, retry_after.as_secs().max(1).to_string())],
+                    Json(body),
+                ).into_response()
+            },
+            OutboundError::Failed(_) => (StatusCode::BAD_GATEWAY, Json(serde_json::json!({
+                "error": format!("{}: {}", context, self)
+            }))).into_response(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    /// Délai écoulé : un appel d'essai est autorisé
+    HalfOpen,
+}
+
+type SharedBreaker = Arc<Mutex<Breaker>>;
+
+struct Breaker {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    opened_since: Option<DateTime<Utc>>,
+    /// Fin de l'appel d'essai en cours (demi-ouvert)
+    probe_until: Option<Instant>,
+    retry_tokens: f64,
+    trips: u64,
+    rejected: u64,
+    last_error: Option<String>,
+}
+
+/// État d'un disjoncteur, pour `/health/ready`
+#[derive(Debug, Serialize)]
+pub struct BreakerStatus {
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    pub opened_at: Option<DateTime<Utc>>,
+    pub retry_after_secs: Option<u64>,
+    pub trips: u64,
+    pub rejected: u64,
+    pub last_error: Option<String>,
+}
+
+struct Policy {
+    failure_threshold: u32,
+    cooldown: Duration,
+    /// Nouvelles tentatives gagnées par appel
+    retry_ratio: f64,
+}
+
+/// Registre des clients sortants et de leurs disjoncteurs
+#[derive(Clone)]
+pub struct Outbound {
+    policy: Arc<Policy>,
+    breakers: Arc<Mutex<BTreeMap<&'static str, SharedBreaker>>>,
+}
+
+impl Outbound {
+    /// Seuil via `OUTBOUND_BREAKER_FAILURES` (5 échecs consécutifs), durée
+    /// d'ouverture via `OUTBOUND_BREAKER_COOLDOWN_SECS` (30s) et budget de
+    /// nouvelles tentatives via `OUTBOUND_RETRY_BUDGET` (0.2 par appel)
+    pub fn from_env() -> Self {
+        let failure_threshold = env::var("OUTBOUND_BREAKER_FAILURES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(5);
+        let cooldown = env::var("OUTBOUND_BREAKER_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(30);
+        let retry_ratio = env::var("OUTBOUND_RETRY_BUDGET")
+            .ok()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+            .map(|v| v.clamp(0.0, 1.0))
+            .unwrap_or(0.2);
+
+        Self {
+            policy: Arc::new(Policy { failure_threshold, cooldown: Duration::from_secs(cooldown), retry_ratio }),
+            breakers: Arc::default(),
+        }
+    }
+
+    /// Client du service (voir `SERVICES`), qui partage son disjoncteur avec
+    /// les autres clients du même service ; `None` si le client ne peut être
+    /// construit. À appeler une fois la configuration du service lue : seuls
+    /// les services configurés apparaissent dans `/health/ready`.
+    pub fn client(&self, service: &'static str) -> Option<OutboundClient> {
+        let (_, timeout, retries) = SERVICES.iter().find(|(name, _, _)| *name == service)?;
+        let var = |suffix: &str| env::var(format!("OUTBOUND_{}_{}", service.to_uppercase(), suffix)).ok();
+        let timeout = var("TIMEOUT_SECS").and_then(|v| v.parse::<u64>().ok()).filter(|v| *v > 0).unwrap_or(*timeout);
+        let retries = var("RETRIES").and_then(|v| v.parse::<u32>().ok()).unwrap_or(*retries);
+
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(timeout))
+            .build()
+            .ok()?;
+        let breaker = self.breakers.lock().unwrap_or_else(|e| e.into_inner())
+            .entry(service)
+            .or_insert_with(|| Arc::new(Mutex::new(Breaker {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                opened_since: None,
+                probe_until: None,
+                retry_tokens: MAX_RETRY_TOKENS,
+                trips: 0,
+                rejected: 0,
+                last_error: None,
+            })))
+            .clone();
+
+        Some(OutboundClient { service, http, retries, breaker, policy: self.policy.clone() })
+    }
+
+    /// État des disjoncteurs des services configurés
+    pub fn statuses(&self) -> BTreeMap<&'static str, BreakerStatus> {
+        let now = Instant::now();
+        let breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        breakers.iter().map(|(service, breaker)| {
+            let breaker = breaker.lock().unwrap_or_else(|e| e.into_inner());
+            let retry_after = match breaker.state {
+                BreakerState::Open => breaker.opened_at.map(|at| (at + self.policy.cooldown).saturating_duration_since(now)),
+                _ => None,
+            };
+            (*service, BreakerStatus {
+                state: breaker.state,
+                consecutive_failures: breaker.consecutive_failures,
+                opened_at: breaker.opened_since,
+                retry_after_secs: retry_after.map(|d| d.as_secs()),
+                trips: breaker.trips,
+                rejected: breaker.rejected,
+                last_error: breaker.last_error.clone(),
+            })
+        }).collect()
+    }
+}
+
+/// Client d'un service, avec délai, nouvelles tentatives et disjoncteur
+#[derive(Clone)]
+pub struct OutboundClient {
+    service: &'static str,
+    http: reqwest::Client,
+    retries: u32,
+    breaker: SharedBreaker,
+    policy: Arc<Policy>,
+}
+
+impl OutboundClient {
+    pub fn request(&self, method: Method, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.http.request(method, url)
+    }
+
+    pub fn get(&self, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.http.get(url)
+    }
+
+    pub fn post(&self, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.http.post(url)
+    }
+
+    /// Échoue aussitôt si le disjoncteur est ouvert, sans réserver l'appel
+    /// d'essai : permet de ne rien engager avant un appel voué à l'échec
+    pub fn check(&self) -> Result<(), OutboundError> {
+        let breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let blocked_until = match breaker.state {
+            BreakerState::Closed => None,
+            BreakerState::Open => breaker.opened_at.map(|at| at + self.policy.cooldown),
+            BreakerState::HalfOpen => breaker.probe_until,
+        };
+        match blocked_until.filter(|until| *until > now) {
+            Some(until) => Err(OutboundError::Unavailable { service: self.service, retry_after: until - now }),
+            None => Ok(()),
+        }
+    }
+
+    /// Envoie la requête. Les nouvelles tentatives ne concernent que les
+    /// requêtes rejouables sans effet de bord (GET, PUT, DELETE, ou en-tête
+    /// `Idempotency-Key`), ou qui n'ont pas atteint le service (connexion refusée).
+    pub async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, OutboundError> {
+        let request = request.build().map_err(|e| OutboundError::Failed(e.to_string()))?;
+        let idempotent = matches!(*request.method(), Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS)
+            || request.headers().contains_key("idempotency-key");
+        self.execute(request, idempotent).await
+    }
+
+    /// Comme `send`, pour une requête que l'appelant sait rejouable (lecture
+    /// JSON-RPC, transaction signée déjà diffusée)
+    pub async fn send_idempotent(&self, request: RequestBuilder) -> Result<reqwest::Response, OutboundError> {
+        let request = request.build().map_err(|e| OutboundError::Failed(e.to_string()))?;
+        self.execute(request, true).await
+    }
+
+    async fn execute(&self, mut request: Request, idempotent: bool) -> Result<reqwest::Response, OutboundError> {
+        self.admit(true)?;
+        let mut attempt = 0;
+        loop {
+            // Corps en flux : pas de copie possible, donc pas de nouvelle tentative
+            let retry = if attempt < self.retries { request.try_clone() } else { None };
+            let result = self.http.execute(request).await;
+
+            let (error, retry_safe) = match &result {
+                Ok(response) if response.status().is_server_error() || response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    (Some(format!("le service a répondu {}", response.status())), idempotent)
+                },
+                Ok(_) => (None, false),
+                Err(e) => (Some(e.to_string()), idempotent || e.is_connect()),
+            };
+            let error = match error {
+                Some(error) => error,
+                None => {
+                    self.record(None);
+                    return result.map_err(|e| OutboundError::Failed(e.to_string()));
+                },
+            };
+            self.record(Some(&error));
+
+            match retry {
+                Some(next) if retry_safe && self.take_retry_token() => {
+                    let backoff = RETRY_BACKOFF_MS * 2u64.pow(attempt) + rand::random::<u64>() % RETRY_BACKOFF_MS;
+                    tokio::time::sleep(Duration::from_millis(backoff)).await;
+                    self.admit(false)?;
+                    request = next;
+                    attempt += 1;
+                },
+                // Réponse d'erreur rendue à l'appelant, qui en lit le message
+                _ => return result.map_err(|e| OutboundError::Failed(format!("{}: {}", self.service, e))),
+            }
+        }
+    }
+
+    /// Autorise l'appel selon l'état du disjoncteur ; `first` crédite le budget
+    /// de nouvelles tentatives
+    fn admit(&self, first: bool) -> Result<(), OutboundError> {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let blocked_until = match breaker.state {
+            BreakerState::Closed => None,
+            BreakerState::Open => breaker.opened_at.map(|at| at + self.policy.cooldown).filter(|until| *until > now),
+            BreakerState::HalfOpen => breaker.probe_until.filter(|until| *until > now),
+        };
+        if let Some(until) = blocked_until {
+            breaker.rejected += 1;
+            return Err(OutboundError::Unavailable { service: self.service, retry_after: until - now });
+        }
+        if breaker.state != BreakerState::Closed {
+            // Appel d'essai ; s'il n'aboutit pas (requête abandonnée), un autre
+            // est autorisé après une nouvelle période
+            breaker.state = BreakerState::HalfOpen;
+            breaker.probe_until = Some(now + self.policy.cooldown);
+        }
+        if first {
+            breaker.retry_tokens = (breaker.retry_tokens + self.policy.retry_ratio).min(MAX_RETRY_TOKENS);
+        }
+        Ok(())
+    }
+
+    fn take_retry_token(&self) -> bool {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        if breaker.retry_tokens < 1.0 {
+            return false;
+        }
+        breaker.retry_tokens -= 1.0;
+        true
+    }
+
+    fn record(&self, error: Option<&str>) {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        let error = match error {
+            Some(error) => error,
+            None => {
+                if breaker.state != BreakerState::Closed {
+                    tracing::info!(service = self.service, "Disjoncteur refermé : le service répond de nouveau");
+                }
+                breaker.state = BreakerState::Closed;
+                breaker.consecutive_failures = 0;
+                breaker.opened_at = None;
+                breaker.opened_since = None;
+                breaker.probe_until = None;
+                return;
+            },
+        };
+
+        breaker.consecutive_failures += 1;
+        breaker.last_error = Some(error.chars().take(300).collect());
+        let trips = breaker.state == BreakerState::HalfOpen
+            || (breaker.state == BreakerState::Closed && breaker.consecutive_failures >= self.policy.failure_threshold);
+        if trips {
+            tracing::warn!(
+                service = self.service,
+                failures = breaker.consecutive_failures,
+                error,
+                "Disjoncteur ouvert : appels refusés pendant {}s",
+                self.policy.cooldown.as_secs()
+            );
+            breaker.state = BreakerState::Open;
+            breaker.opened_at = Some(Instant::now());
+            breaker.opened_since.get_or_insert_with(Utc::now);
+            breaker.probe_until = None;
+            breaker.trips += 1;
+        }
+    }
+}
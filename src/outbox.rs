@@ -0,0 +1,179 @@
+// outbox.rs
+//
+// Outbox transactionnelle des événements métier : les handlers écrivent
+// l'événement (`InvestmentCreated`, `PropertyValidated`) dans la table
+// `outbox` dans la même transaction que la modification qu'il décrit. Un
+// événement n'existe donc que si la modification est validée, et n'est jamais
+// perdu si elle l'est. Les workers (voir worker.rs) publient ensuite chaque
+// événement dans sa propre transaction, qui le marque publié : les
+// notifications qu'il produit sont écrites une seule fois. Un consommateur
+// externe s'y branche en mettant une tâche en file (voir jobs.rs) depuis
+// `publish`. Une publication en échec est retentée plus tard.
+
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgExecutor;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::env;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::money::TokenAmount;
+use crate::notifications::notify;
+
+/// Délai maximal avant un nouvel essai de publication
+const MAX_RETRY_SECS: u64 = 3600;
+
+/// Événement métier, enregistré avec la modification qu'il décrit
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEvent {
+    InvestmentCreated {
+        investment_id: Uuid,
+        user_id: Uuid,
+        property_id: Uuid,
+        amount_eth: TokenAmount,
+        shares: i32,
+    },
+    PropertyValidated {
+        property_id: Uuid,
+        validated_by: Uuid,
+    },
+}
+
+impl DomainEvent {
+    /// Entité concernée (investissement ou propriété)
+    fn aggregate_id(&self) -> Uuid {
+        match self {
+            DomainEvent::InvestmentCreated { investment_id, .. } => *investment_id,
+            DomainEvent::PropertyValidated { property_id, .. } => *property_id,
+        }
+    }
+}
+
+/// Enregistre l'événement ; à appeler dans la transaction de la modification
+pub async fn record<'e>(db: impl PgExecutor<'e>, event: &DomainEvent) -> Result<(), sqlx::Error> {
+    let value = serde_json::to_value(event).unwrap_or_default();
+    sqlx::query!(
+        "INSERT INTO outbox (event_type, aggregate_id, payload) VALUES ($1, $2, $3)",
+        value["type"].as_str().unwrap_or_default(),
+        event.aggregate_id(),
+        value["data"]
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Effets d'un événement, dans la transaction qui le marque publié
+async fn publish(tx: &mut Transaction<'_, Postgres>, event: &DomainEvent) -> Result<(), sqlx::Error> {
+    match event {
+        DomainEvent::InvestmentCreated { investment_id, property_id, amount_eth, shares, .. } => {
+            let property = sqlx::query!("SELECT name, created_by FROM properties WHERE id = $1", property_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            if let Some(property) = property {
+                notify(&mut *tx, property.created_by, "investment_received",
+                    &format!("Nouvel investissement dans {} : {} part(s)", property.name, shares),
+                    serde_json::json!({
+                        "investment_id": investment_id,
+                        "property_id": property_id,
+                        "amount_eth": amount_eth,
+                        "shares": shares
+                    })).await?;
+            }
+        },
+        DomainEvent::PropertyValidated { property_id, .. } => {
+            let property = sqlx::query!("SELECT name, created_by FROM properties WHERE id = $1", property_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            if let Some(property) = property {
+                notify(&mut *tx, property.created_by, "property_validated",
+                    &format!("Votre propriété {} a été validée", property.name),
+                    serde_json::json!({ "property_id": property_id })).await?;
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Publie le plus ancien événement en attente ; renvoie `false` s'il n'y en a pas
+async fn publish_next(pool: &PgPool) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let pending = sqlx::query!(
+        r#"SELECT id, event_type, payload, attempts FROM outbox
+           WHERE published_at IS NULL AND available_at <= NOW()
+           ORDER BY created_at
+           LIMIT 1
+           FOR UPDATE SKIP LOCKED"#
+    )
+    .fetch_optional(&mut tx)
+    .await?;
+    let pending = match pending {
+        Some(pending) => pending,
+        None => return Ok(false),
+    };
+
+    let event = serde_json::from_value::<DomainEvent>(serde_json::json!({
+        "type": pending.event_type,
+        "data": pending.payload
+    }));
+    let published = match event {
+        Ok(event) => publish(&mut tx, &event).await.map_err(|e| e.to_string()),
+        Err(e) => Err(format!("Événement illisible: {}", e)),
+    };
+
+    match published {
+        Ok(()) => {
+            sqlx::query!("UPDATE outbox SET published_at = NOW() WHERE id = $1", pending.id)
+                .execute(&mut tx)
+                .await?;
+            tx.commit().await?;
+        },
+        Err(e) => {
+            tx.rollback().await?;
+            let attempts = pending.attempts + 1;
+            let delay = (1u64 << attempts.clamp(0, 30)).min(MAX_RETRY_SECS);
+            tracing::warn!(event_id = %pending.id, event_type = %pending.event_type, attempts, delay, "Publication de l'événement en échec: {}", e);
+            sqlx::query!(
+                r#"UPDATE outbox SET attempts = $2, last_error = $3,
+                   available_at = NOW() + make_interval(secs => $4)
+                   WHERE id = $1"#,
+                pending.id,
+                attempts,
+                e,
+                delay as f64
+            )
+            .execute(pool)
+            .await?;
+        },
+    }
+    Ok(true)
+}
+
+/// Lance la publication des événements. Intervalle configurable via
+/// `OUTBOX_INTERVAL_SECS` (1s par défaut).
+pub fn spawn(pool: PgPool) {
+    let interval_secs = env::var("OUTBOX_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(1);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            // Vide la file avant d'attendre le prochain tour
+            loop {
+                match publish_next(&pool).await {
+                    Ok(true) => continue,
+                    Ok(false) => break,
+                    Err(e) => {
+                        tracing::error!("Erreur de la publication des événements: {}", e);
+                        break;
+                    },
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,198 @@
+// pagination.rs
+//
+// Pagination des routes de liste, par page (`?page=2&per_page=50`) ou par
+// curseur (`?cursor=` pour la première page, puis `?cursor=<next_cursor>`).
+// La taille par défaut et le maximum sont réglés par déploiement
+// (`PAGINATION_DEFAULT_PER_PAGE`, 50, et `PAGINATION_MAX_PER_PAGE`, 500) ;
+// `?limit=` reste accepté comme synonyme de `per_page`. Le curseur est opaque
+// pour le client : il désigne la dernière ligne renvoyée (date de création et
+// identifiant). La page retenue est renvoyée dans `meta` (voir envelope.rs).
+
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use std::env;
+use uuid::Uuid;
+
+use crate::error_codes::ErrorCode;
+
+/// Taille des pages, par déploiement
+#[derive(Clone, Copy)]
+pub struct PaginationConfig {
+    default_per_page: i64,
+    max_per_page: i64,
+}
+
+impl PaginationConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: i64| env::var(name).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default);
+        let max_per_page = var("PAGINATION_MAX_PER_PAGE", 500).max(1);
+        Self {
+            default_per_page: var("PAGINATION_DEFAULT_PER_PAGE", 50).clamp(1, max_per_page),
+            max_per_page,
+        }
+    }
+}
+
+/// Position après la dernière ligne renvoyée, pour les listes triées par date de création
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(created_at: DateTime<Utc>, id: Uuid) -> Self {
+        Self { created_at, id }
+    }
+
+    fn encode(&self) -> String {
+        hex::encode(format!("{}|{}", self.created_at.to_rfc3339(), self.id))
+    }
+
+    fn decode(raw: &str) -> Option<Self> {
+        let decoded = String::from_utf8(hex::decode(raw.trim()).ok()?).ok()?;
+        let (created_at, id) = decoded.split_once('|')?;
+        Some(Self {
+            created_at: DateTime::parse_from_rfc3339(created_at).ok()?.with_timezone(&Utc),
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PageKind {
+    /// Page numérotée à partir de 1
+    Page(i64),
+    /// Curseur de la page précédente (`None` : première page)
+    Cursor(Option<Cursor>),
+}
+
+/// Extracteur : page demandée, bornée par la configuration
+#[derive(Debug, Clone, Copy)]
+pub struct Pagination {
+    pub per_page: i64,
+    pub kind: PageKind,
+}
+
+/// Page renvoyée, reprise dans `meta`
+pub struct PageInfo {
+    pub page: Option<i64>,
+    pub per_page: i64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+impl Pagination {
+    /// Lignes à lire : une de plus que la page pour savoir s'il en reste
+    pub fn limit(&self) -> i64 {
+        self.per_page + 1
+    }
+
+    pub fn offset(&self) -> i64 {
+        match self.kind {
+            PageKind::Page(page) => (page - 1).saturating_mul(self.per_page),
+            PageKind::Cursor(_) => 0,
+        }
+    }
+
+    pub fn cursor(&self) -> Option<Cursor> {
+        match self.kind {
+            PageKind::Cursor(cursor) => cursor,
+            PageKind::Page(_) => None,
+        }
+    }
+
+    pub fn is_cursor(&self) -> bool {
+        matches!(self.kind, PageKind::Cursor(_))
+    }
+
+    /// Position du curseur en paramètres d'une requête `query!` triée par
+    /// date de création décroissante :
+    /// `($n::timestamptz IS NULL OR (created_at, id) < ($n, $m))`
+    pub fn cursor_bounds(&self) -> (Option<DateTime<Utc>>, Option<Uuid>) {
+        let cursor = self.cursor();
+        (cursor.map(|c| c.created_at), cursor.map(|c| c.id))
+    }
+
+    /// Retire la ligne lue en trop et décrit la page ; `cursor_of` donne la
+    /// position d'une ligne (utilisée pour le curseur suivant)
+    pub fn finish<T>(&self, items: &mut Vec<T>, cursor_of: impl Fn(&T) -> Cursor) -> PageInfo {
+        let has_more = items.len() as i64 > self.per_page;
+        items.truncate(self.per_page as usize);
+        let next_cursor = match self.kind {
+            PageKind::Cursor(_) if has_more => items.last().map(|item| cursor_of(item).encode()),
+            _ => None,
+        };
+        PageInfo {
+            page: match self.kind {
+                PageKind::Page(page) => Some(page),
+                PageKind::Cursor(_) => None,
+            },
+            per_page: self.per_page,
+            has_more,
+            next_cursor,
+        }
+    }
+}
+
+/// Paramètres de pagination invalides (400)
+pub struct PaginationRejection(String);
+
+impl IntoResponse for PaginationRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": self.0,
+            "code": ErrorCode::InvalidPagination
+        }))).into_response()
+    }
+}
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for Pagination
+where
+    S: Send + Sync,
+{
+    type Rejection = PaginationRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let config = parts.extensions.get::<PaginationConfig>().copied().unwrap_or_else(PaginationConfig::from_env);
+
+        let (mut page, mut per_page, mut cursor) = (None, None, None);
+        for (key, value) in url::form_urlencoded::parse(parts.uri.query().unwrap_or("").as_bytes()) {
+            match key.as_ref() {
+                "page" => page = Some(value.into_owned()),
+                "per_page" | "limit" => per_page = Some(value.into_owned()),
+                "cursor" => cursor = Some(value.into_owned()),
+                _ => {},
+            }
+        }
+
+        let per_page = match per_page {
+            Some(raw) => raw.trim().parse::<i64>().ok().filter(|n| *n > 0).ok_or_else(|| PaginationRejection(format!(
+                "per_page invalide: '{}' (entier positif, au plus {})", raw, config.max_per_page
+            )))?.min(config.max_per_page),
+            None => config.default_per_page,
+        };
+
+        let kind = match (page, cursor) {
+            (Some(_), Some(_)) => return Err(PaginationRejection(
+                "page et cursor ne peuvent pas être utilisés ensemble".to_string()
+            )),
+            (Some(raw), None) => PageKind::Page(raw.trim().parse::<i64>().ok().filter(|n| *n > 0).ok_or_else(|| {
+                PaginationRejection(format!("page invalide: '{}' (entier positif)", raw))
+            })?),
+            (None, Some(raw)) if raw.trim().is_empty() => PageKind::Cursor(None),
+            (None, Some(raw)) => PageKind::Cursor(Some(Cursor::decode(&raw).ok_or_else(|| {
+                PaginationRejection("Curseur invalide".to_string())
+            })?)),
+            (None, None) => PageKind::Page(1),
+        };
+
+        Ok(Pagination { per_page, kind })
+    }
+}
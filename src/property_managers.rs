@@ -0,0 +1,377 @@
+// property_managers.rs
+//
+// Co-gestion des propriétés : en plus de son créateur, une propriété peut
+// avoir des co-gestionnaires (table `property_managers`), managers de la même
+// plateforme, avec un rôle :
+// - `owner` : comme le créateur, ajoute et retire les co-gestionnaires ;
+// - `editor` : modifie aussi la propriété, la soumet à la revue, gère sa
+//   galerie et envoie des documents (voir `check_edit` et media.rs) ;
+// - `viewer` : consultation seule.
+// Tous voient la propriété (brouillon compris) et ses investissements dans
+// leurs listes. Le créateur, les owners et l'admin ajoutent un co-gestionnaire
+// par son wallet (ou changent son rôle) ; un co-gestionnaire peut se retirer
+// lui-même.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::envelope;
+use crate::error_codes::ErrorCode;
+use crate::models::{AddPropertyManagerRequest, PropertyManager, PropertyManagerRole, PropertyStatus, UserRole};
+use crate::notifications::notify;
+use crate::wallet_address::WalletAddress;
+
+/// Propriété vue par l'utilisateur
+struct Access {
+    created_by: Uuid,
+    role: Option<PropertyManagerRole>,
+}
+
+impl Access {
+    /// Créateur, owner ou admin
+    fn can_manage(&self, user: &SessionUser) -> bool {
+        self.created_by == user.id
+            || self.role == Some(PropertyManagerRole::Owner)
+            || matches!(user.role, UserRole::Admin)
+    }
+}
+
+/// Créateur, co-gestionnaire owner ou editor, ou admin
+pub fn can_edit(user: &SessionUser, created_by: Uuid, role: Option<PropertyManagerRole>) -> bool {
+    created_by == user.id
+        || matches!(role, Some(PropertyManagerRole::Owner | PropertyManagerRole::Editor))
+        || matches!(user.role, UserRole::Admin)
+}
+
+/// Rôle de l'utilisateur parmi les co-gestionnaires de la propriété
+pub async fn manager_role<'e, E: PgExecutor<'e>>(
+    executor: E,
+    property_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<PropertyManagerRole>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT role as "role: PropertyManagerRole" FROM property_managers
+           WHERE property_id = $1 AND user_id = $2"#,
+        property_id,
+        user_id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+/// Droit de modifier une propriété déjà lue (mise à jour, soumission) :
+/// 404 si c'est le brouillon d'un autre dont l'utilisateur n'est pas
+/// co-gestionnaire, 403 pour un co-gestionnaire viewer
+pub async fn check_edit<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user: &SessionUser,
+    property_id: Uuid,
+    created_by: Uuid,
+    status: &PropertyStatus,
+) -> Result<(), Response> {
+    let role = if created_by == user.id {
+        None
+    } else {
+        manager_role(executor, property_id, user.id).await.map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Erreur lors de la vérification: {}", e),
+                "code": ErrorCode::DatabaseError
+            }))).into_response()
+        })?
+    };
+
+    if matches!(status, PropertyStatus::Draft) && created_by != user.id && role.is_none() {
+        return Err(not_found());
+    }
+    if !can_edit(user, created_by, role) {
+        return Err((StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls le créateur, ses co-gestionnaires owner ou editor et l'admin peuvent modifier la propriété",
+            "code": ErrorCode::Forbidden
+        }))).into_response());
+    }
+
+    Ok(())
+}
+
+fn not_found() -> Response {
+    (StatusCode::NOT_FOUND, Json(serde_json::json!({
+        "error": "Propriété non trouvée",
+        "code": ErrorCode::PropertyNotFound
+    }))).into_response()
+}
+
+fn forbidden() -> Response {
+    (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls le créateur, les co-gestionnaires owner et l'admin gèrent les co-gestionnaires",
+        "code": ErrorCode::PropertyManagersForbidden
+    }))).into_response()
+}
+
+fn internal(e: sqlx::Error) -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de la gestion des co-gestionnaires: {}", e),
+        "code": ErrorCode::DatabaseError
+    }))).into_response()
+}
+
+/// Charge la propriété et le rôle de l'utilisateur ; `None` si elle n'existe
+/// pas sur sa plateforme ou s'il s'agit du brouillon d'un autre
+async fn access(pool: &PgPool, user: &SessionUser, property_id: Uuid) -> Result<Option<Access>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT p.created_by, p.status as "status: PropertyStatus", pm.role as "role?: PropertyManagerRole"
+           FROM properties p
+           LEFT JOIN property_managers pm ON pm.property_id = p.id AND pm.user_id = $2
+           WHERE p.id = $1 AND p.tenant_id = $3"#,
+        property_id,
+        user.id,
+        user.tenant_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row
+        .filter(|row| !matches!(row.status, PropertyStatus::Draft) || row.created_by == user.id || row.role.is_some())
+        .map(|row| Access { created_by: row.created_by, role: row.role }))
+}
+
+async fn list_managers(pool: &PgPool, property_id: Uuid) -> Result<Vec<PropertyManager>, sqlx::Error> {
+    sqlx::query_as!(
+        PropertyManager,
+        r#"SELECT pm.property_id, pm.user_id, u.wallet, u.name, pm.role as "role: PropertyManagerRole", pm.added_by, pm.created_at
+           FROM property_managers pm
+           JOIN users u ON u.id = pm.user_id
+           WHERE pm.property_id = $1
+           ORDER BY pm.created_at"#,
+        property_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Route : créateur et co-gestionnaires d'une propriété (créateur,
+/// co-gestionnaires et admin)
+pub async fn get_property_managers(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let access = match access(&pool, &user, property_id).await {
+        Ok(Some(access)) => access,
+        Ok(None) => return not_found(),
+        Err(e) => return internal(e),
+    };
+    if access.role.is_none() && access.created_by != user.id && !matches!(user.role, UserRole::Admin) {
+        return forbidden();
+    }
+
+    match list_managers(&pool, property_id).await {
+        Ok(managers) => envelope::list("managers", &managers)
+            .with("created_by", access.created_by)
+            .into_response(),
+        Err(e) => internal(e),
+    }
+}
+
+/// Route : ajouter un co-gestionnaire par son wallet, ou changer son rôle
+/// (créateur, owner ou admin)
+pub async fn add_property_manager(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+    Json(payload): Json<AddPropertyManagerRequest>,
+) -> impl IntoResponse {
+    let access = match access(&pool, &user, property_id).await {
+        Ok(Some(access)) => access,
+        Ok(None) => return not_found(),
+        Err(e) => return internal(e),
+    };
+    if !access.can_manage(&user) {
+        return forbidden();
+    }
+
+    let invalid = || (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+        "error": "Seul un manager actif de la plateforme peut être co-gestionnaire",
+        "code": ErrorCode::InvalidPropertyManager
+    }))).into_response();
+    let Some(wallet) = WalletAddress::parse(payload.wallet.trim()) else {
+        return invalid();
+    };
+    let invitee = match sqlx::query!(
+        r#"SELECT id FROM users
+           WHERE wallet = $1 AND tenant_id = $2 AND role = 'manager' AND deactivated_at IS NULL"#,
+        wallet.as_str(),
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(invitee)) => invitee.id,
+        Ok(None) => return invalid(),
+        Err(e) => return internal(e),
+    };
+    if invitee == access.created_by {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Le créateur gère déjà la propriété",
+            "code": ErrorCode::PropertyCreatorManager
+        }))).into_response();
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let added = sqlx::query_scalar!(
+            r#"INSERT INTO property_managers (property_id, user_id, role, added_by)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (property_id, user_id) DO UPDATE SET role = EXCLUDED.role
+               RETURNING (xmax = 0) as "added!""#,
+            property_id,
+            invitee,
+            payload.role as PropertyManagerRole,
+            user.id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        if added {
+            let name = sqlx::query_scalar!("SELECT name FROM properties WHERE id = $1", property_id)
+                .fetch_one(&mut tx)
+                .await?;
+            notify(&mut tx, invitee, "property_manager_added",
+                &format!("Vous êtes co-gestionnaire de la propriété \"{}\"", name),
+                serde_json::json!({ "property_id": property_id, "role": payload.role })).await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(added)
+    }.await;
+
+    match result {
+        Ok(added) => {
+            tracing::info!(%property_id, manager_id = %invitee, role = ?payload.role, by = %user.id, "Co-gestionnaire enregistré");
+            match list_managers(&pool, property_id).await {
+                Ok(managers) => {
+                    let manager = managers.into_iter().find(|m| m.user_id == invitee);
+                    let status = if added { StatusCode::CREATED } else { StatusCode::OK };
+                    (status, Json(serde_json::json!({
+                        "manager": manager,
+                        "message": if added { "Co-gestionnaire ajouté" } else { "Rôle du co-gestionnaire modifié" }
+                    }))).into_response()
+                },
+                Err(e) => internal(e),
+            }
+        },
+        Err(e) => internal(e),
+    }
+}
+
+/// Route : retirer un co-gestionnaire (créateur, owner ou admin ; un
+/// co-gestionnaire peut se retirer lui-même)
+pub async fn remove_property_manager(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path((property_id, manager_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let access = match access(&pool, &user, property_id).await {
+        Ok(Some(access)) => access,
+        Ok(None) => return not_found(),
+        Err(e) => return internal(e),
+    };
+    if !access.can_manage(&user) && manager_id != user.id {
+        return forbidden();
+    }
+
+    match sqlx::query!(
+        "DELETE FROM property_managers WHERE property_id = $1 AND user_id = $2",
+        property_id,
+        manager_id
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Co-gestionnaire non trouvé",
+            "code": ErrorCode::PropertyManagerNotFound
+        }))).into_response(),
+        Ok(_) => {
+            tracing::info!(%property_id, %manager_id, by = %user.id, "Co-gestionnaire retiré");
+            (StatusCode::OK, Json(serde_json::json!({
+                "message": "Co-gestionnaire retiré"
+            }))).into_response()
+        },
+        Err(e) => internal(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+    use crate::wallet_address::WalletAddress;
+    use sqlx::PgConnection;
+
+    async fn session(conn: &mut PgConnection, role: UserRole) -> SessionUser {
+        let (id, wallet) = test_db::insert_user(conn, role).await;
+        SessionUser {
+            id,
+            tenant_id: crate::tenants::DEFAULT_TENANT_ID,
+            wallet: WalletAddress::parse(&wallet).unwrap(),
+            name: None,
+            role,
+            created_at: chrono::Utc::now(),
+            impersonation_id: None,
+        }
+    }
+
+    /// Brouillon créé par un manager, avec les co-gestionnaires donnés
+    async fn property_with(conn: &mut PgConnection, managers: &[(&SessionUser, PropertyManagerRole)]) -> (Uuid, Uuid) {
+        let (creator, _) = test_db::insert_user(conn, UserRole::Manager).await;
+        let suffix = Uuid::new_v4().simple().to_string();
+        let property_id = sqlx::query_scalar!(
+            r#"INSERT INTO properties (onchain_id, slug, name, location, type, total_price, token_price, annual_yield, created_by)
+               VALUES ($1, $1, 'Test', 'Paris', 'appartement', 1000, 10, 5, $2) RETURNING id"#,
+            format!("test-{}", suffix),
+            creator
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap();
+        for (user, role) in managers {
+            sqlx::query!(
+                "INSERT INTO property_managers (property_id, user_id, role, added_by) VALUES ($1, $2, $3, $4)",
+                property_id,
+                user.id,
+                *role as PropertyManagerRole,
+                creator
+            )
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        }
+        (property_id, creator)
+    }
+
+    async fn edit_status(conn: &mut PgConnection, user: &SessionUser, property_id: Uuid, created_by: Uuid) -> StatusCode {
+        match check_edit(&mut *conn, user, property_id, created_by, &PropertyStatus::Draft).await {
+            Ok(()) => StatusCode::OK,
+            Err(response) => response.status(),
+        }
+    }
+
+    #[tokio::test]
+    async fn editor_may_edit_viewer_may_not() {
+        let Some(mut tx) = test_db::begin().await else { return };
+        let editor = session(&mut tx, UserRole::Manager).await;
+        let viewer = session(&mut tx, UserRole::Manager).await;
+        let stranger = session(&mut tx, UserRole::Manager).await;
+        let (property_id, creator) = property_with(
+            &mut tx,
+            &[(&editor, PropertyManagerRole::Editor), (&viewer, PropertyManagerRole::Viewer)],
+        ).await;
+
+        assert_eq!(edit_status(&mut tx, &editor, property_id, creator).await, StatusCode::OK);
+        assert_eq!(edit_status(&mut tx, &viewer, property_id, creator).await, StatusCode::FORBIDDEN);
+        assert_eq!(edit_status(&mut tx, &stranger, property_id, creator).await, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,205 @@
+// public.rs
+//
+// API publique en lecture seule (`/public/v1`) destinée aux sites partenaires.
+// Les lectures passent par shadow_reads.rs, qui peut les comparer à celles
+// d'un réplica ou d'une requête réécrite.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::api_keys::ApiKeyAuth;
+use crate::envelope;
+use crate::models::Tenant;
+use crate::shadow_reads::{self, ShadowReads};
+
+/// Propriétés validées et publiées d'une plateforme
+async fn fetch_public_properties(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<Value>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"SELECT id, onchain_id, slug, name, location, type, description,
+           total_price, token_price, annual_yield, image_url, created_at
+           FROM properties
+           WHERE status = 'validated' AND published_at IS NOT NULL AND tenant_id = $1
+           ORDER BY created_at DESC"#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| {
+        serde_json::json!({
+            "id": row.id,
+            "onchain_id": row.onchain_id,
+            "slug": row.slug,
+            "name": row.name,
+            "location": row.location,
+            "type": row.r#type,
+            "description": row.description,
+            "total_price": row.total_price,
+            "token_price": row.token_price,
+            "annual_yield": row.annual_yield,
+            "image_url": row.image_url,
+            "created_at": row.created_at
+        })
+    }).collect())
+}
+
+/// Statistiques agrégées d'une plateforme
+async fn fetch_public_stats(pool: &PgPool, tenant_id: Uuid) -> Result<Value, sqlx::Error> {
+    let stats = sqlx::query!(
+        r#"SELECT
+           (SELECT COUNT(*) FROM properties WHERE status = 'validated' AND published_at IS NOT NULL AND tenant_id = $1) as "properties_count!",
+           COUNT(i.id) as "investments_count!",
+           COUNT(DISTINCT i.user_id) as "investors_count!",
+           COALESCE(SUM(i.amount_eth), 0) as "total_invested_eth!"
+           FROM investments i
+           JOIN properties p ON p.id = i.property_id
+           WHERE p.status = 'validated' AND p.published_at IS NOT NULL AND i.status <> 'refunded'
+           AND p.tenant_id = $1"#,
+        tenant_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(serde_json::json!({
+        "properties_count": stats.properties_count,
+        "investments_count": stats.investments_count,
+        "investors_count": stats.investors_count,
+        "total_invested_eth": stats.total_invested_eth
+    }))
+}
+
+/// Données du widget, financement calculé sur les investissements non remboursés
+async fn fetch_widget(pool: &PgPool, property_id: Uuid) -> Result<Option<Value>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT p.id, p.slug, p.name, p.image_url, p.location, p.token_price, p.total_price, p.annual_yield,
+           COALESCE(ROUND(SUM(i.shares) * p.token_price * 100 / NULLIF(p.total_price, 0), 2), 0) as "funding_percent!"
+           FROM properties p
+           LEFT JOIN investments i ON i.property_id = p.id AND i.status <> 'refunded'
+           WHERE p.id = $1 AND p.status = 'validated' AND p.published_at IS NOT NULL
+           GROUP BY p.id"#,
+        property_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| serde_json::json!({
+        "id": row.id,
+        "slug": row.slug,
+        "name": row.name,
+        "image_url": row.image_url,
+        "location": row.location,
+        "token_price": row.token_price,
+        "total_price": row.total_price,
+        "annual_yield": row.annual_yield,
+        "funding_percent": row.funding_percent
+    })))
+}
+
+/// Réécriture de `fetch_widget` : financement lu dans `shares_sold`, tenu à
+/// jour par trigger, sans agrégation des investissements
+async fn fetch_widget_from_shares_sold(pool: &PgPool, property_id: Uuid) -> Result<Option<Value>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"SELECT id, slug, name, image_url, location, token_price, total_price, annual_yield,
+           COALESCE(ROUND(shares_sold * token_price * 100 / NULLIF(total_price, 0), 2), 0) as "funding_percent!"
+           FROM properties
+           WHERE id = $1 AND status = 'validated' AND published_at IS NOT NULL"#,
+        property_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| serde_json::json!({
+        "id": row.id,
+        "slug": row.slug,
+        "name": row.name,
+        "image_url": row.image_url,
+        "location": row.location,
+        "token_price": row.token_price,
+        "total_price": row.total_price,
+        "annual_yield": row.annual_yield,
+        "funding_percent": row.funding_percent
+    })))
+}
+
+/// Route partenaire listant les propriétés validées de la plateforme (clé d'API requise)
+pub async fn get_public_properties(
+    ApiKeyAuth(_api_key_id): ApiKeyAuth,
+    State(pool): State<PgPool>,
+    Extension(tenant): Extension<Tenant>,
+    Extension(shadow): Extension<Option<ShadowReads>>,
+) -> impl IntoResponse {
+    let tenant_id = tenant.id;
+    match shadow_reads::read(
+        shadow.as_ref(),
+        &pool,
+        "public_properties",
+        format!("tenant={}", tenant_id),
+        fetch_public_properties(&pool, tenant_id),
+        move |replica| async move { fetch_public_properties(&replica, tenant_id).await },
+    ).await {
+        Ok(properties) => envelope::list("properties", &properties).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route partenaire renvoyant les statistiques agrégées de la plateforme (clé d'API requise)
+pub async fn get_public_stats(
+    ApiKeyAuth(_api_key_id): ApiKeyAuth,
+    State(pool): State<PgPool>,
+    Extension(tenant): Extension<Tenant>,
+    Extension(shadow): Extension<Option<ShadowReads>>,
+) -> impl IntoResponse {
+    let tenant_id = tenant.id;
+    match shadow_reads::read(
+        shadow.as_ref(),
+        &pool,
+        "public_stats",
+        format!("tenant={}", tenant_id),
+        fetch_public_stats(&pool, tenant_id),
+        move |replica| async move { fetch_public_stats(&replica, tenant_id).await },
+    ).await {
+        Ok(stats) => (StatusCode::OK, Json(stats)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route de données pour le widget embarquable (sans authentification).
+/// Réponse compacte et mise en cache côté client/CDN.
+pub async fn get_property_widget(
+    State(pool): State<PgPool>,
+    Extension(shadow): Extension<Option<ShadowReads>>,
+    Path(property_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match shadow_reads::read(
+        shadow.as_ref(),
+        &pool,
+        "property_widget",
+        format!("property={}", property_id),
+        fetch_widget(&pool, property_id),
+        move |replica| async move { fetch_widget_from_shares_sold(&replica, property_id).await },
+    ).await {
+        Ok(Some(widget)) => (
+            StatusCode::OK,
+            [(header::CACHE_CONTROL, "public, max-age=3600, stale-while-revalidate=86400")],
+            Json(widget),
+        ).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
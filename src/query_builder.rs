@@ -0,0 +1,339 @@
+// query_builder.rs
+//
+// Tri et filtres des routes de liste qui déclarent une liste blanche
+// (`ListSpec`) : `GET /api/users` (`USER_LIST`), `GET /api/admin/request-logs`
+// (`REQUEST_LOG_LIST`) et `GET /api/admin/jobs/failed` (`FAILED_JOB_LIST`).
+// Les autres routes de liste lisent leurs propres paramètres typés. Chaque clé
+// de tri et chaque filtre correspond à une colonne fixée dans le code, les
+// valeurs sont toujours passées en paramètres liés (jamais interpolées dans le
+// SQL) et un paramètre inconnu est refusé (400). La page est celle de
+// l'extracteur `Pagination` (voir pagination.rs). La requête est complétée
+// avec `sqlx::QueryBuilder`.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, QueryBuilder};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::error_codes::ErrorCode;
+use crate::pagination::Pagination;
+
+/// Paramètres lus par l'extracteur `Pagination`
+const PAGINATION_PARAMS: &[&str] = &["page", "per_page", "limit", "cursor"];
+
+/// Seule colonne de tri compatible avec la pagination par curseur
+const CURSOR_SORT_COLUMN: &str = "created_at";
+
+/// Sens du tri (`?sort=cle` croissant, `?sort=-cle` décroissant)
+#[derive(Debug, Clone, Copy)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// Comparaison appliquée par un filtre
+#[derive(Debug, Clone, Copy)]
+pub enum FilterOp {
+    /// `colonne = valeur`
+    Eq,
+    /// `colonne >= valeur`
+    Gte,
+    /// `colonne < valeur`
+    Lt,
+    /// `colonne ILIKE '%valeur%'` (texte uniquement)
+    Contains,
+    /// `colonne` commence par `valeur` (texte uniquement)
+    StartsWith,
+}
+
+/// Type attendu de la valeur d'un filtre
+#[derive(Debug, Clone, Copy)]
+pub enum FilterKind {
+    Text,
+    Uuid,
+    Int,
+    /// Date RFC 3339 (`2024-01-31T00:00:00Z`) ou jour (`2024-01-31`)
+    Timestamp,
+    /// Valeur parmi une liste ; comparée au texte de la colonne (types enum)
+    OneOf(&'static [&'static str]),
+}
+
+/// Filtre autorisé : paramètre de requête, colonne et comparaison
+pub struct Filter {
+    pub param: &'static str,
+    pub column: &'static str,
+    pub op: FilterOp,
+    pub kind: FilterKind,
+}
+
+/// Tri et filtres autorisés pour une route de liste
+pub struct ListSpec {
+    /// Clé de tri publique et colonne correspondante
+    pub sorts: &'static [(&'static str, &'static str)],
+    pub default_sort: (&'static str, SortDirection),
+    pub filters: &'static [Filter],
+    /// Paramètres traités ailleurs par la route (`include`, `fields`...)
+    pub passthrough: &'static [&'static str],
+}
+
+#[derive(Debug)]
+enum FilterValue {
+    Text(String),
+    Uuid(Uuid),
+    Int(i64),
+    Timestamp(DateTime<Utc>),
+}
+
+/// Tri, filtres et page validés d'une requête de liste
+pub struct ListQuery {
+    filters: Vec<(&'static Filter, FilterValue)>,
+    sort_column: &'static str,
+    direction: SortDirection,
+    pagination: Pagination,
+}
+
+/// Paramètre de requête refusé
+pub struct ListQueryError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+fn reject(code: ErrorCode, message: String) -> ListQueryError {
+    ListQueryError { code, message }
+}
+
+/// Valeur d'un filtre `LIKE` : les jokers saisis (`%`, `_`) sont pris
+/// littéralement
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+fn parse_value(filter: &Filter, raw: &str) -> Option<FilterValue> {
+    let raw = raw.trim();
+    match filter.kind {
+        FilterKind::Text => Some(FilterValue::Text(raw.to_string())).filter(|_| !raw.is_empty()),
+        FilterKind::Uuid => raw.parse().ok().map(FilterValue::Uuid),
+        FilterKind::Int => raw.parse().ok().map(FilterValue::Int),
+        FilterKind::Timestamp => DateTime::parse_from_rfc3339(raw)
+            .map(|t| t.with_timezone(&Utc))
+            .ok()
+            .or_else(|| raw.parse::<chrono::NaiveDate>().ok().and_then(|d| d.and_hms_opt(0, 0, 0)).map(|t| t.and_utc()))
+            .map(FilterValue::Timestamp),
+        FilterKind::OneOf(values) => values.iter()
+            .find(|v| v.eq_ignore_ascii_case(raw))
+            .map(|v| FilterValue::Text(v.to_string())),
+    }
+}
+
+impl ListSpec {
+    /// Valide les paramètres de requête contre la liste blanche
+    pub fn parse(&'static self, params: &HashMap<String, String>, pagination: Pagination) -> Result<ListQuery, ListQueryError> {
+        let mut keys: Vec<&String> = params.keys().collect();
+        keys.sort();
+
+        let mut filters = Vec::new();
+        for key in keys {
+            let value = &params[key];
+            match key.as_str() {
+                "sort" => {},
+                key if PAGINATION_PARAMS.contains(&key) => {},
+                key if self.passthrough.contains(&key) => {},
+                key => {
+                    let Some(filter) = self.filters.iter().find(|f| f.param == key) else {
+                        let mut allowed: Vec<&str> = self.filters.iter().map(|f| f.param).collect();
+                        allowed.push("sort");
+                        allowed.extend(PAGINATION_PARAMS);
+                        allowed.extend(self.passthrough);
+                        return Err(reject(ErrorCode::UnknownParameter, format!(
+                            "Paramètre inconnu: '{}' (paramètres possibles: {})", key, allowed.join(", ")
+                        )));
+                    };
+                    let Some(parsed) = parse_value(filter, value) else {
+                        let expected = match filter.kind {
+                            FilterKind::OneOf(values) => values.join(", "),
+                            kind => format!("{:?}", kind).to_lowercase(),
+                        };
+                        return Err(reject(ErrorCode::InvalidFilter, format!(
+                            "Valeur invalide pour '{}': '{}' (attendu: {})", key, value, expected
+                        )));
+                    };
+                    filters.push((filter, parsed));
+                },
+            }
+        }
+
+        let (sort_key, direction) = match params.get("sort").map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            Some(sort) => match sort.strip_prefix('-') {
+                Some(key) => (key, SortDirection::Desc),
+                None => (sort, SortDirection::Asc),
+            },
+            None => self.default_sort,
+        };
+        let Some(sort_column) = self.sorts.iter().find(|(key, _)| *key == sort_key).map(|(_, column)| *column) else {
+            return Err(reject(ErrorCode::InvalidSort, format!(
+                "Tri inconnu: '{}' (tris possibles: {}, préfixés de '-' pour l'ordre décroissant)",
+                sort_key,
+                self.sorts.iter().map(|(key, _)| *key).collect::<Vec<_>>().join(", ")
+            )));
+        };
+
+        if pagination.is_cursor() && sort_column != CURSOR_SORT_COLUMN {
+            return Err(reject(ErrorCode::InvalidPagination, format!(
+                "La pagination par curseur n'est possible qu'avec le tri par {}", CURSOR_SORT_COLUMN
+            )));
+        }
+
+        Ok(ListQuery { filters, sort_column, direction, pagination })
+    }
+}
+
+impl ListQuery {
+    /// Ajoute les filtres (`AND ...`) après une clause `WHERE` existante
+    pub fn push_filters(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        for (filter, value) in &self.filters {
+            builder.push(" AND ");
+            builder.push(filter.column);
+            if matches!(filter.kind, FilterKind::OneOf(_)) {
+                builder.push("::text");
+            }
+            builder.push(match filter.op {
+                FilterOp::Eq => " = ",
+                FilterOp::Gte => " >= ",
+                FilterOp::Lt => " < ",
+                FilterOp::Contains => " ILIKE '%' || ",
+                FilterOp::StartsWith => " LIKE ",
+            });
+            match value {
+                FilterValue::Text(v) if matches!(filter.op, FilterOp::Contains | FilterOp::StartsWith) => {
+                    builder.push_bind(escape_like(v));
+                },
+                FilterValue::Text(v) => {
+                    builder.push_bind(v.clone());
+                },
+                FilterValue::Uuid(v) => {
+                    builder.push_bind(*v);
+                },
+                FilterValue::Int(v) => {
+                    builder.push_bind(*v);
+                },
+                FilterValue::Timestamp(v) => {
+                    builder.push_bind(*v);
+                },
+            }
+            if matches!(filter.op, FilterOp::Contains | FilterOp::StartsWith) {
+                builder.push(" || '%'");
+            }
+        }
+    }
+
+    /// Ajoute la position du curseur, `ORDER BY` (départagé par la colonne
+    /// `id` de la table listée) puis `LIMIT` et `OFFSET` ; à appeler après
+    /// `push_filters`
+    pub fn push_order_and_page(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        let (direction, after) = match self.direction {
+            SortDirection::Asc => ("ASC", ">"),
+            SortDirection::Desc => ("DESC", "<"),
+        };
+        if let Some(cursor) = self.pagination.cursor() {
+            builder.push(format!(" AND ({}, id) {} (", CURSOR_SORT_COLUMN, after));
+            builder.push_bind(cursor.created_at);
+            builder.push(", ");
+            builder.push_bind(cursor.id);
+            builder.push(")");
+        }
+        builder.push(format!(" ORDER BY {} {}, id {} LIMIT ", self.sort_column, direction, direction));
+        builder.push_bind(self.pagination.limit());
+        builder.push(" OFFSET ");
+        builder.push_bind(self.pagination.offset());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagination::PageKind;
+    use crate::test_db;
+    use sqlx::Row;
+
+    static SPEC: ListSpec = ListSpec {
+        sorts: &[("created_at", "created_at"), ("name", "name")],
+        default_sort: ("created_at", SortDirection::Desc),
+        filters: &[
+            Filter { param: "name", column: "name", op: FilterOp::Contains, kind: FilterKind::Text },
+            Filter { param: "prefix", column: "name", op: FilterOp::StartsWith, kind: FilterKind::Text },
+            Filter { param: "role", column: "role", op: FilterOp::Eq, kind: FilterKind::OneOf(&["user", "admin"]) },
+        ],
+        passthrough: &["include"],
+    };
+
+    fn page() -> Pagination {
+        Pagination { per_page: 50, kind: PageKind::Page(1) }
+    }
+
+    fn parse(params: &[(&str, &str)], pagination: Pagination) -> Result<ListQuery, ListQueryError> {
+        let params = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        SPEC.parse(&params, pagination)
+    }
+
+    fn rejection(params: &[(&str, &str)], pagination: Pagination) -> ErrorCode {
+        match parse(params, pagination) {
+            Ok(_) => panic!("paramètres acceptés : {:?}", params),
+            Err(e) => e.code,
+        }
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert_eq!(rejection(&[("password", "x")], page()), ErrorCode::UnknownParameter);
+        assert_eq!(rejection(&[("sort", "password")], page()), ErrorCode::InvalidSort);
+        assert_eq!(rejection(&[("sort", "-name; DROP TABLE users")], page()), ErrorCode::InvalidSort);
+        assert_eq!(rejection(&[("role", "superadmin")], page()), ErrorCode::InvalidFilter);
+        assert_eq!(rejection(&[("sort", "name")], Pagination { per_page: 50, kind: PageKind::Cursor(None) }), ErrorCode::InvalidPagination);
+
+        assert!(parse(&[("include", "x"), ("per_page", "10"), ("sort", "-name"), ("role", "ADMIN")], page()).is_ok());
+    }
+
+    #[test]
+    fn values_are_bound_not_interpolated() {
+        let list = parse(&[("name", "'; DROP TABLE users; --"), ("role", "admin"), ("sort", "name")], page()).ok().unwrap();
+        let mut builder = QueryBuilder::new("SELECT id FROM users WHERE TRUE");
+        list.push_filters(&mut builder);
+        list.push_order_and_page(&mut builder);
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT id FROM users WHERE TRUE AND name ILIKE '%' || $1 || '%' AND role::text = $2 \
+             ORDER BY name ASC, id ASC LIMIT $3 OFFSET $4"
+        );
+    }
+
+    #[test]
+    fn like_wildcards_are_escaped() {
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("a_b"), "a\\_b");
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+        assert_eq!(escape_like("plain"), "plain");
+    }
+
+    #[tokio::test]
+    async fn like_wildcards_match_literally() {
+        let Some(mut tx) = test_db::begin().await else { return };
+        let cases: &[(&str, &str, &[&str])] = &[
+            ("name", "%", &["100%"]),
+            ("name", "_", &["a_b"]),
+            ("prefix", "a_", &["a_b"]),
+            ("prefix", "a", &["a_b", "axb"]),
+        ];
+        for (param, value, expected) in cases {
+            let list = parse(&[(param, value)], page()).ok().unwrap();
+            let mut builder = QueryBuilder::new(
+                "SELECT name FROM (VALUES ('100%'), ('1000'), ('a_b'), ('axb')) AS t(name) WHERE TRUE"
+            );
+            list.push_filters(&mut builder);
+            builder.push(" ORDER BY name");
+            let rows = builder.build().fetch_all(&mut tx).await.unwrap();
+            let names: Vec<String> = rows.iter().map(|row| row.get("name")).collect();
+            assert_eq!(names, *expected, "{}={}", param, value);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// rates.rs
+//
+// Cours de l'ETH en euros et en dollars. Une tâche de fond interroge
+// périodiquement un service de cotation (format CoinGecko `simple/price` :
+// `{"ethereum": {"eur": 3012.5, "usd": 3301.2}}`) et historise chaque cours
+// dans `exchange_rates`. L'historique est servi par période au frontend, qui
+// valorise ainsi les portefeuilles avec les mêmes cours que le backend.
+// Optionnel : sans `RATES_API_URL`, aucun cours n'est relevé.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::auth::BearerAuthUser;
+use crate::models::{AnalyticsGranularity, RateHistoryQuery};
+use crate::outbound::{Outbound, OutboundClient};
+use crate::stats::{granularity_unit, MAX_ANALYTICS_POINTS};
+use crate::worker;
+
+/// Devises relevées (cours d'un ETH dans chacune)
+pub const CURRENCIES: &[&str] = &["EUR", "USD"];
+
+#[derive(Clone)]
+pub struct RatesFeed {
+    api_url: String,
+    api_key: Option<String>,
+    client: OutboundClient,
+}
+
+impl RatesFeed {
+    /// Renvoie `None` si `RATES_API_URL` manque (`RATES_API_KEY` optionnelle,
+    /// envoyée dans l'en-tête `x-cg-demo-api-key`)
+    pub fn from_env(outbound: &Outbound) -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+        Some(Self {
+            api_url: var("RATES_API_URL")?,
+            api_key: var("RATES_API_KEY"),
+            client: outbound.client("rates")?,
+        })
+    }
+
+    /// Cours actuels, par devise
+    async fn fetch(&self) -> Result<Vec<(&'static str, BigDecimal)>, String> {
+        let mut request = self.client.get(&self.api_url);
+        if let Some(key) = &self.api_key {
+            request = request.header("x-cg-demo-api-key", key);
+        }
+        let response = self.client.send(request).await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("le service de cotation a répondu {}", response.status()));
+        }
+        let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+
+        CURRENCIES.iter()
+            .map(|currency| {
+                body["ethereum"][currency.to_lowercase()]
+                    .as_number()
+                    .and_then(|rate| BigDecimal::from_str(&rate.to_string()).ok())
+                    .filter(|rate| *rate > BigDecimal::from(0))
+                    .map(|rate| (*currency, rate))
+                    .ok_or_else(|| format!("cours ETH/{} absent de la réponse", currency))
+            })
+            .collect()
+    }
+}
+
+/// Relève et historise les cours actuels
+async fn refresh(pool: &PgPool, feed: &RatesFeed) -> Result<usize, String> {
+    let rates = feed.fetch().await?;
+    let fetched_at = Utc::now();
+    for (currency, rate) in &rates {
+        sqlx::query!(
+            "INSERT INTO exchange_rates (currency, eth_rate, fetched_at) VALUES ($1, $2, $3)",
+            currency,
+            rate,
+            fetched_at
+        )
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(rates.len())
+}
+
+/// Relève les cours toutes les `RATES_INTERVAL_SECS` secondes (300 par défaut)
+pub fn spawn(pool: PgPool, feed: RatesFeed) {
+    let interval_secs = env::var("RATES_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(300);
+
+    tokio::spawn(async move {
+        let period = Duration::from_secs(interval_secs);
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Some(Err(e)) = worker::run_exclusive(&pool, "rates", period, refresh(&pool, &feed)).await {
+                tracing::error!("Erreur lors du relevé des cours de l'ETH: {}", e);
+            }
+        }
+    });
+}
+
+/// Route : historique des cours de l'ETH, un point par période et par devise.
+/// Chaque point est le dernier cours relevé avant la fin de la période (repris
+/// de la période précédente si aucun relevé), `null` avant le premier relevé.
+pub async fn get_rate_history(
+    BearerAuthUser(_user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<RateHistoryQuery>,
+) -> impl IntoResponse {
+    let granularity = query.granularity.unwrap_or(AnalyticsGranularity::Day);
+    let (unit, step) = granularity_unit(granularity);
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - step * 30);
+    if from > to {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "La date de début doit précéder la date de fin"
+        }))).into_response();
+    }
+    if (to - from).num_seconds() / step.num_seconds() >= MAX_ANALYTICS_POINTS {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Période trop longue pour cette granularité (au plus {} points)", MAX_ANALYTICS_POINTS)
+        }))).into_response();
+    }
+
+    let currencies: Vec<String> = CURRENCIES.iter().map(|c| c.to_string()).collect();
+    match sqlx::query!(
+        r#"WITH buckets AS (
+               SELECT generate_series(date_trunc($1, $2::timestamptz), date_trunc($1, $3::timestamptz),
+                                      ('1 ' || $1)::interval) AS bucket
+           )
+           SELECT b.bucket as "bucket!", c.currency as "currency!", r.eth_rate as "eth_rate?"
+           FROM buckets b
+           CROSS JOIN unnest($4::text[]) AS c(currency)
+           LEFT JOIN LATERAL (
+               SELECT eth_rate FROM exchange_rates
+               WHERE currency = c.currency
+               AND fetched_at < b.bucket + ('1 ' || $1)::interval AND fetched_at <= $3
+               ORDER BY fetched_at DESC
+               LIMIT 1
+           ) r ON TRUE
+           ORDER BY b.bucket"#,
+        unit,
+        from,
+        to,
+        &currencies
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(points) => {
+            let mut labels = Vec::new();
+            let mut rates: BTreeMap<String, Vec<Option<f64>>> = BTreeMap::new();
+            for point in points {
+                if labels.last() != Some(&point.bucket) {
+                    labels.push(point.bucket);
+                }
+                rates.entry(point.currency)
+                    .or_default()
+                    .push(point.eth_rate.and_then(|rate| rate.to_string().parse::<f64>().ok()));
+            }
+            (StatusCode::OK, Json(serde_json::json!({
+                "granularity": granularity,
+                "from": from,
+                "to": to,
+                "labels": labels,
+                "rates": rates
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération des cours: {}", e)
+        }))).into_response(),
+    }
+}
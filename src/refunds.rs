@@ -0,0 +1,313 @@
+// refunds.rs
+//
+// Remboursement des investissements : l'investisseur en fait la demande, un
+// admin l'approuve (les parts sont alors remises en vente) ou la refuse, puis
+// enregistre la transaction de remboursement. Chaque étape est notifiée.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::chain::parse_tx_hash;
+use crate::envelope;
+use crate::investment_revisions;
+use crate::models::{
+    CreateRefundRequest, InvestmentRefund, InvestmentStatus, RefundListQuery, RefundPaidRequest, RefundStatus,
+    ReviewRefundRequest, UserRole,
+};
+use crate::notifications::{notify, notify_tenant_admins};
+use crate::tags::is_unique_violation;
+use crate::money::{TokenAmount, Wei};
+
+type Outcome = Result<InvestmentRefund, (StatusCode, &'static str)>;
+
+fn outcome_response(result: Result<Outcome, sqlx::Error>, success: StatusCode, message: &str) -> Response {
+    match result {
+        Ok(Ok(refund)) => (success, Json(serde_json::json!({
+            "refund": refund,
+            "message": message
+        }))).into_response(),
+        Ok(Err((status, error))) => (status, Json(serde_json::json!({
+            "error": error
+        }))).into_response(),
+        Err(e) if is_unique_violation(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Une demande de remboursement est déjà en cours pour cet investissement"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du traitement du remboursement: {}", e)
+        }))).into_response(),
+    }
+}
+
+fn admin_only(user: &SessionUser) -> Option<Response> {
+    (!matches!(user.role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent traiter les remboursements"
+    }))).into_response())
+}
+
+/// Route pour demander le remboursement d'un investissement (investisseur uniquement)
+pub async fn request_refund(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(investment_id): Path<Uuid>,
+    Json(payload): Json<CreateRefundRequest>,
+) -> impl IntoResponse {
+    let reason = payload.reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let investment = sqlx::query!(
+            r#"SELECT user_id, property_id, amount_eth, amount_wei, status as "status: InvestmentStatus"
+               FROM investments WHERE id = $1 FOR UPDATE"#,
+            investment_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let investment = match investment {
+            Some(investment) if investment.user_id == user.id => investment,
+            _ => return Ok(Err((StatusCode::NOT_FOUND, "Investissement non trouvé"))),
+        };
+        if matches!(investment.status, InvestmentStatus::Refunded) {
+            return Ok(Err((StatusCode::CONFLICT, "Cet investissement a déjà été remboursé")));
+        }
+
+        let refund = sqlx::query_as!(
+            InvestmentRefund,
+            r#"INSERT INTO investment_refunds (investment_id, user_id, reason, amount_eth, amount_wei)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING id, investment_id, user_id, reason, status as "status: RefundStatus", amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei",
+                         reviewed_by, reviewed_at, review_comment, tx_hash, paid_at, created_at, updated_at"#,
+            investment_id,
+            user.id,
+            reason,
+            investment.amount_eth,
+            investment.amount_wei
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        let data = serde_json::json!({
+            "refund_id": refund.id,
+            "investment_id": investment_id,
+            "property_id": investment.property_id
+        });
+        notify(&mut tx, user.id, "refund_requested", "Votre demande de remboursement a été enregistrée", data.clone()).await?;
+        notify_tenant_admins(&mut tx, user.tenant_id, None, "refund_requested", "Nouvelle demande de remboursement à examiner", data).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(refund))
+    }
+    .await;
+
+    outcome_response(result, StatusCode::CREATED, "Demande de remboursement enregistrée")
+}
+
+/// Route pour lister les remboursements : les siens, ou tous ceux de la
+/// plateforme pour un admin (`?status=` pour filtrer)
+pub async fn get_refunds(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<RefundListQuery>,
+) -> impl IntoResponse {
+    let user_filter = (!matches!(user.role, UserRole::Admin)).then_some(user.id);
+
+    match sqlx::query_as!(
+        InvestmentRefund,
+        r#"SELECT id, investment_id, user_id, reason, status as "status: RefundStatus", amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei",
+                  reviewed_by, reviewed_at, review_comment, tx_hash, paid_at, created_at, updated_at
+           FROM investment_refunds
+           WHERE ($1::uuid IS NULL OR user_id = $1)
+           AND ($2::refund_status IS NULL OR status = $2)
+           AND investment_id IN (SELECT id FROM investments WHERE tenant_id = $3)
+           ORDER BY created_at DESC"#,
+        user_filter,
+        query.status as Option<RefundStatus>,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(refunds) => envelope::list("refunds", &refunds).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Existence d'une demande portant sur un investissement de la plateforme
+async fn refund_exists(tx: &mut Transaction<'_, Postgres>, refund_id: Uuid, tenant_id: Uuid) -> Result<bool, sqlx::Error> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT 1 FROM investment_refunds r
+           JOIN investments i ON i.id = r.investment_id
+           WHERE r.id = $1 AND i.tenant_id = $2"#,
+        refund_id,
+        tenant_id
+    )
+    .fetch_optional(tx)
+    .await?;
+    Ok(exists.is_some())
+}
+
+/// Approuve ou refuse une demande. L'approbation remet les parts en vente :
+/// l'investissement passe au statut `refunded` et n'est plus compté dans le
+/// financement de la propriété.
+async fn review_refund(
+    pool: &PgPool,
+    admin: &SessionUser,
+    refund_id: Uuid,
+    approve: bool,
+    comment: Option<String>,
+) -> Result<Outcome, sqlx::Error> {
+    let comment = comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    let status = if approve { RefundStatus::Approved } else { RefundStatus::Rejected };
+
+    let mut tx = pool.begin().await?;
+    let refund = sqlx::query_as!(
+        InvestmentRefund,
+        r#"UPDATE investment_refunds
+           SET status = $2, reviewed_by = $3, reviewed_at = NOW(), review_comment = $4, updated_at = NOW()
+           WHERE id = $1 AND status = 'requested'
+           AND investment_id IN (SELECT id FROM investments WHERE tenant_id = $5)
+           RETURNING id, investment_id, user_id, reason, status as "status: RefundStatus", amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei",
+                     reviewed_by, reviewed_at, review_comment, tx_hash, paid_at, created_at, updated_at"#,
+        refund_id,
+        status as RefundStatus,
+        admin.id,
+        comment,
+        admin.tenant_id
+    )
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let refund = match refund {
+        Some(refund) => refund,
+        None => {
+            return Ok(Err(match refund_exists(&mut tx, refund_id, admin.tenant_id).await? {
+                true => (StatusCode::CONFLICT, "Cette demande a déjà été traitée"),
+                false => (StatusCode::NOT_FOUND, "Demande de remboursement non trouvée"),
+            }));
+        },
+    };
+
+    if approve {
+        let previous_status = sqlx::query_scalar!(
+            r#"UPDATE investments i SET status = 'refunded'
+               FROM (SELECT id, status FROM investments WHERE id = $1 FOR UPDATE) previous
+               WHERE i.id = previous.id
+               RETURNING previous.status::text as "status!""#,
+            refund.investment_id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        investment_revisions::record(&mut tx, refund.investment_id, Some(admin.id), Some("Remboursement approuvé"), serde_json::json!({
+            "status": { "from": previous_status, "to": "refunded" }
+        }))
+        .await?;
+    }
+
+    let message = if approve {
+        "Votre demande de remboursement a été approuvée, le paiement est en cours"
+    } else {
+        "Votre demande de remboursement a été refusée"
+    };
+    notify(&mut tx, refund.user_id, if approve { "refund_approved" } else { "refund_rejected" }, message, serde_json::json!({
+        "refund_id": refund.id,
+        "investment_id": refund.investment_id,
+        "comment": refund.review_comment
+    }))
+    .await?;
+    tx.commit().await?;
+    Ok(Ok(refund))
+}
+
+/// Route admin : approuver une demande de remboursement
+pub async fn approve_refund(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(refund_id): Path<Uuid>,
+    Json(payload): Json<ReviewRefundRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+    let result = review_refund(&pool, &user, refund_id, true, payload.comment).await;
+    outcome_response(result, StatusCode::OK, "Remboursement approuvé, parts remises en vente")
+}
+
+/// Route admin : refuser une demande de remboursement
+pub async fn reject_refund(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(refund_id): Path<Uuid>,
+    Json(payload): Json<ReviewRefundRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+    let result = review_refund(&pool, &user, refund_id, false, payload.comment).await;
+    outcome_response(result, StatusCode::OK, "Demande de remboursement refusée")
+}
+
+/// Route admin : enregistrer la transaction de remboursement d'une demande approuvée
+pub async fn mark_refund_paid(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(refund_id): Path<Uuid>,
+    Json(payload): Json<RefundPaidRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+    let tx_hash = match parse_tx_hash(&payload.tx_hash) {
+        Some(tx_hash) => tx_hash,
+        None => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Hash de transaction invalide (0x + 64 caractères hexadécimaux)"
+        }))).into_response(),
+    };
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let refund = sqlx::query_as!(
+            InvestmentRefund,
+            r#"UPDATE investment_refunds
+               SET status = 'paid', tx_hash = $2, paid_at = NOW(), updated_at = NOW()
+               WHERE id = $1 AND status = 'approved'
+               AND investment_id IN (SELECT id FROM investments WHERE tenant_id = $3)
+               RETURNING id, investment_id, user_id, reason, status as "status: RefundStatus", amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei",
+                         reviewed_by, reviewed_at, review_comment, tx_hash, paid_at, created_at, updated_at"#,
+            refund_id,
+            tx_hash,
+            user.tenant_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let refund = match refund {
+            Some(refund) => refund,
+            None => {
+                return Ok(Err(match refund_exists(&mut tx, refund_id, user.tenant_id).await? {
+                    true => (StatusCode::CONFLICT, "Seule une demande approuvée et non payée peut être réglée"),
+                    false => (StatusCode::NOT_FOUND, "Demande de remboursement non trouvée"),
+                }));
+            },
+        };
+
+        notify(&mut tx, refund.user_id, "refund_paid", "Votre remboursement a été versé", serde_json::json!({
+            "refund_id": refund.id,
+            "investment_id": refund.investment_id,
+            "amount_eth": refund.amount_eth,
+            "tx_hash": refund.tx_hash
+        }))
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(refund))
+    }
+    .await;
+
+    outcome_response(result, StatusCode::OK, "Remboursement enregistré")
+}
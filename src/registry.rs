@@ -0,0 +1,191 @@
+// registry.rs
+//
+// Enregistrement on-chain des propriétés : quand l'admin valide une propriété,
+// un appel `registerProperty(string)` au contrat registre est mis en file du
+// relayer. Une fois la transaction minée, l'identifiant émis par le contrat et
+// le hash de la transaction sont reportés sur la propriété, qui peut alors être
+// publiée ; si la transaction échoue définitivement, la validation est annulée.
+// La propriété est alors rattachée au contrat (`contract_address`) : son
+// identifiant et son contrat ne changent plus.
+
+use sqlx::{Postgres, Transaction};
+use std::env;
+use uuid::Uuid;
+
+use crate::chain::{uint256_to_decimal, TxReceipt};
+use crate::eip712::{encode_uint, format_address, keccak256, parse_address};
+use crate::models::{PendingTx, PropertyStatus, RelayStatus};
+use crate::relayer;
+use crate::stats;
+
+pub const REGISTER_KIND: &str = "register_property";
+pub const REGISTER_SIGNATURE: &str = "registerProperty(string)";
+pub const REGISTERED_EVENT: &str = "PropertyRegistered(uint256,string)";
+/// Longueur maximale d'un identifiant on-chain
+const MAX_ONCHAIN_ID_LEN: usize = 64;
+
+/// Identifiant on-chain valide : 1 à 64 caractères parmi lettres, chiffres,
+/// `-` et `_` (contrainte `properties_onchain_id_check`)
+pub fn is_valid_onchain_id(onchain_id: &str) -> bool {
+    !onchain_id.is_empty()
+        && onchain_id.len() <= MAX_ONCHAIN_ID_LEN
+        && onchain_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Adresse de contrat fournie pour une propriété, en minuscules. Doit être
+/// celle du contrat registre configuré ; sans registre, seul le format est vérifié.
+pub fn check_contract_address(raw: &str) -> Result<String, ContractAddressError> {
+    let address = parse_address(raw).map(|a| format_address(&a)).ok_or(ContractAddressError::Invalid)?;
+    match Registry::from_env() {
+        Some(registry) if registry.address != address => Err(ContractAddressError::NotRegistry(registry.address)),
+        _ => Ok(address),
+    }
+}
+
+pub enum ContractAddressError {
+    Invalid,
+    NotRegistry(String), // Adresse du registre configuré
+}
+
+/// Contrat registre des actifs, lu depuis l'environnement
+#[derive(Clone)]
+pub struct Registry {
+    pub address: String,
+}
+
+impl Registry {
+    /// Renvoie `None` si l'adresse du contrat registre n'est pas configurée
+    pub fn from_env() -> Option<Self> {
+        let address = parse_address(&env::var("REGISTRY_CONTRACT_ADDRESS").ok()?)?;
+        Some(Self { address: format_address(&address) })
+    }
+
+    /// Données d'appel ABI de `registerProperty(onchain_id)`
+    fn register_calldata(&self, onchain_id: &str) -> String {
+        let mut data = keccak256(REGISTER_SIGNATURE.as_bytes())[..4].to_vec();
+        data.extend_from_slice(&encode_uint(32)); // Position de la chaîne
+        data.extend_from_slice(&encode_uint(onchain_id.len() as u128));
+        data.extend_from_slice(onchain_id.as_bytes());
+        data.resize(data.len() + (32 - onchain_id.len() % 32) % 32, 0);
+        format!("0x{}", hex::encode(data))
+    }
+}
+
+/// Demande l'enregistrement de la propriété qui passe en `validated`.
+/// Renvoie la transaction d'enregistrement en cours, ou `None` si la propriété
+/// est déjà enregistrée. `previous_status` est le statut restauré en cas d'échec.
+pub async fn request_registration(
+    db: &mut Transaction<'_, Postgres>,
+    registry: &Registry,
+    property_id: Uuid,
+    previous_status: PropertyStatus,
+    created_by: Uuid,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let property = sqlx::query!(
+        r#"SELECT p.tenant_id, p.onchain_id, p.registered_at, p.registry_tx_id, t.status as "tx_status?: RelayStatus"
+           FROM properties p
+           LEFT JOIN pending_txs t ON t.id = p.registry_tx_id
+           WHERE p.id = $1
+           FOR UPDATE OF p"#,
+        property_id
+    )
+    .fetch_one(&mut *db)
+    .await?;
+
+    if property.registered_at.is_some() {
+        return Ok(None);
+    }
+
+    // Un enregistrement déjà en cours (ex. relancé par l'admin) est réutilisé
+    let tx_id = match (property.registry_tx_id, property.tx_status) {
+        (Some(tx_id), Some(RelayStatus::Queued | RelayStatus::Submitted)) => tx_id,
+        _ => {
+            let data = registry.register_calldata(&property.onchain_id);
+            relayer::enqueue(&mut *db, property.tenant_id, REGISTER_KIND, Some(property_id), &registry.address, &data, 0.into(), Some(created_by))
+                .await?
+                .id
+        },
+    };
+
+    sqlx::query!(
+        "UPDATE properties SET registry_tx_id = $2, registry_rollback_status = $3 WHERE id = $1",
+        property_id,
+        tx_id,
+        previous_status as PropertyStatus
+    )
+    .execute(&mut *db)
+    .await?;
+    Ok(Some(tx_id))
+}
+
+/// Reporte l'issue d'une transaction d'enregistrement sur la propriété :
+/// `confirmed` contient le reçu en cas de succès, `None` en cas d'échec définitif.
+/// Renvoie `true` si la propriété vient d'être publiée.
+pub async fn settle(
+    db: &mut Transaction<'_, Postgres>,
+    tx: &PendingTx,
+    confirmed: Option<&TxReceipt>,
+) -> Result<bool, sqlx::Error> {
+    let property_id = match tx.property_id {
+        Some(property_id) => property_id,
+        None => return Ok(false),
+    };
+
+    let receipt = match confirmed {
+        Some(receipt) => receipt,
+        None => {
+            let rolled_back = sqlx::query_scalar!(
+                r#"UPDATE properties
+                   SET status = COALESCE(registry_rollback_status, 'pending'), status_updated_at = NOW(),
+                       published_at = NULL
+                   WHERE id = $1 AND registry_tx_id = $2 AND status = 'validated' AND registered_at IS NULL
+                   RETURNING status as "status: PropertyStatus""#,
+                property_id,
+                tx.id
+            )
+            .fetch_optional(&mut *db)
+            .await?;
+            if let Some(status) = rolled_back {
+                stats::record_status_change(db, property_id, status, None).await?;
+                tracing::warn!("Enregistrement on-chain de la propriété {} en échec : validation annulée", property_id);
+            }
+            return Ok(false);
+        },
+    };
+
+    // Identifiant de l'actif émis par le contrat (premier topic indexé de l'événement)
+    let event_topic = format!("0x{}", hex::encode(keccak256(REGISTERED_EVENT.as_bytes())));
+    let asset_id = receipt.logs.iter()
+        .find(|log| log.address == tx.to_address && log.topics.first() == Some(&event_topic))
+        .and_then(|log| log.topics.get(1))
+        .and_then(|topic| hex::decode(topic.trim_start_matches("0x")).ok())
+        .map(|word| uint256_to_decimal(&word));
+
+    let published = sqlx::query_scalar!(
+        r#"UPDATE properties
+           SET onchain_id = CASE
+                   WHEN $3::TEXT IS NOT NULL
+                        AND NOT EXISTS (SELECT 1 FROM properties o WHERE o.onchain_id = $3 AND o.id <> $1)
+                       THEN $3
+                   ELSE onchain_id
+               END,
+               contract_address = $5, registry_tx_hash = $4, registered_at = NOW(),
+               published_at = CASE
+                   WHEN status = 'validated' AND (publish_at IS NULL OR publish_at <= NOW())
+                       THEN COALESCE(published_at, NOW())
+                   ELSE published_at
+               END
+           WHERE id = $1 AND registry_tx_id = $2
+           RETURNING published_at IS NOT NULL as "published!""#,
+        property_id,
+        tx.id,
+        asset_id,
+        tx.tx_hash,
+        tx.to_address.to_lowercase()
+    )
+    .fetch_optional(&mut *db)
+    .await?;
+
+    tracing::info!("Propriété {} enregistrée on-chain (actif {:?})", property_id, asset_id);
+    Ok(published.unwrap_or(false))
+}
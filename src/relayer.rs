@@ -0,0 +1,757 @@
+// relayer.rs
+//
+// Relayer : envoie depuis le wallet du serveur les transactions décidées côté
+// admin (ex. enregistrement d'une propriété validée sur le contrat). Les
+// transactions sont mises en file dans `pending_txs` puis traitées par une
+// tâche de fond : attribution du nonce, frais EIP-1559, remplacement avec des
+// frais augmentés si la transaction tarde à être minée, nouveaux essais espacés.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use bigdecimal::BigDecimal;
+use k256::ecdsa::SigningKey;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::chain::{ChainRpc, TxReceipt};
+use crate::eip712::{format_address, keccak256, parse_address};
+use crate::envelope;
+use crate::feeds::{self, FeedCache};
+use crate::models::{PendingTx, RelayStatus, RelayTxListQuery, RelayTxRequest, UserRole};
+use crate::pagination::{Cursor, Pagination};
+use crate::registry;
+use crate::tags::is_unique_violation;
+
+// Verrou consultatif : une seule instance traite la file à la fois
+const RELAYER_LOCK_ID: i64 = 0x0072_656c_6179_6572;
+const GWEI: u128 = 1_000_000_000;
+
+/// Wallet du serveur et paramètres d'envoi, lus depuis l'environnement
+#[derive(Clone)]
+pub struct Relayer {
+    rpc: ChainRpc,
+    key: SigningKey,
+    pub address: String,
+    expected_chain_id: Option<u64>,
+    max_fee_wei: u128,
+    bump_after_secs: i64,
+    max_attempts: i32,
+    interval_secs: u64,
+    feed_cache: FeedCache,
+}
+
+impl Relayer {
+    /// Renvoie `None` si le nœud ou la clé du wallet ne sont pas configurés.
+    /// La clé est lue dans `RELAYER_PRIVATE_KEY`, ou dans le fichier désigné par
+    /// `RELAYER_PRIVATE_KEY_FILE` (secret monté depuis un KMS / gestionnaire de secrets).
+    pub fn from_env(rpc: Option<ChainRpc>, feed_cache: FeedCache) -> Option<Self> {
+        let rpc = rpc?;
+        let raw_key = match env::var("RELAYER_PRIVATE_KEY_FILE") {
+            Ok(path) => std::fs::read_to_string(&path)
+                .map_err(|e| tracing::error!("Lecture de {} impossible: {}", path, e))
+                .ok()?,
+            Err(_) => env::var("RELAYER_PRIVATE_KEY").ok()?,
+        };
+        let key = hex::decode(raw_key.trim().trim_start_matches("0x"))
+            .ok()
+            .and_then(|bytes| SigningKey::from_slice(&bytes).ok());
+        let key = match key {
+            Some(key) => key,
+            None => {
+                tracing::error!("Clé privée du relayer invalide");
+                return None;
+            },
+        };
+
+        let point = key.verifying_key().to_encoded_point(false);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&keccak256(&point.as_bytes()[1..])[12..]);
+
+        let env_u64 = |name: &str, default: u64| {
+            env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).filter(|v| *v > 0).unwrap_or(default)
+        };
+
+        Some(Self {
+            rpc,
+            key,
+            address: format_address(&address),
+            expected_chain_id: env::var("CHAIN_ID").ok().and_then(|v| v.parse().ok()),
+            max_fee_wei: env_u64("RELAYER_MAX_FEE_GWEI", 200) as u128 * GWEI,
+            bump_after_secs: env_u64("RELAYER_BUMP_AFTER_SECS", 60) as i64,
+            max_attempts: env_u64("RELAYER_MAX_ATTEMPTS", 5) as i32,
+            interval_secs: env_u64("RELAYER_INTERVAL_SECS", 5),
+            feed_cache,
+        })
+    }
+
+    /// Lance la tâche de traitement de la file
+    pub fn spawn(self, pool: PgPool) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(self.interval_secs));
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.tick(&pool).await {
+                    tracing::error!("Erreur du relayer: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn tick(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let mut lock = pool.acquire().await?;
+        let locked = sqlx::query_scalar!(r#"SELECT pg_try_advisory_lock($1) as "locked!""#, RELAYER_LOCK_ID)
+            .fetch_one(&mut lock)
+            .await?;
+        if !locked {
+            return Ok(());
+        }
+
+        let result = async {
+            self.track_submitted(pool).await?;
+            self.submit_queued(pool).await
+        }.await;
+
+        sqlx::query_scalar!("SELECT pg_advisory_unlock($1)", RELAYER_LOCK_ID)
+            .fetch_one(&mut lock)
+            .await?;
+        result
+    }
+
+    /// Suit les transactions diffusées : confirmation, échec ou remplacement
+    async fn track_submitted(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let txs = sqlx::query_as!(
+            PendingTx,
+            r#"SELECT id, kind, property_id, to_address, data, value_wei, status as "status: RelayStatus",
+                      from_address, nonce, gas_limit, max_fee_per_gas, max_priority_fee_per_gas,
+                      tx_hash, tx_hashes as "tx_hashes!", attempts, last_error, next_attempt_at,
+                      submitted_at, confirmed_at, block_number, created_by, created_at, updated_at
+               FROM pending_txs
+               WHERE status = 'submitted'
+               ORDER BY nonce"#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        for tx in txs {
+            if let Err(e) = self.track(pool, &tx).await? {
+                tracing::warn!("Relayer: suivi de {} impossible: {}", tx.id, e);
+                record_error(pool, tx.id, &e).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn track(&self, pool: &PgPool, tx: &PendingTx) -> Result<Result<(), String>, sqlx::Error> {
+        let (from, nonce) = match (&tx.from_address, tx.nonce) {
+            (Some(from), Some(nonce)) => (from.clone(), nonce as u64),
+            _ => return Ok(Err("Transaction diffusée sans nonce".to_string())),
+        };
+
+        // Nombre de transactions minées, lu avant les reçus pour ne pas conclure
+        // à tort qu'un autre envoi a consommé le nonce
+        let mined_count = match self.rpc.transaction_count(&from, "latest").await {
+            Ok(count) => count,
+            Err(e) => return Ok(Err(e)),
+        };
+
+        // Le reçu peut concerner n'importe quel remplacement diffusé
+        for hash in tx.tx_hashes.iter().rev() {
+            match self.rpc.transaction_receipt(hash).await {
+                Ok(Some(receipt)) => {
+                    let status = if receipt.success { RelayStatus::Confirmed } else { RelayStatus::Failed };
+                    let error = (!receipt.success).then(|| "Transaction annulée par le contrat (revert)".to_string());
+                    let mut db = pool.begin().await?;
+                    let settled = sqlx::query_as!(
+                        PendingTx,
+                        r#"UPDATE pending_txs
+                           SET status = $2, tx_hash = $3, block_number = $4, last_error = $5,
+                               confirmed_at = NOW(), updated_at = NOW()
+                           WHERE id = $1
+                           RETURNING id, kind, property_id, to_address, data, value_wei, status as "status: RelayStatus",
+                                     from_address, nonce, gas_limit, max_fee_per_gas, max_priority_fee_per_gas,
+                                     tx_hash, tx_hashes as "tx_hashes!", attempts, last_error, next_attempt_at,
+                                     submitted_at, confirmed_at, block_number, created_by, created_at, updated_at"#,
+                        tx.id,
+                        status as RelayStatus,
+                        hash,
+                        receipt.block_number as i64,
+                        error
+                    )
+                    .fetch_one(&mut db)
+                    .await?;
+                    self.settle(db, &settled, receipt.success.then_some(&receipt)).await?;
+                    tracing::info!("Relayer: transaction {} minée au bloc {} ({:?})", hash, receipt.block_number, status);
+                    return Ok(Ok(()));
+                },
+                Ok(None) => {},
+                Err(e) => return Ok(Err(e)),
+            }
+        }
+
+        if mined_count > nonce {
+            let mut db = pool.begin().await?;
+            sqlx::query!(
+                r#"UPDATE pending_txs
+                   SET status = 'failed', last_error = 'Nonce consommé par une autre transaction', updated_at = NOW()
+                   WHERE id = $1"#,
+                tx.id
+            )
+            .execute(&mut db)
+            .await?;
+            self.settle(db, tx, None).await?;
+            return Ok(Ok(()));
+        }
+
+        let stale = tx.submitted_at
+            .map(|at| at + chrono::Duration::seconds(self.bump_after_secs) <= chrono::Utc::now())
+            .unwrap_or(true);
+        if stale {
+            return self.bump(pool, tx, nonce).await;
+        }
+        Ok(Ok(()))
+    }
+
+    /// Rediffuse la transaction avec le même nonce et des frais augmentés de 12,5 %
+    /// (les nœuds exigent au moins +10 % pour accepter un remplacement)
+    async fn bump(&self, pool: &PgPool, tx: &PendingTx, nonce: u64) -> Result<Result<(), String>, sqlx::Error> {
+        let old_max_fee = tx.max_fee_per_gas.as_ref().and_then(from_decimal).unwrap_or(0);
+        let old_priority_fee = tx.max_priority_fee_per_gas.as_ref().and_then(from_decimal).unwrap_or(0);
+        if old_max_fee >= self.max_fee_wei {
+            return Ok(Err("Plafond de frais atteint, en attente de minage".to_string()));
+        }
+
+        let base_fee = match self.rpc.base_fee().await {
+            Ok(fee) => fee,
+            Err(e) => return Ok(Err(e)),
+        };
+        let priority_fee = (old_priority_fee + old_priority_fee / 8 + 1).min(self.max_fee_wei);
+        let max_fee = (old_max_fee + old_max_fee / 8 + 1)
+            .max(2 * base_fee + priority_fee)
+            .min(self.max_fee_wei);
+
+        let request = match self.build(tx, nonce, tx.gas_limit.unwrap_or(0) as u64, priority_fee, max_fee).await {
+            Ok(request) => request,
+            Err(e) => return Ok(Err(e)),
+        };
+        let (raw, hash) = request.sign(&self.key);
+        if let Err(e) = self.broadcast(&raw).await {
+            return Ok(Err(e));
+        }
+
+        sqlx::query!(
+            r#"UPDATE pending_txs
+               SET max_fee_per_gas = $2, max_priority_fee_per_gas = $3, tx_hash = $4,
+                   tx_hashes = array_append(tx_hashes, $4), submitted_at = NOW(),
+                   last_error = NULL, updated_at = NOW()
+               WHERE id = $1"#,
+            tx.id,
+            to_decimal(max_fee),
+            to_decimal(priority_fee),
+            hash
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Relayer: transaction {} remplacée par {} (frais max {} wei)", tx.id, hash, max_fee);
+        Ok(Ok(()))
+    }
+
+    /// Envoie les transactions en file arrivées à échéance, dans l'ordre de création
+    async fn submit_queued(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let txs = sqlx::query_as!(
+            PendingTx,
+            r#"SELECT id, kind, property_id, to_address, data, value_wei, status as "status: RelayStatus",
+                      from_address, nonce, gas_limit, max_fee_per_gas, max_priority_fee_per_gas,
+                      tx_hash, tx_hashes as "tx_hashes!", attempts, last_error, next_attempt_at,
+                      submitted_at, confirmed_at, block_number, created_by, created_at, updated_at
+               FROM pending_txs
+               WHERE status = 'queued' AND next_attempt_at <= NOW()
+               ORDER BY created_at
+               LIMIT 10"#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        for tx in txs {
+            if let Err(e) = self.submit(pool, &tx).await? {
+                tracing::warn!("Relayer: envoi de {} impossible: {}", tx.id, e);
+                self.schedule_retry(pool, &tx, &e).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn submit(&self, pool: &PgPool, tx: &PendingTx) -> Result<Result<(), String>, sqlx::Error> {
+        let value = from_decimal(&tx.value_wei).unwrap_or(0);
+        let gas_limit = match self.rpc.estimate_gas(&self.address, &tx.to_address, &tx.data, value).await {
+            // Marge de 20 % sur l'estimation
+            Ok(gas) => gas + gas / 5,
+            Err(e) => return Ok(Err(e)),
+        };
+        let base_fee = match self.rpc.base_fee().await {
+            Ok(fee) => fee,
+            Err(e) => return Ok(Err(e)),
+        };
+        let priority_fee = self.rpc.max_priority_fee().await.unwrap_or(GWEI * 3 / 2).min(self.max_fee_wei);
+        if base_fee + priority_fee > self.max_fee_wei {
+            return Ok(Err(format!("Base fee ({} wei) au-dessus du plafond RELAYER_MAX_FEE_GWEI", base_fee)));
+        }
+        let max_fee = (2 * base_fee + priority_fee).min(self.max_fee_wei);
+
+        // Nonce : le plus grand entre celui du nœud (mempool incluse) et le suivant
+        // de ceux déjà attribués localement (transactions éventuellement évincées)
+        let pending_count = match self.rpc.transaction_count(&self.address, "pending").await {
+            Ok(count) => count,
+            Err(e) => return Ok(Err(e)),
+        };
+        let local_next = sqlx::query_scalar!(
+            r#"SELECT COALESCE(MAX(nonce) + 1, 0) as "next!" FROM pending_txs WHERE from_address = $1"#,
+            self.address
+        )
+        .fetch_one(pool)
+        .await? as u64;
+        let nonce = pending_count.max(local_next);
+
+        let request = match self.build(tx, nonce, gas_limit, priority_fee, max_fee).await {
+            Ok(request) => request,
+            Err(e) => return Ok(Err(e)),
+        };
+        let (raw, hash) = request.sign(&self.key);
+
+        // Enregistré avant la diffusion : après un redémarrage, la transaction sera
+        // rediffusée avec le même nonce plutôt qu'envoyée une seconde fois
+        let reserved = sqlx::query!(
+            r#"UPDATE pending_txs
+               SET status = 'submitted', from_address = $2, nonce = $3, gas_limit = $4,
+                   max_fee_per_gas = $5, max_priority_fee_per_gas = $6, tx_hash = $7,
+                   tx_hashes = ARRAY[$7], submitted_at = NOW(), last_error = NULL, updated_at = NOW()
+               WHERE id = $1"#,
+            tx.id,
+            self.address,
+            nonce as i64,
+            gas_limit as i64,
+            to_decimal(max_fee),
+            to_decimal(priority_fee),
+            hash
+        )
+        .execute(pool)
+        .await;
+        match reserved {
+            Ok(_) => {},
+            Err(e) if is_unique_violation(&e) => return Ok(Err(format!("Nonce {} déjà attribué", nonce))),
+            Err(e) => return Err(e),
+        }
+
+        if let Err(e) = self.broadcast(&raw).await {
+            sqlx::query!(
+                r#"UPDATE pending_txs
+                   SET status = 'queued', from_address = NULL, nonce = NULL, tx_hash = NULL,
+                       tx_hashes = '{}', submitted_at = NULL, updated_at = NOW()
+                   WHERE id = $1"#,
+                tx.id
+            )
+            .execute(pool)
+            .await?;
+            return Ok(Err(e));
+        }
+
+        tracing::info!("Relayer: transaction {} diffusée ({}, nonce {})", tx.id, hash, nonce);
+        Ok(Ok(()))
+    }
+
+    async fn build(&self, tx: &PendingTx, nonce: u64, gas_limit: u64, priority_fee: u128, max_fee: u128) -> Result<Eip1559Tx, String> {
+        // Signer avec l'identifiant de la chaîne réellement jointe
+        let chain_id = self.rpc.chain_id().await?;
+        if matches!(self.expected_chain_id, Some(expected) if expected != chain_id) {
+            return Err(format!("Le nœud est sur la chaîne {} au lieu de CHAIN_ID", chain_id));
+        }
+
+        Ok(Eip1559Tx {
+            chain_id,
+            nonce,
+            max_priority_fee_per_gas: priority_fee,
+            max_fee_per_gas: max_fee,
+            gas_limit,
+            to: parse_address(&tx.to_address).ok_or_else(|| "Adresse de destination invalide".to_string())?,
+            value: from_decimal(&tx.value_wei).unwrap_or(0),
+            data: hex::decode(tx.data.trim_start_matches("0x")).map_err(|_| "Données d'appel invalides".to_string())?,
+        })
+    }
+
+    async fn broadcast(&self, raw: &[u8]) -> Result<(), String> {
+        match self.rpc.send_raw_transaction(raw).await {
+            Ok(_) => Ok(()),
+            // Déjà dans la mempool (rediffusion après redémarrage)
+            Err(e) if e.to_lowercase().contains("already known") => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Nouvel essai avec un délai croissant, ou abandon après `RELAYER_MAX_ATTEMPTS`
+    async fn schedule_retry(&self, pool: &PgPool, tx: &PendingTx, error: &str) -> Result<(), sqlx::Error> {
+        let attempts = tx.attempts + 1;
+        let status = if attempts >= self.max_attempts { RelayStatus::Failed } else { RelayStatus::Queued };
+        let delay_secs = (self.interval_secs as i64).saturating_mul(1 << attempts.min(16)).min(600);
+
+        let mut db = pool.begin().await?;
+        sqlx::query!(
+            r#"UPDATE pending_txs
+               SET status = $2, attempts = $3, last_error = $4,
+                   next_attempt_at = NOW() + make_interval(secs => $5), updated_at = NOW()
+               WHERE id = $1"#,
+            tx.id,
+            status as RelayStatus,
+            attempts,
+            error,
+            delay_secs as f64
+        )
+        .execute(&mut db)
+        .await?;
+
+        if matches!(status, RelayStatus::Failed) {
+            self.settle(db, tx, None).await
+        } else {
+            db.commit().await
+        }
+    }
+
+    /// Reporte l'issue d'une transaction terminée sur l'action qui l'a demandée,
+    /// dans la même transaction SQL que son changement de statut
+    async fn settle(&self, mut db: Transaction<'_, Postgres>, tx: &PendingTx, confirmed: Option<&TxReceipt>) -> Result<(), sqlx::Error> {
+        let published = match tx.kind.as_str() {
+            registry::REGISTER_KIND => registry::settle(&mut db, tx, confirmed).await?,
+            _ => false,
+        };
+        if published {
+            feeds::notify_invalidation(&mut db).await?;
+        }
+        db.commit().await?;
+
+        if published {
+            self.feed_cache.invalidate();
+        }
+        Ok(())
+    }
+}
+
+async fn record_error(pool: &PgPool, tx_id: Uuid, error: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE pending_txs SET last_error = $2, updated_at = NOW() WHERE id = $1",
+        tx_id,
+        error
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Transaction EIP-1559 (type 2)
+struct Eip1559Tx {
+    chain_id: u64,
+    nonce: u64,
+    max_priority_fee_per_gas: u128,
+    max_fee_per_gas: u128,
+    gas_limit: u64,
+    to: [u8; 20],
+    value: u128,
+    data: Vec<u8>,
+}
+
+impl Eip1559Tx {
+    /// Signe la transaction ; renvoie l'encodage à diffuser et son hash
+    fn sign(&self, key: &SigningKey) -> (Vec<u8>, String) {
+        let mut fields = vec![
+            rlp_uint(self.chain_id as u128),
+            rlp_uint(self.nonce as u128),
+            rlp_uint(self.max_priority_fee_per_gas),
+            rlp_uint(self.max_fee_per_gas),
+            rlp_uint(self.gas_limit as u128),
+            rlp_bytes(&self.to),
+            rlp_uint(self.value),
+            rlp_bytes(&self.data),
+            rlp_list(&[]), // Liste d'accès vide
+        ];
+
+        let mut unsigned = vec![0x02];
+        unsigned.extend(rlp_list(&fields));
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(&keccak256(&unsigned))
+            .expect("un digest de 32 octets est toujours signable");
+
+        fields.push(rlp_uint(recovery_id.is_y_odd() as u128));
+        fields.push(rlp_bytes(trim_leading_zeros(&signature.r().to_bytes())));
+        fields.push(rlp_bytes(trim_leading_zeros(&signature.s().to_bytes())));
+
+        let mut raw = vec![0x02];
+        raw.extend(rlp_list(&fields));
+        let hash = format!("0x{}", hex::encode(keccak256(&raw)));
+        (raw, hash)
+    }
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn rlp_length_prefix(len: usize, offset: u8) -> Vec<u8> {
+    if len <= 55 {
+        vec![offset + len as u8]
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let len_bytes = trim_leading_zeros(&len_bytes);
+        let mut prefix = vec![offset + 55 + len_bytes.len() as u8];
+        prefix.extend_from_slice(len_bytes);
+        prefix
+    }
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+    let mut encoded = rlp_length_prefix(bytes.len(), 0x80);
+    encoded.extend_from_slice(bytes);
+    encoded
+}
+
+fn rlp_uint(value: u128) -> Vec<u8> {
+    rlp_bytes(trim_leading_zeros(&value.to_be_bytes()))
+}
+
+pub fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload: Vec<u8> = items.concat();
+    let mut encoded = rlp_length_prefix(payload.len(), 0xc0);
+    encoded.extend(payload);
+    encoded
+}
+
+fn to_decimal(value: u128) -> BigDecimal {
+    BigDecimal::from_str(&value.to_string()).unwrap_or_default()
+}
+
+fn from_decimal(value: &BigDecimal) -> Option<u128> {
+    value.with_scale(0).to_string().parse().ok()
+}
+
+/// Met une transaction en file pour une plateforme ; elle sera envoyée par la
+/// tâche du relayer
+#[allow(clippy::too_many_arguments)]
+pub async fn enqueue<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+    kind: &str,
+    property_id: Option<Uuid>,
+    to_address: &str,
+    data: &str,
+    value_wei: BigDecimal,
+    created_by: Option<Uuid>,
+) -> Result<PendingTx, sqlx::Error> {
+    sqlx::query_as!(
+        PendingTx,
+        r#"INSERT INTO pending_txs (kind, property_id, to_address, data, value_wei, created_by, tenant_id)
+           VALUES ($1, $2, $3, $4, $5, $6, $7)
+           RETURNING id, kind, property_id, to_address, data, value_wei, status as "status: RelayStatus",
+                     from_address, nonce, gas_limit, max_fee_per_gas, max_priority_fee_per_gas,
+                     tx_hash, tx_hashes as "tx_hashes!", attempts, last_error, next_attempt_at,
+                     submitted_at, confirmed_at, block_number, created_by, created_at, updated_at"#,
+        kind,
+        property_id,
+        to_address,
+        data,
+        value_wei,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(executor)
+    .await
+}
+
+/// Route pour consulter l'état du relayer et de la file de la plateforme (admin seulement)
+pub async fn get_relayer_status(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(relayer): Extension<Option<Relayer>>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut consulter le relayer"
+        }))).into_response();
+    }
+
+    let counts = match sqlx::query!(
+        r#"SELECT status as "status!: RelayStatus", COUNT(*) as "count!" FROM pending_txs
+           WHERE tenant_id = $1
+           GROUP BY status"#,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => rows,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    };
+    let mut queue = serde_json::json!({ "queued": 0, "submitted": 0, "confirmed": 0, "failed": 0 });
+    for row in counts {
+        queue[serde_json::json!(row.status).as_str().unwrap_or_default()] = serde_json::json!(row.count);
+    }
+
+    let (address, balance_wei) = match &relayer {
+        Some(relayer) => (
+            Some(relayer.address.clone()),
+            relayer.rpc.balance(&relayer.address).await.ok().map(|balance| balance.to_string()),
+        ),
+        None => (None, None),
+    };
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "enabled": relayer.is_some(),
+        "address": address,
+        "balance_wei": balance_wei,
+        "queue": queue
+    }))).into_response()
+}
+
+/// Route pour lister les transactions du relayer de la plateforme, `?status=`
+/// optionnel (admin seulement)
+pub async fn get_relayer_txs(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<RelayTxListQuery>,
+    pagination: Pagination,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut consulter le relayer"
+        }))).into_response();
+    }
+    let (after_at, after_id) = pagination.cursor_bounds();
+
+    match sqlx::query_as!(
+        PendingTx,
+        r#"SELECT id, kind, property_id, to_address, data, value_wei, status as "status: RelayStatus",
+                  from_address, nonce, gas_limit, max_fee_per_gas, max_priority_fee_per_gas,
+                  tx_hash, tx_hashes as "tx_hashes!", attempts, last_error, next_attempt_at,
+                  submitted_at, confirmed_at, block_number, created_by, created_at, updated_at
+           FROM pending_txs
+           WHERE ($1::relay_status IS NULL OR status = $1) AND tenant_id = $3
+           AND ($4::timestamptz IS NULL OR (created_at, id) < ($4, $5))
+           ORDER BY created_at DESC, id DESC
+           LIMIT $2 OFFSET $6"#,
+        query.status as Option<RelayStatus>,
+        pagination.limit(),
+        user.tenant_id,
+        after_at,
+        after_id,
+        pagination.offset()
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(mut txs) => {
+            let page = pagination.finish(&mut txs, |tx| Cursor::new(tx.created_at, tx.id));
+            envelope::list("transactions", &txs).paginated(page).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour mettre en file un appel de contrat envoyé par le relayer (admin seulement)
+pub async fn create_relayer_tx(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(relayer): Extension<Option<Relayer>>,
+    Json(payload): Json<RelayTxRequest>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut utiliser le relayer"
+        }))).into_response();
+    }
+    if relayer.is_none() {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Relayer non configuré (CHAIN_RPC_URL, RELAYER_PRIVATE_KEY)"
+        }))).into_response();
+    }
+
+    let to = match parse_address(&payload.to) {
+        Some(to) => format_address(&to),
+        None => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Adresse de destination invalide"
+        }))).into_response(),
+    };
+    let data = payload.data.trim().to_lowercase();
+    if !data.starts_with("0x") || hex::decode(&data[2..]).is_err() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Les données d'appel doivent être en hexadécimal (0x...)"
+        }))).into_response();
+    }
+    let value_wei = match payload.value_wei.as_deref().map(BigDecimal::from_str) {
+        None => BigDecimal::from(0),
+        Some(Ok(value)) if value >= BigDecimal::from(0) && value.is_integer() => value,
+        Some(_) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "value_wei doit être un entier positif"
+        }))).into_response(),
+    };
+
+    match enqueue(&pool, user.tenant_id, "contract_call", None, &to, &data, value_wei, Some(user.id)).await {
+        Ok(tx) => (StatusCode::ACCEPTED, Json(serde_json::json!({
+            "transaction": tx,
+            "message": "Transaction mise en file"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour relancer une transaction en échec (admin seulement)
+pub async fn retry_relayer_tx(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(tx_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut utiliser le relayer"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        PendingTx,
+        r#"UPDATE pending_txs
+           SET status = 'queued', attempts = 0, last_error = NULL, next_attempt_at = NOW(),
+               from_address = NULL, nonce = NULL, tx_hash = NULL, tx_hashes = '{}',
+               submitted_at = NULL, confirmed_at = NULL, block_number = NULL, updated_at = NOW()
+           WHERE id = $1 AND status = 'failed' AND tenant_id = $2
+           RETURNING id, kind, property_id, to_address, data, value_wei, status as "status: RelayStatus",
+                     from_address, nonce, gas_limit, max_fee_per_gas, max_priority_fee_per_gas,
+                     tx_hash, tx_hashes as "tx_hashes!", attempts, last_error, next_attempt_at,
+                     submitted_at, confirmed_at, block_number, created_by, created_at, updated_at"#,
+        tx_id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(tx)) => (StatusCode::OK, Json(serde_json::json!({
+            "transaction": tx,
+            "message": "Transaction remise en file"
+        }))).into_response(),
+        Ok(None) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Transaction introuvable ou pas en échec"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
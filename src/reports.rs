@@ -0,0 +1,645 @@
+// reports.rs
+//
+// Rapports d'export (CSV ou PDF) générés en tâche de fond : la route admin ne
+// fait qu'enregistrer la demande et mettre en file une tâche `generate_report`
+// (voir jobs.rs) ; un worker la prend en charge, écrit le fichier ligne à ligne
+// dans un fichier temporaire puis le dépose dans le bucket privé. Le
+// téléchargement passe par une URL présignée. Un rapport n'exporte que les
+// données de la plateforme de l'admin qui l'a demandé.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures_util::{stream::BoxStream, StreamExt};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::env;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::jobs;
+use crate::models::{CreateReportRequest, Job, Report, ReportFormat, ReportKind, ReportStatus, UserRole};
+use crate::notifications::notify;
+use crate::storage::StorageConfig;
+
+type Rows<'a> = BoxStream<'a, Result<Vec<String>, sqlx::Error>>;
+
+const RECENT_REPORTS_LIMIT: i64 = 50;
+
+// Mise en page PDF : A4 paysage, Helvetica 8 pt
+const PDF_PAGE_WIDTH: u32 = 842;
+const PDF_PAGE_HEIGHT: u32 = 595;
+const PDF_MARGIN: u32 = 36;
+const PDF_LINE_HEIGHT: u32 = 11;
+const PDF_LINE_CHARS: usize = 190;
+
+fn extension(format: ReportFormat) -> &'static str {
+    match format {
+        ReportFormat::Csv => "csv",
+        ReportFormat::Pdf => "pdf",
+    }
+}
+
+fn content_type(format: ReportFormat) -> &'static str {
+    match format {
+        ReportFormat::Csv => "text/csv; charset=utf-8",
+        ReportFormat::Pdf => "application/pdf",
+    }
+}
+
+/// Tâche `generate_report` de la file (voir jobs.rs) : génère le rapport et
+/// le dépose dans le bucket privé. Un échec est renvoyé pour être retenté ; le
+/// rapport n'est marqué en échec qu'à la dernière tentative.
+pub async fn run_job(pool: &PgPool, storage: &StorageConfig, job: &Job) -> Result<(), String> {
+    let report_id = job.payload.get("report_id")
+        .and_then(|v| v.as_str())
+        .and_then(|v| v.parse::<Uuid>().ok())
+        .ok_or("report_id manquant ou invalide")?;
+
+    let report = sqlx::query_as!(
+        Report,
+        r#"UPDATE reports SET status = 'running', started_at = NOW()
+           WHERE id = $1 AND status IN ('queued', 'running')
+           RETURNING id, kind as "kind: ReportKind", format as "format: ReportFormat", from_date, to_date,
+                     status as "status: ReportStatus", storage_key, row_count, size, error, requested_by,
+                     created_at, started_at, completed_at"#,
+        report_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let report = match report {
+        Some(report) => report,
+        // Rapport supprimé ou déjà terminé
+        None => return Ok(()),
+    };
+
+    let tenant_id = sqlx::query_scalar!("SELECT tenant_id FROM reports WHERE id = $1", report.id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let path = env::temp_dir().join(format!("report-{}.{}", report.id, extension(report.format)));
+    let key = format!("reports/{}.{}", report.id, extension(report.format));
+    let outcome = match generate(pool, tenant_id, &report, &path).await {
+        Ok(written) => storage
+            .put_document(&key, &path, content_type(report.format))
+            .await
+            .map(|_| written),
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&path).await;
+
+    match outcome {
+        Ok((row_count, size)) => {
+            sqlx::query!(
+                r#"UPDATE reports SET status = 'completed', storage_key = $2, row_count = $3, size = $4,
+                   error = NULL, completed_at = NOW()
+                   WHERE id = $1"#,
+                report.id,
+                key,
+                row_count,
+                size as i64
+            )
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            if let Some(user_id) = report.requested_by {
+                notify(pool, user_id, "report_completed", "Votre rapport est prêt à être téléchargé", serde_json::json!({
+                    "report_id": report.id
+                })).await.map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        },
+        Err(e) if job.attempts >= job.max_attempts => {
+            sqlx::query!(
+                "UPDATE reports SET status = 'failed', error = $2, completed_at = NOW() WHERE id = $1",
+                report.id,
+                e
+            )
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            if let Some(user_id) = report.requested_by {
+                notify(pool, user_id, "report_failed", "La génération de votre rapport a échoué", serde_json::json!({
+                    "report_id": report.id
+                })).await.map_err(|e| e.to_string())?;
+            }
+            Err(e)
+        },
+        // Nouvel essai prévu par la file : le rapport reste en attente
+        Err(e) => {
+            sqlx::query!("UPDATE reports SET status = 'queued', error = $2 WHERE id = $1", report.id, e)
+                .execute(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            Err(e)
+        },
+    }
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.to_rfc3339()
+}
+
+/// Colonnes et lignes d'un rapport sur une plateforme, lues en flux depuis la base
+fn report_rows<'a>(
+    pool: &'a PgPool,
+    tenant_id: Uuid,
+    kind: ReportKind,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> (&'static [&'static str], Rows<'a>) {
+    match kind {
+        ReportKind::Investments => (
+            &["id", "wallet", "property", "amount_eth", "shares", "status", "tx_hash", "created_at"],
+            sqlx::query!(
+                r#"SELECT i.id, u.wallet, p.name, i.amount_eth, i.shares, i.status::text as "status!", i.tx_hash, i.created_at
+                   FROM investments i
+                   JOIN users u ON u.id = i.user_id
+                   JOIN properties p ON p.id = i.property_id
+                   WHERE ($1::timestamptz IS NULL OR i.created_at >= $1)
+                   AND ($2::timestamptz IS NULL OR i.created_at <= $2)
+                   AND i.tenant_id = $3
+                   ORDER BY i.created_at"#,
+                from,
+                to,
+                tenant_id
+            )
+            .fetch(pool)
+            .map(|row| row.map(|row| vec![
+                row.id.to_string(),
+                row.wallet,
+                row.name,
+                row.amount_eth.to_string(),
+                row.shares.to_string(),
+                row.status,
+                row.tx_hash.unwrap_or_default(),
+                format_date(row.created_at),
+            ]))
+            .boxed(),
+        ),
+        ReportKind::Users => (
+            &["id", "wallet", "name", "role", "created_at"],
+            sqlx::query!(
+                r#"SELECT id, wallet, name, role::text as "role!", created_at
+                   FROM users
+                   WHERE ($1::timestamptz IS NULL OR created_at >= $1)
+                   AND ($2::timestamptz IS NULL OR created_at <= $2)
+                   AND tenant_id = $3
+                   ORDER BY created_at"#,
+                from,
+                to,
+                tenant_id
+            )
+            .fetch(pool)
+            .map(|row| row.map(|row| vec![
+                row.id.to_string(),
+                row.wallet,
+                row.name.unwrap_or_default(),
+                row.role,
+                format_date(row.created_at),
+            ]))
+            .boxed(),
+        ),
+        ReportKind::Properties => (
+            &["id", "onchain_id", "name", "location", "status", "total_price", "token_price", "annual_yield", "created_at"],
+            sqlx::query!(
+                r#"SELECT id, onchain_id, name, location, status::text as "status!", total_price, token_price,
+                          annual_yield, created_at
+                   FROM properties
+                   WHERE ($1::timestamptz IS NULL OR created_at >= $1)
+                   AND ($2::timestamptz IS NULL OR created_at <= $2)
+                   AND tenant_id = $3
+                   ORDER BY created_at"#,
+                from,
+                to,
+                tenant_id
+            )
+            .fetch(pool)
+            .map(|row| row.map(|row| vec![
+                row.id.to_string(),
+                row.onchain_id,
+                row.name,
+                row.location,
+                row.status,
+                row.total_price.to_string(),
+                row.token_price.to_string(),
+                row.annual_yield.to_string(),
+                format_date(row.created_at),
+            ]))
+            .boxed(),
+        ),
+        ReportKind::Refunds => (
+            &["id", "investment_id", "wallet", "amount_eth", "status", "tx_hash", "created_at", "paid_at"],
+            sqlx::query!(
+                r#"SELECT r.id, r.investment_id, u.wallet, r.amount_eth, r.status::text as "status!", r.tx_hash,
+                          r.created_at, r.paid_at
+                   FROM investment_refunds r
+                   JOIN users u ON u.id = r.user_id
+                   WHERE ($1::timestamptz IS NULL OR r.created_at >= $1)
+                   AND ($2::timestamptz IS NULL OR r.created_at <= $2)
+                   AND u.tenant_id = $3
+                   ORDER BY r.created_at"#,
+                from,
+                to,
+                tenant_id
+            )
+            .fetch(pool)
+            .map(|row| row.map(|row| vec![
+                row.id.to_string(),
+                row.investment_id.to_string(),
+                row.wallet,
+                row.amount_eth.to_string(),
+                row.status,
+                row.tx_hash.unwrap_or_default(),
+                format_date(row.created_at),
+                row.paid_at.map(format_date).unwrap_or_default(),
+            ]))
+            .boxed(),
+        ),
+    }
+}
+
+/// Écrit le rapport dans `path` ; renvoie le nombre de lignes et la taille du fichier
+async fn generate(pool: &PgPool, tenant_id: Uuid, report: &Report, path: &std::path::Path) -> Result<(i32, u64), String> {
+    let (columns, mut rows) = report_rows(pool, tenant_id, report.kind, report.from_date, report.to_date);
+    let file = BufWriter::new(File::create(path).await.map_err(|e| e.to_string())?);
+
+    let mut writer = match report.format {
+        ReportFormat::Csv => ReportWriter::Csv(CsvWriter { out: file, written: 0 }),
+        ReportFormat::Pdf => {
+            let mut pdf = PdfWriter::new(file).await?;
+            let bound = |date: Option<DateTime<Utc>>| date.map(|d| d.format("%Y-%m-%d %H:%M").to_string());
+            pdf.push_line(format!(
+                "Rapport {} - période : {} à {} - généré le {}",
+                kind_title(report.kind),
+                bound(report.from_date).unwrap_or_else(|| "origine".to_string()),
+                bound(report.to_date).unwrap_or_else(|| "aujourd'hui".to_string()),
+                Utc::now().format("%Y-%m-%d %H:%M UTC")
+            )).await?;
+            pdf.push_line(String::new()).await?;
+            ReportWriter::Pdf(pdf)
+        },
+    };
+
+    writer.write_row(&columns.iter().map(|c| c.to_string()).collect::<Vec<_>>()).await?;
+    let mut row_count = 0;
+    while let Some(row) = rows.next().await {
+        writer.write_row(&row.map_err(|e| e.to_string())?).await?;
+        row_count += 1;
+    }
+    let size = writer.finish().await?;
+    Ok((row_count, size))
+}
+
+fn kind_title(kind: ReportKind) -> &'static str {
+    match kind {
+        ReportKind::Investments => "des investissements",
+        ReportKind::Users => "des utilisateurs",
+        ReportKind::Properties => "des propriétés",
+        ReportKind::Refunds => "des remboursements",
+    }
+}
+
+enum ReportWriter {
+    Csv(CsvWriter),
+    Pdf(PdfWriter),
+}
+
+impl ReportWriter {
+    async fn write_row(&mut self, cells: &[String]) -> Result<(), String> {
+        match self {
+            ReportWriter::Csv(csv) => csv.write_row(cells).await,
+            ReportWriter::Pdf(pdf) => {
+                let mut line = cells.join(" | ");
+                if line.chars().count() > PDF_LINE_CHARS {
+                    line = line.chars().take(PDF_LINE_CHARS - 1).collect::<String>() + "…";
+                }
+                pdf.push_line(line).await
+            },
+        }
+    }
+
+    async fn finish(self) -> Result<u64, String> {
+        match self {
+            ReportWriter::Csv(csv) => csv.finish().await,
+            ReportWriter::Pdf(pdf) => pdf.finish().await,
+        }
+    }
+}
+
+struct CsvWriter {
+    out: BufWriter<File>,
+    written: u64,
+}
+
+impl CsvWriter {
+    async fn write_row(&mut self, cells: &[String]) -> Result<(), String> {
+        let line = cells.iter().map(|cell| csv_escape(cell)).collect::<Vec<_>>().join(",") + "\r\n";
+        self.out.write_all(line.as_bytes()).await.map_err(|e| e.to_string())?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<u64, String> {
+        self.out.flush().await.map_err(|e| e.to_string())?;
+        Ok(self.written)
+    }
+}
+
+fn csv_escape(cell: &str) -> String {
+    // Neutralise les formules à l'ouverture dans un tableur
+    let cell = if cell.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", cell)
+    } else {
+        cell.to_string()
+    };
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell
+    }
+}
+
+/// PDF minimal (texte seul, police standard Helvetica) écrit page par page :
+/// seules les lignes de la page en cours sont gardées en mémoire
+struct PdfWriter {
+    out: BufWriter<File>,
+    written: u64,
+    offsets: BTreeMap<usize, u64>,
+    next_id: usize,
+    page_ids: Vec<usize>,
+    lines: Vec<String>,
+}
+
+// Objets réservés : catalogue, arbre des pages (écrits à la fin) et police
+const PDF_CATALOG_ID: usize = 1;
+const PDF_PAGES_ID: usize = 2;
+const PDF_FONT_ID: usize = 3;
+
+impl PdfWriter {
+    async fn new(out: BufWriter<File>) -> Result<Self, String> {
+        let mut pdf = Self {
+            out,
+            written: 0,
+            offsets: BTreeMap::new(),
+            next_id: PDF_FONT_ID + 1,
+            page_ids: Vec::new(),
+            lines: Vec::new(),
+        };
+        pdf.write(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n").await?;
+        pdf.write_object(
+            PDF_FONT_ID,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        ).await?;
+        Ok(pdf)
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.out.write_all(bytes).await.map_err(|e| e.to_string())?;
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+
+    async fn write_object(&mut self, id: usize, body: &[u8]) -> Result<(), String> {
+        self.offsets.insert(id, self.written);
+        self.write(format!("{} 0 obj\n", id).as_bytes()).await?;
+        self.write(body).await?;
+        self.write(b"\nendobj\n").await
+    }
+
+    fn lines_per_page() -> usize {
+        ((PDF_PAGE_HEIGHT - 2 * PDF_MARGIN) / PDF_LINE_HEIGHT) as usize
+    }
+
+    async fn push_line(&mut self, line: String) -> Result<(), String> {
+        self.lines.push(line);
+        if self.lines.len() >= Self::lines_per_page() {
+            self.flush_page().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush_page(&mut self) -> Result<(), String> {
+        let mut content = format!(
+            "BT /F1 8 Tf {} TL {} {} Td\n",
+            PDF_LINE_HEIGHT,
+            PDF_MARGIN,
+            PDF_PAGE_HEIGHT - PDF_MARGIN
+        ).into_bytes();
+        for line in self.lines.drain(..) {
+            content.push(b'(');
+            content.extend(pdf_text(&line));
+            content.extend_from_slice(b") Tj T*\n");
+        }
+        content.extend_from_slice(b"ET");
+
+        let content_id = self.next_id;
+        let page_id = self.next_id + 1;
+        self.next_id += 2;
+
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend(content);
+        stream.extend_from_slice(b"\nendstream");
+        self.write_object(content_id, &stream).await?;
+        self.write_object(page_id, format!(
+            "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 {} 0 R >> >> /Contents {} 0 R >>",
+            PDF_PAGES_ID, PDF_PAGE_WIDTH, PDF_PAGE_HEIGHT, PDF_FONT_ID, content_id
+        ).as_bytes()).await?;
+        self.page_ids.push(page_id);
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<u64, String> {
+        if !self.lines.is_empty() || self.page_ids.is_empty() {
+            self.flush_page().await?;
+        }
+
+        let kids = self.page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" ");
+        self.write_object(PDF_PAGES_ID, format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids,
+            self.page_ids.len()
+        ).as_bytes()).await?;
+        self.write_object(PDF_CATALOG_ID, format!("<< /Type /Catalog /Pages {} 0 R >>", PDF_PAGES_ID).as_bytes()).await?;
+
+        // Table des références croisées : un objet par identifiant attribué
+        let xref_offset = self.written;
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", self.next_id);
+        for id in 1..self.next_id {
+            xref.push_str(&format!("{:010} 00000 n \n", self.offsets.get(&id).copied().unwrap_or_default()));
+        }
+        xref.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.next_id, PDF_CATALOG_ID, xref_offset
+        ));
+        self.write(xref.as_bytes()).await?;
+        self.out.flush().await.map_err(|e| e.to_string())?;
+        Ok(self.written)
+    }
+}
+
+/// Chaîne PDF en WinAnsi (Latin-1) échappée ; les caractères hors de l'encodage deviennent `?`
+fn pdf_text(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                bytes.push(b'\\');
+                bytes.push(c as u8);
+            },
+            '…' => bytes.push(0x85),
+            c if (c as u32) < 0x100 && !c.is_control() => bytes.push(c as u32 as u8),
+            _ => bytes.push(b'?'),
+        }
+    }
+    bytes
+}
+
+fn admin_only(role: &UserRole) -> Option<axum::response::Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer les rapports"
+    }))).into_response())
+}
+
+/// Route admin : demander un rapport, généré en tâche de fond
+pub async fn create_report(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+    Json(payload): Json<CreateReportRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+    if storage.is_none() {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Stockage des documents non configuré"
+        }))).into_response();
+    }
+    if matches!((payload.from, payload.to), (Some(from), Some(to)) if from > to) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "La date de début doit précéder la date de fin"
+        }))).into_response();
+    }
+
+    let created = async {
+        let mut tx = pool.begin().await?;
+        let report = sqlx::query_as!(
+            Report,
+            r#"INSERT INTO reports (kind, format, from_date, to_date, requested_by, tenant_id)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id, kind as "kind: ReportKind", format as "format: ReportFormat", from_date, to_date,
+                         status as "status: ReportStatus", storage_key, row_count, size, error, requested_by,
+                         created_at, started_at, completed_at"#,
+            payload.kind as ReportKind,
+            payload.format as ReportFormat,
+            payload.from,
+            payload.to,
+            user.id,
+            user.tenant_id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        jobs::enqueue(&mut tx, jobs::GENERATE_REPORT, serde_json::json!({ "report_id": report.id })).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(report)
+    }.await;
+
+    match created {
+        Ok(report) => (StatusCode::ACCEPTED, Json(serde_json::json!({
+            "report": report,
+            "message": "Rapport en cours de génération"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : rapports récents de la plateforme, les plus récents d'abord
+pub async fn get_reports(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        Report,
+        r#"SELECT id, kind as "kind: ReportKind", format as "format: ReportFormat", from_date, to_date,
+                  status as "status: ReportStatus", storage_key, row_count, size, error, requested_by,
+                  created_at, started_at, completed_at
+           FROM reports
+           WHERE tenant_id = $2
+           ORDER BY created_at DESC
+           LIMIT $1"#,
+        RECENT_REPORTS_LIMIT,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(reports) => envelope::list("reports", &reports).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : état d'un rapport, avec une URL de téléchargement signée une fois généré
+pub async fn get_report(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Option<StorageConfig>>,
+    Path(report_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    let report = match sqlx::query_as!(
+        Report,
+        r#"SELECT id, kind as "kind: ReportKind", format as "format: ReportFormat", from_date, to_date,
+                  status as "status: ReportStatus", storage_key, row_count, size, error, requested_by,
+                  created_at, started_at, completed_at
+           FROM reports
+           WHERE id = $1 AND tenant_id = $2"#,
+        report_id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(report)) => report,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Rapport non trouvé"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    };
+
+    let now = Utc::now();
+    let download = match (&storage, &report.storage_key) {
+        (Some(storage), Some(key)) if report.status == ReportStatus::Completed => Some(serde_json::json!({
+            "url": storage.presign_get(key, now),
+            "expires_at": now + ChronoDuration::seconds(storage.url_ttl_secs as i64),
+            "expires_in": storage.url_ttl_secs
+        })),
+        _ => None,
+    };
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "report": report,
+        "download": download
+    }))).into_response()
+}
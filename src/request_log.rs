@@ -0,0 +1,217 @@
+// request_log.rs
+//
+// Journal des requêtes des routes financièrement sensibles. Le middleware est
+// activé groupe par groupe de routes (`route_layer` dans main.rs) : méthode,
+// chemin, auteur, statut de la réponse et corps de la requête expurgé des
+// champs secrets sont enregistrés dans `request_logs`. Les lectures (GET) ne
+// sont pas journalisées. La durée de conservation est celle de l'entité
+// `request_logs` (voir retention.rs).
+
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{MatchedPath, Query, State},
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{PgPool, QueryBuilder};
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::auth::BearerAuthUser;
+use crate::cache::UserCache;
+use crate::envelope;
+use crate::forwarded;
+use crate::models::{RequestLog, UserRole};
+use crate::pagination::{Cursor, Pagination};
+use crate::query_builder::{Filter, FilterKind, FilterOp, ListSpec, SortDirection};
+use crate::risk;
+
+/// Au-delà, le corps n'est pas conservé (la requête est tout de même journalisée)
+const MAX_LOGGED_BODY: u64 = 64 * 1024;
+
+/// Filtres et tri de la consultation du journal (`?path=` : préfixe du chemin,
+/// `?from=` inclus, `?to=` exclu)
+static REQUEST_LOG_LIST: ListSpec = ListSpec {
+    sorts: &[("created_at", "created_at"), ("duration_ms", "duration_ms"), ("status", "status")],
+    default_sort: ("created_at", SortDirection::Desc),
+    filters: &[
+        Filter { param: "group", column: "route_group", op: FilterOp::Eq, kind: FilterKind::Text },
+        Filter { param: "actor_id", column: "actor_id", op: FilterOp::Eq, kind: FilterKind::Uuid },
+        Filter { param: "path", column: "path", op: FilterOp::StartsWith, kind: FilterKind::Text },
+        Filter { param: "status", column: "status", op: FilterOp::Eq, kind: FilterKind::Int },
+        Filter { param: "from", column: "created_at", op: FilterOp::Gte, kind: FilterKind::Timestamp },
+        Filter { param: "to", column: "created_at", op: FilterOp::Lt, kind: FilterKind::Timestamp },
+    ],
+    passthrough: &[],
+};
+
+/// Champs remplacés par `[expurgé]` avant l'enregistrement
+const SENSITIVE_KEYS: &[&str] = &[
+    "signature", "password", "secret", "token", "access_token", "refresh_token",
+    "private_key", "api_key", "client_secret", "card_number", "iban",
+];
+
+/// Middleware de journalisation ; l'état est le nom du groupe de routes
+pub async fn log_requests(
+    State(group): State<&'static str>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    if matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(request).await;
+    }
+
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let route = request.extensions().get::<MatchedPath>().map(|m| m.as_str().to_string());
+    let ip = risk::client_ip(request.headers());
+    let scheme = forwarded::client_scheme(request.headers());
+    let wallet = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_string());
+    let pool = request.extensions().get::<PgPool>().cloned();
+    let user_cache = request.extensions().get::<UserCache>().cloned();
+
+    // Seuls les corps JSON de taille connue et raisonnable sont lus puis conservés
+    let is_json = request.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let content_length = request.headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let (request, request_body) = if is_json && content_length.is_some_and(|len| len <= MAX_LOGGED_BODY) {
+        let (parts, mut body) = request.into_parts();
+        let mut buffer = Vec::new();
+        while let Some(chunk) = body.data().await {
+            match chunk {
+                Ok(chunk) => buffer.extend_from_slice(&chunk),
+                Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                    "error": format!("Erreur lors de la lecture de la requête: {}", e)
+                }))).into_response(),
+            }
+        }
+        let logged = serde_json::from_slice::<serde_json::Value>(&buffer).ok().map(redact);
+        (Request::from_parts(parts, Body::from(Bytes::from(buffer))), logged)
+    } else {
+        (request, None)
+    };
+
+    let response = next.run(request).await;
+    let status = i32::from(response.status().as_u16());
+    let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let Some(pool) = pool else {
+        return response;
+    };
+    // L'extracteur Bearer a normalement déjà mis l'utilisateur en cache
+    let cached_actor = wallet.as_deref()
+        .zip(user_cache)
+        .and_then(|(wallet, cache)| cache.get(wallet))
+        .map(|user| user.id);
+
+    tokio::spawn(async move {
+        let result = async {
+            let actor_id = match (cached_actor, wallet) {
+                (Some(id), _) => Some(id),
+                (None, Some(wallet)) => sqlx::query_scalar!(
+                    r#"SELECT id as "id!" FROM users WHERE wallet = $1 AND deactivated_at IS NULL
+                       UNION ALL
+                       SELECT user_id FROM user_wallets WHERE wallet = lower($1) AND verified_at IS NOT NULL
+                       LIMIT 1"#,
+                    wallet
+                )
+                .fetch_optional(&pool)
+                .await?,
+                (None, None) => None,
+            };
+            sqlx::query!(
+                r#"INSERT INTO request_logs (route_group, method, path, route, actor_id, status, request_body, ip, scheme, duration_ms)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"#,
+                group,
+                method,
+                path,
+                route,
+                actor_id,
+                status,
+                request_body,
+                ip,
+                scheme,
+                duration_ms
+            )
+            .execute(&pool)
+            .await?;
+            Ok::<_, sqlx::Error>(())
+        }.await;
+        if let Err(e) = result {
+            tracing::error!("Erreur lors de la journalisation de la requête: {}", e);
+        }
+    });
+
+    response
+}
+
+/// Remplace récursivement la valeur des champs sensibles
+pub fn redact(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    if SENSITIVE_KEYS.contains(&key.to_lowercase().as_str()) {
+                        (key, serde_json::Value::String("[expurgé]".to_string()))
+                    } else {
+                        (key, redact(value))
+                    }
+                })
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => serde_json::Value::Array(items.into_iter().map(redact).collect()),
+        other => other,
+    }
+}
+
+/// Route admin : consultation du journal des requêtes sensibles
+pub async fn get_request_logs(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(params): Query<HashMap<String, String>>,
+    pagination: Pagination,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent consulter le journal des requêtes"
+        }))).into_response();
+    }
+
+    let list = match REQUEST_LOG_LIST.parse(&params, pagination) {
+        Ok(list) => list,
+        Err(e) => return (e.code.status(), Json(serde_json::json!({
+            "error": e.message,
+            "code": e.code
+        }))).into_response(),
+    };
+
+    // Requêtes des utilisateurs de la plateforme, et requêtes anonymes
+    let mut builder = QueryBuilder::new(
+        "SELECT id, route_group, method, path, route, actor_id, status, request_body, ip, scheme, duration_ms, created_at \
+         FROM request_logs WHERE (actor_id IS NULL OR actor_id IN (SELECT id FROM users WHERE tenant_id = "
+    );
+    builder.push_bind(user.tenant_id).push("))");
+    list.push_filters(&mut builder);
+    list.push_order_and_page(&mut builder);
+    match builder.build_query_as::<RequestLog>().fetch_all(&pool).await {
+        Ok(mut logs) => {
+            let page = pagination.finish(&mut logs, |log| Cursor::new(log.created_at, log.id));
+            envelope::list("logs", &logs).paginated(page).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,279 @@
+// retention.rs
+//
+// Conservation des données : les lignes plus anciennes que la durée fixée pour
+// leur table (notifications, journal des téléchargements de documents, échecs
+// d'authentification, journal des requêtes sensibles) sont déplacées par lots dans la table d'archive
+// correspondante, pour garder les tables chaudes de taille réduite.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::PgPool;
+use std::env;
+use std::time::Duration;
+
+use crate::auth::BearerAuthUser;
+use crate::models::{RetentionEntity, RetentionPolicy, UpdateRetentionPolicyRequest, UserRole};
+use crate::worker;
+
+/// Lignes déplacées par requête, pour ne pas verrouiller la table trop longtemps
+const ARCHIVE_BATCH_SIZE: i64 = 5000;
+
+/// Lance la tâche d'archivage. Intervalle configurable via
+/// `RETENTION_INTERVAL_SECS` (3600s par défaut).
+pub fn spawn(pool: PgPool) {
+    let interval_secs = env::var("RETENTION_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(3600);
+
+    tokio::spawn(async move {
+        let period = Duration::from_secs(interval_secs);
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match worker::run_exclusive(&pool, "retention", period, run_policies(&pool)).await {
+                None => {},
+                Some(Ok(results)) => {
+                    for (entity, archived) in results.into_iter().filter(|(_, archived)| *archived > 0) {
+                        tracing::info!("{} ligne(s) archivée(s) pour {:?}", archived, entity);
+                    }
+                },
+                Some(Err(e)) => tracing::error!("Erreur de l'archivage: {}", e),
+            }
+        }
+    });
+}
+
+/// Déplace un lot de lignes échues dans la table d'archive ; renvoie le nombre de lignes
+async fn archive_batch(pool: &PgPool, entity: RetentionEntity, retention_days: i32) -> Result<u64, sqlx::Error> {
+    let result = match entity {
+        RetentionEntity::Notifications => sqlx::query!(
+            r#"WITH moved AS (
+                   DELETE FROM notifications
+                   WHERE id IN (SELECT id FROM notifications
+                                WHERE created_at < NOW() - make_interval(days => $1)
+                                ORDER BY created_at LIMIT $2)
+                   RETURNING *
+               )
+               INSERT INTO notifications_archive SELECT *, NOW() FROM moved"#,
+            retention_days,
+            ARCHIVE_BATCH_SIZE
+        )
+        .execute(pool)
+        .await?,
+        RetentionEntity::DocumentDownloads => sqlx::query!(
+            r#"WITH moved AS (
+                   DELETE FROM document_downloads
+                   WHERE id IN (SELECT id FROM document_downloads
+                                WHERE created_at < NOW() - make_interval(days => $1)
+                                ORDER BY created_at LIMIT $2)
+                   RETURNING *
+               )
+               INSERT INTO document_downloads_archive SELECT *, NOW() FROM moved"#,
+            retention_days,
+            ARCHIVE_BATCH_SIZE
+        )
+        .execute(pool)
+        .await?,
+        RetentionEntity::AuthFailures => sqlx::query!(
+            r#"WITH moved AS (
+                   DELETE FROM auth_failures
+                   WHERE id IN (SELECT id FROM auth_failures
+                                WHERE created_at < NOW() - make_interval(days => $1)
+                                ORDER BY created_at LIMIT $2)
+                   RETURNING *
+               )
+               INSERT INTO auth_failures_archive SELECT *, NOW() FROM moved"#,
+            retention_days,
+            ARCHIVE_BATCH_SIZE
+        )
+        .execute(pool)
+        .await?,
+        RetentionEntity::RequestLogs => sqlx::query!(
+            r#"WITH moved AS (
+                   DELETE FROM request_logs
+                   WHERE id IN (SELECT id FROM request_logs
+                                WHERE created_at < NOW() - make_interval(days => $1)
+                                ORDER BY created_at LIMIT $2)
+                   RETURNING *
+               )
+               INSERT INTO request_logs_archive SELECT *, NOW() FROM moved"#,
+            retention_days,
+            ARCHIVE_BATCH_SIZE
+        )
+        .execute(pool)
+        .await?,
+    };
+    Ok(result.rows_affected())
+}
+
+/// Applique toutes les politiques actives ; renvoie le nombre de lignes archivées par table
+async fn run_policies(pool: &PgPool) -> Result<Vec<(RetentionEntity, u64)>, sqlx::Error> {
+    let policies = sqlx::query!(
+        r#"SELECT entity as "entity: RetentionEntity", retention_days
+           FROM retention_policies
+           WHERE enabled
+           ORDER BY entity"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut results = Vec::with_capacity(policies.len());
+    for policy in policies {
+        let mut archived = 0;
+        loop {
+            let moved = archive_batch(pool, policy.entity, policy.retention_days).await?;
+            archived += moved;
+            if moved < ARCHIVE_BATCH_SIZE as u64 {
+                break;
+            }
+        }
+
+        sqlx::query!(
+            "UPDATE retention_policies SET last_run_at = NOW(), last_archived = $2 WHERE entity = $1",
+            policy.entity as RetentionEntity,
+            archived.min(i32::MAX as u64) as i32
+        )
+        .execute(pool)
+        .await?;
+        results.push((policy.entity, archived));
+    }
+    Ok(results)
+}
+
+fn admin_only(role: &UserRole) -> Option<Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer la conservation des données"
+    }))).into_response())
+}
+
+/// Route admin : politiques de conservation, avec la taille des tables et de leurs archives
+pub async fn get_retention_policies(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    let result = async {
+        let policies = sqlx::query_as!(
+            RetentionPolicy,
+            r#"SELECT entity as "entity: RetentionEntity", retention_days, enabled, updated_by, updated_at,
+                      last_run_at, last_archived
+               FROM retention_policies
+               ORDER BY entity"#
+        )
+        .fetch_all(&pool)
+        .await?;
+        let counts = sqlx::query!(
+            r#"SELECT
+               (SELECT COUNT(*) FROM notifications) as "notifications!",
+               (SELECT COUNT(*) FROM notifications_archive) as "notifications_archive!",
+               (SELECT COUNT(*) FROM document_downloads) as "document_downloads!",
+               (SELECT COUNT(*) FROM document_downloads_archive) as "document_downloads_archive!",
+               (SELECT COUNT(*) FROM auth_failures) as "auth_failures!",
+               (SELECT COUNT(*) FROM auth_failures_archive) as "auth_failures_archive!",
+               (SELECT COUNT(*) FROM request_logs) as "request_logs!",
+               (SELECT COUNT(*) FROM request_logs_archive) as "request_logs_archive!""#
+        )
+        .fetch_one(&pool)
+        .await?;
+        Ok::<_, sqlx::Error>((policies, counts))
+    }.await;
+
+    match result {
+        Ok((policies, counts)) => {
+            let policies: Vec<_> = policies.into_iter().map(|policy| {
+                let (rows, archived_rows) = match policy.entity {
+                    RetentionEntity::Notifications => (counts.notifications, counts.notifications_archive),
+                    RetentionEntity::DocumentDownloads => (counts.document_downloads, counts.document_downloads_archive),
+                    RetentionEntity::AuthFailures => (counts.auth_failures, counts.auth_failures_archive),
+                    RetentionEntity::RequestLogs => (counts.request_logs, counts.request_logs_archive),
+                };
+                serde_json::json!({
+                    "policy": policy,
+                    "rows": rows,
+                    "archived_rows": archived_rows
+                })
+            }).collect();
+            (StatusCode::OK, Json(serde_json::json!({
+                "policies": policies
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : modifier la durée de conservation d'une table ou suspendre son archivage
+pub async fn update_retention_policy(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(entity): Path<RetentionEntity>,
+    Json(payload): Json<UpdateRetentionPolicyRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+    if payload.retention_days.is_some_and(|days| days <= 0) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "retention_days doit être positif"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        RetentionPolicy,
+        r#"UPDATE retention_policies
+           SET retention_days = COALESCE($2, retention_days), enabled = COALESCE($3, enabled),
+               updated_by = $4, updated_at = NOW()
+           WHERE entity = $1
+           RETURNING entity as "entity: RetentionEntity", retention_days, enabled, updated_by, updated_at,
+                     last_run_at, last_archived"#,
+        entity as RetentionEntity,
+        payload.retention_days,
+        payload.enabled,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(policy)) => (StatusCode::OK, Json(serde_json::json!({
+            "policy": policy,
+            "message": "Politique de conservation mise à jour"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Politique de conservation non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : lancer l'archivage immédiatement
+pub async fn run_retention(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user.role) {
+        return response;
+    }
+
+    match run_policies(&pool).await {
+        Ok(results) => (StatusCode::OK, Json(serde_json::json!({
+            "archived": results.into_iter()
+                .map(|(entity, archived)| serde_json::json!({ "entity": entity, "archived": archived }))
+                .collect::<Vec<_>>(),
+            "message": "Archivage effectué"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'archivage: {}", e)
+        }))).into_response(),
+    }
+}
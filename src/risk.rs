@@ -0,0 +1,448 @@
+// risk.rs
+//
+// Détection d'activité suspecte : un moteur de règles léger évalue les
+// événements (investissements, échecs d'authentification) et lève des alertes
+// dans une file traitée par les admins (gravité, prise en compte, notes). Tant
+// qu'une alerte est ouverte, les nouveaux déclenchements de la même règle pour
+// le même sujet s'y ajoutent au lieu d'en créer une autre.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use bigdecimal::BigDecimal;
+use sqlx::PgPool;
+use std::env;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::envelope;
+use crate::models::{
+    AcknowledgeAlertRequest, AlertListQuery, AlertNoteRequest, AlertSeverity, AlertStatus, RiskAlert, RiskAlertNote,
+    UserRole,
+};
+use crate::notifications::{notify_admins, notify_tenant_admins};
+
+/// Seuils des règles, lus depuis l'environnement (une règle dont le seuil
+/// vaut 0 ou n'est pas défini est désactivée)
+#[derive(Clone)]
+pub struct RiskRules {
+    velocity_max_investments: i64,
+    velocity_window_secs: f64,
+    amount_threshold_eth: Option<BigDecimal>,
+    amount_margin_pct: BigDecimal,
+    amount_window_secs: f64,
+    auth_max_failures: i64,
+    auth_window_secs: f64,
+}
+
+impl RiskRules {
+    pub fn from_env() -> Self {
+        let env_u64 = |name: &str, default: u64| {
+            env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).unwrap_or(default)
+        };
+        let env_decimal = |name: &str| {
+            env::var(name).ok().and_then(|v| BigDecimal::from_str(v.trim()).ok()).filter(|v| *v > BigDecimal::from(0))
+        };
+        Self {
+            velocity_max_investments: env_u64("RISK_VELOCITY_MAX_INVESTMENTS", 5) as i64,
+            velocity_window_secs: env_u64("RISK_VELOCITY_WINDOW_SECS", 3_600).max(1) as f64,
+            amount_threshold_eth: env_decimal("RISK_AMOUNT_THRESHOLD_ETH"),
+            amount_margin_pct: env_decimal("RISK_AMOUNT_MARGIN_PCT")
+                .filter(|v| *v < BigDecimal::from(100))
+                .unwrap_or_else(|| BigDecimal::from(10)),
+            amount_window_secs: env_u64("RISK_AMOUNT_WINDOW_SECS", 86_400).max(1) as f64,
+            auth_max_failures: env_u64("RISK_AUTH_MAX_FAILURES", 10) as i64,
+            auth_window_secs: env_u64("RISK_AUTH_WINDOW_SECS", 900).max(1) as f64,
+        }
+    }
+
+    /// Règles sur les investissements : nombreux investissements sur une
+    /// courte période, montants juste sous le seuil déclaratif
+    async fn evaluate_investment(&self, pool: &PgPool, investment_id: Uuid) -> Result<(), sqlx::Error> {
+        let investment = match sqlx::query!(
+            "SELECT user_id, amount_eth, created_at FROM investments WHERE id = $1",
+            investment_id
+        )
+        .fetch_optional(pool)
+        .await? {
+            Some(investment) => investment,
+            None => return Ok(()),
+        };
+        let subject = format!("user:{}", investment.user_id);
+
+        if self.velocity_max_investments > 0 {
+            let recent = sqlx::query!(
+                r#"SELECT COUNT(*) as "count!", COALESCE(SUM(amount_eth), 0) as "total_eth!"
+                   FROM investments
+                   WHERE user_id = $1 AND created_at > $2::timestamptz - make_interval(secs => $3)"#,
+                investment.user_id,
+                investment.created_at,
+                self.velocity_window_secs
+            )
+            .fetch_one(pool)
+            .await?;
+
+            if recent.count >= self.velocity_max_investments {
+                raise_alert(pool, "investment_velocity", AlertSeverity::Medium, &subject, Some(investment.user_id), serde_json::json!({
+                    "investment_id": investment_id,
+                    "investments": recent.count,
+                    "total_eth": recent.total_eth,
+                    "window_secs": self.velocity_window_secs as u64
+                }))
+                .await?;
+            }
+        }
+
+        if let Some(threshold) = &self.amount_threshold_eth {
+            let lower = threshold * (BigDecimal::from(100) - &self.amount_margin_pct) / BigDecimal::from(100);
+            if investment.amount_eth >= lower && investment.amount_eth < *threshold {
+                let near_threshold = sqlx::query_scalar!(
+                    r#"SELECT COUNT(*) as "count!"
+                       FROM investments
+                       WHERE user_id = $1 AND amount_eth >= $2 AND amount_eth < $3
+                       AND created_at > $4::timestamptz - make_interval(secs => $5)"#,
+                    investment.user_id,
+                    lower,
+                    threshold,
+                    investment.created_at,
+                    self.amount_window_secs
+                )
+                .fetch_one(pool)
+                .await?;
+
+                // Des montants répétés juste sous le seuil évoquent un fractionnement
+                let severity = if near_threshold > 1 { AlertSeverity::High } else { AlertSeverity::Low };
+                raise_alert(pool, "amount_below_threshold", severity, &subject, Some(investment.user_id), serde_json::json!({
+                    "investment_id": investment_id,
+                    "amount_eth": investment.amount_eth,
+                    "threshold_eth": threshold,
+                    "near_threshold_investments": near_threshold,
+                    "window_secs": self.amount_window_secs as u64
+                }))
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Règle sur les échecs d'authentification répétés, par adresse IP quand
+    /// elle est connue, sinon par wallet présenté. L'échec est toujours
+    /// enregistré : il sert aussi au blocage temporaire (voir brute_force.rs)
+    async fn evaluate_auth_failure(&self, pool: &PgPool, wallet: &str, ip: Option<&str>, reason: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!("INSERT INTO auth_failures (wallet, ip, reason) VALUES ($1, $2, $3)", wallet, ip, reason)
+            .execute(pool)
+            .await?;
+        if self.auth_max_failures == 0 {
+            return Ok(());
+        }
+
+        let failures = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!"
+               FROM auth_failures
+               WHERE (CASE WHEN $2::text IS NULL THEN wallet = $1 ELSE ip = $2 END)
+               AND created_at > NOW() - make_interval(secs => $3)"#,
+            wallet,
+            ip,
+            self.auth_window_secs
+        )
+        .fetch_one(pool)
+        .await?;
+
+        if failures >= self.auth_max_failures {
+            let subject = match ip {
+                Some(ip) => format!("ip:{}", ip),
+                None => format!("wallet:{}", wallet),
+            };
+            raise_alert(pool, "auth_failures", AlertSeverity::High, &subject, None, serde_json::json!({
+                "failures": failures,
+                "last_wallet": wallet,
+                "ip": ip,
+                "window_secs": self.auth_window_secs as u64
+            }))
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Crée l'alerte (et prévient les admins, ceux de la plateforme de
+/// l'utilisateur s'il est connu) ou l'ajoute à l'alerte ouverte de même règle
+/// et même sujet, dont la gravité ne peut qu'augmenter
+async fn raise_alert(
+    pool: &PgPool,
+    rule: &str,
+    severity: AlertSeverity,
+    subject: &str,
+    user_id: Option<Uuid>,
+    details: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let alert = sqlx::query!(
+        r#"INSERT INTO risk_alerts (rule, severity, subject, user_id, details)
+           VALUES ($1, $2, $3, $4, $5)
+           ON CONFLICT (rule, subject) WHERE status = 'open'
+           DO UPDATE SET severity = GREATEST(risk_alerts.severity, EXCLUDED.severity),
+                         details = EXCLUDED.details,
+                         occurrences = risk_alerts.occurrences + 1,
+                         last_seen_at = NOW()
+           RETURNING id, (xmax = 0) as "created!", (SELECT tenant_id FROM users WHERE id = $4) as tenant_id"#,
+        rule,
+        severity as AlertSeverity,
+        subject,
+        user_id,
+        details
+    )
+    .fetch_one(&mut tx)
+    .await?;
+
+    if alert.created {
+        let message = "Nouvelle alerte d'activité suspecte à examiner";
+        let data = serde_json::json!({
+            "alert_id": alert.id,
+            "rule": rule,
+            "severity": severity,
+            "subject": subject
+        });
+        match alert.tenant_id {
+            Some(tenant_id) => notify_tenant_admins(&mut tx, tenant_id, None, "risk_alert", message, data).await?,
+            None => notify_admins(&mut tx, "risk_alert", message, data).await?,
+        }
+    }
+    tx.commit().await
+}
+
+/// Évalue un investissement créé, en tâche de fond pour ne pas retarder la réponse
+pub fn investment_created(pool: PgPool, rules: RiskRules, investment_id: Uuid) {
+    tokio::spawn(async move {
+        if let Err(e) = rules.evaluate_investment(&pool, investment_id).await {
+            tracing::error!("Erreur de l'évaluation des règles de risque (investissement {}): {}", investment_id, e);
+        }
+    });
+}
+
+/// Enregistre un échec d'authentification (`reason` : unknown_wallet,
+/// invalid_token, bad_signature) et l'évalue, en tâche de fond
+pub fn auth_failed(pool: PgPool, rules: RiskRules, wallet: String, ip: Option<String>, reason: &'static str) {
+    tokio::spawn(async move {
+        if let Err(e) = rules.evaluate_auth_failure(&pool, &wallet, ip.as_deref(), reason).await {
+            tracing::error!("Erreur de l'évaluation des règles de risque (authentification): {}", e);
+        }
+    });
+}
+
+/// Adresse du client, retenue d'après les proxies de confiance (`X-Real-IP`
+/// réécrit par forwarded.rs)
+pub fn client_ip(headers: &HeaderMap) -> Option<String> {
+    headers.get("x-real-ip")
+        .and_then(|v| v.to_str().ok())
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+}
+
+fn admin_only(user: &SessionUser) -> Option<Response> {
+    (!matches!(user.role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent traiter les alertes d'activité suspecte"
+    }))).into_response())
+}
+
+/// Route admin : file des alertes (ouvertes et plus graves d'abord,
+/// `?status=` et `?severity=` pour filtrer). Les alertes d'un utilisateur ne
+/// sont visibles que des admins de sa plateforme ; celles sans utilisateur
+/// (adresse IP, wallet inconnu) le sont de tous.
+pub async fn get_alerts(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<AlertListQuery>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        RiskAlert,
+        r#"SELECT id, rule, severity as "severity: AlertSeverity", subject, user_id, details, occurrences,
+                  status as "status: AlertStatus", acknowledged_by, acknowledged_at, created_at, last_seen_at
+           FROM risk_alerts
+           WHERE ($1::alert_status IS NULL OR status = $1)
+           AND ($2::alert_severity IS NULL OR severity = $2)
+           AND (user_id IS NULL OR user_id IN (SELECT id FROM users WHERE tenant_id = $3))
+           ORDER BY status = 'open' DESC, severity DESC, last_seen_at DESC"#,
+        query.status as Option<AlertStatus>,
+        query.severity as Option<AlertSeverity>,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(alerts) => envelope::list("alerts", &alerts).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : détail d'une alerte avec ses notes
+pub async fn get_alert(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(alert_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+
+    let result = async {
+        let alert = sqlx::query_as!(
+            RiskAlert,
+            r#"SELECT id, rule, severity as "severity: AlertSeverity", subject, user_id, details, occurrences,
+                      status as "status: AlertStatus", acknowledged_by, acknowledged_at, created_at, last_seen_at
+               FROM risk_alerts
+               WHERE id = $1 AND (user_id IS NULL OR user_id IN (SELECT id FROM users WHERE tenant_id = $2))"#,
+            alert_id,
+            user.tenant_id
+        )
+        .fetch_optional(&pool)
+        .await?;
+        let notes = sqlx::query_as!(
+            RiskAlertNote,
+            r#"SELECT id, alert_id, author_id, body, created_at
+               FROM risk_alert_notes WHERE alert_id = $1 ORDER BY created_at"#,
+            alert_id
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok::<_, sqlx::Error>(alert.map(|alert| (alert, notes)))
+    }
+    .await;
+
+    match result {
+        Ok(Some((alert, notes))) => (StatusCode::OK, Json(serde_json::json!({
+            "alert": alert,
+            "notes": notes
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Alerte non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : prendre en compte une alerte ouverte, avec une note facultative
+pub async fn acknowledge_alert(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(alert_id): Path<Uuid>,
+    Json(payload): Json<AcknowledgeAlertRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+    let note = payload.note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let alert = sqlx::query_as!(
+            RiskAlert,
+            r#"UPDATE risk_alerts
+               SET status = 'acknowledged', acknowledged_by = $2, acknowledged_at = NOW()
+               WHERE id = $1 AND status = 'open' AND (user_id IS NULL OR user_id IN (SELECT id FROM users WHERE tenant_id = $3))
+               RETURNING id, rule, severity as "severity: AlertSeverity", subject, user_id, details, occurrences,
+                         status as "status: AlertStatus", acknowledged_by, acknowledged_at, created_at, last_seen_at"#,
+            alert_id,
+            user.id,
+            user.tenant_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let alert = match alert {
+            Some(alert) => alert,
+            None => {
+                let exists = sqlx::query_scalar!(
+                    r#"SELECT 1 FROM risk_alerts
+                       WHERE id = $1 AND (user_id IS NULL OR user_id IN (SELECT id FROM users WHERE tenant_id = $2))"#,
+                    alert_id,
+                    user.tenant_id
+                )
+                .fetch_optional(&mut tx)
+                .await?;
+                return Ok(Err(match exists {
+                    Some(_) => (StatusCode::CONFLICT, "Cette alerte a déjà été prise en compte"),
+                    None => (StatusCode::NOT_FOUND, "Alerte non trouvée"),
+                }));
+            },
+        };
+
+        if let Some(note) = note {
+            sqlx::query!(
+                "INSERT INTO risk_alert_notes (alert_id, author_id, body) VALUES ($1, $2, $3)",
+                alert_id,
+                user.id,
+                note
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(alert))
+    }
+    .await;
+
+    match result {
+        Ok(Ok(alert)) => (StatusCode::OK, Json(serde_json::json!({
+            "alert": alert,
+            "message": "Alerte prise en compte"
+        }))).into_response(),
+        Ok(Err((status, error))) => (status, Json(serde_json::json!({
+            "error": error
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : ajouter une note à une alerte (ouverte ou déjà prise en compte)
+pub async fn add_alert_note(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(alert_id): Path<Uuid>,
+    Json(payload): Json<AlertNoteRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+    let body = payload.body.trim();
+    if body.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "La note ne peut pas être vide"
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        RiskAlertNote,
+        r#"INSERT INTO risk_alert_notes (alert_id, author_id, body)
+           SELECT id, $2, $3 FROM risk_alerts
+           WHERE id = $1 AND (user_id IS NULL OR user_id IN (SELECT id FROM users WHERE tenant_id = $4))
+           RETURNING id, alert_id, author_id, body, created_at"#,
+        alert_id,
+        user.id,
+        body,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(note)) => (StatusCode::CREATED, Json(note)).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Alerte non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'ajout de la note: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,348 @@
+// role_approvals.rs
+//
+// Principe des quatre yeux pour le rôle admin : un changement de rôle qui
+// octroie ou retire le rôle admin n'est pas appliqué immédiatement. Il crée
+// une demande en attente, notifiée aux autres admins de la plateforme, qu'un
+// second admin (ni le demandeur, ni l'utilisateur visé) doit approuver avant
+// `ROLE_CHANGE_REQUEST_TTL_HOURS` heures. La demande conserve le demandeur,
+// l'approbateur et les dates : c'est l'entrée d'audit du changement.
+// `ROLE_CHANGE_APPROVAL_REQUIRED=false` rétablit le changement immédiat.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::activity;
+use crate::cache::UserCache;
+use crate::envelope;
+use crate::models::{RoleChangeRequest, RoleChangeRequestListQuery, UserRole};
+use crate::notifications::{notify, notify_tenant_admins};
+use crate::tags::is_unique_violation;
+use crate::two_factor;
+use crate::wallets;
+
+type Outcome = Result<RoleChangeRequest, (StatusCode, &'static str)>;
+
+fn approval_enabled() -> bool {
+    env::var("ROLE_CHANGE_APPROVAL_REQUIRED").map_or(true, |v| v != "false" && v != "0")
+}
+
+fn ttl_hours() -> i64 {
+    env::var("ROLE_CHANGE_REQUEST_TTL_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(48)
+}
+
+/// Vrai si le changement doit être approuvé par un second admin
+pub fn requires_approval(current: UserRole, requested: UserRole) -> bool {
+    approval_enabled()
+        && matches!(current, UserRole::Admin) != matches!(requested, UserRole::Admin)
+}
+
+fn outcome_response(result: Result<Outcome, sqlx::Error>, success: StatusCode, message: &str) -> Response {
+    match result {
+        Ok(Ok(request)) => (success, Json(serde_json::json!({
+            "request": request,
+            "message": message
+        }))).into_response(),
+        Ok(Err((status, error))) => (status, Json(serde_json::json!({
+            "error": error
+        }))).into_response(),
+        Err(e) if is_unique_violation(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Un changement de rôle est déjà en attente pour cet utilisateur"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du traitement du changement de rôle: {}", e)
+        }))).into_response(),
+    }
+}
+
+fn admin_only(user: &SessionUser) -> Option<Response> {
+    (!matches!(user.role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent traiter les changements de rôle"
+    }))).into_response())
+}
+
+/// Les demandes arrivées à échéance sans décision passent en `expired`
+async fn expire_pending<'e, E: sqlx::PgExecutor<'e>>(executor: E) -> Result<(), sqlx::Error> {
+    sqlx::query!("UPDATE role_change_requests SET status = 'expired' WHERE status = 'pending' AND expires_at <= NOW()")
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Appelé par `PUT /api/users/:id/role` quand `requires_approval` : enregistre
+/// la demande (202) au lieu de modifier le rôle
+pub async fn request_role_change(
+    pool: &PgPool,
+    admin: &SessionUser,
+    user_id: Uuid,
+    current: UserRole,
+    requested: UserRole,
+) -> Response {
+    let expires_at = Utc::now() + Duration::hours(ttl_hours());
+    let result = async {
+        let mut tx = pool.begin().await?;
+        expire_pending(&mut tx).await?;
+        let request = sqlx::query_as!(
+            RoleChangeRequest,
+            r#"INSERT INTO role_change_requests (tenant_id, user_id, previous_role, requested_role, requested_by, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id, user_id, previous_role as "previous_role: UserRole", requested_role as "requested_role: UserRole",
+                         requested_by, status, decided_by, decided_at, expires_at, created_at"#,
+            admin.tenant_id,
+            user_id,
+            current as UserRole,
+            requested as UserRole,
+            admin.id,
+            expires_at
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        let data = serde_json::json!({
+            "request_id": request.id,
+            "user_id": user_id,
+            "previous_role": current,
+            "requested_role": requested,
+            "requested_by": admin.id
+        });
+        notify_tenant_admins(
+            &mut tx,
+            admin.tenant_id,
+            Some(admin.id),
+            "role_change_requested",
+            &format!("Changement de rôle vers '{}' à approuver", requested),
+            data
+        ).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(request))
+    }
+    .await;
+
+    outcome_response(result, StatusCode::ACCEPTED, "Changement de rôle en attente de l'approbation d'un second admin")
+}
+
+/// Route admin : demandes de changement de rôle de la plateforme (`?status=` pour filtrer)
+pub async fn get_role_change_requests(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<RoleChangeRequestListQuery>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&user) {
+        return response;
+    }
+    if let Err(e) = expire_pending(&pool).await {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response();
+    }
+
+    match sqlx::query_as!(
+        RoleChangeRequest,
+        r#"SELECT id, user_id, previous_role as "previous_role: UserRole", requested_role as "requested_role: UserRole",
+                  requested_by, status, decided_by, decided_at, expires_at, created_at
+           FROM role_change_requests
+           WHERE tenant_id = $1 AND ($2::text IS NULL OR status = $2)
+           ORDER BY created_at DESC"#,
+        user.tenant_id,
+        query.status
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(requests) => envelope::list("requests", &requests).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Demande en attente verrouillée pour une décision, ou l'erreur à renvoyer
+async fn lock_pending(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    request_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Outcome, sqlx::Error> {
+    let request = sqlx::query_as!(
+        RoleChangeRequest,
+        r#"SELECT id, user_id, previous_role as "previous_role: UserRole", requested_role as "requested_role: UserRole",
+                  requested_by, status, decided_by, decided_at, expires_at, created_at
+           FROM role_change_requests
+           WHERE id = $1 AND tenant_id = $2
+           FOR UPDATE"#,
+        request_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    Ok(match request {
+        None => Err((StatusCode::NOT_FOUND, "Demande de changement de rôle non trouvée")),
+        Some(request) if request.status != "pending" => Err((StatusCode::CONFLICT, "Cette demande a déjà été traitée")),
+        Some(request) if request.expires_at <= Utc::now() => Err((StatusCode::GONE, "Cette demande a expiré")),
+        Some(request) => Ok(request),
+    })
+}
+
+/// Route admin : approuver une demande (second facteur requis). Le rôle est
+/// appliqué si l'utilisateur a toujours le rôle constaté lors de la demande.
+pub async fn approve_role_change(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(user_cache): Extension<UserCache>,
+    Path(request_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin) {
+        return response;
+    }
+    if let Err(response) = two_factor::ensure_second_factor(&pool, &admin, &headers).await {
+        return response;
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let request = match lock_pending(&mut tx, request_id, admin.tenant_id).await? {
+            Ok(request) => request,
+            Err(error) => return Ok((Err(error), None)),
+        };
+        if request.requested_by == Some(admin.id) {
+            return Ok((Err((StatusCode::FORBIDDEN, "Le demandeur ne peut pas approuver sa propre demande")), None));
+        }
+        if request.user_id == admin.id {
+            return Ok((Err((StatusCode::FORBIDDEN, "Impossible d'approuver un changement de son propre rôle")), None));
+        }
+
+        let wallet = sqlx::query!(
+            r#"UPDATE users SET role = $3
+               WHERE id = $1 AND role = $2
+               RETURNING wallet"#,
+            request.user_id,
+            request.previous_role as UserRole,
+            request.requested_role as UserRole
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        .map(|row| row.wallet);
+        let Some(wallet) = wallet else {
+            return Ok((Err((StatusCode::CONFLICT, "Le rôle de l'utilisateur a changé depuis la demande")), None));
+        };
+
+        let request = sqlx::query_as!(
+            RoleChangeRequest,
+            r#"UPDATE role_change_requests SET status = 'approved', decided_by = $2, decided_at = NOW()
+               WHERE id = $1
+               RETURNING id, user_id, previous_role as "previous_role: UserRole", requested_role as "requested_role: UserRole",
+                         requested_by, status, decided_by, decided_at, expires_at, created_at"#,
+            request.id,
+            admin.id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        activity::record(
+            &mut tx,
+            request.user_id,
+            Some(admin.id),
+            "role_changed",
+            serde_json::json!({
+                "from": request.previous_role,
+                "to": request.requested_role,
+                "request_id": request.id,
+                "requested_by": request.requested_by
+            }),
+            None
+        ).await?;
+        if let Some(requested_by) = request.requested_by {
+            notify(
+                &mut tx,
+                requested_by,
+                "role_change_approved",
+                &format!("Votre changement de rôle vers '{}' a été approuvé", request.requested_role),
+                serde_json::json!({ "request_id": request.id, "user_id": request.user_id, "approved_by": admin.id })
+            ).await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>((Ok(request), Some(wallet)))
+    }
+    .await;
+
+    let result = match result {
+        Ok((outcome, wallet)) => {
+            // Le rôle en cache n'est plus valide, quel que soit le wallet utilisé
+            if let (Ok(request), Some(wallet)) = (&outcome, wallet) {
+                let mut stale = vec![wallet];
+                match wallets::linked_wallets(&pool, request.user_id).await {
+                    Ok(linked) => stale.extend(linked),
+                    Err(e) => tracing::error!("Erreur lors de la lecture des wallets rattachés: {}", e),
+                }
+                user_cache.invalidate_everywhere(&pool, &stale).await;
+            }
+            Ok(outcome)
+        },
+        Err(e) => Err(e),
+    };
+    outcome_response(result, StatusCode::OK, "Changement de rôle approuvé et appliqué")
+}
+
+/// Route admin : refuser une demande, ou l'annuler pour son auteur
+pub async fn reject_role_change(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(request_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin) {
+        return response;
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let request = match lock_pending(&mut tx, request_id, admin.tenant_id).await? {
+            Ok(request) => request,
+            Err(error) => return Ok(Err(error)),
+        };
+        let cancelled = request.requested_by == Some(admin.id);
+        if !cancelled && request.user_id == admin.id {
+            return Ok(Err((StatusCode::FORBIDDEN, "Impossible de statuer sur un changement de son propre rôle")));
+        }
+
+        let request = sqlx::query_as!(
+            RoleChangeRequest,
+            r#"UPDATE role_change_requests SET status = $3, decided_by = $2, decided_at = NOW()
+               WHERE id = $1
+               RETURNING id, user_id, previous_role as "previous_role: UserRole", requested_role as "requested_role: UserRole",
+                         requested_by, status, decided_by, decided_at, expires_at, created_at"#,
+            request.id,
+            admin.id,
+            if cancelled { "cancelled" } else { "rejected" }
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        if let Some(requested_by) = request.requested_by.filter(|_| !cancelled) {
+            notify(
+                &mut tx,
+                requested_by,
+                "role_change_rejected",
+                &format!("Votre changement de rôle vers '{}' a été refusé", request.requested_role),
+                serde_json::json!({ "request_id": request.id, "user_id": request.user_id, "rejected_by": admin.id })
+            ).await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(request))
+    }
+    .await;
+
+    outcome_response(result, StatusCode::OK, "Demande de changement de rôle close")
+}
@@ -1,65 +1,188 @@
 // routes.rs
 
 use axum::{
-    extract::{State, Path},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{State, Path, Query},
+    Extension,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use sqlx::PgPool;
+use sqlx::{Connection, PgConnection, PgPool, QueryBuilder};
+use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::Utc;
+use bigdecimal::BigDecimal;
 
-use crate::models::{CreateUserRequest, UpdateUserRoleRequest, Property, CreatePropertyRequest, UpdatePropertyStatusRequest, PropertyStatus, Investment, CreateInvestmentRequest, UpdateInvestmentRequest, User, UserRole};
-use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::error_codes::ErrorCode;
+use crate::models::{Amenities, CreateUserRequest, DeletePropertyQuery, UpdateUserRoleRequest, Property, CreatePropertyRequest, IncludeQuery, InvestmentListQuery, OnchainLookupQuery, PropertyListQuery, PublicPropertyQuery, UpdatePropertyStatusRequest, PropertyStatus, Investment, InvestmentStatus, CreateInvestmentRequest, UpdateInvestmentRequest, Tenant, User, UserRole};
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::cache::UserCache;
+use crate::db::DbHealth;
+use crate::db_tx::{transaction_error, RequestTx};
+use crate::outbound::{BreakerState, Outbound};
+use crate::compliance::{self, SanctionsScreener};
+use crate::accreditation;
+use crate::legal;
+use crate::exposure;
+use crate::feeds::FeedCache;
+use crate::outbox::{self, DomainEvent};
+use crate::wallet_address::WalletAddress;
+use crate::registry::{self, ContractAddressError, Registry};
+use crate::risk::{self, RiskRules};
+use crate::signed_requests::{self, SignedRequest};
+use crate::slug;
+use crate::stats;
+use crate::tags;
+use crate::property_managers;
+use crate::activity;
+use crate::chain::parse_tx_hash;
+use crate::eip712::{format_address, parse_address};
+use crate::investment_revisions;
+use crate::funding;
+use crate::currency;
+use crate::confirmations;
+use crate::deletion_impact;
+use crate::facets::PropertyFilters;
+use crate::geocoding::Geocoder;
+use crate::jobs;
+use crate::role_approvals;
+use crate::trash;
+use crate::two_factor;
+use crate::wallets;
+use crate::includes::{self, INVESTMENT_INCLUDES, PROPERTY_INCLUDES};
+use crate::fields::{self, INVESTMENT_FIELDS, PROPERTY_FIELDS};
+use crate::pagination::{Cursor, Pagination};
+use crate::query_builder::{Filter, FilterKind, FilterOp, ListSpec, SortDirection};
+use crate::money::{TokenAmount, Wei};
+use crate::onchain_investments;
 
-// Route de santé
-pub async fn health_check() -> impl IntoResponse {
+// Nombre maximum d'identifiants acceptés par `?ids=`
+const MAX_BATCH_IDS: usize = 100;
+
+/// Tri et filtres de `GET /api/users` (ex. `?role=manager&sort=-created_at&page=2`)
+static USER_LIST: ListSpec = ListSpec {
+    sorts: &[("created_at", "created_at"), ("name", "name"), ("wallet", "wallet"), ("role", "role")],
+    default_sort: ("created_at", SortDirection::Desc),
+    filters: &[
+        Filter { param: "role", column: "role", op: FilterOp::Eq, kind: FilterKind::OneOf(&["user", "manager", "admin"]) },
+        Filter { param: "name", column: "name", op: FilterOp::Contains, kind: FilterKind::Text },
+        Filter { param: "wallet", column: "wallet", op: FilterOp::Contains, kind: FilterKind::Text },
+        Filter { param: "created_after", column: "created_at", op: FilterOp::Gte, kind: FilterKind::Timestamp },
+        Filter { param: "created_before", column: "created_at", op: FilterOp::Lt, kind: FilterKind::Timestamp },
+    ],
+    passthrough: &[],
+};
+
+/// Route de santé : `status: degraded` et `db: down` tant que la base est
+/// injoignable (le serveur reste en vie et retente la connexion, voir db.rs)
+pub async fn health_check(
+    Extension(db_health): Extension<DbHealth>,
+) -> impl IntoResponse {
+    let db_up = db_health.is_up();
     Json(serde_json::json!({
-        "status": "ok",
+        "status": if db_up { "ok" } else { "degraded" },
+        "db": if db_up { "up" } else { "down" },
         "message": "API is running"
     }))
 }
 
+/// Disponibilité : 503 tant que la base est injoignable. Un disjoncteur ouvert
+/// (service externe en panne, routes concernées en repli) rend l'état
+/// `degraded` sans retirer l'instance du trafic.
+pub async fn readiness_check(
+    Extension(db_health): Extension<DbHealth>,
+    Extension(outbound): Extension<Outbound>,
+) -> impl IntoResponse {
+    let db_up = db_health.is_up();
+    let services = outbound.statuses();
+    let degraded = services.values().any(|service| service.state != BreakerState::Closed);
+    let status = match (db_up, degraded) {
+        (false, _) => "unavailable",
+        (true, true) => "degraded",
+        (true, false) => "ready",
+    };
+    (
+        if db_up { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE },
+        Json(serde_json::json!({
+            "status": status,
+            "db": if db_up { "up" } else { "down" },
+            "services": services
+        })),
+    )
+}
+
 // Route simple pour créer un utilisateur
 pub async fn create_user(
     State(pool): State<PgPool>,
+    Extension(screener): Extension<Option<SanctionsScreener>>,
+    Extension(tenant): Extension<Tenant>,
     Json(payload): Json<CreateUserRequest>,
 ) -> impl IntoResponse {
     let role_str = payload.role.unwrap_or_else(|| "user".to_string());
     let role: UserRole = role_str.into();
     
+    // Un wallet déjà rattaché à un compte (voir wallets.rs) ne crée pas de second compte
     match sqlx::query!(
-        r#"INSERT INTO users (wallet, name, role)
-        VALUES ($1, $2, $3)
+        r#"INSERT INTO users (wallet, name, role, tenant_id)
+        SELECT $1, $2, $3, $4
+        WHERE NOT EXISTS (SELECT 1 FROM user_wallets WHERE wallet = $1 AND verified_at IS NOT NULL)
         RETURNING id"#,
-        payload.wallet,
+        payload.wallet.as_str(),
         payload.name,
-        role as UserRole
+        role as UserRole,
+        tenant.id
     )
-    .fetch_one(&pool)
+    .fetch_optional(&pool)
     .await {
-        Ok(record) => (StatusCode::CREATED, Json(serde_json::json!({ 
-            "id": record.id,
-            "message": "Utilisateur créé avec succès"
+        Ok(None) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Ce wallet est déjà rattaché à un compte",
+            "code": ErrorCode::WalletAlreadyRegistered
+        }))).into_response(),
+        // Wallet déjà inscrit, quelle que soit la casse envoyée
+        Err(e) if tags::is_unique_violation(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Ce wallet est déjà inscrit",
+            "code": ErrorCode::WalletAlreadyRegistered
         }))).into_response(),
+        Ok(Some(record)) => {
+            // Filtrage AML du wallet à l'inscription
+            compliance::screen_new_user(pool.clone(), screener, record.id);
+            (StatusCode::CREATED, Json(serde_json::json!({ 
+                "id": record.id,
+                "message": "Utilisateur créé avec succès"
+            }))).into_response()
+        },
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ 
-            "error": format!("Erreur lors de la création: {}", e.to_string())
+            "error": format!("Erreur lors de la création: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
 
-// Route publique pour lister uniquement les propriétés validées
+// Route publique pour lister uniquement les propriétés validées (filtrable par `?tags=`)
 pub async fn get_properties(
     State(pool): State<PgPool>,
+    Extension(tenant): Extension<Tenant>,
+    Query(query): Query<PublicPropertyQuery>,
 ) -> impl IntoResponse {
+    let tag_filter = tags::parse_tag_filter(query.tags.as_deref());
+
     match sqlx::query!(
-        r#"SELECT id, onchain_id, name, location, type, description, 
+        r#"SELECT id, onchain_id, slug, name, location, type, description, 
            total_price, token_price, annual_yield, image_url, documents, 
-           created_at
+           created_at, amenities as "amenities: Amenities", requires_accreditation,
+           ARRAY(SELECT t.slug FROM property_tags pt JOIN tags t ON t.id = pt.tag_id
+                 WHERE pt.property_id = properties.id ORDER BY t.slug) as "tags!"
            FROM properties 
-           WHERE status = 'validated' 
-           ORDER BY created_at DESC"#
+           WHERE status = 'validated' AND published_at IS NOT NULL AND tenant_id = $2
+           AND ($1::text[] IS NULL OR id IN (SELECT pt.property_id FROM property_tags pt
+               JOIN tags t ON t.id = pt.tag_id
+               WHERE t.slug = ANY($1)
+               GROUP BY pt.property_id
+               HAVING COUNT(*) = cardinality($1)))
+           ORDER BY created_at DESC"#,
+        tag_filter.as_deref(),
+        tenant.id
     )
     .fetch_all(&pool)
     .await {
@@ -68,6 +191,7 @@ pub async fn get_properties(
                 serde_json::json!({
                     "id": row.id,
                     "onchain_id": row.onchain_id,
+                    "slug": row.slug,
                     "name": row.name,
                     "location": row.location,
                     "type": row.r#type,
@@ -77,32 +201,112 @@ pub async fn get_properties(
                     "annual_yield": row.annual_yield,
                     "image_url": row.image_url,
                     "documents": row.documents,
+                    "amenities": row.amenities,
+                    "requires_accreditation": row.requires_accreditation,
+                    "tags": row.tags,
                     "created_at": row.created_at
                 })
             }).collect();
             
-            (StatusCode::OK, Json(serde_json::json!({
-                "properties": properties,
-                "count": properties.len(),
-                "message": "Propriétés validées uniquement"
-            }))).into_response()
+            envelope::list("properties", &properties)
+                .with("message", "Propriétés validées uniquement")
+                .into_response()
         },
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ 
-            "error": format!("Erreur lors de la récupération: {}", e.to_string())
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
 
-/// Route pour créer une property (manager ou admin requis)
+/// Contrainte d'unicité de `properties.onchain_id`
+const ONCHAIN_ID_CONSTRAINT: &str = "properties_onchain_id_key";
+
+/// Identifiant on-chain attribué entre la vérification et l'écriture
+fn is_onchain_id_taken(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Database(db)
+        if db.code().as_deref() == Some("23505") && db.constraint() == Some(ONCHAIN_ID_CONSTRAINT))
+}
+
+/// Slug pris par des écritures concurrentes à chaque essai (voir slug.rs)
+fn slug_taken() -> Response {
+    (StatusCode::CONFLICT, Json(serde_json::json!({
+        "error": "Le slug de la propriété a été pris par des modifications simultanées, veuillez réessayer",
+        "code": ErrorCode::Conflict
+    }))).into_response()
+}
+
+fn onchain_id_taken(onchain_id: &str) -> Response {
+    (StatusCode::CONFLICT, Json(serde_json::json!({
+        "error": format!("L'identifiant on-chain '{}' est déjà attribué à une autre propriété", onchain_id),
+        "code": ErrorCode::OnchainIdTaken
+    }))).into_response()
+}
+
+/// Vérifie l'identifiant on-chain et l'adresse de contrat d'une propriété créée
+/// ou modifiée (`property_id`) : format, unicité (corbeille comprise, l'élément
+/// pouvant être restauré) et contrat registre. Renvoie l'adresse normalisée.
+async fn check_onchain_binding(
+    pool: &PgPool,
+    onchain_id: &str,
+    contract_address: Option<&str>,
+    property_id: Option<Uuid>,
+) -> Result<Option<String>, Response> {
+    if !registry::is_valid_onchain_id(onchain_id) {
+        return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "onchain_id doit compter 1 à 64 caractères parmi lettres, chiffres, - et _",
+            "code": ErrorCode::InvalidOnchainId
+        }))).into_response());
+    }
+
+    let contract_address = match contract_address.map(registry::check_contract_address) {
+        None => None,
+        Some(Ok(address)) => Some(address),
+        Some(Err(ContractAddressError::Invalid)) => return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "contract_address doit être une adresse (0x suivi de 40 caractères hexadécimaux)",
+            "code": ErrorCode::InvalidContractAddress
+        }))).into_response()),
+        Some(Err(ContractAddressError::NotRegistry(expected))) => return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+            "error": "contract_address ne correspond pas au contrat registre configuré",
+            "code": ErrorCode::ContractAddressMismatch,
+            "expected": expected
+        }))).into_response()),
+    };
+
+    let taken = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM properties WHERE onchain_id = $1 AND id IS DISTINCT FROM $2)
+               OR EXISTS (SELECT 1 FROM properties_trash WHERE onchain_id = $1 AND id IS DISTINCT FROM $2) as "taken!""#,
+        onchain_id,
+        property_id
+    )
+    .fetch_one(pool)
+    .await;
+    match taken {
+        Ok(false) => Ok(contract_address),
+        Ok(true) => Err(onchain_id_taken(onchain_id)),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification de l'identifiant on-chain: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response()),
+    }
+}
+
+/// Route pour créer une property (manager ou admin requis).
+/// La propriété est créée en brouillon, invisible des admins jusqu'à sa soumission.
+/// Propriété et tags sont écrits dans la transaction de la requête (voir db_tx.rs),
+/// avec le géocodage de sa localisation si le service est configuré.
 pub async fn create_property(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
+    Extension(geocoder): Extension<Option<Geocoder>>,
+    tx: RequestTx,
     Json(payload): Json<CreatePropertyRequest>,
 ) -> impl IntoResponse {
     // Vérifier le rôle
     if !matches!(user.role, UserRole::Admin | UserRole::Manager) {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Accès manager ou admin requis"
+            "error": "Accès manager ou admin requis",
+            "code": ErrorCode::ManagerRequired
         }))).into_response();
     }
 
@@ -118,58 +322,200 @@ pub async fn create_property(
         }
     });
 
-    match sqlx::query_as!(
-        Property,
-        r#"INSERT INTO properties (onchain_id, name, location, type, description, 
-           total_price, token_price, annual_yield, image_url, documents, created_by, status)
-           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 'pending')
-           RETURNING id, onchain_id, name, location, type as property_type, description, 
-           total_price, token_price, annual_yield, image_url, documents, 
-           created_by, created_at, status as "status: PropertyStatus", 
-           status_updated_at, status_updated_by"#,
-        payload.onchain_id,
-        payload.name,
-        payload.location,
-        payload.property_type,
-        payload.description,
-        payload.total_price,
-        payload.token_price,
-        payload.annual_yield,
-        payload.image_url,
-        documents.as_deref(),
-        user.id
-    )
-    .fetch_one(&pool)
-    .await {
-        Ok(property) => (StatusCode::CREATED, Json(serde_json::json!({
-            "property": property,
-            "message": "Propriété créée avec succès"
-        }))).into_response(),
+    // Les tags doivent appartenir au vocabulaire défini par l'admin
+    let tag_ids = match resolve_payload_tags(&pool, payload.tags.as_deref()).await {
+        Ok(tag_ids) => tag_ids,
+        Err(response) => return response,
+    };
+    let amenities = serde_json::json!(payload.amenities.unwrap_or_default());
+
+    let contract_address = match check_onchain_binding(&pool, &payload.onchain_id, payload.contract_address.as_deref(), None).await {
+        Ok(contract_address) => contract_address,
+        Err(response) => return response,
+    };
+
+    let mut conn = match tx.conn().await {
+        Ok(conn) => conn,
+        Err(e) => return transaction_error(e),
+    };
+
+    // Slug d'URL unique dérivé du nom. Une création concurrente peut prendre le
+    // même slug entre son choix et l'insertion : l'insertion, isolée dans un
+    // point de sauvegarde, est alors rejouée avec le suffixe suivant.
+    let mut attempt = 1;
+    let created = loop {
+        let slug = match slug::unique_property_slug(&mut *conn, user.tenant_id, &payload.name, None).await {
+            Ok(slug) => slug,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Erreur lors de la génération du slug: {}", e),
+                "code": ErrorCode::DatabaseError
+            }))).into_response(),
+        };
+        let mut savepoint = match conn.begin().await {
+            Ok(savepoint) => savepoint,
+            Err(e) => return transaction_error(e),
+        };
+        let result = sqlx::query_as!(
+            Property,
+            r#"INSERT INTO properties (onchain_id, name, location, type, description, 
+               total_price, token_price, annual_yield, image_url, documents, created_by, status, slug, amenities,
+               requires_accreditation, tenant_id, contract_address)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 'draft', $12, $13, $14, $15, $16)
+               RETURNING id, onchain_id, slug, name, location, type as property_type, description, 
+               total_price, token_price, annual_yield, image_url, documents, 
+               created_by, created_at, status as "status: PropertyStatus", 
+               status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address"#,
+            payload.onchain_id,
+            payload.name,
+            payload.location,
+            payload.property_type,
+            payload.description,
+            payload.total_price,
+            payload.token_price,
+            payload.annual_yield,
+            payload.image_url,
+            documents.as_deref(),
+            user.id,
+            slug,
+            amenities,
+            payload.requires_accreditation.unwrap_or(false),
+            user.tenant_id,
+            contract_address
+        )
+        .fetch_one(&mut savepoint)
+        .await;
+        match result {
+            Err(e) if slug::is_slug_taken(&e) && attempt < slug::MAX_SLUG_ATTEMPTS => {
+                if let Err(e) = savepoint.rollback().await {
+                    return transaction_error(e);
+                }
+                attempt += 1;
+            },
+            Ok(property) => break savepoint.commit().await.map(|()| property),
+            Err(e) => break Err(e),
+        }
+    };
+
+    match created {
+        Ok(property) => {
+            if let Err(e) = schedule_geocoding(&mut conn, geocoder.as_ref(), property.id).await {
+                return geocoding_error(e);
+            }
+            match with_tags(&mut conn, property, tag_ids.as_deref()).await {
+                Ok(property) => (StatusCode::CREATED, Json(serde_json::json!({
+                    "property": property,
+                    "message": "Propriété créée en brouillon avec succès"
+                }))).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                    "error": format!("Erreur lors de l'enregistrement des tags: {}", e),
+                    "code": ErrorCode::DatabaseError
+                }))).into_response(),
+            }
+        },
+        Err(e) if is_onchain_id_taken(&e) => onchain_id_taken(&payload.onchain_id),
+        Err(e) if slug::is_slug_taken(&e) => slug_taken(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la création: {}", e.to_string())
+            "error": format!("Erreur lors de la création: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
 
 /// Route pour récupérer toutes les properties (authentification requise)
 /// Le comportement diffère selon le rôle de l'utilisateur :
-/// - Admin: voit toutes les propriétés
+/// - Admin: voit toutes les propriétés de sa plateforme (hors brouillons des autres utilisateurs)
 /// - Manager: voit uniquement les propriétés qu'il a créées
 /// - User: voit uniquement les propriétés dans lesquelles il a investi
+///
+/// Le paramètre optionnel `?ids=uuid1,uuid2` restreint la liste à ces propriétés
+/// (le filtrage par rôle reste appliqué), `?tags=a,b` ne garde que les propriétés portant
+/// tous ces tags, `?country=`, `?region=`, `?city=`, `?property_type=` et `?yield_bucket=`
+/// filtrent par localisation géocodée, type et tranche de rendement (voir facets.rs),
+/// `?include=` ajoute les relations demandées et `?fields=` limite les champs renvoyés.
 pub async fn get_all_properties(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
+    Query(query): Query<PropertyListQuery>,
 ) -> impl IntoResponse {
+    // Analyser la liste d'identifiants demandée
+    let ids = match query.ids.as_deref().map(parse_uuid_list) {
+        Some(Ok(ids)) if ids.len() > MAX_BATCH_IDS => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Maximum {} identifiants par requête", MAX_BATCH_IDS),
+            "code": ErrorCode::TooManyIds
+        }))).into_response(),
+        Some(Ok(ids)) => Some(ids),
+        Some(Err(invalid)) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Identifiant invalide: {}", invalid),
+            "code": ErrorCode::InvalidId
+        }))).into_response(),
+        None => None,
+    };
+
+    let includes = match includes::parse_includes(query.include.as_deref(), PROPERTY_INCLUDES) {
+        Ok(includes) => includes,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidInclude
+        }))).into_response(),
+    };
+
+    let fields = match fields::parse_fields(query.fields.as_deref(), PROPERTY_FIELDS) {
+        Ok(fields) => fields,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidFields
+        }))).into_response(),
+    };
+
+    // `?tags=a,b` : propriétés portant tous les tags demandés
+    let tag_filter = tags::parse_tag_filter(query.tags.as_deref());
+
+    // Localisation géocodée, type et tranche de rendement (voir facets.rs)
+    let filters = match PropertyFilters::parse(
+        query.country.as_deref(),
+        query.region.as_deref(),
+        query.city.as_deref(),
+        query.property_type.as_deref(),
+        query.yield_bucket.as_deref(),
+    ) {
+        Ok(filters) => filters,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidFilter
+        }))).into_response(),
+    };
+
     let properties_result = match user.role {
         UserRole::Admin => {
             sqlx::query_as!(
                 Property,
-                r#"SELECT id, onchain_id, name, location, type as property_type, description, 
+                r#"SELECT id, onchain_id, slug, name, location, type as property_type, description, 
                    total_price, token_price, annual_yield, image_url, documents, 
                    created_by, created_at, status as "status: PropertyStatus", 
-                   status_updated_at, status_updated_by
+                   status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address
                    FROM properties 
-                   ORDER BY created_at DESC"#
+                   WHERE (status <> 'draft' OR created_by = $1) AND tenant_id = $4
+                   AND ($2::uuid[] IS NULL OR id = ANY($2))
+                   AND ($3::text[] IS NULL OR id IN (SELECT pt.property_id FROM property_tags pt
+                       JOIN tags t ON t.id = pt.tag_id
+                       WHERE t.slug = ANY($3)
+                       GROUP BY pt.property_id
+                       HAVING COUNT(*) = cardinality($3)))
+                   AND ($5::text IS NULL OR country = $5)
+                   AND ($6::text IS NULL OR region = $6)
+                   AND ($7::text IS NULL OR city = $7)
+                   AND ($8::text IS NULL OR type = $8)
+                   AND ($9::text IS NULL OR property_yield_bucket(annual_yield) = $9)
+                   ORDER BY created_at DESC"#,
+                user.id,
+                ids.as_deref(),
+                tag_filter.as_deref(),
+                user.tenant_id,
+                filters.country,
+                filters.region,
+                filters.city,
+                filters.property_type,
+                filters.yield_bucket
             )
             .fetch_all(&pool)
             .await
@@ -177,14 +523,32 @@ pub async fn get_all_properties(
         UserRole::Manager => {
             sqlx::query_as!(
                 Property,
-                r#"SELECT id, onchain_id, name, location, type as property_type, description, 
+                r#"SELECT id, onchain_id, slug, name, location, type as property_type, description, 
                    total_price, token_price, annual_yield, image_url, documents, 
                    created_by, created_at, status as "status: PropertyStatus", 
-                   status_updated_at, status_updated_by
+                   status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address
                    FROM properties 
-                   WHERE created_by = $1
+                   WHERE (created_by = $1 OR id IN (SELECT property_id FROM property_managers WHERE user_id = $1))
+                   AND ($2::uuid[] IS NULL OR id = ANY($2))
+                   AND ($3::text[] IS NULL OR id IN (SELECT pt.property_id FROM property_tags pt
+                       JOIN tags t ON t.id = pt.tag_id
+                       WHERE t.slug = ANY($3)
+                       GROUP BY pt.property_id
+                       HAVING COUNT(*) = cardinality($3)))
+                   AND ($4::text IS NULL OR country = $4)
+                   AND ($5::text IS NULL OR region = $5)
+                   AND ($6::text IS NULL OR city = $6)
+                   AND ($7::text IS NULL OR type = $7)
+                   AND ($8::text IS NULL OR property_yield_bucket(annual_yield) = $8)
                    ORDER BY created_at DESC"#,
-                user.id
+                user.id,
+                ids.as_deref(),
+                tag_filter.as_deref(),
+                filters.country,
+                filters.region,
+                filters.city,
+                filters.property_type,
+                filters.yield_bucket
             )
             .fetch_all(&pool)
             .await
@@ -192,94 +556,349 @@ pub async fn get_all_properties(
         UserRole::User => {
             sqlx::query_as!(
                 Property,
-                r#"SELECT DISTINCT p.id, p.onchain_id, p.name, p.location, p.type as property_type, p.description, 
+                r#"SELECT DISTINCT p.id, p.onchain_id, p.slug, p.name, p.location, p.type as property_type, p.description, 
                    p.total_price, p.token_price, p.annual_yield, p.image_url, p.documents, 
                    p.created_by, p.created_at, p.status as "status: PropertyStatus", 
-                   p.status_updated_at, p.status_updated_by
+                   p.status_updated_at, p.status_updated_by, p.publish_at, p.published_at, p.amenities as "amenities: Amenities", p.requires_accreditation, p.contract_address
                    FROM properties p
                    JOIN investments i ON p.id = i.property_id
-                   WHERE i.user_id = $1
+                   WHERE i.user_id = $1 AND i.status <> 'refunded'
+                   AND ($2::uuid[] IS NULL OR p.id = ANY($2))
+                   AND ($3::text[] IS NULL OR p.id IN (SELECT pt.property_id FROM property_tags pt
+                       JOIN tags t ON t.id = pt.tag_id
+                       WHERE t.slug = ANY($3)
+                       GROUP BY pt.property_id
+                       HAVING COUNT(*) = cardinality($3)))
+                   AND ($4::text IS NULL OR p.country = $4)
+                   AND ($5::text IS NULL OR p.region = $5)
+                   AND ($6::text IS NULL OR p.city = $6)
+                   AND ($7::text IS NULL OR p.type = $7)
+                   AND ($8::text IS NULL OR property_yield_bucket(p.annual_yield) = $8)
                    ORDER BY p.created_at DESC"#,
-                user.id
+                user.id,
+                ids.as_deref(),
+                tag_filter.as_deref(),
+                filters.country,
+                filters.region,
+                filters.city,
+                filters.property_type,
+                filters.yield_bucket
             )
             .fetch_all(&pool)
             .await
         }
     };
 
+    let properties_result = match properties_result {
+        Ok(properties) => includes::expand_properties(&pool, properties, &includes).await
+            .map(|properties| fields::select_fields(properties, fields.as_deref(), &includes)),
+        Err(e) => Err(e),
+    };
+
     match properties_result {
-        Ok(properties) => (StatusCode::OK, Json(serde_json::json!({
-            "properties": properties,
-            "count": properties.len()
-        }))).into_response(),
+        Ok(properties) => envelope::list("properties", &properties).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la récupération: {}", e.to_string())
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
 
+/// Résout les tags d'une création/modification ; `None` laisse les tags inchangés
+async fn resolve_payload_tags(
+    pool: &PgPool,
+    tags: Option<&[String]>,
+) -> Result<Option<Vec<Uuid>>, axum::response::Response> {
+    let tags = match tags {
+        Some(tags) => tags,
+        None => return Ok(None),
+    };
+
+    match tags::resolve_tags(pool, tags).await {
+        Ok(Ok(tag_ids)) => Ok(Some(tag_ids)),
+        Ok(Err(e)) => Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::UnknownTags
+        }))).into_response()),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification des tags: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response()),
+    }
+}
+
+/// Enregistre les tags demandés puis sérialise la propriété avec ses tags
+async fn with_tags(
+    conn: &mut PgConnection,
+    property: Property,
+    tag_ids: Option<&[Uuid]>,
+) -> Result<serde_json::Value, sqlx::Error> {
+    if let Some(tag_ids) = tag_ids {
+        tags::set_property_tags(&mut *conn, property.id, tag_ids).await?;
+    }
+
+    let mut properties = includes::expand_properties_on(conn, vec![property], &["tags".to_string()]).await?;
+    Ok(properties.remove(0))
+}
+
+/// Met en file le géocodage de la localisation d'une propriété, si le service
+/// est configuré (voir geocoding.rs)
+async fn schedule_geocoding(
+    conn: &mut PgConnection,
+    geocoder: Option<&Geocoder>,
+    property_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    if geocoder.is_some() {
+        jobs::enqueue(conn, jobs::GEOCODE_PROPERTY, serde_json::json!({ "property_id": property_id })).await?;
+    }
+    Ok(())
+}
+
+fn geocoding_error(e: sqlx::Error) -> axum::response::Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de la mise en file du géocodage: {}", e),
+        "code": ErrorCode::DatabaseError
+    }))).into_response()
+}
+
+/// Analyse une liste d'UUID séparés par des virgules, renvoie la valeur fautive en cas d'erreur
+fn parse_uuid_list(raw: &str) -> Result<Vec<Uuid>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| Uuid::parse_str(s).map_err(|_| s.to_string()))
+        .collect()
+}
+
+/// Relations du détail d'une propriété : la galerie est toujours incluse
+fn detail_includes(raw: Option<&str>) -> Result<Vec<String>, String> {
+    let mut includes = includes::parse_includes(raw, PROPERTY_INCLUDES)?;
+    if !includes.iter().any(|i| i == "media") {
+        includes.push("media".to_string());
+    }
+    Ok(includes)
+}
+
 /// Route pour récupérer une property par ID (authentification requise)
 pub async fn get_property_by_id(
-    BearerAuthUser(_user): BearerAuthUser,
+    BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
     Path(property_id): Path<Uuid>,
+    Query(query): Query<IncludeQuery>,
 ) -> impl IntoResponse {
-    match sqlx::query_as!(
+    let includes = match detail_includes(query.include.as_deref()) {
+        Ok(includes) => includes,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidInclude
+        }))).into_response(),
+    };
+
+    let property = match sqlx::query_as!(
         Property,
-        r#"SELECT id, onchain_id, name, location, type as property_type, description, 
+        r#"SELECT id, onchain_id, slug, name, location, type as property_type, description, 
            total_price, token_price, annual_yield, image_url, documents, 
            created_by, created_at, status as "status: PropertyStatus", 
-           status_updated_at, status_updated_by
+           status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address
            FROM properties 
-           WHERE id = $1"#,
-        property_id
+           WHERE id = $1 AND tenant_id = $2
+           AND (status <> 'draft' OR created_by = $3
+                OR id IN (SELECT property_id FROM property_managers WHERE user_id = $3))"#,
+        property_id,
+        user.tenant_id,
+        user.id
     )
     .fetch_optional(&pool)
     .await {
-        Ok(Some(property)) => (StatusCode::OK, Json(property)).into_response(),
+        // Un brouillon n'est visible que par son créateur et ses co-gestionnaires
+        Ok(Some(property)) => property,
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    };
+
+    match includes::expand_properties(&pool, vec![property], &includes).await {
+        Ok(mut expanded) => (StatusCode::OK, Json(expanded.remove(0))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
+
+/// Route pour récupérer une property par son slug (authentification requise)
+pub async fn get_property_by_slug(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(slug): Path<String>,
+    Query(query): Query<IncludeQuery>,
+) -> impl IntoResponse {
+    let includes = match detail_includes(query.include.as_deref()) {
+        Ok(includes) => includes,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidInclude
+        }))).into_response(),
+    };
+
+    let property = match sqlx::query_as!(
+        Property,
+        r#"SELECT id, onchain_id, slug, name, location, type as property_type, description, 
+           total_price, token_price, annual_yield, image_url, documents, 
+           created_by, created_at, status as "status: PropertyStatus", 
+           status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address
+           FROM properties 
+           WHERE slug = $1 AND tenant_id = $2
+           AND (status <> 'draft' OR created_by = $3
+                OR id IN (SELECT property_id FROM property_managers WHERE user_id = $3))"#,
+        slug,
+        user.tenant_id,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        // Un brouillon n'est visible que par son créateur et ses co-gestionnaires
+        Ok(Some(property)) => property,
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    };
+
+    match includes::expand_properties(&pool, vec![property], &includes).await {
+        Ok(mut expanded) => (StatusCode::OK, Json(expanded.remove(0))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
+
+/// Route publique : propriété correspondant à un identifiant on-chain, pour
+/// l'indexation et les explorateurs. Seules les propriétés validées sont
+/// résolues ; l'identifiant étant unique, la recherche ne dépend pas de la
+/// plateforme. `?contract=` exige en plus le contrat registre de l'actif.
+pub async fn get_property_by_onchain_id(
+    State(pool): State<PgPool>,
+    Path(onchain_id): Path<String>,
+    Query(query): Query<OnchainLookupQuery>,
+) -> impl IntoResponse {
+    let contract = match query.contract.as_deref().map(parse_address) {
+        None => None,
+        Some(Some(address)) => Some(format_address(&address)),
+        Some(None) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "contract doit être une adresse (0x suivi de 40 caractères hexadécimaux)",
+            "code": ErrorCode::InvalidContractAddress
+        }))).into_response(),
+    };
+
+    match sqlx::query!(
+        r#"SELECT id, tenant_id, onchain_id, contract_address, slug, name, status as "status: PropertyStatus",
+           registry_tx_hash, registered_at, published_at, total_shares as "total_shares!", shares_sold
+           FROM properties
+           WHERE onchain_id = $1 AND status = 'validated'
+           AND ($2::text IS NULL OR contract_address = $2)"#,
+        onchain_id,
+        contract
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(row)) => (StatusCode::OK, Json(serde_json::json!({
+            "id": row.id,
+            "tenant_id": row.tenant_id,
+            "onchain_id": row.onchain_id,
+            "contract_address": row.contract_address,
+            "slug": row.slug,
+            "name": row.name,
+            "status": row.status,
+            "registry_tx_hash": row.registry_tx_hash,
+            "registered_at": row.registered_at,
+            "published_at": row.published_at,
+            "total_shares": row.total_shares,
+            "shares_sold": row.shares_sold
+        }))).into_response(),
         Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
-            "error": "Propriété non trouvée"
+            "error": "Aucune propriété validée pour cet identifiant on-chain",
+            "code": ErrorCode::PropertyNotFound
         }))).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la récupération: {}", e.to_string())
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
 
-/// Route pour mettre à jour une property (seulement si non validée)
+/// Route pour mettre à jour une property (seulement si non validée). La
+/// propriété est verrouillée dans la transaction de la requête, de la
+/// vérification de son état jusqu'à l'écriture des tags (voir db_tx.rs).
 pub async fn update_property(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
+    Extension(feed_cache): Extension<FeedCache>,
+    Extension(geocoder): Extension<Option<Geocoder>>,
     Path(property_id): Path<Uuid>,
+    tx: RequestTx,
     Json(payload): Json<CreatePropertyRequest>,
 ) -> impl IntoResponse {
     // Vérifier le rôle
     if !matches!(user.role, UserRole::Admin | UserRole::Manager) {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Accès manager ou admin requis"
+            "error": "Accès manager ou admin requis",
+            "code": ErrorCode::ManagerRequired
         }))).into_response();
     }
 
+    let mut conn = match tx.conn().await {
+        Ok(conn) => conn,
+        Err(e) => return transaction_error(e),
+    };
+
     // Vérifier d'abord que la property existe et n'est pas validée
     let existing_property = match sqlx::query!(
-        r#"SELECT status as "status: PropertyStatus" FROM properties WHERE id = $1"#,
-        property_id
+        r#"SELECT name, slug, location, created_by, status as "status: PropertyStatus", onchain_id, contract_address, registered_at
+           FROM properties WHERE id = $1 AND tenant_id = $2
+           FOR UPDATE"#,
+        property_id,
+        user.tenant_id
     )
-    .fetch_optional(&pool)
+    .fetch_optional(&mut *conn)
     .await {
         Ok(Some(prop)) => prop,
         Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
-            "error": "Propriété non trouvée"
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
         }))).into_response(),
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la vérification: {}", e.to_string())
+            "error": format!("Erreur lors de la vérification: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     };
 
+    // Créateur, co-gestionnaires owner ou editor et admin (voir property_managers.rs)
+    if let Err(response) = property_managers::check_edit(
+        &mut *conn,
+        &user,
+        property_id,
+        existing_property.created_by,
+        &existing_property.status,
+    ).await {
+        return response;
+    }
+
     // Empêcher la modification si la property est validée (sauf pour l'admin)
     if matches!(existing_property.status, PropertyStatus::Validated) && !matches!(user.role, UserRole::Admin) {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Impossible de modifier une propriété validée par l'admin"
+            "error": "Impossible de modifier une propriété validée par l'admin",
+            "code": ErrorCode::PropertyLocked
         }))).into_response();
     }
 
@@ -295,37 +914,250 @@ pub async fn update_property(
         }
     });
 
-    match sqlx::query_as!(
+    let tag_ids = match resolve_payload_tags(&pool, payload.tags.as_deref()).await {
+        Ok(tag_ids) => tag_ids,
+        Err(response) => return response,
+    };
+    let amenities = payload.amenities.map(|amenities| serde_json::json!(amenities));
+
+    let contract_address = match check_onchain_binding(&pool, &payload.onchain_id, payload.contract_address.as_deref(), Some(property_id)).await {
+        Ok(contract_address) => contract_address,
+        Err(response) => return response,
+    };
+
+    // Une fois enregistrée sur le registre, la propriété reste liée à son actif
+    if existing_property.registered_at.is_some()
+        && (payload.onchain_id != existing_property.onchain_id
+            || contract_address.is_some() && contract_address != existing_property.contract_address)
+    {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Propriété enregistrée on-chain : onchain_id et contract_address ne peuvent plus changer",
+            "code": ErrorCode::OnchainBindingLocked
+        }))).into_response();
+    }
+
+    // Le slug suit le nom : il n'est régénéré qu'en cas de renommage. Comme à
+    // la création, la mise à jour est rejouée avec le suffixe suivant si une
+    // écriture concurrente prend le slug choisi.
+    let renamed = existing_property.name != payload.name;
+    let mut attempt = 1;
+    let updated = loop {
+        let slug = if renamed {
+            match slug::unique_property_slug(&mut *conn, user.tenant_id, &payload.name, Some(property_id)).await {
+                Ok(slug) => slug,
+                Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                    "error": format!("Erreur lors de la génération du slug: {}", e),
+                    "code": ErrorCode::DatabaseError
+                }))).into_response(),
+            }
+        } else {
+            existing_property.slug.clone()
+        };
+        let mut savepoint = match conn.begin().await {
+            Ok(savepoint) => savepoint,
+            Err(e) => return transaction_error(e),
+        };
+        let result = sqlx::query_as!(
+            Property,
+            r#"UPDATE properties SET 
+               onchain_id = $2, name = $3, location = $4, type = $5, 
+               description = $6, total_price = $7, token_price = $8, 
+               annual_yield = $9, image_url = $10, documents = $11, slug = $12,
+               amenities = COALESCE($13, amenities),
+               requires_accreditation = COALESCE($14, requires_accreditation),
+               contract_address = COALESCE($15, contract_address),
+               -- Nouvelle localisation : l'ancien géocodage ne vaut plus
+               country = CASE WHEN location = $4 THEN country END,
+               region = CASE WHEN location = $4 THEN region END,
+               city = CASE WHEN location = $4 THEN city END,
+               geocoded_at = CASE WHEN location = $4 THEN geocoded_at END
+               WHERE id = $1
+               RETURNING id, onchain_id, slug, name, location, type as property_type, description, 
+               total_price, token_price, annual_yield, image_url, documents, 
+               created_by, created_at, status as "status: PropertyStatus", 
+               status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address"#,
+            property_id,
+            payload.onchain_id,
+            payload.name,
+            payload.location,
+            payload.property_type,
+            payload.description,
+            payload.total_price,
+            payload.token_price,
+            payload.annual_yield,
+            payload.image_url,
+            documents.as_deref(),
+            slug,
+            amenities,
+            payload.requires_accreditation,
+            contract_address
+        )
+        .fetch_one(&mut savepoint)
+        .await;
+        match result {
+            Err(e) if slug::is_slug_taken(&e) && attempt < slug::MAX_SLUG_ATTEMPTS => {
+                if let Err(e) = savepoint.rollback().await {
+                    return transaction_error(e);
+                }
+                attempt += 1;
+            },
+            Ok(property) => break savepoint.commit().await.map(|()| property),
+            Err(e) => break Err(e),
+        }
+    };
+
+    match updated {
+        Ok(property) => {
+            feed_cache.invalidate();
+
+            if existing_property.location != property.location {
+                if let Err(e) = schedule_geocoding(&mut conn, geocoder.as_ref(), property.id).await {
+                    return geocoding_error(e);
+                }
+            }
+
+            match with_tags(&mut conn, property, tag_ids.as_deref()).await {
+                Ok(property) => (StatusCode::OK, Json(serde_json::json!({
+                    "property": property,
+                    "message": "Propriété mise à jour avec succès"
+                }))).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                    "error": format!("Erreur lors de l'enregistrement des tags: {}", e),
+                    "code": ErrorCode::DatabaseError
+                }))).into_response(),
+            }
+        },
+        // Prix ramenés sous les parts déjà vendues
+        Err(e) if funding::is_oversold(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Le nombre de parts émises ne peut pas descendre sous les parts déjà vendues",
+            "code": ErrorCode::SharesBelowSold
+        }))).into_response(),
+        Err(e) if is_onchain_id_taken(&e) => onchain_id_taken(&payload.onchain_id),
+        Err(e) if slug::is_slug_taken(&e) => slug_taken(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
+
+/// Liste les éléments manquants empêchant la soumission d'une propriété
+fn submission_errors(property: &Property) -> Vec<&'static str> {
+    let zero = BigDecimal::from(0);
+    let mut errors = Vec::new();
+
+    if property.onchain_id.trim().is_empty() {
+        errors.push("onchain_id est requis");
+    }
+    if property.name.trim().is_empty() {
+        errors.push("name est requis");
+    }
+    if property.location.trim().is_empty() {
+        errors.push("location est requis");
+    }
+    if property.property_type.trim().is_empty() {
+        errors.push("property_type est requis");
+    }
+    if property.total_price <= zero {
+        errors.push("total_price doit être positif");
+    }
+    if property.token_price <= zero {
+        errors.push("token_price doit être positif");
+    }
+    if property.annual_yield < zero {
+        errors.push("annual_yield ne peut pas être négatif");
+    }
+    if property.documents.as_ref().is_none_or(|docs| docs.iter().all(|d| d.trim().is_empty())) {
+        errors.push("au moins un document est requis");
+    }
+
+    errors
+}
+
+/// Route pour soumettre un brouillon à la revue de l'admin (créateur,
+/// co-gestionnaires owner ou editor et admin)
+pub async fn submit_property(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let property = match sqlx::query_as!(
         Property,
-        r#"UPDATE properties SET 
-           onchain_id = $2, name = $3, location = $4, type = $5, 
-           description = $6, total_price = $7, token_price = $8, 
-           annual_yield = $9, image_url = $10, documents = $11
-           WHERE id = $1
-           RETURNING id, onchain_id, name, location, type as property_type, description, 
+        r#"SELECT id, onchain_id, slug, name, location, type as property_type, description, 
            total_price, token_price, annual_yield, image_url, documents, 
            created_by, created_at, status as "status: PropertyStatus", 
-           status_updated_at, status_updated_by"#,
+           status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address
+           FROM properties 
+           WHERE id = $1 AND tenant_id = $2"#,
         property_id,
-        payload.onchain_id,
-        payload.name,
-        payload.location,
-        payload.property_type,
-        payload.description,
-        payload.total_price,
-        payload.token_price,
-        payload.annual_yield,
-        payload.image_url,
-        documents.as_deref()
+        user.tenant_id
     )
-    .fetch_one(&pool)
+    .fetch_optional(&pool)
     .await {
-        Ok(property) => (StatusCode::OK, Json(serde_json::json!({
+        Ok(Some(property)) => property,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    };
+
+    if let Err(response) = property_managers::check_edit(&pool, &user, property_id, property.created_by, &property.status).await {
+        return response;
+    }
+
+    if !matches!(property.status, PropertyStatus::Draft) {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Seul un brouillon peut être soumis",
+            "code": ErrorCode::PropertyNotDraft
+        }))).into_response();
+    }
+
+    let errors = submission_errors(&property);
+    if !errors.is_empty() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+            "error": "Propriété incomplète",
+            "code": ErrorCode::PropertyIncomplete,
+            "details": errors
+        }))).into_response();
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let property = sqlx::query_as!(
+            Property,
+            r#"UPDATE properties SET status = 'pending'
+               WHERE id = $1 AND status = 'draft'
+               RETURNING id, onchain_id, slug, name, location, type as property_type, description, 
+               total_price, token_price, annual_yield, image_url, documents, 
+               created_by, created_at, status as "status: PropertyStatus", 
+               status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address"#,
+            property_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if property.is_some() {
+            stats::record_status_change(&mut tx, property_id, PropertyStatus::Pending, Some(user.id)).await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(property)
+    }.await;
+
+    match result {
+        Ok(Some(property)) => (StatusCode::OK, Json(serde_json::json!({
             "property": property,
-            "message": "Propriété mise à jour avec succès"
+            "message": "Propriété soumise pour validation"
+        }))).into_response(),
+        Ok(None) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Seul un brouillon peut être soumis",
+            "code": ErrorCode::PropertyNotDraft
         }))).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la mise à jour: {}", e.to_string())
+            "error": format!("Erreur lors de la soumission: {}", e),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
@@ -334,104 +1166,241 @@ pub async fn update_property(
 pub async fn update_property_status(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
+    Extension(feed_cache): Extension<FeedCache>,
+    Extension(registry): Extension<Option<Registry>>,
     Path(property_id): Path<Uuid>,
     Json(payload): Json<UpdatePropertyStatusRequest>,
 ) -> impl IntoResponse {
     // Seul l'admin peut modifier le statut
     if !matches!(user.role, UserRole::Admin) {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Seul l'admin peut modifier le statut des propriétés"
+            "error": "Seul l'admin peut modifier le statut des propriétés",
+            "code": ErrorCode::AdminRequired
         }))).into_response();
     }
 
-    // Vérifier que la property existe
+    // Le passage en brouillon n'est pas une décision de revue
+    if matches!(payload.status, PropertyStatus::Draft) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Statut invalide : une propriété ne peut pas être remise en brouillon",
+            "code": ErrorCode::InvalidStatusTransition
+        }))).into_response();
+    }
+
+    // Vérifier que la property existe et a été soumise
     let property_exists = sqlx::query!(
-        "SELECT id FROM properties WHERE id = $1",
-        property_id
+        r#"SELECT created_by, status as "status: PropertyStatus" FROM properties WHERE id = $1 AND tenant_id = $2"#,
+        property_id,
+        user.tenant_id
     )
     .fetch_optional(&pool)
     .await;
 
-    match property_exists {
-        Ok(Some(_)) => {},
+    let previous_status = match property_exists {
+        Ok(Some(prop)) if matches!(prop.status, PropertyStatus::Draft) => {
+            // Les brouillons des autres restent invisibles
+            return if prop.created_by == user.id {
+                (StatusCode::CONFLICT, Json(serde_json::json!({
+                    "error": "La propriété doit d'abord être soumise",
+                    "code": ErrorCode::PropertyNotSubmitted
+                }))).into_response()
+            } else {
+                (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                    "error": "Propriété non trouvée",
+                    "code": ErrorCode::PropertyNotFound
+                }))).into_response()
+            };
+        },
+        Ok(Some(prop)) => prop.status,
         Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
-            "error": "Propriété non trouvée"
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
         }))).into_response(),
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la vérification: {}", e.to_string())
+            "error": format!("Erreur lors de la vérification: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
-    }
+    };
 
-    match sqlx::query_as!(
-        Property,
-        r#"UPDATE properties SET 
-           status = $2, status_updated_at = $3, status_updated_by = $4
-           WHERE id = $1
-           RETURNING id, onchain_id, name, location, type as property_type, description, 
-           total_price, token_price, annual_yield, image_url, documents, 
-           created_by, created_at, status as "status: PropertyStatus", 
-           status_updated_at, status_updated_by"#,
-        property_id,
-        payload.status as PropertyStatus,
-        Utc::now(),
-        user.id
-    )
-    .fetch_one(&pool)
-    .await {
-        Ok(property) => (StatusCode::OK, Json(serde_json::json!({
-            "property": property,
-            "message": "Statut de la propriété mis à jour avec succès"
-        }))).into_response(),
+    // La validation déclenche l'enregistrement sur le contrat registre (si configuré) ;
+    // la propriété n'est publiée qu'une fois la transaction confirmée
+    let registers = matches!(payload.status, PropertyStatus::Validated)
+        && !matches!(previous_status, PropertyStatus::Validated);
+    let status_changed = payload.status != previous_status;
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let registration = match &registry {
+            Some(registry) if registers => {
+                registry::request_registration(&mut tx, registry, property_id, previous_status, user.id).await?
+            },
+            _ => None,
+        };
+
+        let property = sqlx::query_as!(
+            Property,
+            r#"UPDATE properties SET 
+               status = $2, status_updated_at = $3, status_updated_by = $4,
+               published_at = CASE
+                   WHEN $2::property_status = 'validated' AND (publish_at IS NULL OR publish_at <= NOW())
+                        AND (registry_tx_id IS NULL OR registered_at IS NOT NULL)
+                       THEN COALESCE(published_at, NOW())
+                   ELSE NULL
+               END
+               WHERE id = $1
+               RETURNING id, onchain_id, slug, name, location, type as property_type, description, 
+               total_price, token_price, annual_yield, image_url, documents, 
+               created_by, created_at, status as "status: PropertyStatus", 
+               status_updated_at, status_updated_by, publish_at, published_at, amenities as "amenities: Amenities", requires_accreditation, contract_address"#,
+            property_id,
+            payload.status as PropertyStatus,
+            Utc::now(),
+            user.id
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        if status_changed {
+            stats::record_status_change(&mut tx, property_id, property.status.clone(), Some(user.id)).await?;
+        }
+        if status_changed && matches!(property.status, PropertyStatus::Validated) {
+            outbox::record(&mut tx, &DomainEvent::PropertyValidated { property_id, validated_by: user.id }).await?;
+        }
+
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>((property, registration))
+    }.await;
+
+    match result {
+        Ok((property, registration)) => {
+            // Le sitemap et le flux dépendent des propriétés publiées
+            feed_cache.invalidate();
+
+            let message = if registration.is_some() {
+                "Statut mis à jour, enregistrement on-chain en cours"
+            } else {
+                "Statut de la propriété mis à jour avec succès"
+            };
+            (StatusCode::OK, Json(serde_json::json!({
+                "property": property,
+                "registration_tx_id": registration,
+                "message": message
+            }))).into_response()
+        },
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la mise à jour du statut: {}", e.to_string())
+            "error": format!("Erreur lors de la mise à jour du statut: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
 
-/// Route pour supprimer une property (admin seulement, et seulement si non validée)
+/// Route pour supprimer une property (admin seulement, et seulement si non validée).
+/// Une propriété avec investissements n'est supprimée qu'avec `?cascade=true`,
+/// qui met aussi ses investissements à la corbeille (voir deletion_impact.rs)
 pub async fn delete_property(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
+    Extension(feed_cache): Extension<FeedCache>,
     Path(property_id): Path<Uuid>,
+    Query(query): Query<DeletePropertyQuery>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     // Seul l'admin peut supprimer
     if !matches!(user.role, UserRole::Admin) {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Seul l'admin peut supprimer des propriétés"
+            "error": "Seul l'admin peut supprimer des propriétés",
+            "code": ErrorCode::AdminRequired
         }))).into_response();
     }
+    let cascade = query.cascade.unwrap_or(false);
 
-    // Vérifier que la property existe et récupérer son statut
-    let existing_property = match sqlx::query!(
-        r#"SELECT status as "status: PropertyStatus" FROM properties WHERE id = $1"#,
-        property_id
-    )
-    .fetch_optional(&pool)
-    .await {
-        Ok(Some(prop)) => prop,
+    // Vérifier que la property existe et évaluer les lignes rattachées
+    let impact = match deletion_impact::compute(&pool, &user, property_id).await {
+        Ok(Some(impact)) => impact,
         Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
-            "error": "Propriété non trouvée"
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
         }))).into_response(),
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la vérification: {}", e.to_string())
+            "error": format!("Erreur lors de la vérification: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     };
 
     // Empêcher la suppression si la property est validée
-    if matches!(existing_property.status, PropertyStatus::Validated) {
+    if matches!(impact.status, PropertyStatus::Validated) {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Impossible de supprimer une propriété validée"
+            "error": "Impossible de supprimer une propriété validée",
+            "code": ErrorCode::PropertyLocked
+        }))).into_response();
+    }
+    // Des investissements confirmés (réglés) empêchent la suppression, quel
+    // que soit le statut de la propriété, même en cascade
+    if impact.dependents.investments.settled > 0 {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": format!("Impossible de supprimer une propriété avec {} investissement(s) confirmé(s)", impact.dependents.investments.settled),
+            "code": ErrorCode::PropertyHasConfirmedInvestments,
+            "blocking_investments": impact.dependents.investments.settled,
+            "impact": impact
+        }))).into_response();
+    }
+    // Les autres investissements ne sont emportés qu'à la demande explicite
+    if impact.dependents.investments.total > 0 && !cascade {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "La propriété a des investissements : relancer avec ?cascade=true pour les mettre à la corbeille avec elle",
+            "code": ErrorCode::PropertyHasInvestments,
+            "impact": impact
         }))).into_response();
     }
 
-    match sqlx::query!("DELETE FROM properties WHERE id = $1", property_id)
-        .execute(&pool)
-        .await {
-        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
-            "message": "Propriété supprimée avec succès"
+    // Confirmation explicite (second facteur vérifié à cette étape), un seul
+    // jeton pour la propriété et toutes les lignes emportées en cascade
+    let action = if cascade { deletion_impact::CASCADE_ACTION } else { "delete_property" };
+    if let Err(response) = confirmations::ensure_confirmed(&pool, &user, &headers, action, &property_id.to_string()).await {
+        return response;
+    }
+
+    // Mise à la corbeille, restaurable par un admin (voir trash.rs)
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let investments = if cascade {
+            deletion_impact::trash_dependents(&mut tx, property_id, user.id).await?
+        } else {
+            0
+        };
+        let deleted = trash::trash_property(&mut tx, property_id, user.id).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>((deleted, investments))
+    }.await;
+
+    match result {
+        Ok((false, _)) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
         }))).into_response(),
+        Ok((true, investments)) => {
+            feed_cache.invalidate();
+
+            (StatusCode::OK, Json(serde_json::json!({
+                "message": "Propriété supprimée avec succès",
+                "trashed_investments": investments
+            }))).into_response()
+        },
+        // Investissement créé ou réglé entre l'aperçu et la suppression : la clé
+        // étrangère (RESTRICT) refuse de supprimer la propriété
+        Err(e) if matches!(e.as_database_error().and_then(|e| e.code()).as_deref(), Some("23503")) => {
+            let (error, code) = if cascade {
+                ("Impossible de supprimer une propriété avec des investissements confirmés", ErrorCode::PropertyHasConfirmedInvestments)
+            } else {
+                ("La propriété a des investissements : relancer avec ?cascade=true pour les mettre à la corbeille avec elle", ErrorCode::PropertyHasInvestments)
+            };
+            (StatusCode::CONFLICT, Json(serde_json::json!({
+                "error": error,
+                "code": code
+            }))).into_response()
+        },
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la suppression: {}", e.to_string())
+            "error": format!("Erreur lors de la suppression: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
@@ -439,17 +1408,39 @@ pub async fn delete_property(
 // Routes pour les Investissements
 
 /// Route pour récupérer tous les investissements (authentification requise)
+/// `?include=property` ajoute la propriété associée à chaque investissement,
+/// `?fields=` limite les champs renvoyés
 pub async fn get_all_investments(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
+    Query(query): Query<InvestmentListQuery>,
 ) -> impl IntoResponse {
+    let includes = match includes::parse_includes(query.include.as_deref(), INVESTMENT_INCLUDES) {
+        Ok(includes) => includes,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidInclude
+        }))).into_response(),
+    };
+
+    let fields = match fields::parse_fields(query.fields.as_deref(), INVESTMENT_FIELDS) {
+        Ok(fields) => fields,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidFields
+        }))).into_response(),
+    };
+
     let investments_result = match user.role {
         UserRole::Admin => {
             sqlx::query_as!(
                 Investment,
-                r#"SELECT id, user_id, property_id, amount_eth, shares, tx_hash, created_at
+                r#"SELECT id, user_id, property_id, amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei", shares, tx_hash,
+                     status as "status: InvestmentStatus", settled_at, created_at
                    FROM investments 
-                   ORDER BY created_at DESC"#
+                   WHERE tenant_id = $1
+                   ORDER BY created_at DESC"#,
+                user.tenant_id
             )
             .fetch_all(&pool)
             .await
@@ -457,10 +1448,11 @@ pub async fn get_all_investments(
         UserRole::Manager => {
             sqlx::query_as!(
                 Investment,
-                r#"SELECT i.id, i.user_id, i.property_id, i.amount_eth, i.shares, i.tx_hash, i.created_at
+                r#"SELECT i.id, i.user_id, i.property_id, i.amount_eth as "amount_eth: TokenAmount", i.amount_wei as "amount_wei: Wei", i.shares, i.tx_hash,
+                   i.status as "status: InvestmentStatus", i.settled_at, i.created_at
                    FROM investments i
                    JOIN properties p ON i.property_id = p.id
-                   WHERE p.created_by = $1
+                   WHERE p.created_by = $1 OR p.id IN (SELECT property_id FROM property_managers WHERE user_id = $1)
                    ORDER BY i.created_at DESC"#,
                 user.id
             )
@@ -470,7 +1462,8 @@ pub async fn get_all_investments(
         UserRole::User => {
             sqlx::query_as!(
                 Investment,
-                r#"SELECT id, user_id, property_id, amount_eth, shares, tx_hash, created_at
+                r#"SELECT id, user_id, property_id, amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei", shares, tx_hash,
+                     status as "status: InvestmentStatus", settled_at, created_at
                    FROM investments 
                    WHERE user_id = $1
                    ORDER BY created_at DESC"#,
@@ -481,13 +1474,37 @@ pub async fn get_all_investments(
         }
     };
 
+    // Contre-valeur de chaque investissement et du total (hors remboursés)
+    // dans la devise préférée de l'utilisateur
+    let investments_result = match investments_result {
+        Ok(investments) => async {
+            let valuation = currency::valuation_for(&pool, user.id).await?;
+            let total_eth: TokenAmount = investments.iter()
+                .filter(|i| !matches!(i.status, InvestmentStatus::Refunded))
+                .map(|i| &i.amount_eth)
+                .sum();
+            let fiat: Vec<_> = investments.iter().map(|i| valuation.value(&i.amount_eth)).collect();
+            let mut investments = includes::expand_investments(&pool, investments, &includes).await?;
+            for (investment, fiat) in investments.iter_mut().zip(fiat) {
+                investment["fiat"] = fiat;
+            }
+            let totals = serde_json::json!({
+                "amount_eth": total_eth,
+                "fiat": valuation.value(&total_eth)
+            });
+            Ok::<_, sqlx::Error>((fields::select_fields(investments, fields.as_deref(), &includes), totals, valuation.format()))
+        }.await,
+        Err(e) => Err(e),
+    };
+
     match investments_result {
-        Ok(investments) => (StatusCode::OK, Json(serde_json::json!({
-            "investments": investments,
-            "count": investments.len()
-        }))).into_response(),
+        Ok((investments, totals, currency)) => envelope::list("investments", &investments)
+            .with("totals", totals)
+            .with("currency", currency)
+            .into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la récupération: {}", e.to_string())
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
@@ -496,50 +1513,255 @@ pub async fn get_all_investments(
 pub async fn create_investment(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
+    Extension(rules): Extension<RiskRules>,
+    signed_request: Option<Extension<SignedRequest>>,
     Json(payload): Json<CreateInvestmentRequest>,
 ) -> impl IntoResponse {
+    // Requête signée par le wallet (voir signed_requests.rs), rattachée à l'investissement créé
+    let signed_request_id = signed_request.map(|Extension(signed)| signed.id);
+
+    // Montant en ETH ou en wei (voir money.rs)
+    let amount_eth = match payload.amount.resolve() {
+        Ok(Some(amount)) => amount,
+        Ok(None) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Montant requis (amount_eth ou amount_wei)",
+            "code": ErrorCode::InvalidAmount
+        }))).into_response(),
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidAmount
+        }))).into_response(),
+    };
+
+    // Utilisateurs signalés par le filtrage AML
+    if let Err(response) = compliance::ensure_not_flagged(&pool, user.id).await {
+        return response;
+    }
+
+    // Documents légaux en vigueur (CGU, avertissement sur les risques) non acceptés
+    if let Err(response) = legal::ensure_terms_accepted(&pool, user.id).await {
+        return response;
+    }
+
     // Vérifier que la propriété existe et est validée
-    let property_status = match sqlx::query!(
-        r#"SELECT status as "status: PropertyStatus" FROM properties WHERE id = $1"#,
-        payload.property_id
+    let property = match sqlx::query!(
+        r#"SELECT status as "status: PropertyStatus", published_at, requires_accreditation FROM properties WHERE id = $1 AND tenant_id = $2"#,
+        payload.property_id,
+        user.tenant_id
     )
     .fetch_optional(&pool)
     .await {
-        Ok(Some(prop)) => prop.status,
+        Ok(Some(prop)) => prop,
         Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
-            "error": "Propriété non trouvée"
+            "error": "Propriété non trouvée",
+            "code": ErrorCode::PropertyNotFound
         }))).into_response(),
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la vérification: {}", e.to_string())
+            "error": format!("Erreur lors de la vérification: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     };
 
     // Seules les propriétés validées peuvent recevoir des investissements
-    if !matches!(property_status, PropertyStatus::Validated) {
+    if !matches!(property.status, PropertyStatus::Validated) {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Impossible d'investir dans une propriété non validée"
+            "error": "Impossible d'investir dans une propriété non validée",
+            "code": ErrorCode::PropertyNotValidated
         }))).into_response();
     }
 
-    match sqlx::query_as!(
-        Investment,
-        r#"INSERT INTO investments (user_id, property_id, amount_eth, shares, tx_hash)
-           VALUES ($1, $2, $3, $4, $5)
-           RETURNING id, user_id, property_id, amount_eth, shares, tx_hash, created_at"#,
-        user.id,
-        payload.property_id,
-        payload.amount_eth,
-        payload.shares,
-        payload.tx_hash
-    )
-    .fetch_one(&pool)
-    .await {
-        Ok(investment) => (StatusCode::CREATED, Json(serde_json::json!({
-            "investment": investment,
-            "message": "Investissement créé avec succès"
+    // ... et déjà publiées (voir la publication programmée)
+    if property.published_at.is_none() {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Cette propriété n'est pas encore publiée",
+            "code": ErrorCode::PropertyNotPublished
+        }))).into_response();
+    }
+
+    // Offre réservée aux investisseurs accrédités
+    if property.requires_accreditation {
+        if let Err(response) = accreditation::ensure_accredited(&pool, user.id).await {
+            return response;
+        }
+    }
+
+    // Intention signée : la transaction doit correspondre à la cotation pré-autorisée
+    if let Some(intent_id) = payload.intent_id {
+        return create_investment_from_intent(&pool, &rules, &user, intent_id, signed_request_id, payload, amount_eth).await;
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        // Limites d'exposition, vérifiées sous verrou dans la transaction d'insertion
+        if let Some(breach) = exposure::check_exposure(&mut tx, user.id, payload.property_id, &amount_eth).await? {
+            return Ok(Err(breach.into_response()));
+        }
+        if let Some(investment_id) = onchain_investments::recorded_investment(&mut tx, user.id, payload.property_id, &payload.tx_hash).await? {
+            return Ok(Err(already_recorded(investment_id)));
+        }
+        let amount_wei = amount_eth.wei();
+        let investment = sqlx::query_as!(
+            Investment,
+            r#"INSERT INTO investments (user_id, property_id, amount_eth, amount_wei, shares, tx_hash, tenant_id)
+               VALUES ($1, $2, $3, $4, $5, $6, (SELECT tenant_id FROM properties WHERE id = $2))
+               RETURNING id, user_id, property_id, amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei", shares, tx_hash,
+                     status as "status: InvestmentStatus", settled_at, created_at"#,
+            user.id,
+            payload.property_id,
+            amount_eth.as_decimal(),
+            amount_wei.as_decimal(),
+            payload.shares,
+            payload.tx_hash
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        if let Some(signed_request_id) = signed_request_id {
+            signed_requests::attach(&mut tx, signed_request_id, investment.id).await?;
+        }
+        outbox::record(&mut tx, &DomainEvent::InvestmentCreated {
+            investment_id: investment.id,
+            user_id: investment.user_id,
+            property_id: investment.property_id,
+            amount_eth: investment.amount_eth.clone(),
+            shares: investment.shares,
+        }).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(investment))
+    }.await;
+
+    match result {
+        Ok(Ok(investment)) => {
+            risk::investment_created(pool.clone(), rules, investment.id);
+            (StatusCode::CREATED, Json(serde_json::json!({
+                "investment": currency::investment_with_fiat(&pool, user.id, &investment).await,
+                "signed_request_id": signed_request_id,
+                "message": "Investissement créé avec succès"
+            }))).into_response()
+        },
+        Ok(Err(response)) => response,
+        Err(e) if funding::is_oversold(&e) => {
+            funding::sold_out_response(funding::available_shares(&pool, payload.property_id).await.ok().flatten())
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
+    }
+}
+
+/// Réponse 409 d'une transaction déjà enregistrée (par l'indexation notamment,
+/// voir onchain_investments.rs)
+fn already_recorded(investment_id: Uuid) -> Response {
+    (StatusCode::CONFLICT, Json(serde_json::json!({
+        "error": "Cette transaction est déjà enregistrée pour cet investissement",
+        "code": ErrorCode::InvestmentAlreadyRecorded,
+        "investment_id": investment_id
+    }))).into_response()
+}
+
+/// Enregistre un investissement rattaché à une intention EIP-712 signée
+/// (même investisseur, même propriété, mêmes parts et même montant)
+async fn create_investment_from_intent(
+    pool: &PgPool,
+    rules: &RiskRules,
+    user: &SessionUser,
+    intent_id: Uuid,
+    signed_request_id: Option<Uuid>,
+    payload: CreateInvestmentRequest,
+    amount_eth: TokenAmount,
+) -> Response {
+    let reject = |code: ErrorCode, error: &str| (code.status(), Json(serde_json::json!({
+        "error": error,
+        "code": code
+    }))).into_response();
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let intent = sqlx::query!(
+            r#"SELECT user_id, property_id, shares, amount_eth as "amount_eth: TokenAmount", signature, investment_id
+               FROM investment_intents WHERE id = $1 FOR UPDATE"#,
+            intent_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+
+        let intent = match intent {
+            Some(intent) if intent.user_id == user.id => intent,
+            _ => return Ok(Err(reject(ErrorCode::IntentNotFound, "Intention non trouvée"))),
+        };
+        if intent.signature.is_none() {
+            return Ok(Err(reject(ErrorCode::IntentNotSigned, "Cette intention n'est pas encore signée")));
+        }
+        if intent.investment_id.is_some() {
+            return Ok(Err(reject(ErrorCode::IntentAlreadyUsed, "Cette intention a déjà été utilisée")));
+        }
+        if intent.property_id != payload.property_id
+            || intent.shares != payload.shares
+            || intent.amount_eth != amount_eth
+        {
+            return Ok(Err(reject(ErrorCode::IntentMismatch, "L'investissement ne correspond pas à la cotation signée")));
+        }
+        if let Some(breach) = exposure::check_exposure(&mut tx, user.id, payload.property_id, &amount_eth).await? {
+            return Ok(Err(breach.into_response()));
+        }
+        if let Some(investment_id) = onchain_investments::recorded_investment(&mut tx, user.id, payload.property_id, &payload.tx_hash).await? {
+            return Ok(Err(already_recorded(investment_id)));
+        }
+
+        let amount_wei = amount_eth.wei();
+        let investment = sqlx::query_as!(
+            Investment,
+            r#"INSERT INTO investments (user_id, property_id, amount_eth, amount_wei, shares, tx_hash, tenant_id)
+               VALUES ($1, $2, $3, $4, $5, $6, (SELECT tenant_id FROM properties WHERE id = $2))
+               RETURNING id, user_id, property_id, amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei", shares, tx_hash,
+                     status as "status: InvestmentStatus", settled_at, created_at"#,
+            user.id,
+            payload.property_id,
+            amount_eth.as_decimal(),
+            amount_wei.as_decimal(),
+            payload.shares,
+            payload.tx_hash
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE investment_intents SET investment_id = $2 WHERE id = $1",
+            intent_id,
+            investment.id
+        )
+        .execute(&mut tx)
+        .await?;
+        if let Some(signed_request_id) = signed_request_id {
+            signed_requests::attach(&mut tx, signed_request_id, investment.id).await?;
+        }
+        outbox::record(&mut tx, &DomainEvent::InvestmentCreated {
+            investment_id: investment.id,
+            user_id: investment.user_id,
+            property_id: investment.property_id,
+            amount_eth: investment.amount_eth.clone(),
+            shares: investment.shares,
+        }).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok(investment))
+    }.await;
+
+    match result {
+        Ok(Ok(investment)) => {
+            risk::investment_created(pool.clone(), rules.clone(), investment.id);
+            (StatusCode::CREATED, Json(serde_json::json!({
+                "investment": currency::investment_with_fiat(pool, user.id, &investment).await,
+                "intent_id": intent_id,
+                "signed_request_id": signed_request_id,
+                "message": "Investissement créé avec succès"
+            }))).into_response()
+        },
+        Ok(Err(response)) => response,
+        Err(e) if funding::is_oversold(&e) => {
+            funding::sold_out_response(funding::available_shares(pool, payload.property_id).await.ok().flatten())
+        },
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la création: {}", e.to_string())
+            "error": format!("Erreur lors de la création: {}", e),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
@@ -549,22 +1771,35 @@ pub async fn get_investment_by_id(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
     Path(investment_id): Path<Uuid>,
+    Query(query): Query<IncludeQuery>,
 ) -> impl IntoResponse {
+    let includes = match includes::parse_includes(query.include.as_deref(), INVESTMENT_INCLUDES) {
+        Ok(includes) => includes,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidInclude
+        }))).into_response(),
+    };
+
     let investment = match sqlx::query_as!(
         Investment,
-        r#"SELECT id, user_id, property_id, amount_eth, shares, tx_hash, created_at
+        r#"SELECT id, user_id, property_id, amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei", shares, tx_hash,
+                     status as "status: InvestmentStatus", settled_at, created_at
            FROM investments 
-           WHERE id = $1"#,
-        investment_id
+           WHERE id = $1 AND tenant_id = $2"#,
+        investment_id,
+        user.tenant_id
     )
     .fetch_optional(&pool)
     .await {
         Ok(Some(inv)) => inv,
         Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
-            "error": "Investissement non trouvé"
+            "error": "Investissement non trouvé",
+            "code": ErrorCode::InvestmentNotFound
         }))).into_response(),
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la récupération: {}", e.to_string())
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     };
 
@@ -588,62 +1823,151 @@ pub async fn get_investment_by_id(
 
     if !has_access {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Accès non autorisé à cet investissement"
+            "error": "Accès non autorisé à cet investissement",
+            "code": ErrorCode::InvestmentForbidden
         }))).into_response();
     }
 
-    (StatusCode::OK, Json(investment)).into_response()
+    let result = async {
+        let fiat = currency::valuation_for(&pool, user.id).await?.value(&investment.amount_eth);
+        let mut expanded = includes::expand_investments(&pool, vec![investment], &includes).await?;
+        expanded[0]["fiat"] = fiat;
+        Ok::<_, sqlx::Error>(expanded.remove(0))
+    }.await;
+
+    match result {
+        Ok(investment) => (StatusCode::OK, Json(investment)).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
 }
 
-/// Route pour mettre à jour un investissement
+/// Route pour mettre à jour un investissement. Chaque modification est
+/// historisée (voir investment_revisions.rs) ; un investissement réglé on-chain
+/// n'est corrigeable que par un admin avec un motif, un investissement
+/// remboursé ne l'est plus.
 pub async fn update_investment(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
     Path(investment_id): Path<Uuid>,
     Json(payload): Json<UpdateInvestmentRequest>,
 ) -> impl IntoResponse {
-    // Vérifier que l'investissement existe et récupérer ses infos
-    let existing_investment = match sqlx::query!(
-        "SELECT user_id FROM investments WHERE id = $1",
-        investment_id
-    )
-    .fetch_optional(&pool)
-    .await {
-        Ok(Some(inv)) => inv,
-        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
-            "error": "Investissement non trouvé"
+    let amount_eth = match payload.amount.resolve() {
+        Ok(amount_eth) => amount_eth,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e,
+            "code": ErrorCode::InvalidAmount
         }))).into_response(),
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la vérification: {}", e.to_string())
+    };
+    if amount_eth.as_ref().is_some_and(|amount| !amount.is_positive()) || payload.shares.is_some_and(|shares| shares <= 0) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le montant et le nombre de parts doivent être positifs",
+            "code": ErrorCode::InvalidAmount
+        }))).into_response();
+    }
+    let tx_hash = match payload.tx_hash.as_deref().map(parse_tx_hash) {
+        Some(None) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Hash de transaction invalide (0x + 64 caractères hexadécimaux)",
+            "code": ErrorCode::InvalidTxHash
         }))).into_response(),
+        Some(tx_hash) => tx_hash,
+        None => None,
     };
+    let reason = payload.reason.as_deref().map(str::trim).filter(|reason| !reason.is_empty());
+    let is_admin = matches!(user.role, UserRole::Admin);
 
-    // Contrôle d'accès : seul l'admin ou le propriétaire peut modifier
-    if !matches!(user.role, UserRole::Admin) && existing_investment.user_id != user.id {
-        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Seul l'admin ou le propriétaire peut modifier cet investissement"
-        }))).into_response();
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        // Verrouille la ligne : les modifications concurrentes sont historisées l'une après l'autre
+        let Some(existing) = sqlx::query!(
+            r#"SELECT user_id, amount_eth as "amount_eth: TokenAmount", shares, tx_hash, status as "status: InvestmentStatus"
+               FROM investments WHERE id = $1 AND tenant_id = $2
+               FOR UPDATE"#,
+            investment_id,
+            user.tenant_id
+        )
+        .fetch_optional(&mut tx)
+        .await? else {
+            return Ok(Err((ErrorCode::InvestmentNotFound, "Investissement non trouvé")));
+        };
+
+        // Contrôle d'accès : seul l'admin ou le propriétaire peut modifier
+        if !is_admin && existing.user_id != user.id {
+            return Ok(Err((ErrorCode::InvestmentForbidden, "Seul l'admin ou le propriétaire peut modifier cet investissement")));
+        }
+        match existing.status {
+            InvestmentStatus::Refunded => {
+                return Ok(Err((ErrorCode::InvestmentRefunded, "Un investissement remboursé ne peut plus être modifié")));
+            },
+            InvestmentStatus::Settled if !is_admin => {
+                return Ok(Err((ErrorCode::InvestmentSettled, "Investissement réglé on-chain : seul un admin peut le corriger")));
+            },
+            InvestmentStatus::Settled if reason.is_none() => {
+                return Ok(Err((ErrorCode::ReasonRequired, "Motif (reason) obligatoire pour corriger un investissement réglé on-chain")));
+            },
+            // Le hash est renseigné au règlement (POST /api/admin/investments/:id/settle)
+            InvestmentStatus::PendingSettlement if tx_hash.is_some() => {
+                return Ok(Err((ErrorCode::TxHashSetAtSettlement, "Le hash de transaction est renseigné au règlement de l'investissement")));
+            },
+            _ => {},
+        }
+
+        let investment = sqlx::query_as!(
+            Investment,
+            r#"UPDATE investments SET
+               amount_eth = COALESCE($2, amount_eth), amount_wei = COALESCE($3, amount_wei),
+               shares = COALESCE($4, shares), tx_hash = COALESCE($5, tx_hash)
+               WHERE id = $1
+               RETURNING id, user_id, property_id, amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei", shares, tx_hash,
+                         status as "status: InvestmentStatus", settled_at, created_at"#,
+            investment_id,
+            amount_eth.as_ref().map(TokenAmount::as_decimal),
+            amount_eth.as_ref().map(|amount| amount.wei().as_decimal().clone()),
+            payload.shares,
+            tx_hash
+        )
+        .fetch_one(&mut tx)
+        .await?;
+
+        // Champs effectivement modifiés, valeurs relues en base
+        let mut changes = serde_json::Map::new();
+        if investment.amount_eth != existing.amount_eth {
+            changes.insert("amount_eth".to_string(), serde_json::json!({ "from": existing.amount_eth, "to": investment.amount_eth }));
+        }
+        if investment.shares != existing.shares {
+            changes.insert("shares".to_string(), serde_json::json!({ "from": existing.shares, "to": investment.shares }));
+        }
+        if investment.tx_hash != existing.tx_hash {
+            changes.insert("tx_hash".to_string(), serde_json::json!({ "from": existing.tx_hash, "to": investment.tx_hash }));
+        }
+
+        let revision_id = if changes.is_empty() {
+            None
+        } else {
+            Some(investment_revisions::record(&mut tx, investment_id, Some(user.id), reason, serde_json::Value::Object(changes)).await?)
+        };
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok((investment, revision_id)))
     }
+    .await;
 
-    match sqlx::query_as!(
-        Investment,
-        r#"UPDATE investments SET 
-           amount_eth = $2, shares = $3, tx_hash = $4
-           WHERE id = $1
-           RETURNING id, user_id, property_id, amount_eth, shares, tx_hash, created_at"#,
-        investment_id,
-        payload.amount_eth,
-        payload.shares,
-        payload.tx_hash
-    )
-    .fetch_one(&pool)
-    .await {
-        Ok(investment) => (StatusCode::OK, Json(serde_json::json!({
+    match result {
+        Ok(Ok((investment, revision_id))) => (StatusCode::OK, Json(serde_json::json!({
             "investment": investment,
-            "message": "Investissement mis à jour avec succès"
+            "revision_id": revision_id,
+            "message": if revision_id.is_some() { "Investissement mis à jour avec succès" } else { "Aucune modification" }
         }))).into_response(),
+        Ok(Err((code, error))) => (code.status(), Json(serde_json::json!({
+            "error": error,
+            "code": code
+        }))).into_response(),
+        Err(e) if funding::is_oversold(&e) => funding::sold_out_response(None),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la mise à jour: {}", e.to_string())
+            "error": format!("Erreur lors de la mise à jour: {}", e),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
@@ -653,38 +1977,48 @@ pub async fn delete_investment(
     BearerAuthUser(user): BearerAuthUser,
     State(pool): State<PgPool>,
     Path(investment_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     // Vérifier que l'investissement existe et récupérer ses infos
     let existing_investment = match sqlx::query!(
-        "SELECT user_id FROM investments WHERE id = $1",
-        investment_id
+        "SELECT user_id FROM investments WHERE id = $1 AND tenant_id = $2",
+        investment_id,
+        user.tenant_id
     )
     .fetch_optional(&pool)
     .await {
         Ok(Some(inv)) => inv,
         Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
-            "error": "Investissement non trouvé"
+            "error": "Investissement non trouvé",
+            "code": ErrorCode::InvestmentNotFound
         }))).into_response(),
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la vérification: {}", e.to_string())
+            "error": format!("Erreur lors de la vérification: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     };
 
     // Contrôle d'accès : seul l'admin ou le propriétaire peut supprimer
     if !matches!(user.role, UserRole::Admin) && existing_investment.user_id != user.id {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Seul l'admin ou le propriétaire peut supprimer cet investissement"
+            "error": "Seul l'admin ou le propriétaire peut supprimer cet investissement",
+            "code": ErrorCode::InvestmentForbidden
         }))).into_response();
     }
+    // Suppression par un admin : second facteur exigé
+    if matches!(user.role, UserRole::Admin) {
+        if let Err(response) = two_factor::ensure_second_factor(&pool, &user, &headers).await {
+            return response;
+        }
+    }
 
-    match sqlx::query!("DELETE FROM investments WHERE id = $1", investment_id)
-        .execute(&pool)
-        .await {
+    match trash::trash_investment(&pool, investment_id, user.id).await {
         Ok(_) => (StatusCode::OK, Json(serde_json::json!({
             "message": "Investissement supprimé avec succès"
         }))).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la suppression: {}", e.to_string())
+            "error": format!("Erreur lors de la suppression: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
@@ -693,13 +2027,16 @@ pub async fn delete_investment(
 pub async fn update_user_role(
     BearerAuthUser(admin_user): BearerAuthUser,
     State(pool): State<PgPool>,
+    Extension(user_cache): Extension<UserCache>,
     Path(user_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateUserRoleRequest>,
 ) -> impl IntoResponse {
     // Seul l'admin peut modifier les rôles
     if !matches!(admin_user.role, UserRole::Admin) {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Seul l'admin peut modifier les rôles des utilisateurs"
+            "error": "Seul l'admin peut modifier les rôles des utilisateurs",
+            "code": ErrorCode::AdminRequired
         }))).into_response();
     }
 
@@ -709,74 +2046,121 @@ pub async fn update_user_role(
 
     // Vérifier que l'utilisateur existe
     let existing_user = match sqlx::query!(
-        r#"SELECT id, wallet, name, role as "role: UserRole" FROM users WHERE id = $1"#,
-        user_id
+        r#"SELECT id, wallet, name, role as "role: UserRole" FROM users WHERE id = $1 AND tenant_id = $2"#,
+        user_id,
+        admin_user.tenant_id
     )
     .fetch_optional(&pool)
     .await {
         Ok(Some(user)) => user,
         Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
-            "error": "Utilisateur non trouvé"
+            "error": "Utilisateur non trouvé",
+            "code": ErrorCode::UserNotFound
         }))).into_response(),
         Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la vérification: {}", e.to_string())
+            "error": format!("Erreur lors de la vérification: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     };
 
     // Empêcher l'admin de modifier son propre rôle
     if existing_user.id == admin_user.id {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Impossible de modifier son propre rôle"
+            "error": "Impossible de modifier son propre rôle",
+            "code": ErrorCode::SelfRoleChange
         }))).into_response();
     }
 
+    // Confirmation explicite, liée à l'utilisateur et au rôle visés
+    // (second facteur vérifié à cette étape)
+    let target = format!("{}:{}", user_id, new_role);
+    if let Err(response) = confirmations::ensure_confirmed(&pool, &admin_user, &headers, "update_user_role", &target).await {
+        return response;
+    }
+
+    // Octroi ou retrait du rôle admin : approbation d'un second admin (voir role_approvals.rs)
+    if role_approvals::requires_approval(existing_user.role, new_role) {
+        return role_approvals::request_role_change(&pool, &admin_user, existing_user.id, existing_user.role, new_role).await;
+    }
+
     // Mettre à jour le rôle
     match sqlx::query_as!(
         User,
         r#"UPDATE users SET role = $2
            WHERE id = $1
-           RETURNING id, wallet, name, role as "role: UserRole", created_at"#,
+           RETURNING id, tenant_id, wallet as "wallet: WalletAddress", name, role as "role: UserRole", created_at"#,
         user_id,
         new_role as UserRole
     )
     .fetch_one(&pool)
     .await {
-        Ok(updated_user) => (StatusCode::OK, Json(serde_json::json!({
-            "user": updated_user,
-            "message": format!("Rôle de l'utilisateur mis à jour vers '{}'", role_display)
-        }))).into_response(),
+        Ok(updated_user) => {
+            activity::record_detached(
+                pool.clone(),
+                updated_user.id,
+                Some(admin_user.id),
+                "role_changed",
+                serde_json::json!({ "from": existing_user.role, "to": new_role }),
+                None
+            );
+
+            // Le rôle en cache n'est plus valide, quel que soit le wallet utilisé
+            let mut stale = vec![updated_user.wallet.as_str().to_string()];
+            match wallets::linked_wallets(&pool, updated_user.id).await {
+                Ok(linked) => stale.extend(linked),
+                Err(e) => tracing::error!("Erreur lors de la lecture des wallets rattachés: {}", e),
+            }
+            user_cache.invalidate_everywhere(&pool, &stale).await;
+
+            (StatusCode::OK, Json(serde_json::json!({
+                "user": updated_user,
+                "message": format!("Rôle de l'utilisateur mis à jour vers '{}'", role_display)
+            }))).into_response()
+        },
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la mise à jour: {}", e.to_string())
+            "error": format!("Erreur lors de la mise à jour: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
 
-/// Route pour lister tous les utilisateurs (admin seulement)
+/// Route pour lister tous les utilisateurs de la plateforme (admin seulement),
+/// triable et filtrable (voir `USER_LIST`)
 pub async fn get_all_users(
     BearerAuthUser(admin_user): BearerAuthUser,
     State(pool): State<PgPool>,
+    Query(params): Query<HashMap<String, String>>,
+    pagination: Pagination,
 ) -> impl IntoResponse {
     // Seul l'admin peut voir tous les utilisateurs
     if !matches!(admin_user.role, UserRole::Admin) {
         return (StatusCode::FORBIDDEN, Json(serde_json::json!({
-            "error": "Seul l'admin peut voir tous les utilisateurs"
+            "error": "Seul l'admin peut voir tous les utilisateurs",
+            "code": ErrorCode::AdminRequired
         }))).into_response();
     }
 
-    match sqlx::query_as!(
-        User,
-        r#"SELECT id, wallet, name, role as "role: UserRole", created_at
-           FROM users 
-           ORDER BY created_at DESC"#
-    )
-    .fetch_all(&pool)
-    .await {
-        Ok(users) => (StatusCode::OK, Json(serde_json::json!({
-            "users": users,
-            "count": users.len()
+    let list = match USER_LIST.parse(&params, pagination) {
+        Ok(list) => list,
+        Err(e) => return (e.code.status(), Json(serde_json::json!({
+            "error": e.message,
+            "code": e.code
         }))).into_response(),
+    };
+
+    let mut builder = QueryBuilder::new("SELECT id, tenant_id, wallet, name, role, created_at FROM users WHERE tenant_id = ");
+    builder.push_bind(admin_user.tenant_id);
+    list.push_filters(&mut builder);
+    list.push_order_and_page(&mut builder);
+
+    match builder.build_query_as::<User>().fetch_all(&pool).await {
+        Ok(mut users) => {
+            let page = pagination.finish(&mut users, |user| Cursor::new(user.created_at, user.id));
+            envelope::list("users", &users).paginated(page).into_response()
+        },
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
-            "error": format!("Erreur lors de la récupération: {}", e.to_string())
+            "error": format!("Erreur lors de la récupération: {}", e.to_string()),
+            "code": ErrorCode::DatabaseError
         }))).into_response(),
     }
 }
\ No newline at end of file
@@ -0,0 +1,405 @@
+// scenarios.rs
+//
+// Scénarios pour les tests de bout en bout (Cypress, Playwright) :
+// `POST /dev/scenarios/:name` vide la base puis la remplit dans un état
+// nommé, avec des identifiants et des wallets fixes, pour que chaque suite
+// parte des mêmes données sans accès direct à la base. Le vidage retire
+// toutes les tables puis remet les lignes créées par la migration (plateforme
+// par défaut, admin, durées de conservation, drapeaux) ; les caches mémoire de
+// ce processus sont vidés.
+//
+// Réservés aux builds de développement (sauf compilé avec la feature
+// `scenarios`), activés par `SCENARIOS_ENABLED=true` et refusés avec
+// `APP_ENV=production`.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use bigdecimal::BigDecimal;
+use sqlx::{PgConnection, PgPool};
+use std::env;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::cache::UserCache;
+use crate::eip712::keccak256;
+use crate::error_codes::ErrorCode;
+use crate::feeds::FeedCache;
+use crate::flags::FeatureFlags;
+use crate::models::{PropertyStatus, UserRole};
+use crate::slug::slugify;
+use crate::stats::{LeaderboardCache, PropertyStatsCache};
+use crate::tenants::TenantRegistry;
+
+/// Scénarios disponibles et leur description
+const SCENARIOS: &[(&str, &str)] = &[
+    ("empty", "Base vide : plateforme par défaut et admin de la migration"),
+    ("one-funded-property", "Une propriété validée et publiée, entièrement financée par deux investisseurs"),
+    ("pending-review-queue", "Trois propriétés soumises en attente de validation, un brouillon et une propriété refusée"),
+];
+
+/// Wallet et identifiant de l'admin recréé au vidage (wallet de la migration)
+const ADMIN_WALLET: &str = "0x00000000000000000000000000000000000ad111";
+const ADMIN_ID: u128 = 0x5ce0_0000_0000_0000_0000_0000_0000_0001;
+
+/// Wallets des utilisateurs des scénarios (préfixe `0x5ce`)
+const MANAGER_WALLET: &str = "0x5ce0000000000000000000000000000000000001";
+const SECOND_MANAGER_WALLET: &str = "0x5ce0000000000000000000000000000000000002";
+const INVESTOR_WALLETS: [&str; 2] = [
+    "0x5ce0000000000000000000000000000000000101",
+    "0x5ce0000000000000000000000000000000000102",
+];
+
+#[derive(Clone)]
+pub struct Scenarios;
+
+impl Scenarios {
+    /// `None` si `SCENARIOS_ENABLED` n'est pas activé, avec `APP_ENV=production`,
+    /// ou dans un build release compilé sans la feature `scenarios`
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("SCENARIOS_ENABLED").is_ok_and(|v| v.trim().eq_ignore_ascii_case("true"));
+        if !enabled {
+            return None;
+        }
+        if !cfg!(any(debug_assertions, feature = "scenarios")) {
+            println!("⚠️  SCENARIOS_ENABLED ignoré : build release compilé sans la feature `scenarios`");
+            return None;
+        }
+        if env::var("APP_ENV").is_ok_and(|v| v.trim().eq_ignore_ascii_case("production")) {
+            println!("⚠️  SCENARIOS_ENABLED ignoré : APP_ENV=production");
+            return None;
+        }
+        Some(Self)
+    }
+}
+
+/// Identifiant fixe d'une ligne créée par un scénario
+fn seed_id(value: u128) -> Uuid {
+    Uuid::from_u128(value)
+}
+
+fn decimal(value: &str) -> BigDecimal {
+    BigDecimal::from_str(value).unwrap_or_default()
+}
+
+/// Vide toutes les tables, puis remet les lignes créées par la migration
+async fn reset(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let tables: Vec<String> = sqlx::query_scalar!(
+        r#"SELECT tablename as "tablename!" FROM pg_tables WHERE schemaname = 'public' ORDER BY tablename"#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let tables = tables.iter().map(|t| format!("\"{}\"", t.replace('"', "\"\""))).collect::<Vec<_>>().join(", ");
+    sqlx::query(&format!("TRUNCATE {} RESTART IDENTITY CASCADE", tables))
+        .execute(&mut *conn)
+        .await?;
+
+    // Mêmes lignes que migrations/supabase_migration.sql
+    sqlx::query!(
+        "INSERT INTO tenants (id, slug, name) VALUES ('00000000-0000-0000-0000-000000000001', 'default', 'Plateforme principale')"
+    )
+    .execute(&mut *conn)
+    .await?;
+    insert_user(conn, seed_id(ADMIN_ID), ADMIN_WALLET, "Admin", UserRole::Admin).await?;
+    sqlx::query!(
+        r#"INSERT INTO retention_policies (entity, retention_days)
+           VALUES ('notifications', 180), ('document_downloads', 365), ('auth_failures', 30), ('request_logs', 1825)"#
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query!(
+        "INSERT INTO feature_flags (key, description, enabled) VALUES ('fiat_payments', 'Paiements en euros via Stripe', TRUE)"
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+async fn insert_user(
+    conn: &mut PgConnection,
+    id: Uuid,
+    wallet: &str,
+    name: &str,
+    role: UserRole,
+) -> Result<serde_json::Value, sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO users (id, wallet, name, role) VALUES ($1, $2, $3, $4)",
+        id,
+        wallet,
+        name,
+        role.clone() as UserRole
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(serde_json::json!({ "id": id, "wallet": wallet, "name": name, "role": role }))
+}
+
+struct SeedProperty {
+    id: u128,
+    onchain_id: &'static str,
+    name: &'static str,
+    location: &'static str,
+    kind: &'static str,
+    total_price: &'static str,
+    token_price: &'static str,
+    annual_yield: &'static str,
+    status: PropertyStatus,
+    /// Ancienneté de la soumission, pour l'ordre de la file de validation
+    age_hours: i32,
+}
+
+/// Propriété créée par `manager` ; son historique de statuts suit le statut final
+async fn insert_property(
+    conn: &mut PgConnection,
+    property: &SeedProperty,
+    manager: Uuid,
+) -> Result<serde_json::Value, sqlx::Error> {
+    let id = seed_id(property.id);
+    let slug = slugify(property.name);
+    let reviewed = matches!(property.status, PropertyStatus::Validated | PropertyStatus::Rejected);
+    let status_updated_by = if reviewed { seed_id(ADMIN_ID) } else { manager };
+    sqlx::query!(
+        r#"INSERT INTO properties (
+               id, onchain_id, slug, name, location, type, description, total_price, token_price, annual_yield,
+               documents, created_by, status, status_updated_at, status_updated_by, published_at, created_at
+           )
+           VALUES (
+               $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, ARRAY['https://example.com/documents/prospectus.pdf'],
+               $11, $12, NOW() - make_interval(hours => $13), $14,
+               CASE WHEN $12 = 'validated'::property_status THEN NOW() - make_interval(hours => $13) END,
+               NOW() - make_interval(hours => $13 + 24)
+           )"#,
+        id,
+        property.onchain_id,
+        slug,
+        property.name,
+        property.location,
+        property.kind,
+        format!("{} ({}), données de scénario", property.name, property.location),
+        decimal(property.total_price),
+        decimal(property.token_price),
+        decimal(property.annual_yield),
+        manager,
+        property.status.clone() as PropertyStatus,
+        property.age_hours,
+        status_updated_by
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if !matches!(property.status, PropertyStatus::Draft) {
+        sqlx::query!(
+            r#"INSERT INTO property_status_events (property_id, status, changed_by, created_at)
+               VALUES ($1, 'pending', $2, NOW() - make_interval(hours => $3 + 1))"#,
+            id,
+            manager,
+            property.age_hours
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    if reviewed {
+        sqlx::query!(
+            r#"INSERT INTO property_status_events (property_id, status, changed_by, created_at)
+               VALUES ($1, $2, $3, NOW() - make_interval(hours => $4))"#,
+            id,
+            property.status.clone() as PropertyStatus,
+            seed_id(ADMIN_ID),
+            property.age_hours
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(serde_json::json!({
+        "id": id,
+        "onchain_id": property.onchain_id,
+        "slug": slug,
+        "status": property.status,
+        "created_by": manager
+    }))
+}
+
+/// Investissement réglé ; le hash de transaction est dérivé de son identifiant
+async fn insert_investment(
+    conn: &mut PgConnection,
+    id: Uuid,
+    user_id: Uuid,
+    property_id: Uuid,
+    shares: i32,
+    amount_eth: &str,
+) -> Result<serde_json::Value, sqlx::Error> {
+    let tx_hash = format!("0x{}", hex::encode(keccak256(format!("scenario:{}", id).as_bytes())));
+    sqlx::query!(
+        r#"INSERT INTO investments (id, user_id, property_id, amount_eth, amount_wei, shares, tx_hash, status, settled_at)
+           VALUES ($1, $2, $3, $4::numeric, $4::numeric * 1000000000000000000, $5, $6, 'settled', NOW())"#,
+        id,
+        user_id,
+        property_id,
+        decimal(amount_eth),
+        shares,
+        tx_hash
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(serde_json::json!({
+        "id": id,
+        "user_id": user_id,
+        "property_id": property_id,
+        "shares": shares,
+        "amount_eth": amount_eth,
+        "tx_hash": tx_hash
+    }))
+}
+
+/// Remplit la base selon le scénario ; renvoie les lignes créées
+async fn seed(conn: &mut PgConnection, name: &str) -> Result<serde_json::Value, sqlx::Error> {
+    let mut users = vec![serde_json::json!({
+        "id": seed_id(ADMIN_ID), "wallet": ADMIN_WALLET, "name": "Admin", "role": UserRole::Admin
+    })];
+    let mut properties = Vec::new();
+    let mut investments = Vec::new();
+
+    match name {
+        "one-funded-property" => {
+            let manager = seed_id(0x5ce0_0000_0000_0000_0000_0000_0000_0011);
+            users.push(insert_user(conn, manager, MANAGER_WALLET, "Manager Scénario", UserRole::Manager).await?);
+            let investors = [
+                seed_id(0x5ce0_0000_0000_0000_0000_0000_0000_0101),
+                seed_id(0x5ce0_0000_0000_0000_0000_0000_0000_0102),
+            ];
+            for (i, (id, wallet)) in investors.iter().zip(INVESTOR_WALLETS).enumerate() {
+                users.push(insert_user(conn, *id, wallet, &format!("Investisseur {}", i + 1), UserRole::User).await?);
+            }
+
+            // 100 parts de 0,1 ETH : 60 + 40 parts vendues
+            let property = SeedProperty {
+                id: 0x5ce0_0000_0000_0000_0000_0000_0000_1001,
+                onchain_id: "scenario-funded-1",
+                name: "Appartement Scénario Financé",
+                location: "Lyon",
+                kind: "apartment",
+                total_price: "10",
+                token_price: "0.1",
+                annual_yield: "5.5",
+                status: PropertyStatus::Validated,
+                age_hours: 72,
+            };
+            properties.push(insert_property(conn, &property, manager).await?);
+            let property_id = seed_id(property.id);
+            investments.push(insert_investment(conn, seed_id(0x5ce0_0000_0000_0000_0000_0000_0000_2001), investors[0], property_id, 60, "6").await?);
+            investments.push(insert_investment(conn, seed_id(0x5ce0_0000_0000_0000_0000_0000_0000_2002), investors[1], property_id, 40, "4").await?);
+        },
+        "pending-review-queue" => {
+            let managers = [
+                seed_id(0x5ce0_0000_0000_0000_0000_0000_0000_0011),
+                seed_id(0x5ce0_0000_0000_0000_0000_0000_0000_0012),
+            ];
+            users.push(insert_user(conn, managers[0], MANAGER_WALLET, "Manager Scénario", UserRole::Manager).await?);
+            users.push(insert_user(conn, managers[1], SECOND_MANAGER_WALLET, "Second Manager Scénario", UserRole::Manager).await?);
+
+            // File de validation : la plus ancienne soumission d'abord
+            let queue = [
+                (0x5ce0_0000_0000_0000_0000_0000_0000_1101, "scenario-pending-1", "Studio Scénario Bordeaux", "Bordeaux", "studio", "50", "0.05", "4.2", PropertyStatus::Pending, 48, managers[0]),
+                (0x5ce0_0000_0000_0000_0000_0000_0000_1102, "scenario-pending-2", "Maison Scénario Nantes", "Nantes", "house", "120", "0.25", "6", PropertyStatus::Pending, 24, managers[1]),
+                (0x5ce0_0000_0000_0000_0000_0000_0000_1103, "scenario-pending-3", "Bureaux Scénario Lille", "Lille", "office", "300", "0.5", "7.5", PropertyStatus::Pending, 2, managers[0]),
+                (0x5ce0_0000_0000_0000_0000_0000_0000_1104, "scenario-draft-1", "Appartement Scénario Brouillon", "Paris", "apartment", "80", "0.1", "3.8", PropertyStatus::Draft, 1, managers[0]),
+                (0x5ce0_0000_0000_0000_0000_0000_0000_1105, "scenario-rejected-1", "Local Scénario Refusé", "Marseille", "commercial", "40", "0.1", "9", PropertyStatus::Rejected, 96, managers[1]),
+            ];
+            for (id, onchain_id, name, location, kind, total_price, token_price, annual_yield, status, age_hours, manager) in queue {
+                let property = SeedProperty { id, onchain_id, name, location, kind, total_price, token_price, annual_yield, status, age_hours };
+                properties.push(insert_property(conn, &property, manager).await?);
+            }
+        },
+        _ => {},
+    }
+
+    Ok(serde_json::json!({
+        "scenario": name,
+        "users": users,
+        "properties": properties,
+        "investments": investments
+    }))
+}
+
+fn error_response(status: StatusCode, code: ErrorCode, error: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": error, "code": code }))).into_response()
+}
+
+/// Réponse des routes `/dev/scenarios*` sans `SCENARIOS_ENABLED=true`
+fn inactive() -> Response {
+    error_response(StatusCode::NOT_FOUND, ErrorCode::NotFound, "Scénarios inactifs (SCENARIOS_ENABLED)")
+}
+
+/// GET /dev/scenarios - Scénarios disponibles
+pub async fn list_scenarios(Extension(scenarios): Extension<Option<Scenarios>>) -> Response {
+    if scenarios.is_none() {
+        return inactive();
+    }
+    let list: Vec<_> = SCENARIOS.iter()
+        .map(|(name, description)| serde_json::json!({ "name": name, "description": description }))
+        .collect();
+    (StatusCode::OK, Json(serde_json::json!({ "scenarios": list }))).into_response()
+}
+
+/// POST /dev/scenarios/:name - Vide la base et la remplit selon le scénario
+#[allow(clippy::too_many_arguments)]
+pub async fn load_scenario(
+    State(pool): State<PgPool>,
+    Path(name): Path<String>,
+    Extension(scenarios): Extension<Option<Scenarios>>,
+    Extension(user_cache): Extension<UserCache>,
+    Extension(feed_cache): Extension<FeedCache>,
+    Extension(leaderboard_cache): Extension<LeaderboardCache>,
+    Extension(property_stats_cache): Extension<PropertyStatsCache>,
+    Extension(feature_flags): Extension<FeatureFlags>,
+    Extension(tenant_registry): Extension<TenantRegistry>,
+) -> Response {
+    if scenarios.is_none() {
+        return inactive();
+    }
+    if !SCENARIOS.iter().any(|(scenario, _)| *scenario == name) {
+        let names: Vec<_> = SCENARIOS.iter().map(|(scenario, _)| *scenario).collect();
+        return error_response(
+            StatusCode::NOT_FOUND,
+            ErrorCode::NotFound,
+            &format!("Scénario inconnu: {} (disponibles : {})", name, names.join(", ")),
+        );
+    }
+
+    let seeded = async {
+        let mut tx = pool.begin().await?;
+        reset(&mut tx).await?;
+        let seeded = seed(&mut tx, &name).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(seeded)
+    }
+    .await;
+    let seeded = match seeded {
+        Ok(seeded) => seeded,
+        Err(e) => return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::DatabaseError,
+            &format!("Erreur lors du chargement du scénario: {}", e),
+        ),
+    };
+
+    // Les caches ne doivent plus servir les données effacées
+    user_cache.invalidate_all_everywhere(&pool).await;
+    feed_cache.invalidate_everywhere(&pool).await;
+    leaderboard_cache.invalidate();
+    property_stats_cache.invalidate();
+    if let Err(e) = feature_flags.refresh(&pool).await {
+        tracing::warn!("Scénario {}: drapeaux non rechargés: {}", name, e);
+    }
+    if let Err(e) = tenant_registry.refresh(&pool).await {
+        tracing::warn!("Scénario {}: plateformes non rechargées: {}", name, e);
+    }
+    tracing::info!(scenario = %name, "Scénario chargé");
+
+    (StatusCode::OK, Json(seeded)).into_response()
+}
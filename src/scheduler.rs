@@ -0,0 +1,243 @@
+// scheduler.rs
+//
+// Publication programmée : une propriété validée ne devient publique qu'à
+// partir de son `publish_at`. Une tâche de fond publie les propriétés arrivées
+// à échéance ; les managers gèrent la date via `/api/properties/:id/schedule`.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::env;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::feeds::FeedCache;
+use crate::models::{PropertyStatus, SchedulePublicationRequest, UserRole};
+use crate::worker;
+
+/// Lance la tâche de publication. Intervalle configurable via
+/// `SCHEDULER_INTERVAL_SECS` (60s par défaut).
+pub fn spawn(pool: PgPool, feed_cache: FeedCache) {
+    let interval_secs = env::var("SCHEDULER_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(60);
+
+    tokio::spawn(async move {
+        let period = Duration::from_secs(interval_secs);
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match worker::run_exclusive(&pool, "scheduler", period, publish_due(&pool)).await {
+                None | Some(Ok(0)) => {},
+                Some(Ok(count)) => {
+                    tracing::info!("{} propriété(s) publiée(s) par le planificateur", count);
+                    feed_cache.invalidate_everywhere(&pool).await;
+                },
+                Some(Err(e)) => tracing::error!("Erreur du planificateur de publication: {}", e),
+            }
+        }
+    });
+}
+
+/// Publie les propriétés validées dont la date de publication est atteinte
+/// (et dont l'enregistrement on-chain éventuel est confirmé)
+async fn publish_due(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"UPDATE properties SET published_at = NOW()
+           WHERE status = 'validated' AND published_at IS NULL
+           AND (publish_at IS NULL OR publish_at <= NOW())
+           AND (registry_tx_id IS NULL OR registered_at IS NOT NULL)"#
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+struct ScheduleInfo {
+    created_by: Uuid,
+    status: PropertyStatus,
+    publish_at: Option<DateTime<Utc>>,
+    published_at: Option<DateTime<Utc>>,
+}
+
+/// Charge la planification d'une propriété si l'utilisateur peut la gérer
+/// (créateur ou admin ; les brouillons des autres restent invisibles)
+async fn load_schedule(
+    pool: &PgPool,
+    user: &SessionUser,
+    property_id: Uuid,
+) -> Result<ScheduleInfo, axum::response::Response> {
+    let row = sqlx::query!(
+        r#"SELECT created_by, status as "status: PropertyStatus", publish_at, published_at
+           FROM properties WHERE id = $1"#,
+        property_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de la vérification: {}", e)
+    }))).into_response())?;
+
+    let info = match row {
+        Some(row) => ScheduleInfo {
+            created_by: row.created_by,
+            status: row.status,
+            publish_at: row.publish_at,
+            published_at: row.published_at,
+        },
+        None => return Err((StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response()),
+    };
+
+    let is_owner = info.created_by == user.id;
+    let is_admin = matches!(user.role, UserRole::Admin);
+    if matches!(info.status, PropertyStatus::Draft) && !is_owner {
+        return Err((StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response());
+    }
+    if !is_owner && !is_admin {
+        return Err((StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul le créateur ou l'admin peut gérer la publication"
+        }))).into_response());
+    }
+
+    Ok(info)
+}
+
+fn schedule_json(property_id: Uuid, info: &ScheduleInfo) -> serde_json::Value {
+    // Compte à rebours jusqu'à la publication, tant qu'elle n'a pas eu lieu
+    let seconds_remaining = match (info.publish_at, info.published_at) {
+        (Some(publish_at), None) => Some((publish_at - Utc::now()).num_seconds().max(0)),
+        _ => None,
+    };
+
+    serde_json::json!({
+        "property_id": property_id,
+        "status": info.status,
+        "publish_at": info.publish_at,
+        "published_at": info.published_at,
+        "seconds_remaining": seconds_remaining
+    })
+}
+
+/// Route pour consulter la planification de publication (créateur ou admin)
+pub async fn get_schedule(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match load_schedule(&pool, &user, property_id).await {
+        Ok(info) => (StatusCode::OK, Json(schedule_json(property_id, &info))).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Route pour programmer la publication d'une propriété (créateur ou admin)
+pub async fn set_schedule(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(property_id): Path<Uuid>,
+    Json(payload): Json<SchedulePublicationRequest>,
+) -> impl IntoResponse {
+    let info = match load_schedule(&pool, &user, property_id).await {
+        Ok(info) => info,
+        Err(response) => return response,
+    };
+
+    if info.published_at.is_some() {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "La propriété est déjà publiée"
+        }))).into_response();
+    }
+
+    if payload.publish_at <= Utc::now() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "La date de publication doit être dans le futur"
+        }))).into_response();
+    }
+
+    match sqlx::query!(
+        r#"UPDATE properties SET publish_at = $2
+           WHERE id = $1 AND published_at IS NULL
+           RETURNING status as "status: PropertyStatus", publish_at, published_at"#,
+        property_id,
+        payload.publish_at
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(row)) => {
+            let info = ScheduleInfo {
+                created_by: info.created_by,
+                status: row.status,
+                publish_at: row.publish_at,
+                published_at: row.published_at,
+            };
+            (StatusCode::OK, Json(serde_json::json!({
+                "schedule": schedule_json(property_id, &info),
+                "message": "Publication programmée avec succès"
+            }))).into_response()
+        },
+        Ok(None) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "La propriété est déjà publiée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la programmation: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour annuler la programmation (créateur ou admin).
+/// Une propriété déjà validée est alors publiée immédiatement.
+pub async fn clear_schedule(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(feed_cache): Extension<FeedCache>,
+    Path(property_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let info = match load_schedule(&pool, &user, property_id).await {
+        Ok(info) => info,
+        Err(response) => return response,
+    };
+
+    if info.published_at.is_some() {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "La propriété est déjà publiée"
+        }))).into_response();
+    }
+
+    match sqlx::query!(
+        r#"UPDATE properties SET publish_at = NULL
+           WHERE id = $1 AND published_at IS NULL"#,
+        property_id
+    )
+    .execute(&pool)
+    .await {
+        Ok(_) => {
+            // Publication immédiate si la propriété est déjà validée
+            if let Ok(count) = publish_due(&pool).await {
+                if count > 0 {
+                    feed_cache.invalidate();
+                }
+            }
+
+            (StatusCode::OK, Json(serde_json::json!({
+                "message": "Programmation de la publication annulée"
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'annulation: {}", e)
+        }))).into_response(),
+    }
+}
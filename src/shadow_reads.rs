@@ -0,0 +1,304 @@
+// shadow_reads.rs
+//
+// Lectures fantômes, pour valider une réécriture de requête ou un réplica de
+// lecture avant de s'y fier. Sur les routes listées dans
+// `SHADOW_READS_ENDPOINTS`, une fraction des requêtes
+// (`SHADOW_READS_SAMPLE_RATE`) exécute aussi, en tâche de fond, la lecture de
+// remplacement : même requête sur le réplica `SHADOW_READS_DATABASE_URL`, ou
+// requête réécrite (sur le réplica s'il est configuré). Les deux résultats
+// sont comparés en JSON ; les écarts et les échecs sont journalisés et
+// enregistrés dans `shadow_read_diffs`. La réponse servie est toujours celle
+// de la lecture d'origine.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::env;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::error_codes::ErrorCode;
+use crate::models::{ShadowReadDiff, UserRole};
+use crate::tenants::DEFAULT_TENANT_ID;
+
+/// Routes équipées d'une lecture fantôme, et lecture de remplacement
+pub const ENDPOINTS: &[(&str, &str)] = &[
+    ("public_properties", "GET /public/v1/properties : même requête sur le réplica"),
+    ("public_stats", "GET /public/v1/stats : même requête sur le réplica"),
+    ("property_widget", "GET /public/v1/widget/:property_id : financement lu dans properties.shares_sold"),
+];
+
+/// Écarts conservés par comparaison
+const MAX_DIFFERENCES: usize = 20;
+
+/// Écarts renvoyés par la route d'état
+const RECENT_DIFFS: i64 = 20;
+
+/// Comparaisons faites par ce processus, par route
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ShadowCounters {
+    pub compared: u64,
+    pub mismatched: u64,
+    pub failed: u64,
+}
+
+#[derive(Clone)]
+pub struct ShadowReads {
+    endpoints: Vec<&'static str>,
+    sample_rate: f64,
+    /// Base des lectures de remplacement : réplica, à défaut la base principale
+    pool: PgPool,
+    replica: bool,
+    counters: Arc<Mutex<BTreeMap<&'static str, ShadowCounters>>>,
+}
+
+impl ShadowReads {
+    /// Routes via `SHADOW_READS_ENDPOINTS` (noms séparés par des virgules, `*`
+    /// pour toutes), fraction des requêtes via `SHADOW_READS_SAMPLE_RATE`
+    /// (0.1 par défaut) et réplica via `SHADOW_READS_DATABASE_URL`
+    /// (optionnel) ; `None` si aucune route n'est listée
+    pub fn from_env(pool: &PgPool) -> Option<Self> {
+        let listed = env::var("SHADOW_READS_ENDPOINTS").unwrap_or_default();
+        let mut endpoints = Vec::new();
+        for name in listed.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if name == "*" {
+                endpoints = ENDPOINTS.iter().map(|(endpoint, _)| *endpoint).collect();
+                break;
+            }
+            match ENDPOINTS.iter().find(|(endpoint, _)| *endpoint == name) {
+                Some((endpoint, _)) => endpoints.push(*endpoint),
+                None => println!("⚠️  SHADOW_READS_ENDPOINTS : route inconnue ignorée : {}", name),
+            }
+        }
+        if endpoints.is_empty() {
+            return None;
+        }
+
+        let sample_rate = env::var("SHADOW_READS_SAMPLE_RATE")
+            .ok()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+            .map(|v| v.clamp(0.0, 1.0))
+            .unwrap_or(0.1);
+
+        let replica_url = env::var("SHADOW_READS_DATABASE_URL").ok().filter(|v| !v.trim().is_empty());
+        let replica = replica_url.is_some();
+        let pool = match replica_url {
+            Some(url) => PgPoolOptions::new()
+                .max_connections(5)
+                .acquire_timeout(Duration::from_secs(5))
+                .connect_lazy(&url)
+                .expect("Invalid SHADOW_READS_DATABASE_URL"),
+            None => pool.clone(),
+        };
+
+        Some(Self { endpoints, sample_rate, pool, replica, counters: Arc::default() })
+    }
+
+    pub fn endpoints(&self) -> &[&'static str] {
+        &self.endpoints
+    }
+
+    pub fn has_replica(&self) -> bool {
+        self.replica
+    }
+
+    fn sampled(&self, endpoint: &str) -> bool {
+        self.endpoints.contains(&endpoint) && rand::random::<f64>() < self.sample_rate
+    }
+
+    fn count(&self, endpoint: &'static str, mismatched: bool, failed: bool) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let counters = counters.entry(endpoint).or_default();
+        counters.compared += 1;
+        counters.mismatched += mismatched as u64;
+        counters.failed += failed as u64;
+    }
+
+    /// Exécute la lecture de remplacement et enregistre l'écart éventuel
+    async fn compare<T, Fut>(
+        &self,
+        pool: &PgPool,
+        endpoint: &'static str,
+        request_key: String,
+        legacy: Value,
+        legacy_ms: i32,
+        shadow: impl FnOnce(PgPool) -> Fut,
+    ) where
+        T: Serialize,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let started = Instant::now();
+        let result = shadow(self.pool.clone()).await;
+        let shadow_ms = elapsed_ms(started);
+
+        let mut differences = Vec::new();
+        let error = match result {
+            Ok(value) => {
+                diff("$", &legacy, &serde_json::to_value(value).unwrap_or_default(), &mut differences);
+                None
+            },
+            Err(e) => Some(e.to_string()),
+        };
+        self.count(endpoint, !differences.is_empty(), error.is_some());
+        if differences.is_empty() && error.is_none() {
+            return;
+        }
+
+        tracing::warn!(
+            endpoint,
+            request_key,
+            differences = differences.len(),
+            error = error.as_deref().unwrap_or_default(),
+            legacy_ms,
+            shadow_ms,
+            "Lecture fantôme différente de la lecture servie"
+        );
+        if let Err(e) = sqlx::query!(
+            r#"INSERT INTO shadow_read_diffs (endpoint, request_key, differences, error, legacy_ms, shadow_ms)
+               VALUES ($1, $2, $3, $4, $5, $6)"#,
+            endpoint,
+            request_key,
+            Value::Array(differences),
+            error,
+            legacy_ms,
+            shadow_ms
+        )
+        .execute(pool)
+        .await {
+            tracing::error!("Erreur lors de l'enregistrement d'une lecture fantôme: {}", e);
+        }
+    }
+}
+
+fn elapsed_ms(started: Instant) -> i32 {
+    started.elapsed().as_millis().min(i32::MAX as u128) as i32
+}
+
+/// Écarts entre deux valeurs JSON, repérés par leur chemin (`$.properties[2].name`)
+fn diff(path: &str, legacy: &Value, shadow: &Value, out: &mut Vec<Value>) {
+    if out.len() >= MAX_DIFFERENCES {
+        return;
+    }
+    match (legacy, shadow) {
+        (Value::Object(a), Value::Object(b)) => {
+            for key in a.keys().chain(b.keys().filter(|key| !a.contains_key(*key))) {
+                let path = format!("{}.{}", path, key);
+                diff(&path, a.get(key).unwrap_or(&Value::Null), b.get(key).unwrap_or(&Value::Null), out);
+            }
+        },
+        (Value::Array(a), Value::Array(b)) if a.len() != b.len() => out.push(serde_json::json!({
+            "path": format!("{}.length", path),
+            "legacy": a.len(),
+            "shadow": b.len(),
+        })),
+        (Value::Array(a), Value::Array(b)) => {
+            for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                diff(&format!("{}[{}]", path, i), a, b, out);
+            }
+        },
+        _ if legacy != shadow => out.push(serde_json::json!({ "path": path, "legacy": legacy, "shadow": shadow })),
+        _ => {},
+    }
+}
+
+/// Lecture d'origine, servie, et lecture de remplacement `shadow` comparée en
+/// tâche de fond si la route est équipée et la requête échantillonnée
+pub async fn read<T, L, S, Fut>(
+    shadow_reads: Option<&ShadowReads>,
+    pool: &PgPool,
+    endpoint: &'static str,
+    request_key: String,
+    legacy: L,
+    shadow: S,
+) -> Result<T, sqlx::Error>
+where
+    T: Serialize + Send + 'static,
+    L: Future<Output = Result<T, sqlx::Error>>,
+    S: FnOnce(PgPool) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, sqlx::Error>> + Send + 'static,
+{
+    let started = Instant::now();
+    let result = legacy.await;
+    let legacy_ms = elapsed_ms(started);
+
+    if let (Some(shadow_reads), Ok(value)) = (shadow_reads.filter(|s| s.sampled(endpoint)), &result) {
+        let legacy = serde_json::to_value(value).unwrap_or_default();
+        let shadow_reads = shadow_reads.clone();
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            shadow_reads.compare(&pool, endpoint, request_key, legacy, legacy_ms, shadow).await;
+        });
+    }
+    result
+}
+
+/// Les lectures fantômes portent sur toutes les plateformes : réservées aux
+/// admins de la plateforme par défaut
+fn platform_admin_only(role: &UserRole, tenant_id: Uuid) -> Option<Response> {
+    (!matches!(role, UserRole::Admin) || tenant_id != DEFAULT_TENANT_ID).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins de la plateforme principale peuvent consulter les lectures fantômes",
+        "code": ErrorCode::Forbidden
+    }))).into_response())
+}
+
+/// Route admin : configuration, compteurs de ce processus et derniers écarts
+pub async fn get_shadow_reads(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(shadow_reads): Extension<Option<ShadowReads>>,
+) -> impl IntoResponse {
+    if let Some(response) = platform_admin_only(&user.role, user.tenant_id) {
+        return response;
+    }
+
+    let result = async {
+        let total = sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!" FROM shadow_read_diffs"#)
+            .fetch_one(&pool)
+            .await?;
+        let recent = sqlx::query_as!(
+            ShadowReadDiff,
+            "SELECT * FROM shadow_read_diffs ORDER BY created_at DESC LIMIT $1",
+            RECENT_DIFFS
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok::<_, sqlx::Error>((total, recent))
+    }.await;
+
+    match result {
+        Ok((total, recent)) => {
+            let counters = shadow_reads.as_ref()
+                .map(|s| s.counters.lock().unwrap_or_else(|e| e.into_inner()).clone())
+                .unwrap_or_default();
+            (StatusCode::OK, Json(serde_json::json!({
+                "enabled": shadow_reads.is_some(),
+                "endpoints": shadow_reads.as_ref().map(ShadowReads::endpoints).unwrap_or_default(),
+                "sample_rate": shadow_reads.as_ref().map(|s| s.sample_rate),
+                "replica": shadow_reads.as_ref().is_some_and(ShadowReads::has_replica),
+                "available_endpoints": ENDPOINTS.iter()
+                    .map(|(endpoint, description)| serde_json::json!({ "endpoint": endpoint, "description": description }))
+                    .collect::<Vec<_>>(),
+                "counters": counters,
+                "diffs": total,
+                "recent_diffs": recent,
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la lecture des lectures fantômes: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,236 @@
+// signed_requests.rs
+//
+// Requêtes signées par le wallet pour les mutations à fort enjeu (création
+// d'investissement) : le client signe avec `personal_sign` le message
+//
+//     <MÉTHODE>\n<chemin?requête>\n<sha256 hex du corps>\n<nonce>\n<horodatage Unix>
+//
+// et l'envoie dans les en-têtes `X-Signature`, `X-Signature-Nonce` et
+// `X-Signature-Timestamp`. Le middleware vérifie la signature (wallet
+// principal ou rattaché), refuse les horodatages trop anciens et les nonces
+// déjà utilisés, puis conserve la requête signée comme preuve. Le mode est
+// optionnel, sauf avec `SIGNED_REQUESTS_REQUIRED=true`.
+
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{Path, State},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{TimeZone, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::env;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::brute_force::{self, AuthLockout};
+use crate::eip712;
+use crate::models::UserRole;
+use crate::risk::{self, RiskRules};
+use crate::wallets;
+
+/// Taille maximale d'un corps signé
+const MAX_SIGNED_BODY: usize = 64 * 1024;
+
+/// Requête signée acceptée, transmise au handler pour rattacher la ressource créée
+#[derive(Clone)]
+pub struct SignedRequest {
+    pub id: Uuid,
+}
+
+fn required() -> bool {
+    env::var("SIGNED_REQUESTS_REQUIRED").is_ok_and(|v| v == "true" || v == "1")
+}
+
+fn max_age_secs() -> i64 {
+    env::var("SIGNED_REQUEST_MAX_AGE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(300)
+}
+
+/// Message signé par le client
+fn signed_message(method: &str, path: &str, body_hash: &str, nonce: &str, timestamp: i64) -> String {
+    format!("{}\n{}\n{}\n{}\n{}", method, path, body_hash, nonce, timestamp)
+}
+
+fn reject(status: StatusCode, error: &str) -> Response {
+    (status, Json(serde_json::json!({
+        "error": error,
+        "signature_required": true
+    }))).into_response()
+}
+
+/// Middleware à placer sur les routes concernées (voir main.rs)
+pub async fn verify_signed_request(
+    BearerAuthUser(user): BearerAuthUser,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let header = |name: &str| request.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let (signature, nonce, timestamp) = match (header("X-Signature"), header("X-Signature-Nonce"), header("X-Signature-Timestamp")) {
+        (None, None, None) if !required() => return next.run(request).await,
+        (None, None, None) => return reject(StatusCode::UNAUTHORIZED, "Requête signée requise (en-têtes X-Signature, X-Signature-Nonce, X-Signature-Timestamp)"),
+        (Some(signature), Some(nonce), Some(timestamp)) => (signature, nonce, timestamp),
+        _ => return reject(StatusCode::BAD_REQUEST, "En-têtes X-Signature, X-Signature-Nonce et X-Signature-Timestamp requis ensemble"),
+    };
+
+    if nonce.len() < 8 || nonce.len() > 128 || !nonce.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return reject(StatusCode::BAD_REQUEST, "Nonce invalide (8 à 128 caractères alphanumériques, '-' ou '_')");
+    }
+    let Some(signed_at) = timestamp.parse::<i64>().ok().and_then(|ts| Utc.timestamp_opt(ts, 0).single()) else {
+        return reject(StatusCode::BAD_REQUEST, "Horodatage invalide (secondes Unix attendues)");
+    };
+    if (Utc::now() - signed_at).num_seconds().abs() > max_age_secs() {
+        return reject(StatusCode::UNAUTHORIZED, "Horodatage de la signature expiré ou dans le futur");
+    }
+
+    let pool = match request.extensions().get::<PgPool>() {
+        Some(pool) => pool.clone(),
+        None => return (StatusCode::INTERNAL_SERVER_ERROR, "Pool manquant").into_response(),
+    };
+    let rules = request.extensions().get::<RiskRules>().cloned();
+    let ip = risk::client_ip(request.headers());
+
+    // Wallet ou adresse IP bloqués après des signatures invalides répétées
+    if let Some(lockout) = request.extensions().get::<AuthLockout>() {
+        match lockout.is_locked(&pool, Some(user.wallet.as_str()), ip.as_deref()).await {
+            Ok(true) => return reject(StatusCode::TOO_MANY_REQUESTS, brute_force::LOCKED_MESSAGE),
+            Ok(false) => {},
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Erreur lors de la vérification de la signature: {}", e)
+            }))).into_response(),
+        }
+    }
+
+    let method = request.method().to_string();
+    let path = request.uri()
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let (parts, mut body) = request.into_parts();
+    let mut buffer = Vec::new();
+    while let Some(chunk) = body.data().await {
+        match chunk {
+            Ok(chunk) if buffer.len() + chunk.len() <= MAX_SIGNED_BODY => buffer.extend_from_slice(&chunk),
+            Ok(_) => return reject(StatusCode::PAYLOAD_TOO_LARGE, "Corps de requête trop volumineux pour une requête signée"),
+            Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "error": format!("Erreur lors de la lecture de la requête: {}", e)
+            }))).into_response(),
+        }
+    }
+    let body_hash = hex::encode(Sha256::digest(&buffer));
+
+    let digest = eip712::personal_message_digest(&signed_message(&method, &path, &body_hash, &nonce, signed_at.timestamp()));
+    let signer = match eip712::recover_signer(&digest, &signature) {
+        Ok(address) => eip712::format_address(&address),
+        Err(e) => return reject(StatusCode::BAD_REQUEST, &e),
+    };
+    let owned = match wallets::linked_wallets(&pool, user.id).await {
+        Ok(linked) => signer == user.wallet.as_str() || linked.contains(&signer),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification de la signature: {}", e)
+        }))).into_response(),
+    };
+    if !owned {
+        if let Some(rules) = rules {
+            risk::auth_failed(pool, rules, user.wallet.as_str().to_string(), ip, "bad_signature");
+        }
+        return reject(StatusCode::UNAUTHORIZED, "La signature ne provient pas d'un wallet de ce compte");
+    }
+
+    // Le couple (wallet, nonce) n'est accepté qu'une seule fois
+    let stored = sqlx::query_scalar!(
+        r#"INSERT INTO signed_requests (user_id, wallet, nonce, method, path, body_hash, signature, signed_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           ON CONFLICT (wallet, nonce) DO NOTHING
+           RETURNING id"#,
+        user.id,
+        signer,
+        nonce,
+        method,
+        path,
+        body_hash,
+        signature,
+        signed_at
+    )
+    .fetch_optional(&pool)
+    .await;
+    let id = match stored {
+        Ok(Some(id)) => id,
+        Ok(None) => return reject(StatusCode::CONFLICT, "Nonce déjà utilisé : requête rejouée"),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement de la requête signée: {}", e)
+        }))).into_response(),
+    };
+
+    let mut request = Request::from_parts(parts, Body::from(Bytes::from(buffer)));
+    request.extensions_mut().insert(SignedRequest { id });
+    next.run(request).await
+}
+
+/// Rattache la ressource créée à sa requête signée, dans la transaction de création
+pub async fn attach(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    signed_request_id: Uuid,
+    resource_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE signed_requests SET resource_id = $2 WHERE id = $1",
+        signed_request_id,
+        resource_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+/// Route admin : preuve signée de la création d'un investissement (message
+/// reconstitué, signature et signataire, vérifiables hors de la plateforme)
+pub async fn get_investment_signed_request(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(investment_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut consulter les requêtes signées"
+        }))).into_response();
+    }
+
+    match sqlx::query!(
+        r#"SELECT s.id, s.user_id, s.wallet, s.nonce, s.method, s.path, s.body_hash, s.signature, s.signed_at, s.created_at
+           FROM signed_requests s
+           JOIN investments i ON i.id = s.resource_id
+           WHERE s.resource_id = $1 AND i.tenant_id = $2"#,
+        investment_id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(row)) => (StatusCode::OK, Json(serde_json::json!({
+            "id": row.id,
+            "user_id": row.user_id,
+            "signer": row.wallet,
+            "message": signed_message(&row.method, &row.path, &row.body_hash, &row.nonce, row.signed_at.timestamp()),
+            "signature": row.signature,
+            "nonce": row.nonce,
+            "signed_at": row.signed_at,
+            "created_at": row.created_at
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Aucune requête signée pour cet investissement"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,97 @@
+// slug.rs
+//
+// Génération des slugs d'URL des propriétés ("Appartement Paris 16e" -> "appartement-paris-16e").
+
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+/// Convertit un texte libre en slug ASCII minuscule séparé par des tirets
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars().flat_map(|c| c.to_lowercase()) {
+        let mapped = match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => "a",
+            'æ' => "ae",
+            'ç' => "c",
+            'è' | 'é' | 'ê' | 'ë' => "e",
+            'ì' | 'í' | 'î' | 'ï' => "i",
+            'ñ' => "n",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => "o",
+            'œ' => "oe",
+            'ù' | 'ú' | 'û' | 'ü' => "u",
+            'ý' | 'ÿ' => "y",
+            'ß' => "ss",
+            c if c.is_ascii_alphanumeric() => {
+                slug.push(c);
+                continue;
+            }
+            _ => "-",
+        };
+        if mapped == "-" {
+            if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        } else {
+            slug.push_str(mapped);
+        }
+    }
+
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() {
+        "propriete".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Contrainte d'unicité des slugs de propriété, par plateforme
+const PROPERTY_SLUG_CONSTRAINT: &str = "properties_tenant_slug_key";
+
+/// Essais d'écriture d'un slug avant d'abandonner, si des écritures
+/// concurrentes prennent à chaque fois le slug choisi
+pub const MAX_SLUG_ATTEMPTS: u32 = 5;
+
+/// Génère un slug unique pour une propriété de la plateforme, suffixé (-2, -3, ...)
+/// en cas de collision. `exclude_id` permet de conserver le slug courant lors
+/// d'un renommage. Le slug peut être pris par une écriture concurrente avant
+/// d'être enregistré : l'écriture échoue alors (voir `is_slug_taken`) et
+/// l'appelant recommence avec un nouveau slug.
+pub async fn unique_property_slug<'e, E: PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+    name: &str,
+    exclude_id: Option<Uuid>,
+) -> Result<String, sqlx::Error> {
+    let base = slugify(name);
+
+    let taken: Vec<String> = sqlx::query_scalar!(
+        r#"SELECT slug FROM properties
+           WHERE tenant_id = $1
+           AND (slug = $2 OR slug LIKE $2 || '-%')
+           AND ($3::uuid IS NULL OR id <> $3)"#,
+        tenant_id,
+        base,
+        exclude_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    if !taken.contains(&base) {
+        return Ok(base);
+    }
+
+    let mut suffix = 2;
+    loop {
+        let candidate = format!("{}-{}", base, suffix);
+        if !taken.contains(&candidate) {
+            return Ok(candidate);
+        }
+        suffix += 1;
+    }
+}
+
+/// Vrai si l'écriture a échoué parce qu'une autre propriété de la plateforme a pris le slug
+pub fn is_slug_taken(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Database(db)
+        if db.code().as_deref() == Some("23505") && db.constraint() == Some(PROPERTY_SLUG_CONSTRAINT))
+}
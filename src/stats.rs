@@ -0,0 +1,616 @@
+// stats.rs
+//
+// Statistiques agrégées de la plateforme. Le classement de la page communauté
+// (investisseurs ayant choisi d'y figurer, sous un pseudonyme, et propriétés
+// qui se financent le plus vite) est recalculé au plus une fois par TTL ; les
+// séries temporelles de l'admin sont agrégées à la demande par `date_trunc`,
+// tout comme le tableau de bord d'un manager sur ses propres propriétés. Les
+// statistiques d'une propriété sont lues dans l'agrégat journalier
+// `property_investment_daily`, tenu à jour par trigger à chaque investissement,
+// et mises en cache par période demandée.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use moka::sync::Cache;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use std::env;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::activity;
+use crate::auth::BearerAuthUser;
+use crate::models::{AnalyticsGranularity, AnalyticsMetric, AnalyticsQuery, LeaderboardOptInRequest, InvestmentStatus, PropertyStatsQuery, PropertyStatus, UserRole};
+use crate::money::TokenAmount;
+
+/// Nombre maximal de points d'une série temporelle
+pub const MAX_ANALYTICS_POINTS: i64 = 400;
+
+/// Cache du classement et paramètres de calcul
+#[derive(Clone)]
+pub struct LeaderboardCache {
+    inner: Cache<(), Value>,
+    size: i64,
+    window_days: i32,
+}
+
+impl LeaderboardCache {
+    /// Paramètres : `LEADERBOARD_SIZE` (10 entrées), `LEADERBOARD_WINDOW_DAYS`
+    /// (7 jours pour la vitesse de financement), `LEADERBOARD_CACHE_TTL_SECS` (300s)
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: u64| env::var(name)
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(default);
+
+        Self {
+            inner: Cache::builder()
+                .max_capacity(1)
+                .time_to_live(Duration::from_secs(var("LEADERBOARD_CACHE_TTL_SECS", 300)))
+                .build(),
+            size: var("LEADERBOARD_SIZE", 10).min(100) as i64,
+            window_days: var("LEADERBOARD_WINDOW_DAYS", 7).min(365) as i32,
+        }
+    }
+
+    /// À appeler quand un investisseur rejoint ou quitte le classement
+    pub fn invalidate(&self) {
+        self.inner.invalidate_all();
+    }
+}
+
+/// Clé du cache des statistiques d'une propriété : propriété, granularité et
+/// bornes telles que demandées (`None` : bornes par défaut)
+type PropertyStatsKey = (Uuid, &'static str, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// Cache des statistiques par propriété et par période
+#[derive(Clone)]
+pub struct PropertyStatsCache {
+    inner: Cache<PropertyStatsKey, Value>,
+}
+
+impl PropertyStatsCache {
+    /// Paramètre : `PROPERTY_STATS_CACHE_TTL_SECS` (60s)
+    pub fn from_env() -> Self {
+        let ttl = env::var("PROPERTY_STATS_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(60);
+
+        Self {
+            inner: Cache::builder()
+                .max_capacity(10_000)
+                .time_to_live(Duration::from_secs(ttl))
+                .build(),
+        }
+    }
+
+    /// À appeler quand les investissements sont effacés en masse
+    pub fn invalidate(&self) {
+        self.inner.invalidate_all();
+    }
+}
+
+/// Pseudonyme stable d'un investisseur, qui ne révèle ni son wallet ni son nom
+fn investor_alias(user_id: Uuid) -> String {
+    let digest = Sha256::digest(format!("leaderboard:{}", user_id).as_bytes());
+    format!("Investisseur #{}", &hex::encode(digest)[..8])
+}
+
+async fn compute_leaderboard(pool: &PgPool, cache: &LeaderboardCache) -> Result<Value, sqlx::Error> {
+    let investors = sqlx::query!(
+        r#"SELECT i.user_id, SUM(i.amount_eth) as "invested_eth!", COUNT(DISTINCT i.property_id) as "properties_count!"
+           FROM investments i
+           JOIN users u ON u.id = i.user_id
+           WHERE u.leaderboard_opt_in AND i.status <> 'refunded'
+           GROUP BY i.user_id
+           ORDER BY 2 DESC
+           LIMIT $1"#,
+        cache.size
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let properties = sqlx::query!(
+        r#"SELECT p.id, p.slug, p.name, p.location, p.image_url,
+                  COALESCE(SUM(i.amount_eth) FILTER (WHERE i.created_at >= NOW() - make_interval(days => $1)), 0) as "raised_eth!",
+                  COUNT(i.id) FILTER (WHERE i.created_at >= NOW() - make_interval(days => $1)) as "investments_count!",
+                  COALESCE(ROUND(SUM(i.shares) * p.token_price * 100 / NULLIF(p.total_price, 0), 2), 0) as "funding_percent!"
+           FROM properties p
+           JOIN investments i ON i.property_id = p.id AND i.status <> 'refunded'
+           WHERE p.status = 'validated' AND p.published_at IS NOT NULL
+           GROUP BY p.id
+           HAVING COUNT(i.id) FILTER (WHERE i.created_at >= NOW() - make_interval(days => $1)) > 0
+           ORDER BY 6 DESC
+           LIMIT $2"#,
+        cache.window_days,
+        cache.size
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let days = BigDecimal::from(cache.window_days);
+    Ok(serde_json::json!({
+        "top_investors": investors.into_iter().enumerate().map(|(rank, row)| serde_json::json!({
+            "rank": rank + 1,
+            "alias": investor_alias(row.user_id),
+            "invested_eth": row.invested_eth,
+            "properties_count": row.properties_count
+        })).collect::<Vec<_>>(),
+        "top_properties": properties.into_iter().enumerate().map(|(rank, row)| serde_json::json!({
+            "rank": rank + 1,
+            "id": row.id,
+            "slug": row.slug,
+            "name": row.name,
+            "location": row.location,
+            "image_url": row.image_url,
+            "raised_eth": row.raised_eth,
+            "eth_per_day": (&row.raised_eth / &days).with_scale(6),
+            "investments_count": row.investments_count,
+            "funding_percent": row.funding_percent
+        })).collect::<Vec<_>>(),
+        "window_days": cache.window_days,
+        "generated_at": Utc::now()
+    }))
+}
+
+/// Route publique : classement des investisseurs (volontaires, anonymisés) et
+/// des propriétés par vitesse de financement sur la période récente
+pub async fn get_leaderboard(
+    State(pool): State<PgPool>,
+    Extension(cache): Extension<LeaderboardCache>,
+) -> impl IntoResponse {
+    if let Some(leaderboard) = cache.inner.get(&()) {
+        return (StatusCode::OK, Json(leaderboard)).into_response();
+    }
+
+    match compute_leaderboard(&pool, &cache).await {
+        Ok(leaderboard) => {
+            cache.inner.insert((), leaderboard.clone());
+            (StatusCode::OK, Json(leaderboard)).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du calcul du classement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour apparaître (ou non) dans le classement public des investisseurs
+pub async fn set_leaderboard_opt_in(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(cache): Extension<LeaderboardCache>,
+    Json(payload): Json<LeaderboardOptInRequest>,
+) -> impl IntoResponse {
+    match sqlx::query!(
+        "UPDATE users SET leaderboard_opt_in = $2 WHERE id = $1",
+        user.id,
+        payload.opt_in
+    )
+    .execute(&pool)
+    .await {
+        Ok(_) => {
+            cache.invalidate();
+            activity::record_detached(pool, user.id, None, "leaderboard_opt_in", serde_json::json!({ "opt_in": payload.opt_in }), None);
+            (StatusCode::OK, Json(serde_json::json!({
+                "opt_in": payload.opt_in,
+                "alias": payload.opt_in.then(|| investor_alias(user.id)),
+                "message": if payload.opt_in {
+                    "Vous apparaissez désormais dans le classement"
+                } else {
+                    "Vous n'apparaissez plus dans le classement"
+                }
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Historise un changement de statut d'une propriété, dans la transaction qui
+/// l'effectue (`changed_by` vaut `None` pour un changement automatique)
+pub async fn record_status_change(
+    conn: &mut PgConnection,
+    property_id: Uuid,
+    status: PropertyStatus,
+    changed_by: Option<Uuid>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO property_status_events (property_id, status, changed_by) VALUES ($1, $2, $3)",
+        property_id,
+        status as PropertyStatus,
+        changed_by
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+fn metric_name(metric: AnalyticsMetric) -> &'static str {
+    match metric {
+        AnalyticsMetric::Signups => "signups",
+        AnalyticsMetric::InvestmentsCount => "investments_count",
+        AnalyticsMetric::InvestmentsVolume => "investments_volume",
+        AnalyticsMetric::PropertiesSubmitted => "properties_submitted",
+        AnalyticsMetric::PropertiesValidated => "properties_validated",
+    }
+}
+
+pub fn granularity_unit(granularity: AnalyticsGranularity) -> (&'static str, ChronoDuration) {
+    match granularity {
+        AnalyticsGranularity::Day => ("day", ChronoDuration::days(1)),
+        AnalyticsGranularity::Week => ("week", ChronoDuration::weeks(1)),
+        AnalyticsGranularity::Month => ("month", ChronoDuration::days(30)),
+    }
+}
+
+/// Route admin : série temporelle d'un indicateur, un point par période
+/// (périodes vides comprises), prête pour un graphique
+pub async fn get_analytics(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<AnalyticsQuery>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent consulter les statistiques"
+        }))).into_response();
+    }
+
+    let granularity = query.granularity.unwrap_or(AnalyticsGranularity::Week);
+    let (unit, step) = granularity_unit(granularity);
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - step * 12);
+    if from > to {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "La date de début doit précéder la date de fin"
+        }))).into_response();
+    }
+    if (to - from).num_seconds() / step.num_seconds() >= MAX_ANALYTICS_POINTS {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Période trop longue pour cette granularité (au plus {} points)", MAX_ANALYTICS_POINTS)
+        }))).into_response();
+    }
+
+    let metric = metric_name(query.metric);
+    match sqlx::query!(
+        r#"WITH buckets AS (
+               SELECT generate_series(date_trunc($1, $2::timestamptz), date_trunc($1, $3::timestamptz),
+                                      ('1 ' || $1)::interval) AS bucket
+           ),
+           events AS (
+               SELECT created_at AS at, 1::numeric AS value FROM users WHERE $4 = 'signups'
+               UNION ALL
+               SELECT created_at, 1 FROM investments WHERE $4 = 'investments_count' AND status <> 'refunded'
+               UNION ALL
+               SELECT created_at, amount_eth FROM investments WHERE $4 = 'investments_volume' AND status <> 'refunded'
+               UNION ALL
+               SELECT created_at, 1 FROM property_status_events WHERE $4 = 'properties_submitted' AND status = 'pending'
+               UNION ALL
+               SELECT created_at, 1 FROM property_status_events WHERE $4 = 'properties_validated' AND status = 'validated'
+           )
+           SELECT b.bucket as "bucket!", COALESCE(SUM(e.value), 0) as "value!"
+           FROM buckets b
+           LEFT JOIN events e ON date_trunc($1, e.at) = b.bucket AND e.at BETWEEN $2 AND $3
+           GROUP BY b.bucket
+           ORDER BY b.bucket"#,
+        unit,
+        from,
+        to,
+        metric
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(points) => {
+            let total: BigDecimal = points.iter().map(|point| &point.value).sum();
+            (StatusCode::OK, Json(serde_json::json!({
+                "metric": query.metric,
+                "granularity": granularity,
+                "from": from,
+                "to": to,
+                "labels": points.iter().map(|point| point.bucket).collect::<Vec<_>>(),
+                "values": points.iter().map(|point| point.value.to_f64().unwrap_or_default()).collect::<Vec<_>>(),
+                "total": total.to_f64().unwrap_or_default()
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du calcul des statistiques: {}", e)
+        }))).into_response(),
+    }
+}
+
+async fn compute_property_stats(
+    pool: &PgPool,
+    property_id: Uuid,
+    granularity: AnalyticsGranularity,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Value, sqlx::Error> {
+    let (unit, _) = granularity_unit(granularity);
+    let points = sqlx::query!(
+        r#"WITH buckets AS (
+               SELECT generate_series(date_trunc($1, $2::timestamptz), date_trunc($1, $3::timestamptz),
+                                      ('1 ' || $1)::interval) AS bucket
+           )
+           SELECT b.bucket as "bucket!",
+                  COALESCE(SUM(d.investments_count), 0)::bigint as "investments_count!",
+                  COALESCE(SUM(d.volume_eth), 0) as "volume_eth!"
+           FROM buckets b
+           LEFT JOIN property_investment_daily d ON d.property_id = $4
+               AND date_trunc($1, d.day) = b.bucket
+               AND d.day BETWEEN date_trunc('day', $2::timestamptz) AND $3
+           GROUP BY b.bucket
+           ORDER BY b.bucket"#,
+        unit,
+        from,
+        to,
+        property_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let property = sqlx::query!(
+        r#"SELECT p.total_shares as "total_shares!", p.shares_sold,
+                  COALESCE(ROUND(p.shares_sold * p.token_price * 100 / NULLIF(p.total_price, 0), 2), 0) as "funding_percent!",
+                  (SELECT COUNT(DISTINCT i.user_id) FROM investments i
+                   WHERE i.property_id = p.id AND i.status <> 'refunded'
+                   AND i.created_at BETWEEN date_trunc('day', $2::timestamptz) AND $3) as "unique_investors!"
+           FROM properties p
+           WHERE p.id = $1"#,
+        property_id,
+        from,
+        to
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let zero = BigDecimal::from(0);
+    let average = |volume: &BigDecimal, count: i64| if count > 0 {
+        (volume / BigDecimal::from(count)).with_scale(8)
+    } else {
+        zero.clone()
+    };
+    let count_total: i64 = points.iter().map(|point| point.investments_count).sum();
+    let volume_total: BigDecimal = points.iter().map(|point| &point.volume_eth).sum();
+
+    Ok(serde_json::json!({
+        "property_id": property_id,
+        "granularity": granularity,
+        "from": from,
+        "to": to,
+        "labels": points.iter().map(|point| point.bucket).collect::<Vec<_>>(),
+        "investments_count": points.iter().map(|point| point.investments_count).collect::<Vec<_>>(),
+        "volume_eth": points.iter().map(|point| point.volume_eth.to_f64().unwrap_or_default()).collect::<Vec<_>>(),
+        "average_ticket_eth": points.iter()
+            .map(|point| average(&point.volume_eth, point.investments_count).to_f64().unwrap_or_default())
+            .collect::<Vec<_>>(),
+        "totals": {
+            "investments_count": count_total,
+            "volume_eth": volume_total,
+            "unique_investors": property.unique_investors,
+            "average_ticket_eth": average(&volume_total, count_total)
+        },
+        "funding": {
+            "shares_sold": property.shares_sold,
+            "total_shares": property.total_shares,
+            "funding_percent": property.funding_percent
+        },
+        "computed_at": Utc::now()
+    }))
+}
+
+/// Route : statistiques d'investissement d'une propriété par période (nombre,
+/// volume, ticket moyen, investisseurs uniques), pour l'onglet analytique de
+/// la page propriété ; un brouillon n'est visible que par son créateur
+pub async fn get_property_stats(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(cache): Extension<PropertyStatsCache>,
+    Path(property_id): Path<Uuid>,
+    Query(query): Query<PropertyStatsQuery>,
+) -> impl IntoResponse {
+    match sqlx::query!(
+        r#"SELECT status as "status: PropertyStatus", created_by FROM properties WHERE id = $1 AND tenant_id = $2"#,
+        property_id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(property)) if !matches!(property.status, PropertyStatus::Draft) || property.created_by == user.id => {},
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+
+    let granularity = query.granularity.unwrap_or(AnalyticsGranularity::Day);
+    let (unit, step) = granularity_unit(granularity);
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - step * 30);
+    if from > to {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "La date de début doit précéder la date de fin"
+        }))).into_response();
+    }
+    if (to - from).num_seconds() / step.num_seconds() >= MAX_ANALYTICS_POINTS {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Période trop longue pour cette granularité (au plus {} points)", MAX_ANALYTICS_POINTS)
+        }))).into_response();
+    }
+
+    let key = (property_id, unit, query.from, query.to);
+    if let Some(stats) = cache.inner.get(&key) {
+        return (StatusCode::OK, Json(stats)).into_response();
+    }
+
+    match compute_property_stats(&pool, property_id, granularity, from, to).await {
+        Ok(stats) => {
+            cache.inner.insert(key, stats.clone());
+            (StatusCode::OK, Json(stats)).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du calcul des statistiques: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Nombre d'investissements récents du tableau de bord manager
+const RECENT_INVESTMENTS_LIMIT: i64 = 10;
+
+/// Route manager : tableau de bord de ses propriétés (avancement du financement,
+/// investisseurs, investissements récents, éléments en attente et distributions
+/// dues estimées d'après le rendement annuel annoncé)
+pub async fn get_manager_stats(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin | UserRole::Manager) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les managers peuvent consulter ce tableau de bord"
+        }))).into_response();
+    }
+
+    let result = async {
+        let properties = sqlx::query!(
+            r#"SELECT p.id, p.slug, p.name, p.status as "status: PropertyStatus", p.total_price, p.annual_yield,
+                      COALESCE(SUM(i.amount_eth), 0) as "raised_eth!",
+                      COALESCE(SUM(i.shares), 0) as "shares_sold!",
+                      COUNT(DISTINCT i.user_id) as "investors_count!",
+                      COALESCE(ROUND(SUM(i.shares) * p.token_price * 100 / NULLIF(p.total_price, 0), 2), 0) as "funding_percent!",
+                      COALESCE(SUM(i.amount_eth * COALESCE(t.rate, 0) / 100), 0) as "withholding_base_eth!"
+               FROM properties p
+               LEFT JOIN investments i ON i.property_id = p.id AND i.status <> 'refunded'
+               LEFT JOIN users u ON u.id = i.user_id
+               LEFT JOIN tax_rules t ON t.tenant_id = p.tenant_id AND t.country = u.country AND t.applies_to = 'distributions'
+               WHERE p.created_by = $1
+               GROUP BY p.id
+               ORDER BY p.created_at DESC"#,
+            user.id
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        let recent_investments = sqlx::query!(
+            r#"SELECT i.id, i.property_id, p.name as property_name, i.amount_eth, i.shares,
+                      i.status as "status: InvestmentStatus", i.created_at
+               FROM investments i
+               JOIN properties p ON p.id = i.property_id
+               WHERE p.created_by = $1
+               ORDER BY i.created_at DESC
+               LIMIT $2"#,
+            user.id,
+            RECENT_INVESTMENTS_LIMIT
+        )
+        .fetch_all(&pool)
+        .await?;
+
+        let pending = sqlx::query!(
+            r#"SELECT
+               (SELECT COUNT(DISTINCT i.user_id) FROM investments i JOIN properties p ON p.id = i.property_id
+                WHERE p.created_by = $1 AND i.status <> 'refunded') as "investors_count!",
+               (SELECT COUNT(*) FROM investment_refunds r JOIN investments i ON i.id = r.investment_id
+                JOIN properties p ON p.id = i.property_id
+                WHERE p.created_by = $1 AND r.status IN ('requested', 'approved')) as "open_refunds!",
+               (SELECT COUNT(*) FROM investments i JOIN properties p ON p.id = i.property_id
+                WHERE p.created_by = $1 AND i.status = 'pending_settlement') as "pending_settlement!""#,
+            user.id
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        Ok::<_, sqlx::Error>((properties, recent_investments, pending))
+    }.await;
+
+    let (properties, recent_investments, pending) = match result {
+        Ok(stats) => stats,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du calcul des statistiques: {}", e)
+        }))).into_response(),
+    };
+
+    let hundred = BigDecimal::from(100);
+    let twelve = BigDecimal::from(12);
+    let distributions: Vec<_> = properties.iter()
+        .filter(|p| matches!(p.status, PropertyStatus::Validated) && p.raised_eth > BigDecimal::from(0))
+        .map(|p| {
+            let annual = &p.raised_eth * &p.annual_yield / &hundred;
+            let withheld = &p.withholding_base_eth * &p.annual_yield / &hundred;
+            serde_json::json!({
+                "property_id": p.id,
+                "name": p.name,
+                "raised_eth": p.raised_eth,
+                "annual_yield": p.annual_yield,
+                "monthly_eth": TokenAmount::estimate(&annual / &twelve),
+                "annual_eth": TokenAmount::estimate(annual.clone()),
+                "withheld_annual_eth": TokenAmount::estimate(withheld.clone()),
+                "net_annual_eth": TokenAmount::estimate(&annual - &withheld)
+            })
+        })
+        .collect();
+    let annual_total: BigDecimal = properties.iter()
+        .filter(|p| matches!(p.status, PropertyStatus::Validated))
+        .map(|p| &p.raised_eth * &p.annual_yield / &hundred)
+        .sum();
+    // Retenue à la source selon le pays de chaque investisseur (voir taxes.rs)
+    let withheld_total: BigDecimal = properties.iter()
+        .filter(|p| matches!(p.status, PropertyStatus::Validated))
+        .map(|p| &p.withholding_base_eth * &p.annual_yield / &hundred)
+        .sum();
+    let raised_total: BigDecimal = properties.iter().map(|p| &p.raised_eth).sum();
+    let pending_review: Vec<_> = properties.iter()
+        .filter(|p| matches!(p.status, PropertyStatus::Pending | PropertyStatus::Rejected))
+        .map(|p| serde_json::json!({ "property_id": p.id, "name": p.name, "status": p.status }))
+        .collect();
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "totals": {
+            "properties_count": properties.len(),
+            "raised_eth": raised_total,
+            "investors_count": pending.investors_count
+        },
+        "properties": properties.iter().map(|p| serde_json::json!({
+            "id": p.id,
+            "slug": p.slug,
+            "name": p.name,
+            "status": p.status,
+            "total_price": p.total_price,
+            "raised_eth": p.raised_eth,
+            "shares_sold": p.shares_sold,
+            "funding_percent": p.funding_percent,
+            "investors_count": p.investors_count
+        })).collect::<Vec<_>>(),
+        "recent_investments": recent_investments.iter().map(|i| serde_json::json!({
+            "id": i.id,
+            "property_id": i.property_id,
+            "property_name": i.property_name,
+            "amount_eth": i.amount_eth,
+            "shares": i.shares,
+            "status": i.status,
+            "created_at": i.created_at
+        })).collect::<Vec<_>>(),
+        "pending": {
+            "properties": pending_review,
+            "open_refunds": pending.open_refunds,
+            "pending_settlement": pending.pending_settlement
+        },
+        "distributions": {
+            "properties": distributions,
+            "monthly_eth": TokenAmount::estimate(&annual_total / &twelve),
+            "annual_eth": TokenAmount::estimate(annual_total.clone()),
+            "withheld_annual_eth": TokenAmount::estimate(withheld_total.clone()),
+            "net_annual_eth": TokenAmount::estimate(&annual_total - &withheld_total)
+        }
+    }))).into_response()
+}
@@ -0,0 +1,168 @@
+// storage.rs
+//
+// Stockage privé des documents (Supabase Storage via son API compatible S3,
+// ou tout bucket S3) : les fichiers ne sont jamais publics, l'accès passe par
+// des URL présignées (AWS Signature V4) à durée de vie courte. Les images de la
+// galerie peuvent être déposées dans un second bucket, lui en lecture publique.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::path::Path;
+use tokio_util::io::ReaderStream;
+use url::Url;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Configuration du bucket privé, lue depuis l'environnement
+#[derive(Clone)]
+pub struct StorageConfig {
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    pub url_ttl_secs: u64,
+    media_bucket: Option<String>,
+    media_public_url: Option<String>,
+    client: reqwest::Client,
+}
+
+impl StorageConfig {
+    /// Renvoie `None` si le stockage n'est pas configuré
+    pub fn from_env() -> Option<Self> {
+        let endpoint = Url::parse(&env::var("STORAGE_S3_ENDPOINT").ok()?).ok()?;
+        let url_ttl_secs = env::var("DOCUMENT_URL_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            // Limite imposée par SigV4 : 7 jours
+            .map(|v| v.clamp(1, 604_800))
+            .unwrap_or(300);
+
+        Some(Self {
+            endpoint,
+            bucket: env::var("STORAGE_BUCKET").ok()?,
+            region: env::var("STORAGE_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            access_key_id: env::var("STORAGE_ACCESS_KEY_ID").ok()?,
+            secret_access_key: env::var("STORAGE_SECRET_ACCESS_KEY").ok()?,
+            url_ttl_secs,
+            media_bucket: env::var("STORAGE_MEDIA_BUCKET").ok(),
+            media_public_url: env::var("STORAGE_MEDIA_PUBLIC_URL")
+                .ok()
+                .map(|url| url.trim_end_matches('/').to_string()),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Génère une URL GET présignée vers le bucket privé des documents
+    pub fn presign_get(&self, key: &str, now: DateTime<Utc>) -> String {
+        self.presign("GET", &self.bucket, key, self.url_ttl_secs, now)
+    }
+
+    /// Dépose un fichier dans le bucket privé des documents
+    pub async fn put_document(&self, key: &str, file: &Path, content_type: &str) -> Result<(), String> {
+        self.put_object(&self.bucket, key, file, content_type).await
+    }
+
+    /// Dépose une image dans le bucket public de la galerie et renvoie son URL publique.
+    /// `None` si aucun bucket média n'est configuré.
+    pub async fn put_media(&self, key: &str, file: &Path, content_type: &str) -> Option<Result<String, String>> {
+        let bucket = self.media_bucket.as_deref()?;
+        let public_url = self.media_public_url.as_deref()?;
+        Some(
+            self.put_object(bucket, key, file, content_type)
+                .await
+                .map(|_| format!("{}/{}", public_url, uri_encode(key, true))),
+        )
+    }
+
+    async fn put_object(&self, bucket: &str, key: &str, file: &Path, content_type: &str) -> Result<(), String> {
+        let file = tokio::fs::File::open(file).await.map_err(|e| e.to_string())?;
+        let length = file.metadata().await.map_err(|e| e.to_string())?.len();
+        let url = self.presign("PUT", bucket, key, 300, Utc::now());
+
+        // Envoi en flux depuis le fichier temporaire, sans tout charger en mémoire
+        let response = self.client
+            .put(url)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .header(reqwest::header::CONTENT_LENGTH, length)
+            .body(reqwest::Body::wrap_stream(ReaderStream::new(file)))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("le stockage a répondu {}", response.status()))
+        }
+    }
+
+    /// URL présignée (adressage par chemin : `endpoint/bucket/key`)
+    fn presign(&self, method: &str, bucket: &str, key: &str, expires_secs: u64, now: DateTime<Utc>) -> String {
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap_or_default(), port),
+            None => self.endpoint.host_str().unwrap_or_default().to_string(),
+        };
+        let path = format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            uri_encode(bucket, false),
+            uri_encode(key.trim_start_matches('/'), true)
+        );
+
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+
+        // Paramètres déjà triés par nom, comme l'exige la requête canonique
+        let query = format!(
+            "X-Amz-Algorithm=AWS4-HMAC-SHA256&X-Amz-Credential={}&X-Amz-Date={}&X-Amz-Expires={}&X-Amz-SignedHeaders=host",
+            uri_encode(&format!("{}/{}", self.access_key_id, scope), false),
+            amz_date,
+            expires_secs
+        );
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+            method, path, query, host
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key_date = hmac_sha256(format!("AWS4{}", self.secret_access_key).as_bytes(), date.as_bytes());
+        let key_region = hmac_sha256(&key_date, self.region.as_bytes());
+        let key_service = hmac_sha256(&key_region, b"s3");
+        let key_signing = hmac_sha256(&key_service, b"aws4_request");
+        let signature = hex::encode(hmac_sha256(&key_signing, string_to_sign.as_bytes()));
+
+        format!(
+            "{}://{}{}?{}&X-Amz-Signature={}",
+            self.endpoint.scheme(), host, path, query, signature
+        )
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepte toute taille de clé");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Encodage URI au sens SigV4 (seuls les caractères non réservés restent tels quels)
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,628 @@
+// stripe.rs
+//
+// Paiement des investissements en euros via Stripe. L'investisseur obtient un
+// PaymentIntent pour un montant en euros (converti en parts au taux configuré),
+// le webhook Stripe confirme le paiement et crée un investissement en attente de
+// règlement, puis un admin enregistre la transaction on-chain qui transfère les
+// parts.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::env;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::chain::parse_tx_hash;
+use crate::compliance;
+use crate::accreditation;
+use crate::envelope;
+use crate::legal;
+use crate::exposure;
+use crate::funding;
+use crate::money::{Money, TokenAmount, Wei};
+use crate::notifications::notify_admins;
+use crate::outbound::{Outbound, OutboundClient, OutboundError};
+use crate::outbox::{self, DomainEvent};
+use crate::investment_revisions;
+use crate::flags::FeatureFlags;
+use crate::risk::{self, RiskRules};
+use crate::models::{
+    CreateFiatIntentRequest, FiatPayment, FiatPaymentStatus, Investment, InvestmentStatus, PropertyStatus,
+    SettleInvestmentRequest, UserRole,
+};
+
+/// Écart toléré entre l'horodatage signé d'un webhook et l'heure du serveur
+const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+/// Montant minimal accepté par Stripe pour un paiement en euros
+const MIN_AMOUNT_CENTS: i64 = 50;
+
+/// Clés Stripe et taux de conversion, lus depuis l'environnement
+#[derive(Clone)]
+pub struct StripeConfig {
+    secret_key: String,
+    webhook_secret: String,
+    eur_per_eth: BigDecimal,
+    api_base: String,
+    client: OutboundClient,
+}
+
+impl StripeConfig {
+    /// Renvoie `None` si une clé ou le taux EUR/ETH manque
+    pub fn from_env(outbound: &Outbound) -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let eur_per_eth = BigDecimal::from_str(&var("FIAT_EUR_PER_ETH")?).ok()
+            .filter(|rate| rate > &BigDecimal::zero())?;
+
+        Some(Self {
+            secret_key: var("STRIPE_SECRET_KEY")?,
+            webhook_secret: var("STRIPE_WEBHOOK_SECRET")?,
+            eur_per_eth,
+            api_base: var("STRIPE_API_BASE")
+                .map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| "https://api.stripe.com".to_string()),
+            client: outbound.client("stripe")?,
+        })
+    }
+
+    /// Crée le PaymentIntent et renvoie `(id, client_secret)`. La clé
+    /// d'idempotence évite un double PaymentIntent si l'appel est rejoué.
+    async fn create_payment_intent(&self, payment: &FiatPayment, amount_cents: i64) -> Result<(String, String), OutboundError> {
+        let request = self.client
+            .post(format!("{}/v1/payment_intents", self.api_base))
+            .bearer_auth(&self.secret_key)
+            .header("Idempotency-Key", payment.id.to_string())
+            .form(&[
+                ("amount", amount_cents.to_string()),
+                ("currency", "eur".to_string()),
+                ("automatic_payment_methods[enabled]", "true".to_string()),
+                ("metadata[fiat_payment_id]", payment.id.to_string()),
+                ("metadata[user_id]", payment.user_id.to_string()),
+                ("metadata[property_id]", payment.property_id.to_string()),
+                ("metadata[shares]", payment.shares.to_string()),
+            ]);
+        let response = self.client.send(request).await?;
+
+        let status = response.status();
+        let body: Value = response.json().await.map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            return Err(body["error"]["message"].as_str().unwrap_or("réponse invalide").to_string().into());
+        }
+        match (body["id"].as_str(), body["client_secret"].as_str()) {
+            (Some(id), Some(client_secret)) => Ok((id.to_string(), client_secret.to_string())),
+            _ => Err("PaymentIntent sans identifiant ni client_secret".to_string().into()),
+        }
+    }
+}
+
+/// Repli proposé quand Stripe est indisponible : l'investissement en crypto
+fn crypto_fallback() -> Value {
+    serde_json::json!({
+        "payment_method": "crypto",
+        "message": "L'investissement en crypto reste disponible (POST /api/investments)"
+    })
+}
+
+/// Vérifie l'en-tête `Stripe-Signature` (`t=…,v1=…`) : HMAC-SHA256 de
+/// `"{t}.{corps}"` avec le secret du endpoint, horodatage dans la tolérance
+fn verify_signature(secret: &str, body: &[u8], header: &str, now: i64) -> bool {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+            Some(("v1", signature)) => signatures.extend(hex::decode(signature).ok()),
+            _ => {},
+        }
+    }
+    let timestamp = match timestamp {
+        Some(timestamp) if (now - timestamp).abs() <= SIGNATURE_TOLERANCE_SECS => timestamp,
+        _ => return false,
+    };
+
+    signatures.iter().any(|signature| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepte toute taille de clé");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac.verify_slice(signature).is_ok()
+    })
+}
+
+/// Route pour créer un paiement en euros (authentification requise)
+pub async fn create_fiat_intent(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(stripe): Extension<Option<StripeConfig>>,
+    Extension(flags): Extension<FeatureFlags>,
+    Json(payload): Json<CreateFiatIntentRequest>,
+) -> impl IntoResponse {
+    if !flags.enabled_for("fiat_payments", user.role) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Le paiement en euros n'est pas disponible"
+        }))).into_response();
+    }
+
+    let stripe = match stripe {
+        Some(stripe) => stripe,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Paiement en euros non configuré (STRIPE_SECRET_KEY, STRIPE_WEBHOOK_SECRET, FIAT_EUR_PER_ETH)"
+        }))).into_response(),
+    };
+
+    // Stripe indisponible : aucun paiement enregistré pour un appel voué à l'échec
+    if let Err(e) = stripe.client.check() {
+        return e.response("Paiement en euros momentanément indisponible", Some(crypto_fallback()));
+    }
+
+    if let Err(response) = compliance::ensure_not_flagged(&pool, user.id).await {
+        return response;
+    }
+    if let Err(response) = legal::ensure_terms_accepted(&pool, user.id).await {
+        return response;
+    }
+
+    let property = match sqlx::query!(
+        r#"SELECT token_price, status as "status: PropertyStatus", published_at, requires_accreditation,
+                  total_shares - shares_sold as "available_shares!"
+           FROM properties WHERE id = $1 AND tenant_id = $2"#,
+        payload.property_id,
+        user.tenant_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(prop)) if !matches!(prop.status, PropertyStatus::Draft) => prop,
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    // Mêmes conditions que pour l'enregistrement d'un investissement
+    if !matches!(property.status, PropertyStatus::Validated) || property.published_at.is_none() {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Impossible d'investir dans une propriété non validée ou non publiée"
+        }))).into_response();
+    }
+
+    // Offre réservée aux investisseurs accrédités
+    if property.requires_accreditation {
+        if let Err(response) = accreditation::ensure_accredited(&pool, user.id).await {
+            return response;
+        }
+    }
+
+    // Nombre entier de parts couvert par le montant, facturé au centime supérieur
+    let price_eur = &property.token_price * &stripe.eur_per_eth;
+    let shares = match (price_eur > BigDecimal::zero())
+        .then(|| (payload.amount_eur.as_decimal() / &price_eur).with_scale(0).to_i32())
+        .flatten()
+    {
+        Some(shares) if shares > 0 => shares,
+        _ => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le montant ne couvre pas une part",
+            "price_per_share_eur": Money::charge(&price_eur)
+        }))).into_response(),
+    };
+    // Parts disponibles vérifiées avant l'encaissement (voir payment_succeeded si elles
+    // sont épuisées entre-temps)
+    if property.available_shares < shares as i64 {
+        return funding::sold_out_response(Some(property.available_shares));
+    }
+    // Limites d'exposition vérifiées avant l'encaissement : un paiement reçu n'est jamais refusé
+    let amount_eth = TokenAmount::for_shares(&property.token_price, shares as i64);
+    let amount_wei = amount_eth.wei();
+    if let Err(response) = exposure::ensure_within_limits(&pool, user.id, payload.property_id, &amount_eth).await {
+        return response;
+    }
+    let amount_eur = Money::charge(&(&price_eur * BigDecimal::from(shares)));
+    let amount_cents = match amount_eur.to_cents() {
+        Some(cents) if cents >= MIN_AMOUNT_CENTS => cents,
+        _ => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Montant hors des limites acceptées par le prestataire de paiement"
+        }))).into_response(),
+    };
+
+    let payment = match sqlx::query_as!(
+        FiatPayment,
+        r#"INSERT INTO fiat_payments (user_id, property_id, shares, amount_eur, amount_eth, amount_wei, eur_per_eth)
+           VALUES ($1, $2, $3, $4, $5, $6, $7)
+           RETURNING id, user_id, property_id, shares, amount_eur as "amount_eur: Money", amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei", eur_per_eth,
+                     stripe_payment_intent_id, status as "status: FiatPaymentStatus", investment_id,
+                     created_at, updated_at"#,
+        user.id,
+        payload.property_id,
+        shares,
+        amount_eur.as_decimal(),
+        amount_eth.as_decimal(),
+        amount_wei.as_decimal(),
+        stripe.eur_per_eth
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(payment) => payment,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    };
+
+    let (payment_intent_id, client_secret) = match stripe.create_payment_intent(&payment, amount_cents).await {
+        Ok(created) => created,
+        Err(e) => {
+            let _ = sqlx::query!(
+                "UPDATE fiat_payments SET status = 'failed', updated_at = NOW() WHERE id = $1",
+                payment.id
+            )
+            .execute(&pool)
+            .await;
+            return e.response("Erreur lors de la création du paiement Stripe", Some(crypto_fallback()));
+        },
+    };
+
+    match sqlx::query_as!(
+        FiatPayment,
+        r#"UPDATE fiat_payments SET stripe_payment_intent_id = $2, updated_at = NOW()
+           WHERE id = $1
+           RETURNING id, user_id, property_id, shares, amount_eur as "amount_eur: Money", amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei", eur_per_eth,
+                     stripe_payment_intent_id, status as "status: FiatPaymentStatus", investment_id,
+                     created_at, updated_at"#,
+        payment.id,
+        payment_intent_id
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(payment) => (StatusCode::CREATED, Json(serde_json::json!({
+            "payment": payment,
+            "client_secret": client_secret,
+            "message": "Paiement créé : confirmez-le côté client avec client_secret"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Paiement confirmé : crée l'investissement en attente de règlement.
+/// Idempotent (Stripe peut livrer plusieurs fois le même événement).
+async fn payment_succeeded(pool: &PgPool, rules: &RiskRules, payment_intent_id: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let payment = sqlx::query!(
+        r#"SELECT id, user_id, property_id, shares, amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei",
+                  amount_eur as "amount_eur: Money", investment_id,
+                  status as "status: FiatPaymentStatus"
+           FROM fiat_payments WHERE stripe_payment_intent_id = $1 FOR UPDATE"#,
+        payment_intent_id
+    )
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let payment = match payment {
+        Some(payment) if payment.status != FiatPaymentStatus::Oversold => payment,
+        _ => return Ok(None),
+    };
+    if let Some(investment_id) = payment.investment_id {
+        return Ok(Some(investment_id));
+    }
+
+    let investment_id = match sqlx::query_scalar!(
+        r#"INSERT INTO investments (user_id, property_id, amount_eth, amount_wei, shares, status, tenant_id)
+           VALUES ($1, $2, $3, $4, $5, 'pending_settlement', (SELECT tenant_id FROM properties WHERE id = $2))
+           RETURNING id"#,
+        payment.user_id,
+        payment.property_id,
+        payment.amount_eth.as_decimal(),
+        payment.amount_wei.as_decimal(),
+        payment.shares
+    )
+    .fetch_one(&mut tx)
+    .await {
+        Ok(investment_id) => investment_id,
+        // Parts épuisées depuis la création du paiement : il est conservé (jamais
+        // refusé après encaissement) et signalé aux admins pour remboursement
+        Err(e) if funding::is_oversold(&e) => {
+            tx.rollback().await?;
+            let mut tx = pool.begin().await?;
+            sqlx::query!(
+                "UPDATE fiat_payments SET status = 'oversold', updated_at = NOW() WHERE id = $1",
+                payment.id
+            )
+            .execute(&mut tx)
+            .await?;
+            notify_admins(&mut tx, "fiat_payment_oversold", "Paiement en euros reçu sans parts disponibles : remboursement à effectuer", serde_json::json!({
+                "fiat_payment_id": payment.id,
+                "payment_intent_id": payment_intent_id,
+                "user_id": payment.user_id,
+                "property_id": payment.property_id,
+                "shares": payment.shares,
+                "amount_eur": payment.amount_eur
+            }))
+            .await?;
+            tx.commit().await?;
+            return Ok(None);
+        },
+        Err(e) => return Err(e),
+    };
+    outbox::record(&mut tx, &DomainEvent::InvestmentCreated {
+        investment_id,
+        user_id: payment.user_id,
+        property_id: payment.property_id,
+        amount_eth: payment.amount_eth.clone(),
+        shares: payment.shares,
+    }).await?;
+    sqlx::query!(
+        "UPDATE fiat_payments SET status = 'succeeded', investment_id = $2, updated_at = NOW() WHERE id = $1",
+        payment.id,
+        investment_id
+    )
+    .execute(&mut tx)
+    .await?;
+    tx.commit().await?;
+    risk::investment_created(pool.clone(), rules.clone(), investment_id);
+    Ok(Some(investment_id))
+}
+
+/// Route publique : événements Stripe, authentifiés par `Stripe-Signature`
+pub async fn stripe_webhook(
+    State(pool): State<PgPool>,
+    Extension(stripe): Extension<Option<StripeConfig>>,
+    Extension(rules): Extension<RiskRules>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let stripe = match stripe {
+        Some(stripe) => stripe,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Paiement en euros non configuré"
+        }))).into_response(),
+    };
+
+    let signature = headers.get("stripe-signature").and_then(|v| v.to_str().ok()).unwrap_or_default();
+    if !verify_signature(&stripe.webhook_secret, &body, signature, chrono::Utc::now().timestamp()) {
+        return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({
+            "error": "Signature du webhook invalide"
+        }))).into_response();
+    }
+
+    let event: Value = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Corps JSON invalide: {}", e)
+        }))).into_response(),
+    };
+    let event_type = event["type"].as_str().unwrap_or_default();
+    let payment_intent_id = event["data"]["object"]["id"].as_str().unwrap_or_default();
+
+    let result = match event_type {
+        "payment_intent.succeeded" => payment_succeeded(&pool, &rules, payment_intent_id).await
+            .map(|investment_id| serde_json::json!({ "investment_id": investment_id })),
+        "payment_intent.payment_failed" | "payment_intent.canceled" => {
+            let status = if event_type == "payment_intent.canceled" {
+                FiatPaymentStatus::Canceled
+            } else {
+                FiatPaymentStatus::Failed
+            };
+            // Un paiement déjà confirmé n'est jamais rétrogradé
+            sqlx::query!(
+                r#"UPDATE fiat_payments SET status = $2, updated_at = NOW()
+                   WHERE stripe_payment_intent_id = $1 AND status = 'requires_payment'"#,
+                payment_intent_id,
+                status as FiatPaymentStatus
+            )
+            .execute(&pool)
+            .await
+            .map(|result| serde_json::json!({ "updated": result.rows_affected() }))
+        },
+        // Les autres événements sont acquittés sans traitement
+        _ => Ok(serde_json::json!({ "ignored": true })),
+    };
+
+    match result {
+        Ok(outcome) => (StatusCode::OK, Json(serde_json::json!({
+            "event": event_type,
+            "result": outcome
+        }))).into_response(),
+        // Stripe relivre l'événement tant que la réponse n'est pas un succès
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du traitement du paiement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : investissements de la plateforme payés en euros en attente de
+/// règlement on-chain
+pub async fn get_pending_settlements(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent consulter les règlements en attente"
+        }))).into_response();
+    }
+
+    match sqlx::query!(
+        r#"SELECT i.id, i.user_id, u.wallet, i.property_id, p.onchain_id, i.shares, i.amount_eth,
+           f.id as "fiat_payment_id?", f.amount_eur as "amount_eur?", f.stripe_payment_intent_id, i.created_at
+           FROM investments i
+           JOIN users u ON u.id = i.user_id
+           JOIN properties p ON p.id = i.property_id
+           LEFT JOIN fiat_payments f ON f.investment_id = i.id
+           WHERE i.status = 'pending_settlement' AND i.tenant_id = $1
+           ORDER BY i.created_at"#,
+        user.tenant_id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => {
+            let investments: Vec<_> = rows.into_iter().map(|row| serde_json::json!({
+                "id": row.id,
+                "user_id": row.user_id,
+                "wallet": row.wallet,
+                "property_id": row.property_id,
+                "onchain_id": row.onchain_id,
+                "shares": row.shares,
+                "amount_eth": row.amount_eth,
+                "fiat_payment_id": row.fiat_payment_id,
+                "amount_eur": row.amount_eur,
+                "stripe_payment_intent_id": row.stripe_payment_intent_id,
+                "created_at": row.created_at
+            })).collect();
+            envelope::list("investments", &investments).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : enregistre la transaction on-chain qui a transféré les parts
+pub async fn settle_investment(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(investment_id): Path<Uuid>,
+    Json(payload): Json<SettleInvestmentRequest>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent régler un investissement"
+        }))).into_response();
+    }
+
+    let tx_hash = match parse_tx_hash(&payload.tx_hash) {
+        Some(tx_hash) => tx_hash,
+        None => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Hash de transaction invalide (0x + 64 caractères hexadécimaux)"
+        }))).into_response(),
+    };
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let investment = sqlx::query_as!(
+            Investment,
+            r#"UPDATE investments SET status = 'settled', tx_hash = $2, settled_at = NOW()
+               WHERE id = $1 AND status = 'pending_settlement' AND tenant_id = $3
+               RETURNING id, user_id, property_id, amount_eth as "amount_eth: TokenAmount", amount_wei as "amount_wei: Wei", shares, tx_hash,
+                         status as "status: InvestmentStatus", settled_at, created_at"#,
+            investment_id,
+            &tx_hash,
+            user.tenant_id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        if investment.is_some() {
+            investment_revisions::record(&mut tx, investment_id, Some(user.id), Some("Règlement on-chain"), serde_json::json!({
+                "status": { "from": "pending_settlement", "to": "settled" },
+                "tx_hash": { "from": null, "to": tx_hash }
+            }))
+            .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(investment)
+    }
+    .await;
+
+    match result {
+        Ok(Some(investment)) => (StatusCode::OK, Json(serde_json::json!({
+            "investment": investment,
+            "message": "Règlement on-chain enregistré"
+        }))).into_response(),
+        Ok(None) => match sqlx::query_scalar!("SELECT 1 FROM investments WHERE id = $1 AND tenant_id = $2", investment_id, user.tenant_id)
+            .fetch_optional(&pool)
+            .await
+        {
+            Ok(Some(_)) => (StatusCode::CONFLICT, Json(serde_json::json!({
+                "error": "Cet investissement est déjà réglé"
+            }))).into_response(),
+            Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": "Investissement non trouvé"
+            }))).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+                "error": format!("Erreur lors de la vérification: {}", e)
+            }))).into_response(),
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du règlement: {}", e)
+        }))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "whsec_test_secret";
+    const BODY: &[u8] = br#"{"id":"evt_1","type":"payment_intent.succeeded"}"#;
+    const TIMESTAMP: i64 = 1_700_000_000;
+    /// HMAC-SHA256 de `"1700000000.{BODY}"` avec `SECRET`
+    const SIGNATURE: &str = "22d8dc182f5c588077683bd378368e29765bfe5df2560112b4f28fef120f0e31";
+    /// Même charge signée avec un autre secret
+    const OTHER_SIGNATURE: &str = "fd1bfea9611123bdabc198314fcbcdd375eb7fc1afebb5148d282d3803151dff";
+
+    fn header(signatures: &[&str]) -> String {
+        let mut header = format!("t={}", TIMESTAMP);
+        for signature in signatures {
+            header.push_str(&format!(",v1={}", signature));
+        }
+        header
+    }
+
+    #[test]
+    fn accepts_valid_signature() {
+        assert!(verify_signature(SECRET, BODY, &header(&[SIGNATURE]), TIMESTAMP));
+        assert!(verify_signature(SECRET, BODY, &header(&[SIGNATURE]), TIMESTAMP + SIGNATURE_TOLERANCE_SECS));
+        assert!(!verify_signature("whsec_other", BODY, &header(&[SIGNATURE]), TIMESTAMP));
+    }
+
+    #[test]
+    fn rejects_tampered_body() {
+        let tampered = br#"{"id":"evt_1","type":"payment_intent.canceled"}"#;
+        assert!(!verify_signature(SECRET, tampered, &header(&[SIGNATURE]), TIMESTAMP));
+    }
+
+    #[test]
+    fn rejects_timestamp_beyond_tolerance() {
+        assert!(!verify_signature(SECRET, BODY, &header(&[SIGNATURE]), TIMESTAMP + SIGNATURE_TOLERANCE_SECS + 1));
+        assert!(!verify_signature(SECRET, BODY, &header(&[SIGNATURE]), TIMESTAMP - SIGNATURE_TOLERANCE_SECS - 1));
+        // L'horodatage fait partie de la charge signée
+        let shifted = format!("t={},v1={}", TIMESTAMP + 1, SIGNATURE);
+        assert!(!verify_signature(SECRET, BODY, &shifted, TIMESTAMP));
+    }
+
+    #[test]
+    fn accepts_any_matching_v1_entry() {
+        // Rotation du secret : Stripe envoie une signature par secret actif
+        assert!(verify_signature(SECRET, BODY, &header(&[OTHER_SIGNATURE, SIGNATURE]), TIMESTAMP));
+        assert!(verify_signature(SECRET, BODY, &header(&[SIGNATURE, "zz"]), TIMESTAMP));
+        assert!(!verify_signature(SECRET, BODY, &header(&[OTHER_SIGNATURE, "00"]), TIMESTAMP));
+    }
+
+    #[test]
+    fn rejects_malformed_header() {
+        let malformed = [
+            String::new(),
+            format!("v1={}", SIGNATURE),
+            format!("t=abc,v1={}", SIGNATURE),
+            format!("t={}", TIMESTAMP),
+            format!("t={},v1=not-hex", TIMESTAMP),
+            format!("t={},v0={}", TIMESTAMP, SIGNATURE),
+            format!("{},{}", TIMESTAMP, SIGNATURE),
+        ];
+        for header in &malformed {
+            assert!(!verify_signature(SECRET, BODY, header, TIMESTAMP), "{}", header);
+        }
+    }
+}
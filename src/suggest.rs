@@ -0,0 +1,94 @@
+// suggest.rs
+//
+// Autocomplétion de la recherche de propriétés : `GET /api/properties/suggest?q=`
+// renvoie quelques propriétés dont un mot du nom ou de la localisation commence
+// par `q` (insensible à la casse), limitées à ce que le rôle peut voir, sans
+// l'habillage des listes complètes. Les index trigrammes (pg_trgm) sur
+// `lower(name)` et `lower(location)` servent ces recherches par préfixe ; les
+// propriétés dont le nom commence par `q` passent en tête, puis les plus
+// proches de `q`.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::PgPool;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::error_codes::ErrorCode;
+use crate::models::{PropertySuggestion, SuggestQuery, UserRole};
+
+/// Suggestions renvoyées par défaut et au plus
+const DEFAULT_LIMIT: u32 = 8;
+const MAX_LIMIT: u32 = 20;
+
+/// En deçà, aucune suggestion (trop de correspondances pour être utiles)
+const MIN_QUERY_CHARS: usize = 2;
+
+/// Au-delà, la saisie est tronquée
+const MAX_QUERY_CHARS: usize = 100;
+
+/// Saisie en minuscules, avec les caractères spéciaux de LIKE échappés
+fn like_prefix(q: &str) -> String {
+    q.chars()
+        .take(MAX_QUERY_CHARS)
+        .collect::<String>()
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Route : suggestions pour la saisie `q`
+///
+/// Visibilité : l'admin voit toutes les propriétés hors brouillons des autres,
+/// le manager celles qu'il a créées ou co-gère, l'investisseur le catalogue
+/// publié et les propriétés dans lesquelles il a investi.
+pub async fn suggest_properties(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<SuggestQuery>,
+) -> impl IntoResponse {
+    let q = query.q.trim();
+    if q.chars().count() < MIN_QUERY_CHARS {
+        return envelope::list("suggestions", &Vec::<PropertySuggestion>::new()).into_response();
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let result = sqlx::query_as!(
+        PropertySuggestion,
+        r#"SELECT p.id, p.slug, p.name, p.location
+           FROM properties p
+           WHERE p.tenant_id = $2
+             AND (lower(p.name) LIKE $1 || '%' OR lower(p.name) LIKE '% ' || $1 || '%'
+                  OR lower(p.location) LIKE $1 || '%' OR lower(p.location) LIKE '% ' || $1 || '%')
+             AND CASE
+                 WHEN $4 THEN p.status <> 'draft' OR p.created_by = $3
+                 WHEN $5 THEN p.created_by = $3
+                     OR p.id IN (SELECT property_id FROM property_managers WHERE user_id = $3)
+                 ELSE (p.status = 'validated' AND p.published_at IS NOT NULL)
+                     OR p.id IN (SELECT property_id FROM investments WHERE user_id = $3 AND status <> 'refunded')
+             END
+           ORDER BY lower(p.name) LIKE $1 || '%' DESC, similarity(lower(p.name), $1) DESC, p.name
+           LIMIT $6"#,
+        like_prefix(q),
+        user.tenant_id,
+        user.id,
+        matches!(user.role, UserRole::Admin),
+        matches!(user.role, UserRole::Manager),
+        limit as i64
+    )
+    .fetch_all(&pool)
+    .await;
+
+    match result {
+        Ok(suggestions) => envelope::list("suggestions", &suggestions).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la recherche: {}", e),
+            "code": ErrorCode::DatabaseError
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,247 @@
+// tags.rs
+//
+// Tags des propriétés : vocabulaire contrôlé par l'admin, associé aux
+// propriétés (many-to-many) et filtrable via `?tags=seafront,renovated`.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::{Connection, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{CreateTagRequest, Tag, UpdateTagRequest, UserRole};
+use crate::slug::slugify;
+
+/// Analyse `?tags=a,b` en une liste de slugs normalisés, sans doublon.
+/// Renvoie `None` si aucun filtre n'est demandé.
+pub fn parse_tag_filter(raw: Option<&str>) -> Option<Vec<String>> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw.unwrap_or("").split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let tag = tag.to_lowercase();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    if tags.is_empty() { None } else { Some(tags) }
+}
+
+/// Résout des slugs en identifiants de tags.
+/// Le résultat interne est une erreur listant les slugs absents du vocabulaire.
+pub async fn resolve_tags(
+    pool: &PgPool,
+    slugs: &[String],
+) -> Result<Result<Vec<Uuid>, String>, sqlx::Error> {
+    let mut wanted: Vec<String> = Vec::new();
+    for slug in slugs.iter().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()) {
+        if !wanted.contains(&slug) {
+            wanted.push(slug);
+        }
+    }
+
+    let rows = sqlx::query!("SELECT id, slug FROM tags WHERE slug = ANY($1)", &wanted)
+        .fetch_all(pool)
+        .await?;
+
+    let unknown: Vec<&str> = wanted.iter()
+        .filter(|slug| !rows.iter().any(|row| &row.slug == *slug))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Ok(Err(format!("Tags inconnus: {}", unknown.join(", "))));
+    }
+
+    Ok(Ok(rows.into_iter().map(|row| row.id).collect()))
+}
+
+/// Remplace l'ensemble des tags d'une propriété (point de sauvegarde si la
+/// connexion est déjà dans une transaction)
+pub async fn set_property_tags(
+    conn: &mut PgConnection,
+    property_id: Uuid,
+    tag_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    sqlx::query!("DELETE FROM property_tags WHERE property_id = $1", property_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query!(
+        r#"INSERT INTO property_tags (property_id, tag_id)
+           SELECT $1, UNNEST($2::uuid[])
+           ON CONFLICT DO NOTHING"#,
+        property_id,
+        tag_ids
+    )
+    .execute(&mut tx)
+    .await?;
+
+    tx.commit().await
+}
+
+pub fn is_unique_violation(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Database(db) if db.code().as_deref() == Some("23505"))
+}
+
+/// Route pour lister le vocabulaire de tags avec le nombre de propriétés associées
+/// (tout utilisateur authentifié)
+pub async fn get_tags(
+    BearerAuthUser(_user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match sqlx::query!(
+        r#"SELECT t.id, t.slug, t.label, t.created_at, COUNT(pt.property_id) as "properties_count!"
+           FROM tags t
+           LEFT JOIN property_tags pt ON pt.tag_id = t.id
+           GROUP BY t.id
+           ORDER BY t.slug"#
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(rows) => {
+            let tags: Vec<serde_json::Value> = rows.into_iter().map(|row| {
+                serde_json::json!({
+                    "id": row.id,
+                    "slug": row.slug,
+                    "label": row.label,
+                    "created_at": row.created_at,
+                    "properties_count": row.properties_count
+                })
+            }).collect();
+
+            envelope::list("tags", &tags).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour ajouter un tag au vocabulaire (admin seulement)
+pub async fn create_tag(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateTagRequest>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut gérer les tags"
+        }))).into_response();
+    }
+
+    let label = payload.label.trim();
+    if label.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le libellé du tag est requis"
+        }))).into_response();
+    }
+
+    // Slug normalisé pour garder un vocabulaire cohérent
+    let slug = slugify(payload.slug.as_deref().unwrap_or(label));
+
+    match sqlx::query_as!(
+        Tag,
+        r#"INSERT INTO tags (slug, label)
+           VALUES ($1, $2)
+           RETURNING id, slug, label, created_at"#,
+        slug,
+        label
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(tag) => (StatusCode::CREATED, Json(serde_json::json!({
+            "tag": tag,
+            "message": "Tag créé avec succès"
+        }))).into_response(),
+        Err(e) if is_unique_violation(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": format!("Le tag '{}' existe déjà", slug)
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour renommer un tag (admin seulement).
+/// Les propriétés associées suivent automatiquement.
+pub async fn update_tag(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(tag_id): Path<Uuid>,
+    Json(payload): Json<UpdateTagRequest>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut gérer les tags"
+        }))).into_response();
+    }
+
+    let label = payload.label.as_deref().map(str::trim);
+    if matches!(label, Some("")) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le libellé du tag est requis"
+        }))).into_response();
+    }
+    let slug = payload.slug.as_deref().map(slugify);
+
+    match sqlx::query_as!(
+        Tag,
+        r#"UPDATE tags SET
+           slug = COALESCE($2, slug),
+           label = COALESCE($3, label)
+           WHERE id = $1
+           RETURNING id, slug, label, created_at"#,
+        tag_id,
+        slug,
+        label
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(tag)) => (StatusCode::OK, Json(serde_json::json!({
+            "tag": tag,
+            "message": "Tag mis à jour avec succès"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Tag non trouvé"
+        }))).into_response(),
+        Err(e) if is_unique_violation(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Un tag avec ce slug existe déjà"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route pour supprimer un tag du vocabulaire (admin seulement).
+/// Le tag est retiré de toutes les propriétés.
+pub async fn delete_tag(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(tag_id): Path<Uuid>,
+) -> impl IntoResponse {
+    if !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul l'admin peut gérer les tags"
+        }))).into_response();
+    }
+
+    match sqlx::query!("DELETE FROM tags WHERE id = $1", tag_id)
+        .execute(&pool)
+        .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Tag non trouvé"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Tag supprimé avec succès"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,250 @@
+// taxes.rs
+//
+// Retenue à la source par juridiction. Les admins d'une plateforme fixent un
+// taux par pays de résidence fiscale (`country`, code ISO 3166-1 alpha-2) et
+// par type de revenu (`applies_to` : `distributions`, `capital_gains`) ;
+// l'utilisateur renseigne son pays sur son profil. Sans règle pour son pays,
+// aucune retenue n'est appliquée. Les distributions estimées du tableau de
+// bord manager sont ventilées en brut, retenue et net (voir stats.rs).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::activity;
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{CreateTaxRuleRequest, TaxRule, TaxRuleListQuery, UpdateCountryRequest, UpdateTaxRuleRequest, UserRole};
+
+/// Types de revenus soumis à retenue
+pub const APPLIES_TO: &[&str] = &["distributions", "capital_gains"];
+
+/// Code pays normalisé (deux lettres majuscules), `None` si invalide
+pub fn normalize_country(country: &str) -> Option<String> {
+    let country = country.trim().to_uppercase();
+    (country.len() == 2 && country.chars().all(|c| c.is_ascii_uppercase())).then_some(country)
+}
+
+fn admin_only(role: &UserRole) -> Option<Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer les règles fiscales"
+    }))).into_response())
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+        "error": message
+    }))).into_response()
+}
+
+fn tax_rule_error(e: sqlx::Error) -> Response {
+    match e.as_database_error().and_then(|e| e.code()).as_deref() {
+        Some("23505") => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Une règle existe déjà pour ce pays et ce type de revenu"
+        }))).into_response(),
+        Some("23514") => bad_request("Le taux doit être compris entre 0 et 100"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement de la règle fiscale: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : pays de résidence fiscale et taux de retenue qui s'y appliquent
+pub async fn get_my_tax(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let result = async {
+        let country = sqlx::query_scalar!("SELECT country FROM users WHERE id = $1", user.id)
+            .fetch_one(&pool)
+            .await?;
+        let rules = sqlx::query!(
+            "SELECT applies_to, rate FROM tax_rules WHERE tenant_id = $1 AND country = $2 ORDER BY applies_to",
+            user.tenant_id,
+            country
+        )
+        .fetch_all(&pool)
+        .await?;
+        Ok::<_, sqlx::Error>((country, rules))
+    }.await;
+
+    match result {
+        Ok((country, rules)) => (StatusCode::OK, Json(serde_json::json!({
+            "country": country,
+            "withholding": rules.into_iter().map(|rule| serde_json::json!({
+                "applies_to": rule.applies_to,
+                "rate": rule.rate
+            })).collect::<Vec<_>>()
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : renseigner (ou supprimer avec `null`) son pays de résidence fiscale
+pub async fn update_my_country(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<UpdateCountryRequest>,
+) -> impl IntoResponse {
+    let country = match payload.country.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        Some(country) => match normalize_country(country) {
+            Some(country) => Some(country),
+            None => return bad_request("Code pays invalide (ISO 3166-1 alpha-2, ex. FR)"),
+        },
+        None => None,
+    };
+
+    match sqlx::query!(
+        "UPDATE users SET country = $1 WHERE id = $2",
+        country,
+        user.id
+    )
+    .execute(&pool)
+    .await {
+        Ok(_) => {
+            activity::record_detached(pool.clone(), user.id, None, "country_changed", serde_json::json!({
+                "country": country
+            }), None);
+            (StatusCode::OK, Json(serde_json::json!({
+                "country": country
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : règles fiscales de la plateforme (`?country=` pour filtrer)
+pub async fn get_tax_rules(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<TaxRuleListQuery>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        TaxRule,
+        r#"SELECT * FROM tax_rules
+           WHERE tenant_id = $1 AND ($2::text IS NULL OR country = $2)
+           ORDER BY country, applies_to"#,
+        admin.tenant_id,
+        query.country.as_deref().map(|c| c.trim().to_uppercase())
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(rules) => envelope::list("tax_rules", &rules).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : créer une règle de retenue pour un pays
+pub async fn create_tax_rule(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateTaxRuleRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+    let Some(country) = normalize_country(&payload.country) else {
+        return bad_request("Code pays invalide (ISO 3166-1 alpha-2, ex. FR)");
+    };
+    let applies_to = payload.applies_to.unwrap_or_else(|| "distributions".to_string());
+    if !APPLIES_TO.contains(&applies_to.as_str()) {
+        return bad_request("Type de revenu invalide (distributions, capital_gains)");
+    }
+
+    match sqlx::query_as!(
+        TaxRule,
+        r#"INSERT INTO tax_rules (tenant_id, country, rate, applies_to, created_by)
+           VALUES ($1, $2, $3, $4, $5)
+           RETURNING *"#,
+        admin.tenant_id,
+        country,
+        payload.rate,
+        applies_to,
+        admin.id
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(rule) => (StatusCode::CREATED, Json(serde_json::json!({
+            "tax_rule": rule,
+            "message": "Règle fiscale créée"
+        }))).into_response(),
+        Err(e) => tax_rule_error(e),
+    }
+}
+
+/// Route admin : modifier le taux d'une règle
+pub async fn update_tax_rule(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateTaxRuleRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    match sqlx::query_as!(
+        TaxRule,
+        r#"UPDATE tax_rules SET rate = $3, updated_at = NOW()
+           WHERE id = $1 AND tenant_id = $2
+           RETURNING *"#,
+        id,
+        admin.tenant_id,
+        payload.rate
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(rule)) => (StatusCode::OK, Json(serde_json::json!({
+            "tax_rule": rule,
+            "message": "Règle fiscale mise à jour"
+        }))).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Règle fiscale non trouvée"
+        }))).into_response(),
+        Err(e) => tax_rule_error(e),
+    }
+}
+
+/// Route admin : supprimer une règle (plus de retenue pour ce pays)
+pub async fn delete_tax_rule(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    match sqlx::query!(
+        "DELETE FROM tax_rules WHERE id = $1 AND tenant_id = $2",
+        id,
+        admin.tenant_id
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Règle fiscale non trouvée"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Règle fiscale supprimée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
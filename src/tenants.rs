@@ -0,0 +1,342 @@
+// tenants.rs
+//
+// Multi-plateforme : plusieurs marques servies par la même instance, chacune
+// avec ses utilisateurs, propriétés et investissements (`tenant_id`). La
+// plateforme d'une requête est résolue par le middleware `resolve_tenant` :
+// en-tête `X-Tenant` (slug) en priorité, sinon nom d'hôte (`Host`), sinon la
+// plateforme par défaut. Le même middleware applique les origines CORS de la
+// plateforme. La table `tenants` est gardée en mémoire et relue
+// périodiquement, comme les drapeaux de fonctionnalités.
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use sqlx::PgPool;
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::envelope;
+use crate::models::{CreateTenantRequest, Tenant, UpdateTenantRequest, UserRole};
+use crate::tags::is_unique_violation;
+
+/// Plateforme par défaut, créée par la migration
+pub const DEFAULT_TENANT_ID: Uuid = Uuid::from_u128(1);
+
+/// En-tête de sélection explicite de la plateforme (slug)
+const TENANT_HEADER: &str = "x-tenant";
+
+#[derive(Clone, Default)]
+pub struct TenantRegistry {
+    inner: Arc<RwLock<Vec<Tenant>>>,
+}
+
+impl TenantRegistry {
+    /// Recharge toutes les plateformes depuis la base
+    pub async fn refresh(&self, pool: &PgPool) -> Result<usize, sqlx::Error> {
+        let tenants = fetch_tenants(pool).await?;
+        let count = tenants.len();
+        *self.inner.write().unwrap_or_else(|e| e.into_inner()) = tenants;
+        Ok(count)
+    }
+
+    /// Relecture périodique, intervalle configurable via `TENANTS_REFRESH_SECS`
+    /// (60s par défaut)
+    pub fn spawn(&self, pool: PgPool) {
+        let interval_secs = env::var("TENANTS_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(60);
+
+        let registry = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+            loop {
+                interval.tick().await;
+                if let Err(e) = registry.refresh(&pool).await {
+                    tracing::error!("Erreur lors du rechargement des plateformes: {}", e);
+                }
+            }
+        });
+    }
+
+    fn find(&self, predicate: impl Fn(&Tenant) -> bool) -> Option<Tenant> {
+        let tenants = self.inner.read().unwrap_or_else(|e| e.into_inner());
+        tenants.iter().find(|tenant| predicate(tenant)).cloned()
+    }
+
+    fn by_slug(&self, slug: &str) -> Option<Tenant> {
+        self.find(|tenant| tenant.slug == slug)
+    }
+
+    fn by_host(&self, host: &str) -> Option<Tenant> {
+        self.find(|tenant| tenant.hostnames.iter().any(|h| h == host))
+    }
+
+    /// Plateforme par défaut ; reconstruite si la table n'a pas pu être lue
+    fn default_tenant(&self) -> Tenant {
+        self.find(|tenant| tenant.id == DEFAULT_TENANT_ID).unwrap_or_else(|| Tenant {
+            id: DEFAULT_TENANT_ID,
+            slug: "default".to_string(),
+            name: "Plateforme principale".to_string(),
+            hostnames: Vec::new(),
+            cors_origins: Vec::new(),
+            branding: serde_json::json!({}),
+            created_at: chrono::Utc::now(),
+        })
+    }
+}
+
+async fn fetch_tenants(pool: &PgPool) -> Result<Vec<Tenant>, sqlx::Error> {
+    sqlx::query_as!(
+        Tenant,
+        r#"SELECT id, slug, name, hostnames, cors_origins, branding, created_at
+           FROM tenants
+           ORDER BY created_at"#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Middleware : résout la plateforme (insérée dans les extensions de la
+/// requête) puis applique ses origines CORS
+pub async fn resolve_tenant(mut request: Request<Body>, next: Next<Body>) -> Response {
+    let Some(registry) = request.extensions().get::<TenantRegistry>().cloned() else {
+        return next.run(request).await;
+    };
+
+    let requested = request.headers()
+        .get(TENANT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| !v.is_empty());
+    let tenant = match requested {
+        Some(slug) => match registry.by_slug(&slug) {
+            Some(tenant) => tenant,
+            None => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                "error": format!("Plateforme inconnue : {}", slug)
+            }))).into_response(),
+        },
+        None => request.headers()
+            .get(header::HOST)
+            .and_then(|v| v.to_str().ok())
+            .map(|host| host.split(':').next().unwrap_or(host).to_lowercase())
+            .and_then(|host| registry.by_host(&host))
+            .unwrap_or_else(|| registry.default_tenant()),
+    };
+
+    let origin = request.headers()
+        .get(header::ORIGIN)
+        .filter(|origin| origin.to_str().is_ok_and(|origin| allows_origin(&tenant, origin)))
+        .cloned();
+    let preflight = request.method() == Method::OPTIONS
+        && request.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+    let requested_headers = request.headers().get(header::ACCESS_CONTROL_REQUEST_HEADERS).cloned();
+
+    // Requête préliminaire d'une origine autorisée : réponse directe
+    if let (true, Some(origin)) = (preflight, &origin) {
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static("GET, POST, PUT, DELETE, OPTIONS"));
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_HEADERS,
+            requested_headers.unwrap_or(HeaderValue::from_static("authorization, content-type, x-tenant")),
+        );
+        headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("600"));
+        headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+        return response;
+    }
+
+    request.extensions_mut().insert(tenant);
+    let mut response = next.run(request).await;
+
+    // Les routes ayant leur propre politique CORS (widget) la conservent
+    if let Some(origin) = origin {
+        let headers = response.headers_mut();
+        if !headers.contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+            headers.append(header::VARY, HeaderValue::from_static("Origin"));
+        }
+    }
+    response
+}
+
+fn allows_origin(tenant: &Tenant, origin: &str) -> bool {
+    tenant.cors_origins.iter().any(|allowed| allowed == "*" || allowed == origin)
+}
+
+fn normalize(values: Option<Vec<String>>, trim_slash: bool) -> Option<Vec<String>> {
+    values.map(|list| {
+        list.into_iter()
+            .map(|v| v.trim().to_lowercase())
+            .map(|v| if trim_slash { v.trim_end_matches('/').to_string() } else { v })
+            .filter(|v| !v.is_empty())
+            .collect()
+    })
+}
+
+fn valid_slug(slug: &str) -> bool {
+    !slug.is_empty() && slug.len() <= 64 && slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Seuls les admins de la plateforme par défaut gèrent les plateformes
+fn platform_admin_only(role: &UserRole, tenant_id: Uuid) -> Option<Response> {
+    (!matches!(role, UserRole::Admin) || tenant_id != DEFAULT_TENANT_ID).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins de la plateforme principale peuvent gérer les plateformes"
+    }))).into_response())
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+        "error": message
+    }))).into_response()
+}
+
+/// Route publique : nom et habillage de la plateforme résolue
+pub async fn get_current_tenant(Extension(tenant): Extension<Tenant>) -> impl IntoResponse {
+    (StatusCode::OK, Json(serde_json::json!({
+        "slug": tenant.slug,
+        "name": tenant.name,
+        "branding": tenant.branding
+    })))
+}
+
+/// Route admin : toutes les plateformes
+pub async fn get_tenants(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    if let Some(response) = platform_admin_only(&user.role, user.tenant_id) {
+        return response;
+    }
+
+    match fetch_tenants(&pool).await {
+        Ok(list) => envelope::list("tenants", &list).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : créer une plateforme
+pub async fn create_tenant(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(registry): Extension<TenantRegistry>,
+    Json(payload): Json<CreateTenantRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = platform_admin_only(&user.role, user.tenant_id) {
+        return response;
+    }
+    let slug = payload.slug.trim().to_lowercase();
+    if !valid_slug(&slug) {
+        return bad_request("Le slug ne doit contenir que des minuscules, chiffres et '-' (64 caractères au maximum)");
+    }
+    if payload.name.trim().is_empty() {
+        return bad_request("Le nom est requis");
+    }
+    if payload.branding.as_ref().is_some_and(|b| !b.is_object()) {
+        return bad_request("L'habillage doit être un objet JSON");
+    }
+    let hostnames = normalize(payload.hostnames, false).unwrap_or_default();
+    let cors_origins = normalize(payload.cors_origins, true).unwrap_or_default();
+
+    let result = sqlx::query_as!(
+        Tenant,
+        r#"INSERT INTO tenants (slug, name, hostnames, cors_origins, branding)
+           VALUES ($1, $2, $3, $4, COALESCE($5, '{}'::jsonb))
+           RETURNING id, slug, name, hostnames, cors_origins, branding, created_at"#,
+        slug,
+        payload.name.trim(),
+        &hostnames,
+        &cors_origins,
+        payload.branding
+    )
+    .fetch_one(&pool)
+    .await;
+
+    match result {
+        Ok(tenant) => {
+            if let Err(e) = registry.refresh(&pool).await {
+                tracing::error!("Erreur lors du rechargement des plateformes: {}", e);
+            }
+            (StatusCode::CREATED, Json(serde_json::json!({
+                "tenant": tenant,
+                "message": "Plateforme créée"
+            }))).into_response()
+        },
+        Err(e) if is_unique_violation(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Ce slug est déjà utilisé"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : modifier le nom, les hôtes, les origines CORS ou l'habillage
+pub async fn update_tenant(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(registry): Extension<TenantRegistry>,
+    Path(tenant_id): Path<Uuid>,
+    Json(payload): Json<UpdateTenantRequest>,
+) -> impl IntoResponse {
+    if let Some(response) = platform_admin_only(&user.role, user.tenant_id) {
+        return response;
+    }
+    if payload.name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+        return bad_request("Le nom ne peut pas être vide");
+    }
+    if payload.branding.as_ref().is_some_and(|b| !b.is_object()) {
+        return bad_request("L'habillage doit être un objet JSON");
+    }
+    let hostnames = normalize(payload.hostnames, false);
+    let cors_origins = normalize(payload.cors_origins, true);
+
+    let result = sqlx::query_as!(
+        Tenant,
+        r#"UPDATE tenants SET
+               name = COALESCE($2, name),
+               hostnames = COALESCE($3, hostnames),
+               cors_origins = COALESCE($4, cors_origins),
+               branding = COALESCE($5, branding)
+           WHERE id = $1
+           RETURNING id, slug, name, hostnames, cors_origins, branding, created_at"#,
+        tenant_id,
+        payload.name.as_deref().map(str::trim),
+        hostnames.as_deref(),
+        cors_origins.as_deref(),
+        payload.branding
+    )
+    .fetch_optional(&pool)
+    .await;
+
+    match result {
+        Ok(Some(tenant)) => {
+            if let Err(e) = registry.refresh(&pool).await {
+                tracing::error!("Erreur lors du rechargement des plateformes: {}", e);
+            }
+            (StatusCode::OK, Json(serde_json::json!({
+                "tenant": tenant,
+                "message": "Plateforme mise à jour"
+            }))).into_response()
+        },
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Plateforme non trouvée"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
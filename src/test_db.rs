@@ -0,0 +1,40 @@
+// test_db.rs
+//
+// Base des tests : les tests qui touchent la base se connectent à
+// `DATABASE_URL` (schéma de migrations/supabase_migration.sql) et travaillent
+// dans une transaction jamais validée, annulée à la fin du test. Sans
+// `DATABASE_URL`, ces tests ne vérifient rien et réussissent.
+
+use rand::RngCore;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::models::UserRole;
+
+/// Transaction de test, `None` si `DATABASE_URL` n'est pas défini
+pub async fn begin() -> Option<Transaction<'static, Postgres>> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let pool = PgPool::connect(&url).await.expect("base de test injoignable");
+    Some(pool.begin().await.expect("transaction de test"))
+}
+
+/// Adresse de wallet aléatoire, en minuscules
+pub fn random_wallet() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Utilisateur de la plateforme principale, avec un wallet aléatoire
+pub async fn insert_user(conn: &mut PgConnection, role: UserRole) -> (Uuid, String) {
+    let wallet = random_wallet();
+    let id = sqlx::query_scalar!(
+        "INSERT INTO users (wallet, role) VALUES ($1, $2) RETURNING id",
+        wallet,
+        role as UserRole
+    )
+    .fetch_one(conn)
+    .await
+    .expect("utilisateur de test");
+    (id, wallet)
+}
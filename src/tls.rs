@@ -0,0 +1,198 @@
+// tls.rs
+//
+// Terminaison TLS par le serveur lui-même, pour un déploiement simple sans
+// reverse proxy (VPS). Activée par `TLS_CERT_PATH` et `TLS_KEY_PATH` (PEM :
+// chaîne de certificats et clé privée PKCS#8, RSA ou EC) ; sinon le serveur
+// écoute en HTTP comme avant. `HTTP_REDIRECT_PORT` ouvre en plus un port HTTP
+// qui redirige chaque requête vers son équivalent HTTPS.
+
+use axum::{
+    extract::{connect_info::Connected, Host},
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Redirect},
+    Router, Server,
+};
+use hyper::server::accept::Accept;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::rustls::{Certificate, PrivateKey, ServerConfig};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+
+/// Délai accordé au client pour terminer la négociation TLS
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connexions négociées en attente d'être servies
+const ACCEPT_QUEUE: usize = 128;
+
+#[derive(Clone)]
+pub struct TlsConfig {
+    acceptor: TlsAcceptor,
+    pub redirect_port: Option<u16>,
+}
+
+fn load_certificates(path: &str) -> Result<Vec<Certificate>, String> {
+    let file = File::open(path).map_err(|e| format!("{} : {}", path, e))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file)).map_err(|e| format!("{} : {}", path, e))?;
+    if certs.is_empty() {
+        return Err(format!("{} : aucun certificat PEM", path));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn load_private_key(path: &str) -> Result<PrivateKey, String> {
+    let file = File::open(path).map_err(|e| format!("{} : {}", path, e))?;
+    rustls_pemfile::read_all(&mut BufReader::new(file))
+        .map_err(|e| format!("{} : {}", path, e))?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| format!("{} : aucune clé privée PEM", path))
+}
+
+impl TlsConfig {
+    /// Renvoie `None` si `TLS_CERT_PATH` ou `TLS_KEY_PATH` manque ; un
+    /// certificat ou une clé illisible arrête le démarrage plutôt que de
+    /// servir en clair
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let cert_path = var("TLS_CERT_PATH")?;
+        let key_path = var("TLS_KEY_PATH")?;
+
+        let mut config = load_certificates(&cert_path)
+            .and_then(|certs| Ok((certs, load_private_key(&key_path)?)))
+            .and_then(|(certs, key)| ServerConfig::builder()
+                .with_safe_defaults()
+                .with_no_client_auth()
+                .with_single_cert(certs, key)
+                .map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| panic!("Configuration TLS invalide : {}", e));
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+        Some(Self {
+            acceptor: TlsAcceptor::from(Arc::new(config)),
+            redirect_port: var("HTTP_REDIRECT_PORT").and_then(|v| v.parse().ok()),
+        })
+    }
+}
+
+/// Connexion TLS établie, avec l'adresse du client (voir forwarded.rs)
+pub struct TlsConnection {
+    stream: TlsStream<TcpStream>,
+    peer: SocketAddr,
+}
+
+impl Connected<&TlsConnection> for SocketAddr {
+    fn connect_info(target: &TlsConnection) -> Self {
+        target.peer
+    }
+}
+
+impl AsyncRead for TlsConnection {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TlsConnection {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+/// Connexions TLS négociées en tâche de fond, une négociation lente ou
+/// échouée ne bloquant pas les suivantes
+struct TlsIncoming(mpsc::Receiver<TlsConnection>);
+
+impl Accept for TlsIncoming {
+    type Conn = TlsConnection;
+    type Error = io::Error;
+
+    fn poll_accept(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        self.0.poll_recv(cx).map(|connection| connection.map(Ok))
+    }
+}
+
+/// Sert l'application en HTTPS sur `addr`
+pub async fn serve(app: Router, addr: SocketAddr, tls: TlsConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(addr).await?;
+    let (sender, receiver) = mpsc::channel(ACCEPT_QUEUE);
+
+    tokio::spawn(async move {
+        loop {
+            let (tcp, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Connexion TCP refusée");
+                    continue;
+                },
+            };
+            let (acceptor, sender) = (tls.acceptor.clone(), sender.clone());
+            tokio::spawn(async move {
+                match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(tcp)).await {
+                    Ok(Ok(stream)) => {
+                        let _ = sender.send(TlsConnection { stream, peer }).await;
+                    },
+                    Ok(Err(e)) => tracing::debug!(%peer, error = %e, "Négociation TLS échouée"),
+                    Err(_) => tracing::debug!(%peer, "Négociation TLS trop longue"),
+                }
+            });
+        }
+    });
+
+    Server::builder(TlsIncoming(receiver))
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await?;
+    Ok(())
+}
+
+/// Redirige toute requête HTTP vers HTTPS, sur le port `https_port`
+pub fn spawn_redirect(port: u16, https_port: u16) {
+    let redirect = move |Host(host): Host, uri: Uri| async move {
+        let host = host.rsplit_once(':')
+            .filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit()))
+            .map(|(host, _)| host.to_string())
+            .unwrap_or(host);
+        let authority = match https_port {
+            443 => host,
+            port => format!("{}:{}", host, port),
+        };
+        let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        match format!("https://{}{}", authority, path).parse::<Uri>() {
+            Ok(target) => Redirect::permanent(&target.to_string()).into_response(),
+            Err(_) => StatusCode::BAD_REQUEST.into_response(),
+        }
+    };
+
+    let addr: SocketAddr = ([0, 0, 0, 0], port).into();
+    tokio::spawn(async move {
+        let app = Router::new().fallback(redirect);
+        if let Err(e) = Server::bind(&addr)
+            .serve(app.into_make_service())
+            .await
+        {
+            tracing::error!(error = %e, "Arrêt de la redirection HTTP vers HTTPS");
+        }
+    });
+}
@@ -0,0 +1,287 @@
+// token.rs
+//
+// Jetons des propriétés : contrat ERC-1155 (`PROPERTY_TOKEN_ADDRESS`) dont
+// l'identifiant de jeton d'une propriété est l'actif attribué par le registre
+// (son `onchain_id`). Les soldes des détenteurs à un bloc donné sont
+// reconstitués à partir des événements de transfert, pour calculer les
+// distributions sur une base figée.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+    Json,
+};
+use moka::sync::Cache;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::chain::{ChainRpc, TxLog};
+use crate::eip712::keccak256;
+use crate::models::{HoldersQuery, PropertyStatus, UserRole};
+
+pub const TRANSFER_SINGLE_EVENT: &str = "TransferSingle(address,address,address,uint256,uint256)";
+const TRANSFER_BATCH_EVENT: &str = "TransferBatch(address,address,address,uint256[],uint256[])";
+// Au-delà de cette profondeur, un bloc n'est plus susceptible d'être réorganisé
+const FINALITY_BLOCKS: u64 = 64;
+
+type Snapshot = Arc<Vec<(String, u128)>>;
+
+/// Mouvement d'un jeton ; l'adresse nulle (mint ou burn) vaut `None`
+pub struct TokenTransfer {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub token_id: [u8; 32],
+    pub value: u128,
+}
+
+/// Topic 0 d'un événement : keccak256 de sa signature
+pub fn event_topic(signature: &str) -> String {
+    format!("0x{}", hex::encode(keccak256(signature.as_bytes())))
+}
+
+/// Contrat des jetons et cache des snapshots des blocs finalisés
+#[derive(Clone)]
+pub struct PropertyToken {
+    rpc: ChainRpc,
+    address: String,
+    deploy_block: u64,
+    logs_chunk: u64,
+    snapshots: Cache<(String, u64), Snapshot>,
+}
+
+impl PropertyToken {
+    /// Renvoie `None` si le nœud ou l'adresse du contrat ne sont pas configurés.
+    /// `PROPERTY_TOKEN_DEPLOY_BLOCK` évite de parcourir les blocs antérieurs au déploiement.
+    pub fn from_env(rpc: Option<ChainRpc>) -> Option<Self> {
+        let rpc = rpc?;
+        let address = env::var("PROPERTY_TOKEN_ADDRESS").ok().filter(|v| !v.trim().is_empty())?;
+        let env_u64 = |name: &str| env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+
+        Some(Self {
+            rpc,
+            address: address.trim().to_lowercase(),
+            deploy_block: env_u64("PROPERTY_TOKEN_DEPLOY_BLOCK").unwrap_or(0),
+            logs_chunk: env_u64("CHAIN_LOGS_CHUNK").filter(|v| *v > 0).unwrap_or(5_000),
+            snapshots: Cache::builder().max_capacity(100).build(),
+        })
+    }
+
+    /// Soldes non nuls du jeton au bloc donné, par solde décroissant
+    async fn balances_at(&self, token_id: &[u8; 32], block: u64, latest: u64) -> Result<Snapshot, String> {
+        let key = (hex::encode(token_id), block);
+        if let Some(snapshot) = self.snapshots.get(&key) {
+            return Ok(snapshot);
+        }
+
+        let single = event_topic(TRANSFER_SINGLE_EVENT);
+        let batch = event_topic(TRANSFER_BATCH_EVENT);
+        let mut balances: HashMap<String, i128> = HashMap::new();
+
+        let mut from_block = self.deploy_block;
+        while from_block <= block {
+            let to_block = (from_block + self.logs_chunk - 1).min(block);
+            for log in self.rpc.get_logs(&self.address, &[&single, &batch], from_block, to_block).await? {
+                for transfer in decode_transfers(&log)?.into_iter().filter(|t| t.token_id == *token_id) {
+                    if let Some(from) = transfer.from {
+                        *balances.entry(from).or_default() -= transfer.value as i128;
+                    }
+                    if let Some(to) = transfer.to {
+                        *balances.entry(to).or_default() += transfer.value as i128;
+                    }
+                }
+            }
+            from_block = to_block + 1;
+        }
+
+        let mut holders: Vec<(String, u128)> = balances.into_iter()
+            .filter(|(_, balance)| *balance > 0)
+            .map(|(address, balance)| (address, balance as u128))
+            .collect();
+        holders.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let snapshot = Arc::new(holders);
+        if block + FINALITY_BLOCKS <= latest {
+            self.snapshots.insert(key, snapshot.clone());
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Mouvements décrits par un événement `TransferSingle` / `TransferBatch`
+/// (aucun pour tout autre événement)
+pub fn decode_transfers(log: &TxLog) -> Result<Vec<TokenTransfer>, String> {
+    let is_batch = match log.topics.first() {
+        Some(topic) if *topic == event_topic(TRANSFER_SINGLE_EVENT) => false,
+        Some(topic) if *topic == event_topic(TRANSFER_BATCH_EVENT) => true,
+        _ => return Ok(Vec::new()),
+    };
+
+    let invalid = || "Événement de transfert mal formé".to_string();
+    let topic_address = |index: usize| -> Result<Option<String>, String> {
+        let topic = log.topics.get(index).ok_or_else(invalid)?;
+        let address = format!("0x{}", topic.trim_start_matches("0x").get(24..).ok_or_else(invalid)?);
+        Ok(Some(address).filter(|a| a.trim_start_matches("0x").chars().any(|c| c != '0')))
+    };
+    let (from, to) = (topic_address(2)?, topic_address(3)?);
+
+    let data = hex::decode(log.data.trim_start_matches("0x")).map_err(|_| invalid())?;
+    let word = |index: usize| -> Result<&[u8], String> { data.get(index * 32..(index + 1) * 32).ok_or_else(invalid) };
+    let as_usize = |bytes: &[u8]| -> Result<usize, String> {
+        uint_value(bytes).filter(|v| *v <= data.len() as u128).map(|v| v as usize).ok_or_else(invalid)
+    };
+
+    let pairs = if is_batch {
+        // (uint256[] ids, uint256[] values) : positions puis longueur et éléments
+        let ids_start = as_usize(word(0)?)? / 32;
+        let values_start = as_usize(word(1)?)? / 32;
+        let count = as_usize(word(ids_start)?)?;
+        (0..count)
+            .map(|i| Ok((word(ids_start + 1 + i)?, word(values_start + 1 + i)?)))
+            .collect::<Result<Vec<_>, String>>()?
+    } else {
+        vec![(word(0)?, word(1)?)]
+    };
+
+    pairs.into_iter()
+        .map(|(id, value)| Ok(TokenTransfer {
+            from: from.clone(),
+            to: to.clone(),
+            token_id: id.try_into().map_err(|_| invalid())?,
+            value: uint_value(value).ok_or_else(|| "Quantité de jetons hors limites".to_string())?,
+        }))
+        .collect()
+}
+
+/// Mot ABI de 32 octets vers entier, `None` s'il dépasse 128 bits
+fn uint_value(word: &[u8]) -> Option<u128> {
+    if word[..16].iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(u128::from_be_bytes(word[16..].try_into().ok()?))
+}
+
+/// Identifiant décimal (uint256) vers mot ABI de 32 octets
+pub fn parse_token_id(decimal: &str) -> Option<[u8; 32]> {
+    if decimal.is_empty() || !decimal.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut word = [0u8; 32];
+    for digit in decimal.bytes().map(|c| (c - b'0') as u32) {
+        let mut carry = digit;
+        for byte in word.iter_mut().rev() {
+            let value = *byte as u32 * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(word)
+}
+
+/// Route pour obtenir les détenteurs des jetons d'une propriété à un bloc donné
+/// (créateur ou admin)
+pub async fn get_property_holders(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(token): Extension<Option<PropertyToken>>,
+    Path(property_id): Path<Uuid>,
+    Query(query): Query<HoldersQuery>,
+) -> impl IntoResponse {
+    let token = match token {
+        Some(token) => token,
+        None => return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({
+            "error": "Contrat des jetons non configuré (CHAIN_RPC_URL, PROPERTY_TOKEN_ADDRESS)"
+        }))).into_response(),
+    };
+
+    let property = match sqlx::query!(
+        r#"SELECT onchain_id, created_by, status as "status: PropertyStatus" FROM properties WHERE id = $1"#,
+        property_id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(prop)) if !matches!(prop.status, PropertyStatus::Draft) || prop.created_by == user.id => prop,
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Propriété non trouvée"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    if property.created_by != user.id && !matches!(user.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seul le créateur ou l'admin peut consulter les détenteurs"
+        }))).into_response();
+    }
+
+    let token_id = match parse_token_id(&property.onchain_id) {
+        Some(token_id) => token_id,
+        None => return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "La propriété n'est pas enregistrée on-chain"
+        }))).into_response(),
+    };
+
+    let latest = match token.rpc.block_number().await {
+        Ok(latest) => latest,
+        Err(e) => return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'appel au nœud: {}", e)
+        }))).into_response(),
+    };
+    let block = query.block.unwrap_or(latest);
+    if block > latest {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Le bloc {} n'est pas encore miné (dernier bloc : {})", block, latest)
+        }))).into_response();
+    }
+
+    let holders = match token.balances_at(&token_id, block, latest).await {
+        Ok(holders) => holders,
+        Err(e) => return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({
+            "error": format!("Erreur lors de la lecture des transferts: {}", e)
+        }))).into_response(),
+    };
+
+    // Rapprochement avec les utilisateurs connus par leur wallet
+    let addresses: Vec<String> = holders.iter().map(|(address, _)| address.clone()).collect();
+    let users = match sqlx::query!(
+        "SELECT id, wallet, name FROM users WHERE LOWER(wallet) = ANY($1) AND deactivated_at IS NULL",
+        &addresses
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(users) => users,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération des utilisateurs: {}", e)
+        }))).into_response(),
+    };
+    let users: HashMap<String, serde_json::Value> = users.into_iter()
+        .map(|u| (u.wallet.to_lowercase(), serde_json::json!({ "id": u.id, "name": u.name })))
+        .collect();
+
+    let total_supply: u128 = holders.iter().map(|(_, balance)| balance).sum();
+    let holders: Vec<serde_json::Value> = holders.iter()
+        .map(|(address, balance)| serde_json::json!({
+            "address": address,
+            "balance": balance.to_string(),
+            "user": users.get(address)
+        }))
+        .collect();
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "property_id": property_id,
+        "token_id": property.onchain_id,
+        "block": block,
+        "holders": holders,
+        "holders_count": holders.len(),
+        "total_supply": total_supply.to_string()
+    }))).into_response()
+}
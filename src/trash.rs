@@ -0,0 +1,282 @@
+// trash.rs
+//
+// Corbeille des admins. La suppression d'une propriété ou d'un investissement
+// déplace la ligne dans la table `<entité>_trash` correspondante (même schéma,
+// plus `deleted_at` et `deleted_by`), comme l'archivage de retention.rs, au
+// lieu de l'effacer : un admin peut la restaurer telle quelle ou la purger
+// définitivement (confirmation requise). Les lignes rattachées supprimées en
+// cascade (médias, documents d'une propriété, certificats d'un investissement)
+// ne sont pas conservées ; les triggers recalculent les statistiques et
+// réémettent le certificat d'un investissement restauré.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::auth::BearerAuthUser;
+use crate::confirmations;
+use crate::envelope;
+use crate::error_codes::ErrorCode;
+use crate::feeds::FeedCache;
+use crate::funding;
+use crate::models::{TrashEntity, TrashListQuery, UserRole};
+
+/// Lignes listées au plus par requête
+const TRASH_LIST_LIMIT: i64 = 200;
+
+/// Met une propriété à la corbeille ; `false` si elle n'existe pas
+pub async fn trash_property<'e, E: PgExecutor<'e>>(executor: E, property_id: Uuid, deleted_by: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"WITH moved AS (
+               DELETE FROM properties WHERE id = $1
+               RETURNING *
+           )
+           INSERT INTO properties_trash SELECT *, NOW(), $2 FROM moved"#,
+        property_id,
+        deleted_by
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Met un investissement à la corbeille ; `false` s'il n'existe pas
+pub async fn trash_investment<'e, E: PgExecutor<'e>>(executor: E, investment_id: Uuid, deleted_by: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"WITH moved AS (
+               DELETE FROM investments WHERE id = $1
+               RETURNING *
+           )
+           INSERT INTO investments_trash SELECT *, NOW(), $2 FROM moved"#,
+        investment_id,
+        deleted_by
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+fn admin_only(role: &UserRole) -> Option<Response> {
+    (!matches!(role, UserRole::Admin)).then(|| (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": "Seuls les admins peuvent gérer la corbeille"
+    }))).into_response())
+}
+
+fn not_found() -> Response {
+    (StatusCode::NOT_FOUND, Json(serde_json::json!({
+        "error": "Élément absent de la corbeille"
+    }))).into_response()
+}
+
+/// Route admin : éléments supprimés d'une entité (`?entity=properties|investments`),
+/// du plus récent au plus ancien
+pub async fn get_trash(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Query(query): Query<TrashListQuery>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    let result = match query.entity {
+        TrashEntity::Properties => sqlx::query!(
+            r#"SELECT to_jsonb(t) - 'deleted_at' - 'deleted_by' as "record!", t.deleted_at, t.deleted_by,
+                      u.wallet as "deleted_by_wallet?"
+               FROM properties_trash t
+               LEFT JOIN users u ON u.id = t.deleted_by
+               WHERE t.tenant_id = $1
+               ORDER BY t.deleted_at DESC
+               LIMIT $2"#,
+            admin.tenant_id,
+            TRASH_LIST_LIMIT
+        )
+        .fetch_all(&pool)
+        .await
+        .map(|rows| rows.into_iter().map(|row| serde_json::json!({
+            "record": row.record,
+            "deleted_at": row.deleted_at,
+            "deleted_by": row.deleted_by,
+            "deleted_by_wallet": row.deleted_by_wallet
+        })).collect::<Vec<_>>()),
+        TrashEntity::Investments => sqlx::query!(
+            r#"SELECT to_jsonb(t) - 'deleted_at' - 'deleted_by' as "record!", t.deleted_at, t.deleted_by,
+                      u.wallet as "deleted_by_wallet?"
+               FROM investments_trash t
+               LEFT JOIN users u ON u.id = t.deleted_by
+               WHERE t.tenant_id = $1
+               ORDER BY t.deleted_at DESC
+               LIMIT $2"#,
+            admin.tenant_id,
+            TRASH_LIST_LIMIT
+        )
+        .fetch_all(&pool)
+        .await
+        .map(|rows| rows.into_iter().map(|row| serde_json::json!({
+            "record": row.record,
+            "deleted_at": row.deleted_at,
+            "deleted_by": row.deleted_by,
+            "deleted_by_wallet": row.deleted_by_wallet
+        })).collect::<Vec<_>>()),
+    };
+
+    match result {
+        Ok(items) => envelope::list("items", &items).with("entity", query.entity).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération de la corbeille: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : restaurer un élément de la corbeille
+pub async fn restore_from_trash(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(feed_cache): Extension<FeedCache>,
+    Path((entity, id)): Path<(TrashEntity, Uuid)>,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    let result = match entity {
+        TrashEntity::Properties => sqlx::query!(
+            r#"WITH restored AS (
+                   DELETE FROM properties_trash WHERE id = $1 AND tenant_id = $2
+                   RETURNING *
+               )
+               -- total_shares est une colonne générée : recalculée à l'insertion
+               INSERT INTO properties (id, tenant_id, onchain_id, slug, name, location, country, region, city, geocoded_at, type, description,
+                                       total_price, token_price, annual_yield, image_url, documents, created_by,
+                                       created_at, status, status_updated_at, status_updated_by, publish_at,
+                                       published_at, amenities, requires_accreditation, contract_address, registry_tx_id,
+                                       registry_rollback_status, registry_tx_hash, registered_at, shares_sold)
+               SELECT id, tenant_id, onchain_id, slug, name, location, country, region, city, geocoded_at, type, description,
+                      total_price, token_price, annual_yield, image_url, documents, created_by,
+                      created_at, status, status_updated_at, status_updated_by, publish_at,
+                      published_at, amenities, requires_accreditation, contract_address, registry_tx_id,
+                      registry_rollback_status, registry_tx_hash, registered_at, shares_sold
+               FROM restored"#,
+            id,
+            admin.tenant_id
+        )
+        .execute(&pool)
+        .await,
+        TrashEntity::Investments => sqlx::query!(
+            r#"WITH restored AS (
+                   DELETE FROM investments_trash WHERE id = $1 AND tenant_id = $2
+                   RETURNING *
+               )
+               INSERT INTO investments (id, tenant_id, user_id, property_id, amount_eth, amount_wei, shares, tx_hash, status,
+                                        settled_at, created_at)
+               SELECT id, tenant_id, user_id, property_id, amount_eth, amount_wei, shares, tx_hash, status, settled_at, created_at
+               FROM restored"#,
+            id,
+            admin.tenant_id
+        )
+        .execute(&pool)
+        .await,
+    };
+
+    match result {
+        Ok(result) if result.rows_affected() == 0 => not_found(),
+        Ok(_) => {
+            if matches!(entity, TrashEntity::Properties) {
+                feed_cache.invalidate();
+            }
+            (StatusCode::OK, Json(serde_json::json!({
+                "entity": entity,
+                "id": id,
+                "message": "Élément restauré"
+            }))).into_response()
+        },
+        // Investissement restauré au-delà des parts encore disponibles
+        Err(e) if funding::is_oversold(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Restauration impossible : plus assez de parts disponibles pour cette propriété",
+            "code": ErrorCode::SharesBelowSold
+        }))).into_response(),
+        // Identifiant unique repris depuis la suppression, ou propriété/utilisateur
+        // rattaché supprimé entre-temps
+        Err(e) if matches!(e.as_database_error().and_then(|e| e.code()).as_deref(), Some("23505" | "23503")) => {
+            (StatusCode::CONFLICT, Json(serde_json::json!({
+                "error": format!("Restauration impossible : {}", e.as_database_error().map(|e| e.message()).unwrap_or_default())
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la restauration: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route admin : supprimer définitivement un élément de la corbeille
+/// (confirmation requise, voir confirmations.rs)
+pub async fn purge_from_trash(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path((entity, id)): Path<(TrashEntity, Uuid)>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(response) = admin_only(&admin.role) {
+        return response;
+    }
+
+    let exists = match entity {
+        TrashEntity::Properties => sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM properties_trash WHERE id = $1 AND tenant_id = $2) as "exists!""#,
+            id,
+            admin.tenant_id
+        )
+        .fetch_one(&pool)
+        .await,
+        TrashEntity::Investments => sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM investments_trash WHERE id = $1 AND tenant_id = $2) as "exists!""#,
+            id,
+            admin.tenant_id
+        )
+        .fetch_one(&pool)
+        .await,
+    };
+    match exists {
+        Ok(true) => {},
+        Ok(false) => return not_found(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    }
+
+    if let Err(response) = confirmations::ensure_confirmed(&pool, &admin, &headers, "purge_trash", &id.to_string()).await {
+        return response;
+    }
+
+    let result = match entity {
+        TrashEntity::Properties => sqlx::query!(
+            "DELETE FROM properties_trash WHERE id = $1 AND tenant_id = $2",
+            id,
+            admin.tenant_id
+        )
+        .execute(&pool)
+        .await,
+        TrashEntity::Investments => sqlx::query!(
+            "DELETE FROM investments_trash WHERE id = $1 AND tenant_id = $2",
+            id,
+            admin.tenant_id
+        )
+        .execute(&pool)
+        .await,
+    };
+
+    match result {
+        Ok(result) if result.rows_affected() == 0 => not_found(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Élément supprimé définitivement"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,435 @@
+// two_factor.rs
+//
+// Double authentification TOTP (RFC 6238 : HMAC-SHA1, pas de 30s, 6 chiffres)
+// pour les opérations d'administration destructrices. L'enrôlement est
+// optionnel : une fois activé, ces opérations exigent l'en-tête `X-TOTP-Code`
+// (code de l'application ou code de secours) ou `X-Step-Up-Token`, un jeton
+// de quelques minutes obtenu via `POST /api/me/2fa/step-up`. Avec
+// `ADMIN_2FA_REQUIRED=true`, un admin non enrôlé est refusé.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use qrcode::{render::svg, QrCode};
+use rand::RngCore;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::env;
+
+use crate::activity;
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::models::{TwoFactorCodeRequest, UserRole};
+
+/// Durée d'un pas TOTP, en secondes
+const STEP_SECS: i64 = 30;
+/// Pas acceptés de part et d'autre du pas courant (décalage d'horloge)
+const STEP_WINDOW: i64 = 1;
+const RECOVERY_CODES: usize = 10;
+
+/// Empreinte SHA-256 : seule forme stockée des codes de secours et des jetons
+pub fn hash_secret(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
+
+pub fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Code à 6 chiffres pour un pas donné (troncature dynamique de la RFC 4226)
+fn totp_code(secret: &[u8], step: i64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepte toute taille de clé");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[19] & 0x0f) as usize;
+    let value = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+    value % 1_000_000
+}
+
+/// Pas accepté pour ce code, dans la fenêtre autour du pas courant
+fn matching_step(secret_base32: &str, code: &str) -> Option<i64> {
+    let secret = base32::decode(base32::Alphabet::Rfc4648 { padding: false }, secret_base32)?;
+    let code: u32 = code.parse().ok()?;
+    let current = Utc::now().timestamp() / STEP_SECS;
+    (current - STEP_WINDOW..=current + STEP_WINDOW).find(|step| totp_code(&secret, *step) == code)
+}
+
+fn admin_2fa_required() -> bool {
+    env::var("ADMIN_2FA_REQUIRED").is_ok_and(|v| v == "true" || v == "1")
+}
+
+fn forbidden(message: &str) -> Response {
+    (StatusCode::FORBIDDEN, Json(serde_json::json!({
+        "error": message,
+        "two_factor_required": true
+    }))).into_response()
+}
+
+/// Vérifie un code TOTP (anti-rejeu) ou consomme un code de secours
+async fn check_code(pool: &PgPool, user_id: uuid::Uuid, secret: &str, code: &str) -> Result<bool, sqlx::Error> {
+    let code = code.trim();
+    if code.len() == 6 && code.chars().all(|c| c.is_ascii_digit()) {
+        let Some(step) = matching_step(secret, code) else {
+            return Ok(false);
+        };
+        // Un code déjà accepté ne peut pas resservir
+        let accepted = sqlx::query!(
+            r#"UPDATE user_totp SET last_used_step = $2
+               WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)"#,
+            user_id,
+            step
+        )
+        .execute(pool)
+        .await?;
+        return Ok(accepted.rows_affected() == 1);
+    }
+
+    let used = sqlx::query!(
+        r#"UPDATE totp_recovery_codes SET used_at = NOW()
+           WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL"#,
+        user_id,
+        hash_secret(&code.to_lowercase())
+    )
+    .execute(pool)
+    .await?;
+    Ok(used.rows_affected() == 1)
+}
+
+/// À appeler au début des opérations destructrices : `Err` contient la
+/// réponse à renvoyer si le second facteur manque ou est invalide
+pub async fn ensure_second_factor(pool: &PgPool, user: &SessionUser, headers: &HeaderMap) -> Result<(), Response> {
+    let internal = |e: sqlx::Error| (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+        "error": format!("Erreur lors de la vérification du second facteur: {}", e)
+    }))).into_response();
+
+    let totp = sqlx::query!(
+        "SELECT secret FROM user_totp WHERE user_id = $1 AND enabled_at IS NOT NULL",
+        user.id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(internal)?;
+    let Some(totp) = totp else {
+        return if matches!(user.role, UserRole::Admin) && admin_2fa_required() {
+            Err(forbidden("Double authentification obligatoire : l'activer via /api/me/2fa/setup"))
+        } else {
+            Ok(())
+        };
+    };
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim).filter(|v| !v.is_empty());
+    if let Some(token) = header("X-Step-Up-Token") {
+        let valid = sqlx::query_scalar!(
+            "SELECT 1 FROM two_factor_step_ups WHERE token_hash = $1 AND user_id = $2 AND expires_at > NOW()",
+            hash_secret(token),
+            user.id
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(internal)?;
+        return match valid {
+            Some(_) => Ok(()),
+            None => Err(forbidden("Jeton de double authentification invalide ou expiré")),
+        };
+    }
+    match header("X-TOTP-Code") {
+        Some(code) => match check_code(pool, user.id, &totp.secret, code).await.map_err(internal)? {
+            true => Ok(()),
+            false => Err(forbidden("Code de double authentification invalide")),
+        },
+        None => Err(forbidden("Code de double authentification requis (en-tête X-TOTP-Code ou X-Step-Up-Token)")),
+    }
+}
+
+/// Route : état de la double authentification de l'utilisateur connecté
+pub async fn get_two_factor_status(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match sqlx::query!(
+        r#"SELECT t.enabled_at,
+           (SELECT COUNT(*) FROM totp_recovery_codes c WHERE c.user_id = t.user_id AND c.used_at IS NULL) as "recovery_codes_remaining!"
+           FROM user_totp t WHERE t.user_id = $1"#,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(row) => (StatusCode::OK, Json(serde_json::json!({
+            "enabled": row.as_ref().is_some_and(|r| r.enabled_at.is_some()),
+            "enabled_at": row.as_ref().and_then(|r| r.enabled_at),
+            "recovery_codes_remaining": row.as_ref().filter(|r| r.enabled_at.is_some()).map_or(0, |r| r.recovery_codes_remaining),
+            "required": matches!(user.role, UserRole::Admin) && admin_2fa_required()
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : générer un secret (URI `otpauth://` et QR code SVG) et des codes
+/// de secours ; la double authentification n'est active qu'après confirmation
+/// via `POST /api/me/2fa/enable`
+pub async fn setup_two_factor(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    let secret = base32::encode(base32::Alphabet::Rfc4648 { padding: false }, &random_bytes::<20>());
+    let recovery_codes: Vec<String> = (0..RECOVERY_CODES)
+        .map(|_| {
+            let bytes = random_bytes::<5>();
+            format!("{}-{}", hex::encode(&bytes[..2]), hex::encode(&bytes[2..]))
+        })
+        .collect();
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        // Un enrôlement actif n'est jamais écrasé
+        let stored = sqlx::query!(
+            r#"INSERT INTO user_totp (user_id, secret)
+               VALUES ($1, $2)
+               ON CONFLICT (user_id) DO UPDATE SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+               WHERE user_totp.enabled_at IS NULL"#,
+            user.id,
+            secret
+        )
+        .execute(&mut tx)
+        .await?;
+        if stored.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query!("DELETE FROM totp_recovery_codes WHERE user_id = $1", user.id)
+            .execute(&mut tx)
+            .await?;
+        let hashes: Vec<String> = recovery_codes.iter().map(|code| hash_secret(code)).collect();
+        sqlx::query!(
+            "INSERT INTO totp_recovery_codes (user_id, code_hash) SELECT $1, unnest($2::text[])",
+            user.id,
+            &hashes
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(true)
+    }
+    .await;
+
+    match result {
+        Ok(true) => {
+            let issuer = env::var("TOTP_ISSUER").unwrap_or_else(|_| "PropertyInvestment".to_string());
+            let encode = |value: &str| url::form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>();
+            let otpauth_url = format!(
+                "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits=6&period={}",
+                encode(&issuer),
+                encode(&user.wallet.to_string()),
+                secret,
+                encode(&issuer),
+                STEP_SECS
+            );
+            let qr_svg = QrCode::new(otpauth_url.as_bytes())
+                .map(|qr| qr.render::<svg::Color>().min_dimensions(200, 200).build())
+                .ok();
+            (StatusCode::OK, Json(serde_json::json!({
+                "secret": secret,
+                "otpauth_url": otpauth_url,
+                "qr_svg": qr_svg,
+                "recovery_codes": recovery_codes,
+                "message": "Scanner le QR code puis confirmer avec un code via /api/me/2fa/enable"
+            }))).into_response()
+        },
+        Ok(false) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "La double authentification est déjà active"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enrôlement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : confirmer l'enrôlement avec un premier code de l'application
+pub async fn enable_two_factor(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> impl IntoResponse {
+    let pending = match sqlx::query!(
+        "SELECT secret, enabled_at FROM user_totp WHERE user_id = $1",
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(row)) if row.enabled_at.is_none() => row,
+        Ok(Some(_)) => return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "La double authentification est déjà active"
+        }))).into_response(),
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Aucun enrôlement en cours : appeler /api/me/2fa/setup"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    // Seul un code de l'application prouve que le secret a été enregistré
+    let Some(step) = matching_step(&pending.secret, payload.code.trim()) else {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Code invalide"
+        }))).into_response();
+    };
+
+    match sqlx::query!(
+        "UPDATE user_totp SET enabled_at = NOW(), last_used_step = $2 WHERE user_id = $1 AND enabled_at IS NULL",
+        user.id,
+        step
+    )
+    .execute(&pool)
+    .await {
+        Ok(_) => {
+            activity::record_detached(pool, user.id, None, "two_factor_enabled", serde_json::json!({}), None);
+            (StatusCode::OK, Json(serde_json::json!({
+                "message": "Double authentification activée"
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'activation: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : désactiver la double authentification (code requis)
+pub async fn disable_two_factor(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> impl IntoResponse {
+    let secret = match sqlx::query_scalar!(
+        "SELECT secret FROM user_totp WHERE user_id = $1 AND enabled_at IS NOT NULL",
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(secret)) => secret,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "La double authentification n'est pas active"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    let result = async {
+        if !check_code(&pool, user.id, &secret, &payload.code).await? {
+            return Ok(false);
+        }
+        let mut tx = pool.begin().await?;
+        sqlx::query!("DELETE FROM user_totp WHERE user_id = $1", user.id).execute(&mut tx).await?;
+        sqlx::query!("DELETE FROM totp_recovery_codes WHERE user_id = $1", user.id).execute(&mut tx).await?;
+        sqlx::query!("DELETE FROM two_factor_step_ups WHERE user_id = $1", user.id).execute(&mut tx).await?;
+        activity::record(&mut tx, user.id, None, "two_factor_disabled", serde_json::json!({}), None).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(true)
+    }
+    .await;
+
+    match result {
+        Ok(true) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Double authentification désactivée"
+        }))).into_response(),
+        Ok(false) => forbidden("Code de double authentification invalide"),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la désactivation: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : échanger un code contre un jeton utilisable pendant
+/// `TWO_FACTOR_STEP_UP_SECS` secondes (300 par défaut) via `X-Step-Up-Token`
+pub async fn step_up(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> impl IntoResponse {
+    let ttl_secs = env::var("TWO_FACTOR_STEP_UP_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(300);
+
+    let secret = match sqlx::query_scalar!(
+        "SELECT secret FROM user_totp WHERE user_id = $1 AND enabled_at IS NOT NULL",
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(secret)) => secret,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "La double authentification n'est pas active"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+
+    let token = hex::encode(random_bytes::<32>());
+    let expires_at = Utc::now() + Duration::seconds(ttl_secs);
+    let result = async {
+        if !check_code(&pool, user.id, &secret, &payload.code).await? {
+            return Ok(false);
+        }
+        // Les jetons expirés sont purgés au passage
+        sqlx::query!("DELETE FROM two_factor_step_ups WHERE expires_at < NOW()")
+            .execute(&pool)
+            .await?;
+        sqlx::query!(
+            "INSERT INTO two_factor_step_ups (token_hash, user_id, expires_at) VALUES ($1, $2, $3)",
+            hash_secret(&token),
+            user.id,
+            expires_at
+        )
+        .execute(&pool)
+        .await?;
+        Ok::<_, sqlx::Error>(true)
+    }
+    .await;
+
+    match result {
+        Ok(true) => (StatusCode::OK, Json(serde_json::json!({
+            "token": token,
+            "expires_at": expires_at
+        }))).into_response(),
+        Ok(false) => forbidden("Code de double authentification invalide"),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vecteurs SHA1 de la RFC 6238 (annexe B), réduits à 6 chiffres
+    #[test]
+    fn totp_matches_rfc6238_vectors() {
+        let secret = base32::decode(base32::Alphabet::Rfc4648 { padding: false }, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").unwrap();
+        assert_eq!(secret, b"12345678901234567890");
+
+        let vectors = [
+            (59, 94_287_082),
+            (1_111_111_109, 7_081_804),
+            (1_111_111_111, 14_050_471),
+            (1_234_567_890, 89_005_924),
+            (2_000_000_000, 69_279_037),
+            (20_000_000_000, 65_353_130),
+        ];
+        for (time, code) in vectors {
+            assert_eq!(totp_code(&secret, time / STEP_SECS), code % 1_000_000, "t = {}", time);
+        }
+    }
+}
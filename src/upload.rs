@@ -0,0 +1,271 @@
+// upload.rs
+//
+// Réception des fichiers envoyés (documents et images) avant leur dépôt dans le
+// stockage : taille maximale contrôlée pendant la lecture du flux, liste blanche
+// d'extensions/MIME, détection du type réel par signature (magic bytes) et
+// analyse antivirus optionnelle via ClamAV (clamd, protocole INSTREAM).
+
+use axum::{
+    extract::BodyStream,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::StreamExt;
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+use crate::slug::slugify;
+
+/// Règles d'acceptation d'un type d'envoi
+pub struct UploadPolicy {
+    allowed: &'static [(&'static str, &'static str)], // (extension, type MIME)
+    max_bytes_env: &'static str,
+    default_max_bytes: u64,
+}
+
+pub const DOCUMENT_POLICY: UploadPolicy = UploadPolicy {
+    allowed: &[
+        ("pdf", "application/pdf"),
+        ("png", "image/png"),
+        ("jpg", "image/jpeg"),
+        ("jpeg", "image/jpeg"),
+        ("webp", "image/webp"),
+    ],
+    max_bytes_env: "UPLOAD_MAX_DOCUMENT_BYTES",
+    default_max_bytes: 20 * 1024 * 1024,
+};
+
+pub const IMAGE_POLICY: UploadPolicy = UploadPolicy {
+    allowed: &[
+        ("png", "image/png"),
+        ("jpg", "image/jpeg"),
+        ("jpeg", "image/jpeg"),
+        ("webp", "image/webp"),
+        ("gif", "image/gif"),
+    ],
+    max_bytes_env: "UPLOAD_MAX_IMAGE_BYTES",
+    default_max_bytes: 10 * 1024 * 1024,
+};
+
+impl UploadPolicy {
+    fn max_bytes(&self) -> u64 {
+        env::var(self.max_bytes_env)
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(self.default_max_bytes)
+    }
+}
+
+/// Fichier reçu et validé, conservé dans un fichier temporaire supprimé à la fin
+pub struct Upload {
+    pub path: PathBuf,
+    pub file_name: String, // Nom assaini, ex. "acte-de-vente.pdf"
+    pub content_type: &'static str,
+    pub size: u64,
+}
+
+impl Drop for Upload {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Refus d'un envoi, renvoyé au client avec la raison
+pub struct Rejection {
+    status: StatusCode,
+    reason: String,
+}
+
+impl Rejection {
+    fn invalid(reason: impl Into<String>) -> Self {
+        Self { status: StatusCode::UNPROCESSABLE_ENTITY, reason: reason.into() }
+    }
+
+    fn internal(reason: impl Into<String>) -> Self {
+        Self { status: StatusCode::INTERNAL_SERVER_ERROR, reason: reason.into() }
+    }
+}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        let error = if self.status == StatusCode::UNPROCESSABLE_ENTITY {
+            "Fichier refusé"
+        } else {
+            "Erreur lors de la réception du fichier"
+        };
+        (self.status, Json(serde_json::json!({
+            "error": error,
+            "reason": self.reason
+        }))).into_response()
+    }
+}
+
+/// Type MIME réel déduit des premiers octets du fichier
+fn sniff(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if head.len() >= 12 && &head[..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else {
+        None
+    }
+}
+
+/// Lit le corps de la requête en flux vers un fichier temporaire en appliquant
+/// la politique : taille, extension, type déclaré et type réel, puis antivirus.
+pub async fn receive(
+    policy: &UploadPolicy,
+    file_name: &str,
+    headers: &HeaderMap,
+    mut body: BodyStream,
+) -> Result<Upload, Rejection> {
+    // Extension et nom assaini
+    let base_name = file_name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let (stem, extension) = match base_name.rsplit_once('.') {
+        Some((stem, ext)) if !ext.is_empty() => (stem, ext.to_lowercase()),
+        _ => return Err(Rejection::invalid("Le nom de fichier doit comporter une extension")),
+    };
+    let expected_mime = match policy.allowed.iter().find(|(ext, _)| *ext == extension) {
+        Some((_, mime)) => *mime,
+        None => return Err(Rejection::invalid(format!(
+            "Extension .{} non autorisée (autorisées : {})",
+            extension,
+            policy.allowed.iter().map(|(ext, _)| *ext).collect::<Vec<_>>().join(", ")
+        ))),
+    };
+
+    // Le type déclaré, s'il est précis, doit correspondre à l'extension
+    let declared = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or_default().trim().to_lowercase());
+    if let Some(declared) = declared.filter(|d| d != "application/octet-stream") {
+        if declared != expected_mime {
+            return Err(Rejection::invalid(format!(
+                "Type déclaré {} incompatible avec l'extension .{}",
+                declared, extension
+            )));
+        }
+    }
+
+    // Refus immédiat si la taille annoncée dépasse déjà la limite
+    let max_bytes = policy.max_bytes();
+    let announced = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if matches!(announced, Some(len) if len > max_bytes) {
+        return Err(Rejection::invalid(format!("Fichier trop volumineux (maximum {} octets)", max_bytes)));
+    }
+
+    let mut upload = Upload {
+        path: env::temp_dir().join(format!("upload-{}", Uuid::new_v4())),
+        file_name: format!("{}.{}", slugify(stem), extension),
+        content_type: expected_mime,
+        size: 0,
+    };
+    let mut file = tokio::fs::File::create(&upload.path)
+        .await
+        .map_err(|e| Rejection::internal(e.to_string()))?;
+
+    // Lecture en flux : rien n'est conservé en mémoire hormis l'en-tête du fichier
+    let mut head: Vec<u8> = Vec::with_capacity(16);
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| Rejection::invalid(format!("Lecture interrompue: {}", e)))?;
+        upload.size += chunk.len() as u64;
+        if upload.size > max_bytes {
+            return Err(Rejection::invalid(format!("Fichier trop volumineux (maximum {} octets)", max_bytes)));
+        }
+        if head.len() < 16 {
+            let missing = 16 - head.len();
+            head.extend_from_slice(&chunk[..chunk.len().min(missing)]);
+        }
+        file.write_all(&chunk).await.map_err(|e| Rejection::internal(e.to_string()))?;
+    }
+    file.flush().await.map_err(|e| Rejection::internal(e.to_string()))?;
+
+    if upload.size == 0 {
+        return Err(Rejection::invalid("Fichier vide"));
+    }
+
+    // Le contenu réel doit correspondre à l'extension
+    match sniff(&head) {
+        Some(mime) if mime == expected_mime => {},
+        Some(mime) => return Err(Rejection::invalid(format!(
+            "Contenu de type {} incompatible avec l'extension .{}",
+            mime, extension
+        ))),
+        None => return Err(Rejection::invalid("Type de contenu non reconnu")),
+    }
+
+    scan(&upload).await?;
+
+    Ok(upload)
+}
+
+/// Analyse antivirus si `CLAMAV_ADDR` (host:port de clamd) est configuré.
+/// Si l'antivirus est configuré mais injoignable, l'envoi est refusé.
+async fn scan(upload: &Upload) -> Result<(), Rejection> {
+    let addr = match env::var("CLAMAV_ADDR") {
+        Ok(addr) if !addr.trim().is_empty() => addr,
+        _ => return Ok(()),
+    };
+
+    match tokio::time::timeout(Duration::from_secs(60), clamav_instream(&addr, upload)).await {
+        Ok(Ok(None)) => Ok(()),
+        Ok(Ok(Some(signature))) => Err(Rejection::invalid(format!("Fichier infecté ({})", signature))),
+        Ok(Err(e)) => {
+            tracing::error!("Analyse ClamAV impossible: {}", e);
+            Err(Rejection {
+                status: StatusCode::SERVICE_UNAVAILABLE,
+                reason: "Analyse antivirus indisponible".to_string(),
+            })
+        },
+        Err(_) => Err(Rejection {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            reason: "Analyse antivirus trop longue".to_string(),
+        }),
+    }
+}
+
+/// Envoie le fichier à clamd ; renvoie la signature détectée le cas échéant
+async fn clamav_instream(addr: &str, upload: &Upload) -> std::io::Result<Option<String>> {
+    let mut stream = TcpStream::connect(addr).await?;
+    stream.write_all(b"zINSTREAM\0").await?;
+
+    let mut file = tokio::fs::File::open(&upload.path).await?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        stream.write_all(&(read as u32).to_be_bytes()).await?;
+        stream.write_all(&buffer[..read]).await?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    let reply = String::from_utf8_lossy(&reply);
+    let reply = reply.trim_end_matches('\0').trim();
+
+    if reply.ends_with(" OK") {
+        Ok(None)
+    } else if let Some(found) = reply.strip_suffix(" FOUND") {
+        Ok(Some(found.trim_start_matches("stream:").trim().to_string()))
+    } else {
+        Err(std::io::Error::other(reply.to_string()))
+    }
+}
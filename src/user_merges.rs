@@ -0,0 +1,272 @@
+// user_merges.rs
+//
+// Fusion de deux comptes d'une même personne (par exemple avant et après une
+// migration de wallet). L'admin désigne le compte conservé et le doublon :
+// investissements, intentions, paiements, remboursements, téléchargements,
+// requêtes signées, notifications et wallets vérifiés passent au compte
+// conservé dans une même transaction ; le wallet principal du doublon y est
+// rattaché comme wallet vérifié, de sorte qu'il continue d'ouvrir une session
+// sur le compte conservé. Le doublon est désactivé (pas supprimé : son
+// historique d'activité reste consultable) ; sa désactivation libère son
+// wallet, unique parmi les comptes actifs seulement (`users_wallet_key`), que
+// le compte conservé peut ensuite choisir comme wallet principal. La fusion
+// est enregistrée dans `user_merges`. Le rattachement d'un wallet déjà inscrit (voir wallets.rs)
+// réutilise la même fusion.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::activity;
+use crate::auth::BearerAuthUser;
+use crate::cache::UserCache;
+use crate::confirmations;
+use crate::models::{MergeUsersRequest, UserMerge, UserRole};
+
+/// Avoirs transférés d'un compte à l'autre
+pub struct MovedHoldings {
+    pub investments: u64,
+    pub intents: u64,
+    pub fiat_payments: u64,
+    pub refunds: u64,
+    /// Wallets vérifiés transférés (à retirer du cache des utilisateurs)
+    pub wallets: Vec<String>,
+}
+
+/// Transfère les avoirs de `from` vers `to`, dans la transaction de l'appelant
+async fn move_holdings(conn: &mut PgConnection, from: Uuid, to: Uuid) -> Result<MovedHoldings, sqlx::Error> {
+    let investments = sqlx::query!("UPDATE investments SET user_id = $2 WHERE user_id = $1", from, to)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    let intents = sqlx::query!("UPDATE investment_intents SET user_id = $2 WHERE user_id = $1", from, to)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    let fiat_payments = sqlx::query!("UPDATE fiat_payments SET user_id = $2 WHERE user_id = $1", from, to)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    let refunds = sqlx::query!("UPDATE investment_refunds SET user_id = $2 WHERE user_id = $1", from, to)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    sqlx::query!("UPDATE document_downloads SET user_id = $2 WHERE user_id = $1", from, to)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query!("UPDATE signed_requests SET user_id = $2 WHERE user_id = $1", from, to)
+        .execute(&mut *conn)
+        .await?;
+
+    // Les wallets déjà vérifiés par l'ancien compte suivent la fusion. Une
+    // demande de rattachement du même wallet par le compte conservé devient
+    // vérifiée, une fois la ligne de l'ancien compte retirée (un wallet vérifié
+    // n'appartient qu'à un compte).
+    let mut wallets = sqlx::query_scalar!(
+        r#"DELETE FROM user_wallets
+           WHERE user_id = $1 AND verified_at IS NOT NULL
+           AND wallet IN (SELECT wallet FROM user_wallets WHERE user_id = $2)
+           RETURNING wallet"#,
+        from,
+        to
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    sqlx::query!(
+        "UPDATE user_wallets SET verified_at = NOW() WHERE user_id = $1 AND wallet = ANY($2) AND verified_at IS NULL",
+        to,
+        &wallets
+    )
+    .execute(&mut *conn)
+    .await?;
+    wallets.extend(sqlx::query_scalar!(
+        r#"UPDATE user_wallets SET user_id = $2
+           WHERE user_id = $1 AND verified_at IS NOT NULL
+           RETURNING wallet"#,
+        from,
+        to
+    )
+    .fetch_all(&mut *conn)
+    .await?);
+
+    // Demandes de rattachement en cours de l'ancien compte, devenues sans objet
+    sqlx::query!("DELETE FROM user_wallets WHERE user_id = $1", from)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(MovedHoldings { investments, intents, fiat_payments, refunds, wallets })
+}
+
+/// Fusionne le doublon dans le compte conservé, tous deux verrouillés par
+/// l'appelant : avoirs et notifications transférés, wallet principal du doublon
+/// rattaché au compte conservé, doublon désactivé et fusion enregistrée.
+/// Renvoie aussi les avoirs transférés (wallets à retirer du cache).
+#[allow(clippy::too_many_arguments)]
+pub async fn merge_accounts(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    survivor_id: Uuid,
+    duplicate_id: Uuid,
+    duplicate_wallet: &str,
+    merged_by: Uuid,
+    reason: Option<&str>,
+) -> Result<(UserMerge, MovedHoldings), sqlx::Error> {
+    let moved = move_holdings(&mut *conn, duplicate_id, survivor_id).await?;
+    let notifications = sqlx::query!(
+        "UPDATE notifications SET user_id = $2 WHERE user_id = $1",
+        duplicate_id,
+        survivor_id
+    )
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    // Le wallet principal du doublon ouvre désormais une session sur le compte conservé
+    sqlx::query!(
+        r#"INSERT INTO user_wallets (user_id, wallet, challenge, verified_at)
+           VALUES ($1, lower($2), 'fusion de comptes', NOW())
+           ON CONFLICT (user_id, wallet) DO UPDATE SET verified_at = COALESCE(user_wallets.verified_at, NOW())"#,
+        survivor_id,
+        duplicate_wallet
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // Désactivation : le wallet du doublon n'est plus réservé (index partiel)
+    sqlx::query!(
+        "UPDATE users SET deactivated_at = NOW(), merged_into = $2 WHERE id = $1",
+        duplicate_id,
+        survivor_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let moved_json = serde_json::json!({
+        "investments": moved.investments,
+        "investment_intents": moved.intents,
+        "fiat_payments": moved.fiat_payments,
+        "investment_refunds": moved.refunds,
+        "notifications": notifications,
+        "wallets": moved.wallets
+    });
+    let merge = sqlx::query_as!(
+        UserMerge,
+        r#"INSERT INTO user_merges (tenant_id, survivor_id, duplicate_id, duplicate_wallet, merged_by, reason, moved)
+           VALUES ($1, $2, $3, $4, $5, $6, $7)
+           RETURNING *"#,
+        tenant_id,
+        survivor_id,
+        duplicate_id,
+        duplicate_wallet,
+        merged_by,
+        reason,
+        moved_json
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok((merge, moved))
+}
+
+/// Route admin : fusionner un doublon dans le compte conservé (confirmation
+/// requise, voir confirmations.rs)
+pub async fn merge_users(
+    BearerAuthUser(admin): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(user_cache): Extension<UserCache>,
+    headers: HeaderMap,
+    Json(payload): Json<MergeUsersRequest>,
+) -> impl IntoResponse {
+    if !matches!(admin.role, UserRole::Admin) {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Seuls les admins peuvent fusionner des comptes"
+        }))).into_response();
+    }
+    if payload.survivor_id == payload.duplicate_id {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le compte conservé et le doublon doivent être différents"
+        }))).into_response();
+    }
+    if payload.duplicate_id == admin.id {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({
+            "error": "Impossible de désactiver son propre compte par fusion"
+        }))).into_response();
+    }
+
+    let target = format!("{}:{}", payload.duplicate_id, payload.survivor_id);
+    if let Err(response) = confirmations::ensure_confirmed(&pool, &admin, &headers, "merge_users", &target).await {
+        return response;
+    }
+
+    let reason = payload.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        // Verrouille les deux comptes (ordre stable pour éviter les interblocages)
+        let users = sqlx::query!(
+            r#"SELECT id, wallet, deactivated_at FROM users
+               WHERE id = ANY($1) AND tenant_id = $2
+               ORDER BY id
+               FOR UPDATE"#,
+            &[payload.survivor_id, payload.duplicate_id],
+            admin.tenant_id
+        )
+        .fetch_all(&mut tx)
+        .await?;
+        if users.len() != 2 {
+            return Ok(Err((StatusCode::NOT_FOUND, "Utilisateur non trouvé")));
+        }
+        if users.iter().any(|u| u.deactivated_at.is_some()) {
+            return Ok(Err((StatusCode::CONFLICT, "L'un des comptes a déjà été fusionné ou désactivé")));
+        }
+        let Some(duplicate_wallet) = users.into_iter().find(|u| u.id == payload.duplicate_id).map(|u| u.wallet) else {
+            return Ok(Err((StatusCode::NOT_FOUND, "Utilisateur non trouvé")));
+        };
+
+        let (merge, moved) = merge_accounts(
+            &mut tx,
+            admin.tenant_id,
+            payload.survivor_id,
+            payload.duplicate_id,
+            &duplicate_wallet,
+            admin.id,
+            reason
+        ).await?;
+
+        activity::record(
+            &mut tx,
+            payload.survivor_id,
+            Some(admin.id),
+            "account_merged",
+            serde_json::json!({ "duplicate_id": payload.duplicate_id, "merge_id": merge.id }),
+            None
+        ).await?;
+        tx.commit().await?;
+
+        let mut stale_wallets = moved.wallets;
+        stale_wallets.push(duplicate_wallet);
+        Ok::<_, sqlx::Error>(Ok((merge, stale_wallets)))
+    }.await;
+
+    match result {
+        Ok(Ok((merge, stale_wallets))) => {
+            let lowercase: Vec<String> = stale_wallets.iter().map(|wallet| wallet.to_lowercase()).collect();
+            user_cache.invalidate_everywhere(&pool, &[stale_wallets, lowercase].concat()).await;
+            (StatusCode::OK, Json(serde_json::json!({
+                "merge": merge,
+                "message": "Comptes fusionnés"
+            }))).into_response()
+        },
+        Ok(Err((status, error))) => (status, Json(serde_json::json!({
+            "error": error
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la fusion des comptes: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,224 @@
+// user_tokens.rs
+//
+// Jetons d'API personnels : un utilisateur crée des jetons longue durée
+// limités à des portées en lecture (tableurs, bots), transmis comme un wallet
+// dans `Authorization: Bearer pat_...`. L'extracteur BearerAuthUser les
+// reconnaît à leur préfixe et refuse (403) toute route hors de leurs portées.
+
+use axum::{
+    extract::{Path, State},
+    http::{Method, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::{BearerAuthUser, SessionUser};
+use crate::envelope;
+use crate::models::{CreateUserTokenRequest, UserRole, UserToken};
+use crate::wallet_address::WalletAddress;
+
+// Préfixe des jetons générés : les distingue d'un wallet dans l'en-tête Authorization
+pub const TOKEN_PREFIX: &str = "pat_";
+const MAX_TOKENS_PER_USER: i64 = 20;
+
+/// Portées disponibles et préfixes des routes `GET` qu'elles ouvrent
+const SCOPES: &[(&str, &[&str])] = &[
+    ("read:portfolio", &["/api/investments", "/api/refunds"]),
+    ("read:properties", &["/api/properties", "/api/tags"]),
+];
+
+/// Hash SHA-256 d'un jeton : seule cette empreinte est stockée en base
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", TOKEN_PREFIX, hex::encode(bytes))
+}
+
+/// Route couverte par l'une des portées (lecture seule)
+fn scope_allows(scopes: &[String], method: &Method, path: &str) -> bool {
+    if method != Method::GET && method != Method::HEAD {
+        return false;
+    }
+    SCOPES.iter()
+        .filter(|(scope, _)| scopes.iter().any(|s| s == scope))
+        .flat_map(|(_, prefixes)| prefixes.iter())
+        .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)))
+}
+
+/// Authentifie un jeton personnel pour cette requête (appelé par BearerAuthUser)
+pub async fn authenticate(
+    pool: &PgPool,
+    token: &str,
+    method: &Method,
+    path: &str,
+) -> Result<SessionUser, (StatusCode, &'static str)> {
+    let row = sqlx::query!(
+        r#"SELECT t.id as token_id, t.scopes, u.id, u.tenant_id, u.wallet as "wallet: WalletAddress", u.name, u.role as "role: UserRole", u.created_at
+           FROM user_api_tokens t
+           JOIN users u ON u.id = t.user_id
+           WHERE t.token_hash = $1 AND t.revoked_at IS NULL AND u.deactivated_at IS NULL
+             AND (t.expires_at IS NULL OR t.expires_at > NOW())"#,
+        hash_token(token)
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| (StatusCode::UNAUTHORIZED, "Erreur de base de données"))?
+    .ok_or((StatusCode::UNAUTHORIZED, "Jeton d'API invalide, expiré ou révoqué"))?;
+
+    if !scope_allows(&row.scopes, method, path) {
+        return Err((StatusCode::FORBIDDEN, "Portée du jeton d'API insuffisante pour cette route"));
+    }
+
+    let _ = sqlx::query!("UPDATE user_api_tokens SET last_used_at = NOW() WHERE id = $1", row.token_id)
+        .execute(pool)
+        .await;
+
+    Ok(SessionUser {
+        id: row.id,
+        tenant_id: row.tenant_id,
+        wallet: row.wallet,
+        name: row.name,
+        role: row.role,
+        created_at: row.created_at,
+        impersonation_id: None,
+    })
+}
+
+/// Route : jetons de l'utilisateur connecté (révoqués compris)
+pub async fn get_my_tokens(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match sqlx::query_as!(
+        UserToken,
+        r#"SELECT id, name, token_prefix, scopes, expires_at, revoked_at, last_used_at, created_at
+           FROM user_api_tokens
+           WHERE user_id = $1
+           ORDER BY created_at DESC"#,
+        user.id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(tokens) => envelope::list("tokens", &tokens)
+            .with("available_scopes", SCOPES.iter().map(|(scope, _)| *scope).collect::<Vec<_>>())
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : créer un jeton. La valeur en clair n'est renvoyée qu'une seule fois.
+pub async fn create_my_token(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<CreateUserTokenRequest>,
+) -> impl IntoResponse {
+    let name = payload.name.trim();
+    if name.is_empty() || name.len() > 100 {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Le nom du jeton est requis (100 caractères au maximum)"
+        }))).into_response();
+    }
+
+    let mut scopes: Vec<String> = payload.scopes.iter().map(|scope| scope.trim().to_lowercase()).collect();
+    scopes.sort();
+    scopes.dedup();
+    if scopes.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Au moins une portée est requise"
+        }))).into_response();
+    }
+    if let Some(invalid) = scopes.iter().find(|scope| !SCOPES.iter().any(|(known, _)| known == scope)) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Portée inconnue : {}", invalid)
+        }))).into_response();
+    }
+
+    let expires_at = match payload.expires_in_days {
+        Some(days) if !(1..=3650).contains(&days) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "La durée de validité doit être comprise entre 1 et 3650 jours"
+        }))).into_response(),
+        Some(days) => Some(Utc::now() + Duration::days(days)),
+        None => None,
+    };
+
+    let active = match sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM user_api_tokens
+           WHERE user_id = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())"#,
+        user.id
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(count) => count,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+    if active >= MAX_TOKENS_PER_USER {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": format!("Nombre maximal de jetons actifs atteint ({})", MAX_TOKENS_PER_USER)
+        }))).into_response();
+    }
+
+    let token = generate_token();
+    let token_prefix = token.chars().take(TOKEN_PREFIX.len() + 8).collect::<String>();
+
+    match sqlx::query_as!(
+        UserToken,
+        r#"INSERT INTO user_api_tokens (user_id, name, token_prefix, token_hash, scopes, expires_at)
+           VALUES ($1, $2, $3, $4, $5, $6)
+           RETURNING id, name, token_prefix, scopes, expires_at, revoked_at, last_used_at, created_at"#,
+        user.id,
+        name,
+        token_prefix,
+        hash_token(&token),
+        &scopes,
+        expires_at
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(created) => (StatusCode::CREATED, Json(serde_json::json!({
+            "token": created,
+            "value": token,
+            "message": "Jeton créé : conservez-le, il ne sera plus affiché"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la création: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : révoquer un jeton (effet immédiat)
+pub async fn revoke_my_token(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Path(token_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match sqlx::query!(
+        "UPDATE user_api_tokens SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+        token_id,
+        user.id
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Jeton non trouvé ou déjà révoqué"
+        }))).into_response(),
+        Ok(_) => (StatusCode::OK, Json(serde_json::json!({
+            "message": "Jeton révoqué"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la révocation: {}", e)
+        }))).into_response(),
+    }
+}
@@ -0,0 +1,6 @@
+// wallet_address.rs
+//
+// Adresse Ethereum d'un utilisateur, définie dans le crate `pa-backend-types`
+// (types/src/wallet_address.rs) pour être partagée avec les clients.
+
+pub use pa_backend_types::wallet_address::WalletAddress;
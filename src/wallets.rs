@@ -0,0 +1,487 @@
+// wallets.rs
+//
+// Comptes multi-wallets : un utilisateur rattache des wallets secondaires en
+// signant (`personal_sign`) un message de défi avec chacun d'eux. Un wallet
+// vérifié authentifie le même utilisateur que son wallet principal
+// (`users.wallet`, voir auth.rs). Si le wallet vérifié possédait déjà son
+// propre compte investisseur, ses avoirs sont fusionnés dans le profil courant
+// et l'ancien compte est désactivé (voir user_merges.rs) : son wallet, libéré,
+// peut devenir le wallet principal du profil courant.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+    Json,
+};
+use chrono::{Duration, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::activity;
+use crate::auth::BearerAuthUser;
+use crate::brute_force::{self, AuthLockout};
+use crate::cache::UserCache;
+use crate::eip712;
+use crate::models::{LinkWalletRequest, UserRole, UserWallet, VerifyWalletRequest};
+use crate::risk::{self, RiskRules};
+use crate::tags::is_unique_violation;
+use crate::user_merges;
+use crate::wallet_address::WalletAddress;
+
+/// Durée de validité d'un message de défi
+const CHALLENGE_TTL_MINUTES: i64 = 30;
+
+/// Adresse Ethereum normalisée (`0x` + hexadécimal en minuscules)
+fn normalize_wallet(raw: &str) -> Option<String> {
+    WalletAddress::parse(raw).map(|address| address.as_str().to_string())
+}
+
+fn invalid_wallet() -> Response {
+    (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+        "error": "Adresse de wallet invalide"
+    }))).into_response()
+}
+
+/// Wallets secondaires vérifiés d'un utilisateur (invalidation du cache)
+pub async fn linked_wallets(pool: &PgPool, user_id: Uuid) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT wallet FROM user_wallets WHERE user_id = $1 AND verified_at IS NOT NULL",
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Route : wallet principal et wallets secondaires de l'utilisateur connecté
+pub async fn get_my_wallets(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+) -> impl IntoResponse {
+    match sqlx::query_as!(
+        UserWallet,
+        r#"SELECT wallet as "wallet: WalletAddress", verified_at, created_at
+           FROM user_wallets
+           WHERE user_id = $1
+           ORDER BY created_at"#,
+        user.id
+    )
+    .fetch_all(&pool)
+    .await {
+        Ok(wallets) => (StatusCode::OK, Json(serde_json::json!({
+            "primary": user.wallet,
+            "wallets": wallets
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la récupération: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : demander le rattachement d'un wallet ; renvoie le message à signer
+/// avec ce wallet
+pub async fn link_wallet(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Json(payload): Json<LinkWalletRequest>,
+) -> impl IntoResponse {
+    let wallet = payload.wallet.as_str().to_string();
+    if payload.wallet == user.wallet {
+        return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Ce wallet est déjà votre wallet principal"
+        }))).into_response();
+    }
+
+    match sqlx::query_scalar!(
+        "SELECT user_id FROM user_wallets WHERE wallet = $1 AND verified_at IS NOT NULL",
+        wallet
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(None) => {},
+        Ok(Some(owner)) => return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": if owner == user.id { "Ce wallet est déjà rattaché à votre compte" } else { "Ce wallet est déjà rattaché à un autre compte" }
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    }
+
+    let challenge = format!(
+        "Je confirme rattacher ce wallet à mon compte.\n\nWallet : {}\nCompte : {}\nNonce : {}",
+        wallet,
+        user.id,
+        Uuid::new_v4()
+    );
+    match sqlx::query!(
+        r#"INSERT INTO user_wallets (user_id, wallet, challenge)
+           VALUES ($1, $2, $3)
+           ON CONFLICT (user_id, wallet) DO UPDATE SET challenge = EXCLUDED.challenge, created_at = NOW()
+           RETURNING created_at"#,
+        user.id,
+        wallet,
+        challenge
+    )
+    .fetch_one(&pool)
+    .await {
+        Ok(row) => (StatusCode::CREATED, Json(serde_json::json!({
+            "wallet": wallet,
+            "message": challenge,
+            "expires_at": row.created_at + Duration::minutes(CHALLENGE_TTL_MINUTES)
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de l'enregistrement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : vérifier la signature du message de défi et rattacher le wallet.
+/// Un compte investisseur existant pour ce wallet est fusionné dans le profil
+/// puis désactivé (voir user_merges.rs), sauf signalement de conformité en cours.
+#[allow(clippy::too_many_arguments)]
+pub async fn verify_wallet(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(user_cache): Extension<UserCache>,
+    Extension(rules): Extension<RiskRules>,
+    Extension(lockout): Extension<AuthLockout>,
+    Path(wallet): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<VerifyWalletRequest>,
+) -> impl IntoResponse {
+    let Some(wallet) = normalize_wallet(&wallet) else {
+        return invalid_wallet();
+    };
+
+    // Wallet ou adresse IP bloqués après des signatures invalides répétées
+    let ip = risk::client_ip(&headers);
+    match lockout.is_locked(&pool, Some(&wallet), ip.as_deref()).await {
+        Ok(true) => return (StatusCode::TOO_MANY_REQUESTS, Json(serde_json::json!({
+            "error": brute_force::LOCKED_MESSAGE
+        }))).into_response(),
+        Ok(false) => {},
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    }
+
+    let pending = match sqlx::query!(
+        "SELECT challenge, verified_at, created_at FROM user_wallets WHERE user_id = $1 AND wallet = $2",
+        user.id,
+        wallet
+    )
+    .fetch_optional(&pool)
+    .await {
+        Ok(Some(pending)) if pending.verified_at.is_none() => pending,
+        Ok(Some(_)) => return (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Ce wallet est déjà vérifié"
+        }))).into_response(),
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Aucune demande de rattachement pour ce wallet"
+        }))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la vérification: {}", e)
+        }))).into_response(),
+    };
+    if pending.created_at + Duration::minutes(CHALLENGE_TTL_MINUTES) < Utc::now() {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": "Message expiré : relancer le rattachement"
+        }))).into_response();
+    }
+
+    let digest = eip712::personal_message_digest(&pending.challenge);
+    match eip712::recover_signer(&digest, &payload.signature) {
+        Ok(signer) if eip712::format_address(&signer) == wallet => {},
+        Ok(signer) => {
+            risk::auth_failed(pool, rules, wallet.clone(), ip, "bad_signature");
+            return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({
+                "error": format!("Signature émise par {} au lieu de {}", eip712::format_address(&signer), wallet)
+            }))).into_response();
+        },
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": e
+        }))).into_response(),
+    }
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+
+        // Compte actif existant pour ce wallet : fusion si c'est un simple
+        // investisseur de la même plateforme. Les deux comptes sont verrouillés
+        // (ordre stable pour éviter les interblocages).
+        let accounts = sqlx::query!(
+            r#"SELECT id, tenant_id, wallet, role as "role: UserRole",
+                      EXISTS(SELECT 1 FROM compliance_flags f
+                             WHERE f.user_id = users.id AND f.status IN ('open', 'confirmed')) as "flagged!"
+               FROM users
+               WHERE id = $2 OR (lower(wallet) = $1 AND deactivated_at IS NULL)
+               ORDER BY id
+               FOR UPDATE"#,
+            wallet,
+            user.id
+        )
+        .fetch_all(&mut tx)
+        .await?;
+        let existing = accounts.into_iter().find(|account| account.id != user.id);
+
+        let verified = sqlx::query!(
+            r#"UPDATE user_wallets SET verified_at = NOW()
+               WHERE user_id = $1 AND wallet = $2 AND verified_at IS NULL"#,
+            user.id,
+            wallet
+        )
+        .execute(&mut tx)
+        .await?;
+        if verified.rows_affected() == 0 {
+            return Ok(Err((StatusCode::CONFLICT, "Ce wallet est déjà vérifié")));
+        }
+
+        let mut merged_investments = 0;
+        let mut stale_wallets = vec![wallet.clone()];
+        if let Some(other) = existing {
+            if other.tenant_id != user.tenant_id {
+                return Ok(Err((StatusCode::CONFLICT, "Ce wallet possède un compte sur une autre plateforme")));
+            }
+            if !matches!(other.role, UserRole::User) {
+                return Ok(Err((StatusCode::CONFLICT, "Ce wallet possède un compte manager ou admin : fusion impossible")));
+            }
+            // L'historique de conformité ne s'efface pas par rattachement
+            if other.flagged {
+                return Ok(Err((StatusCode::CONFLICT, "Ce wallet possède un compte faisant l'objet d'une vérification de conformité : fusion impossible")));
+            }
+
+            // L'ancien compte est désactivé, pas supprimé : KYC, acceptations,
+            // accréditation, signalements et activité restent consultables
+            let (_, moved) = user_merges::merge_accounts(
+                &mut tx,
+                user.tenant_id,
+                user.id,
+                other.id,
+                &other.wallet,
+                user.id,
+                Some("rattachement de wallet")
+            ).await?;
+            merged_investments = moved.investments;
+            stale_wallets.extend(moved.wallets);
+            stale_wallets.push(other.wallet);
+        }
+
+        activity::record(
+            &mut tx,
+            user.id,
+            None,
+            "wallet_linked",
+            serde_json::json!({ "wallet": wallet, "merged_investments": merged_investments }),
+            ip
+        ).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Ok((merged_investments, stale_wallets)))
+    }
+    .await;
+
+    match result {
+        Ok(Ok((merged_investments, stale_wallets))) => {
+            user_cache.invalidate_everywhere(&pool, &stale_wallets).await;
+            (StatusCode::OK, Json(serde_json::json!({
+                "wallet": wallet,
+                "merged_investments": merged_investments,
+                "message": "Wallet rattaché à votre compte"
+            }))).into_response()
+        },
+        Ok(Err((status, error))) => (status, Json(serde_json::json!({
+            "error": error
+        }))).into_response(),
+        Err(e) if is_unique_violation(&e) => (StatusCode::CONFLICT, Json(serde_json::json!({
+            "error": "Ce wallet est déjà rattaché à un autre compte"
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors du rattachement: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Fait d'un wallet vérifié de l'utilisateur son wallet principal, l'ancien
+/// wallet principal devenant un wallet vérifié ; renvoie l'ancien wallet
+/// principal, ou l'erreur à renvoyer si le wallet n'est pas vérifié ou reste
+/// le wallet principal d'un autre compte actif (le wallet d'un compte fusionné
+/// est libéré par la fusion)
+async fn switch_primary_wallet(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    wallet: &str,
+) -> Result<Result<String, (StatusCode, &'static str)>, sqlx::Error> {
+    let removed = sqlx::query!(
+        "DELETE FROM user_wallets WHERE user_id = $1 AND wallet = $2 AND verified_at IS NOT NULL",
+        user_id,
+        wallet
+    )
+    .execute(&mut *conn)
+    .await?;
+    if removed.rows_affected() == 0 {
+        return Ok(Err((StatusCode::NOT_FOUND, "Wallet vérifié non trouvé")));
+    }
+
+    let previous = sqlx::query_scalar!(
+        "SELECT wallet FROM users WHERE id = $1 FOR UPDATE",
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    let switched = sqlx::query!("UPDATE users SET wallet = $2 WHERE id = $1", user_id, wallet)
+        .execute(&mut *conn)
+        .await;
+    match switched {
+        Ok(_) => {},
+        Err(e) if is_unique_violation(&e) => {
+            return Ok(Err((StatusCode::CONFLICT, "Ce wallet est le wallet principal d'un autre compte actif")));
+        },
+        Err(e) => return Err(e),
+    }
+    sqlx::query!(
+        r#"INSERT INTO user_wallets (user_id, wallet, challenge, verified_at)
+           VALUES ($1, lower($2), 'Ancien wallet principal', NOW())"#,
+        user_id,
+        previous
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    activity::record(
+        &mut *conn,
+        user_id,
+        None,
+        "primary_wallet_changed",
+        serde_json::json!({ "from": previous, "to": wallet }),
+        None
+    ).await?;
+    Ok(Ok(previous))
+}
+
+/// Route : faire d'un wallet secondaire vérifié le wallet principal ;
+/// l'ancien wallet principal devient un wallet secondaire
+pub async fn set_primary_wallet(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(user_cache): Extension<UserCache>,
+    Path(wallet): Path<String>,
+) -> impl IntoResponse {
+    let Some(wallet) = normalize_wallet(&wallet) else {
+        return invalid_wallet();
+    };
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let previous = switch_primary_wallet(&mut tx, user.id, &wallet).await?;
+        if previous.is_ok() {
+            tx.commit().await?;
+        }
+        Ok::<_, sqlx::Error>(previous)
+    }
+    .await;
+
+    match result {
+        Ok(Ok(previous)) => {
+            // Le wallet principal fait partie de l'utilisateur mis en cache
+            let stale = [previous.to_lowercase(), previous, wallet.clone()];
+            user_cache.invalidate_everywhere(&pool, &stale).await;
+            (StatusCode::OK, Json(serde_json::json!({
+                "primary": wallet,
+                "message": "Wallet principal modifié"
+            }))).into_response()
+        },
+        Ok(Err((status, error))) => (status, Json(serde_json::json!({
+            "error": error
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la mise à jour: {}", e)
+        }))).into_response(),
+    }
+}
+
+/// Route : détacher un wallet secondaire (ou annuler une demande en attente)
+pub async fn unlink_wallet(
+    BearerAuthUser(user): BearerAuthUser,
+    State(pool): State<PgPool>,
+    Extension(user_cache): Extension<UserCache>,
+    Path(wallet): Path<String>,
+) -> impl IntoResponse {
+    let Some(wallet) = normalize_wallet(&wallet) else {
+        return invalid_wallet();
+    };
+
+    match sqlx::query!(
+        "DELETE FROM user_wallets WHERE user_id = $1 AND wallet = $2",
+        user.id,
+        wallet
+    )
+    .execute(&pool)
+    .await {
+        Ok(result) if result.rows_affected() == 0 => (StatusCode::NOT_FOUND, Json(serde_json::json!({
+            "error": "Wallet non trouvé"
+        }))).into_response(),
+        Ok(_) => {
+            user_cache.invalidate_everywhere(&pool, std::slice::from_ref(&wallet)).await;
+            activity::record_detached(pool, user.id, None, "wallet_unlinked", serde_json::json!({ "wallet": wallet }), None);
+            (StatusCode::OK, Json(serde_json::json!({
+                "message": "Wallet détaché"
+            }))).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
+            "error": format!("Erreur lors de la suppression: {}", e)
+        }))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tenants::DEFAULT_TENANT_ID;
+    use crate::test_db;
+
+    #[tokio::test]
+    async fn merged_wallet_can_become_primary() {
+        let Some(mut tx) = test_db::begin().await else { return };
+        let (survivor, survivor_wallet) = test_db::insert_user(&mut tx, UserRole::User).await;
+        let (duplicate, duplicate_wallet) = test_db::insert_user(&mut tx, UserRole::User).await;
+
+        user_merges::merge_accounts(&mut tx, DEFAULT_TENANT_ID, survivor, duplicate, &duplicate_wallet, survivor, None)
+            .await
+            .unwrap();
+        let previous = switch_primary_wallet(&mut tx, survivor, &duplicate_wallet).await.unwrap();
+        assert_eq!(previous, Ok(survivor_wallet.clone()));
+
+        let primary = sqlx::query_scalar!("SELECT wallet FROM users WHERE id = $1", survivor)
+            .fetch_one(&mut tx)
+            .await
+            .unwrap();
+        assert_eq!(primary, duplicate_wallet);
+        let secondary = sqlx::query_scalar!(
+            "SELECT wallet FROM user_wallets WHERE user_id = $1 AND verified_at IS NOT NULL",
+            survivor
+        )
+        .fetch_all(&mut tx)
+        .await
+        .unwrap();
+        assert_eq!(secondary, vec![survivor_wallet]);
+    }
+
+    #[tokio::test]
+    async fn primary_switch_to_active_account_wallet_conflicts() {
+        let Some(mut tx) = test_db::begin().await else { return };
+        let (user_id, _) = test_db::insert_user(&mut tx, UserRole::User).await;
+        let (_, other_wallet) = test_db::insert_user(&mut tx, UserRole::User).await;
+        // Lien vérifié antérieur à la fusion des comptes au rattachement
+        sqlx::query!(
+            "INSERT INTO user_wallets (user_id, wallet, challenge, verified_at) VALUES ($1, $2, 'test', NOW())",
+            user_id,
+            other_wallet
+        )
+        .execute(&mut tx)
+        .await
+        .unwrap();
+
+        let result = switch_primary_wallet(&mut tx, user_id, &other_wallet).await.unwrap();
+        assert_eq!(result.unwrap_err().0, StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,126 @@
+// worker.rs
+//
+// Séparation du serveur HTTP et des tâches de fond. Le binaire se lance en
+// `my-api api` (routes HTTP uniquement), `my-api worker` (tâches de fond
+// uniquement : planificateur de publication, file du relayer, file de tâches,
+// outbox des événements, archivage, contrôle d'intégrité, double écriture,
+// résumés par e-mail, cours de l'ETH, filtrage AML) ou sans argument
+// (les deux dans le même processus, comme avant). Les workers se coordonnent
+// par Postgres : plusieurs peuvent tourner, chaque tâche périodique prenant un
+// bail dans `worker_jobs` avant de s'exécuter, pour ne tourner que sur un
+// processus à la fois. Les invalidations du cache des flux passent par
+// `NOTIFY` (voir feeds.rs).
+
+use sqlx::PgPool;
+use std::env;
+use std::future::Future;
+use std::time::Duration;
+
+/// Bail d'une tâche : au-delà, un worker arrêté en cours de tâche est
+/// considéré comme perdu et la tâche peut être reprise par un autre
+const JOB_LEASE_SECS: f64 = 900.0;
+
+/// Rôle du processus, choisi par le premier argument de la ligne de commande
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessRole {
+    /// Routes HTTP et tâches de fond (sans argument)
+    All,
+    Api,
+    Worker,
+}
+
+impl ProcessRole {
+    pub fn from_args() -> Self {
+        match env::args().nth(1).as_deref().map(str::trim) {
+            None | Some("") => ProcessRole::All,
+            Some("api") => ProcessRole::Api,
+            Some("worker") => ProcessRole::Worker,
+            Some(other) => panic!("Rôle de processus inconnu : '{}' (api ou worker, ou aucun argument pour les deux)", other),
+        }
+    }
+
+    pub fn serves_api(self) -> bool {
+        self != ProcessRole::Worker
+    }
+
+    pub fn runs_jobs(self) -> bool {
+        self != ProcessRole::Api
+    }
+}
+
+/// Identifiant du worker dans `worker_jobs` (hôte et pid)
+pub fn worker_id() -> String {
+    let host = env::var("HOSTNAME").unwrap_or_else(|_| "worker".to_string());
+    format!("{}:{}", host, std::process::id())
+}
+
+/// Prend le bail de `job`, sauf s'il est tenu par un autre worker ou si la
+/// tâche a démarré il y a moins d'une demi-période
+async fn claim(pool: &PgPool, job: &str, period: Duration) -> Result<bool, sqlx::Error> {
+    let claimed = sqlx::query_scalar!(
+        r#"INSERT INTO worker_jobs (name, locked_by, locked_until, last_started_at)
+           VALUES ($1, $2, NOW() + make_interval(secs => $3), NOW())
+           ON CONFLICT (name) DO UPDATE SET
+               locked_by = EXCLUDED.locked_by,
+               locked_until = EXCLUDED.locked_until,
+               last_started_at = EXCLUDED.last_started_at
+           WHERE (worker_jobs.locked_until IS NULL OR worker_jobs.locked_until < NOW())
+             AND (worker_jobs.last_started_at IS NULL
+                  OR worker_jobs.last_started_at < NOW() - make_interval(secs => $4))
+           RETURNING name"#,
+        job,
+        worker_id(),
+        JOB_LEASE_SECS,
+        period.as_secs_f64() / 2.0
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(claimed.is_some())
+}
+
+async fn release(pool: &PgPool, job: &str, error: Option<String>) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"UPDATE worker_jobs
+           SET locked_by = NULL, locked_until = NULL, last_finished_at = NOW(), last_error = $3
+           WHERE name = $1 AND locked_by = $2"#,
+        job,
+        worker_id(),
+        error
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Exécute une passe de la tâche périodique `job` si ce worker en obtient le
+/// bail ; `None` si un autre worker s'en charge ou si le bail n'a pas pu être pris
+pub async fn run_exclusive<T, E, F>(pool: &PgPool, job: &str, period: Duration, run: F) -> Option<Result<T, E>>
+where
+    E: std::fmt::Display,
+    F: Future<Output = Result<T, E>>,
+{
+    match claim(pool, job, period).await {
+        Ok(true) => {},
+        Ok(false) => return None,
+        Err(e) => {
+            tracing::error!(job, "Bail de la tâche non obtenu: {}", e);
+            return None;
+        },
+    }
+
+    let result = run.await;
+    if let Err(e) = release(pool, job, result.as_ref().err().map(|e| e.to_string())).await {
+        tracing::error!(job, "Bail de la tâche non libéré: {}", e);
+    }
+    Some(result)
+}
+
+/// Attend l'arrêt du processus worker (Ctrl+C ou SIGINT)
+pub async fn wait_for_shutdown() {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        tracing::error!("Attente du signal d'arrêt impossible: {}", e);
+        std::future::pending::<()>().await;
+    }
+    println!("🛑 Arrêt du worker");
+}
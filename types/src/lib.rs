@@ -0,0 +1,16 @@
+// lib.rs
+//
+// Types des réponses de l'API (utilisateur de session, propriétés,
+// investissements) et leurs briques (adresses de wallet, montants), partagés
+// entre l'API et ses clients Rust : un client désérialise les réponses avec
+// les types mêmes qui les produisent, au lieu de les redéclarer. Compilé sans
+// sqlx par défaut ; la feature `sqlx` ajoute la lecture depuis Postgres
+// (`query_as!`, colonnes typées), utilisée par l'API.
+
+pub mod models;
+pub mod money;
+pub mod wallet_address;
+
+pub use models::{Amenities, Investment, InvestmentStatus, Property, PropertyStatus, SessionUser, UserRole};
+pub use money::{AmountInput, Money, TokenAmount, Wei};
+pub use wallet_address::WalletAddress;
@@ -0,0 +1,202 @@
+// models.rs
+//
+// Ressources renvoyées par l'API. Les énumérations correspondent aux types
+// Postgres du même nom (feature `sqlx`) et sont sérialisées en minuscules.
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::money::{TokenAmount, Wei};
+use crate::wallet_address::WalletAddress;
+
+// Enum pour les rôles utilisateur
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type), sqlx(type_name = "user_role", rename_all = "lowercase"))]
+pub enum UserRole {
+    User,
+    Manager,
+    Admin,
+}
+
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserRole::User => write!(f, "user"),
+            UserRole::Manager => write!(f, "manager"),
+            UserRole::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl From<String> for UserRole {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "manager" => UserRole::Manager,
+            "admin" => UserRole::Admin,
+            _ => UserRole::User,
+        }
+    }
+}
+
+// Enum pour le statut des propriétés
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type), sqlx(type_name = "property_status", rename_all = "lowercase"))]
+pub enum PropertyStatus {
+    Draft,
+    Pending,
+    Validated,
+    Rejected,
+}
+
+impl std::fmt::Display for PropertyStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PropertyStatus::Draft => write!(f, "draft"),
+            PropertyStatus::Pending => write!(f, "pending"),
+            PropertyStatus::Validated => write!(f, "validated"),
+            PropertyStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+impl From<String> for PropertyStatus {
+    fn from(s: String) -> Self {
+        match s.to_lowercase().as_str() {
+            "draft" => PropertyStatus::Draft,
+            "validated" => PropertyStatus::Validated,
+            "rejected" => PropertyStatus::Rejected,
+            _ => PropertyStatus::Pending,
+        }
+    }
+}
+
+// Enum pour le règlement on-chain d'un investissement
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type), sqlx(type_name = "investment_status", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum InvestmentStatus {
+    PendingSettlement, // Payé en euros, parts pas encore transférées on-chain
+    Settled,           // Réglé on-chain (tx_hash renseigné)
+    Refunded,          // Remboursement approuvé, parts remises en vente
+}
+
+/// Structure renvoyée après connexion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+pub struct SessionUser {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub wallet: WalletAddress,
+    pub name: Option<String>,
+    pub role: UserRole,
+    pub created_at: DateTime<Utc>,
+    /// Session d'impersonation par un admin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts", ts(optional))]
+    pub impersonation_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Property {
+    pub id: Uuid,
+    pub onchain_id: String,
+    pub slug: String,
+    pub name: String,
+    pub location: String,
+    pub property_type: String,  // Mappé depuis la colonne "type"
+    pub description: Option<String>,
+    pub total_price: BigDecimal,  // NOT NULL dans la DB
+    pub token_price: BigDecimal,  // NOT NULL dans la DB  
+    pub annual_yield: BigDecimal, // NOT NULL dans la DB
+    pub image_url: Option<String>,
+    pub documents: Option<Vec<String>>,
+    pub created_by: Uuid,         // NOT NULL dans la DB
+    pub created_at: DateTime<Utc>,
+    pub status: PropertyStatus,
+    pub status_updated_at: Option<DateTime<Utc>>,
+    pub status_updated_by: Option<Uuid>,
+    pub publish_at: Option<DateTime<Utc>>,   // Publication programmée (optionnelle)
+    pub published_at: Option<DateTime<Utc>>, // Renseigné quand la propriété devient publique
+    pub amenities: Amenities,                    // Colonne JSONB
+    pub requires_accreditation: bool,            // Offre réservée aux investisseurs accrédités
+    pub contract_address: Option<String>,        // Contrat registre de l'actif (minuscules)
+}
+
+/// Équipements d'une propriété, stockés en JSONB.
+/// Tous les champs sont optionnels : seuls ceux renseignés sont sérialisés.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export, optional_fields))]
+#[serde(default, deny_unknown_fields)]
+pub struct Amenities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_m2: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rooms: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bedrooms: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bathrooms: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elevator: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parking: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balcony: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub furnished: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Investment {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub property_id: Uuid,
+    pub amount_eth: TokenAmount,
+    pub amount_wei: Wei,
+    pub shares: i32,
+    pub tx_hash: Option<String>,
+    pub status: InvestmentStatus,
+    pub settled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "sqlx")]
+mod postgres {
+    use sqlx::error::BoxDynError;
+    use sqlx::postgres::{PgTypeInfo, PgValueRef};
+    use sqlx::types::Json;
+    use sqlx::{Decode, Postgres, Type};
+
+    use super::Amenities;
+
+    // Lecture depuis la colonne JSONB (`amenities as "amenities: Amenities"`) ;
+    // l'écriture passe par `serde_json::json!`
+    impl Type<Postgres> for Amenities {
+        fn type_info() -> PgTypeInfo {
+            <Json<Amenities> as Type<Postgres>>::type_info()
+        }
+
+        fn compatible(ty: &PgTypeInfo) -> bool {
+            <Json<Amenities> as Type<Postgres>>::compatible(ty)
+        }
+    }
+
+    impl<'r> Decode<'r, Postgres> for Amenities {
+        fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            <Json<Amenities> as Decode<Postgres>>::decode(value).map(|amenities| amenities.0)
+        }
+    }
+}
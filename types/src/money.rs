@@ -0,0 +1,443 @@
+// money.rs
+//
+// Montants et politique d'arrondi. `TokenAmount` porte un montant en ETH
+// (18 décimales au plus, la précision du wei) et `Money` un montant en devise
+// (EUR, USD : 2 décimales). Les deux sont sérialisés en chaînes, pour ne pas
+// perdre de précision côté client (`"0.15"` en ETH sans zéros superflus,
+// `"12.30"` en devise), et acceptent en entrée une chaîne ou un nombre JSON ;
+// une entrée plus précise que l'échelle du type est refusée.
+//
+// Politique d'arrondi :
+// - un montant exact (prix de la part × nombre de parts) n'est pas arrondi ;
+// - un montant calculé par division ou conversion (contre-valeur en devise,
+//   distributions estimées) est arrondi au pair le plus proche (arrondi
+//   bancaire), pour ne pas biaiser les sommes ;
+// - un montant facturé en devise est arrondi au centime supérieur, pour que
+//   le paiement couvre toujours les parts achetées.
+//
+// Les montants réglés on-chain sont aussi conservés en wei (`Wei`, entier
+// NUMERIC(78, 0) comme un uint256), à côté de leur valeur en ETH, pour être
+// rapprochés des données de la chaîne sans conversion. Les deux unités sont
+// acceptées en entrée (`AmountInput`) et renvoyées en sortie ; toute
+// conversion passe par `TokenAmount::wei` et `Wei::to_eth`, exactes dans les
+// deux sens. `GET /api/units/convert` expose cette conversion aux clients.
+
+use bigdecimal::{BigDecimal, Signed, ToPrimitive, Zero};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::str::FromStr;
+
+/// Décimales d'un montant en ETH (1 wei = 10^-18 ETH)
+pub const ETH_SCALE: i64 = 18;
+
+/// Décimales d'un montant en devise
+pub const FIAT_SCALE: i64 = 2;
+
+/// Nombre de wei dans un ETH
+const WEI_PER_ETH: u64 = 1_000_000_000_000_000_000;
+
+/// Plus grand montant en wei (uint256 : 78 chiffres au plus)
+const MAX_WEI_DIGITS: usize = 78;
+
+/// Décimales des estimations en ETH (distributions)
+pub const ESTIMATE_SCALE: i64 = 6;
+
+/// Arrondi au pair le plus proche à `scale` décimales : 0,125 → 0,12 et
+/// 0,135 → 0,14 (à 2 décimales)
+pub fn round_half_even(value: &BigDecimal, scale: i64) -> BigDecimal {
+    let truncated = value.with_scale(scale);
+    let twice_remainder = (value - &truncated).abs() * BigDecimal::from(2);
+    let unit = BigDecimal::new(1.into(), scale);
+    let away = || match value.is_negative() {
+        true => &truncated - &unit,
+        false => &truncated + &unit,
+    };
+    match twice_remainder.cmp(&unit) {
+        std::cmp::Ordering::Less => truncated,
+        std::cmp::Ordering::Greater => away(),
+        std::cmp::Ordering::Equal => {
+            let (digits, _) = truncated.as_bigint_and_exponent();
+            if (digits % 2u32).is_zero() { truncated } else { away() }
+        },
+    }
+}
+
+/// Arrondi à `scale` décimales vers l'infini positif
+pub fn round_up(value: &BigDecimal, scale: i64) -> BigDecimal {
+    let truncated = value.with_scale(scale);
+    match &truncated < value {
+        true => truncated + BigDecimal::new(1.into(), scale),
+        false => truncated,
+    }
+}
+
+/// Décimal lu depuis une chaîne ou un nombre JSON. Un nombre décimal passe par
+/// sa représentation la plus courte (`0.1` et non 0,1000000000000000055…).
+fn deserialize_decimal<'de, D: Deserializer<'de>>(deserializer: D, scale: i64) -> Result<BigDecimal, D::Error> {
+    struct DecimalVisitor;
+
+    impl<'de> de::Visitor<'de> for DecimalVisitor {
+        type Value = BigDecimal;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("un montant (chaîne ou nombre)")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<BigDecimal, E> {
+            BigDecimal::from_str(value.trim()).map_err(|_| E::custom(format!("montant invalide : '{}'", value)))
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<BigDecimal, E> {
+            Ok(BigDecimal::from(value))
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<BigDecimal, E> {
+            Ok(BigDecimal::from(value))
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<BigDecimal, E> {
+            self.visit_str(&value.to_string())
+        }
+    }
+
+    let value = deserializer.deserialize_any(DecimalVisitor)?;
+    if value.with_scale(scale) != value {
+        return Err(de::Error::custom(format!("montant trop précis : {} ({} décimales au plus)", value, scale)));
+    }
+    Ok(value)
+}
+
+/// Montant en ETH
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export, type = "string"))]
+pub struct TokenAmount(BigDecimal);
+
+impl TokenAmount {
+    /// Montant calculé, arrondi au pair le plus proche au wei
+    pub fn from_decimal(value: BigDecimal) -> Self {
+        Self(round_half_even(&value, ETH_SCALE))
+    }
+
+    /// Prix de `shares` parts au prix unitaire `price` (exact au wei près)
+    pub fn for_shares(price: &BigDecimal, shares: i64) -> Self {
+        Self::from_decimal(price * BigDecimal::from(shares))
+    }
+
+    pub fn as_decimal(&self) -> &BigDecimal {
+        &self.0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.0 > BigDecimal::zero()
+    }
+
+    /// Montant en wei (exact : le montant a au plus 18 décimales)
+    pub fn wei(&self) -> Wei {
+        Wei((&self.0 * BigDecimal::from(WEI_PER_ETH)).with_scale(0))
+    }
+
+    /// Contre-valeur au cours `rate` (devise par ETH), arrondie au centime le plus proche
+    pub fn to_fiat(&self, rate: &BigDecimal) -> Money {
+        Money::from_decimal(&self.0 * rate)
+    }
+
+    /// Estimation (`ESTIMATE_SCALE` décimales, arrondi bancaire)
+    pub fn estimate(value: BigDecimal) -> Self {
+        Self(round_half_even(&value, ESTIMATE_SCALE))
+    }
+}
+
+/// Montant en wei, entier positif ou nul
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export, type = "string"))]
+pub struct Wei(BigDecimal);
+
+impl Wei {
+    /// Montant depuis sa forme décimale entière (`"150000000000000000"`)
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.is_empty() || raw.len() > MAX_WEI_DIGITS || !raw.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        BigDecimal::from_str(raw).ok().map(|value| Self(value.with_scale(0)))
+    }
+
+    pub fn as_decimal(&self) -> &BigDecimal {
+        &self.0
+    }
+
+    /// Montant en ETH (exact)
+    pub fn to_eth(&self) -> TokenAmount {
+        let (digits, _) = self.0.with_scale(0).into_bigint_and_exponent();
+        TokenAmount(BigDecimal::new(digits, ETH_SCALE))
+    }
+
+    /// Montant pour l'encodage ABI ; `None` s'il dépasse un u128
+    pub fn to_u128(&self) -> Option<u128> {
+        self.0.to_u128()
+    }
+}
+
+/// Montant reçu en ETH (`amount_eth`) ou en wei (`amount_wei`) ; les deux
+/// peuvent être fournis s'ils désignent le même montant
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export, optional_fields = nullable))]
+pub struct AmountInput {
+    pub amount_eth: Option<TokenAmount>,
+    pub amount_wei: Option<Wei>,
+}
+
+impl AmountInput {
+    /// Montant en ETH ; `Ok(None)` si aucune unité n'est fournie
+    pub fn resolve(&self) -> Result<Option<TokenAmount>, String> {
+        match (&self.amount_eth, &self.amount_wei) {
+            (Some(eth), Some(wei)) if eth.wei() != *wei => Err(format!(
+                "amount_eth ({} ETH) et amount_wei ({} wei) ne désignent pas le même montant", eth, wei
+            )),
+            (Some(eth), _) => Ok(Some(eth.clone())),
+            (None, Some(wei)) => Ok(Some(wei.to_eth())),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// Montant en devise
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export, type = "string"))]
+pub struct Money(BigDecimal);
+
+impl Money {
+    /// Montant calculé, arrondi au centime le plus proche (arrondi bancaire)
+    pub fn from_decimal(value: BigDecimal) -> Self {
+        Self(round_half_even(&value, FIAT_SCALE))
+    }
+
+    /// Montant facturé, arrondi au centime supérieur
+    pub fn charge(value: &BigDecimal) -> Self {
+        Self(round_up(value, FIAT_SCALE))
+    }
+
+    pub fn as_decimal(&self) -> &BigDecimal {
+        &self.0
+    }
+
+    /// Montant en centimes (prestataires de paiement)
+    pub fn to_cents(&self) -> Option<i64> {
+        (&self.0 * BigDecimal::from(100)).with_scale(0).to_i64()
+    }
+}
+
+impl<'a> Sum<&'a TokenAmount> for TokenAmount {
+    fn sum<I: Iterator<Item = &'a TokenAmount>>(iter: I) -> Self {
+        Self(iter.map(|amount| &amount.0).sum())
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Sans zéros superflus, ni notation exponentielle pour un entier
+        let trimmed = self.0.normalized();
+        let (_, scale) = trimmed.as_bigint_and_exponent();
+        trimmed.with_scale(scale.max(0)).fmt(f)
+    }
+}
+
+impl fmt::Display for Wei {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.with_scale(0).fmt(f)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.with_scale(FIAT_SCALE).fmt(f)
+    }
+}
+
+impl Serialize for TokenAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for Wei {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TokenAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_decimal(deserializer, ETH_SCALE).map(Self)
+    }
+}
+
+// Chaîne de chiffres (les montants en wei dépassent la précision d'un nombre
+// JSON) ou entier JSON
+impl<'de> Deserialize<'de> for Wei {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = deserialize_decimal(deserializer, 0)?;
+        Self::parse(&value.to_string()).ok_or_else(|| de::Error::custom(format!(
+            "montant en wei invalide : {} (entier positif de {} chiffres au plus)", value, MAX_WEI_DIGITS
+        )))
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_decimal(deserializer, FIAT_SCALE).map(Self)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+mod postgres {
+    use bigdecimal::BigDecimal;
+    use sqlx::error::BoxDynError;
+    use sqlx::postgres::{PgTypeInfo, PgValueRef};
+    use sqlx::{Decode, Postgres, Type};
+
+    use super::{Money, TokenAmount, Wei};
+
+    // Lecture depuis une colonne NUMERIC (`amount_eth as "amount_eth: TokenAmount"`) ;
+    // les paramètres des requêtes passent par `as_decimal`
+    impl Type<Postgres> for TokenAmount {
+        fn type_info() -> PgTypeInfo {
+            <BigDecimal as Type<Postgres>>::type_info()
+        }
+
+        fn compatible(ty: &PgTypeInfo) -> bool {
+            <BigDecimal as Type<Postgres>>::compatible(ty)
+        }
+    }
+
+    impl<'r> Decode<'r, Postgres> for TokenAmount {
+        fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            <BigDecimal as Decode<Postgres>>::decode(value).map(Self)
+        }
+    }
+
+    impl Type<Postgres> for Wei {
+        fn type_info() -> PgTypeInfo {
+            <BigDecimal as Type<Postgres>>::type_info()
+        }
+
+        fn compatible(ty: &PgTypeInfo) -> bool {
+            <BigDecimal as Type<Postgres>>::compatible(ty)
+        }
+    }
+
+    impl<'r> Decode<'r, Postgres> for Wei {
+        fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            <BigDecimal as Decode<Postgres>>::decode(value).map(Self)
+        }
+    }
+
+    impl Type<Postgres> for Money {
+        fn type_info() -> PgTypeInfo {
+            <BigDecimal as Type<Postgres>>::type_info()
+        }
+
+        fn compatible(ty: &PgTypeInfo) -> bool {
+            <BigDecimal as Type<Postgres>>::compatible(ty)
+        }
+    }
+
+    impl<'r> Decode<'r, Postgres> for Money {
+        fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            <BigDecimal as Decode<Postgres>>::decode(value).map(Self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(raw: &str) -> BigDecimal {
+        BigDecimal::from_str(raw).unwrap()
+    }
+
+    #[test]
+    fn half_even_rounds_ties_to_even_digit() {
+        let cases = [
+            ("0.125", 2, "0.12"),
+            ("0.135", 2, "0.14"),
+            ("0.1251", 2, "0.13"),
+            ("0.1249", 2, "0.12"),
+            ("-0.125", 2, "-0.12"),
+            ("-0.135", 2, "-0.14"),
+            ("2.5", 0, "2"),
+            ("3.5", 0, "4"),
+            ("0.0000125", ESTIMATE_SCALE, "0.000012"),
+        ];
+        for (value, scale, expected) in cases {
+            assert_eq!(round_half_even(&dec(value), scale), dec(expected), "{} à {} décimales", value, scale);
+        }
+    }
+
+    #[test]
+    fn half_even_differs_from_truncation() {
+        // `with_scale` tronque : 0,129 → 0,12 là où l'arrondi donne 0,13
+        assert_eq!(dec("0.129").with_scale(2), dec("0.12"));
+        assert_eq!(round_half_even(&dec("0.129"), 2), dec("0.13"));
+        assert_eq!(dec("-0.129").with_scale(2), dec("-0.12"));
+        assert_eq!(round_half_even(&dec("-0.129"), 2), dec("-0.13"));
+    }
+
+    #[test]
+    fn charge_rounds_up_to_the_cent() {
+        assert_eq!(Money::charge(&dec("10.001")).to_string(), "10.01");
+        assert_eq!(Money::charge(&dec("10.01")).to_string(), "10.01");
+        assert_eq!(Money::charge(&dec("10")).to_string(), "10.00");
+        assert_eq!(Money::from_decimal(dec("10.001")).to_string(), "10.00");
+        assert_eq!(Money::from_decimal(dec("10.005")).to_string(), "10.00");
+        assert_eq!(Money::from_decimal(dec("10.015")).to_string(), "10.02");
+    }
+
+    #[test]
+    fn inputs_beyond_scale_are_rejected() {
+        assert!(serde_json::from_str::<Money>("\"12.34\"").is_ok());
+        assert!(serde_json::from_str::<Money>("\"12.345\"").is_err());
+        assert!(serde_json::from_str::<Money>("12.345").is_err());
+        assert!(serde_json::from_str::<TokenAmount>("\"0.000000000000000001\"").is_ok());
+        assert!(serde_json::from_str::<TokenAmount>("\"0.0000000000000000001\"").is_err());
+        assert!(serde_json::from_str::<Wei>("\"1.5\"").is_err());
+        assert!(serde_json::from_str::<Wei>("\"-1\"").is_err());
+
+        assert!(Wei::parse(&"9".repeat(MAX_WEI_DIGITS)).is_some());
+        assert!(Wei::parse(&"9".repeat(MAX_WEI_DIGITS + 1)).is_none());
+    }
+
+    #[test]
+    fn eth_and_wei_convert_exactly() {
+        let amount: TokenAmount = serde_json::from_str("0.15").unwrap();
+        assert_eq!(amount.wei().to_string(), "150000000000000000");
+        assert_eq!(amount.wei().to_eth(), amount);
+
+        let one_wei = Wei::parse("1").unwrap();
+        assert_eq!(one_wei.to_eth().to_string(), "0.000000000000000001");
+        assert_eq!(Wei::parse("2000000000000000000").unwrap().to_eth().to_string(), "2");
+
+        let input = AmountInput { amount_eth: Some(amount.clone()), amount_wei: Wei::parse("150000000000000001") };
+        assert!(input.resolve().is_err());
+        let input = AmountInput { amount_eth: None, amount_wei: Wei::parse("150000000000000000") };
+        assert_eq!(input.resolve().unwrap(), Some(amount));
+    }
+
+    #[test]
+    fn eth_to_eur_rounds_half_even_to_the_cent() {
+        let half = TokenAmount::from_decimal(dec("0.5"));
+        // 0,5 × 2000,01 = 1000,005 → 1000,00 ; 0,5 × 2000,03 = 1000,015 → 1000,02
+        assert_eq!(half.to_fiat(&dec("2000.01")).to_string(), "1000.00");
+        assert_eq!(half.to_fiat(&dec("2000.03")).to_string(), "1000.02");
+        assert_eq!(TokenAmount::from_decimal(dec("0.15")).to_fiat(&dec("3000")).to_string(), "450.00");
+        assert_eq!(half.to_fiat(&dec("2000.03")).to_cents(), Some(100002));
+    }
+}
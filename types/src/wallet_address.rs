@@ -0,0 +1,138 @@
+// wallet_address.rs
+//
+// Adresse Ethereum d'un utilisateur. Validée à l'entrée (`0x` + 40 caractères
+// hexadécimaux), stockée en minuscules (contrainte sur `users.wallet`) et
+// renvoyée dans les réponses avec la casse de contrôle EIP-55 : `0xABC…` et
+// `0xabc…` désignent le même utilisateur.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(export, type = "string"))]
+pub struct WalletAddress(String);
+
+impl WalletAddress {
+    /// Adresse depuis sa forme hexadécimale, quelle que soit sa casse.
+    /// Une casse mixte doit respecter la somme de contrôle EIP-55.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let hex_part = raw.strip_prefix("0x").or_else(|| raw.strip_prefix("0X"))?;
+        let bytes: [u8; 20] = hex::decode(hex_part).ok()?.try_into().ok()?;
+        let address = Self(format!("0x{}", hex::encode(bytes)));
+        let mixed_case = hex_part.chars().any(|c| c.is_ascii_uppercase())
+            && hex_part.chars().any(|c| c.is_ascii_lowercase());
+        if mixed_case && hex_part != &address.checksummed()[2..] {
+            return None;
+        }
+        Some(address)
+    }
+
+    /// Forme stockée (minuscules), à utiliser dans les requêtes
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Forme EIP-55 : chaque lettre est en majuscule si le quartet
+    /// correspondant de keccak256(adresse en minuscules) vaut au moins 8
+    pub fn checksummed(&self) -> String {
+        let hex_part = &self.0[2..];
+        let hash = Keccak256::digest(hex_part.as_bytes());
+        let checksummed: String = hex_part
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = match i % 2 {
+                    0 => hash[i / 2] >> 4,
+                    _ => hash[i / 2] & 0x0f,
+                };
+                if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+            })
+            .collect();
+        format!("0x{}", checksummed)
+    }
+}
+
+impl fmt::Display for WalletAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.checksummed())
+    }
+}
+
+impl Serialize for WalletAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.checksummed())
+    }
+}
+
+impl<'de> Deserialize<'de> for WalletAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Self::parse(&raw).ok_or_else(|| serde::de::Error::custom(format!(
+            "adresse de wallet invalide : '{}' (0x suivi de 40 caractères hexadécimaux, casse EIP-55 si mixte)", raw
+        )))
+    }
+}
+
+#[cfg(feature = "sqlx")]
+mod postgres {
+    use sqlx::error::BoxDynError;
+    use sqlx::postgres::{PgTypeInfo, PgValueRef};
+    use sqlx::{Decode, Postgres, Type};
+
+    use super::WalletAddress;
+
+    // Lecture depuis une colonne texte (`wallet as "wallet: WalletAddress"`) ;
+    // les paramètres des requêtes passent par `as_str`
+    impl Type<Postgres> for WalletAddress {
+        fn type_info() -> PgTypeInfo {
+            <String as Type<Postgres>>::type_info()
+        }
+
+        fn compatible(ty: &PgTypeInfo) -> bool {
+            <String as Type<Postgres>>::compatible(ty)
+        }
+    }
+
+    impl<'r> Decode<'r, Postgres> for WalletAddress {
+        fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            let raw = <&str as Decode<Postgres>>::decode(value)?;
+            Self::parse(raw).ok_or_else(|| format!("Adresse de wallet invalide en base : '{}'", raw).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WalletAddress;
+
+    /// Adresses de référence de l'EIP-55
+    const EIP55: &[&str] = &[
+        "0x52908400098527886E0F7030069857D2E4169EE7",
+        "0x8617E340B3D01FA5F11F306F4090FD50E238070D",
+        "0xde709f2102306220921060314715629080e2fb77",
+        "0x27b1fdb04752bbc536007a920d24acb045561c26",
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn checksums_match_eip55() {
+        for expected in EIP55 {
+            let address = WalletAddress::parse(&expected.to_lowercase()).unwrap();
+            assert_eq!(address.as_str(), expected.to_lowercase());
+            assert_eq!(&address.checksummed(), expected);
+            assert_eq!(WalletAddress::parse(expected), Some(address));
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_mixed_case() {
+        // Casse d'une lettre inversée dans une adresse à casse mixte
+        assert_eq!(WalletAddress::parse("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"), None);
+        assert_eq!(WalletAddress::parse("0xfb6916095ca1df60bB79Ce92cE3Ea74c37c5d359"), None);
+    }
+}
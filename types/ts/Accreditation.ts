@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccreditationStatus } from "./AccreditationStatus";
+
+/**
+ * Accréditation d'un investisseur qualifié
+ */
+export type Accreditation = { user_id: string, status: AccreditationStatus, documents: Array<string>, submitted_at: string, reviewed_by: string | null, reviewed_at: string | null, review_note: string | null, expires_at: string | null, updated_at: string, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AlertSeverity } from "./AlertSeverity";
+import type { AlertStatus } from "./AlertStatus";
+
+export type AlertListQuery = { status?: AlertStatus | null, severity?: AlertSeverity | null, };
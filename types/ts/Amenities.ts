@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Équipements d'une propriété, stockés en JSONB.
+ * Tous les champs sont optionnels : seuls ceux renseignés sont sérialisés.
+ */
+export type Amenities = { surface_m2?: number, rooms?: number, bedrooms?: number, bathrooms?: number, floor?: number, elevator?: boolean, parking?: boolean, balcony?: boolean, pool?: boolean, furnished?: boolean, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenAmount } from "./TokenAmount";
+import type { Wei } from "./Wei";
+
+/**
+ * Montant reçu en ETH (`amount_eth`) ou en wei (`amount_wei`) ; les deux
+ * peuvent être fournis s'ils désignent le même montant
+ */
+export type AmountInput = { amount_eth?: TokenAmount | null, amount_wei?: Wei | null, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AnalyticsGranularity } from "./AnalyticsGranularity";
+import type { AnalyticsMetric } from "./AnalyticsMetric";
+
+export type AnalyticsQuery = { metric: AnalyticsMetric, granularity?: AnalyticsGranularity | null, from?: string | null, to?: string | null, };
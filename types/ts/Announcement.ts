@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Bannière d'annonce ciblée (`audience` : all, investors, managers)
+ */
+export type Announcement = { id: string, tenant_id: string, title: string, body: string, audience: string, starts_at: string, ends_at: string | null, created_by: string | null, created_at: string, updated_at: string, };
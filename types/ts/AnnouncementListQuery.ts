@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Paramètre `?active=true` de `GET /api/admin/announcements`
+ */
+export type AnnouncementListQuery = { active?: boolean | null, };
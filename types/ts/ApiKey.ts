@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Clé d'API partenaire (le hash de la clé n'est jamais exposé)
+ */
+export type ApiKey = { id: string, name: string, key_prefix: string, daily_quota: number, is_active: boolean, created_by: string, created_at: string, last_used_at: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReportStatus } from "./ReportStatus";
+
+/**
+ * Sauvegarde logique demandée par un admin (fichier SQL déposé dans le bucket privé une fois exporté)
+ */
+export type Backup = { id: string, status: ReportStatus, comment: string | null, tables: Array<string>, storage_key: string | null, row_count: bigint | null, size: bigint | null, error: string | null, requested_by: string | null, created_at: string, started_at: string | null, completed_at: string | null, };
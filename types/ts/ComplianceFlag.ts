@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FlagStatus } from "./FlagStatus";
+
+/**
+ * Signalement du filtrage des wallets (sanctions, listes de blocage)
+ */
+export type ComplianceFlag = { id: string, user_id: string, wallet: string, source: string, reason: string, status: FlagStatus, reviewed_by: string | null, reviewed_at: string | null, review_note: string | null, created_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Contenu éditorial (FAQ, avertissement, annonce) rédigé en Markdown
+ */
+export type Content = { id: string, tenant_id: string, slug: string, locale: string, title: string, body: string, published: boolean, created_by: string | null, updated_by: string | null, created_at: string, updated_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Paramètre `?locale=` de `GET /public/v1/content/:slug`
+ */
+export type ContentQuery = { locale?: string | null, };
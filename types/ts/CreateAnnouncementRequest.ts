@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CreateAnnouncementRequest = { title: string, body: string, audience?: string | null, starts_at?: string | null, ends_at?: string | null, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenAmount } from "./TokenAmount";
+import type { Wei } from "./Wei";
+
+export type CreateInvestmentRequest = { property_id: string, shares: number, tx_hash: string, intent_id?: string | null, amount_eth?: TokenAmount | null, amount_wei?: Wei | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LegalDocumentKind } from "./LegalDocumentKind";
+
+export type CreateLegalDocumentRequest = { kind: LegalDocumentKind, version: string, title: string, content: string, };
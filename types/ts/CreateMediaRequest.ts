@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MediaKind } from "./MediaKind";
+
+export type CreateMediaRequest = { kind: MediaKind, url: string, caption?: string | null, is_cover?: boolean | null, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Amenities } from "./Amenities";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type CreatePropertyRequest = { onchain_id: string, contract_address?: string | null, name: string, location: string, property_type: string, description?: string | null, total_price: string, token_price: string, annual_yield: string, image_url?: string | null, documents?: JsonValue | null, amenities?: Amenities | null, requires_accreditation?: boolean | null, tags?: Array<string> | null, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReportFormat } from "./ReportFormat";
+import type { ReportKind } from "./ReportKind";
+
+export type CreateReportRequest = { kind: ReportKind, format: ReportFormat, from?: string | null, to?: string | null, };
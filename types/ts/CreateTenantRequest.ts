@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type CreateTenantRequest = { slug: string, name: string, hostnames?: Array<string> | null, cors_origins?: Array<string> | null, branding?: JsonValue | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WalletAddress } from "./WalletAddress";
+
+export type CreateUserRequest = { wallet: WalletAddress, name: string, role?: string | null, };
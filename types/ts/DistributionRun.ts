@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Versement des revenus d'une propriété jusqu'à `paid_through` (voir distributions.rs)
+ */
+export type DistributionRun = { id: string, tenant_id: string, property_id: string, paid_through: string, recorded_by: string | null, created_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Écart constaté par la double écriture (voir dual_write.rs)
+ */
+export type DualWriteDivergence = { id: string, change_id: bigint, table_name: string, row_id: string, kind: string, expected: JsonValue | null, actual: JsonValue | null, error: string | null, created_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Limite d'exposition : générale (`user_id` absent) ou dérogation individuelle,
+ * globale (`property_id` absent) ou propre à une propriété
+ */
+export type ExposureLimit = { id: string, user_id: string | null, property_id: string | null, max_eth: string | null, max_pct: string | null, updated_by: string | null, updated_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Valeur d'une facette et nombre de propriétés correspondantes
+ */
+export type FacetCount = { value: string, count: bigint, };
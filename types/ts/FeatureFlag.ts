@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Drapeau de fonctionnalité (voir flags.rs)
+ */
+export type FeatureFlag = { key: string, description: string | null, enabled: boolean, environments: Array<string>, roles: Array<string>, updated_by: string | null, updated_at: string, created_at: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FiatPaymentStatus } from "./FiatPaymentStatus";
+import type { Money } from "./Money";
+import type { TokenAmount } from "./TokenAmount";
+import type { Wei } from "./Wei";
+
+/**
+ * Paiement en euros d'un investissement via Stripe
+ */
+export type FiatPayment = { id: string, user_id: string, property_id: string, shares: number, amount_eur: Money, amount_eth: TokenAmount, amount_wei: Wei, eur_per_eth: string, stripe_payment_intent_id: string | null, status: FiatPaymentStatus, investment_id: string | null, created_at: string, updated_at: string, };
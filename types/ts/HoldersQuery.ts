@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Paramètre `?block=` du snapshot des détenteurs (dernier bloc par défaut)
+ */
+export type HoldersQuery = { block?: bigint | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenAmount } from "./TokenAmount";
+
+/**
+ * Revenus courus et non versés d'une position de l'investisseur
+ */
+export type HoldingAccrual = { property_id: string, slug: string, name: string, shares: bigint, amount_eth: TokenAmount, annual_yield: string, last_distribution_at: string | null, accrual_start: string, daily_accrual_eth: TokenAmount, accrued_eth: TokenAmount, net_accrued_eth: TokenAmount, };
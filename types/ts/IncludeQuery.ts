@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Paramètre `?include=` pour les routes de détail
+ */
+export type IncludeQuery = { include?: string | null, };
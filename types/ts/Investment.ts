@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InvestmentStatus } from "./InvestmentStatus";
+import type { TokenAmount } from "./TokenAmount";
+import type { Wei } from "./Wei";
+
+export type Investment = { id: string, user_id: string, property_id: string, amount_eth: TokenAmount, amount_wei: Wei, shares: number, tx_hash: string | null, status: InvestmentStatus, settled_at: string | null, created_at: string, };
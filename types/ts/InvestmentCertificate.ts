@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Certificat d'un investissement confirmé (voir certificates.rs)
+ */
+export type InvestmentCertificate = { id: string, investment_id: string, hash: string, wallet: string, onchain_id: string, shares: number, tx_hash: string, issued_at: string, revoked_at: string | null, };
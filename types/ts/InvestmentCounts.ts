@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Investissements d'une propriété par statut
+ */
+export type InvestmentCounts = { total: bigint, pending_settlement: bigint, settled: bigint, refunded: bigint, };
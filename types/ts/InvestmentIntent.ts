@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenAmount } from "./TokenAmount";
+import type { Wei } from "./Wei";
+
+/**
+ * Intention d'investissement EIP-712 (cotation pré-autorisée par l'investisseur)
+ */
+export type InvestmentIntent = { id: string, user_id: string, property_id: string, investor: string, shares: number, price_per_share_wei: Wei, amount_eth: TokenAmount, amount_wei: Wei, nonce: bigint, deadline: string, digest: string, signature: string | null, signed_at: string | null, investment_id: string | null, created_at: string, };
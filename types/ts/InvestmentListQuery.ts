@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Paramètres de requête pour `GET /api/investments`
+ */
+export type InvestmentListQuery = { include?: string | null, fields?: string | null, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RefundStatus } from "./RefundStatus";
+import type { TokenAmount } from "./TokenAmount";
+import type { Wei } from "./Wei";
+
+/**
+ * Demande de remboursement d'un investissement
+ */
+export type InvestmentRefund = { id: string, investment_id: string, user_id: string, reason: string | null, status: RefundStatus, amount_eth: TokenAmount, amount_wei: Wei, reviewed_by: string | null, reviewed_at: string | null, review_comment: string | null, tx_hash: string | null, paid_at: string | null, created_at: string, updated_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Modification enregistrée d'un investissement
+ */
+export type InvestmentRevision = { id: string, investment_id: string, changed_by: string | null, reason: string | null, changes: JsonValue, created_at: string, };
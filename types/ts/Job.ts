@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JobStatus } from "./JobStatus";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Tâche de la file durable (voir jobs.rs)
+ */
+export type Job = { id: string, kind: string, payload: JsonValue, status: JobStatus, attempts: number, max_attempts: number, run_at: string, locked_by: string | null, locked_at: string | null, last_error: string | null, created_at: string, updated_at: string, completed_at: string | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { KycStatus } from "./KycStatus";
+
+export type KycOverrideRequest = { status: KycStatus, reason: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { KycStatus } from "./KycStatus";
+
+/**
+ * Vérification d'identité (KYC) d'un utilisateur
+ */
+export type KycVerification = { user_id: string, provider: string, applicant_id: string | null, status: KycStatus, review_answer: string | null, reject_labels: Array<string>, moderation_comment: string | null, reviewed_at: string | null, overridden_by: string | null, override_reason: string | null, created_at: string, updated_at: string, };
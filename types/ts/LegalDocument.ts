@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LegalDocumentKind } from "./LegalDocumentKind";
+
+/**
+ * Version d'un document légal
+ */
+export type LegalDocument = { id: string, kind: LegalDocumentKind, version: string, title: string, content: string, created_by: string | null, published_at: string, };
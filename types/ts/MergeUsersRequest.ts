@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Fusion d'un doublon dans le compte conservé (voir user_merges.rs)
+ */
+export type MergeUsersRequest = { survivor_id: string, duplicate_id: string, reason?: string | null, };
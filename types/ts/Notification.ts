@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Notification in-app d'un utilisateur
+ */
+export type Notification = { id: string, user_id: string, kind: string, message: string, data: JsonValue, read_at: string | null, created_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OnchainInvestmentStatus } from "./OnchainInvestmentStatus";
+
+/**
+ * Événement `Invested` observé on-chain (voir onchain_investments.rs)
+ */
+export type OnchainInvestment = { id: string, tx_hash: string, log_index: number, block_number: bigint, wallet: string, onchain_property_id: string, shares: number, source: string, status: OnchainInvestmentStatus, reason: string | null, investment_id: string | null, resolved_by: string | null, resolved_at: string | null, comment: string | null, created_at: string, };
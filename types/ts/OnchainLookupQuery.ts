@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Paramètres de requête pour `GET /api/properties/by-onchain/:id`
+ */
+export type OnchainLookupQuery = { contract?: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RelayStatus } from "./RelayStatus";
+
+/**
+ * Transaction de la file du relayer
+ */
+export type PendingTx = { id: string, kind: string, property_id: string | null, to_address: string, data: string, value_wei: string, status: RelayStatus, from_address: string | null, nonce: bigint | null, gas_limit: bigint | null, max_fee_per_gas: string | null, max_priority_fee_per_gas: string | null, tx_hash: string | null, tx_hashes: Array<string>, attempts: number, last_error: string | null, next_attempt_at: string, submitted_at: string | null, confirmed_at: string | null, block_number: bigint | null, created_by: string | null, created_at: string, updated_at: string, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Amenities } from "./Amenities";
+import type { PropertyStatus } from "./PropertyStatus";
+
+export type Property = { id: string, onchain_id: string, slug: string, name: string, location: string, property_type: string, description: string | null, total_price: string, token_price: string, annual_yield: string, image_url: string | null, documents: Array<string> | null, created_by: string, created_at: string, status: PropertyStatus, status_updated_at: string | null, status_updated_by: string | null, publish_at: string | null, published_at: string | null, amenities: Amenities, requires_accreditation: boolean, contract_address: string | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PropertyDependents } from "./PropertyDependents";
+import type { PropertyStatus } from "./PropertyStatus";
+
+/**
+ * Aperçu de la suppression d'une propriété
+ */
+export type PropertyDeletionImpact = { property_id: string, status: PropertyStatus, dependents: PropertyDependents, deletable: boolean, cascade_deletable: boolean, blockers: Array<string>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InvestmentCounts } from "./InvestmentCounts";
+
+/**
+ * Lignes rattachées à une propriété et touchées par sa suppression (voir deletion_impact.rs)
+ */
+export type PropertyDependents = { investments: InvestmentCounts, distributions: bigint, documents: bigint, document_pins: bigint, media: bigint, intents: bigint, };
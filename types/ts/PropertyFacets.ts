@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FacetCount } from "./FacetCount";
+
+/**
+ * Facettes des propriétés visibles (voir facets.rs) : chacune est comptée avec
+ * tous les filtres demandés sauf le sien ; `total` applique tous les filtres
+ */
+export type PropertyFacets = { country: Array<FacetCount>, region: Array<FacetCount>, city: Array<FacetCount>, property_type: Array<FacetCount>, yield_bucket: Array<FacetCount>, total: bigint, };
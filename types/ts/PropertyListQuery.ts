@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Paramètres de requête pour `GET /api/properties`
+ */
+export type PropertyListQuery = { ids?: string | null, include?: string | null, fields?: string | null, tags?: string | null, country?: string | null, region?: string | null, city?: string | null, property_type?: string | null, yield_bucket?: string | null, };
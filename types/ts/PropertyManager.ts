@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PropertyManagerRole } from "./PropertyManagerRole";
+
+/**
+ * Co-gestionnaire d'une propriété (voir property_managers.rs)
+ */
+export type PropertyManager = { property_id: string, user_id: string, wallet: string, name: string | null, role: PropertyManagerRole, added_by: string | null, created_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MediaKind } from "./MediaKind";
+
+/**
+ * Élément de la galerie d'une propriété
+ */
+export type PropertyMedia = { id: string, property_id: string, kind: MediaKind, url: string, caption: string | null, position: number, is_cover: boolean, created_at: string, };
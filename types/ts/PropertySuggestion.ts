@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Suggestion d'autocomplétion : de quoi afficher et ouvrir la propriété
+ */
+export type PropertySuggestion = { id: string, slug: string, name: string, location: string, };
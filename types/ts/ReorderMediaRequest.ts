@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Nouvel ordre de la galerie : tous les identifiants de médias de la propriété
+ */
+export type ReorderMediaRequest = { media_ids: Array<string>, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReportFormat } from "./ReportFormat";
+import type { ReportKind } from "./ReportKind";
+import type { ReportStatus } from "./ReportStatus";
+
+/**
+ * Rapport demandé par un admin (fichier déposé dans le bucket privé une fois généré)
+ */
+export type Report = { id: string, kind: ReportKind, format: ReportFormat, from_date: string | null, to_date: string | null, status: ReportStatus, storage_key: string | null, row_count: number | null, size: bigint | null, error: string | null, requested_by: string | null, created_at: string, started_at: string | null, completed_at: string | null, };
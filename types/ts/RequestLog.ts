@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Requête journalisée sur une route financièrement sensible
+ */
+export type RequestLog = { id: string, route_group: string, method: string, path: string, route: string | null, actor_id: string | null, status: number, request_body: JsonValue | null, ip: string | null, scheme: string | null, duration_ms: number, created_at: string, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ResolveOnchainInvestmentRequest = { user_id?: string | null, property_id?: string | null, comment?: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RetentionEntity } from "./RetentionEntity";
+
+/**
+ * Durée de conservation d'une table avant archivage
+ */
+export type RetentionPolicy = { entity: RetentionEntity, retention_days: number, enabled: boolean, updated_by: string | null, updated_at: string, last_run_at: string | null, last_archived: number | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccreditationStatus } from "./AccreditationStatus";
+
+export type ReviewAccreditationRequest = { status: AccreditationStatus, expires_at?: string | null, note?: string | null, };
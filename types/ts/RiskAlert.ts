@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AlertSeverity } from "./AlertSeverity";
+import type { AlertStatus } from "./AlertStatus";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Alerte d'activité suspecte levée par le moteur de règles
+ */
+export type RiskAlert = { id: string, rule: string, severity: AlertSeverity, subject: string, user_id: string | null, details: JsonValue, occurrences: number, status: AlertStatus, acknowledged_by: string | null, acknowledged_at: string | null, created_at: string, last_seen_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Note d'un admin sur une alerte
+ */
+export type RiskAlertNote = { id: string, alert_id: string, author_id: string | null, body: string, created_at: string, };
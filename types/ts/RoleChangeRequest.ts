@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UserRole } from "./UserRole";
+
+/**
+ * Demande de changement de rôle en attente d'un second admin
+ */
+export type RoleChangeRequest = { id: string, user_id: string, previous_role: UserRole, requested_role: UserRole, requested_by?: string | null, status: string, decided_by?: string | null, decided_at?: string | null, expires_at: string, created_at: string, };
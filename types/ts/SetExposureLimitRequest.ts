@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SetExposureLimitRequest = { property_id?: string | null, max_eth?: string | null, max_pct?: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Écart constaté par une lecture fantôme (voir shadow_reads.rs)
+ */
+export type ShadowReadDiff = { id: string, endpoint: string, request_key: string, differences: JsonValue, error: string | null, legacy_ms: number, shadow_ms: number, created_at: string, };
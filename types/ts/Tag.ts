@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Tag du vocabulaire géré par l'admin (ex: "seafront", "renovated")
+ */
+export type Tag = { id: string, slug: string, label: string, created_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Taux de retenue à la source d'un pays pour un type de revenu
+ * (`applies_to` : distributions, capital_gains)
+ */
+export type TaxRule = { id: string, tenant_id: string, country: string, rate: string, applies_to: string, created_by: string | null, created_at: string, updated_at: string, };
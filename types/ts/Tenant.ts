@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Plateforme (marque) servie par l'instance (voir tenants.rs)
+ */
+export type Tenant = { id: string, slug: string, name: string, hostnames: Array<string>, cors_origins: Array<string>, branding: JsonValue, created_at: string, };
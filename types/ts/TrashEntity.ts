@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Entités dont la suppression passe par la corbeille (voir trash.rs)
+ */
+export type TrashEntity = "properties" | "investments";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TrashEntity } from "./TrashEntity";
+
+/**
+ * Paramètre `?entity=` de `GET /api/admin/trash`
+ */
+export type TrashListQuery = { entity: TrashEntity, };
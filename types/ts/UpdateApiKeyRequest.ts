@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type UpdateApiKeyRequest = { name?: string | null, daily_quota?: number | null, is_active?: boolean | null, };
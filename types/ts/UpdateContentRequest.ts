@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type UpdateContentRequest = { slug?: string | null, locale?: string | null, title?: string | null, body?: string | null, published?: boolean | null, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenAmount } from "./TokenAmount";
+import type { Wei } from "./Wei";
+
+export type UpdateInvestmentRequest = { shares?: number | null, tx_hash?: string | null, reason?: string | null, amount_eth?: TokenAmount | null, amount_wei?: Wei | null, };
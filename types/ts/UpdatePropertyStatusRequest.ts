@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PropertyStatus } from "./PropertyStatus";
+
+export type UpdatePropertyStatusRequest = { status: PropertyStatus, comment?: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Paramètres des routes d'envoi de fichier (le corps de la requête est le fichier brut)
+ */
+export type UploadQuery = { filename: string, caption?: string | null, is_cover?: boolean | null, };
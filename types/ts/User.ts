@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UserRole } from "./UserRole";
+import type { WalletAddress } from "./WalletAddress";
+
+export type User = { id: string, tenant_id: string, wallet: WalletAddress, name: string | null, role: UserRole, created_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Fusion de deux comptes enregistrée (voir user_merges.rs)
+ */
+export type UserMerge = { id: string, tenant_id: string, survivor_id: string, duplicate_id: string | null, duplicate_wallet: string, merged_by: string | null, reason: string | null, moved: JsonValue, created_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Jeton d'API personnel (la valeur en clair n'est jamais stockée)
+ */
+export type UserToken = { id: string, name: string, token_prefix: string, scopes: Array<string>, expires_at: string | null, revoked_at: string | null, last_used_at: string | null, created_at: string, };
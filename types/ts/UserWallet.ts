@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WalletAddress } from "./WalletAddress";
+
+/**
+ * Wallet secondaire rattaché à un utilisateur (voir wallets.rs)
+ */
+export type UserWallet = { wallet: WalletAddress, verified_at: string | null, created_at: string, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenAmount } from "./TokenAmount";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Projection d'un investissement dans une propriété (voir calculator.rs)
+ */
+export type YieldCalculation = { property_id: string, amount_eth: TokenAmount, token_price: string, shares: bigint, invested_eth: TokenAmount, remainder_eth: TokenAmount, available_shares: bigint, exceeds_available: boolean, annual_yield: string, annual_income_eth: TokenAmount, monthly_income_eth: TokenAmount, withholding_rate: string | null, net_annual_income_eth: TokenAmount, payback_years: string | null, payback_months: bigint | null, fiat: JsonValue, };